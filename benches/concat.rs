@@ -0,0 +1,66 @@
+extern crate criterion;
+extern crate las;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use las::{synthetic, Bounds, Builder, Reader, Vector, Writer};
+use std::io::Cursor;
+
+const NUMBER_OF_POINTS: usize = 100_000;
+const NUMBER_OF_TILES: usize = 4;
+
+/// A square kilometre, with a hundred metres of relief.
+fn bounds() -> Bounds {
+    Bounds {
+        min: Vector {
+            x: 0.,
+            y: 0.,
+            z: 0.,
+        },
+        max: Vector {
+            x: 1000.,
+            y: 1000.,
+            z: 100.,
+        },
+    }
+}
+
+fn fixture() -> Vec<u8> {
+    let mut builder = Builder::from((1, 2));
+    builder.point_format.is_compressed = true;
+    let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    for point in synthetic::terrain(bounds(), 1., 42).take(NUMBER_OF_POINTS) {
+        writer.write_point(point).unwrap();
+    }
+    writer.into_inner().unwrap().into_inner()
+}
+
+fn readers(bytes: &[u8]) -> Vec<Reader<Cursor<Vec<u8>>>> {
+    (0..NUMBER_OF_TILES)
+        .map(|_| Reader::new(Cursor::new(bytes.to_vec())).unwrap())
+        .collect()
+}
+
+fn recompress(inputs: Vec<Reader<Cursor<Vec<u8>>>>) {
+    let mut header = inputs[0].header().clone();
+    header.clear();
+    let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    for mut input in inputs {
+        for point in input.points() {
+            writer.write_point(point.unwrap()).unwrap();
+        }
+    }
+    writer.close().unwrap();
+}
+
+fn bench(criterion: &mut Criterion) {
+    let bytes = fixture();
+    criterion.bench_function("concat_laz", |b| {
+        b.iter(|| las::concat_laz(readers(&bytes), Cursor::new(Vec::new())).unwrap())
+    });
+    criterion.bench_function("concat by recompressing", |b| {
+        b.iter(|| recompress(readers(&bytes)))
+    });
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
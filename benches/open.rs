@@ -0,0 +1,39 @@
+extern crate criterion;
+extern crate las;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use las::Reader;
+
+const NUMBER_OF_FILES: usize = 1000;
+const PATH: &str = "tests/data/autzen.laz";
+
+fn bench(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("open 1000 files");
+    let _ = group.sample_size(10);
+    let _ = group.bench_function("read_header", |b| {
+        b.iter(|| {
+            for _ in 0..NUMBER_OF_FILES {
+                let _ = las::read_header(PATH).unwrap();
+            }
+        })
+    });
+    let _ = group.bench_function("Reader::from_path", |b| {
+        b.iter(|| {
+            for _ in 0..NUMBER_OF_FILES {
+                let _ = Reader::from_path(PATH).unwrap();
+            }
+        })
+    });
+    // Reading a point builds the decompressor, which is what opening a reader used to cost.
+    let _ = group.bench_function("Reader::from_path and read_point", |b| {
+        b.iter(|| {
+            for _ in 0..NUMBER_OF_FILES {
+                let _ = Reader::from_path(PATH).unwrap().read_point().unwrap();
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
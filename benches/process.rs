@@ -0,0 +1,84 @@
+extern crate criterion;
+extern crate las;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use las::{synthetic, Bounds, Builder, Point, Reader, Vector, Writer};
+use std::io::Cursor;
+
+const NUMBER_OF_POINTS: usize = 200_000;
+const CHUNK_SIZE: usize = 10_000;
+
+/// A square kilometre, with a hundred metres of relief.
+fn bounds() -> Bounds {
+    Bounds {
+        min: Vector {
+            x: 0.,
+            y: 0.,
+            z: 0.,
+        },
+        max: Vector {
+            x: 1000.,
+            y: 1000.,
+            z: 100.,
+        },
+    }
+}
+
+fn file() -> Vec<u8> {
+    let header = Builder::from((1, 4)).into_header().unwrap();
+    let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    for point in synthetic::terrain(bounds(), 1., 42).take(NUMBER_OF_POINTS) {
+        writer.write_point(point).unwrap();
+    }
+    writer.into_inner().unwrap().into_inner()
+}
+
+/// A classification histogram, with some extra arithmetic so mapping takes about as long as
+/// reading.
+fn histogram(points: &[Point]) -> [f64; 256] {
+    let mut histogram = [0.; 256];
+    for point in points {
+        let weight = (0..20).fold(point.z, |z, _| (z * z + 1.).sqrt());
+        histogram[usize::from(u8::from(point.classification))] += weight;
+    }
+    histogram
+}
+
+fn add(mut a: [f64; 256], b: [f64; 256]) -> [f64; 256] {
+    for (a, b) in a.iter_mut().zip(b) {
+        *a += b;
+    }
+    a
+}
+
+fn bench(criterion: &mut Criterion) {
+    let mut reader = Reader::new(Cursor::new(file())).unwrap();
+    criterion.bench_function("read then map", |b| {
+        b.iter(|| {
+            reader.seek(0).unwrap();
+            let mut result = [0.; 256];
+            let mut points = Vec::new();
+            loop {
+                points.clear();
+                if reader
+                    .read_points_into(CHUNK_SIZE as u64, &mut points)
+                    .unwrap()
+                    == 0
+                {
+                    break;
+                }
+                result = add(result, histogram(&points));
+            }
+            result
+        })
+    });
+    criterion.bench_function("process_par", |b| {
+        b.iter(|| {
+            reader.seek(0).unwrap();
+            reader.process_par(CHUNK_SIZE, histogram, add).unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
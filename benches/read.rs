@@ -0,0 +1,84 @@
+extern crate criterion;
+extern crate las;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use las::{
+    point::Format, reader::ReadSeekSend, synthetic, Bounds, Builder, Point, Reader, Vector, Writer,
+};
+use std::io::Cursor;
+
+const NUMBER_OF_POINTS: u64 = 100_000;
+
+/// A square kilometre, with a hundred metres of relief.
+fn bounds() -> Bounds {
+    Bounds {
+        min: Vector {
+            x: 0.,
+            y: 0.,
+            z: 0.,
+        },
+        max: Vector {
+            x: 1000.,
+            y: 1000.,
+            z: 100.,
+        },
+    }
+}
+
+fn fixture() -> Vec<u8> {
+    let mut writer = Writer::default();
+    for point in synthetic::uniform(bounds(), NUMBER_OF_POINTS, 42) {
+        writer.write_point(point).unwrap();
+    }
+    writer.into_inner().unwrap().into_inner()
+}
+
+/// Points with more extra bytes than are stored inline.
+fn extra_bytes_fixture() -> Vec<u8> {
+    let mut builder = Builder::from((1, 4));
+    builder.point_format = Format {
+        extra_bytes: 32,
+        ..Format::new(1).unwrap()
+    };
+    let format = builder.point_format;
+    let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    for point in synthetic::uniform(bounds(), NUMBER_OF_POINTS, 42).format(format) {
+        writer.write_point(point).unwrap();
+    }
+    writer.into_inner().unwrap().into_inner()
+}
+
+fn read_all_into<R: std::io::Read + std::io::Seek + Send>(reader: &mut Reader<R>) {
+    reader.seek(0).unwrap();
+    let mut point = Point::default();
+    while reader.read_point_into(&mut point).unwrap() {}
+}
+
+fn read_all<R: std::io::Read + std::io::Seek + Send>(reader: &mut Reader<R>) {
+    reader.seek(0).unwrap();
+    for point in reader.points() {
+        let _ = point.unwrap();
+    }
+}
+
+fn bench(criterion: &mut Criterion) {
+    let bytes = fixture();
+
+    let mut generic = Reader::new(Cursor::new(bytes.clone())).unwrap();
+    criterion.bench_function("read generic", |b| b.iter(|| read_all(&mut generic)));
+
+    let read: Box<dyn ReadSeekSend> = Box::new(Cursor::new(bytes));
+    let mut boxed = Reader::new(read).unwrap();
+    criterion.bench_function("read boxed", |b| b.iter(|| read_all(&mut boxed)));
+
+    let mut extra_bytes = Reader::new(Cursor::new(extra_bytes_fixture())).unwrap();
+    criterion.bench_function("read extra bytes", |b| {
+        b.iter(|| read_all(&mut extra_bytes))
+    });
+    criterion.bench_function("read extra bytes into", |b| {
+        b.iter(|| read_all_into(&mut extra_bytes))
+    });
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -1,8 +1,9 @@
 extern crate criterion;
 extern crate las;
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
 use las::{Point, Reader, Writer};
+use std::hint::black_box;
 
 fn roundtrip(npoints: usize) {
     let mut writer = Writer::default();
@@ -0,0 +1,83 @@
+extern crate criterion;
+extern crate las;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use las::{point::Format, synthetic, Bounds, Builder, Point, Vector, Writer};
+use rayon::prelude::*;
+use std::io::Cursor;
+
+const NUMBER_OF_POINTS: u64 = 100_000;
+
+/// A square kilometre, with a hundred metres of relief.
+fn bounds() -> Bounds {
+    Bounds {
+        min: Vector {
+            x: 0.,
+            y: 0.,
+            z: 0.,
+        },
+        max: Vector {
+            x: 1000.,
+            y: 1000.,
+            z: 100.,
+        },
+    }
+}
+
+fn points() -> Vec<Point> {
+    synthetic::uniform(bounds(), NUMBER_OF_POINTS, 42).collect()
+}
+
+fn bench(criterion: &mut Criterion) {
+    let points = points();
+    let mut builder = Builder::from((1, 4));
+    builder.point_format = Format {
+        extra_bytes: 8,
+        ..Default::default()
+    };
+    let header = builder.into_header().unwrap();
+    let points_with_extra_bytes: Vec<Point> = points
+        .iter()
+        .map(|point| Point {
+            extra_bytes: vec![0; 8].into(),
+            ..point.clone()
+        })
+        .collect();
+    criterion.bench_function("write_point with extra bytes", |b| {
+        b.iter(|| {
+            let mut writer = Writer::new(Cursor::new(Vec::new()), header.clone()).unwrap();
+            for point in points_with_extra_bytes.iter().cloned() {
+                writer.write_point(point).unwrap();
+            }
+            writer.into_inner().unwrap()
+        })
+    });
+    criterion.bench_function("write_point_ref with extra bytes", |b| {
+        b.iter(|| {
+            let mut writer = Writer::new(Cursor::new(Vec::new()), header.clone()).unwrap();
+            for point in &points_with_extra_bytes {
+                writer.write_point_ref(point).unwrap();
+            }
+            writer.into_inner().unwrap()
+        })
+    });
+    criterion.bench_function("write_point", |b| {
+        b.iter(|| {
+            let mut writer = Writer::default();
+            for point in points.iter().cloned() {
+                writer.write_point(point).unwrap();
+            }
+            writer.into_inner().unwrap()
+        })
+    });
+    criterion.bench_function("write_par_iter", |b| {
+        b.iter(|| {
+            let mut writer = Writer::default();
+            writer.write_par_iter(points.par_iter().cloned()).unwrap();
+            writer.into_inner().unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
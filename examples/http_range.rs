@@ -0,0 +1,118 @@
+//! Reads the header and first points of a las file over http, with range requests.
+//!
+//! Only the bytes that are read are downloaded. This speaks plain http/1.1 over a tcp stream to
+//! stay dependency-free; a real viewer would use an http client with tls.
+//!
+//! ```text
+//! cargo run --example http_range -- localhost:8000 /autzen.laz
+//! ```
+
+extern crate las;
+
+use las::{
+    reader::{ReadAt, ReadAtSource},
+    Reader,
+};
+use std::{
+    io::{BufRead, BufReader, Error, Read, Write},
+    net::TcpStream,
+};
+
+/// A file on an http server that answers range requests.
+struct Http {
+    host: String,
+    path: String,
+}
+
+impl Http {
+    /// Sends a request and returns the response's headers and body reader.
+    fn request(&self, method: &str, range: Option<(u64, u64)>) -> std::io::Result<Response> {
+        let mut stream = TcpStream::connect(&self.host)?;
+        write!(
+            stream,
+            "{method} {} HTTP/1.1\r\nHost: {}\r\n",
+            self.path, self.host
+        )?;
+        if let Some((start, end)) = range {
+            write!(stream, "Range: bytes={start}-{end}\r\n")?;
+        }
+        write!(stream, "Connection: close\r\n\r\n")?;
+        let mut body = BufReader::new(stream);
+        let mut status = String::new();
+        let _ = body.read_line(&mut status)?;
+        let mut content_length = None;
+        loop {
+            let mut line = String::new();
+            let _ = body.read_line(&mut line)?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse().ok();
+            }
+        }
+        Ok(Response {
+            status: status.trim_end().to_string(),
+            content_length,
+            body,
+        })
+    }
+}
+
+/// The parts of an http response that are needed here.
+struct Response {
+    status: String,
+    content_length: Option<u64>,
+    body: BufReader<TcpStream>,
+}
+
+impl ReadAt for Http {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut response = self.request("GET", Some((offset, offset + buf.len() as u64 - 1)))?;
+        if response.status.contains(" 416 ") {
+            return Ok(0);
+        }
+        if !response.status.contains(" 206 ") {
+            return Err(Error::other(format!(
+                "range request failed: {}",
+                response.status
+            )));
+        }
+        let mut n = 0;
+        while n < buf.len() {
+            match response.body.read(&mut buf[n..])? {
+                0 => break,
+                read => n += read,
+            }
+        }
+        Ok(n)
+    }
+
+    fn size(&self) -> std::io::Result<u64> {
+        let response = self.request("HEAD", None)?;
+        response
+            .content_length
+            .ok_or_else(|| Error::other("the server didn't send a content length"))
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let host = args
+        .next()
+        .expect("Must provide a host, e.g. localhost:8000");
+    let path = args.next().expect("Must provide a path, e.g. /autzen.laz");
+    let source = ReadAtSource::with_block_size(Http { host, path }, 1024 * 1024);
+    let mut reader = Reader::new(source).expect("Unable to open reader");
+    println!("Number of points: {}", reader.header().number_of_points());
+    for point in reader.points().take(5) {
+        let point = point.expect("Unable to read point");
+        println!("{} {} {}", point.x, point.y, point.z);
+    }
+}
@@ -0,0 +1,568 @@
+//! Convert points to and from [Apache Arrow](https://arrow.apache.org) record batches.
+//!
+//! A record batch has one column per attribute of the header's point format, named like
+//! [Dimension::name], followed by one nullable `Float64` column per named extra bytes attribute,
+//! with its scale and offset applied and a null for its no data value. Points with waveforms get
+//! seven more columns, one per [Waveform] field. If some extra bytes aren't covered by a named
+//! attribute, e.g. undocumented bytes, the raw extra bytes of each point are kept in a fixed size
+//! binary `ExtraBytes` column, so a round trip doesn't lose them.
+//!
+//! ```
+//! use las::{arrow, point::PointBuffer, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let mut buffer = PointBuffer::new();
+//! let _ = reader.read_batch(&mut buffer, 10).unwrap();
+//! let batch = arrow::to_record_batch(reader.header(), &buffer).unwrap();
+//! assert_eq!(10, batch.num_rows());
+//! assert_eq!(buffer, arrow::from_record_batch(reader.header(), &batch).unwrap());
+//! ```
+
+use crate::{
+    point::{Dimension, ExtraBytes, PointBuffer, ScanDirection},
+    raw::point::Waveform,
+    Error, Header, Point, Reader, Result,
+};
+use arrow::{
+    array::{
+        Array, ArrayRef, AsArray, BooleanArray, FixedSizeBinaryArray, Float32Array, Float64Array,
+        PrimitiveArray, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+    },
+    buffer::Buffer,
+    compute::cast,
+    datatypes::{
+        ArrowPrimitiveType, DataType, Field as ArrowField, Float32Type, Float64Type, Schema,
+        SchemaRef, UInt32Type, UInt64Type, UInt8Type,
+    },
+    error::ArrowError,
+    record_batch::{RecordBatch, RecordBatchReader},
+};
+use std::{
+    io::{Read, Seek},
+    sync::Arc,
+};
+
+/// The names of the waveform columns, in the order of the [Waveform] fields.
+const WAVEFORM_COLUMNS: [&str; 7] = [
+    "WavePacketDescriptorIndex",
+    "ByteOffsetToWaveformData",
+    "WaveformPacketSizeInBytes",
+    "ReturnPointWaveformLocation",
+    "XT",
+    "YT",
+    "ZT",
+];
+
+/// The name of the column with the raw extra bytes.
+const EXTRA_BYTES_COLUMN: &str = "ExtraBytes";
+
+/// Returns the arrow schema of the record batches for this header's points.
+///
+/// Returns an error if the extra bytes vlr is invalid, see [Header::extra_bytes_schema].
+///
+/// # Examples
+///
+/// ```
+/// use las::Reader;
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let schema = las::arrow::schema(reader.header()).unwrap();
+/// assert_eq!("X", schema.field(0).name());
+/// assert!(schema.field_with_name("GpsTime").is_ok());
+/// ```
+pub fn schema(header: &Header) -> Result<SchemaRef> {
+    Ok(Columns::new(header)?.schema)
+}
+
+/// Converts a buffer of points into a record batch.
+///
+/// Returns an error if the buffer's columns don't match the header's point format, e.g. if the
+/// format has gps times but the buffer doesn't.
+///
+/// # Examples
+///
+/// ```
+/// use las::{point::PointBuffer, Point, Reader};
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut buffer = PointBuffer::new();
+/// buffer.push(&Point { gps_time: Some(1.), ..Default::default() });
+/// let batch = las::arrow::to_record_batch(reader.header(), &buffer).unwrap();
+/// assert_eq!(1, batch.num_rows());
+/// ```
+pub fn to_record_batch(header: &Header, buffer: &PointBuffer) -> Result<RecordBatch> {
+    Columns::new(header)?.encode(buffer)
+}
+
+/// Converts a record batch into a buffer of points.
+///
+/// Columns are found by name, so their order doesn't matter and columns that the point format
+/// doesn't have are ignored. A column can have any type that casts to the attribute's type, e.g.
+/// an `Int64` intensity. Returns [Error::ColumnNotFound] if the batch is missing a column,
+/// [Error::NullValue] if a standard attribute is null, and [Error::InvalidDimensionValue] if a
+/// value doesn't fit its attribute. A null extra bytes attribute is stored as its no data value.
+///
+/// # Examples
+///
+/// ```
+/// use las::{point::PointBuffer, Point, Reader};
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut buffer = PointBuffer::new();
+/// buffer.push(&Point { intensity: 42, gps_time: Some(1.), ..Default::default() });
+/// let batch = las::arrow::to_record_batch(reader.header(), &buffer).unwrap();
+/// let points = las::arrow::from_record_batch(reader.header(), &batch).unwrap();
+/// assert_eq!([42], points.intensities[..]);
+/// ```
+pub fn from_record_batch(header: &Header, batch: &RecordBatch) -> Result<PointBuffer> {
+    Columns::new(header)?.decode(batch)
+}
+
+impl<R: Read + Seek + Send> Reader<R> {
+    /// Streams the rest of the points as record batches of up to `batch_size` points.
+    ///
+    /// Returns an error if the extra bytes vlr is invalid, see [Header::extra_bytes_schema].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arrow::record_batch::RecordBatchReader;
+    /// use las::Reader;
+    ///
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let batches = reader.into_record_batches(50).unwrap();
+    /// assert_eq!("X", batches.schema().field(0).name());
+    /// let rows: usize = batches.map(|batch| batch.unwrap().num_rows()).sum();
+    /// assert_eq!(106, rows);
+    /// ```
+    pub fn into_record_batches(self, batch_size: u64) -> Result<RecordBatches<R>> {
+        Ok(RecordBatches {
+            columns: Columns::new(self.header())?,
+            reader: self,
+            buffer: PointBuffer::new(),
+            batch_size,
+        })
+    }
+}
+
+/// A [RecordBatchReader] over the points of a [Reader].
+///
+/// Made by [Reader::into_record_batches].
+#[allow(missing_debug_implementations)]
+pub struct RecordBatches<R: Read + Seek + Send> {
+    reader: Reader<R>,
+    columns: Columns,
+    buffer: PointBuffer,
+    batch_size: u64,
+}
+
+impl<R: Read + Seek + Send> RecordBatches<R> {
+    /// Returns the underlying reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let reader = reader.into_record_batches(50).unwrap().into_reader();
+    /// ```
+    pub fn into_reader(self) -> Reader<R> {
+        self.reader
+    }
+}
+
+impl<R: Read + Seek + Send> Iterator for RecordBatches<R> {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = match self.reader.read_batch(&mut self.buffer, self.batch_size) {
+            Ok(0) => return None,
+            Ok(_) => self.columns.encode(&self.buffer),
+            Err(err) => Err(err),
+        };
+        Some(result.map_err(|err| match err {
+            Error::Arrow(err) => err,
+            err => ArrowError::ExternalError(Box::new(err)),
+        }))
+    }
+}
+
+impl<R: Read + Seek + Send> RecordBatchReader for RecordBatches<R> {
+    fn schema(&self) -> SchemaRef {
+        self.columns.schema.clone()
+    }
+}
+
+/// The columns of a header's points.
+struct Columns {
+    dimensions: Vec<Dimension>,
+    has_waveform: bool,
+    extra_bytes: u16,
+    has_extra_bytes_column: bool,
+    schema: SchemaRef,
+}
+
+impl Columns {
+    fn new(header: &Header) -> Result<Columns> {
+        let format = header.point_format();
+        let dimensions = header.dimensions()?;
+        let covered: usize = dimensions
+            .iter()
+            .map(|dimension| match dimension {
+                Dimension::Extra { descriptor, .. } => descriptor.len(),
+                _ => 0,
+            })
+            .sum();
+        let has_extra_bytes_column = covered < usize::from(format.extra_bytes);
+        let mut arrow_fields: Vec<ArrowField> = dimensions
+            .iter()
+            .map(|dimension| {
+                ArrowField::new(
+                    dimension.name(),
+                    data_type(dimension),
+                    matches!(dimension, Dimension::Extra { .. }),
+                )
+            })
+            .collect();
+        if format.has_waveform {
+            let data_types = [
+                DataType::UInt8,
+                DataType::UInt64,
+                DataType::UInt32,
+                DataType::Float32,
+                DataType::Float32,
+                DataType::Float32,
+                DataType::Float32,
+            ];
+            arrow_fields.extend(
+                WAVEFORM_COLUMNS
+                    .iter()
+                    .zip(data_types)
+                    .map(|(name, data_type)| ArrowField::new(*name, data_type, false)),
+            );
+        }
+        if has_extra_bytes_column {
+            arrow_fields.push(ArrowField::new(
+                EXTRA_BYTES_COLUMN,
+                DataType::FixedSizeBinary(i32::from(format.extra_bytes)),
+                false,
+            ));
+        }
+        Ok(Columns {
+            dimensions,
+            has_waveform: format.has_waveform,
+            extra_bytes: format.extra_bytes,
+            has_extra_bytes_column,
+            schema: Arc::new(Schema::new(arrow_fields)),
+        })
+    }
+
+    fn encode(&self, buffer: &PointBuffer) -> Result<RecordBatch> {
+        let mut columns: Vec<ArrayRef> = self
+            .dimensions
+            .iter()
+            .map(|dimension| column(buffer, dimension))
+            .collect();
+        if self.has_waveform {
+            let waveforms = &buffer.waveforms;
+            let floats = |value: fn(&Waveform) -> f32| -> ArrayRef {
+                Arc::new(Float32Array::from_iter_values(waveforms.iter().map(value)))
+            };
+            columns.push(Arc::new(UInt8Array::from_iter_values(
+                waveforms.iter().map(|w| w.wave_packet_descriptor_index),
+            )));
+            columns.push(Arc::new(UInt64Array::from_iter_values(
+                waveforms.iter().map(|w| w.byte_offset_to_waveform_data),
+            )));
+            columns.push(Arc::new(UInt32Array::from_iter_values(
+                waveforms.iter().map(|w| w.waveform_packet_size_in_bytes),
+            )));
+            columns.extend([
+                floats(|w| w.return_point_waveform_location),
+                floats(|w| w.x_t),
+                floats(|w| w.y_t),
+                floats(|w| w.z_t),
+            ]);
+        }
+        if self.has_extra_bytes_column {
+            columns.push(Arc::new(FixedSizeBinaryArray::try_new(
+                i32::from(self.extra_bytes),
+                Buffer::from(buffer.extra_bytes.clone()),
+                None,
+            )?));
+        }
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+
+    fn decode(&self, batch: &RecordBatch) -> Result<PointBuffer> {
+        let values = self
+            .dimensions
+            .iter()
+            .map(|dimension| primitive::<Float64Type>(batch, dimension.name()))
+            .collect::<Result<Vec<_>>>()?;
+        let waveforms = if self.has_waveform {
+            Some((
+                primitive::<UInt8Type>(batch, WAVEFORM_COLUMNS[0])?,
+                primitive::<UInt64Type>(batch, WAVEFORM_COLUMNS[1])?,
+                primitive::<UInt32Type>(batch, WAVEFORM_COLUMNS[2])?,
+                WAVEFORM_COLUMNS[3..]
+                    .iter()
+                    .map(|name| primitive::<Float32Type>(batch, name))
+                    .collect::<Result<Vec<_>>>()?,
+            ))
+        } else {
+            None
+        };
+        let extra_bytes = if self.has_extra_bytes_column {
+            let column = batch
+                .column_by_name(EXTRA_BYTES_COLUMN)
+                .ok_or_else(|| Error::ColumnNotFound(EXTRA_BYTES_COLUMN.to_string()))?;
+            let data_type = DataType::FixedSizeBinary(i32::from(self.extra_bytes));
+            Some(cast(column, &data_type)?.as_fixed_size_binary().clone())
+        } else {
+            None
+        };
+
+        let mut buffer = PointBuffer::new();
+        for index in 0..batch.num_rows() {
+            let mut point = Point {
+                extra_bytes: match &extra_bytes {
+                    Some(column) if column.is_valid(index) => ExtraBytes::from(column.value(index)),
+                    _ => ExtraBytes::zeroed(usize::from(self.extra_bytes)),
+                },
+                ..Default::default()
+            };
+            for (dimension, column) in self.dimensions.iter().zip(&values) {
+                if column.is_valid(index) {
+                    point.set_value(dimension, column.value(index))?;
+                } else if let Dimension::Extra { start, descriptor } = dimension {
+                    let _ = descriptor.encode_no_data(&mut point.extra_bytes[*start..]);
+                } else {
+                    return Err(Error::NullValue {
+                        column: dimension.name().to_string(),
+                        index,
+                    });
+                }
+            }
+            if let Some((index_column, offsets, sizes, floats)) = &waveforms {
+                let require = |name: &str, valid: bool| {
+                    valid.then_some(()).ok_or_else(|| Error::NullValue {
+                        column: name.to_string(),
+                        index,
+                    })
+                };
+                require(WAVEFORM_COLUMNS[0], index_column.is_valid(index))?;
+                require(WAVEFORM_COLUMNS[1], offsets.is_valid(index))?;
+                require(WAVEFORM_COLUMNS[2], sizes.is_valid(index))?;
+                for (name, column) in WAVEFORM_COLUMNS[3..].iter().zip(floats) {
+                    require(name, column.is_valid(index))?;
+                }
+                point.waveform = Some(Waveform {
+                    wave_packet_descriptor_index: index_column.value(index),
+                    byte_offset_to_waveform_data: offsets.value(index),
+                    waveform_packet_size_in_bytes: sizes.value(index),
+                    return_point_waveform_location: floats[0].value(index),
+                    x_t: floats[1].value(index),
+                    y_t: floats[2].value(index),
+                    z_t: floats[3].value(index),
+                });
+            }
+            buffer.push(&point);
+        }
+        Ok(buffer)
+    }
+}
+
+/// Returns the arrow type of a dimension's column.
+fn data_type(dimension: &Dimension) -> DataType {
+    match dimension {
+        Dimension::X
+        | Dimension::Y
+        | Dimension::Z
+        | Dimension::GpsTime
+        | Dimension::Extra { .. } => DataType::Float64,
+        Dimension::Intensity
+        | Dimension::PointSourceId
+        | Dimension::Red
+        | Dimension::Green
+        | Dimension::Blue
+        | Dimension::Nir => DataType::UInt16,
+        Dimension::ReturnNumber
+        | Dimension::NumberOfReturns
+        | Dimension::Classification
+        | Dimension::ScannerChannel
+        | Dimension::UserData => DataType::UInt8,
+        Dimension::ScanDirection
+        | Dimension::EdgeOfFlightLine
+        | Dimension::Synthetic
+        | Dimension::KeyPoint
+        | Dimension::Withheld
+        | Dimension::Overlap => DataType::Boolean,
+        Dimension::ScanAngle => DataType::Float32,
+    }
+}
+
+/// Builds a dimension's column from a buffer.
+fn column(buffer: &PointBuffer, dimension: &Dimension) -> ArrayRef {
+    let flags = |flags: &[bool]| -> ArrayRef { Arc::new(BooleanArray::from(flags.to_vec())) };
+    match dimension {
+        Dimension::X => Arc::new(Float64Array::from(buffer.xs.clone())),
+        Dimension::Y => Arc::new(Float64Array::from(buffer.ys.clone())),
+        Dimension::Z => Arc::new(Float64Array::from(buffer.zs.clone())),
+        Dimension::Intensity => Arc::new(UInt16Array::from(buffer.intensities.clone())),
+        Dimension::ReturnNumber => Arc::new(UInt8Array::from(buffer.return_numbers.clone())),
+        Dimension::NumberOfReturns => Arc::new(UInt8Array::from(buffer.numbers_of_returns.clone())),
+        Dimension::ScanDirection => Arc::new(BooleanArray::from_iter(
+            buffer
+                .scan_directions
+                .iter()
+                .map(|&direction| Some(direction == ScanDirection::LeftToRight)),
+        )),
+        Dimension::EdgeOfFlightLine => flags(&buffer.is_edge_of_flight_line),
+        Dimension::Classification => Arc::new(UInt8Array::from_iter_values(
+            buffer.classifications.iter().map(|&c| u8::from(c)),
+        )),
+        Dimension::Synthetic => flags(&buffer.is_synthetic),
+        Dimension::KeyPoint => flags(&buffer.is_key_point),
+        Dimension::Withheld => flags(&buffer.is_withheld),
+        Dimension::Overlap => flags(&buffer.is_overlap),
+        Dimension::ScannerChannel => Arc::new(UInt8Array::from(buffer.scanner_channels.clone())),
+        Dimension::ScanAngle => Arc::new(Float32Array::from(buffer.scan_angles.clone())),
+        Dimension::UserData => Arc::new(UInt8Array::from(buffer.user_data.clone())),
+        Dimension::PointSourceId => Arc::new(UInt16Array::from(buffer.point_source_ids.clone())),
+        Dimension::GpsTime => Arc::new(Float64Array::from(buffer.gps_times.clone())),
+        Dimension::Red => Arc::new(UInt16Array::from_iter_values(
+            buffer.colors.iter().map(|color| color.red),
+        )),
+        Dimension::Green => Arc::new(UInt16Array::from_iter_values(
+            buffer.colors.iter().map(|color| color.green),
+        )),
+        Dimension::Blue => Arc::new(UInt16Array::from_iter_values(
+            buffer.colors.iter().map(|color| color.blue),
+        )),
+        Dimension::Nir => Arc::new(UInt16Array::from(buffer.nirs.clone())),
+        Dimension::Extra { start, descriptor } => {
+            Arc::new(Float64Array::from_iter((0..buffer.len()).map(|index| {
+                descriptor.decode(buffer.point_extra_bytes(index)?.get(*start..)?)
+            })))
+        }
+    }
+}
+
+/// Returns a batch's column, cast to a primitive type.
+fn primitive<T: ArrowPrimitiveType>(batch: &RecordBatch, name: &str) -> Result<PrimitiveArray<T>> {
+    let column = batch
+        .column_by_name(name)
+        .ok_or_else(|| Error::ColumnNotFound(name.to_string()))?;
+    Ok(cast(column, &T::DATA_TYPE)?.as_primitive::<T>().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, Builder};
+    use arrow::array::Int64Array;
+
+    fn read(path: &str) -> (Header, PointBuffer) {
+        let mut reader = Reader::from_path(path).unwrap();
+        let mut buffer = PointBuffer::new();
+        let _ = reader.read_batch(&mut buffer, u64::MAX).unwrap();
+        (reader.header().clone(), buffer)
+    }
+
+    #[test]
+    fn roundtrip() {
+        for path in ["tests/data/autzen.las", "tests/data/waveform.las"] {
+            let (header, buffer) = read(path);
+            let batch = to_record_batch(&header, &buffer).unwrap();
+            assert_eq!(buffer.len(), batch.num_rows());
+            assert_eq!(*schema(&header).unwrap(), *batch.schema());
+            assert_eq!(
+                buffer,
+                from_record_batch(&header, &batch).unwrap(),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn extra_bytes() {
+        use crate::point::{ExtraBytesDescriptor, ExtraBytesType};
+
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(0).unwrap();
+        builder.point_format.extra_bytes = 3;
+        let mut descriptor = ExtraBytesDescriptor::new("Height", ExtraBytesType::U16);
+        descriptor.no_data = Some(0.);
+        builder.vlrs.push(
+            ExtraBytesDescriptor::to_vlr(&[
+                descriptor,
+                ExtraBytesDescriptor::new("", ExtraBytesType::Undocumented(1)),
+            ])
+            .unwrap(),
+        );
+        let header = builder.into_header().unwrap();
+        let mut buffer = PointBuffer::new();
+        for extra_bytes in [[7, 0, 1], [0, 0, 2]] {
+            buffer.push(&Point {
+                extra_bytes: ExtraBytes::from(&extra_bytes[..]),
+                ..Default::default()
+            });
+        }
+
+        let batch = to_record_batch(&header, &buffer).unwrap();
+        let heights = batch
+            .column_by_name("Height")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        assert_eq!(Some(7.), heights.iter().next().flatten());
+        assert!(heights.is_null(1));
+        assert!(batch.column_by_name(EXTRA_BYTES_COLUMN).is_some());
+        assert_eq!(buffer, from_record_batch(&header, &batch).unwrap());
+    }
+
+    #[test]
+    fn casts_columns() {
+        let (header, buffer) = read("tests/data/autzen.las");
+        let batch = to_record_batch(&header, &buffer).unwrap();
+        let index = batch.schema().index_of("Intensity").unwrap();
+        let mut columns = batch.columns().to_vec();
+        columns[index] = Arc::new(Int64Array::from_iter_values(
+            buffer.intensities.iter().map(|&i| i64::from(i)),
+        ));
+        let mut fields = batch.schema().fields().to_vec();
+        fields[index] = Arc::new(ArrowField::new("Intensity", DataType::Int64, false));
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap();
+        assert_eq!(buffer, from_record_batch(&header, &batch).unwrap());
+    }
+
+    #[test]
+    fn missing_column() {
+        let (header, buffer) = read("tests/data/autzen.las");
+        let mut batch = to_record_batch(&header, &buffer).unwrap();
+        let index = batch.schema().index_of("GpsTime").unwrap();
+        let _ = batch.remove_column(index);
+        assert!(matches!(
+            from_record_batch(&header, &batch),
+            Err(Error::ColumnNotFound(name)) if name == "GpsTime"
+        ));
+    }
+
+    #[test]
+    fn mismatched_buffer() {
+        let (header, _) = read("tests/data/autzen.las");
+        let mut buffer = PointBuffer::new();
+        buffer.push(&Point::default());
+        assert!(matches!(
+            to_record_batch(&header, &buffer),
+            Err(Error::Arrow(_))
+        ));
+    }
+
+    #[test]
+    fn record_batches() {
+        let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let batches = reader.into_record_batches(100).unwrap();
+        let schema = batches.schema();
+        let rows: Vec<usize> = batches.map(|batch| batch.unwrap().num_rows()).collect();
+        assert_eq!(vec![100, 6], rows);
+        assert!(schema.field_with_name("GpsTime").is_ok());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{
+    point::{ExtraBytes, ExtraBytesDescriptor},
+    Builder, Error, Header, Point, Reader, Result, Writer,
+};
+use std::io::{Read, Seek, Write};
+
+/// What [copy_with_attribute] wrote.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeReport {
+    /// The header of the written file.
+    pub header: Header,
+
+    /// The number of points written.
+    pub points: u64,
+
+    /// The number of points whose value couldn't be stored, so they got the no data value.
+    pub no_data: u64,
+}
+
+/// Copies a reader's points to a destination, with a new extra bytes attribute computed from
+/// each point.
+///
+/// The attribute goes after the points' existing extra bytes, and its descriptor is appended to
+/// the builder's extra bytes vlr, which is created if there isn't one. Existing extra bytes that
+/// the vlr doesn't describe are kept, as undocumented bytes. The builder's other fields, e.g. its
+/// version and point format, are kept as they are.
+///
+/// Each value is encoded with the descriptor's scale and offset. Values that the descriptor's
+/// type can't hold are stored as its no data value and counted, or, if it doesn't have one,
+/// cause an error with the point's index, leaving the destination incomplete.
+///
+/// # Examples
+///
+/// ```
+/// use las::{
+///     point::{ExtraBytesDescriptor, ExtraBytesType},
+///     Reader,
+/// };
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let builder = reader.header().clone().into_builder();
+/// let mut descriptor = ExtraBytesDescriptor::new("HeightAboveGround", ExtraBytesType::I32);
+/// descriptor.scale = Some(0.01);
+/// descriptor.no_data = Some(f64::from(i32::MIN));
+/// let report = las::copy_with_attribute(
+///     reader,
+///     Cursor::new(Vec::new()),
+///     builder,
+///     descriptor,
+///     |point| point.z - 120.,
+/// )
+/// .unwrap();
+/// assert_eq!(106, report.points);
+/// assert_eq!(4, report.header.point_format().extra_bytes);
+/// ```
+pub fn copy_with_attribute<R, W>(
+    mut reader: Reader<R>,
+    dest: W,
+    mut builder: Builder,
+    descriptor: ExtraBytesDescriptor,
+    mut f: impl FnMut(&Point) -> f64,
+) -> Result<AttributeReport>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let existing = usize::from(reader.header().point_format().extra_bytes);
+    let len = descriptor.len();
+    builder.extra_bytes(u16::try_from(existing)?);
+    builder.add_extra_attribute(descriptor.clone())?;
+
+    let mut writer = Writer::new(dest, builder.into_header()?)?;
+    let mut points = 0;
+    let mut no_data = 0;
+    let mut point = Point::default();
+    while reader.read_point_into(&mut point)? {
+        let value = f(&point);
+        let mut extra_bytes = ExtraBytes::zeroed(existing + len);
+        extra_bytes[..existing].copy_from_slice(&point.extra_bytes);
+        let attribute = &mut extra_bytes[existing..];
+        if !descriptor.encode(value, attribute) {
+            if !descriptor.encode_no_data(attribute) {
+                return Err(Error::AttributeNotRepresentable {
+                    index: points,
+                    value,
+                });
+            }
+            no_data += 1;
+        }
+        point.extra_bytes = extra_bytes;
+        writer.write_point_ref(&point)?;
+        points += 1;
+    }
+    let _ = writer.close()?;
+    Ok(AttributeReport {
+        header: writer.header().clone(),
+        points,
+        no_data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        point::{ExtraBytesType, Format},
+        synthetic::fixture,
+    };
+    use std::{fs::File, io::Cursor};
+
+    fn source(extra_bytes: &[Vec<u8>]) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(1).unwrap();
+        builder.extra_bytes(extra_bytes.first().map_or(0, |bytes| bytes.len() as u16));
+        let points = extra_bytes.iter().enumerate().map(|(i, bytes)| Point {
+            z: i as f64,
+            gps_time: Some(0.),
+            extra_bytes: bytes.clone().into(),
+            ..Default::default()
+        });
+        fixture::reader(fixture::write(builder, points))
+    }
+
+    fn copy(
+        name: &str,
+        reader: Reader<Cursor<Vec<u8>>>,
+        descriptor: ExtraBytesDescriptor,
+        f: impl FnMut(&Point) -> f64,
+    ) -> Result<(AttributeReport, Reader<Cursor<Vec<u8>>>)> {
+        let path = std::env::temp_dir().join(format!("las-rs-copy-with-attribute-{name}.las"));
+        let builder = reader.header().clone().into_builder();
+        let report = copy_with_attribute(reader, File::create(&path)?, builder, descriptor, f);
+        let bytes = std::fs::read(&path)?;
+        std::fs::remove_file(&path)?;
+        Ok((report?, fixture::reader(bytes)))
+    }
+
+    fn descriptors(header: &Header) -> Vec<ExtraBytesDescriptor> {
+        header
+            .vlrs()
+            .iter()
+            .find_map(ExtraBytesDescriptor::from_vlr)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn height_above_ground() {
+        let mut descriptor = ExtraBytesDescriptor::new("HeightAboveGround", ExtraBytesType::I16);
+        descriptor.scale = Some(0.01);
+        descriptor.no_data = Some(f64::from(i16::MIN));
+        let (report, reader) = copy(
+            "height-above-ground",
+            source(&[vec![], vec![], vec![]]),
+            descriptor.clone(),
+            |point| [1.234, -400., 0.][point.z as usize],
+        )
+        .unwrap();
+        assert_eq!(3, report.points);
+        assert_eq!(1, report.no_data);
+        assert_eq!(vec![descriptor.clone()], descriptors(reader.header()));
+        assert_eq!(report.header.bounds(), reader.header().bounds());
+        assert_eq!(3, reader.header().number_of_points());
+        let values: Vec<Option<f64>> = reader
+            .into_points()
+            .unwrap()
+            .iter()
+            .map(|point| descriptor.decode(&point.extra_bytes))
+            .collect();
+        assert_eq!(vec![Some(1.23), None, Some(0.)], values);
+    }
+
+    #[test]
+    fn keeps_undocumented_extra_bytes() {
+        let descriptor = ExtraBytesDescriptor::new("Value", ExtraBytesType::F64);
+        let (_, reader) = copy(
+            "undocumented",
+            source(&[vec![1, 2, 3], vec![4, 5, 6]]),
+            descriptor.clone(),
+            |point| point.z * 2.,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                ExtraBytesDescriptor::new("", ExtraBytesType::Undocumented(3)),
+                descriptor.clone()
+            ],
+            descriptors(reader.header())
+        );
+        let points = reader.into_points().unwrap();
+        assert_eq!([1, 2, 3], points[0].extra_bytes[..3]);
+        assert_eq!([4, 5, 6], points[1].extra_bytes[..3]);
+        assert_eq!(Some(2.), descriptor.decode(&points[1].extra_bytes[3..]));
+    }
+
+    #[test]
+    fn appends_to_existing_descriptors() {
+        let amplitude = ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::U16);
+        let (_, reader) = copy(
+            "first",
+            source(&[vec![], vec![]]),
+            amplitude.clone(),
+            |point| point.z + 10.,
+        )
+        .unwrap();
+        let reflectance = ExtraBytesDescriptor::new("Reflectance", ExtraBytesType::U8);
+        let (_, reader) = copy("second", reader, reflectance.clone(), |point| point.z).unwrap();
+        assert_eq!(
+            vec![amplitude.clone(), reflectance.clone()],
+            descriptors(reader.header())
+        );
+        assert_eq!(1, reader.header().vlrs().len());
+        let point = &reader.into_points().unwrap()[1];
+        assert_eq!(Some(11.), amplitude.decode(&point.extra_bytes));
+        assert_eq!(Some(1.), reflectance.decode(&point.extra_bytes[2..]));
+    }
+
+    #[test]
+    fn not_representable_without_no_data() {
+        let descriptor = ExtraBytesDescriptor::new("Value", ExtraBytesType::U8);
+        let err = copy(
+            "not-representable",
+            source(&[vec![], vec![]]),
+            descriptor,
+            |point| point.z * 1000.,
+        )
+        .map(|_| ())
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::AttributeNotRepresentable { index: 1, value } if value == 1000.
+        ));
+    }
+}
@@ -1,8 +1,11 @@
-use crate::{transform::RoundingMode, Point, Result, Transform, Vector};
-use std::f64;
+#[cfg(feature = "std")]
+use crate::Point;
+use crate::{transform::RoundingMode, Result, Transform, Vector};
+use core::fmt;
 
 /// Minimum and maximum bounds in three dimensions.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bounds {
     /// The minimum values.
     pub min: Vector<f64>,
@@ -23,6 +26,7 @@ impl Bounds {
     /// bounds.grow(&point);
     /// assert_eq!(1., bounds.min.x);
     /// ```
+    #[cfg(feature = "std")]
     pub fn grow(&mut self, point: &Point) {
         if point.x < self.min.x {
             self.min.x = point.x;
@@ -111,7 +115,29 @@ impl Default for Bounds {
     }
 }
 
-#[cfg(test)]
+/// Formats the bounds as `(min x, min y, min z) to (max x, max y, max z)`.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Bounds, Vector};
+/// let bounds = Bounds {
+///     min: Vector { x: 1., y: 2., z: 3. },
+///     max: Vector { x: 4.5, y: 5., z: 6. },
+/// };
+/// assert_eq!("(1, 2, 3) to (4.5, 5, 6)", bounds.to_string());
+/// ```
+impl fmt::Display for Bounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "({}, {}, {}) to ({}, {}, {})",
+            self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z
+        )
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
 
     use super::*;
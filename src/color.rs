@@ -1,5 +1,17 @@
 /// A RGB color value.
+///
+/// The las spec says that colors are stored with sixteen bits per channel, but plenty of files
+/// have eight bit values in the sixteen bit fields. [Color::is_probably_8bit] detects those, and
+/// [Color::scaled_to_16bit] stretches them:
+///
+/// ```
+/// use las::Color;
+/// let color = Color::new(255, 128, 0);
+/// assert!(color.is_probably_8bit());
+/// assert_eq!(Color::new(65535, 32896, 0), color.scaled_to_16bit());
+/// ```
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     /// Red channel.
     pub red: u16,
@@ -26,4 +38,150 @@ impl Color {
     pub fn new(red: u16, green: u16, blue: u16) -> Color {
         Color { red, green, blue }
     }
+
+    /// Returns true if every channel fits in eight bits.
+    ///
+    /// This is only a guess. Dark colors from a sixteen bit source look like eight bit colors,
+    /// and black is both, so check many points before deciding a file is eight bit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Color;
+    /// assert!(Color::new(255, 255, 255).is_probably_8bit());
+    /// assert!(!Color::new(256, 0, 0).is_probably_8bit());
+    /// assert!(Color::new(0, 0, 0).is_probably_8bit());
+    /// ```
+    pub fn is_probably_8bit(&self) -> bool {
+        self.red <= 0xFF && self.green <= 0xFF && self.blue <= 0xFF
+    }
+
+    /// Stretches an eight bit color to sixteen bits, so 255 becomes 65535.
+    ///
+    /// Channels that don't fit in eight bits saturate at 65535.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Color;
+    /// assert_eq!(Color::new(0, 257, 65535), Color::new(0, 1, 255).scaled_to_16bit());
+    /// assert_eq!(65535, Color::new(1000, 0, 0).scaled_to_16bit().red);
+    /// ```
+    pub fn scaled_to_16bit(&self) -> Color {
+        let scale = |channel: u16| channel.saturating_mul(0x101);
+        Color::new(scale(self.red), scale(self.green), scale(self.blue))
+    }
+
+    /// Shrinks a sixteen bit color to eight bits, rounding to the nearest value.
+    ///
+    /// This is the inverse of [Color::scaled_to_16bit].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Color;
+    /// assert_eq!(Color::new(0, 1, 255), Color::new(0, 257, 65535).scaled_to_8bit());
+    /// assert_eq!(128, Color::new(32896, 0, 0).scaled_to_8bit().red);
+    /// ```
+    pub fn scaled_to_8bit(&self) -> Color {
+        let scale = |channel: u16| ((u32::from(channel) + 0x80) / 0x101) as u16;
+        Color::new(scale(self.red), scale(self.green), scale(self.blue))
+    }
+}
+
+/// Stretches eight bit channels to sixteen bits.
+///
+/// # Examples
+///
+/// ```
+/// use las::Color;
+/// assert_eq!(Color::new(65535, 0, 257), Color::from([255u8, 0, 1]));
+/// ```
+impl From<[u8; 3]> for Color {
+    fn from([red, green, blue]: [u8; 3]) -> Color {
+        Color::new(red.into(), green.into(), blue.into()).scaled_to_16bit()
+    }
+}
+
+impl From<[u16; 3]> for Color {
+    fn from([red, green, blue]: [u16; 3]) -> Color {
+        Color::new(red, green, blue)
+    }
+}
+
+impl From<Color> for [u16; 3] {
+    fn from(color: Color) -> [u16; 3] {
+        [color.red, color.green, color.blue]
+    }
+}
+
+#[cfg(feature = "rgb")]
+impl From<rgb::RGB16> for Color {
+    fn from(rgb: rgb::RGB16) -> Color {
+        Color::new(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+/// Stretches eight bit channels to sixteen bits.
+#[cfg(feature = "rgb")]
+impl From<rgb::RGB8> for Color {
+    fn from(rgb: rgb::RGB8) -> Color {
+        Color::from([rgb.r, rgb.g, rgb.b])
+    }
+}
+
+#[cfg(feature = "rgb")]
+impl From<Color> for rgb::RGB16 {
+    fn from(color: Color) -> rgb::RGB16 {
+        rgb::RGB16::new(color.red, color.green, color.blue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_8bit() {
+        for channel in 0..=255 {
+            let color = Color::new(channel, channel, channel);
+            assert_eq!(color, color.scaled_to_16bit().scaled_to_8bit());
+        }
+    }
+
+    #[test]
+    fn black_is_ambiguous() {
+        let black = Color::default();
+        assert!(black.is_probably_8bit());
+        assert_eq!(black, black.scaled_to_16bit());
+        assert_eq!(black, black.scaled_to_8bit());
+    }
+
+    #[test]
+    fn mixed_depth() {
+        // A dark sixteen bit color looks eight bit...
+        assert!(Color::new(200, 10, 0).is_probably_8bit());
+        // ...but one bright channel gives it away.
+        assert!(!Color::new(200, 10, 300).is_probably_8bit());
+        assert_eq!(
+            Color::new(51400, 2570, 65535),
+            Color::new(200, 10, 300).scaled_to_16bit()
+        );
+    }
+
+    #[test]
+    fn arrays() {
+        assert_eq!([1, 2, 3], <[u16; 3]>::from(Color::from([1u16, 2, 3])));
+    }
+
+    #[cfg(feature = "rgb")]
+    #[test]
+    fn rgb() {
+        let color = Color::new(1, 2, 3);
+        assert_eq!(color, Color::from(rgb::RGB16::from(color)));
+        assert_eq!(
+            Color::new(257, 0, 65535),
+            Color::from(rgb::RGB8::new(1, 0, 255))
+        );
+    }
 }
@@ -0,0 +1,215 @@
+use crate::{header::SystemIdentifier, raw, Color, Reader, Result, Writer};
+use std::io::{Read, Seek, Write};
+
+/// Whether a file's points have colors, see [color_status].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorStatus {
+    /// At least one point has a color that isn't black.
+    Present,
+
+    /// The point format has colors, but every point's color is zero.
+    AllZero,
+
+    /// The point format has no colors.
+    Absent,
+}
+
+/// What [drop_zero_color] did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorDrop {
+    /// The color status of the source.
+    pub status: ColorStatus,
+
+    /// True if the points were written without colors, false if they were copied as they are.
+    pub dropped: bool,
+
+    /// The number of points written.
+    pub points: u64,
+}
+
+/// Finds out whether a reader's points have colors.
+///
+/// Point formats two, three, five, seven, eight, and ten have colors, but plenty of producers
+/// without imagery fill them with zeros. The points are read from the start until one with a
+/// color that isn't zero is found, so a file with colors is usually only read a little way. The
+/// reader is left back at the start.
+///
+/// # Examples
+///
+/// ```
+/// use las::{ColorStatus, Reader};
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// assert_eq!(ColorStatus::Absent, las::color_status(&mut reader).unwrap());
+/// ```
+pub fn color_status<R>(reader: &mut Reader<R>) -> Result<ColorStatus>
+where
+    R: Read + Seek + Send,
+{
+    let format = *reader.header().point_format();
+    if !format.has_color {
+        return Ok(ColorStatus::Absent);
+    }
+    let mut record = vec![0; usize::from(format.len())];
+    let mut status = ColorStatus::AllZero;
+    reader.seek(0)?;
+    while reader.read_record(&mut record)? {
+        let raw_point = raw::Point::read_from(record.as_slice(), &format)?;
+        if raw_point
+            .color
+            .is_some_and(|color| color != Color::default())
+        {
+            status = ColorStatus::Present;
+            break;
+        }
+    }
+    reader.seek(0)?;
+    Ok(status)
+}
+
+/// Copies a reader's points to a destination, dropping the colors if they're all zero.
+///
+/// If [color_status] is [ColorStatus::AllZero], the points are written with the point format
+/// that's the same but without colors, e.g. format three becomes format one. Formats eight and
+/// ten have no such format, since there's no format with near infrared but no colors, so they're
+/// copied as they are. Everything else about the points and the header is kept.
+///
+/// # Examples
+///
+/// ```
+/// use las::Reader;
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let drop = las::drop_zero_color(reader, Cursor::new(Vec::new())).unwrap();
+/// assert!(!drop.dropped);
+/// ```
+pub fn drop_zero_color<R, W>(mut reader: Reader<R>, dest: W) -> Result<ColorDrop>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let status = color_status(&mut reader)?;
+    let mut builder = reader
+        .header()
+        .to_derived(&SystemIdentifier::Modification)
+        .into_builder();
+    let mut format = builder.point_format;
+    format.has_color = false;
+    let dropped = status == ColorStatus::AllZero && format.to_u8().is_ok();
+    if dropped {
+        builder.point_format = format;
+    }
+    let mut writer = Writer::new(dest, builder.into_header()?)?;
+    let mut points = 0;
+    for point in reader.points() {
+        let mut point = point?;
+        if dropped {
+            point.color = None;
+        }
+        writer.write_point(point)?;
+        points += 1;
+    }
+    let _ = writer.close()?;
+    Ok(ColorDrop {
+        status,
+        dropped,
+        points,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, synthetic::fixture, Builder, GpsTimeType, Point};
+    use std::{fs::File, io::Cursor};
+
+    fn bytes(format: u8, colored: Option<u64>) -> Vec<u8> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.gps_time_type = GpsTimeType::Standard;
+        let format = builder.point_format;
+        let points = (0..100).map(|i| Point {
+            x: i as f64,
+            gps_time: format.has_gps_time.then_some(i as f64),
+            color: format.has_color.then(|| {
+                if colored == Some(i) {
+                    Color::new(0, 0, 1)
+                } else {
+                    Color::default()
+                }
+            }),
+            nir: format.has_nir.then_some(1),
+            ..Default::default()
+        });
+        fixture::write(builder, points)
+    }
+
+    fn source(format: u8, colored: Option<u64>) -> Reader<Cursor<Vec<u8>>> {
+        fixture::reader(bytes(format, colored))
+    }
+
+    fn drop(name: &str, reader: Reader<Cursor<Vec<u8>>>) -> (ColorDrop, Reader<Cursor<Vec<u8>>>) {
+        let path = std::env::temp_dir().join(format!("las-rs-drop-zero-color-{name}.las"));
+        let drop = drop_zero_color(reader, File::create(&path).unwrap()).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        (drop, fixture::reader(bytes))
+    }
+
+    #[test]
+    fn status() {
+        for (format, colored, expected) in [
+            (3, None, ColorStatus::AllZero),
+            (3, Some(99), ColorStatus::Present),
+            (7, Some(0), ColorStatus::Present),
+            (1, None, ColorStatus::Absent),
+        ] {
+            let mut reader = source(format, colored);
+            assert_eq!(expected, color_status(&mut reader).unwrap());
+            assert_eq!(100, reader.read_points(1000).unwrap().len());
+        }
+    }
+
+    #[test]
+    fn stops_at_first_color() {
+        let mut bytes = bytes(2, Some(10));
+        let len = usize::from(Format::new(2).unwrap().len());
+        let offset_to_point_data = bytes.len() - 100 * len;
+        // The records after the colored point are cut off, but they're never read.
+        bytes.truncate(offset_to_point_data + 11 * len);
+        let mut reader = fixture::reader(bytes);
+        assert_eq!(ColorStatus::Present, color_status(&mut reader).unwrap());
+    }
+
+    #[test]
+    fn drops_all_zero() {
+        let (drop, reader) = drop("all-zero", source(3, None));
+        assert_eq!(ColorStatus::AllZero, drop.status);
+        assert!(drop.dropped);
+        assert_eq!(100, drop.points);
+        assert_eq!(Format::new(1).unwrap(), *reader.header().point_format());
+        let points = reader.into_points().unwrap();
+        assert_eq!(None, points[0].color);
+        assert_eq!(Some(42.), points[42].gps_time);
+    }
+
+    #[test]
+    fn keeps_single_color() {
+        let (drop, reader) = drop("single-color", source(7, Some(50)));
+        assert_eq!(ColorStatus::Present, drop.status);
+        assert!(!drop.dropped);
+        assert_eq!(Format::new(7).unwrap(), *reader.header().point_format());
+        assert_eq!(
+            source(7, Some(50)).into_points().unwrap(),
+            reader.into_points().unwrap()
+        );
+    }
+
+    #[test]
+    fn keeps_nir_formats() {
+        let (drop, reader) = drop("nir", source(8, None));
+        assert_eq!(ColorStatus::AllZero, drop.status);
+        assert!(!drop.dropped);
+        assert_eq!(Format::new(8).unwrap(), *reader.header().point_format());
+    }
+}
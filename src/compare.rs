@@ -0,0 +1,484 @@
+use crate::{point::Format, raw, Error, Header, Point, Reader, Result, Transform, Vector};
+use std::{fmt::Debug, io::Seek};
+
+/// The most point differences that a [ComparisonReport] lists.
+///
+/// Points past these are still counted in [ComparisonReport::differing_points].
+pub const MAX_POINT_DIFFERENCES: usize = 100;
+
+/// How [verify_equal] compares points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompareMode {
+    /// The raw point records must be the same, byte for byte.
+    ///
+    /// The two files must have the same point layout, see [Header::layout_compatible_with].
+    RawBytes,
+
+    /// The coordinates, as the integers they're stored as, and every other field must be the
+    /// same.
+    ///
+    /// Records that read as the same fields are equal, even if their bytes differ, e.g. the legacy
+    /// overlap class in point formats six through ten, which reads as unclassified.
+    Quantized,
+
+    /// The coordinates must be within this distance of each other, and every other field must
+    /// be the same.
+    ///
+    /// The files can have different scales and offsets.
+    Approx(f64),
+}
+
+/// What [verify_equal] found.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ComparisonReport {
+    /// The header fields that describe the points and differ between the two files.
+    pub header_differences: Vec<FieldDifference>,
+
+    /// The number of point pairs compared, which is the smaller of the two point counts.
+    pub points_compared: u64,
+
+    /// The number of point pairs that differ.
+    pub differing_points: u64,
+
+    /// The first [MAX_POINT_DIFFERENCES] point pairs that differ.
+    pub point_differences: Vec<PointDifference>,
+}
+
+/// A point that differs between two files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PointDifference {
+    /// The index of the point in both files.
+    pub index: u64,
+
+    /// The fields that differ.
+    pub fields: Vec<FieldDifference>,
+}
+
+/// A field that differs between two files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldDifference {
+    /// The name of the field.
+    pub field: &'static str,
+
+    /// The field's value in the first file.
+    pub a: String,
+
+    /// The field's value in the second file.
+    pub b: String,
+}
+
+impl ComparisonReport {
+    /// Returns true if neither the headers nor the points differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ComparisonReport;
+    /// assert!(ComparisonReport::default().is_equal());
+    /// ```
+    pub fn is_equal(&self) -> bool {
+        self.header_differences.is_empty() && self.differing_points == 0
+    }
+
+    fn add(&mut self, index: u64, fields: Vec<FieldDifference>) {
+        if fields.is_empty() {
+            return;
+        }
+        self.differing_points += 1;
+        if self.point_differences.len() < MAX_POINT_DIFFERENCES {
+            self.point_differences
+                .push(PointDifference { index, fields });
+        }
+    }
+}
+
+impl FieldDifference {
+    pub(crate) fn new(field: &'static str, a: impl Debug, b: impl Debug) -> FieldDifference {
+        FieldDifference {
+            field,
+            a: format!("{:?}", a),
+            b: format!("{:?}", b),
+        }
+    }
+}
+
+/// Compares the points of two readers, e.g. to check that a conversion was lossless.
+///
+/// Both readers are read from their first point, and are left after the last point compared.
+/// Header fields that describe the points are compared too, but not compression, vlrs, or the
+/// version. Returns an error if the files can't be read, or if [CompareMode::RawBytes] is used on
+/// files with different point layouts.
+///
+/// # Examples
+///
+/// ```
+/// use las::{CompareMode, Reader};
+///
+/// let mut a = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut b = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let report = las::verify_equal(&mut a, &mut b, CompareMode::RawBytes).unwrap();
+/// assert!(report.is_equal());
+/// assert_eq!(106, report.points_compared);
+/// ```
+pub fn verify_equal<R, S>(
+    a: &mut Reader<R>,
+    b: &mut Reader<S>,
+    mode: CompareMode,
+) -> Result<ComparisonReport>
+where
+    R: std::io::Read + Seek + Send,
+    S: std::io::Read + Seek + Send,
+{
+    let (header_a, header_b) = (a.header().clone(), b.header().clone());
+    if mode == CompareMode::RawBytes
+        && let Err(incompatibilities) = header_a.layout_compatible_with(&header_b)
+    {
+        return Err(Error::IncompatibleLayout {
+            index: 1,
+            incompatibilities,
+        });
+    }
+    let mut report = ComparisonReport {
+        header_differences: header_differences(&header_a, &header_b, mode),
+        ..Default::default()
+    };
+    a.seek(0)?;
+    b.seek(0)?;
+    let n = header_a.number_of_points().min(header_b.number_of_points());
+    if mode == CompareMode::RawBytes {
+        let format = *header_a.point_format();
+        let mut record_a = vec![0; usize::from(format.len())];
+        let mut record_b = record_a.clone();
+        for index in 0..n {
+            if !(a.read_record(&mut record_a)? && b.read_record(&mut record_b)?) {
+                break;
+            }
+            report.points_compared += 1;
+            if record_a != record_b {
+                let point_a = decode(&record_a, &format, header_a.effective_transforms())?;
+                let point_b = decode(&record_b, &format, header_b.effective_transforms())?;
+                let mut fields = point_differences(&point_a, &point_b, &header_a, &header_b, mode)?;
+                if fields.is_empty() {
+                    fields.push(FieldDifference {
+                        field: "record",
+                        a: format!("{:02x?}", record_a),
+                        b: format!("{:02x?}", record_b),
+                    });
+                }
+                report.add(index, fields);
+            }
+        }
+    } else {
+        for index in 0..n {
+            let (Some(point_a), Some(point_b)) = (a.read_point()?, b.read_point()?) else {
+                break;
+            };
+            report.points_compared += 1;
+            let fields = point_differences(&point_a, &point_b, &header_a, &header_b, mode)?;
+            report.add(index, fields);
+        }
+    }
+    Ok(report)
+}
+
+/// Compares two lists of points, like [verify_equal] compares two readers' points.
+///
+/// Lists of different lengths differ in `number_of_points`.
+#[cfg(any(test, feature = "test-support"))]
+pub(crate) fn compare_points(
+    a: &[Point],
+    b: &[Point],
+    header_a: &Header,
+    header_b: &Header,
+    mode: CompareMode,
+) -> Result<ComparisonReport> {
+    let mut report = ComparisonReport::default();
+    if a.len() != b.len() {
+        report
+            .header_differences
+            .push(FieldDifference::new("number_of_points", a.len(), b.len()));
+    }
+    for (index, (point_a, point_b)) in (0..).zip(a.iter().zip(b)) {
+        report.points_compared += 1;
+        let fields = point_differences(point_a, point_b, header_a, header_b, mode)?;
+        report.add(index, fields);
+    }
+    Ok(report)
+}
+
+fn decode(record: &[u8], format: &Format, transforms: &Vector<Transform>) -> Result<Point> {
+    raw::Point::from_bytes(record, format).map(|raw_point| Point::new(raw_point, transforms))
+}
+
+pub(crate) fn header_differences(
+    a: &Header,
+    b: &Header,
+    mode: CompareMode,
+) -> Vec<FieldDifference> {
+    let mut differences = Vec::new();
+    let uncompressed = |format: &Format| Format {
+        is_compressed: false,
+        ..*format
+    };
+    if uncompressed(a.point_format()) != uncompressed(b.point_format()) {
+        differences.push(FieldDifference::new(
+            "point_format",
+            a.point_format(),
+            b.point_format(),
+        ));
+    }
+    if a.number_of_points() != b.number_of_points() {
+        differences.push(FieldDifference::new(
+            "number_of_points",
+            a.number_of_points(),
+            b.number_of_points(),
+        ));
+    }
+    let by_return = |header: &Header| {
+        (1..=15)
+            .map(|n| header.number_of_points_by_return(n).unwrap_or(0))
+            .collect::<Vec<_>>()
+    };
+    if by_return(a) != by_return(b) {
+        differences.push(FieldDifference::new(
+            "number_of_points_by_return",
+            by_return(a),
+            by_return(b),
+        ));
+    }
+    if let CompareMode::Approx(tolerance) = mode {
+        let (a, b) = (a.bounds(), b.bounds());
+        let corners = [
+            (a.min.x, b.min.x),
+            (a.min.y, b.min.y),
+            (a.min.z, b.min.z),
+            (a.max.x, b.max.x),
+            (a.max.y, b.max.y),
+            (a.max.z, b.max.z),
+        ];
+        if corners.iter().any(|(a, b)| (a - b).abs() > tolerance) {
+            differences.push(FieldDifference::new("bounds", a, b));
+        }
+    } else {
+        if a.effective_transforms() != b.effective_transforms() {
+            differences.push(FieldDifference::new(
+                "transforms",
+                a.effective_transforms(),
+                b.effective_transforms(),
+            ));
+        }
+        if a.bounds() != b.bounds() {
+            differences.push(FieldDifference::new("bounds", a.bounds(), b.bounds()));
+        }
+    }
+    if a.gps_time_type() != b.gps_time_type() {
+        differences.push(FieldDifference::new(
+            "gps_time_type",
+            a.gps_time_type(),
+            b.gps_time_type(),
+        ));
+    }
+    differences
+}
+
+fn point_differences(
+    a: &Point,
+    b: &Point,
+    header_a: &Header,
+    header_b: &Header,
+    mode: CompareMode,
+) -> Result<Vec<FieldDifference>> {
+    let mut differences = Vec::new();
+    let (transforms_a, transforms_b) = (
+        header_a.effective_transforms(),
+        header_b.effective_transforms(),
+    );
+    for (field, a, b, transform_a, transform_b) in [
+        ("x", a.x, b.x, transforms_a.x, transforms_b.x),
+        ("y", a.y, b.y, transforms_a.y, transforms_b.y),
+        ("z", a.z, b.z, transforms_a.z, transforms_b.z),
+    ] {
+        if let CompareMode::Approx(tolerance) = mode {
+            if (a - b).abs() > tolerance {
+                differences.push(FieldDifference::new(field, a, b));
+            }
+        } else {
+            let (a, b) = (transform_a.inverse(a)?, transform_b.inverse(b)?);
+            if a != b {
+                differences.push(FieldDifference::new(field, a, b));
+            }
+        }
+    }
+    macro_rules! compare {
+        ($($field:ident),*) => {
+            $(
+                if a.$field != b.$field {
+                    differences.push(FieldDifference::new(stringify!($field), &a.$field, &b.$field));
+                }
+            )*
+        };
+    }
+    compare!(
+        intensity,
+        return_number,
+        number_of_returns,
+        scan_direction,
+        is_edge_of_flight_line,
+        classification,
+        is_synthetic,
+        is_key_point,
+        is_withheld,
+        is_overlap,
+        scanner_channel,
+        scan_angle,
+        user_data,
+        point_source_id,
+        color,
+        waveform,
+        nir,
+        extra_bytes
+    );
+    // Compare the bits, so equal NaNs are equal.
+    if a.gps_time.map(f64::to_bits) != b.gps_time.map(f64::to_bits) {
+        differences.push(FieldDifference::new("gps_time", a.gps_time, b.gps_time));
+    }
+    Ok(differences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, synthetic::fixture, Builder};
+
+    fn points() -> Vec<Point> {
+        (0..10)
+            .map(|i| Point {
+                x: f64::from(i),
+                y: 2. * f64::from(i),
+                z: 0.5,
+                intensity: i as u16,
+                return_number: 1,
+                number_of_returns: 1,
+                // The legacy overlap class reads as unclassified.
+                classification: crate::point::Classification::Unclassified,
+                gps_time: Some(f64::from(i)),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn write(points: &[Point], compress: bool) -> Vec<u8> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        builder.point_format.is_compressed = compress;
+        builder.gps_time_type = crate::GpsTimeType::Standard;
+        fixture::write(builder, points)
+    }
+
+    fn compare(a: Vec<u8>, b: Vec<u8>, mode: CompareMode) -> ComparisonReport {
+        verify_equal(&mut fixture::reader(a), &mut fixture::reader(b), mode).unwrap()
+    }
+
+    /// Changes a point's class to the legacy overlap class.
+    fn use_legacy_overlap_class(bytes: &mut [u8], index: usize) {
+        let start = u32::from_le_bytes(bytes[96..100].try_into().unwrap()) as usize;
+        bytes[start + 30 * index + 16] = raw::point::OVERLAP_CLASSIFICATION_CODE;
+    }
+
+    #[test]
+    fn same() {
+        for mode in [
+            CompareMode::RawBytes,
+            CompareMode::Quantized,
+            CompareMode::Approx(0.),
+        ] {
+            let report = compare(write(&points(), false), write(&points(), false), mode);
+            assert!(report.is_equal(), "{mode:?}: {report:?}");
+            assert_eq!(10, report.points_compared);
+        }
+    }
+
+    #[test]
+    fn legacy_overlap_class_is_only_a_raw_difference() {
+        let a = write(&points(), false);
+        let mut b = a.clone();
+        use_legacy_overlap_class(&mut b, 3);
+
+        let report = compare(a.clone(), b.clone(), CompareMode::RawBytes);
+        assert_eq!(1, report.differing_points);
+        assert_eq!(3, report.point_differences[0].index);
+        assert_eq!("record", report.point_differences[0].fields[0].field);
+
+        assert!(compare(a, b, CompareMode::Quantized).is_equal());
+    }
+
+    #[test]
+    fn one_coordinate_tick() {
+        let mut points_b = points();
+        points_b[7].x += 0.001;
+        let (a, b) = (write(&points(), false), write(&points_b, false));
+
+        for mode in [CompareMode::RawBytes, CompareMode::Quantized] {
+            let report = compare(a.clone(), b.clone(), mode);
+            assert_eq!(1, report.differing_points, "{mode:?}");
+            let difference = &report.point_differences[0];
+            assert_eq!(7, difference.index);
+            assert_eq!(1, difference.fields.len());
+            assert_eq!("x", difference.fields[0].field);
+            assert_eq!(
+                ("7000", "7001"),
+                (&*difference.fields[0].a, &*difference.fields[0].b)
+            );
+        }
+
+        let report = compare(a.clone(), b.clone(), CompareMode::Approx(0.01));
+        assert!(report.point_differences.is_empty());
+        let report = compare(a, b, CompareMode::Approx(0.0001));
+        assert_eq!(1, report.differing_points);
+    }
+
+    #[test]
+    fn header_differences() {
+        let report = compare(
+            write(&points(), false),
+            write(&points()[..9], false),
+            CompareMode::Quantized,
+        );
+        assert_eq!(9, report.points_compared);
+        assert_eq!(0, report.differing_points);
+        assert!(!report.is_equal());
+        let fields: Vec<_> = report.header_differences.iter().map(|d| d.field).collect();
+        assert_eq!(
+            vec!["number_of_points", "number_of_points_by_return", "bounds"],
+            fields
+        );
+        assert_eq!("10", report.header_differences[0].a);
+    }
+
+    #[test]
+    fn raw_bytes_requires_the_same_layout() {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(1).unwrap();
+        let point = Point {
+            gps_time: Some(0.),
+            ..Default::default()
+        };
+        let mut a = fixture::reader(fixture::write(builder, [point]));
+        let mut b = fixture::reader(write(&points(), false));
+        assert!(matches!(
+            verify_equal(&mut a, &mut b, CompareMode::RawBytes).unwrap_err(),
+            Error::IncompatibleLayout { index: 1, .. }
+        ));
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn las_and_laz() {
+        let report = compare(
+            write(&points(), false),
+            write(&points(), true),
+            CompareMode::RawBytes,
+        );
+        assert!(report.is_equal(), "{report:?}");
+    }
+}
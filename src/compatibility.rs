@@ -0,0 +1,430 @@
+//! Laszip's las 1.4 compatibility mode, for compressed points in the extended point formats.
+//!
+//! Readers that only know the legacy point formats can't decompress formats six through ten, so
+//! compatibility mode stores them as formats one, three, four, or five instead. Whatever doesn't
+//! fit in the legacy fields, e.g. the high return numbers, the classes above 31, the scanner
+//! channel, and the near infrared, goes into extra bytes attributes at the end of each point, and
+//! a "lascompatible" vlr holds the extended counts of the header.
+
+use crate::{
+    point::{Classification, ExtraBytesDescriptor, ExtraBytesType, Format},
+    raw::point::ScanAngle,
+    writer::verify_point,
+    Header, Point, Result, Vlr,
+};
+#[cfg(feature = "laz")]
+use {
+    crate::{Builder, Version},
+    std::collections::HashMap,
+};
+
+/// The user id of the compatibility vlr.
+const USER_ID: &str = "lascompatible";
+
+/// The record id of the compatibility vlr.
+const RECORD_ID: u16 = 22204;
+
+/// The version of compatibility mode that's written, and the only one that's read.
+const COMPATIBLE_VERSION: u16 = 3;
+
+/// The length of the compatibility vlr's data.
+const RECORD_LEN: usize = 2 + 2 + 4 + 8 + 8 + 4 + 8 + 15 * 8;
+
+/// The description of every compatibility attribute.
+const DESCRIPTION: &str = "additional attributes";
+
+const SCAN_ANGLE: &str = "LAS 1.4 scan angle";
+const EXTENDED_RETURNS: &str = "LAS 1.4 extended returns";
+const CLASSIFICATION: &str = "LAS 1.4 classification";
+const FLAGS_AND_CHANNEL: &str = "LAS 1.4 flags and channel";
+const NIR_BAND: &str = "LAS 1.4 NIR band";
+
+/// The most returns that a legacy point is given.
+///
+/// Laszip uses seven, the most that three bits can hold, but writers refuse return numbers above
+/// five in a las 1.2 or 1.3 file, since their header can't count them.
+const LEGACY_RETURNS: u8 = 5;
+
+/// The extended point format and return counts of a file that's written in compatibility mode.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Extended {
+    format: Format,
+    number_of_points_by_return: [u64; 15],
+}
+
+/// Where the compatibility attributes are in the extra bytes of a stored point.
+#[cfg(feature = "laz")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Layout {
+    /// The number of extra bytes before the compatibility attributes, which are the extended
+    /// point's own.
+    extra_bytes: usize,
+    scan_angle: usize,
+    extended_returns: usize,
+    classification: usize,
+    flags_and_channel: usize,
+    nir: Option<usize>,
+}
+
+/// The data of the compatibility vlr.
+#[derive(Clone, Copy, Debug, Default)]
+struct Record {
+    start_of_waveform_data_packet_record: u64,
+    number_of_points: u64,
+    number_of_points_by_return: [u64; 15],
+}
+
+impl Extended {
+    /// Turns an extended, compressed header into the header that's stored.
+    ///
+    /// The point format becomes the legacy format with the same fields, the compatibility
+    /// attributes are declared after the existing extra bytes, and the version is lowered to the
+    /// lowest one that can hold the legacy format, i.e. las 1.2, or las 1.3 for waveforms.
+    pub(crate) fn new(header: Header) -> Result<(Extended, Header)> {
+        let format = *header.point_format();
+        let mut builder = header.into_builder();
+        builder.point_format = Format {
+            is_extended: false,
+            has_nir: false,
+            ..format
+        };
+        for descriptor in descriptors(format.has_nir) {
+            builder.add_extra_attribute(descriptor)?;
+        }
+        builder.has_wkt_crs = false;
+        builder.vlrs.retain(|vlr| !is_compatibility_vlr(vlr));
+        builder.vlrs.push(Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: RECORD_ID,
+            description: "LAS 1.4 compatibility mode".to_string(),
+            data: Record::default().encode(),
+            ..Default::default()
+        });
+        builder.version = builder.required_version();
+        let extended = Extended {
+            format,
+            number_of_points_by_return: [0; 15],
+        };
+        Ok((extended, builder.into_header()?))
+    }
+
+    /// Checks a point against the extended format, and returns the legacy point that's stored.
+    pub(crate) fn distill(&self, point: &Point) -> Result<Point> {
+        verify_point(point, &self.format)?;
+        let _ = point.flags()?;
+        let (return_number, number_of_returns) =
+            legacy_returns(point.return_number, point.number_of_returns);
+        let class = u8::from(point.classification);
+        let (classification, extended_class) = if class <= 31 {
+            (point.classification, 0)
+        } else {
+            (Classification::CreatedNeverClassified, class)
+        };
+        let scan_angle = i16::from(ScanAngle::from(point.scan_angle));
+        let rank = i8::from(ScanAngle::Scaled(scan_angle));
+        let remainder = scan_angle - i16::from(ScanAngle::Rank(rank));
+        let mut extra_bytes = point.extra_bytes.to_vec();
+        extra_bytes.extend(remainder.to_le_bytes());
+        extra_bytes.push(
+            ((point.return_number - return_number) << 4)
+                | (point.number_of_returns - number_of_returns),
+        );
+        extra_bytes.push(extended_class);
+        extra_bytes.push((point.scanner_channel << 1) | u8::from(point.is_overlap));
+        if let Some(nir) = point.nir {
+            extra_bytes.extend(nir.to_le_bytes());
+        }
+        Ok(Point {
+            return_number,
+            number_of_returns,
+            classification,
+            is_overlap: false,
+            original_flags: None,
+            scanner_channel: 0,
+            scan_angle: f32::from(rank),
+            nir: None,
+            extra_bytes: extra_bytes.into(),
+            ..point.clone()
+        })
+    }
+
+    /// Counts a point that was written with this extended return number.
+    pub(crate) fn add(&mut self, return_number: u8) {
+        if let Some(count) = self
+            .number_of_points_by_return
+            .get_mut(usize::from(return_number).wrapping_sub(1))
+        {
+            *count += 1;
+        }
+    }
+
+    /// Counts points that were written with these extended return counts.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn add_all(&mut self, number_of_points_by_return: &[u64; 15]) {
+        for (count, n) in self
+            .number_of_points_by_return
+            .iter_mut()
+            .zip(number_of_points_by_return)
+        {
+            *count += n;
+        }
+    }
+
+    /// Returns the index of the compatibility vlr in this stored header, and its finished data.
+    pub(crate) fn finish(
+        &self,
+        header: &Header,
+        start_of_waveform_data_packet_record: Option<u64>,
+    ) -> Option<(usize, Vec<u8>)> {
+        let index = header.vlrs().iter().position(is_compatibility_vlr)?;
+        let record = Record {
+            start_of_waveform_data_packet_record: start_of_waveform_data_packet_record
+                .or(header.start_of_waveform_data_packet_record())
+                .unwrap_or(0),
+            number_of_points: header.number_of_points(),
+            number_of_points_by_return: self.number_of_points_by_return,
+        };
+        Some((index, record.encode()))
+    }
+}
+
+#[cfg(feature = "laz")]
+impl Layout {
+    /// Returns the header of the extended points, and where their attributes are, if this header
+    /// was written in compatibility mode.
+    ///
+    /// Headers without a compatibility vlr, or whose attributes aren't all at the end of the extra
+    /// bytes, aren't restored.
+    pub(crate) fn restore(header: &Header) -> Result<Option<(Header, Layout)>> {
+        let format = *header.point_format();
+        if format.is_extended || !format.has_gps_time {
+            return Ok(None);
+        }
+        let Some(record) = header
+            .vlrs()
+            .iter()
+            .find(|vlr| is_compatibility_vlr(vlr))
+            .and_then(|vlr| Record::decode(&vlr.data))
+        else {
+            return Ok(None);
+        };
+        let schema = header.extra_bytes_schema()?;
+        let offset = |name| schema.get(name).map(|(offset, _)| offset);
+        let (Some(scan_angle), Some(extended_returns), Some(classification), Some(flags)) = (
+            offset(SCAN_ANGLE),
+            offset(EXTENDED_RETURNS),
+            offset(CLASSIFICATION),
+            offset(FLAGS_AND_CHANNEL),
+        ) else {
+            return Ok(None);
+        };
+        let layout = Layout {
+            extra_bytes: scan_angle,
+            scan_angle,
+            extended_returns,
+            classification,
+            flags_and_channel: flags,
+            nir: offset(NIR_BAND),
+        };
+        let len: usize = descriptors(layout.nir.is_some())
+            .iter()
+            .map(ExtraBytesDescriptor::len)
+            .sum();
+        if scan_angle + len != usize::from(format.extra_bytes) {
+            log::warn!("the las 1.4 compatibility attributes aren't at the end of the extra bytes");
+            return Ok(None);
+        }
+
+        let names = [
+            SCAN_ANGLE,
+            EXTENDED_RETURNS,
+            CLASSIFICATION,
+            FLAGS_AND_CHANNEL,
+            NIR_BAND,
+        ];
+        let mut builder = Builder::from(header.clone());
+        builder.point_format = Format {
+            is_extended: true,
+            has_nir: layout.nir.is_some(),
+            extra_bytes: u16::try_from(layout.extra_bytes)?,
+            ..format
+        };
+        let mut vlrs = Vec::with_capacity(builder.vlrs.len());
+        for vlr in std::mem::take(&mut builder.vlrs) {
+            if is_compatibility_vlr(&vlr) {
+                continue;
+            }
+            match ExtraBytesDescriptor::from_vlr(&vlr) {
+                Some(descriptors) => {
+                    let descriptors: Vec<_> = descriptors?
+                        .into_iter()
+                        .filter(|descriptor| !names.contains(&descriptor.name.as_str()))
+                        .collect();
+                    if !descriptors.is_empty() {
+                        vlrs.push(ExtraBytesDescriptor::to_vlr(&descriptors)?);
+                    }
+                }
+                None => vlrs.push(vlr),
+            }
+        }
+        builder.vlrs = vlrs;
+        builder.version = Version::new(1, 4);
+        builder.start_of_waveform_data_packet_record =
+            match record.start_of_waveform_data_packet_record {
+                0 => builder.start_of_waveform_data_packet_record,
+                start => Some(start),
+            };
+        builder.set_number_of_points_by_return(
+            (1..)
+                .zip(record.number_of_points_by_return)
+                .filter(|&(_, count)| count > 0)
+                .collect::<HashMap<u8, u64>>(),
+        );
+        Ok(Some((builder.into_header()?, layout)))
+    }
+
+    /// Turns a stored legacy point back into its extended point.
+    pub(crate) fn restore_point(&self, point: &mut Point) -> Result<()> {
+        let bytes = &point.extra_bytes;
+        let remainder = i16::from_le_bytes([bytes[self.scan_angle], bytes[self.scan_angle + 1]]);
+        let extended_returns = bytes[self.extended_returns];
+        let class = bytes[self.classification];
+        let flags = bytes[self.flags_and_channel];
+        let nir = self
+            .nir
+            .map(|offset| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]));
+
+        let rank = i8::from(ScanAngle::from(point.scan_angle));
+        let scan_angle = i16::from(ScanAngle::Rank(rank)).saturating_add(remainder);
+        point.scan_angle = f32::from(ScanAngle::Scaled(scan_angle));
+        point.return_number += extended_returns >> 4;
+        point.number_of_returns += extended_returns & 0b1111;
+        if class > 0 {
+            point.classification = Classification::new(class)?;
+        }
+        point.is_overlap = flags & 1 == 1;
+        point.scanner_channel = (flags >> 1) & 0b11;
+        point.original_flags = None;
+        point.nir = nir;
+        point.extra_bytes = point.extra_bytes[..self.extra_bytes].into();
+        Ok(())
+    }
+}
+
+impl Record {
+    fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(RECORD_LEN);
+        data.extend(0u16.to_le_bytes());
+        data.extend(COMPATIBLE_VERSION.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(self.start_of_waveform_data_packet_record.to_le_bytes());
+        // The evlrs are never moved, since a las 1.2 or 1.3 file can't have any.
+        data.extend(0u64.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(self.number_of_points.to_le_bytes());
+        for count in self.number_of_points_by_return {
+            data.extend(count.to_le_bytes());
+        }
+        data
+    }
+
+    #[cfg(feature = "laz")]
+    fn decode(data: &[u8]) -> Option<Record> {
+        if data.len() < RECORD_LEN || data[2..4] != COMPATIBLE_VERSION.to_le_bytes() {
+            return None;
+        }
+        let u64_at = |offset: usize| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&data[offset..offset + 8]);
+            u64::from_le_bytes(bytes)
+        };
+        let mut number_of_points_by_return = [0; 15];
+        for (i, count) in number_of_points_by_return.iter_mut().enumerate() {
+            *count = u64_at(36 + 8 * i);
+        }
+        Some(Record {
+            start_of_waveform_data_packet_record: u64_at(8),
+            number_of_points: u64_at(28),
+            number_of_points_by_return,
+        })
+    }
+}
+
+/// Returns true if this is the compatibility vlr.
+fn is_compatibility_vlr(vlr: &Vlr) -> bool {
+    vlr.user_id == USER_ID && vlr.record_id == RECORD_ID
+}
+
+/// Returns the compatibility attributes, in the order they're stored.
+fn descriptors(has_nir: bool) -> Vec<ExtraBytesDescriptor> {
+    let descriptor = |name, data_type| ExtraBytesDescriptor {
+        description: DESCRIPTION.to_string(),
+        ..ExtraBytesDescriptor::new(name, data_type)
+    };
+    let mut descriptors = vec![
+        ExtraBytesDescriptor {
+            scale: Some(0.006),
+            ..descriptor(SCAN_ANGLE, ExtraBytesType::I16)
+        },
+        descriptor(EXTENDED_RETURNS, ExtraBytesType::U8),
+        descriptor(CLASSIFICATION, ExtraBytesType::U8),
+        descriptor(FLAGS_AND_CHANNEL, ExtraBytesType::U8),
+    ];
+    if has_nir {
+        descriptors.push(descriptor(NIR_BAND, ExtraBytesType::U16));
+    }
+    descriptors
+}
+
+/// Returns the legacy return number and number of returns for an extended pair.
+///
+/// The last returns of a pulse with too many keep their place at the end, like laszip does.
+fn legacy_returns(return_number: u8, number_of_returns: u8) -> (u8, u8) {
+    if number_of_returns <= LEGACY_RETURNS {
+        (return_number.min(LEGACY_RETURNS), number_of_returns)
+    } else if return_number < LEGACY_RETURNS - 2 {
+        (return_number, LEGACY_RETURNS)
+    } else {
+        let return_number = match number_of_returns.saturating_sub(return_number) {
+            0 => LEGACY_RETURNS,
+            difference if difference >= 3 => LEGACY_RETURNS - 3,
+            difference => LEGACY_RETURNS - difference,
+        };
+        (return_number, LEGACY_RETURNS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_returns_keep_the_last_returns() {
+        assert_eq!((3, 3), legacy_returns(3, 3));
+        assert_eq!((1, 5), legacy_returns(1, 8));
+        assert_eq!((2, 5), legacy_returns(2, 8));
+        assert_eq!((2, 5), legacy_returns(4, 8));
+        assert_eq!((3, 5), legacy_returns(6, 8));
+        assert_eq!((4, 5), legacy_returns(7, 8));
+        assert_eq!((5, 5), legacy_returns(8, 8));
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn record_roundtrip() {
+        let record = Record {
+            start_of_waveform_data_packet_record: 42,
+            number_of_points: 3,
+            number_of_points_by_return: [1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        };
+        let data = record.encode();
+        assert_eq!(RECORD_LEN, data.len());
+        let decoded = Record::decode(&data).unwrap();
+        assert_eq!(42, decoded.start_of_waveform_data_packet_record);
+        assert_eq!(3, decoded.number_of_points);
+        assert_eq!(
+            record.number_of_points_by_return,
+            decoded.number_of_points_by_return
+        );
+    }
+}
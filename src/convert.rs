@@ -0,0 +1,254 @@
+//! Convert points and files from one point format and las version to another.
+//!
+//! [convert_point] changes a single point, and a [Converter] rewrites a whole file's header and
+//! points:
+//!
+//! ```
+//! use las::{convert::Converter, point::Format, Reader, Version};
+//! use std::io::Cursor;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let converter = Converter::new(Version::new(1, 4), Format::new(6).unwrap());
+//! let cursor = converter.convert(&mut reader, Cursor::new(Vec::new())).unwrap();
+//! let mut reader = Reader::new(cursor).unwrap();
+//! assert_eq!(Format::new(6).unwrap(), *reader.header().point_format());
+//! assert_eq!(106, reader.points().count());
+//! ```
+
+use crate::{
+    header::Adjustment,
+    point::{Classification, ExtraBytes, Format},
+    Builder, Header, Point, Reader, Result, Version, Writer,
+};
+use std::io::{Read, Seek, Write};
+
+/// The largest return number, and number of returns, that a legacy point format can hold.
+const LEGACY_MAX_RETURN_NUMBER: u8 = 7;
+
+/// The largest classification that a legacy point format can hold.
+const LEGACY_MAX_CLASSIFICATION: u8 = 31;
+
+/// Converts a point from one point format to another.
+///
+/// Optional attributes that `to` doesn't have are dropped, and ones that `from` doesn't have are
+/// added with their default values. The extra bytes are cut or zero-padded to `to`'s length.
+///
+/// Converting to a legacy format loses what legacy points can't hold: return numbers and numbers
+/// of returns above seven become seven, classifications above 31 become
+/// [Classification::Unclassified], and the scanner channel becomes zero. Overlap points keep
+/// [Point::is_overlap], which legacy formats write as the overlap classification.
+///
+/// # Examples
+///
+/// ```
+/// use las::{convert, point::Format, Point};
+///
+/// let point = Point { return_number: 9, gps_time: Some(1.), ..Default::default() };
+/// let point = convert::convert_point(&point, Format::new(6).unwrap(), Format::new(2).unwrap());
+/// assert_eq!(7, point.return_number);
+/// assert_eq!(None, point.gps_time);
+/// assert!(point.color.is_some());
+/// ```
+pub fn convert_point(point: &Point, from: Format, to: Format) -> Point {
+    let mut point = Point {
+        gps_time: field(point.gps_time, from.has_gps_time, to.has_gps_time),
+        color: field(point.color, from.has_color, to.has_color),
+        waveform: field(point.waveform, from.has_waveform, to.has_waveform),
+        nir: field(point.nir, from.has_nir, to.has_nir),
+        extra_bytes: resize(&point.extra_bytes, to.extra_bytes),
+        ..point.clone()
+    };
+    if from.is_extended != to.is_extended {
+        point.original_flags = None;
+    }
+    if !to.is_extended {
+        point.return_number = point.return_number.min(LEGACY_MAX_RETURN_NUMBER);
+        point.number_of_returns = point.number_of_returns.min(LEGACY_MAX_RETURN_NUMBER);
+        if u8::from(point.classification) > LEGACY_MAX_CLASSIFICATION {
+            point.classification = Classification::Unclassified;
+        }
+        point.scanner_channel = 0;
+    }
+    point
+}
+
+fn field<T: Default>(value: Option<T>, from: bool, to: bool) -> Option<T> {
+    to.then(|| value.filter(|_| from).unwrap_or_default())
+}
+
+fn resize(extra_bytes: &ExtraBytes, len: u16) -> ExtraBytes {
+    let mut resized = ExtraBytes::zeroed(usize::from(len));
+    let n = resized.len().min(extra_bytes.len());
+    resized[..n].copy_from_slice(&extra_bytes[..n]);
+    resized
+}
+
+/// Converts las files to a point format and version.
+///
+/// The header is rewritten with [Builder::normalize], so the version is raised if it can't hold
+/// the point format, and extended point formats get standard gps time. Gps times aren't changed,
+/// so converting a file with gps week time to an extended point format leaves its times as they
+/// were.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Converter {
+    version: Version,
+    format: Format,
+}
+
+impl Converter {
+    /// Creates a converter to this version and point format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{convert::Converter, point::Format, Version};
+    /// let converter = Converter::new(Version::new(1, 2), Format::new(3).unwrap());
+    /// ```
+    pub fn new(version: Version, format: Format) -> Converter {
+        Converter { version, format }
+    }
+
+    /// Returns the empty header that converted points are written with, and how it was changed
+    /// beyond the version and point format.
+    ///
+    /// The point counts and bounds are cleared, since they're filled in as the points are written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{convert::Converter, header::Adjustment, point::Format, Reader, Version};
+    ///
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let converter = Converter::new(Version::new(1, 2), Format::new(6).unwrap());
+    /// let (header, adjustments) = converter.header(reader.header()).unwrap();
+    /// assert_eq!(Version::new(1, 4), header.version());
+    /// assert!(adjustments.contains(&Adjustment::GpsTimeType));
+    /// ```
+    pub fn header(&self, header: &Header) -> Result<(Header, Vec<Adjustment>)> {
+        let mut builder = Builder::from(header.to_empty());
+        builder.version = self.version;
+        builder.point_format = self.format;
+        let adjustments = builder.normalize();
+        builder.into_header().map(|header| (header, adjustments))
+    }
+
+    /// Reads every point, converts it, and writes it with the converted header.
+    ///
+    /// The reader is read from the start, and is left at the end. Returns the destination once
+    /// the writer is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{convert::Converter, point::Format, Reader, Version};
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let converter = Converter::new(Version::new(1, 2), Format::new(0).unwrap());
+    /// let cursor = converter.convert(&mut reader, Cursor::new(Vec::new())).unwrap();
+    /// let mut reader = Reader::new(cursor).unwrap();
+    /// assert_eq!(None, reader.read_point().unwrap().unwrap().gps_time);
+    /// ```
+    pub fn convert<R, W>(&self, reader: &mut Reader<R>, write: W) -> Result<W>
+    where
+        R: Read + Seek + Send,
+        W: Write + Seek + Send,
+    {
+        let from = *reader.header().point_format();
+        let (header, _) = self.header(reader.header())?;
+        let to = *header.point_format();
+        let mut writer = Writer::new(write, header)?;
+        let mut point = Point::default();
+        reader.seek(0)?;
+        while reader.read_point_into(&mut point)? {
+            writer.write_point(convert_point(&point, from, to))?;
+        }
+        writer.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, GpsTimeType};
+    use std::io::Cursor;
+
+    fn format(n: u8) -> Format {
+        Format::new(n).unwrap()
+    }
+
+    #[test]
+    fn optional_attributes() {
+        let point = Point {
+            gps_time: Some(1.),
+            color: Some(Color::new(1, 2, 3)),
+            ..Default::default()
+        };
+        let converted = convert_point(&point, format(3), format(8));
+        assert_eq!(Some(1.), converted.gps_time);
+        assert_eq!(Some(Color::new(1, 2, 3)), converted.color);
+        assert_eq!(Some(0), converted.nir);
+        assert!(converted.matches(&format(8)));
+
+        let converted = convert_point(&point, format(0), format(1));
+        assert_eq!(Some(0.), converted.gps_time);
+    }
+
+    #[test]
+    fn extra_bytes() {
+        let point = Point {
+            extra_bytes: vec![1, 2, 3].into(),
+            ..Default::default()
+        };
+        let mut to = format(0);
+        to.extra_bytes = 2;
+        assert_eq!(convert_point(&point, format(0), to).extra_bytes, vec![1, 2]);
+        to.extra_bytes = 4;
+        assert_eq!(
+            convert_point(&point, format(0), to).extra_bytes,
+            vec![1, 2, 3, 0]
+        );
+    }
+
+    #[test]
+    fn legacy() {
+        let point = Point {
+            return_number: 12,
+            number_of_returns: 15,
+            classification: Classification::UserDefinable(200),
+            scanner_channel: 2,
+            is_overlap: true,
+            gps_time: Some(1.),
+            ..Default::default()
+        };
+        let converted = convert_point(&point, format(6), format(1));
+        assert_eq!(7, converted.return_number);
+        assert_eq!(7, converted.number_of_returns);
+        assert_eq!(Classification::Unclassified, converted.classification);
+        assert_eq!(0, converted.scanner_channel);
+        assert!(converted.is_overlap);
+        assert!(converted.flags().unwrap().to_two_bytes().is_ok());
+    }
+
+    #[test]
+    fn convert() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let points: Vec<Point> = reader.points().map(|point| point.unwrap()).collect();
+        let converter = Converter::new(Version::new(1, 2), format(7));
+        let cursor = converter
+            .convert(&mut reader, Cursor::new(Vec::new()))
+            .unwrap();
+        let mut reader = Reader::new(cursor).unwrap();
+        let header = reader.header();
+        assert_eq!(Version::new(1, 4), header.version());
+        assert_eq!(format(7), *header.point_format());
+        assert_eq!(GpsTimeType::Standard, header.gps_time_type());
+        assert_eq!(106, header.number_of_points());
+        for (point, converted) in points.iter().zip(reader.points()) {
+            let converted = converted.unwrap();
+            assert_eq!(point.x, converted.x);
+            assert_eq!(point.gps_time, converted.gps_time);
+            assert_eq!(Some(Color::default()), converted.color);
+        }
+    }
+}
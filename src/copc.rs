@@ -1,11 +1,20 @@
 //! [COPC](https://copc.io/) header data
 
-use crate::{raw, Point};
+use crate::{raw, reader::contains, writer::verify_point, Bounds, Builder, Point, Vector, Version};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use laz::record::{LayeredPointRecordDecompressor, RecordDecompressor};
+use laz::{
+    laszip::{ChunkTable, ChunkTableEntry},
+    record::{
+        LayeredPointRecordCompressor, LayeredPointRecordDecompressor, RecordCompressor,
+        RecordDecompressor,
+    },
+};
 use std::{
-    collections::HashMap,
-    io::{Cursor, Read, Seek, SeekFrom, Write},
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::File,
+    io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
+    ops::Range,
+    path::Path,
 };
 
 /// The user id of the LasZip VLR header.
@@ -14,7 +23,7 @@ pub const USER_ID: &str = "copc";
 /// The description of the LasZip VLR header.
 pub const DESCRIPTION: &str = "https://copc.io";
 
-use crate::{Error, Header, Result, Vlr};
+use crate::{Error, Header, Result, Vlr, VlrRecord};
 
 /// The COPC Info Vlr.
 ///
@@ -25,7 +34,7 @@ use crate::{Error, Header, Result, Vlr};
 ///   from the beginning of the file).
 /// - The info VLR is 160 bytes described by the following structure. reserved
 ///   elements MUST be set to 0.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CopcInfoVlr {
     /// Actual (unscaled) X coordinate of center of octree
     pub center_x: f64,
@@ -103,7 +112,23 @@ impl TryFrom<&Vlr> for CopcInfoVlr {
     type Error = Error;
 
     fn try_from(value: &Vlr) -> Result<Self> {
-        Self::read_from::<&[u8]>(value.data.as_ref())
+        Self::decode(&value.data)
+    }
+}
+
+impl VlrRecord for CopcInfoVlr {
+    const USER_ID: &'static str = USER_ID;
+    const RECORD_ID: u16 = CopcInfoVlr::RECORD_ID;
+    const DESCRIPTION: &'static str = DESCRIPTION;
+
+    fn decode(data: &[u8]) -> Result<CopcInfoVlr> {
+        CopcInfoVlr::read_from(data)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.write_to(&mut data)?;
+        Ok(data)
     }
 }
 
@@ -164,6 +189,61 @@ impl VoxelKey {
         z: 0,
     };
 
+    /// Returns the octree level, zero for the root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::copc::VoxelKey;
+    /// assert_eq!(1, VoxelKey::ROOT.child(3).unwrap().level());
+    /// ```
+    pub fn level(&self) -> i32 {
+        self.l
+    }
+
+    /// Returns the cube that this node covers in an octree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{copc::VoxelKey, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+    /// let info = reader.header().copc_info_vlr().unwrap();
+    /// let bounds = VoxelKey::ROOT.bounds(&info);
+    /// assert_eq!(2. * info.halfsize, bounds.max.x - bounds.min.x);
+    /// ```
+    pub fn bounds(&self, info: &CopcInfoVlr) -> Bounds {
+        let side = 2. * info.halfsize / f64::powi(2., self.l);
+        let min = |center: f64, i: i32| center - info.halfsize + f64::from(i) * side;
+        let min = Vector {
+            x: min(info.center_x, self.x),
+            y: min(info.center_y, self.y),
+            z: min(info.center_z, self.z),
+        };
+        Bounds {
+            min,
+            max: Vector {
+                x: min.x + side,
+                y: min.y + side,
+                z: min.z + side,
+            },
+        }
+    }
+
+    /// Returns the space between this node's points in an octree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{copc::VoxelKey, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+    /// let info = reader.header().copc_info_vlr().unwrap();
+    /// assert_eq!(info.spacing / 2., VoxelKey::ROOT.child(0).unwrap().spacing(&info));
+    /// ```
+    pub fn spacing(&self, info: &CopcInfoVlr) -> f64 {
+        info.spacing / f64::powi(2., self.l)
+    }
+
     /// Read a VoxelKey from Vlr Payload data.
     pub fn read_from<R: Read>(read: &mut R) -> Result<Self> {
         Ok(Self {
@@ -277,6 +357,10 @@ impl Page {
 /// child hierarchy page, octree node data chunk, or an empty octree node. The
 /// size and file offset of each data chunk is provided in the hierarchy
 /// entries, allowing the chunks to be directly read for decoding.
+///
+/// Unlike [CopcInfoVlr], this isn't a [VlrRecord]: the pages are found with the root page's
+/// offset and size from the info vlr, so the data can't be decoded on its own. Use
+/// [CopcHierarchyVlr::read_from_with].
 #[derive(Debug)]
 pub struct CopcHierarchyVlr {
     root: Page,
@@ -414,7 +498,7 @@ impl Vlr {
     /// assert!(&vlr.is_copc_info());
     /// ```
     pub fn is_copc_info(&self) -> bool {
-        self.user_id == USER_ID && self.record_id == CopcInfoVlr::RECORD_ID
+        CopcInfoVlr::matches(self)
     }
 }
 
@@ -501,6 +585,10 @@ impl<R: Read + Seek> CopcEntryReader<'_, R> {
     /// ```
     pub fn new(mut read: R) -> Result<Self> {
         let header = Header::new(read.by_ref())?;
+        Self::with_header(read, header)
+    }
+
+    fn with_header(read: R, header: Header) -> Result<Self> {
         let mut decompressor = LayeredPointRecordDecompressor::new(read);
         decompressor.set_fields_from(header.laz_vlr()?.items())?;
         let buffer = Cursor::new(Vec::new());
@@ -550,6 +638,10 @@ impl<R: Read + Seek> CopcEntryReader<'_, R> {
             .decompressor
             .get_mut()
             .seek(SeekFrom::Start(entry.offset))?;
+        // Each node is its own chunk, so the decompressor starts over.
+        self.decompressor.reset();
+        self.decompressor
+            .set_fields_from(self.header.laz_vlr()?.items())?;
         points.reserve_exact(entry.point_count as usize);
 
         let resize = usize::try_from(
@@ -562,7 +654,7 @@ impl<R: Read + Seek> CopcEntryReader<'_, R> {
 
         for _ in 0..entry.point_count as usize {
             let point = raw::Point::read_from(&mut self.buffer, self.header.point_format())
-                .map(|raw_point| Point::new(raw_point, self.header.transforms()))?;
+                .map(|raw_point| Point::new(raw_point, self.header.effective_transforms()))?;
             points.push(point);
         }
         Ok(entry.point_count as u64)
@@ -589,10 +681,671 @@ impl<R: Read + Seek> CopcEntryReader<'_, R> {
     }
 }
 
+/// Reads a COPC file by octree node, so a query only decompresses the nodes it needs.
+///
+/// The whole hierarchy is read when the reader is created, following every hierarchy page from
+/// the root page in the info vlr.
+///
+/// # Examples
+///
+/// ```
+/// use las::copc::Reader;
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+/// let bounds = *reader.bounds();
+/// let points = reader.query(&bounds, None).collect::<las::Result<Vec<_>>>().unwrap();
+/// assert_eq!(reader.header().number_of_points(), points.len() as u64);
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct Reader<R: Read + Seek> {
+    entry_reader: CopcEntryReader<'static, R>,
+    info: CopcInfoVlr,
+    entries: Vec<Entry>,
+    bounds: Bounds,
+}
+
+/// The points of the nodes that a [Reader::query] selected, inside its bounds.
+#[allow(missing_debug_implementations)]
+pub struct Query<'a, R: Read + Seek> {
+    entry_reader: &'a mut CopcEntryReader<'static, R>,
+    nodes: std::vec::IntoIter<Entry>,
+    bounds: Bounds,
+    points: std::vec::IntoIter<Point>,
+}
+
+impl Reader<BufReader<File>> {
+    /// Opens a COPC file at a path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::copc::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>> {
+        File::open(path)
+            .map_err(Error::from)
+            .and_then(|file| Reader::new(BufReader::new(file)))
+    }
+}
+
+impl<R: Read + Seek> Reader<R> {
+    /// Creates a reader, reading the header and the hierarchy.
+    ///
+    /// Returns [Error::NotCopc] if the file has no copc info vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{copc::Reader, Error};
+    /// use std::{fs::File, io::BufReader};
+    ///
+    /// let file = BufReader::new(File::open("tests/data/autzen.copc.laz").unwrap());
+    /// let reader = Reader::new(file).unwrap();
+    /// let file = BufReader::new(File::open("tests/data/autzen.laz").unwrap());
+    /// assert!(matches!(Reader::new(file), Err(Error::NotCopc)));
+    /// ```
+    pub fn new(mut read: R) -> Result<Reader<R>> {
+        let header = Header::new(read.by_ref())?;
+        let info = header.copc_info_vlr().ok_or(Error::NotCopc)?;
+        let mut entry_reader = CopcEntryReader::with_header(read, header)?;
+        let source = entry_reader.decompressor.get_mut();
+        let mut entries = Vec::new();
+        let mut pages = vec![(info.root_hier_offset, info.root_hier_size)];
+        let mut seen = HashSet::new();
+        while let Some((offset, size)) = pages.pop() {
+            if !seen.insert(offset) {
+                return Err(Error::CyclicCopcHierarchy(offset));
+            }
+            let mut data = vec![0; usize::try_from(size)?];
+            let _ = source.seek(SeekFrom::Start(offset))?;
+            source.read_exact(&mut data)?;
+            for entry in Page::read_from(&data)?.entries {
+                if entry.is_referencing_page() {
+                    pages.push((entry.offset, u64::try_from(entry.byte_size)?));
+                } else {
+                    entries.push(entry);
+                }
+            }
+        }
+        entries.sort_by_key(|entry| (entry.key.l, entry.key.x, entry.key.y, entry.key.z));
+        let bounds = entry_reader.header().bounds();
+        Ok(Reader {
+            entry_reader,
+            info,
+            entries,
+            bounds,
+        })
+    }
+
+    /// Returns the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::copc::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+    /// assert!(reader.header().point_format().is_compressed);
+    /// ```
+    pub fn header(&self) -> &Header {
+        self.entry_reader.header()
+    }
+
+    /// Returns the copc info vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::copc::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+    /// assert!(reader.info().halfsize > 0.);
+    /// ```
+    pub fn info(&self) -> &CopcInfoVlr {
+        &self.info
+    }
+
+    /// Returns the bounds of the points, from the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::copc::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+    /// assert_eq!(reader.header().bounds(), *reader.bounds());
+    /// ```
+    pub fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    /// Returns every hierarchy entry that isn't a reference to another page, by level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::copc::{Reader, VoxelKey};
+    /// let reader = Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+    /// assert_eq!(VoxelKey::ROOT, reader.entries()[0].key);
+    /// ```
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Returns the nodes with points that a [query](Reader::query) with these arguments reads.
+    ///
+    /// A node is selected if its cube intersects the bounds, and, with a resolution, if it's at
+    /// most one level finer than the first level whose [spacing](VoxelKey::spacing) is at or
+    /// below the resolution. So the points that are read are at least as dense as the
+    /// resolution, where the file has them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{copc::Reader, Bounds, Vector};
+    /// let reader = Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+    /// let far = Bounds {
+    ///     min: Vector { x: 0., y: 0., z: 0. },
+    ///     max: Vector { x: 1., y: 1., z: 1. },
+    /// };
+    /// assert!(reader.nodes(&far, None).is_empty());
+    /// ```
+    pub fn nodes(&self, bounds: &Bounds, max_resolution: Option<f64>) -> Vec<Entry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.point_count > 0)
+            .filter(|entry| {
+                max_resolution.is_none_or(|resolution| {
+                    entry.key.l == 0 || 2. * entry.key.spacing(&self.info) > resolution
+                })
+            })
+            .filter(|entry| intersects(&entry.key.bounds(&self.info), bounds))
+            .copied()
+            .collect()
+    }
+
+    /// Returns the points inside the bounds, from the [nodes](Reader::nodes) that are needed for
+    /// the resolution.
+    ///
+    /// Only the selected nodes are decompressed, one at a time as the points are read, coarsest
+    /// level first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::copc::Reader;
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+    /// let mut bounds = *reader.bounds();
+    /// bounds.max.x = (bounds.min.x + bounds.max.x) / 2.;
+    /// for point in reader.query(&bounds, Some(10.)) {
+    ///     assert!(point.unwrap().x <= bounds.max.x);
+    /// }
+    /// ```
+    pub fn query(&mut self, bounds: &Bounds, max_resolution: Option<f64>) -> Query<'_, R> {
+        Query {
+            nodes: self.nodes(bounds, max_resolution).into_iter(),
+            entry_reader: &mut self.entry_reader,
+            bounds: *bounds,
+            points: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for Query<'_, R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        loop {
+            if let Some(point) = self.points.find(|point| contains(&self.bounds, point)) {
+                return Some(Ok(point));
+            }
+            let node = self.nodes.next()?;
+            let mut points = Vec::new();
+            if let Err(err) = self.entry_reader.read_entry_points(&node, &mut points) {
+                self.nodes = Vec::new().into_iter();
+                return Some(Err(err));
+            }
+            self.points = points.into_iter();
+        }
+    }
+}
+
+/// The number of cells along each side of a node's grid, so each node keeps at most one point per
+/// cell and passes the rest down to its children.
+const GRID_SIZE: f64 = 128.;
+
+/// The deepest level that [Writer] makes, which keeps every point that reaches it.
+const MAX_LEVEL: i32 = 20;
+
+/// Writes a COPC file, sorting the points into an octree when it closes.
+///
+/// Every point is kept in memory until the writer closes. Then each node keeps at most one point
+/// per cell of a 128 by 128 by 128 grid over its cube, in the order the points were written, and
+/// passes the rest down to its children. Each node is written as its own laz chunk, and the copc
+/// info vlr and a single hierarchy page evlr are written with them.
+///
+/// The header's point format must be 6, 7, or 8. The writer makes it a compressed las 1.4 header,
+/// without any padding, so the info vlr is the first vlr at the spec's offset.
+///
+/// # Examples
+///
+/// ```
+/// use las::{copc, point::Format, Builder, GpsTimeType, Point};
+/// use std::io::Cursor;
+///
+/// let mut builder = Builder::from((1, 4));
+/// builder.point_format = Format::new(6).unwrap();
+/// builder.gps_time_type = GpsTimeType::Standard;
+/// let mut writer = copc::Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+/// for i in 0..10 {
+///     writer.write_point(Point { x: f64::from(i), gps_time: Some(0.), ..Default::default() }).unwrap();
+/// }
+/// let reader = copc::Reader::new(writer.into_inner().unwrap()).unwrap();
+/// assert_eq!(10, reader.header().number_of_points());
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct Writer<W: Write + Seek> {
+    write: Option<W>,
+    start: u64,
+    header: Header,
+    points: Vec<Point>,
+    closed: bool,
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a COPC file at a path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{copc::Writer, point::Format, Builder, GpsTimeType};
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format = Format::new(6).unwrap();
+    /// builder.gps_time_type = GpsTimeType::Standard;
+    /// let path = std::env::temp_dir().join("las-rs-copc-writer-from-path.copc.laz");
+    /// let writer = Writer::from_path(&path, builder.into_header().unwrap()).unwrap();
+    /// # drop(writer);
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P, header: Header) -> Result<Writer<BufWriter<File>>> {
+        File::create(path)
+            .map_err(Error::from)
+            .and_then(|file| Writer::new(BufWriter::new(file), header))
+    }
+}
+
+impl<W: Write + Seek> Writer<W> {
+    /// Creates a COPC writer.
+    ///
+    /// Returns [Error::InvalidCopcFormat] if the point format isn't 6, 7, or 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{copc::Writer, Builder, Error};
+    /// use std::io::Cursor;
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// assert!(matches!(
+    ///     Writer::new(Cursor::new(Vec::new()), header),
+    ///     Err(Error::InvalidCopcFormat(_))
+    /// ));
+    /// ```
+    pub fn new(mut write: W, header: Header) -> Result<Writer<W>> {
+        let format = *header.point_format();
+        if !(6..=8).contains(&format.to_u8()?) {
+            return Err(Error::InvalidCopcFormat(format));
+        }
+        let mut builder = Builder::from(header.to_empty());
+        builder.version = Version::new(1, 4);
+        builder.point_format.is_compressed = true;
+        builder.padding.clear();
+        builder.vlr_padding.clear();
+        builder.point_padding.clear();
+        let mut header = builder.into_header()?;
+        header.add_laz_vlr_with_chunk_size(Some(u32::MAX))?;
+        let start = write.stream_position()?;
+        Ok(Writer {
+            write: Some(write),
+            start,
+            header,
+            points: Vec::new(),
+            closed: false,
+        })
+    }
+
+    /// Returns the header.
+    ///
+    /// Its point counts and bounds are filled in when the writer closes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{copc::Writer, point::Format, Builder, GpsTimeType};
+    /// use std::io::Cursor;
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format = Format::new(6).unwrap();
+    /// builder.gps_time_type = GpsTimeType::Standard;
+    /// let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    /// assert!(writer.header().point_format().is_compressed);
+    /// ```
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Adds a point, to be written when the writer closes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{copc::Writer, point::Format, Builder, GpsTimeType, Point};
+    /// use std::io::Cursor;
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format = Format::new(6).unwrap();
+    /// builder.gps_time_type = GpsTimeType::Standard;
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    /// writer.write_point(Point { gps_time: Some(0.), ..Default::default() }).unwrap();
+    /// assert!(writer.write_point(Point::default()).is_err());
+    /// ```
+    pub fn write_point(&mut self, point: Point) -> Result<()> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        verify_point(&point, self.header.point_format())?;
+        self.points.push(point);
+        Ok(())
+    }
+
+    /// Sorts the points into an octree and writes the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{copc::Writer, point::Format, Builder, GpsTimeType};
+    /// use std::io::Cursor;
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format = Format::new(6).unwrap();
+    /// builder.gps_time_type = GpsTimeType::Standard;
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    /// writer.close().unwrap();
+    /// assert!(writer.close().is_err());
+    /// ```
+    pub fn close(&mut self) -> Result<()> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.closed = true;
+        let points = std::mem::take(&mut self.points);
+        let write = self.write.as_mut().ok_or(Error::ClosedWriter)?;
+        self.header = write_copc(write, self.start, &self.header, points)?;
+        Ok(())
+    }
+
+    /// Closes this writer and returns its inner `Write`, seeked to the beginning of the las data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{copc::Writer, point::Format, Builder, GpsTimeType};
+    /// use std::io::Cursor;
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format = Format::new(6).unwrap();
+    /// builder.gps_time_type = GpsTimeType::Standard;
+    /// let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    /// let cursor = writer.into_inner().unwrap();
+    /// ```
+    pub fn into_inner(mut self) -> Result<W> {
+        if !self.closed {
+            self.close()?;
+        }
+        let mut write = self.write.take().ok_or(Error::ClosedWriter)?;
+        let _ = write.seek(SeekFrom::Start(self.start))?;
+        Ok(write)
+    }
+}
+
+impl<W: Write + Seek> Drop for Writer<W> {
+    fn drop(&mut self) {
+        // Like the las writer, finishing here is best effort.
+        if !self.closed {
+            let _ = self.close();
+        }
+    }
+}
+
+/// The points of each node, by level and then position.
+type Nodes = BTreeMap<(i32, i32, i32, i32), Vec<Point>>;
+
+/// Sorts the points into nodes, and returns the info vlr that describes the octree.
+fn build_octree(points: Vec<Point>) -> (CopcInfoVlr, Nodes) {
+    let mut bounds = Bounds::default();
+    let mut gps_time = (f64::INFINITY, f64::NEG_INFINITY);
+    for point in &points {
+        bounds.grow(point);
+        if let Some(t) = point.gps_time {
+            gps_time = (gps_time.0.min(t), gps_time.1.max(t));
+        }
+    }
+    let (center, halfsize) = if points.is_empty() {
+        (Vector::default(), 0.)
+    } else {
+        let center = |min: f64, max: f64| (min + max) / 2.;
+        let halfsize = (bounds.max.x - bounds.min.x)
+            .max(bounds.max.y - bounds.min.y)
+            .max(bounds.max.z - bounds.min.z)
+            / 2.;
+        (
+            Vector {
+                x: center(bounds.min.x, bounds.max.x),
+                y: center(bounds.min.y, bounds.max.y),
+                z: center(bounds.min.z, bounds.max.z),
+            },
+            halfsize,
+        )
+    };
+    if points.is_empty() {
+        gps_time = (0., 0.);
+    }
+    let info = CopcInfoVlr {
+        center_x: center.x,
+        center_y: center.y,
+        center_z: center.z,
+        halfsize,
+        spacing: 2. * halfsize / GRID_SIZE,
+        root_hier_offset: 0,
+        root_hier_size: 0,
+        gpstime_minimum: gps_time.0,
+        gpstime_maximum: gps_time.1,
+        reserved: [0; 11],
+    };
+
+    let mut nodes = Nodes::new();
+    let mut cells = HashSet::new();
+    for point in points {
+        let mut key = VoxelKey::ROOT;
+        loop {
+            let node = key.bounds(&info);
+            let cell = |min: f64, max: f64, n: f64| {
+                let side = max - min;
+                if side > 0. {
+                    (((n - min) / side * GRID_SIZE) as i64).clamp(0, GRID_SIZE as i64 - 1)
+                } else {
+                    0
+                }
+            };
+            let cell = (
+                cell(node.min.x, node.max.x, point.x),
+                cell(node.min.y, node.max.y, point.y),
+                cell(node.min.z, node.max.z, point.z),
+            );
+            if key.l == MAX_LEVEL || cells.insert((key, cell)) {
+                break;
+            }
+            let half = |min: f64, max: f64, n: f64| i32::from(n >= (min + max) / 2.);
+            let direction = half(node.min.x, node.max.x, point.x)
+                | (half(node.min.y, node.max.y, point.y) << 1)
+                | (half(node.min.z, node.max.z, point.z) << 2);
+            key = key.child(direction).expect("direction is in 0..8");
+        }
+        nodes
+            .entry((key.l, key.x, key.y, key.z))
+            .or_default()
+            .push(point);
+    }
+    (info, nodes)
+}
+
+/// Writes a COPC file, and returns the header it wrote.
+///
+/// The layout follows [concat_laz](crate::laz::concat_laz): the header and vlrs, the offset to
+/// the chunk table, one chunk per node, the chunk table, and then the evlrs, with the hierarchy
+/// first.
+fn write_copc<W: Write + Seek>(
+    mut write: W,
+    start: u64,
+    header: &Header,
+    points: Vec<Point>,
+) -> Result<Header> {
+    let mut header = header.clone();
+    let laz_vlr = header.laz_vlr()?;
+    let transforms = *header.transforms();
+    let format = *header.point_format();
+    let number_of_points = points.len() as u64;
+    let mut number_of_points_by_return = HashMap::new();
+    let mut bounds = Bounds::default();
+    for point in &points {
+        if point.return_number > 0 {
+            *number_of_points_by_return
+                .entry(point.return_number)
+                .or_insert(0) += 1;
+        }
+        bounds.grow(point);
+    }
+    let (mut info, nodes) = build_octree(points);
+
+    let mut chunks = Vec::with_capacity(nodes.len());
+    let mut chunk_table = ChunkTable::with_capacity(nodes.len());
+    let mut record = Vec::with_capacity(usize::from(format.len()));
+    for ((l, x, y, z), points) in nodes {
+        let mut compressor = LayeredPointRecordCompressor::new(Cursor::new(Vec::new()));
+        compressor.set_fields_from(laz_vlr.items())?;
+        for point in &points {
+            record.clear();
+            point
+                .clone()
+                .into_raw(&transforms)?
+                .write_to(&mut record, &format)?;
+            compressor.compress_next(&record)?;
+        }
+        compressor.done()?;
+        let bytes = compressor.into_inner().into_inner();
+        chunk_table.push(ChunkTableEntry {
+            point_count: points.len() as u64,
+            byte_count: bytes.len() as u64,
+        });
+        chunks.push((VoxelKey { l, x, y, z }, points.len(), bytes));
+    }
+    let mut chunk_table_bytes = Vec::new();
+    chunk_table.write_to(&mut chunk_table_bytes, &laz_vlr)?;
+
+    header.vlrs.insert(0, info.to_vlr()?);
+    header.evlrs.insert(
+        0,
+        Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: CopcHierarchyVlr::RECORD_ID,
+            description: "EPT hierarchy".to_string(),
+            data: vec![0; chunks.len() * 32],
+            ..Default::default()
+        },
+    );
+    header.verify_room_for(number_of_points, number_of_points_by_return.clone())?;
+    if number_of_points > 0 {
+        header.add_points(number_of_points, &number_of_points_by_return, &bounds);
+    }
+    let _ = header.recompute_offsets();
+    let offset_to_point_data = header.offsets().offset_to_point_data;
+    let mut offset = offset_to_point_data + ChunkTable::OFFSET_SIZE as u64;
+    let mut entries = Vec::with_capacity(chunks.len());
+    for (key, point_count, bytes) in &chunks {
+        entries.push(Entry {
+            key: *key,
+            offset,
+            byte_size: i32::try_from(bytes.len())?,
+            point_count: i32::try_from(*point_count)?,
+        });
+        offset += bytes.len() as u64;
+    }
+    let offset_to_chunk_table = offset;
+    let start_of_first_evlr = offset_to_chunk_table + chunk_table_bytes.len() as u64;
+    let mut hierarchy = Vec::with_capacity(entries.len() * 32);
+    Page { entries }.write_to(&mut hierarchy)?;
+    info.root_hier_offset = start_of_first_evlr + 60;
+    info.root_hier_size = hierarchy.len() as u64;
+    header.vlrs[0].data = info.encode()?;
+    header.evlrs[0].data = hierarchy;
+    header.set_start_of_first_evlr(start_of_first_evlr);
+
+    let _ = write.seek(SeekFrom::Start(start))?;
+    header.write_to(&mut write)?;
+    write.write_i64::<LittleEndian>(offset_to_chunk_table.try_into()?)?;
+    for (_, _, bytes) in &chunks {
+        write.write_all(bytes)?;
+    }
+    write.write_all(&chunk_table_bytes)?;
+    for evlr in header.evlrs() {
+        evlr.clone().into_raw(true)?.write_to(&mut write)?;
+    }
+    write.flush()?;
+    Ok(header)
+}
+
+/// Returns the ranges of point indices in the nodes whose cubes intersect the bounds.
+///
+/// A node's points are one laz chunk, and the chunks are in the order of their offsets. Returns
+/// `None` if the header isn't a COPC header, or if its hierarchy evlr doesn't account for every
+/// point.
+pub(crate) fn point_intervals(header: &Header, bounds: &Bounds) -> Option<Vec<Range<u64>>> {
+    let info = header.copc_info_vlr()?;
+    let hierarchy = header.copc_hierarchy_evlr()?;
+    let mut entries = hierarchy
+        .iter_entries()
+        .map(|entry| entry.ok().copied())
+        .collect::<Option<Vec<_>>>()?;
+    entries.retain(|entry| entry.point_count > 0);
+    entries.sort_by_key(|entry| entry.offset);
+    let mut intervals: Vec<Range<u64>> = Vec::new();
+    let mut start = 0;
+    for entry in entries {
+        let end = start + entry.point_count as u64;
+        if intersects(&entry.key.bounds(&info), bounds) {
+            match intervals.last_mut() {
+                Some(interval) if interval.end == start => interval.end = end,
+                _ => intervals.push(start..end),
+            }
+        }
+        start = end;
+    }
+    (start == header.number_of_points()).then_some(intervals)
+}
+
+fn intersects(a: &Bounds, b: &Bounds) -> bool {
+    a.min.x <= b.max.x
+        && b.min.x <= a.max.x
+        && a.min.y <= b.max.y
+        && b.min.y <= a.max.y
+        && a.min.z <= b.max.z
+        && b.min.z <= a.max.z
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Result, VoxelKey};
-    use crate::{copc::CopcEntryReader, Reader};
+    use crate::{copc::CopcEntryReader, synthetic::fixture, Reader};
     use std::{fs::File, io::BufReader};
     #[test]
     fn test_voxelkey() {
@@ -634,6 +1387,46 @@ mod tests {
         assert_eq!(root_entry.point_count, 107);
     }
 
+    #[test]
+    fn child_bounds() {
+        let reader = super::Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+        let info = reader.info();
+        let root = VoxelKey::ROOT.bounds(info);
+        let child = VoxelKey::ROOT.child(7).unwrap().bounds(info);
+        assert_eq!(root.max, child.max);
+        assert_eq!(info.center_x, child.min.x);
+        assert_eq!(info.halfsize, child.max.z - child.min.z);
+    }
+
+    #[test]
+    fn query_half() {
+        let mut reader = super::Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+        let mut bounds = *reader.bounds();
+        bounds.max.y = (bounds.min.y + bounds.max.y) / 2.;
+        let expected = Reader::from_path("tests/data/autzen.copc.laz")
+            .unwrap()
+            .points()
+            .map(|point| point.unwrap())
+            .filter(|point| point.y <= bounds.max.y)
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        let points = reader
+            .query(&bounds, Some(1.))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(expected, points);
+    }
+
+    #[test]
+    fn query_outside() {
+        let mut reader = super::Reader::from_path("tests/data/autzen.copc.laz").unwrap();
+        let mut bounds = *reader.bounds();
+        bounds.min.x = bounds.max.x + 2. * reader.info().halfsize;
+        bounds.max.x = bounds.min.x + 1.;
+        assert!(reader.nodes(&bounds, None).is_empty());
+        assert_eq!(0, reader.query(&bounds, None).count());
+    }
+
     #[test]
     fn test_copc_read_autzen() {
         let copc_points = {
@@ -656,4 +1449,94 @@ mod tests {
             .zip(copc_points)
             .all(|(laz_point, copc_point)| laz_point.eq(&copc_point)));
     }
+    fn written() -> (Vec<crate::Point>, Vec<u8>) {
+        let mut builder = crate::Builder::from((1, 4));
+        builder.point_format = crate::point::Format::new(6).unwrap();
+        builder.gps_time_type = crate::GpsTimeType::Standard;
+        let mut writer = super::Writer::new(
+            std::io::Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+        )
+        .unwrap();
+        let points = (0..3000)
+            .map(|i| crate::Point {
+                x: f64::from(i % 250),
+                y: f64::from(i * 7 % 30),
+                z: f64::from(i % 3),
+                return_number: 1,
+                gps_time: Some(f64::from(i)),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        for point in &points {
+            writer.write_point(point.clone()).unwrap();
+        }
+        (points, writer.into_inner().unwrap().into_inner())
+    }
+
+    fn by_gps_time(mut points: Vec<crate::Point>) -> Vec<crate::Point> {
+        points.sort_by(|a, b| a.gps_time.partial_cmp(&b.gps_time).unwrap());
+        points
+    }
+
+    #[test]
+    fn write_and_read_as_laz() {
+        let (points, bytes) = written();
+        let reader = fixture::reader(bytes);
+        assert_eq!(3000, reader.header().number_of_points());
+        assert_eq!(Some(3000), reader.header().number_of_points_by_return(1));
+        assert_eq!(points, by_gps_time(reader.into_points().unwrap()));
+    }
+
+    #[test]
+    fn write_and_query() {
+        let (points, bytes) = written();
+        let mut reader = super::Reader::new(std::io::Cursor::new(bytes)).unwrap();
+        assert!(reader.entries().len() > 1);
+        assert!(reader.entries().iter().any(|entry| entry.key.level() > 1));
+        assert_eq!(
+            3000,
+            reader
+                .entries()
+                .iter()
+                .map(|entry| entry.point_count)
+                .sum::<i32>()
+        );
+        let mut bounds = *reader.bounds();
+        bounds.max.x = 100.;
+        let expected = points
+            .into_iter()
+            .filter(|point| point.x <= 100.)
+            .collect::<Vec<_>>();
+        let queried = reader
+            .query(&bounds, None)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(expected, by_gps_time(queried));
+    }
+
+    #[test]
+    fn write_info_vlr_first() {
+        let (_, bytes) = written();
+        let reader = fixture::reader(bytes);
+        assert!(reader.header().vlrs()[0].is_copc_info());
+        // The vlr's own header starts at 375, and its data after that.
+        assert_eq!(375 + 54, reader.header().vlr_data_offset(0));
+    }
+    #[test]
+    fn points_in_bounds_uses_hierarchy() {
+        let (_, bytes) = written();
+        let mut reader = fixture::reader(bytes);
+        let mut bounds = reader.header().bounds();
+        bounds.max.x = 60.;
+        let intervals = super::point_intervals(reader.header(), &bounds).unwrap();
+        let covered: u64 = intervals.iter().map(|range| range.end - range.start).sum();
+        assert!(covered < 3000);
+        let expected = reader
+            .points()
+            .map(|point| point.unwrap())
+            .filter(|point| point.x <= 60.)
+            .collect::<Vec<_>>();
+        assert_eq!(expected, reader.points_in_bounds(bounds).unwrap());
+    }
 }
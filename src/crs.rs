@@ -3,15 +3,29 @@
 //! CRSes are stored either as WKT or in GeoTiff
 //! [Header::get_epsg_crs] parses the CRS data to EPSG code(s)
 //!
-//! Only WKT is supported for writing CRS data to a header
-
-use crate::{Error, Header, Result};
-use byteorder::{LittleEndian, ReadBytesExt};
+//! Only WKT is supported for writing CRS data to a header, but a [Crs] set on a [Builder] is
+//! written as WKT or GeoTIFF keys, whichever the version and point format call for
+
+use crate::{
+    geotiff::{
+        GeoAsciiParams, GeoDoubleParams, GeoKeyDirectory, GeoKeyEntry, GEOGRAPHIC_TYPE_GEO_KEY,
+        GT_MODEL_TYPE_GEO_KEY, GT_RASTER_TYPE_GEO_KEY, PROJECTED_CS_TYPE_GEO_KEY,
+        VERTICAL_CS_TYPE_GEO_KEY,
+    },
+    Builder, Error, Header, Result, Version, Vlr, VlrRecord,
+};
 use log::{log, Level};
-use std::io::{Cursor, Seek, SeekFrom};
+use std::fmt;
+
+/// The widest longitudes that a geographic crs's bounds can plausibly have, since some files use
+/// zero to 360 degrees.
+const LONGITUDE_RANGE: (f64, f64) = (-180., 360.);
+
+/// The widest latitudes that a geographic crs's bounds can plausibly have.
+const LATITUDE_RANGE: (f64, f64) = (-90., 90.);
 
 /// Horizontal and optional vertical CRS given by EPSG code(s)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EpsgCrs {
     /// EPSG code for the horizontal CRS
     pub horizontal: u16,
@@ -19,7 +33,337 @@ pub struct EpsgCrs {
     pub vertical: Option<u16>,
 }
 
+/// Whether a header's bounds look like they're in the units of its crs.
+///
+/// Returned by [Header::crs_bounds_plausibility].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Plausibility {
+    /// The bounds could be in the crs's units.
+    Consistent,
+
+    /// The crs is projected, but the bounds are small enough to be longitudes and latitudes.
+    SuspectGeographicInProjected,
+
+    /// The crs is geographic, but the bounds are too big to be longitudes and latitudes.
+    SuspectProjectedInGeographic,
+
+    /// There's no crs, it can't be told whether it's projected or geographic, or there are no
+    /// bounds.
+    Unknown,
+}
+
+impl fmt::Display for Plausibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Plausibility::Consistent => write!(f, "consistent"),
+            Plausibility::SuspectGeographicInProjected => {
+                write!(f, "suspect geographic bounds in a projected crs")
+            }
+            Plausibility::SuspectProjectedInGeographic => {
+                write!(f, "suspect projected bounds in a geographic crs")
+            }
+            Plausibility::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Whether a crs is projected or geographic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrsKind {
+    /// Coordinates are on a map projection, e.g. metres of UTM.
+    Projected,
+
+    /// Coordinates are longitudes and latitudes.
+    Geographic,
+}
+
+/// A coordinate reference system, from a header's WKT or GeoTIFF (E)VLRs.
+///
+/// Read with [Header::crs], and set with [Builder::crs], which writes the (E)VLRs that the
+/// builder's version and point format call for.
+///
+/// # Examples
+///
+/// ```
+/// use las::crs::{Crs, CrsKind, EpsgCrs};
+///
+/// let crs = Crs::from_wkt(r#"PROJCS["ETRS89 / UTM zone 32N",AUTHORITY["EPSG","25832"]]"#);
+/// assert_eq!(Some(CrsKind::Projected), crs.kind());
+/// assert_eq!(25832, crs.epsg().unwrap().horizontal);
+///
+/// let crs = Crs::from_epsg(CrsKind::Geographic, EpsgCrs { horizontal: 4326, vertical: None });
+/// assert!(crs.wkt().is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Crs {
+    wkt: Option<String>,
+    epsg: Option<EpsgCrs>,
+    kind: Option<CrsKind>,
+}
+
+impl Crs {
+    /// Creates a crs from OGC well known text.
+    ///
+    /// The EPSG code(s) and kind are taken from the text, if they're in it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::Crs;
+    /// let crs = Crs::from_wkt(r#"GEOGCS["unnamed"]"#);
+    /// assert_eq!(Some(r#"GEOGCS["unnamed"]"#), crs.wkt());
+    /// assert!(crs.epsg().is_none());
+    /// ```
+    pub fn from_wkt(wkt: impl Into<String>) -> Crs {
+        let wkt = wkt.into();
+        Crs {
+            epsg: get_epsg_from_wkt_crs_bytes(wkt.as_bytes()).ok().flatten(),
+            kind: wkt_kind(&wkt),
+            wkt: Some(wkt),
+        }
+    }
+
+    /// Creates a crs from EPSG code(s), without well known text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{Crs, CrsKind, EpsgCrs};
+    /// let epsg = EpsgCrs { horizontal: 25832, vertical: Some(5941) };
+    /// assert_eq!(Some(epsg), Crs::from_epsg(CrsKind::Projected, epsg).epsg());
+    /// ```
+    pub fn from_epsg(kind: CrsKind, epsg: EpsgCrs) -> Crs {
+        Crs {
+            wkt: None,
+            epsg: Some(epsg),
+            kind: Some(kind),
+        }
+    }
+
+    /// Returns the well known text, if there is any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::Crs;
+    /// assert!(Crs::from_wkt("GEOGCS[]").wkt().is_some());
+    /// ```
+    pub fn wkt(&self) -> Option<&str> {
+        self.wkt.as_deref()
+    }
+
+    /// Returns the EPSG code(s), if they could be worked out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::Crs;
+    /// let crs = Crs::from_wkt(r#"GEOGCS["WGS 84",AUTHORITY["EPSG","4326"]]"#);
+    /// assert_eq!(4326, crs.epsg().unwrap().horizontal);
+    /// ```
+    pub fn epsg(&self) -> Option<EpsgCrs> {
+        self.epsg
+    }
+
+    /// Returns whether the crs is projected or geographic, if that could be worked out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{Crs, CrsKind};
+    /// assert_eq!(Some(CrsKind::Geographic), Crs::from_wkt("GEOGCS[]").kind());
+    /// ```
+    pub fn kind(&self) -> Option<CrsKind> {
+        self.kind
+    }
+
+    /// Returns the GeoTIFF key directory vlr, if there are EPSG code(s) and a kind to put in it.
+    fn to_geotiff_vlr(&self) -> Option<Vlr> {
+        let epsg = self.epsg?;
+        let (model_type, key) = match self.kind? {
+            CrsKind::Projected => (1, PROJECTED_CS_TYPE_GEO_KEY),
+            CrsKind::Geographic => (2, GEOGRAPHIC_TYPE_GEO_KEY),
+        };
+        // Raster type 1 is pixel is area.
+        let mut directory = GeoKeyDirectory {
+            keys: vec![
+                GeoKeyEntry::new_short(GT_MODEL_TYPE_GEO_KEY, model_type),
+                GeoKeyEntry::new_short(GT_RASTER_TYPE_GEO_KEY, 1),
+                GeoKeyEntry::new_short(key, epsg.horizontal),
+            ],
+            ..Default::default()
+        };
+        if let Some(vertical) = epsg.vertical {
+            directory
+                .keys
+                .push(GeoKeyEntry::new_short(VERTICAL_CS_TYPE_GEO_KEY, vertical));
+        }
+        directory.to_vlr().ok()
+    }
+}
+
+/// Returns the kind of a crs by its WKT keywords.
+fn wkt_kind(wkt: &str) -> Option<CrsKind> {
+    let wkt = wkt.to_uppercase();
+    // A projected crs has its geographic crs inside, so look for projections first.
+    if ["PROJCS[", "PROJCRS[", "PROJECTEDCRS["]
+        .iter()
+        .any(|keyword| wkt.contains(keyword))
+    {
+        Some(CrsKind::Projected)
+    } else if ["GEOGCS[", "GEOGCRS[", "GEOGRAPHICCRS["]
+        .iter()
+        .any(|keyword| wkt.contains(keyword))
+    {
+        Some(CrsKind::Geographic)
+    } else {
+        None
+    }
+}
+
+/// Returns the kind of a crs by its GeoTIFF model type.
+fn geotiff_kind(geotiff: &GeoTiffCrs) -> Option<CrsKind> {
+    geotiff
+        .entries
+        .iter()
+        .find(|entry| entry.id == GT_MODEL_TYPE_GEO_KEY)
+        .and_then(|entry| match entry.data {
+            GeoTiffData::U16(1) => Some(CrsKind::Projected),
+            GeoTiffData::U16(2) => Some(CrsKind::Geographic),
+            _ => None,
+        })
+}
+
+impl Builder {
+    /// Sets the crs, replacing any crs (E)VLRs.
+    ///
+    /// Extended point formats need WKT, and versions before 1.4 can only have GeoTIFF keys, which
+    /// are made from the EPSG code(s) and kind. Otherwise, WKT is written if the crs has it. The
+    /// builder's version and point format are used as they are, so set those first. Returns an
+    /// error if the crs doesn't have what they need.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{crs::{Crs, CrsKind, EpsgCrs}, Builder};
+    ///
+    /// let crs = Crs::from_epsg(CrsKind::Projected, EpsgCrs { horizontal: 25832, vertical: None });
+    /// let mut builder = Builder::from((1, 2));
+    /// builder.crs(&crs).unwrap();
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(Some(crs), header.crs());
+    /// ```
+    pub fn crs(&mut self, crs: &Crs) -> Result<()> {
+        let use_wkt = if self.point_format.is_extended {
+            true
+        } else if self.version < Version::new(1, 4) {
+            false
+        } else {
+            crs.wkt.is_some()
+        };
+        let not_representable = |needs| Error::CrsNotRepresentable {
+            version: self.version,
+            format: self.point_format,
+            needs,
+        };
+        let vlr = if use_wkt {
+            let wkt = crs.wkt.as_ref().ok_or_else(|| not_representable("WKT"))?;
+            let mut data = wkt.as_bytes().to_vec();
+            data.push(0);
+            Vlr {
+                user_id: "LASF_Projection".to_string(),
+                record_id: 2112,
+                description: String::new(),
+                data,
+                reserved: None,
+            }
+        } else {
+            crs.to_geotiff_vlr().ok_or_else(|| {
+                not_representable("an EPSG code and whether it's projected or geographic")
+            })?
+        };
+        self.vlrs.retain(|vlr| !vlr.is_projection());
+        self.evlrs.retain(|vlr| !vlr.is_projection());
+        match vlr.into_evlr().try_into_vlr() {
+            Ok(vlr) => self.vlrs.push(vlr),
+            Err(evlr) => self.evlrs.push(evlr.0),
+        }
+        self.has_wkt_crs = use_wkt;
+        Ok(())
+    }
+}
+
 impl Header {
+    /// Checks whether the bounds look like they're in the units of the crs.
+    ///
+    /// This is a heuristic, for catching longitudes and latitudes in a file whose crs says
+    /// projected metres, or the other way around. The crs is projected or geographic by its WKT
+    /// keywords, or by the GeoTIFF model type. Bounds that fit inside longitudes of -180 to 360
+    /// and latitudes of -90 to 90 look geographic, and anything else looks projected, so a small
+    /// local grid near its projection's origin is reported as suspect too. Nothing is changed
+    /// when a file is read, whatever this says.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{crs::Plausibility, Builder, Point};
+    ///
+    /// let mut header = Builder::from((1, 4)).into_header().unwrap();
+    /// header.set_wkt_crs(br#"PROJCS["WGS 84 / UTM zone 10N"]"#.to_vec()).unwrap();
+    /// header.add_point(&Point { x: -122.5, y: 45.5, ..Default::default() });
+    /// assert_eq!(
+    ///     Plausibility::SuspectGeographicInProjected,
+    ///     header.crs_bounds_plausibility()
+    /// );
+    /// ```
+    pub fn crs_bounds_plausibility(&self) -> Plausibility {
+        let bounds = self.bounds();
+        if self.number_of_points() == 0 || bounds.min.x > bounds.max.x {
+            return Plausibility::Unknown;
+        }
+        let looks_geographic = LONGITUDE_RANGE.0 <= bounds.min.x
+            && bounds.max.x <= LONGITUDE_RANGE.1
+            && LATITUDE_RANGE.0 <= bounds.min.y
+            && bounds.max.y <= LATITUDE_RANGE.1;
+        match (self.crs_kind(), looks_geographic) {
+            (Some(CrsKind::Projected), true) => Plausibility::SuspectGeographicInProjected,
+            (Some(CrsKind::Geographic), false) => Plausibility::SuspectProjectedInGeographic,
+            (Some(_), _) => Plausibility::Consistent,
+            (None, _) => Plausibility::Unknown,
+        }
+    }
+
+    fn crs_kind(&self) -> Option<CrsKind> {
+        self.crs()?.kind()
+    }
+
+    /// Returns the crs, from the WKT (E)VLR, or else the GeoTIFF (E)VLRs.
+    ///
+    /// The crs is returned even if its EPSG code(s) can't be worked out, e.g. for a user defined
+    /// crs. Use [Self::get_geotiff_crs] for all of the GeoTIFF keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let crs = reader.header().crs().unwrap();
+    /// assert_eq!(2994, crs.epsg().unwrap().horizontal);
+    /// ```
+    pub fn crs(&self) -> Option<Crs> {
+        if let Some(wkt) = self.get_wkt_crs_bytes() {
+            return Some(Crs::from_wkt(
+                String::from_utf8_lossy(wkt).trim_end_matches('\0'),
+            ));
+        }
+        let geotiff = self.get_geotiff_crs().ok()??;
+        Some(Crs {
+            wkt: None,
+            kind: geotiff_kind(&geotiff),
+            epsg: get_epsg_from_geotiff_crs(geotiff).ok().flatten(),
+        })
+    }
+
     /// Parse the EPSG coordinate reference system (CRSes) code(s) from the header.
     /// Las stores CRS-info in (E)VLRs either as Well Known Text (WKT) or in GeoTIff-format
     ///
@@ -83,14 +427,7 @@ impl Header {
 
     /// remove all CRS (E)VLRs from the header
     pub fn remove_crs_vlrs(&mut self) {
-        // remove projection vlrs
-        self.vlrs = self.vlrs.drain(..).filter(|v| !v.is_projection()).collect();
-        // remove projection evlrs
-        self.evlrs = self
-            .evlrs
-            .drain(..)
-            .filter(|v| !v.is_projection())
-            .collect();
+        let _ = self.remove_vlrs(|vlr| vlr.is_projection());
         self.has_wkt_crs = false;
     }
 
@@ -100,7 +437,7 @@ impl Header {
     ///
     /// The WKT bytes can be obtained from a horizontal EPSG code by using the crs_definitions crate
     pub fn set_wkt_crs(&mut self, wkt_crs_bytes: Vec<u8>) -> Result<()> {
-        if self.version() < crate::Version::new(1, 4) {
+        if self.version() < Version::new(1, 4) {
             return Err(Error::UnsupportedFeature {
                 version: self.version(),
                 feature: "WKT CRS VLR",
@@ -113,36 +450,18 @@ impl Header {
             }
         }
 
-        let mut user_id = [0; 16];
-        for (i, c) in "LASF_Projection".as_bytes().iter().enumerate() {
-            user_id[i] = *c;
-        }
-
-        let num_bytes = wkt_crs_bytes.len();
-
-        if num_bytes > u16::MAX as usize {
-            // must be added as an evlr, because of length
-            let crs_vlr = crate::raw::Vlr {
-                reserved: 0,
-                user_id,
-                record_id: 2112,
-                record_length_after_header: crate::raw::vlr::RecordLength::Evlr(num_bytes as u64),
-                description: [0; 32],
-                data: wkt_crs_bytes,
-            };
-            self.evlrs.push(crate::Vlr::new(crs_vlr));
-        } else {
-            // just a normal vlr
-            let crs_vlr = crate::raw::Vlr {
-                reserved: 0,
-                user_id,
-                record_id: 2112,
-                record_length_after_header: crate::raw::vlr::RecordLength::Vlr(num_bytes as u16),
-                description: [0; 32],
-                data: wkt_crs_bytes,
-            };
-            self.vlrs.push(crate::Vlr::new(crs_vlr));
+        let crs_vlr = Vlr {
+            user_id: "LASF_Projection".to_string(),
+            record_id: 2112,
+            description: String::new(),
+            data: wkt_crs_bytes,
+            reserved: None,
         };
+        // must be added as an evlr if it's too long for a vlr
+        match crs_vlr.into_evlr().try_into_vlr() {
+            Ok(vlr) => self.vlrs.push(vlr),
+            Err(evlr) => self.evlrs.push(evlr.0),
+        }
 
         self.has_wkt_crs = true;
         Ok(())
@@ -153,7 +472,7 @@ impl Header {
         for vlr in self.all_vlrs() {
             if let ("lasf_projection", 2112) = (vlr.user_id.to_lowercase().as_str(), vlr.record_id)
             {
-                return Some(vlr.data.as_slice());
+                return Some(vlr.vlr.data.as_slice());
             }
         }
         None
@@ -171,7 +490,7 @@ impl Header {
                     _ => continue,
                 };
 
-                geotiff_vlrs[pos] = Some(vlr.data.as_slice());
+                geotiff_vlrs[pos] = Some(vlr.vlr.data.as_slice());
             }
         }
         let [geotiff_main, double, string] = geotiff_vlrs;
@@ -295,21 +614,19 @@ impl GeoTiffCrs {
         double_vlr: Option<&[u8]>,
         ascii_vlr: Option<&[u8]>,
     ) -> Result<Self> {
-        let mut main_vlr = Cursor::new(main_vlr);
-
-        let _ = main_vlr.read_u16::<LittleEndian>()?; // should always be 1
-        let _ = main_vlr.read_u16::<LittleEndian>()?; // should always be 1
-        let _ = main_vlr.read_u16::<LittleEndian>()?; // should always be 0
-        let num_keys = main_vlr.read_u16::<LittleEndian>()?;
-
-        let mut entries = Vec::with_capacity(num_keys as usize);
-        for _ in 0..num_keys {
-            entries.push(GeoTiffKeyEntry::read_from(
-                &mut main_vlr,
-                &double_vlr,
-                &ascii_vlr,
-            )?);
-        }
+        let directory = GeoKeyDirectory::decode(main_vlr)?;
+        let doubles = double_vlr.map(GeoDoubleParams::decode).transpose()?;
+        let ascii = ascii_vlr.map(GeoAsciiParams::decode).transpose()?;
+        let entries = directory
+            .keys
+            .iter()
+            .map(|key| {
+                Ok(GeoTiffKeyEntry {
+                    id: key.id,
+                    data: key.value(doubles.as_ref(), ascii.as_ref())?,
+                })
+            })
+            .collect::<Result<_>>()?;
         Ok(GeoTiffCrs { entries })
     }
 }
@@ -335,45 +652,149 @@ pub struct GeoTiffKeyEntry {
     pub data: GeoTiffData,
 }
 
-impl GeoTiffKeyEntry {
-    fn read_from(
-        main_vlr: &mut Cursor<&[u8]>,
-        double_vlr: &Option<&[u8]>,
-        ascii_vlr: &Option<&[u8]>,
-    ) -> Result<Self> {
-        let id = main_vlr.read_u16::<LittleEndian>()?;
-        let location = main_vlr.read_u16::<LittleEndian>()?;
-        let count = main_vlr.read_u16::<LittleEndian>()?;
-        let offset = main_vlr.read_u16::<LittleEndian>()?;
-        let data = match location {
-            0 => GeoTiffData::U16(offset),
-            34736 => {
-                let mut cursor = Cursor::new(double_vlr.ok_or(Error::UnreadableGeoTiffCrs)?);
-                let _ = cursor.seek(SeekFrom::Start(offset as u64 * 8_u64))?; // 8 is the byte size of a f64 and offset is not a byte offset but an index
-                let mut doubles = Vec::with_capacity(count as usize);
-                for _ in 0..count {
-                    doubles.push(cursor.read_f64::<LittleEndian>()?);
-                }
-                GeoTiffData::Doubles(doubles)
-            }
-            34737 => {
-                let mut cursor = Cursor::new(ascii_vlr.ok_or(Error::UnreadableGeoTiffCrs)?);
-                let _ = cursor.seek(SeekFrom::Start(offset as u64))?; // no need to multiply the index as the byte size of char is 1
-                let mut string = String::with_capacity(count as usize);
-                for _ in 0..count {
-                    string.push(cursor.read_u8()? as char);
-                }
-                GeoTiffData::String(string)
-            }
-            _ => return Err(Error::UndefinedDataForGeoTiffKey(id)),
+#[cfg(test)]
+mod tests {
+    use super::{Crs, CrsKind, EpsgCrs, Plausibility};
+    use crate::{point::Format, Builder, Error, GpsTimeType, Header, Point, Reader, Vlr};
+
+    const UTM: (f64, f64) = (500_000., 5_000_000.);
+    const DEGREES: (f64, f64) = (10.5, 59.9);
+
+    fn with_point(mut header: Header, (x, y): (f64, f64)) -> Header {
+        header.add_point(&Point {
+            x,
+            y,
+            ..Default::default()
+        });
+        header
+    }
+
+    fn wkt(wkt: &str, xy: (f64, f64)) -> Header {
+        let mut header = Builder::from((1, 4)).into_header().unwrap();
+        header.set_wkt_crs(wkt.as_bytes().to_vec()).unwrap();
+        with_point(header, xy)
+    }
+
+    fn geotiff(model_type: u16, xy: (f64, f64)) -> Header {
+        let mut builder = Builder::from((1, 2));
+        builder.vlrs.push(Vlr {
+            user_id: "LASF_Projection".to_string(),
+            record_id: 34735,
+            data: [1, 1, 0, 1, 1024, 0, 1, model_type]
+                .iter()
+                .flat_map(|n: &u16| n.to_le_bytes())
+                .collect(),
+            ..Default::default()
+        });
+        with_point(builder.into_header().unwrap(), xy)
+    }
+
+    #[test]
+    fn crs_bounds_plausibility() {
+        let projected = r#"PROJCS["ETRS89 / UTM zone 32N",GEOGCS["ETRS89"]]"#;
+        let geographic = r#"GEOGCRS["WGS 84",DATUM["World Geodetic System 1984"]]"#;
+        for (header, expected) in [
+            (wkt(projected, UTM), Plausibility::Consistent),
+            (
+                wkt(projected, DEGREES),
+                Plausibility::SuspectGeographicInProjected,
+            ),
+            (wkt(geographic, DEGREES), Plausibility::Consistent),
+            (
+                wkt(geographic, UTM),
+                Plausibility::SuspectProjectedInGeographic,
+            ),
+            (wkt(geographic, (350., 10.)), Plausibility::Consistent),
+            (wkt("COMPD_CS[]", UTM), Plausibility::Unknown),
+            (geotiff(1, UTM), Plausibility::Consistent),
+            (
+                geotiff(1, DEGREES),
+                Plausibility::SuspectGeographicInProjected,
+            ),
+            (geotiff(2, UTM), Plausibility::SuspectProjectedInGeographic),
+            (geotiff(3, UTM), Plausibility::Unknown),
+            (
+                with_point(Header::default(), DEGREES),
+                Plausibility::Unknown,
+            ),
+        ] {
+            assert_eq!(expected, header.crs_bounds_plausibility(), "{header}");
+        }
+        let mut empty = Builder::from((1, 4)).into_header().unwrap();
+        empty.set_wkt_crs(projected.as_bytes().to_vec()).unwrap();
+        assert_eq!(Plausibility::Unknown, empty.crs_bounds_plausibility());
+    }
+
+    #[test]
+    fn builder_crs() {
+        let utm = r#"PROJCS["ETRS89 / UTM zone 32N",AUTHORITY["EPSG","25832"]]"#;
+        let epsg = EpsgCrs {
+            horizontal: 25832,
+            vertical: None,
         };
-        Ok(GeoTiffKeyEntry { id, data })
+        let header = |version: (u8, u8), format: u8, crs: &Crs| {
+            let mut builder = Builder::from(version);
+            builder.point_format = Format::new(format).unwrap();
+            builder.gps_time_type = GpsTimeType::Standard;
+            builder.vlrs.push(Vlr {
+                user_id: "LASF_Projection".to_string(),
+                record_id: 2112,
+                data: b"GEOGCS[]".to_vec(),
+                ..Default::default()
+            });
+            builder.crs(crs).map(|()| builder.into_header().unwrap())
+        };
+
+        let legacy = header((1, 2), 1, &Crs::from_wkt(utm)).unwrap();
+        assert!(!legacy.has_wkt_crs());
+        assert_eq!(Some(Crs::from_epsg(CrsKind::Projected, epsg)), legacy.crs());
+        assert_eq!(1, legacy.vlrs().len());
+
+        let extended = header((1, 4), 6, &Crs::from_wkt(utm)).unwrap();
+        assert!(extended.has_wkt_crs());
+        assert_eq!(Some(Crs::from_wkt(utm)), extended.crs());
+        assert_eq!(1, extended.vlrs().len());
+
+        let geotiff_in_1_4 = header((1, 4), 1, &Crs::from_epsg(CrsKind::Projected, epsg));
+        assert!(!geotiff_in_1_4.unwrap().has_wkt_crs());
+
+        assert!(matches!(
+            header((1, 4), 6, &Crs::from_epsg(CrsKind::Projected, epsg)).unwrap_err(),
+            Error::CrsNotRepresentable { .. }
+        ));
+        assert!(matches!(
+            header((1, 2), 1, &Crs::from_wkt("COMPD_CS[]")).unwrap_err(),
+            Error::CrsNotRepresentable { .. }
+        ));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::Reader;
+    #[cfg(feature = "laz")]
+    #[test]
+    fn crs_norway() {
+        let reader =
+            Reader::from_path("tests/data/32-1-472-150-76.laz").expect("Cannot open reader");
+        let crs = reader.header().crs().unwrap();
+        assert_eq!(Some(CrsKind::Projected), crs.kind());
+        assert_eq!(
+            Some(EpsgCrs {
+                horizontal: 25832,
+                vertical: Some(5941)
+            }),
+            crs.epsg()
+        );
+        assert!(crs.wkt().is_none());
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn crs_bounds_plausibility_norway() {
+        let reader =
+            Reader::from_path("tests/data/32-1-472-150-76.laz").expect("Cannot open reader");
+        assert_eq!(
+            Plausibility::Consistent,
+            reader.header().crs_bounds_plausibility()
+        );
+    }
 
     #[cfg(feature = "laz")]
     #[test]
@@ -0,0 +1,240 @@
+use crate::{
+    header::SystemIdentifier, point::Format, Point, Reader, Result, Transform, Vector, Writer,
+};
+use std::{
+    collections::HashSet,
+    io::{Read, Seek, Write},
+};
+
+/// The fields that make two points duplicates, for [dedup] and [dedup_sorted].
+///
+/// Coordinates are compared as the integers they're stored as, so floating point noise from the
+/// scale and offset can't hide a duplicate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DedupKey {
+    /// Points with the same x, y, and z are duplicates.
+    #[default]
+    Coordinates,
+
+    /// Points with the same x, y, z, and gps time are duplicates.
+    CoordinatesAndTime,
+
+    /// Points whose whole point records, extra bytes included, are the same are duplicates.
+    Record,
+}
+
+/// What [dedup] or [dedup_sorted] did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DedupReport {
+    /// The number of points read.
+    pub input: u64,
+
+    /// The number of points written.
+    pub output: u64,
+
+    /// The number of duplicate points that weren't written.
+    pub duplicates: u64,
+}
+
+/// What identifies a point, for one [DedupKey].
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Identity {
+    Coordinates([i32; 3]),
+    CoordinatesAndTime([i32; 3], u64),
+    Record(Vec<u8>),
+}
+
+/// Copies a reader's points to a destination, leaving out duplicates.
+///
+/// The first of each set of duplicates is kept, and the output has the source's layout and vlrs.
+///
+/// Every distinct point's identity is kept in a hash set until the end, so memory grows with the
+/// number of distinct points: roughly 40 bytes a point for [DedupKey::Coordinates] and
+/// [DedupKey::CoordinatesAndTime], plus the record length for [DedupKey::Record]. If duplicates are
+/// always next to each other, e.g. because the points are sorted, use [dedup_sorted] instead.
+///
+/// # Examples
+///
+/// ```
+/// use las::{DedupKey, Reader};
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let report = las::dedup(reader, Cursor::new(Vec::new()), DedupKey::Record).unwrap();
+/// assert_eq!(report.input, report.output + report.duplicates);
+/// ```
+pub fn dedup<R, W>(reader: Reader<R>, dest: W, key: DedupKey) -> Result<DedupReport>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let mut seen = HashSet::new();
+    copy_distinct(reader, dest, key, |identity| !seen.insert(identity))
+}
+
+/// Copies a reader's points to a destination, leaving out points that duplicate the point
+/// before them.
+///
+/// This only finds duplicates that are next to each other, but it only keeps the previous point's
+/// identity, so memory use doesn't grow. Otherwise, it's the same as [dedup].
+///
+/// # Examples
+///
+/// ```
+/// use las::{DedupKey, Reader};
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let report = las::dedup_sorted(reader, Cursor::new(Vec::new()), DedupKey::Record).unwrap();
+/// assert_eq!(0, report.duplicates);
+/// ```
+pub fn dedup_sorted<R, W>(reader: Reader<R>, dest: W, key: DedupKey) -> Result<DedupReport>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let mut previous = None;
+    copy_distinct(reader, dest, key, |identity| {
+        let is_duplicate = previous.as_ref() == Some(&identity);
+        previous = Some(identity);
+        is_duplicate
+    })
+}
+
+fn copy_distinct<R, W>(
+    mut reader: Reader<R>,
+    dest: W,
+    key: DedupKey,
+    mut is_duplicate: impl FnMut(Identity) -> bool,
+) -> Result<DedupReport>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let header = reader.header().to_derived(&SystemIdentifier::Modification);
+    let format = *header.point_format();
+    let transforms = *header.transforms();
+    let mut writer = Writer::new(dest, header)?;
+    let mut report = DedupReport::default();
+    for point in reader.points() {
+        let point = point?;
+        report.input += 1;
+        if is_duplicate(identity(&point, key, &format, &transforms)?) {
+            report.duplicates += 1;
+        } else {
+            writer.write_point(point)?;
+            report.output += 1;
+        }
+    }
+    let _ = writer.close()?;
+    Ok(report)
+}
+
+fn identity(
+    point: &Point,
+    key: DedupKey,
+    format: &Format,
+    transforms: &Vector<Transform>,
+) -> Result<Identity> {
+    let xyz = || -> Result<[i32; 3]> {
+        Ok([
+            transforms.x.inverse(point.x)?,
+            transforms.y.inverse(point.y)?,
+            transforms.z.inverse(point.z)?,
+        ])
+    };
+    Ok(match key {
+        DedupKey::Coordinates => Identity::Coordinates(xyz()?),
+        DedupKey::CoordinatesAndTime => {
+            Identity::CoordinatesAndTime(xyz()?, point.gps_time.unwrap_or(0.).to_bits())
+        }
+        DedupKey::Record => {
+            let mut bytes = Vec::with_capacity(usize::from(format.len()));
+            point
+                .to_raw_without_extra_bytes(transforms)?
+                .write_without_extra_bytes_to(&mut bytes, format)?;
+            bytes.extend_from_slice(&point.extra_bytes);
+            Identity::Record(bytes)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{synthetic::fixture, Builder};
+    use std::{fs::File, io::Cursor};
+
+    type Dedup = fn(Reader<Cursor<Vec<u8>>>, File, DedupKey) -> Result<DedupReport>;
+
+    fn point(x: f64, gps_time: f64, intensity: u16) -> Point {
+        Point {
+            x,
+            y: 2.,
+            z: 3.,
+            gps_time: Some(gps_time),
+            intensity,
+            ..Default::default()
+        }
+    }
+
+    fn points() -> Vec<Point> {
+        vec![
+            point(1., 10., 1),
+            // The same quantized coordinates, time, and record.
+            point(1.0000001, 10., 1),
+            // The same coordinates and time, with another intensity.
+            point(1., 10., 2),
+            // The same coordinates at another time.
+            point(1., 11., 1),
+            point(4., 10., 1),
+            // Not next to the point it duplicates.
+            point(1., 10., 1),
+        ]
+    }
+
+    fn run(f: Dedup, name: &str, key: DedupKey) -> (DedupReport, Vec<Point>) {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(1).unwrap();
+        let reader = fixture::reader(fixture::write(builder, points()));
+        let path = std::env::temp_dir().join(format!("las-rs-dedup-{name}-{key:?}.las"));
+        let report = f(reader, File::create(&path).unwrap(), key).unwrap();
+        let points = Reader::from_path(&path).unwrap().into_points().unwrap();
+        std::fs::remove_file(path).unwrap();
+        (report, points)
+    }
+
+    #[test]
+    fn keys() {
+        for (key, expected) in [
+            (DedupKey::Coordinates, vec![0, 4]),
+            (DedupKey::CoordinatesAndTime, vec![0, 3, 4]),
+            (DedupKey::Record, vec![0, 2, 3, 4]),
+        ] {
+            let (report, written) = run(dedup, "hash", key);
+            assert_eq!(6, report.input);
+            assert_eq!(expected.len() as u64, report.output, "{key:?}");
+            assert_eq!(6 - report.output, report.duplicates, "{key:?}");
+            let points = points();
+            let expected: Vec<Point> = expected.into_iter().map(|i| points[i].clone()).collect();
+            assert_eq!(expected, written, "{key:?}");
+        }
+    }
+
+    #[test]
+    fn sorted() {
+        for (key, expected) in [
+            (DedupKey::Coordinates, vec![0, 4, 5]),
+            (DedupKey::CoordinatesAndTime, vec![0, 3, 4, 5]),
+            (DedupKey::Record, vec![0, 2, 3, 4, 5]),
+        ] {
+            let (report, written) = run(dedup_sorted, "sorted", key);
+            assert_eq!(6, report.input);
+            assert_eq!(expected.len() as u64, report.output, "{key:?}");
+            assert_eq!(6 - report.output, report.duplicates, "{key:?}");
+            let points = points();
+            let expected: Vec<Point> = expected.into_iter().map(|i| points[i].clone()).collect();
+            assert_eq!(expected, written, "{key:?}");
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Digests of the point records, to check that they haven't changed.
+
+#[cfg(feature = "digest")]
+use crate::Reader;
+use crate::{vlr::LAS_RS_USER_ID, Error, Result, VlrRecord};
+#[cfg(feature = "digest")]
+use sha2::{Digest as _, Sha256};
+#[cfg(feature = "digest")]
+use std::io::{Read, Seek};
+
+/// The length of every digest.
+const DIGEST_LEN: usize = 32;
+
+/// The length of a [PointDataDigest] record.
+const RECORD_LEN: usize = 2 + 8 + DIGEST_LEN;
+
+/// A way of digesting the point records.
+///
+/// The digest covers the raw point records only, decompressed if they're compressed, so it
+/// doesn't change when a header field is patched, and a las file and its laz twin digest the
+/// same.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Digest {
+    /// SHA-256.
+    #[default]
+    Sha256,
+}
+
+/// What [verify_digest] found.
+#[cfg(feature = "digest")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigestStatus {
+    /// The point records match the stored digest.
+    Match,
+
+    /// The point records don't match the stored digest.
+    Mismatch {
+        /// The stored digest.
+        expected: [u8; DIGEST_LEN],
+
+        /// The digest of the point records.
+        actual: [u8; DIGEST_LEN],
+    },
+
+    /// The file has no [PointDataDigest].
+    Missing,
+}
+
+/// A digest of a file's point records, stored in a vlr.
+///
+/// With the `digest` feature, a [Writer](crate::Writer) adds it when it's asked to with
+/// `WriteOptions::point_data_digest`. It's stored as the digest's number as a little-endian
+/// `u16`, the number of points as a little-endian `u64`, and the digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PointDataDigest {
+    /// How the points were digested.
+    pub digest: Digest,
+
+    /// The number of points that were digested.
+    pub number_of_points: u64,
+
+    /// The digest.
+    pub value: [u8; DIGEST_LEN],
+}
+
+/// Checks a reader's point records against the digest that was stored when they were written.
+///
+/// Every record is read, so the reader shouldn't skip withheld points. The reader's cursor is
+/// left where it was.
+///
+/// # Examples
+///
+/// ```
+/// use las::{writer::WriteOptions, Digest, DigestStatus, Reader, Writer};
+/// use std::io::Cursor;
+///
+/// let options = WriteOptions::default().point_data_digest(Digest::Sha256);
+/// let mut writer =
+///     Writer::with_options(Cursor::new(Vec::new()), Default::default(), options).unwrap();
+/// writer.write_point(Default::default()).unwrap();
+/// let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+/// assert_eq!(DigestStatus::Match, las::verify_digest(&mut reader).unwrap());
+/// ```
+#[cfg(feature = "digest")]
+pub fn verify_digest<R: Read + Seek + Send>(reader: &mut Reader<R>) -> Result<DigestStatus> {
+    let Some(stored) = reader.header().get_record::<PointDataDigest>()? else {
+        return Ok(DigestStatus::Missing);
+    };
+    let actual = reader.point_data_digest(stored.digest)?;
+    if actual == stored.value && stored.number_of_points == reader.header().number_of_points() {
+        Ok(DigestStatus::Match)
+    } else {
+        Ok(DigestStatus::Mismatch {
+            expected: stored.value,
+            actual,
+        })
+    }
+}
+
+impl Digest {
+    fn id(self) -> u16 {
+        match self {
+            Digest::Sha256 => 1,
+        }
+    }
+
+    fn from_id(id: u16) -> Option<Digest> {
+        match id {
+            1 => Some(Digest::Sha256),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "digest")]
+    pub(crate) fn hasher(self) -> Hasher {
+        match self {
+            Digest::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+}
+
+impl VlrRecord for PointDataDigest {
+    const USER_ID: &'static str = LAS_RS_USER_ID;
+    const RECORD_ID: u16 = 2;
+    const DESCRIPTION: &'static str = "Point data digest";
+
+    fn decode(data: &[u8]) -> Result<PointDataDigest> {
+        let invalid = |message: String| Error::InvalidPointDataDigest(message);
+        if data.len() != RECORD_LEN {
+            return Err(invalid(format!(
+                "the record is {} bytes, not {RECORD_LEN}",
+                data.len()
+            )));
+        }
+        let id = u16::from_le_bytes([data[0], data[1]]);
+        let digest =
+            Digest::from_id(id).ok_or_else(|| invalid(format!("digest {id} is unknown")))?;
+        Ok(PointDataDigest {
+            digest,
+            number_of_points: u64::from_le_bytes(data[2..10].try_into().unwrap()),
+            value: data[10..].try_into().unwrap(),
+        })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(RECORD_LEN);
+        data.extend(self.digest.id().to_le_bytes());
+        data.extend(self.number_of_points.to_le_bytes());
+        data.extend(self.value);
+        Ok(data)
+    }
+}
+
+/// A running digest.
+#[cfg(feature = "digest")]
+#[derive(Clone, Debug)]
+pub(crate) enum Hasher {
+    Sha256(Sha256),
+}
+
+#[cfg(feature = "digest")]
+impl Hasher {
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Sha256(sha256) => sha256.update(bytes),
+        }
+    }
+
+    pub(crate) fn finish(self) -> [u8; DIGEST_LEN] {
+        match self {
+            Hasher::Sha256(sha256) => sha256.finalize().into(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "digest"))]
+mod tests {
+    use super::*;
+    use crate::{point::Format, synthetic::fixture, writer::WriteOptions, Builder, Point};
+
+    fn write(compressed: bool) -> Vec<u8> {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(1).unwrap();
+        builder.point_format.is_compressed = compressed;
+        let options = WriteOptions::default().point_data_digest(Digest::Sha256);
+        let points = (0..100).map(|i| Point {
+            x: f64::from(i),
+            intensity: i as u16,
+            gps_time: Some(f64::from(i)),
+            ..Default::default()
+        });
+        fixture::write_with_options(builder, options, points)
+    }
+
+    #[test]
+    fn verify() {
+        let bytes = write(false);
+        let mut reader = fixture::reader(bytes.clone());
+        reader.seek(10).unwrap();
+        assert_eq!(DigestStatus::Match, verify_digest(&mut reader).unwrap());
+        assert_eq!(10., reader.read_point().unwrap().unwrap().x);
+        let stored = reader
+            .header()
+            .get_record::<PointDataDigest>()
+            .unwrap()
+            .unwrap();
+        assert_eq!(100, stored.number_of_points);
+
+        let mut flipped = bytes;
+        let offset = reader.header().offsets().offset_to_point_data as usize + 50 * 28 + 12;
+        flipped[offset] ^= 1;
+        let mut reader = fixture::reader(flipped);
+        assert!(matches!(
+            verify_digest(&mut reader).unwrap(),
+            DigestStatus::Mismatch { expected, .. } if expected == stored.value
+        ));
+    }
+
+    #[test]
+    fn missing() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        assert_eq!(DigestStatus::Missing, verify_digest(&mut reader).unwrap());
+    }
+
+    #[test]
+    fn points_but_not_header() {
+        let mut reader = fixture::reader(write(false));
+        let digest = reader.point_data_digest(Digest::Sha256).unwrap();
+        let mut header = reader.header().clone();
+        header.vlrs_mut().clear();
+        let points = reader.points().map(Result::unwrap).collect::<Vec<_>>();
+        let mut rewritten = fixture::reader(fixture::write(header.into_builder(), points));
+        assert!(rewritten.header().vlrs().is_empty());
+        assert_eq!(digest, rewritten.point_data_digest(Digest::Sha256).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn las_and_laz_twins() {
+        let mut las = fixture::reader(write(false));
+        let mut laz = fixture::reader(write(true));
+        assert!(laz.header().point_format().is_compressed);
+        assert_eq!(
+            las.point_data_digest(Digest::Sha256).unwrap(),
+            laz.point_data_digest(Digest::Sha256).unwrap()
+        );
+        assert_eq!(DigestStatus::Match, verify_digest(&mut laz).unwrap());
+    }
+}
@@ -0,0 +1,440 @@
+//! Edit the header and vlrs of a las file in place, without rewriting its points.
+//!
+//! A [HeaderEditor] reads a file's header into a [Builder], and writing the edited header back
+//! only touches the bytes around the points. If the header and vlrs get smaller, the difference
+//! becomes vlr padding. If they grow past the vlr padding, the points are shifted towards the end
+//! of the file to make room, which is as slow as copying them. The evlrs are written again after
+//! the points.
+//!
+//! ```
+//! use las::{edit::HeaderEditor, Reader, Writer};
+//!
+//! let mut writer = Writer::default();
+//! writer.write_point(Default::default()).unwrap();
+//! let mut editor = HeaderEditor::new(writer.into_inner().unwrap()).unwrap();
+//! editor.builder().file_source_id = 42;
+//! let reader = Reader::new(editor.save().unwrap()).unwrap();
+//! assert_eq!(42, reader.header().file_source_id());
+//! assert_eq!(1, reader.header().number_of_points());
+//! ```
+
+use crate::{raw, waveform, Bounds, Builder, Error, Header, Result};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// The user id of the copc vlrs, whose offsets to the point data can't be moved.
+const COPC_USER_ID: &str = "copc";
+
+/// The number of bytes moved at a time when shifting the points.
+const SHIFT_BUFFER_LEN: usize = 1 << 20;
+
+/// Edits the header, vlrs, and evlrs of existing las data.
+///
+/// The point format can't change, since the points aren't rewritten. Changes to anything else,
+/// e.g. the transforms, are written as they are, so be sure they still describe the points.
+#[allow(missing_debug_implementations)]
+pub struct HeaderEditor<T: Read + Write + Seek> {
+    stream: T,
+    raw_header: raw::Header,
+    header: Header,
+    builder: Builder,
+    end: u64,
+    /// Called with the stream and its new length, if the evlrs got shorter.
+    set_len: Option<fn(&mut T, u64) -> Result<()>>,
+}
+
+/// Where an edited header puts the points and the evlrs.
+struct Layout {
+    header: Header,
+    shift: u64,
+    start_of_first_evlr: u64,
+}
+
+impl HeaderEditor<File> {
+    /// Opens a las file for editing.
+    ///
+    /// If the evlrs get shorter, the file is truncated after them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use las::edit::HeaderEditor;
+    /// let mut editor = HeaderEditor::open("points.las").unwrap();
+    /// editor.builder().generating_software = "my software".to_string();
+    /// let _ = editor.save().unwrap();
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<HeaderEditor<File>> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut editor = HeaderEditor::new(file)?;
+        editor.set_len = Some(|file, len| file.set_len(len).map_err(Error::from));
+        Ok(editor)
+    }
+}
+
+impl<T: Read + Write + Seek> HeaderEditor<T> {
+    /// Reads the header of las data for editing.
+    ///
+    /// The las data must start at the beginning of the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{edit::HeaderEditor, Writer};
+    /// let editor = HeaderEditor::new(Writer::default().into_inner().unwrap()).unwrap();
+    /// ```
+    pub fn new(mut stream: T) -> Result<HeaderEditor<T>> {
+        let _ = stream.seek(SeekFrom::Start(0))?;
+        let raw_header = raw::Header::read_from(&mut stream)?;
+        let _ = stream.seek(SeekFrom::Start(0))?;
+        let header = Header::new(&mut stream)?;
+        let end = stream.seek(SeekFrom::End(0))?;
+        Ok(HeaderEditor {
+            stream,
+            raw_header,
+            builder: header.clone().into_builder(),
+            header,
+            end,
+            set_len: None,
+        })
+    }
+
+    /// Returns the header as it was read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{edit::HeaderEditor, Writer};
+    /// let mut editor = HeaderEditor::new(Writer::default().into_inner().unwrap()).unwrap();
+    /// editor.builder().file_source_id = 42;
+    /// assert_eq!(0, editor.header().file_source_id());
+    /// ```
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the builder that the edited header will be built from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{edit::HeaderEditor, Vlr, Writer};
+    /// let mut editor = HeaderEditor::new(Writer::default().into_inner().unwrap()).unwrap();
+    /// editor.builder().vlrs.push(Vlr::default());
+    /// ```
+    pub fn builder(&mut self) -> &mut Builder {
+        &mut self.builder
+    }
+
+    /// Sets the bounds of the edited header, e.g. to fix bounds that don't match the points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{edit::HeaderEditor, Bounds, Reader, Vector, Writer};
+    /// let mut editor = HeaderEditor::new(Writer::default().into_inner().unwrap()).unwrap();
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 1., y: 2., z: 3. },
+    ///     max: Vector { x: 4., y: 5., z: 6. },
+    /// };
+    /// editor.set_bounds(bounds);
+    /// let reader = Reader::new(editor.save().unwrap()).unwrap();
+    /// assert_eq!(bounds, reader.header().bounds());
+    /// ```
+    pub fn set_bounds(&mut self, bounds: Bounds) {
+        self.builder.set_bounds(bounds);
+    }
+
+    /// Returns how many bytes saving would shift the points towards the end of the file.
+    ///
+    /// Points are only shifted if the header and vlrs grow past the vlr padding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{edit::HeaderEditor, Vlr, Writer};
+    /// let mut editor = HeaderEditor::new(Writer::default().into_inner().unwrap()).unwrap();
+    /// assert_eq!(0, editor.point_data_shift().unwrap());
+    /// editor.builder().vlrs.push(Vlr::default());
+    /// assert_eq!(54, editor.point_data_shift().unwrap());
+    /// ```
+    pub fn point_data_shift(&self) -> Result<u64> {
+        self.layout().map(|layout| layout.shift)
+    }
+
+    /// Writes the edited header, vlrs, and evlrs, and returns the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{edit::HeaderEditor, Reader, Vlr, Writer};
+    /// let mut writer = Writer::default();
+    /// writer.write_point(Default::default()).unwrap();
+    /// let mut editor = HeaderEditor::new(writer.into_inner().unwrap()).unwrap();
+    /// editor.builder().vlrs.push(Vlr::default());
+    /// let mut reader = Reader::new(editor.save().unwrap()).unwrap();
+    /// assert_eq!(1, reader.header().vlrs().len());
+    /// assert!(reader.read_point().unwrap().is_some());
+    /// ```
+    pub fn save(mut self) -> Result<T> {
+        let layout = self.layout()?;
+        let offset_to_point_data = u64::from(self.raw_header.offset_to_point_data);
+        let start_of_first_evlr = self.start_of_first_evlr();
+        if layout.shift > 0 {
+            shift(
+                &mut self.stream,
+                offset_to_point_data,
+                start_of_first_evlr,
+                layout.shift,
+            )?;
+            if self.header.point_format().is_compressed {
+                shift_chunk_table_offset(
+                    &mut self.stream,
+                    offset_to_point_data + layout.shift,
+                    layout.shift,
+                )?;
+            }
+        }
+        if !self.header.evlrs().is_empty() || !layout.header.evlrs().is_empty() {
+            let _ = self
+                .stream
+                .seek(SeekFrom::Start(layout.start_of_first_evlr))?;
+            let mut write = BufWriter::new(&mut self.stream);
+            for evlr in layout.header.evlrs() {
+                evlr.clone().into_raw(true)?.write_to(&mut write)?;
+            }
+            write.flush()?;
+            drop(write);
+            let end = self.stream.stream_position()?;
+            if let Some(set_len) = self.set_len
+                && end < self.end + layout.shift
+            {
+                set_len(&mut self.stream, end)?;
+            }
+        }
+        let _ = self.stream.seek(SeekFrom::Start(0))?;
+        let mut write = BufWriter::new(&mut self.stream);
+        layout.header.write_to(&mut write)?;
+        write.flush()?;
+        drop(write);
+        let _ = self.stream.seek(SeekFrom::Start(0))?;
+        Ok(self.stream)
+    }
+
+    /// Returns where the evlrs start in the file as read, or its end if there aren't any.
+    fn start_of_first_evlr(&self) -> u64 {
+        match self.raw_header.evlr {
+            Some(evlr) if evlr.number_of_evlrs > 0 => evlr.start_of_first_evlr,
+            _ => self.end,
+        }
+    }
+
+    /// Builds the edited header, padded or shifted to fit in front of the points.
+    fn layout(&self) -> Result<Layout> {
+        if self.builder.point_format != *self.header.point_format() {
+            return Err(Error::InvalidHeaderEdit("the point format changed"));
+        }
+        let offset_to_point_data = u64::from(self.raw_header.offset_to_point_data);
+        let mut builder = self.builder.clone();
+        let header = builder.clone().into_header()?;
+        let needed = header.offsets().offset_to_point_data;
+        let shift = needed.saturating_sub(offset_to_point_data);
+        if shift > 0
+            && self
+                .header
+                .vlrs()
+                .iter()
+                .any(|vlr| vlr.user_id == COPC_USER_ID)
+        {
+            return Err(Error::InvalidHeaderEdit(
+                "the points of a copc file can't be moved",
+            ));
+        }
+        if needed < offset_to_point_data {
+            let padding = vec![0; (offset_to_point_data - needed) as usize];
+            let _ = builder.vlr_padding.splice(0..0, padding);
+        }
+        let start_of_first_evlr = self.start_of_first_evlr() + shift;
+        if let Some(start) = builder.start_of_waveform_data_packet_record
+            && start >= offset_to_point_data
+        {
+            builder.start_of_waveform_data_packet_record = Some(start + shift);
+        }
+        let mut position = start_of_first_evlr;
+        for evlr in &builder.evlrs {
+            if evlr.user_id == waveform::USER_ID && evlr.record_id == waveform::RECORD_ID {
+                builder.start_of_waveform_data_packet_record = Some(position);
+            }
+            position += evlr.total_len(true) as u64;
+        }
+        let mut header = builder.into_header()?;
+        header.set_start_of_first_evlr(start_of_first_evlr);
+        Ok(Layout {
+            header,
+            shift,
+            start_of_first_evlr,
+        })
+    }
+}
+
+/// Moves the bytes from `start` to `end` towards the end of the stream, starting with the last
+/// ones so nothing is overwritten before it's moved.
+fn shift<T: Read + Write + Seek>(stream: &mut T, start: u64, end: u64, by: u64) -> Result<()> {
+    let mut buffer = vec![0; SHIFT_BUFFER_LEN];
+    let mut position = end;
+    while position > start {
+        let len = (position - start).min(SHIFT_BUFFER_LEN as u64) as usize;
+        position -= len as u64;
+        let _ = stream.seek(SeekFrom::Start(position))?;
+        stream.read_exact(&mut buffer[..len])?;
+        let _ = stream.seek(SeekFrom::Start(position + by))?;
+        stream.write_all(&buffer[..len])?;
+    }
+    Ok(())
+}
+
+/// Moves the offset to the laz chunk table, which starts the compressed point data, along with
+/// the points.
+fn shift_chunk_table_offset<T: Read + Write + Seek>(
+    stream: &mut T,
+    offset_to_point_data: u64,
+    by: u64,
+) -> Result<()> {
+    let _ = stream.seek(SeekFrom::Start(offset_to_point_data))?;
+    let offset = stream.read_i64::<LittleEndian>()?;
+    // Streaming compressors write -1 when the chunk table's offset is only known at the end.
+    if offset > 0 {
+        let _ = stream.seek(SeekFrom::Start(offset_to_point_data))?;
+        stream.write_i64::<LittleEndian>(offset + i64::try_from(by)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{synthetic::fixture, Point, Vlr};
+    use std::io::Cursor;
+
+    fn points(builder: Builder) -> Cursor<Vec<u8>> {
+        let points = (0..3).map(|i| Point {
+            x: f64::from(i),
+            ..Default::default()
+        });
+        Cursor::new(fixture::write(builder, points))
+    }
+
+    fn read_xs(cursor: Cursor<Vec<u8>>) -> (Header, Vec<f64>) {
+        let mut reader = fixture::reader(cursor.into_inner());
+        let xs = reader.points().map(|point| point.unwrap().x).collect();
+        (reader.header().clone(), xs)
+    }
+
+    #[test]
+    fn shrink_into_padding() {
+        let mut builder = Builder::from((1, 2));
+        builder.vlrs.push(Vlr {
+            data: vec![1; 100],
+            ..Default::default()
+        });
+        let cursor = points(builder);
+        let len = cursor.get_ref().len();
+        let mut editor = HeaderEditor::new(cursor).unwrap();
+        editor.builder().vlrs.clear();
+        assert_eq!(0, editor.point_data_shift().unwrap());
+        let cursor = editor.save().unwrap();
+        assert_eq!(len, cursor.get_ref().len());
+        let (header, xs) = read_xs(cursor);
+        assert!(header.vlrs().is_empty());
+        assert_eq!(154, header.vlr_padding_len());
+        assert_eq!(vec![0., 1., 2.], xs);
+    }
+
+    #[test]
+    fn grow_and_shift() {
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            data: vec![2; 10],
+            ..Default::default()
+        });
+        let mut editor = HeaderEditor::new(points(builder)).unwrap();
+        editor.builder().vlrs.push(Vlr {
+            data: vec![1; 1000],
+            ..Default::default()
+        });
+        editor.builder().evlrs[0].data = vec![3; 5];
+        assert_eq!(1054, editor.point_data_shift().unwrap());
+        let (header, xs) = read_xs(editor.save().unwrap());
+        assert_eq!(vec![1; 1000], header.vlrs()[0].data);
+        assert_eq!(vec![3; 5], header.evlrs()[0].data);
+        assert_eq!(vec![0., 1., 2.], xs);
+    }
+
+    #[test]
+    fn shift_in_chunks() {
+        let bytes: Vec<u8> = (0..SHIFT_BUFFER_LEN * 5 / 2).map(|i| i as u8).collect();
+        let mut cursor = Cursor::new(bytes.clone());
+        shift(&mut cursor, 10, bytes.len() as u64, 7).unwrap();
+        let shifted = cursor.into_inner();
+        assert_eq!(bytes[..17], shifted[..17]);
+        assert_eq!(bytes[10..], shifted[17..]);
+    }
+
+    #[test]
+    fn add_evlr() {
+        let mut editor = HeaderEditor::new(points(Builder::from((1, 4)))).unwrap();
+        editor.builder().evlrs.push(Vlr {
+            data: vec![4; 10],
+            ..Default::default()
+        });
+        let (header, xs) = read_xs(editor.save().unwrap());
+        assert_eq!(vec![4; 10], header.evlrs()[0].data);
+        assert_eq!(vec![0., 1., 2.], xs);
+    }
+
+    #[test]
+    fn point_format_changed() {
+        let mut editor = HeaderEditor::new(points(Builder::from((1, 4)))).unwrap();
+        editor.builder().point_format.has_color = true;
+        assert!(matches!(
+            editor.save().unwrap_err(),
+            Error::InvalidHeaderEdit(_)
+        ));
+    }
+
+    #[test]
+    fn truncate_file() {
+        let path = std::env::temp_dir().join("las-rs-edit-truncate-file.las");
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            data: vec![2; 100],
+            ..Default::default()
+        });
+        std::fs::write(&path, fixture::write(builder, [Point::default()])).unwrap();
+        let len = std::fs::metadata(&path).unwrap().len();
+        let mut editor = HeaderEditor::open(&path).unwrap();
+        editor.builder().evlrs.clear();
+        let _ = editor.save().unwrap();
+        assert_eq!(len - 160, std::fs::metadata(&path).unwrap().len());
+        assert!(crate::read_header(&path).unwrap().evlrs().is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn shift_compressed() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.is_compressed = true;
+        let mut editor = HeaderEditor::new(points(builder)).unwrap();
+        editor.builder().vlrs.push(Vlr {
+            data: vec![1; 10],
+            ..Default::default()
+        });
+        let (header, xs) = read_xs(editor.save().unwrap());
+        assert_eq!(2, header.vlrs().len());
+        assert_eq!(vec![0., 1., 2.], xs);
+    }
+}
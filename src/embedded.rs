@@ -0,0 +1,164 @@
+use std::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom, Write};
+
+/// A stream that starts at an offset inside another stream, e.g. a las file after a container's
+/// preamble.
+///
+/// Positions are relative to the offset, so the las file's own offsets work as they are, and a
+/// seek to before the offset is an error. The end is the end of the inner stream. Made by
+/// [Reader::new_at](crate::Reader::new_at) and [Writer::new_at](crate::Writer::new_at).
+///
+/// # Examples
+///
+/// ```
+/// use las::Embedded;
+/// use std::io::{Cursor, Read, Seek, SeekFrom};
+///
+/// let mut embedded = Embedded::new(Cursor::new(b"preamble:data".to_vec()), 9).unwrap();
+/// let mut data = String::new();
+/// embedded.read_to_string(&mut data).unwrap();
+/// assert_eq!("data", data);
+/// assert!(embedded.seek(SeekFrom::Current(-5)).is_err());
+/// ```
+#[derive(Debug)]
+pub struct Embedded<T> {
+    inner: T,
+    base: u64,
+}
+
+impl<T: Seek> Embedded<T> {
+    /// Wraps a stream, and moves it to the offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Embedded;
+    /// use std::io::{Cursor, Seek};
+    /// let mut embedded = Embedded::new(Cursor::new(vec![0; 10]), 4).unwrap();
+    /// assert_eq!(0, embedded.stream_position().unwrap());
+    /// assert_eq!(4, embedded.get_mut().stream_position().unwrap());
+    /// ```
+    pub fn new(mut inner: T, base: u64) -> Result<Embedded<T>> {
+        let _ = inner.seek(SeekFrom::Start(base))?;
+        Ok(Embedded { inner, base })
+    }
+
+    /// Wraps a stream, with the offset at its current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Embedded;
+    /// use std::io::{Cursor, Seek, SeekFrom};
+    /// let mut cursor = Cursor::new(vec![0; 10]);
+    /// cursor.seek(SeekFrom::Start(3)).unwrap();
+    /// assert_eq!(3, Embedded::here(cursor).unwrap().base());
+    /// ```
+    pub fn here(mut inner: T) -> Result<Embedded<T>> {
+        let base = inner.stream_position()?;
+        Ok(Embedded { inner, base })
+    }
+}
+
+impl<T> Embedded<T> {
+    /// Returns the offset in the inner stream where this one starts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Embedded;
+    /// use std::io::Cursor;
+    /// assert_eq!(4, Embedded::new(Cursor::new(vec![0; 10]), 4).unwrap().base());
+    /// ```
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    /// Returns a reference to the inner stream.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner stream.
+    ///
+    /// Its position is the absolute one, not relative to the offset.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Returns the inner stream.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns an absolute target, or an error if it's before the offset.
+    fn not_below_base(&self, target: Option<u64>) -> Result<u64> {
+        target.filter(|&target| target >= self.base).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "seek to before the start of the embedded stream at {}",
+                    self.base
+                ),
+            )
+        })
+    }
+}
+
+impl<T: Read> Read for Embedded<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<T: Write> Write for Embedded<T> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: Seek> Seek for Embedded<T> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(n) => SeekFrom::Start(self.base.checked_add(n).ok_or_else(|| {
+                Error::new(ErrorKind::InvalidInput, "seek past the end of the stream")
+            })?),
+            SeekFrom::Current(n) => {
+                let position = self.inner.stream_position()?;
+                SeekFrom::Start(self.not_below_base(position.checked_add_signed(n))?)
+            }
+            SeekFrom::End(n) => {
+                let position = self.inner.stream_position()?;
+                let end = self.inner.seek(SeekFrom::End(0))?;
+                let target = end.checked_add_signed(n);
+                if target.is_none_or(|target| target < self.base) {
+                    let _ = self.inner.seek(SeekFrom::Start(position))?;
+                }
+                SeekFrom::Start(self.not_below_base(target)?)
+            }
+        };
+        Ok(self.inner.seek(pos)? - self.base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn seek() {
+        let mut embedded = Embedded::new(Cursor::new(vec![0; 10]), 4).unwrap();
+        assert_eq!(2, embedded.seek(SeekFrom::Start(2)).unwrap());
+        assert_eq!(6, embedded.get_ref().position());
+        assert_eq!(3, embedded.seek(SeekFrom::Current(1)).unwrap());
+        assert_eq!(5, embedded.seek(SeekFrom::End(-1)).unwrap());
+        assert_eq!(0, embedded.seek(SeekFrom::Current(-5)).unwrap());
+        assert!(embedded.seek(SeekFrom::Current(-1)).is_err());
+        assert!(embedded.seek(SeekFrom::End(-7)).is_err());
+        assert_eq!(0, embedded.stream_position().unwrap());
+    }
+}
@@ -1,3 +1,10 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+#[cfg(feature = "std")]
+use crate::{
+    header::{Incompatibility, ReturnCountConflict},
+    point::FieldMismatch,
+};
 use crate::{point::Format, Transform, Version};
 use thiserror::Error;
 
@@ -13,6 +20,280 @@ pub enum Error {
     #[error("the header is too large ({0} bytes) to convert to a raw header")]
     HeaderTooLarge(usize),
 
+    /// There are not enough bytes to hold the header.
+    #[error("the header needs {expected} bytes, but only {len} were given")]
+    HeaderTooShort {
+        /// The number of bytes the header needs.
+        expected: usize,
+
+        /// The number of bytes given.
+        len: usize,
+    },
+
+    /// There are not enough bytes to hold a point.
+    #[error("the point needs {expected} bytes, but only {len} were given")]
+    PointTooShort {
+        /// The number of bytes the point needs.
+        expected: usize,
+
+        /// The number of bytes given.
+        len: usize,
+    },
+
+    /// A raw point record isn't as long as the point format's records.
+    #[error("the point record must be {expected} bytes, but it is {len} bytes")]
+    InvalidRecordLength {
+        /// The length of the point format's records.
+        expected: usize,
+
+        /// The length of the record.
+        len: usize,
+    },
+
+    /// There are not enough bytes to hold a vlr.
+    #[error("the vlr needs {expected} bytes, but only {len} were given")]
+    VlrTooShort {
+        /// The number of bytes the vlr needs.
+        expected: u64,
+
+        /// The number of bytes given.
+        len: usize,
+    },
+
+    /// Points have different numbers of extra bytes, so no one format can hold them all.
+    #[error("point {index} has {len} extra bytes, but the points before it have {expected}")]
+    InconsistentExtraBytes {
+        /// The index of the first point with a different number of extra bytes.
+        index: usize,
+
+        /// The number of extra bytes on the points before it.
+        expected: usize,
+
+        /// The number of extra bytes on the point.
+        len: usize,
+    },
+
+    /// The cell size of a grid isn't a positive number.
+    #[error("invalid cell size: {0}")]
+    InvalidCellSize(f64),
+
+    /// A density grid would have too many cells to allocate.
+    #[error("a density grid of {width} by {height} cells is too large")]
+    DensityGridTooLarge {
+        /// The number of columns.
+        width: usize,
+        /// The number of rows.
+        height: usize,
+    },
+
+    /// The header size field is smaller than the standard header for the version.
+    #[error("the header size {header_size} is too small for las {version}, which needs at least {minimum} bytes")]
+    HeaderSizeTooSmall {
+        /// The las version.
+        version: Version,
+
+        /// The header size field.
+        header_size: u16,
+
+        /// The size of the standard header for the version.
+        minimum: u16,
+    },
+
+    /// A transform can't store a coordinate at the edge of the source's bounds.
+    #[error("the {axis} transform {transform} cannot store the bound {value}")]
+    BoundsNotRepresentable {
+        /// The axis, `x`, `y`, or `z`.
+        axis: char,
+
+        /// The bound.
+        value: f64,
+
+        /// The transform.
+        transform: Transform,
+    },
+
+    /// A streamed evlr wasn't finished before the writer was closed.
+    #[error("a streamed evlr was not finished")]
+    UnfinishedEvlr,
+
+    /// Points can't be written once an evlr has been streamed.
+    #[error("points cannot be written after an evlr has been streamed")]
+    PointsAfterEvlr,
+
+    /// A point's coordinate is NaN or infinite.
+    #[error("the {axis} coordinate {value} is not finite")]
+    NonFiniteCoordinate {
+        /// The axis, `x`, `y`, or `z`.
+        axis: char,
+
+        /// The coordinate.
+        value: f64,
+    },
+
+    /// A point's coordinate would be stored more than half the scale away from its value.
+    #[error("the {axis} coordinate {value} would be stored as {stored}")]
+    PrecisionLoss {
+        /// The axis, `x`, `y`, or `z`.
+        axis: char,
+
+        /// The coordinate.
+        value: f64,
+
+        /// The coordinate as it would be stored.
+        stored: f64,
+    },
+
+    /// The extra bytes vlr is invalid.
+    #[error("invalid extra bytes vlr: {0}")]
+    InvalidExtraBytesVlr(String),
+
+    /// An extra bytes attribute can't store a point's value, and has no no data value.
+    #[error("the attribute of point {index} cannot store {value}, and has no no data value")]
+    AttributeNotRepresentable {
+        /// The index of the point.
+        index: u64,
+
+        /// The value.
+        value: f64,
+    },
+
+    /// A chunk index is past the last chunk.
+    #[error("chunk {index} is out of range, there are {chunks} chunks")]
+    ChunkOutOfRange {
+        /// The chunk index.
+        index: usize,
+
+        /// The number of chunks.
+        chunks: usize,
+    },
+
+    /// A point's gps time is before the previous point's, see
+    /// [WriteOptions::require_gps_time_order](crate::writer::WriteOptions::require_gps_time_order).
+    #[error(
+        "the gps time {time} is before the gps time {previous_time} of point {previous_index}"
+    )]
+    GpsTimeOutOfOrder {
+        /// The point's gps time.
+        time: f64,
+
+        /// The index of the previous point.
+        previous_index: u64,
+
+        /// The previous point's gps time.
+        previous_time: f64,
+    },
+
+    /// A point has no gps time, but the gps time order is checked.
+    #[error("the point has no gps time, but the gps time order is required")]
+    MissingGpsTime,
+
+    /// The point format isn't one this crate knows, so its points can only be read as raw
+    /// records.
+    #[error("point format {0} is unknown, so its points can only be read as raw records")]
+    UnknownPointFormat(u8),
+
+    /// A vlr's description can't be written.
+    #[error("the description of vlr {user_id} {record_id} is invalid: {source}")]
+    InvalidVlrDescription {
+        /// The vlr's user id.
+        user_id: String,
+
+        /// The vlr's record id.
+        record_id: u16,
+
+        /// Why the description is invalid.
+        source: Box<Error>,
+    },
+
+    /// A raw header's legacy and extended return counts disagree.
+    #[cfg(feature = "std")]
+    #[error(
+        "the legacy return counts {:?} don't match the extended return counts {:?}",
+        .0.legacy,
+        .0.extended
+    )]
+    ReturnCountConflict(Box<ReturnCountConflict>),
+
+    /// A [PointDataDigest](crate::PointDataDigest) record can't be decoded.
+    #[error("invalid point data digest: {0}")]
+    InvalidPointDataDigest(String),
+
+    /// A [ClassificationMap](crate::ClassificationMap) sends a class to one that the point format
+    /// can't hold.
+    #[error("the classification map sends {from} to {to}, which point format {format} can't hold")]
+    ClassificationNotRepresentable {
+        /// The source class.
+        from: u8,
+
+        /// The class it's mapped to.
+        to: u8,
+
+        /// The point format.
+        format: Format,
+    },
+
+    /// A [ResumeToken](crate::reader::ResumeToken) came from a reader over a different file.
+    #[error("the resume token is from a different file")]
+    ResumeTokenMismatch,
+
+    /// Writing to the sink failed earlier, so the writer can't write or close.
+    #[error("the writer is poisoned by an earlier failure: {0}")]
+    WriterPoisoned(String),
+
+    /// The file has no copc info vlr, so it isn't a COPC file.
+    #[error("the file has no copc info vlr")]
+    NotCopc,
+
+    /// A COPC hierarchy entry points at a hierarchy page that was already read.
+    #[cfg(feature = "laz")]
+    #[error("the copc hierarchy page at offset {0} is referenced more than once")]
+    CyclicCopcHierarchy(u64),
+
+    /// A COPC file can only have point format 6, 7, or 8.
+    #[error("copc files need point format 6, 7, or 8, not {0}")]
+    InvalidCopcFormat(Format),
+
+    /// The extra bytes schema has no attribute with this name, or the point's extra bytes are too
+    /// short to hold it.
+    #[error("extra bytes attribute not found: {0}")]
+    ExtraAttributeNotFound(String),
+
+    /// An extra bytes attribute's value can't be represented by the requested or stored type.
+    #[error("extra bytes attribute {name} can't represent {value}")]
+    ExtraAttributeNotRepresentable {
+        /// The name of the attribute.
+        name: String,
+
+        /// The value.
+        value: f64,
+    },
+
+    /// A point field can't hold a value set through a [Dimension](crate::point::Dimension).
+    #[error("{dimension} can't hold {value}")]
+    InvalidDimensionValue {
+        /// The name of the dimension.
+        dimension: String,
+
+        /// The value.
+        value: f64,
+    },
+
+    /// A [Reprojection](crate::writer::Reprojection) couldn't reproject some coordinates.
+    #[error("reprojection failed: {0}")]
+    ReprojectionFailed(String),
+
+    /// Points can't be appended to an existing file.
+    #[error("points cannot be appended to this file: {0}")]
+    CannotAppend(&'static str),
+
+    /// Points can't be written to a [NonSeekable](crate::writer::NonSeekable) sink.
+    #[error("points cannot be written to a sink that can't seek: {0}")]
+    CannotStream(&'static str),
+
+    /// A [HeaderEditor](crate::edit::HeaderEditor) can't write an edited header in place.
+    #[error("the header cannot be edited in place: {0}")]
+    InvalidHeaderEdit(&'static str),
+
     /// The seek index used was too large
     #[error("Seek Index reached the end: {0}")]
     SeekIndexOutOfBounds(u64),
@@ -26,10 +307,44 @@ pub enum Error {
     #[error("Entry referenced a page that is not present")]
     ReferencedPageMissingFromEvlr(crate::copc::Entry),
 
+    /// The day of the year isn't in the year, or the year doesn't fit in the header.
+    #[error("invalid date: day {day_of_year} of {year}")]
+    InvalidDate {
+        /// The year.
+        year: i32,
+
+        /// The day of the year.
+        day_of_year: u32,
+    },
+
+    /// The gps time is NaN or infinite.
+    #[error("invalid gps time: {0}")]
+    InvalidGpsTime(f64),
+
+    /// There isn't enough vlr padding to insert a vlr in place.
+    #[error("inserting the vlr requires {required} bytes, but only {available} bytes of vlr padding are available")]
+    InsufficientVlrPadding {
+        /// The number of bytes the vlr needs, including its header.
+        required: u64,
+
+        /// The number of bytes between the last vlr and the point data.
+        available: u64,
+    },
+
     /// The file signature is not LASF.
     #[error("the file signature is not 'LASF': {0:?}")]
     InvalidFileSignature([u8; 4]),
 
+    /// The file starts with the gzip magic bytes, so it's a gzip-compressed file, not a las file.
+    #[error(
+        "the file is gzip-compressed, decompress it (e.g. with gunzip) before reading it as las"
+    )]
+    GzippedInput,
+
+    /// The file starts with the zip magic bytes, so it's a zip archive, not a las file.
+    #[error("the file is a zip archive, extract the las file from it before reading it")]
+    ZippedInput,
+
     /// The value can't have the inverse transform applied.
     #[error("the transform {transform} cannot be inversely applied to {n}")]
     InvalidInverseTransform {
@@ -40,12 +355,41 @@ pub enum Error {
         transform: Transform,
     },
 
+    /// The lax spatial index is invalid.
+    #[error("invalid lax spatial index: {0}")]
+    InvalidLax(String),
+
+    /// The user data dictionary is invalid.
+    #[error("invalid user data dictionary: {0}")]
+    InvalidUserDataDictionary(String),
+
+    /// The classification lookup vlr is invalid.
+    #[error("invalid classification lookup: {0}")]
+    InvalidClassificationLookup(String),
+
+    /// This vlr isn't the kind of record it was converted to.
+    #[error("vlr {user_id} {record_id} isn't a {expected} record")]
+    UnexpectedVlr {
+        /// The vlr's user id.
+        user_id: String,
+
+        /// The vlr's record id.
+        record_id: u16,
+
+        /// The kind of record that was expected.
+        expected: &'static str,
+    },
+
     /// This is an invalid point format.
     ///
     /// It has a combination of options that can't exist.
     #[error("invalid point format: {0}")]
     InvalidPointFormat(Format),
 
+    /// This extended point format has no legacy equivalent.
+    #[error("there is no legacy point format for {0}")]
+    NoLegacyFormat(Format),
+
     /// This is an invalid format number.
     #[error("invalid format number: {0}")]
     InvalidPointFormatNumber(u8),
@@ -55,11 +399,14 @@ pub enum Error {
     InvalidScannerChannel(u8),
 
     /// [std::io::Error]
+    #[cfg(feature = "std")]
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
     /// The las data is laszip compressed.
-    #[error("the las data is laszip compressed, but the las crate is not built with laz support")]
+    #[error(
+        "the las data is laszip compressed, but the las crate is not built with laz support, enable its `laz` feature"
+    )]
     LaszipNotEnabled,
 
     /// [laz::LasZipError]
@@ -72,6 +419,32 @@ pub enum Error {
     #[error("laszip vlr not found")]
     LasZipVlrNotFound,
 
+    /// The laz chunk table doesn't fit in the file.
+    #[cfg(feature = "laz")]
+    #[error("invalid laz chunk table: {0}")]
+    InvalidChunkTable(String),
+
+    /// [arrow::error::ArrowError]
+    #[cfg(feature = "arrow")]
+    #[error(transparent)]
+    Arrow(#[from] arrow::error::ArrowError),
+
+    /// A record batch doesn't have a column that the point format needs.
+    #[cfg(feature = "arrow")]
+    #[error("the record batch has no column named {0}")]
+    ColumnNotFound(String),
+
+    /// A record batch has a null where the point format needs a value.
+    #[cfg(feature = "arrow")]
+    #[error("column {column} is null at row {index}")]
+    NullValue {
+        /// The name of the column.
+        column: String,
+
+        /// The row of the null.
+        index: usize,
+    },
+
     /// This string is not ASCII.
     #[error("this string is not ascii: {0}")]
     NotAscii(String),
@@ -97,17 +470,133 @@ pub enum Error {
     OverlapClassification,
 
     /// The attributes of the point format and point do not match.
-    #[error("the attributes of the point format ({0}) do not match the point")]
-    PointAttributesDoNotMatch(Format),
+    #[cfg(feature = "std")]
+    #[error(
+        "the attributes of the point format ({format}) do not match the point: {}",
+        .mismatches.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    PointAttributesDoNotMatch {
+        /// The point format.
+        format: Format,
+
+        /// Every way that the point doesn't match the format.
+        mismatches: Vec<FieldMismatch>,
+    },
 
     /// The point data record length is too small for the format.
-    #[error("the point data record length {len} is too small for format {format}")]
+    #[error(
+        "the point data record length {len} is too small for format {format}, which needs at least {minimum} bytes"
+    )]
     PointDataRecordLengthTooLarge {
         /// The point format.
         format: Format,
 
         /// The length of the point data record.
         len: u16,
+
+        /// The minimum length of a point data record for the format.
+        minimum: u16,
+    },
+
+    /// Building a spatial index needs bounds.
+    #[error(
+        "a spatial index needs bounds, either from the header or from WriteOptions::index_bounds"
+    )]
+    SpatialIndexBoundsRequired,
+
+    /// A write option conflicts with the header it's used with.
+    #[error("the write option {option} conflicts with the header: {reason}")]
+    InvalidWriteOption {
+        /// The name of the option.
+        option: &'static str,
+
+        /// Why the option can't be used.
+        reason: String,
+    },
+
+    /// A coordinate resolution isn't a positive, finite number.
+    #[error("invalid coordinate resolution: {0}")]
+    InvalidResolution(f64),
+
+    /// A fixed laz chunk size is zero, or too big to tell apart from variable size chunks.
+    #[error("invalid laz chunk size: {0}")]
+    InvalidLazChunkSize(u32),
+
+    /// A point couldn't be written.
+    #[error("could not write point {index}: {source}")]
+    PointWriteFailed {
+        /// The index of the point in the input, starting at zero.
+        ///
+        /// Points left out by a skip policy are counted.
+        index: u64,
+
+        /// The reason the point couldn't be written.
+        source: Box<Error>,
+    },
+
+    /// A point couldn't be read.
+    #[error("could not read point {index}: {source}")]
+    PointReadFailed {
+        /// The index of the point in the source, starting at zero.
+        ///
+        /// For compressed points, this is the first point of the batch that failed to decompress.
+        index: u64,
+
+        /// The byte offset of the point record in the source, if the points are uncompressed.
+        offset: Option<u64>,
+
+        /// The reason the point couldn't be read.
+        source: Box<Error>,
+    },
+
+    /// The header couldn't be read.
+    #[error("could not read at byte {offset}: {source}")]
+    ReadFailed {
+        /// The byte offset in the source where reading stopped.
+        offset: u64,
+
+        /// The reason reading failed.
+        source: Box<Error>,
+    },
+
+    /// A vlr couldn't be read.
+    #[error("could not read vlr {index} at byte {offset}: {source}")]
+    VlrReadFailed {
+        /// The index of the vlr.
+        index: u32,
+
+        /// The byte offset in the source where reading stopped.
+        offset: u64,
+
+        /// The reason reading failed.
+        source: Box<Error>,
+    },
+
+    /// An evlr couldn't be read.
+    #[error("could not read evlr {index} at byte {offset}: {source}")]
+    EvlrReadFailed {
+        /// The index of the evlr.
+        index: u32,
+
+        /// The byte offset in the source where reading stopped.
+        offset: u64,
+
+        /// The reason reading failed.
+        source: Box<Error>,
+    },
+
+    /// Reading stopped at a point that couldn't be read, and the rest of the points were given
+    /// up on, see [ReadOptions::recover_truncated](crate::reader::ReadOptions::recover_truncated).
+    #[error("{lost} point records were lost, starting at point {index}: {source}")]
+    PointsLost {
+        /// The index of the first point that couldn't be read.
+        index: u64,
+
+        /// The number of points in the header that weren't read, starting at `index`.
+        lost: u64,
+
+        /// The reason the point couldn't be read.
+        source: Box<Error>,
     },
 
     /// Point padding is only allowed when evlrs are present.
@@ -138,6 +627,10 @@ pub enum Error {
     #[error("too many extended variable length records: {0}")]
     TooManyEvlrs(usize),
 
+    /// The extra bytes don't fit in a point data record alongside the format's fields.
+    #[error("{0} extra bytes don't fit in a point data record")]
+    TooManyExtraBytes(u16),
+
     /// Too many points for this version.
     #[error("too many points for version {version}: {n}")]
     TooManyPoints {
@@ -152,9 +645,9 @@ pub enum Error {
     #[error("too many variable length records: {0}")]
     TooManyVlrs(usize),
 
-    /// [std::num::TryFromIntError]
+    /// [core::num::TryFromIntError]
     #[error(transparent)]
-    TryFromIntError(#[from] std::num::TryFromIntError),
+    TryFromIntError(#[from] core::num::TryFromIntError),
 
     /// Feature is not supported by version.
     #[error("feature {feature} is not supported by version {version}")]
@@ -183,9 +676,110 @@ pub enum Error {
         i32,
     ),
 
-    /// [std::str::Utf8Error]
+    /// [core::str::Utf8Error]
     #[error(transparent)]
-    Utf8(#[from] std::str::Utf8Error),
+    Utf8(#[from] core::str::Utf8Error),
+
+    /// The point doesn't have a waveform packet.
+    #[error("the point does not have a waveform packet")]
+    NoWaveform,
+
+    /// The header doesn't say where the waveform data packets are, or the waveform data packet
+    /// record is missing.
+    #[error("the file does not have any waveform data packets")]
+    NoWaveformDataPackets,
+
+    /// The waveform data packets are in a `.wdp` file, but the reader doesn't know where it is.
+    #[error("the waveform data packets are in a .wdp file, but the reader was not given its path")]
+    WaveformFileRequired,
+
+    /// The `.wdp` file couldn't be opened.
+    #[cfg(feature = "std")]
+    #[error("could not open waveform data packet file {}: {source}", path.display())]
+    WaveformFile {
+        /// The path to the `.wdp` file.
+        path: std::path::PathBuf,
+
+        /// The reason the file couldn't be opened.
+        source: std::io::Error,
+    },
+
+    /// A waveform packet runs past the end of the waveform data.
+    #[error("the waveform packet at offset {offset} with {size} bytes does not fit in the waveform data ({len} bytes)")]
+    WaveformPacketOutOfBounds {
+        /// The byte offset of the packet.
+        offset: u64,
+
+        /// The size of the packet in bytes.
+        size: u32,
+
+        /// The length of the file or record holding the waveform data.
+        len: u64,
+    },
+
+    /// A waveform packet descriptor doesn't have the right number of bytes.
+    #[error("a waveform packet descriptor has 26 bytes, not {0}")]
+    InvalidWaveformPacketDescriptor(usize),
+
+    /// There's no waveform packet descriptor for this index.
+    #[error("there is no waveform packet descriptor with index {0}")]
+    WaveformPacketDescriptorNotFound(u8),
+
+    /// Compressed waveform packets aren't supported.
+    #[error("unsupported waveform packet compression type: {0}")]
+    UnsupportedWaveformCompression(u8),
+
+    /// Waveform samples of this many bits aren't supported.
+    #[error("unsupported number of bits per waveform sample: {0}")]
+    UnsupportedBitsPerSample(u8),
+
+    /// A waveform packet doesn't have the number of bytes that its descriptor calls for.
+    #[error("the waveform packet has {actual} bytes, but its descriptor calls for {expected}")]
+    WaveformPacketSize {
+        /// The number of bytes called for by the descriptor.
+        expected: u64,
+
+        /// The number of bytes in the packet.
+        actual: usize,
+    },
+
+    /// Waveform samples are wider than their descriptor's bits per sample.
+    #[error("the waveform samples don't fit in {0} bits per sample")]
+    WaveformSamplesDoNotFit(u8),
+
+    /// Adjusted standard gps time is required for this point format.
+    #[error("adjusted standard gps time is required for {0}")]
+    StandardGpsTimeRequired(Format),
+
+    /// The laszip vlr is too short for the items it lists.
+    #[error("the laszip vlr is too short for its items: {0} bytes")]
+    InvalidLaszipVlr(usize),
+
+    /// There are no inputs to concatenate.
+    #[error("there are no inputs to concatenate")]
+    NoInputs,
+
+    /// An input's raw points can't be copied into the first input's layout.
+    #[cfg(feature = "std")]
+    #[error(
+        "input {index} has a different point layout than the first input: {incompatibilities:?}"
+    )]
+    IncompatibleLayout {
+        /// The index of the input.
+        index: usize,
+
+        /// The ways the input's layout differs from the first input's.
+        incompatibilities: Vec<Incompatibility>,
+    },
+
+    /// An input's coordinate reference system vlrs are different from the first input's.
+    #[error("input {0} has a different crs than the first input")]
+    CrsConflict(usize),
+
+    /// An input isn't compressed with the same laszip items as the first input.
+    #[cfg(feature = "laz")]
+    #[error("input {0} isn't compressed with the same laszip items as the first input")]
+    LazItemsDoNotMatch(usize),
 
     /// Wkt is required for this point format.
     #[error("wkt is required for this point format: {0}")]
@@ -195,6 +789,36 @@ pub enum Error {
     #[error("the vlr is too long: {0}")]
     VlrTooLong(usize),
 
+    /// A vlr runs past the offset to the point data.
+    #[error(
+        "vlr {index} has a record length of {len} bytes, but only {remaining} bytes remain before the point data"
+    )]
+    VlrPastPointData {
+        /// The index of the vlr.
+        index: u32,
+
+        /// The record length after the header, as declared by the vlr.
+        len: u64,
+
+        /// The number of bytes between the end of the vlr's header and the point data.
+        remaining: u64,
+    },
+
+    /// An evlr's declared record length runs past the end of the stream.
+    #[error(
+        "evlr {index} has a record length of {len} bytes, but only {remaining} bytes remain in the stream"
+    )]
+    EvlrPastEnd {
+        /// The index of the evlr.
+        index: u32,
+
+        /// The record length after the header, as declared by the evlr.
+        len: u64,
+
+        /// The number of bytes between the end of the evlr's header and the end of the stream.
+        remaining: u64,
+    },
+
     /// Cannot get EPSG code(s) from user defined CRSes
     #[error("Parsing of User Defined CRS not implemented")]
     UserDefinedCrs,
@@ -212,10 +836,50 @@ pub enum Error {
     UndefinedDataForGeoTiffKey(u16),
 
     /// Cannot get EPSG code(s) from GeoTiff String or Double data
+    #[cfg(feature = "std")]
     #[error("The CRS parser does not handle CRS's defined by Geotiff String and Double data")]
     UnimplementedForGeoTiffStringAndDoubleData(crate::crs::GeoTiffData),
 
     /// Cannot write CRS VLR to the header as it already contains a CRS VLR
     #[error("Cannot write CRS VLR as Header already contains CRS VLR")]
     HeaderContainsCrsVlr,
+
+    /// A crs can't be stored with a builder's version and point format.
+    #[error("the crs can't be stored in a las {version} file with {format}, it needs {needs}")]
+    CrsNotRepresentable {
+        /// The version.
+        version: Version,
+
+        /// The point format.
+        format: Format,
+
+        /// What the crs is missing.
+        needs: &'static str,
+    },
+}
+
+impl Error {
+    /// Returns the byte offset in the source where reading failed, if it's known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut bytes = std::fs::read("tests/data/autzen.las").unwrap();
+    /// bytes.truncate(100);
+    /// let err = Reader::new(Cursor::new(bytes)).err().unwrap();
+    /// assert_eq!(Some(100), err.offset());
+    /// ```
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Error::ReadFailed { offset, .. }
+            | Error::VlrReadFailed { offset, .. }
+            | Error::EvlrReadFailed { offset, .. } => Some(*offset),
+            Error::PointReadFailed { offset, .. } => *offset,
+            Error::PointsLost { source, .. } => source.offset(),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,300 @@
+use crate::{
+    header::SystemIdentifier, Bounds, Error, Header, Point, Reader, Result, Vector, Writer,
+};
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, SeekFrom, Write},
+    ops::Range,
+};
+
+/// Copies the points in an index range to a destination, with the source's layout.
+///
+/// The output header is the source's, with counts and bounds for the copied points. Spatial
+/// indices and COPC info are dropped, since they wouldn't describe the copied points.
+///
+/// Uncompressed records are copied as they are, and their counts and bounds come straight from
+/// the stored integers, so nothing is decoded. A compressed source only decompresses the chunks
+/// that overlap the range, and the points are compressed again.
+///
+/// Records of an [unknown](crate::point::Format::unknown) point format are copied too, but their
+/// fields can't be read, so the output keeps the source's bounds and has no counts by return.
+///
+/// The range is cut off at the last point, so an empty range, or one that starts past the end,
+/// writes a file with no points. Indices count every point, but withheld points are left out if
+/// the reader skips them. The reader's cursor is left at the end of the range.
+///
+/// # Examples
+///
+/// ```
+/// use las::Reader;
+/// use std::io::Cursor;
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let header = las::extract_range(&mut reader, 10..20, Cursor::new(Vec::new())).unwrap();
+/// assert_eq!(10, header.number_of_points());
+/// ```
+pub fn extract_range<R, W>(reader: &mut Reader<R>, range: Range<u64>, dest: W) -> Result<Header>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let number_of_points = reader.header().number_of_points();
+    let end = range.end.min(number_of_points);
+    let start = range.start.min(end);
+    let header = reader.header().to_derived(&SystemIdentifier::Extraction);
+    header
+        .layout_compatible_with(reader.header())
+        .map_err(|incompatibilities| Error::IncompatibleLayout {
+            index: 0,
+            incompatibilities,
+        })?;
+    reader.seek(start)?;
+    if header.point_format().is_compressed {
+        decode(reader, end - start, dest, header)
+    } else {
+        copy_records(reader, end - start, dest, header)
+    }
+}
+
+fn decode<R, W>(reader: &mut Reader<R>, n: u64, dest: W, header: Header) -> Result<Header>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    const BATCH_SIZE: u64 = 1 << 16;
+
+    let mut writer = Writer::new(dest, header)?;
+    let mut points = Vec::new();
+    let end = reader.index() + n;
+    while reader.read_points_before(end, BATCH_SIZE, &mut points)? > 0 {
+        for point in points.drain(..) {
+            writer.write_point(point)?;
+        }
+    }
+    let _ = writer.close()?;
+    Ok(writer.header().clone())
+}
+
+fn copy_records<R, W>(
+    reader: &mut Reader<R>,
+    n: u64,
+    mut dest: W,
+    mut header: Header,
+) -> Result<Header>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek,
+{
+    let format = *header.point_format();
+    let transforms = *header.transforms();
+    let source_bounds = reader.header().bounds();
+    let start = dest.stream_position()?;
+    let _ = header.recompute_offsets();
+    header.write_to(&mut dest)?;
+
+    let mut record = vec![0; usize::from(format.len())];
+    let mut count = 0;
+    let mut number_of_points_by_return = HashMap::new();
+    let mut min = Vector {
+        x: i32::MAX,
+        y: i32::MAX,
+        z: i32::MAX,
+    };
+    let mut max = Vector {
+        x: i32::MIN,
+        y: i32::MIN,
+        z: i32::MIN,
+    };
+    let end = reader.index() + n;
+    while reader.index() < end && reader.read_record(&mut record)? {
+        if reader.index() > end {
+            // The last points in the range were withheld and skipped.
+            break;
+        }
+        count += 1;
+        dest.write_all(&record)?;
+        if format.unknown.is_some() {
+            continue;
+        }
+        let stored = |i: usize| i32::from_le_bytes(record[i..i + 4].try_into().unwrap());
+        let (x, y, z) = (stored(0), stored(4), stored(8));
+        min.x = min.x.min(x);
+        min.y = min.y.min(y);
+        min.z = min.z.min(z);
+        max.x = max.x.max(x);
+        max.y = max.y.max(y);
+        max.z = max.z.max(z);
+        let return_number = if format.is_extended {
+            record[14] & 0b0000_1111
+        } else {
+            record[14] & 0b0000_0111
+        };
+        if return_number > 0 {
+            *number_of_points_by_return.entry(return_number).or_insert(0) += 1;
+        }
+    }
+    if count > 0 && format.unknown.is_some() {
+        header.verify_room_for(count, [])?;
+        header.add_points(count, &number_of_points_by_return, &source_bounds);
+    } else if count > 0 {
+        let mut bounds = Bounds::default();
+        for (x, y, z) in [(min.x, min.y, min.z), (max.x, max.y, max.z)] {
+            bounds.grow(&Point {
+                x: transforms.x.direct(x),
+                y: transforms.y.direct(y),
+                z: transforms.z.direct(z),
+                ..Default::default()
+            });
+        }
+        header.verify_room_for(count, number_of_points_by_return.clone())?;
+        header.add_points(count, &number_of_points_by_return, &bounds);
+    }
+
+    dest.write_all(header.point_padding())?;
+    let start_of_first_evlr = dest.stream_position()?;
+    for evlr in header.evlrs() {
+        evlr.clone().into_raw(true)?.write_to(&mut dest)?;
+    }
+    let end_of_file = dest.stream_position()?;
+    header.set_start_of_first_evlr(start_of_first_evlr);
+    let _ = dest.seek(SeekFrom::Start(start))?;
+    header.write_to(&mut dest)?;
+    let _ = dest.seek(SeekFrom::Start(end_of_file))?;
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        point::Format, reader::ReadOptions, synthetic::fixture, writer::WriteOptions, Builder,
+        GpsTimeType,
+    };
+    use std::{fs::File, io::Cursor};
+
+    fn source(compressed: bool) -> Vec<u8> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(1).unwrap();
+        builder.point_format.is_compressed = compressed;
+        builder.gps_time_type = GpsTimeType::Standard;
+        let options = if compressed {
+            WriteOptions::default().laz_chunk_size(50)
+        } else {
+            WriteOptions::default()
+        };
+        let points = (0..300).map(|i| Point {
+            x: f64::from(i),
+            y: -f64::from(i),
+            z: f64::from(i % 7),
+            return_number: (i % 3) as u8 + 1,
+            number_of_returns: 3,
+            is_withheld: i % 5 == 0,
+            gps_time: Some(f64::from(i)),
+            ..Default::default()
+        });
+        fixture::write_with_options(builder, options, points)
+    }
+
+    fn reader(bytes: &[u8], skip_withheld: bool) -> Reader<Cursor<Vec<u8>>> {
+        let options = ReadOptions::default().skip_withheld(skip_withheld);
+        Reader::with_options(Cursor::new(bytes.to_vec()), options).unwrap()
+    }
+
+    /// Extracts a range and returns the returned header and the points read back.
+    fn extract(
+        name: &str,
+        bytes: &[u8],
+        range: Range<u64>,
+        skip_withheld: bool,
+    ) -> (Header, Vec<u8>) {
+        let path = std::env::temp_dir().join(format!("las-rs-extract-range-{name}.las"));
+        let header = extract_range(
+            &mut reader(bytes, skip_withheld),
+            range,
+            File::create(&path).unwrap(),
+        )
+        .unwrap();
+        let extracted = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        (header, extracted)
+    }
+
+    /// Writes points through a writer, for the header it would make.
+    fn expected_header(bytes: &[u8], points: &[Point]) -> Header {
+        let mut writer = Writer::new(
+            Cursor::new(Vec::new()),
+            reader(bytes, false).header().to_empty(),
+        )
+        .unwrap();
+        for point in points {
+            writer.write_point_ref(point).unwrap();
+        }
+        let _ = writer.close().unwrap();
+        writer.header().clone()
+    }
+
+    fn check(name: &str, compressed: bool) {
+        let bytes = source(compressed);
+        let points = reader(&bytes, false).into_points().unwrap();
+        for (i, (range, skip_withheld)) in [
+            (10..240, false),
+            (10..240, true),
+            (10..241, true),
+            (0..300, false),
+            (250..1000, false),
+            (5..5, false),
+            (400..500, false),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let (header, extracted) =
+                extract(&format!("{name}-{i}"), &bytes, range.clone(), skip_withheld);
+            let end = range.end.min(300) as usize;
+            let start = (range.start as usize).min(end);
+            let expected: Vec<Point> = points[start..end]
+                .iter()
+                .filter(|point| !(skip_withheld && point.is_withheld))
+                .cloned()
+                .collect();
+            let written = reader(&extracted, false);
+            assert_eq!(compressed, written.header().point_format().is_compressed);
+            assert_eq!(
+                SystemIdentifier::Extraction,
+                written.header().system_identifier_kind()
+            );
+            let expected_header = expected_header(&bytes, &expected);
+            for header in [&header, written.header()] {
+                assert_eq!(expected.len() as u64, header.number_of_points());
+                if !expected.is_empty() {
+                    assert_eq!(expected_header.bounds(), header.bounds());
+                }
+                for n in 1..=3 {
+                    assert_eq!(
+                        expected_header.number_of_points_by_return(n),
+                        header.number_of_points_by_return(n)
+                    );
+                }
+            }
+            assert_eq!(expected, written.into_points().unwrap());
+        }
+    }
+
+    #[test]
+    fn uncompressed() {
+        check("uncompressed", false);
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn compressed() {
+        check("compressed", true);
+    }
+
+    #[test]
+    fn cursor_at_end_of_range() {
+        let mut reader = reader(&source(false), false);
+        let _ = extract_range(&mut reader, 10..20, Cursor::new(Vec::new())).unwrap();
+        assert_eq!(20., reader.read_point().unwrap().unwrap().x);
+    }
+}
@@ -59,7 +59,7 @@ macro_rules! features {
 
             impl Feature for $name {
                 fn is_supported_by(version: Version) -> bool {
-                    vec![$($versions),+]
+                    [$($versions),+]
                         .into_iter()
                         .map(|minor| Version::new(MAJOR, minor))
                         .any(|v| version == v)
@@ -86,4 +86,6 @@ features! {
     LargeFiles(4);
     /// Does this file support extended variable length records?
     Evlrs(4);
+    /// Is there a bit flag to say that the coordinate reference system is WKT?
+    WktCrs(4);
 }
@@ -0,0 +1,421 @@
+//! GeoTIFF keys, as stored in the `LASF_Projection` vlrs of files that don't use WKT.
+//!
+//! The keys are in a [GeoKeyDirectory] (record 34735). Short values are stored in the keys, and
+//! doubles and strings in [GeoDoubleParams] (34736) and [GeoAsciiParams] (34737), which the keys
+//! point into. All three are [VlrRecord]s:
+//!
+//! ```
+//! use las::{geotiff::{GeoKeyDirectory, PROJECTED_CS_TYPE_GEO_KEY}, Reader};
+//!
+//! let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let directory: GeoKeyDirectory = reader.header().get_record().unwrap().unwrap();
+//! let key = directory.get(PROJECTED_CS_TYPE_GEO_KEY).unwrap();
+//! assert_eq!(Some(2994), key.short());
+//! ```
+
+use crate::{crs::GeoTiffData, Error, Result, VlrRecord};
+
+/// The user id of the GeoTIFF vlrs.
+const USER_ID: &str = "LASF_Projection";
+
+/// Whether the model is projected (1), geographic (2), or geocentric (3).
+pub const GT_MODEL_TYPE_GEO_KEY: u16 = 1024;
+
+/// Whether raster pixels are areas (1) or points (2).
+pub const GT_RASTER_TYPE_GEO_KEY: u16 = 1025;
+
+/// A description of the model.
+pub const GT_CITATION_GEO_KEY: u16 = 1026;
+
+/// The EPSG code of a geographic crs.
+pub const GEOGRAPHIC_TYPE_GEO_KEY: u16 = 2048;
+
+/// A description of the geographic crs.
+pub const GEOG_CITATION_GEO_KEY: u16 = 2049;
+
+/// The EPSG code of the geodetic datum.
+pub const GEOG_GEODETIC_DATUM_GEO_KEY: u16 = 2050;
+
+/// The EPSG code of the geographic crs's angular units.
+pub const GEOG_ANGULAR_UNITS_GEO_KEY: u16 = 2054;
+
+/// The EPSG code of a projected crs.
+pub const PROJECTED_CS_TYPE_GEO_KEY: u16 = 3072;
+
+/// A description of the projected crs.
+pub const PCS_CITATION_GEO_KEY: u16 = 3073;
+
+/// The EPSG code of the projection.
+pub const PROJECTION_GEO_KEY: u16 = 3074;
+
+/// The EPSG code of the projected crs's linear units.
+pub const PROJ_LINEAR_UNITS_GEO_KEY: u16 = 3076;
+
+/// The EPSG code of a vertical crs.
+pub const VERTICAL_CS_TYPE_GEO_KEY: u16 = 4096;
+
+/// A description of the vertical crs.
+pub const VERTICAL_CITATION_GEO_KEY: u16 = 4097;
+
+/// The EPSG code of the vertical datum.
+pub const VERTICAL_DATUM_GEO_KEY: u16 = 4098;
+
+/// The EPSG code of the vertical crs's units.
+pub const VERTICAL_UNITS_GEO_KEY: u16 = 4099;
+
+/// The GeoTIFF keys, from the vlr with record id 34735.
+///
+/// # Examples
+///
+/// ```
+/// use las::{geotiff::{GeoKeyDirectory, GeoKeyEntry, GT_MODEL_TYPE_GEO_KEY}, VlrRecord};
+///
+/// let mut directory = GeoKeyDirectory::default();
+/// directory.keys.push(GeoKeyEntry::new_short(GT_MODEL_TYPE_GEO_KEY, 1));
+/// let vlr = directory.to_vlr().unwrap();
+/// assert_eq!(34735, vlr.record_id);
+/// assert_eq!(directory, GeoKeyDirectory::decode(&vlr.data).unwrap());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeoKeyDirectory {
+    /// The version of the key directory, always 1.
+    pub key_directory_version: u16,
+
+    /// The major revision of the keys, always 1.
+    pub key_revision: u16,
+
+    /// The minor revision of the keys, 0 or 1.
+    pub minor_revision: u16,
+
+    /// The keys, which should be sorted by id.
+    pub keys: Vec<GeoKeyEntry>,
+}
+
+/// One GeoTIFF key.
+///
+/// If the [location](GeoKeyEntry::location) is zero, the value is the offset itself. Otherwise
+/// it's the id of the params record that holds the value, and the offset is the index of the
+/// first double, or of the first character of the string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GeoKeyEntry {
+    /// The key id, e.g. [PROJECTED_CS_TYPE_GEO_KEY].
+    pub id: u16,
+
+    /// Where the value is: 0 for here, 34736 for the doubles, or 34737 for the strings.
+    pub location: u16,
+
+    /// The number of values, or characters of the string.
+    pub count: u16,
+
+    /// The value, or where it starts in the params record.
+    pub value_offset: u16,
+}
+
+/// The doubles that GeoTIFF keys point into, from the vlr with record id 34736.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeoDoubleParams {
+    /// The doubles.
+    pub values: Vec<f64>,
+}
+
+/// The strings that GeoTIFF keys point into, from the vlr with record id 34737.
+///
+/// Each string conventionally ends with a `|`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GeoAsciiParams {
+    /// The strings, one after another.
+    pub value: String,
+}
+
+impl GeoKeyDirectory {
+    /// Returns the key with this id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::geotiff::{GeoKeyDirectory, GeoKeyEntry};
+    /// let mut directory = GeoKeyDirectory::default();
+    /// directory.keys.push(GeoKeyEntry::new_short(3072, 32610));
+    /// assert_eq!(Some(32610), directory.get(3072).and_then(|key| key.short()));
+    /// ```
+    pub fn get(&self, id: u16) -> Option<&GeoKeyEntry> {
+        self.keys.iter().find(|key| key.id == id)
+    }
+
+    /// Adds a key whose value is doubles, appending them to the double params.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::geotiff::{GeoDoubleParams, GeoKeyDirectory};
+    /// let mut directory = GeoKeyDirectory::default();
+    /// let mut doubles = GeoDoubleParams { values: vec![1.] };
+    /// directory.push_doubles(2057, &[6378137.], &mut doubles).unwrap();
+    /// assert_eq!(1, directory.keys[0].value_offset);
+    /// ```
+    pub fn push_doubles(
+        &mut self,
+        id: u16,
+        values: &[f64],
+        params: &mut GeoDoubleParams,
+    ) -> Result<()> {
+        self.keys.push(GeoKeyEntry {
+            id,
+            location: GeoDoubleParams::RECORD_ID,
+            count: u16::try_from(values.len())?,
+            value_offset: u16::try_from(params.values.len())?,
+        });
+        params.values.extend_from_slice(values);
+        Ok(())
+    }
+
+    /// Adds a key whose value is a string, appending it and a `|` to the ascii params.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::geotiff::{GeoAsciiParams, GeoKeyDirectory, PCS_CITATION_GEO_KEY};
+    /// let mut directory = GeoKeyDirectory::default();
+    /// let mut ascii = GeoAsciiParams::default();
+    /// directory.push_ascii(PCS_CITATION_GEO_KEY, "UTM 10N", &mut ascii).unwrap();
+    /// assert_eq!("UTM 10N|", ascii.value);
+    /// assert_eq!(8, directory.keys[0].count);
+    /// ```
+    pub fn push_ascii(&mut self, id: u16, value: &str, params: &mut GeoAsciiParams) -> Result<()> {
+        self.keys.push(GeoKeyEntry {
+            id,
+            location: GeoAsciiParams::RECORD_ID,
+            count: u16::try_from(value.len() + 1)?,
+            value_offset: u16::try_from(params.value.len())?,
+        });
+        params.value.push_str(value);
+        params.value.push('|');
+        Ok(())
+    }
+}
+
+impl Default for GeoKeyDirectory {
+    fn default() -> GeoKeyDirectory {
+        GeoKeyDirectory {
+            key_directory_version: 1,
+            key_revision: 1,
+            minor_revision: 0,
+            keys: Vec::new(),
+        }
+    }
+}
+
+impl GeoKeyEntry {
+    /// Creates a key whose value is a short, stored in the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::geotiff::{GeoKeyEntry, GT_MODEL_TYPE_GEO_KEY};
+    /// let key = GeoKeyEntry::new_short(GT_MODEL_TYPE_GEO_KEY, 2);
+    /// assert_eq!(0, key.location);
+    /// ```
+    pub fn new_short(id: u16, value: u16) -> GeoKeyEntry {
+        GeoKeyEntry {
+            id,
+            location: 0,
+            count: 1,
+            value_offset: value,
+        }
+    }
+
+    /// Returns the value, if it's a short stored in the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::geotiff::GeoKeyEntry;
+    /// assert_eq!(Some(2), GeoKeyEntry::new_short(1024, 2).short());
+    /// ```
+    pub fn short(&self) -> Option<u16> {
+        (self.location == 0).then_some(self.value_offset)
+    }
+
+    /// Looks up the value, in the params records if it isn't in the key.
+    ///
+    /// Returns an error if the params record that the key points to is missing, or too short.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{crs::GeoTiffData, geotiff::{GeoAsciiParams, GeoKeyDirectory}};
+    /// let mut directory = GeoKeyDirectory::default();
+    /// let mut ascii = GeoAsciiParams::default();
+    /// directory.push_ascii(1026, "first", &mut ascii).unwrap();
+    /// directory.push_ascii(1026, "second", &mut ascii).unwrap();
+    /// let value = directory.keys[1].value(None, Some(&ascii)).unwrap();
+    /// assert!(matches!(value, GeoTiffData::String(s) if s == "second|"));
+    /// ```
+    pub fn value(
+        &self,
+        doubles: Option<&GeoDoubleParams>,
+        ascii: Option<&GeoAsciiParams>,
+    ) -> Result<GeoTiffData> {
+        let range = usize::from(self.value_offset)
+            ..usize::from(self.value_offset) + usize::from(self.count);
+        match self.location {
+            0 => Ok(GeoTiffData::U16(self.value_offset)),
+            GeoDoubleParams::RECORD_ID => doubles
+                .and_then(|doubles| doubles.values.get(range))
+                .map(|values| GeoTiffData::Doubles(values.to_vec()))
+                .ok_or(Error::UnreadableGeoTiffCrs),
+            GeoAsciiParams::RECORD_ID => ascii
+                .and_then(|ascii| ascii.value.as_bytes().get(range))
+                .map(|bytes| GeoTiffData::String(String::from_utf8_lossy(bytes).into_owned()))
+                .ok_or(Error::UnreadableGeoTiffCrs),
+            _ => Err(Error::UndefinedDataForGeoTiffKey(self.id)),
+        }
+    }
+}
+
+/// Reads little-endian shorts, ignoring a trailing odd byte.
+fn shorts(data: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    data.chunks_exact(2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+impl VlrRecord for GeoKeyDirectory {
+    const USER_ID: &'static str = USER_ID;
+    const RECORD_ID: u16 = 34735;
+    const DESCRIPTION: &'static str = "GeoTiff GeoKeyDirectoryTag";
+
+    fn decode(data: &[u8]) -> Result<GeoKeyDirectory> {
+        let values: Vec<u16> = shorts(data).collect();
+        if values.len() < 4 {
+            return Err(Error::UnreadableGeoTiffCrs);
+        }
+        let number_of_keys = values[3];
+        let keys = values[4..]
+            .chunks_exact(4)
+            .take(usize::from(number_of_keys))
+            .map(|key| GeoKeyEntry {
+                id: key[0],
+                location: key[1],
+                count: key[2],
+                value_offset: key[3],
+            })
+            .collect::<Vec<_>>();
+        if keys.len() < usize::from(number_of_keys) {
+            return Err(Error::UnreadableGeoTiffCrs);
+        }
+        Ok(GeoKeyDirectory {
+            key_directory_version: values[0],
+            key_revision: values[1],
+            minor_revision: values[2],
+            keys,
+        })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let header = [
+            self.key_directory_version,
+            self.key_revision,
+            self.minor_revision,
+            u16::try_from(self.keys.len())?,
+        ];
+        let keys = self
+            .keys
+            .iter()
+            .flat_map(|key| [key.id, key.location, key.count, key.value_offset]);
+        Ok(header
+            .into_iter()
+            .chain(keys)
+            .flat_map(u16::to_le_bytes)
+            .collect())
+    }
+}
+
+impl VlrRecord for GeoDoubleParams {
+    const USER_ID: &'static str = USER_ID;
+    const RECORD_ID: u16 = 34736;
+    const DESCRIPTION: &'static str = "GeoTiff GeoDoubleParamsTag";
+
+    fn decode(data: &[u8]) -> Result<GeoDoubleParams> {
+        Ok(GeoDoubleParams {
+            values: data
+                .chunks_exact(8)
+                .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()))
+                .collect(),
+        })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        Ok(self.values.iter().flat_map(|n| n.to_le_bytes()).collect())
+    }
+}
+
+impl VlrRecord for GeoAsciiParams {
+    const USER_ID: &'static str = USER_ID;
+    const RECORD_ID: u16 = 34737;
+    const DESCRIPTION: &'static str = "GeoTiff GeoAsciiParamsTag";
+
+    fn decode(data: &[u8]) -> Result<GeoAsciiParams> {
+        Ok(GeoAsciiParams {
+            value: String::from_utf8_lossy(data).into_owned(),
+        })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        Ok(self.value.as_bytes().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut directory = GeoKeyDirectory::default();
+        let mut doubles = GeoDoubleParams::default();
+        let mut ascii = GeoAsciiParams::default();
+        directory
+            .keys
+            .push(GeoKeyEntry::new_short(GT_MODEL_TYPE_GEO_KEY, 1));
+        directory
+            .push_ascii(GT_CITATION_GEO_KEY, "NAD83 / UTM 10N", &mut ascii)
+            .unwrap();
+        directory
+            .push_doubles(3081, &[500000., 0.], &mut doubles)
+            .unwrap();
+        directory
+            .push_ascii(PCS_CITATION_GEO_KEY, "UTM", &mut ascii)
+            .unwrap();
+        let directory = GeoKeyDirectory::decode(&directory.encode().unwrap()).unwrap();
+        let doubles = GeoDoubleParams::decode(&doubles.encode().unwrap()).unwrap();
+        let ascii = GeoAsciiParams::decode(&ascii.encode().unwrap()).unwrap();
+        let values = directory
+            .keys
+            .iter()
+            .map(|key| key.value(Some(&doubles), Some(&ascii)).unwrap())
+            .collect::<Vec<_>>();
+        assert!(matches!(values[0], GeoTiffData::U16(1)));
+        assert!(matches!(&values[1], GeoTiffData::String(s) if s == "NAD83 / UTM 10N|"));
+        assert!(matches!(&values[2], GeoTiffData::Doubles(d) if d == &[500000., 0.]));
+        assert!(matches!(&values[3], GeoTiffData::String(s) if s == "UTM|"));
+    }
+
+    #[test]
+    fn value_out_of_range() {
+        let key = GeoKeyEntry {
+            id: 3081,
+            location: 34736,
+            count: 2,
+            value_offset: 1,
+        };
+        let doubles = GeoDoubleParams { values: vec![1.] };
+        assert!(key.value(Some(&doubles), None).is_err());
+        assert!(key.value(None, None).is_err());
+    }
+
+    #[test]
+    fn short_directory() {
+        assert!(GeoKeyDirectory::decode(&[1, 0, 1, 0]).is_err());
+        assert!(GeoKeyDirectory::decode(&[1, 0, 1, 0, 0, 0, 1, 0]).is_err());
+    }
+}
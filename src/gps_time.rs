@@ -0,0 +1,397 @@
+use crate::{Error, GpsTimeType, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::cmp::Ordering;
+
+/// The number of seconds in a gps week.
+const SECONDS_PER_WEEK: f64 = 604_800.;
+
+/// Adjusted standard gps time is gps time minus this many seconds.
+const STANDARD_OFFSET: f64 = 1e9;
+
+/// The start of gps time, 1980-01-06T00:00:00Z, as a unix timestamp.
+const GPS_EPOCH: i64 = 315_964_800;
+
+/// The years and months on whose first day a leap second was added to utc, since the gps epoch.
+///
+/// Gps time doesn't have leap seconds, so it's ahead of utc by the number of these that have
+/// passed.
+const LEAP_SECONDS: [(i32, u32); 18] = [
+    (1981, 7),
+    (1982, 7),
+    (1983, 7),
+    (1985, 7),
+    (1988, 1),
+    (1990, 1),
+    (1991, 1),
+    (1992, 7),
+    (1993, 7),
+    (1994, 7),
+    (1996, 1),
+    (1997, 7),
+    (1999, 1),
+    (2006, 1),
+    (2009, 1),
+    (2012, 7),
+    (2015, 7),
+    (2017, 1),
+];
+
+/// A finite gps time.
+///
+/// Unlike a bare `f64`, a gps time can't be NaN or infinite, so gps times are totally ordered and
+/// can be sorted:
+///
+/// ```
+/// use las::GpsTime;
+/// let mut times = vec![GpsTime::new(3.).unwrap(), GpsTime::new(1.).unwrap()];
+/// times.sort();
+/// assert_eq!(1., f64::from(times[0]));
+/// assert!(GpsTime::new(f64::NAN).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpsTime(f64);
+
+impl GpsTime {
+    /// Creates a new gps time, or returns an error if the value is NaN or infinite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::GpsTime;
+    /// assert!(GpsTime::new(42.).is_ok());
+    /// assert!(GpsTime::new(f64::INFINITY).is_err());
+    /// ```
+    pub fn new(gps_time: f64) -> Result<GpsTime> {
+        if gps_time.is_finite() {
+            Ok(GpsTime(gps_time))
+        } else {
+            Err(Error::InvalidGpsTime(gps_time))
+        }
+    }
+
+    /// Returns true if this value is unlikely to be a gps time of the given type.
+    ///
+    /// Week times are seconds since the start of the gps week, so they should be in `[0, 604800)`.
+    /// Any finite standard time is plausible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTime, GpsTimeType};
+    /// let gps_time = GpsTime::new(1e6).unwrap();
+    /// assert!(gps_time.is_suspicious(GpsTimeType::Week));
+    /// assert!(!gps_time.is_suspicious(GpsTimeType::Standard));
+    /// ```
+    pub fn is_suspicious(&self, gps_time_type: GpsTimeType) -> bool {
+        match gps_time_type {
+            GpsTimeType::Week => !(0. ..SECONDS_PER_WEEK).contains(&self.0),
+            GpsTimeType::Standard => false,
+        }
+    }
+}
+
+impl PartialEq for GpsTime {
+    fn eq(&self, other: &GpsTime) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for GpsTime {}
+
+impl PartialOrd for GpsTime {
+    fn partial_cmp(&self, other: &GpsTime) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GpsTime {
+    fn cmp(&self, other: &GpsTime) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl TryFrom<f64> for GpsTime {
+    type Error = Error;
+
+    fn try_from(gps_time: f64) -> Result<GpsTime> {
+        GpsTime::new(gps_time)
+    }
+}
+
+impl From<GpsTime> for f64 {
+    fn from(gps_time: GpsTime) -> f64 {
+        gps_time.0
+    }
+}
+
+/// A gps time and the type of gps time that it is.
+///
+/// A bare gps time doesn't say whether it's seconds into the gps week or adjusted standard gps
+/// time, which is set for the whole file in the header. A timestamp keeps the two together, so it
+/// can be converted between them and to utc:
+///
+/// ```
+/// use las::{GpsTime, GpsTimeType, GpsTimestamp};
+///
+/// let week_time = GpsTimestamp::new(GpsTime::new(3600.).unwrap(), GpsTimeType::Week);
+/// let standard = week_time.to_standard(2000);
+/// assert_eq!(Some(2000), standard.week());
+/// assert_eq!(week_time, standard.to_week());
+/// println!("{}", standard.to_utc().unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GpsTimestamp {
+    gps_time: GpsTime,
+    gps_time_type: GpsTimeType,
+}
+
+impl GpsTimestamp {
+    /// Creates a timestamp from a gps time and its type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTime, GpsTimeType, GpsTimestamp};
+    /// let timestamp = GpsTimestamp::new(GpsTime::new(42.).unwrap(), GpsTimeType::Standard);
+    /// ```
+    pub fn new(gps_time: GpsTime, gps_time_type: GpsTimeType) -> GpsTimestamp {
+        GpsTimestamp {
+            gps_time,
+            gps_time_type,
+        }
+    }
+
+    /// Returns the gps time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTime, GpsTimeType, GpsTimestamp};
+    /// let gps_time = GpsTime::new(42.).unwrap();
+    /// assert_eq!(gps_time, GpsTimestamp::new(gps_time, GpsTimeType::Week).gps_time());
+    /// ```
+    pub fn gps_time(&self) -> GpsTime {
+        self.gps_time
+    }
+
+    /// Returns the type of the gps time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTime, GpsTimeType, GpsTimestamp};
+    /// let timestamp = GpsTimestamp::new(GpsTime::default(), GpsTimeType::Week);
+    /// assert_eq!(GpsTimeType::Week, timestamp.gps_time_type());
+    /// ```
+    pub fn gps_time_type(&self) -> GpsTimeType {
+        self.gps_time_type
+    }
+
+    /// Returns the gps week of this timestamp, or `None` for a week time, which doesn't know its
+    /// week.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTime, GpsTimeType, GpsTimestamp};
+    /// let timestamp = GpsTimestamp::new(GpsTime::new(120_089_600.).unwrap(), GpsTimeType::Standard);
+    /// assert_eq!(Some(1852), timestamp.week());
+    /// ```
+    pub fn week(&self) -> Option<u32> {
+        match self.gps_time_type {
+            GpsTimeType::Week => None,
+            GpsTimeType::Standard => {
+                let week = ((self.gps_time.0 + STANDARD_OFFSET) / SECONDS_PER_WEEK).floor();
+                Some(week.clamp(0., f64::from(u32::MAX)) as u32)
+            }
+        }
+    }
+
+    /// Converts this timestamp to seconds into its gps week.
+    ///
+    /// Week times are returned as they are. Use [GpsTimestamp::week] to keep the week.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTime, GpsTimeType, GpsTimestamp};
+    /// let timestamp = GpsTimestamp::new(GpsTime::new(120_089_600. + 60.).unwrap(), GpsTimeType::Standard);
+    /// assert_eq!(60., f64::from(timestamp.to_week().gps_time()));
+    /// ```
+    pub fn to_week(self) -> GpsTimestamp {
+        match self.gps_time_type {
+            GpsTimeType::Week => self,
+            GpsTimeType::Standard => GpsTimestamp {
+                gps_time: GpsTime((self.gps_time.0 + STANDARD_OFFSET).rem_euclid(SECONDS_PER_WEEK)),
+                gps_time_type: GpsTimeType::Week,
+            },
+        }
+    }
+
+    /// Converts this timestamp to adjusted standard gps time, using the gps week that a week time
+    /// is in.
+    ///
+    /// Standard times are returned as they are, and `week` is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTime, GpsTimeType, GpsTimestamp};
+    /// let timestamp = GpsTimestamp::new(GpsTime::new(60.).unwrap(), GpsTimeType::Week);
+    /// assert_eq!(120_089_600. + 60., f64::from(timestamp.to_standard(1852).gps_time()));
+    /// ```
+    pub fn to_standard(self, week: u32) -> GpsTimestamp {
+        match self.gps_time_type {
+            GpsTimeType::Week => GpsTimestamp {
+                gps_time: GpsTime(
+                    f64::from(week) * SECONDS_PER_WEEK + self.gps_time.0 - STANDARD_OFFSET,
+                ),
+                gps_time_type: GpsTimeType::Standard,
+            },
+            GpsTimeType::Standard => self,
+        }
+    }
+
+    /// Creates a standard gps timestamp from a utc time, accounting for leap seconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use las::{GpsTimeType, GpsTimestamp};
+    /// let timestamp = GpsTimestamp::from_utc(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap());
+    /// assert_eq!(GpsTimeType::Standard, timestamp.gps_time_type());
+    /// ```
+    pub fn from_utc(utc: DateTime<Utc>) -> GpsTimestamp {
+        let unix = utc.timestamp();
+        let leap_seconds = leap_second_timestamps()
+            .filter(|&timestamp| timestamp <= unix)
+            .count();
+        let seconds = (unix - GPS_EPOCH) as f64
+            + f64::from(utc.timestamp_subsec_nanos()) * 1e-9
+            + leap_seconds as f64;
+        GpsTimestamp {
+            gps_time: GpsTime(seconds - STANDARD_OFFSET),
+            gps_time_type: GpsTimeType::Standard,
+        }
+    }
+
+    /// Converts this timestamp to utc, accounting for leap seconds.
+    ///
+    /// Returns `None` for week times, which need their week (see [GpsTimestamp::to_standard]),
+    /// and for times that utc can't represent. Leap seconds after 2017 aren't known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use las::{GpsTime, GpsTimeType, GpsTimestamp};
+    ///
+    /// let utc = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    /// assert_eq!(Some(utc), GpsTimestamp::from_utc(utc).to_utc());
+    ///
+    /// let week_time = GpsTimestamp::new(GpsTime::default(), GpsTimeType::Week);
+    /// assert_eq!(None, week_time.to_utc());
+    /// ```
+    pub fn to_utc(&self) -> Option<DateTime<Utc>> {
+        if !self.gps_time_type.is_standard() {
+            return None;
+        }
+        let seconds = self.gps_time.0 + STANDARD_OFFSET;
+        let leap_seconds = leap_second_timestamps()
+            .enumerate()
+            .filter(|&(i, timestamp)| seconds >= (timestamp - GPS_EPOCH + i as i64 + 1) as f64)
+            .count();
+        let seconds = seconds - leap_seconds as f64;
+        let whole = seconds.floor();
+        let nanos = ((seconds - whole) * 1e9).round().min(999_999_999.) as u32;
+        if !(i64::MIN as f64..i64::MAX as f64).contains(&whole) {
+            return None;
+        }
+        DateTime::from_timestamp((whole as i64).checked_add(GPS_EPOCH)?, nanos)
+    }
+}
+
+fn leap_second_timestamps() -> impl Iterator<Item = i64> {
+    LEAP_SECONDS.into_iter().map(|(year, month)| {
+        NaiveDate::from_ymd_opt(year, month, 1)
+            .expect("leap second dates are valid")
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight is valid")
+            .and_utc()
+            .timestamp()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn sort() {
+        let mut times: Vec<GpsTime> = [3., -1., 0., 1e9, 2.5]
+            .into_iter()
+            .map(|t| GpsTime::new(t).unwrap())
+            .collect();
+        times.sort();
+        let times: Vec<f64> = times.into_iter().map(f64::from).collect();
+        assert_eq!(vec![-1., 0., 2.5, 3., 1e9], times);
+    }
+
+    #[test]
+    fn reject_non_finite() {
+        for gps_time in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(
+                GpsTime::try_from(gps_time),
+                Err(Error::InvalidGpsTime(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn suspicious_week_time() {
+        for (gps_time, suspicious) in [
+            (-1., true),
+            (0., false),
+            (604_799.9, false),
+            (604_800., true),
+        ] {
+            let gps_time = GpsTime::new(gps_time).unwrap();
+            assert_eq!(suspicious, gps_time.is_suspicious(GpsTimeType::Week));
+            assert!(!gps_time.is_suspicious(GpsTimeType::Standard));
+        }
+    }
+
+    #[test]
+    fn week_and_standard() {
+        let week_time = GpsTimestamp::new(GpsTime(12.5), GpsTimeType::Week);
+        assert_eq!(None, week_time.week());
+        assert_eq!(week_time, week_time.to_week());
+        let standard = week_time.to_standard(2100);
+        assert_eq!(Some(2100), standard.week());
+        assert_eq!(standard, standard.to_standard(0));
+        assert_eq!(week_time, standard.to_week());
+    }
+
+    #[test]
+    fn utc() {
+        let epoch = Utc.with_ymd_and_hms(1980, 1, 6, 0, 0, 0).unwrap();
+        assert_eq!(
+            GpsTime(-STANDARD_OFFSET),
+            GpsTimestamp::from_utc(epoch).gps_time()
+        );
+        // Eighteen leap seconds had been added by 2017-01-01
+        let utc = Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap();
+        let timestamp = GpsTimestamp::from_utc(utc);
+        assert_eq!(
+            (utc.timestamp() - GPS_EPOCH + 18) as f64 - STANDARD_OFFSET,
+            f64::from(timestamp.gps_time())
+        );
+        assert_eq!(Some(utc), timestamp.to_utc());
+        let before = Utc.with_ymd_and_hms(2016, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(Some(before), GpsTimestamp::from_utc(before).to_utc());
+        let subsecond = utc + chrono::Duration::milliseconds(250);
+        assert_eq!(Some(subsecond), GpsTimestamp::from_utc(subsecond).to_utc());
+    }
+}
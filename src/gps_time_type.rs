@@ -1,5 +1,6 @@
 /// The meaning of GPS time in the point records.
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GpsTimeType {
     /// GPS Week Time (the same as previous versions of LAS).
     #[default]
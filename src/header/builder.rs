@@ -1,13 +1,57 @@
 use crate::{
-    header::Error, point::Format, raw, Bounds, GpsTimeType, Header, Result, Transform, Vector,
-    Version, Vlr,
+    feature::{FileSourceId, GpsStandardTime},
+    header::{Date, Error, LazChunks, ReturnCountConflict},
+    point::{ExtraBytesDescriptor, ExtraBytesType, Format},
+    raw,
+    vlr::UserDataDictionary,
+    Bounds, GpsTimeType, Header, Point, Result, Transform, Vector, Version, Vlr, VlrRecord,
 };
 use chrono::NaiveDate;
 use std::{cmp::Ordering, collections::HashMap};
 use uuid::Uuid;
 
+/// A change made by [Builder::normalize] to make the builder's fields consistent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Adjustment {
+    /// The version was raised to support the builder's point format, features, or evlrs.
+    Version {
+        /// The version before normalization.
+        from: Version,
+
+        /// The version after normalization.
+        to: Version,
+    },
+
+    /// An evlr was small enough to be a vlr, and the version doesn't support evlrs.
+    EvlrToVlr {
+        /// The user id of the moved record.
+        user_id: String,
+
+        /// The record id of the moved record.
+        record_id: u16,
+    },
+
+    /// A vlr had too much data to be a vlr, so it was moved to the evlrs.
+    VlrToEvlr {
+        /// The user id of the moved record.
+        user_id: String,
+
+        /// The record id of the moved record.
+        record_id: u16,
+    },
+
+    /// The gps time type was set to standard, as required for extended point formats.
+    GpsTimeType,
+
+    /// The WKT CRS bit was set because there is a WKT CRS record.
+    WktCrs,
+}
+
 /// Use this structure to build a [Header].
-#[derive(Clone, Debug, Default)]
+///
+/// The default builder's date is today.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Builder {
     /// The date of file creation.
     pub date: Option<NaiveDate>,
@@ -15,6 +59,11 @@ pub struct Builder {
     /// The file source id, sometimes the flight line.
     pub file_source_id: u16,
 
+    /// The bytes where later versions have the file source id, which are reserved in las 1.0.
+    ///
+    /// Only written for las 1.0.
+    pub reserved: u16,
+
     /// The software that created this file.
     pub generating_software: String,
 
@@ -24,12 +73,26 @@ pub struct Builder {
     /// A globally unique identifier.
     pub guid: Uuid,
 
+    /// Are the waveform data packets in an auxiliary `.wdp` file?
+    pub has_external_waveform_data_packets: bool,
+
+    /// Are the waveform data packets in this file?
+    pub has_internal_waveform_data_packets: bool,
+
     /// Are the return numbers in this file synthetic?
     pub has_synthetic_return_numbers: bool,
 
     /// Does this file has a WKT CRS?
     pub has_wkt_crs: bool,
 
+    /// How a writer splits the points into laz chunks, or the laszip default if `None`.
+    ///
+    /// Only used if the point format is compressed, and
+    /// [WriteOptions::laz_chunk_size](crate::writer::WriteOptions::laz_chunk_size) and
+    /// [WriteOptions::variable_laz_chunks](crate::writer::WriteOptions::variable_laz_chunks)
+    /// override it.
+    pub laz_chunks: Option<LazChunks>,
+
     /// Bytes after the header but before the vlrs.
     pub padding: Vec<u8>,
 
@@ -41,6 +104,11 @@ pub struct Builder {
     /// Discouraged.
     pub point_padding: Vec<u8>,
 
+    /// The offset from the start of the file to the waveform data packet record.
+    ///
+    /// Carried through as-is, since waveform data packets are not read or written.
+    pub start_of_waveform_data_packet_record: Option<u64>,
+
     /// The system that generated the points.
     pub system_identifier: String,
 
@@ -61,8 +129,11 @@ pub struct Builder {
     pub evlrs: Vec<Vlr>,
 
     number_of_points_by_return: HashMap<u8, u64>,
+    return_count_conflict: Option<ReturnCountConflict>,
+    week_time_from_file: bool,
     number_of_points: u64,
     bounds: Bounds,
+    expected_number_of_points: Option<u64>,
 }
 
 impl Builder {
@@ -85,15 +156,25 @@ impl Builder {
                 .map(|l| l.number_of_point_records)
                 .unwrap_or(0)
         };
-        let number_of_points_by_return =
-            if raw_header.number_of_points_by_return.iter().any(|&n| n > 0) {
-                number_of_points_hash_map(&raw_header.number_of_points_by_return)
-            } else {
-                raw_header
-                    .large_file
-                    .map(|f| number_of_points_hash_map(&f.number_of_points_by_return))
-                    .unwrap_or_default()
-            };
+        let (file_source_id, reserved) = if raw_header.version.supports::<FileSourceId>() {
+            (raw_header.file_source_id, 0)
+        } else {
+            (0, raw_header.file_source_id)
+        };
+        // Las 1.0 and 1.1 have reserved bytes where the global encoding is, which some old
+        // writers filled, so they'd otherwise be read as features that need a later version.
+        let global_encoding = if raw_header.version.supports::<GpsStandardTime>() {
+            raw_header.global_encoding
+        } else {
+            if raw_header.global_encoding != 0 {
+                log::warn!(
+                    "ignoring reserved bytes {:#06x} where las {} has no global encoding",
+                    raw_header.global_encoding,
+                    raw_header.version
+                );
+            }
+            0
+        };
         let mut point_format = Format::new(raw_header.point_data_record_format)?;
         let n = point_format.len();
         match raw_header.point_data_record_length.cmp(&n) {
@@ -101,77 +182,558 @@ impl Builder {
                 return Err(Error::PointDataRecordLengthTooLarge {
                     format: point_format,
                     len: raw_header.point_data_record_length,
+                    minimum: n,
                 })
             }
             Ordering::Equal => {} // pass
             Ordering::Greater => point_format.extra_bytes = raw_header.point_data_record_length - n,
         }
-        Ok(Builder {
-            date: NaiveDate::from_yo_opt(
-                i32::from(raw_header.file_creation_year),
-                u32::from(raw_header.file_creation_day_of_year),
+        let legacy = raw_header.number_of_points_by_return;
+        let has_legacy = legacy.iter().any(|&n| n > 0);
+        let (number_of_points_by_return, return_count_conflict) = match raw_header.large_file {
+            Some(large_file) if point_format.is_extended || !has_legacy => {
+                let extended = large_file.number_of_points_by_return;
+                (
+                    number_of_points_hash_map(&extended),
+                    return_count_conflict(legacy, extended, true),
+                )
+            }
+            Some(large_file) => (
+                number_of_points_hash_map(&legacy),
+                return_count_conflict(legacy, large_file.number_of_points_by_return, false),
             ),
+            None => (number_of_points_hash_map(&legacy), None),
+        };
+        let transforms = raw_header.transforms();
+        let bounds = raw_header.bounds();
+        Ok(Builder {
+            date: Date::new(
+                raw_header.file_creation_year,
+                raw_header.file_creation_day_of_year,
+            )
+            .ok()
+            .map(NaiveDate::from),
             point_padding: Vec::new(),
             evlrs: Vec::new(),
-            file_source_id: raw_header.file_source_id,
+            file_source_id,
+            reserved,
             generating_software: raw_header
                 .generating_software
                 .as_ref()
                 .as_las_string_lossy(),
-            gps_time_type: raw_header.global_encoding.into(),
+            gps_time_type: global_encoding.into(),
             guid: Uuid::from_bytes(raw_header.guid),
-            has_synthetic_return_numbers: raw_header.global_encoding & 8 == 8,
-            has_wkt_crs: raw_header.global_encoding & 16 == 16,
+            has_external_waveform_data_packets: global_encoding & 4 == 4,
+            has_internal_waveform_data_packets: global_encoding & 2 == 2,
+            has_synthetic_return_numbers: global_encoding & 8 == 8,
+            has_wkt_crs: global_encoding & 16 == 16,
+            laz_chunks: None,
             padding: raw_header.padding,
             point_format,
+            start_of_waveform_data_packet_record: raw_header.start_of_waveform_data_packet_record,
             system_identifier: raw_header.system_identifier.as_ref().as_las_string_lossy(),
-            transforms: Vector {
-                x: Transform {
-                    scale: raw_header.x_scale_factor,
-                    offset: raw_header.x_offset,
-                },
-                y: Transform {
-                    scale: raw_header.y_scale_factor,
-                    offset: raw_header.y_offset,
-                },
-                z: Transform {
-                    scale: raw_header.z_scale_factor,
-                    offset: raw_header.z_offset,
-                },
-            },
+            transforms,
             version: raw_header.version,
             vlr_padding: Vec::new(),
             vlrs: Vec::new(),
-            bounds: Bounds {
-                min: Vector {
-                    x: raw_header.min_x,
-                    y: raw_header.min_y,
-                    z: raw_header.min_z,
-                },
-                max: Vector {
-                    x: raw_header.max_x,
-                    y: raw_header.max_y,
-                    z: raw_header.max_z,
-                },
-            },
+            bounds,
             number_of_points,
             number_of_points_by_return,
+            return_count_conflict,
+            week_time_from_file: false,
+            expected_number_of_points: None,
         })
     }
 
-    /// Builds a [Header].
+    /// Reserves `bytes` zero bytes between the last vlr and the point records.
+    ///
+    /// The padding can later be used by [insert_vlr_in_place](crate::insert_vlr_in_place) to add
+    /// a vlr without moving the point data.
     ///
     /// # Examples
     ///
     /// ```
     /// use las::Builder;
-    /// let header = Builder::new(Default::default()).unwrap().into_header().unwrap();
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.vlr_padding(1024);
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(1024, header.vlr_padding_len());
+    /// ```
+    pub fn vlr_padding(&mut self, bytes: u32) {
+        self.vlr_padding
+            .resize(self.vlr_padding.len() + bytes as usize, 0);
+    }
+
+    /// Sets the bytes after the standard header, before the vlrs.
+    ///
+    /// Las 1.4 lets the header size be larger than the standard header, with any user-defined
+    /// content in the extra bytes. The header size and offset to point data account for them. This
+    /// is the same as setting [Builder::padding].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.extra_header_bytes(vec![42; 64]);
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(439, header.clone().into_raw().unwrap().header_size);
+    /// assert_eq!([42; 64], header.extra_header_bytes());
+    /// ```
+    pub fn extra_header_bytes(&mut self, bytes: Vec<u8>) {
+        self.padding = bytes;
+    }
+
+    /// Adds a typed record, replacing any vlrs or evlrs that already hold one.
+    ///
+    /// The record goes in the vlrs, unless it's too big for a vlr, in which case it goes in the
+    /// evlrs. The vlr's description is [VlrRecord::DESCRIPTION].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{vlr::VlrRecord, Builder, Result};
+    ///
+    /// struct Marker;
+    ///
+    /// impl VlrRecord for Marker {
+    ///     const USER_ID: &'static str = "my software";
+    ///     const RECORD_ID: u16 = 7;
+    ///
+    ///     fn decode(_: &[u8]) -> Result<Marker> {
+    ///         Ok(Marker)
+    ///     }
+    ///
+    ///     fn encode(&self) -> Result<Vec<u8>> {
+    ///         Ok(Vec::new())
+    ///     }
+    /// }
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.set_record(&Marker).unwrap();
+    /// builder.set_record(&Marker).unwrap();
+    /// assert_eq!(1, builder.vlrs.len());
+    /// ```
+    pub fn set_record<T: VlrRecord>(&mut self, record: &T) -> Result<()> {
+        let vlr = record.to_vlr()?;
+        self.vlrs.retain(|vlr| !T::matches(vlr));
+        self.evlrs.retain(|vlr| !T::matches(vlr));
+        match vlr.into_evlr().try_into_vlr() {
+            Ok(vlr) => self.vlrs.push(vlr),
+            Err(evlr) => self.evlrs.push(evlr.0),
+        }
+        Ok(())
+    }
+
+    /// Adds a [UserDataDictionary] with labels for the points' user data values.
+    ///
+    /// Any dictionary that's already in the vlrs or evlrs is replaced. Later labels replace
+    /// earlier ones for the same value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.user_data_dictionary(vec![(1, "channel 1".to_string())]).unwrap();
+    /// assert_eq!("las-rs", builder.vlrs[0].user_id);
+    /// ```
+    pub fn user_data_dictionary(&mut self, labels: Vec<(u8, String)>) -> Result<()> {
+        self.set_record(&UserDataDictionary::new(labels))
+    }
+
+    /// Sets the number of extra bytes at the end of each point record.
+    ///
+    /// The point record length becomes the format's length plus `n`. No extra bytes vlr is added,
+    /// so the bytes are undocumented unless one is pushed to the vlrs. Every point written with
+    /// the header must have exactly `n` [extra bytes](crate::Point::extra_bytes), or the write fails
+    /// with the point's index, and points read back have exactly `n`, compressed or not.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.extra_bytes(4);
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(24, header.point_format().len());
+    /// ```
+    pub fn extra_bytes(&mut self, n: u16) {
+        self.point_format.extra_bytes = n;
+    }
+
+    /// Declares an extra bytes attribute, after the points' existing extra bytes.
+    ///
+    /// The descriptor is appended to the extra bytes vlr, which is created if there isn't one,
+    /// and the point record length grows by the attribute's length. Existing extra bytes that the
+    /// vlr doesn't describe are kept, as undocumented bytes. Writers then refuse points whose
+    /// extra bytes don't hold every declared attribute, see
+    /// [Point::set_extra_attribute](crate::Point::set_extra_attribute).
+    ///
+    /// Returns an error if the vlr is invalid, or describes more bytes than the points have.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{
+    ///     point::{ExtraBytesDescriptor, ExtraBytesType},
+    ///     Builder,
+    /// };
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.add_extra_attribute(ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32)).unwrap();
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(24, header.point_format().len());
+    /// assert!(header.extra_bytes_schema().unwrap().get("Amplitude").is_some());
+    /// ```
+    pub fn add_extra_attribute(&mut self, descriptor: ExtraBytesDescriptor) -> Result<()> {
+        let existing = usize::from(self.point_format.extra_bytes);
+        let mut descriptors = self
+            .vlrs
+            .iter()
+            .chain(&self.evlrs)
+            .find_map(ExtraBytesDescriptor::from_vlr)
+            .transpose()?
+            .unwrap_or_default();
+        let documented: usize = descriptors.iter().map(ExtraBytesDescriptor::len).sum();
+        if documented > existing {
+            return Err(Error::InvalidExtraBytesVlr(format!(
+                "the descriptors take up {documented} bytes, but the points only have {existing}"
+            )));
+        }
+        let mut undocumented = existing - documented;
+        while undocumented > 0 {
+            let n = undocumented.min(usize::from(u8::MAX));
+            descriptors.push(ExtraBytesDescriptor::new(
+                "",
+                ExtraBytesType::Undocumented(n as u8),
+            ));
+            undocumented -= n;
+        }
+        let extra_bytes = u16::try_from(existing + descriptor.len())?;
+        descriptors.push(descriptor);
+        let vlr = ExtraBytesDescriptor::to_vlr(&descriptors)?;
+        self.vlrs
+            .retain(|vlr| ExtraBytesDescriptor::from_vlr(vlr).is_none());
+        self.evlrs
+            .retain(|vlr| ExtraBytesDescriptor::from_vlr(vlr).is_none());
+        self.vlrs.push(vlr);
+        self.extra_bytes(extra_bytes);
+        Ok(())
+    }
+
+    /// Picks the smallest point format and fitting transforms for these points.
+    ///
+    /// The format comes from [Format::smallest_for], and keeps this builder's compression. Each
+    /// transform's offset becomes the middle of the points' extent, rounded to a whole number, and
+    /// its scale is kept unless the extent doesn't fit in i32 at that scale, in which case it grows
+    /// by factors of ten until it does. With no points, the transforms are left as-is.
+    ///
+    /// # Examples
+    ///
     /// ```
-    pub fn into_header(mut self) -> Result<Header> {
-        use crate::{
-            feature::{Evlrs, FileSourceId, GpsStandardTime, SyntheticReturnNumbers},
-            raw::POINT_DATA_START_SIGNATURE,
+    /// use las::{Builder, Point};
+    /// let points = [
+    ///     Point { x: 1e6, gps_time: Some(1.), ..Default::default() },
+    ///     Point { x: 1e6 + 10., gps_time: Some(2.), ..Default::default() },
+    /// ];
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.fit_format(&points).unwrap();
+    /// assert_eq!(1, builder.point_format.to_u8().unwrap());
+    /// assert_eq!(1e6 + 5., builder.transforms.x.offset);
+    /// assert_eq!(0.001, builder.transforms.x.scale);
+    /// ```
+    pub fn fit_format(&mut self, points: &[Point]) -> Result<()> {
+        let format = Format::smallest_for(points.iter(), false)?;
+        self.point_format = Format {
+            is_compressed: self.point_format.is_compressed,
+            ..format
+        };
+        if points.is_empty() {
+            return Ok(());
+        }
+        let mut bounds = Bounds::default();
+        for point in points {
+            bounds.grow(point);
+        }
+        self.transforms = Vector {
+            x: fit_transform(self.transforms.x.scale, bounds.min.x, bounds.max.x),
+            y: fit_transform(self.transforms.y.scale, bounds.min.y, bounds.max.y),
+            z: fit_transform(self.transforms.z.scale, bounds.min.z, bounds.max.z),
         };
+        Ok(())
+    }
+
+    /// Picks transforms for points within these bounds, at this resolution or the finest coarser
+    /// one that fits.
+    ///
+    /// Each transform's offset becomes the middle of the bounds, rounded to a whole number, and
+    /// its scale starts at `resolution` and grows by factors of ten until the bounds fit in i32,
+    /// so every coordinate in them can be written. Empty bounds, like [Bounds::default], leave the
+    /// transforms as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Builder, Vector};
+    ///
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 500_000., y: 4_000_000., z: -10. },
+    ///     max: Vector { x: 501_000., y: 4_000_500., z: 1e7 },
+    /// };
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.auto_transforms(&bounds, 0.001).unwrap();
+    /// assert_eq!(500_500., builder.transforms.x.offset);
+    /// assert_eq!(0.001, builder.transforms.x.scale);
+    /// assert_eq!(0.01, builder.transforms.z.scale);
+    /// assert!(builder.auto_transforms(&bounds, 0.).is_err());
+    /// ```
+    pub fn auto_transforms(&mut self, bounds: &Bounds, resolution: f64) -> Result<()> {
+        if !(resolution.is_finite() && resolution > 0.) {
+            return Err(Error::InvalidResolution(resolution));
+        }
+        if !(bounds.min.x <= bounds.max.x
+            && bounds.min.y <= bounds.max.y
+            && bounds.min.z <= bounds.max.z)
+        {
+            return Ok(());
+        }
+        self.transforms = Vector {
+            x: fit_transform(resolution, bounds.min.x, bounds.max.x),
+            y: fit_transform(resolution, bounds.min.y, bounds.max.y),
+            z: fit_transform(resolution, bounds.min.z, bounds.max.z),
+        };
+        Ok(())
+    }
+
+    /// Picks the smallest point format that can hold this point, and the lowest version that
+    /// supports it.
+    ///
+    /// The format comes from [Format::smallest_for] and keeps this builder's compression. An
+    /// extended format also sets the gps time type to standard, which extended formats require.
+    /// The version becomes [Builder::required_version], so it's lowered if the builder doesn't
+    /// need a newer one for anything else.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Color, Point, Version};
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// let point = Point { color: Some(Color::new(1, 2, 3)), ..Default::default() };
+    /// builder.infer_point_format_from(&point).unwrap();
+    /// assert_eq!(2, builder.point_format.to_u8().unwrap());
+    /// assert_eq!(Version::new(1, 2), builder.version);
+    ///
+    /// let point = Point { nir: Some(4), ..point };
+    /// builder.infer_point_format_from(&point).unwrap();
+    /// assert_eq!(8, builder.point_format.to_u8().unwrap());
+    /// assert_eq!(Version::new(1, 4), builder.version);
+    /// ```
+    pub fn infer_point_format_from(&mut self, point: &Point) -> Result<()> {
+        let format = Format::smallest_for(std::iter::once(point), false)?;
+        self.point_format = Format {
+            is_compressed: self.point_format.is_compressed,
+            ..format
+        };
+        if format.is_extended {
+            self.gps_time_type = GpsTimeType::Standard;
+        }
+        self.version = self.required_version();
+        Ok(())
+    }
+
+    /// Declares how many points will be written with the header.
+    ///
+    /// [Builder::into_header] then fails if the version can't hold that many points, instead of the
+    /// writer failing partway through. [Builder::normalize] raises the version instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    /// let mut builder = Builder::from((1, 2));
+    /// builder.expected_point_count(5_000_000_000);
+    /// assert!(builder.clone().into_header().is_err());
+    /// let _ = builder.normalize();
+    /// assert!(builder.into_header().is_ok());
+    /// ```
+    pub fn expected_point_count(&mut self, number_of_points: u64) {
+        self.expected_number_of_points = Some(number_of_points);
+    }
+
+    /// Fixes fields that are individually valid but inconsistent with each other, and returns
+    /// what was changed.
+    ///
+    /// In order, this:
+    ///
+    /// 1. sets the gps time type to standard for extended point formats,
+    /// 2. moves vlrs with too much data to the evlrs,
+    /// 3. raises the version to the lowest one that supports the point format, features, evlrs,
+    ///    and expected point count,
+    /// 4. moves evlrs to the vlrs if the version still doesn't support evlrs,
+    /// 5. sets the WKT CRS bit on las 1.4 if there is a WKT CRS record.
+    ///
+    /// The version is never lowered. If [Builder::into_header] still fails after normalization,
+    /// the conflict can't be fixed automatically, e.g. point padding without any evlrs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{header::Adjustment, point::Format, Builder, Version};
+    /// let mut builder = Builder::from((1, 2));
+    /// builder.point_format = Format::new(6).unwrap();
+    /// assert!(builder.clone().into_header().is_err());
+    /// let adjustments = builder.normalize();
+    /// assert!(adjustments.contains(&Adjustment::Version {
+    ///     from: Version::new(1, 2),
+    ///     to: Version::new(1, 4),
+    /// }));
+    /// let header = builder.into_header().unwrap();
+    /// ```
+    pub fn normalize(&mut self) -> Vec<Adjustment> {
+        use crate::feature::Evlrs;
+
+        let mut adjustments = Vec::new();
+        if self.point_format.is_extended && !self.gps_time_type.is_standard() {
+            self.gps_time_type = GpsTimeType::Standard;
+            adjustments.push(Adjustment::GpsTimeType);
+        }
+        for vlr in std::mem::take(&mut self.vlrs) {
+            if vlr.has_large_data() {
+                adjustments.push(Adjustment::VlrToEvlr {
+                    user_id: vlr.user_id.clone(),
+                    record_id: vlr.record_id,
+                });
+                self.evlrs.push(vlr.into_evlr().0);
+            } else {
+                self.vlrs.push(vlr);
+            }
+        }
+        adjustments.extend(self.ensure_version());
+        if !self.version.supports::<Evlrs>() {
+            for evlr in std::mem::take(&mut self.evlrs) {
+                match evlr.into_evlr().try_into_vlr() {
+                    Ok(vlr) => {
+                        adjustments.push(Adjustment::EvlrToVlr {
+                            user_id: vlr.user_id.clone(),
+                            record_id: vlr.record_id,
+                        });
+                        self.vlrs.push(vlr);
+                    }
+                    Err(evlr) => self.evlrs.push(evlr.0),
+                }
+            }
+        }
+        if self.version >= Version::new(1, 4)
+            && !self.has_wkt_crs
+            && self.vlrs.iter().chain(&self.evlrs).any(|vlr| {
+                vlr.user_id.eq_ignore_ascii_case("lasf_projection") && vlr.record_id == 2112
+            })
+        {
+            self.has_wkt_crs = true;
+            adjustments.push(Adjustment::WktCrs);
+        }
+        adjustments
+    }
+
+    /// Sets the number of points and their bounds, for a header that's written before its points.
+    ///
+    /// A [Writer::non_seekable](crate::Writer::non_seekable) can't go back to fill in the header
+    /// once the points are written, so it writes this header first. The counts by return are
+    /// cleared, since they aren't known ahead of time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Builder};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.precommit(42, Bounds::default());
+    /// assert_eq!(42, builder.into_header().unwrap().number_of_points());
+    /// ```
+    pub fn precommit(&mut self, number_of_points: u64, bounds: Bounds) {
+        self.number_of_points = number_of_points;
+        self.number_of_points_by_return.clear();
+        self.return_count_conflict = None;
+        self.bounds = bounds;
+    }
+
+    /// Sets the bounds, for a header that's edited without its points.
+    pub(crate) fn set_bounds(&mut self, bounds: Bounds) {
+        self.bounds = bounds;
+    }
+
+    /// Sets the point counts by return, for a header that's restored without its points.
+    #[cfg(feature = "laz")]
+    pub(crate) fn set_number_of_points_by_return(
+        &mut self,
+        number_of_points_by_return: HashMap<u8, u64>,
+    ) {
+        self.number_of_points_by_return = number_of_points_by_return;
+        self.return_count_conflict = None;
+    }
+
+    /// Builds a [Header].
+    ///
+    /// Extended point formats must use [GpsTimeType::Standard], which the spec calls adjusted
+    /// standard gps time, unless the builder was made from a header that was read with gps week
+    /// time, which is only warned about. A builder made from a raw header whose legacy and
+    /// extended return counts disagree returns [Error::ReturnCountConflict].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Format, Builder, GpsTimeType};
+    /// let header = Builder::new(Default::default()).unwrap().into_header().unwrap();
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format = Format::new(6).unwrap();
+    /// assert!(builder.clone().into_header().is_err());
+    /// builder.gps_time_type = GpsTimeType::Standard;
+    /// let header = builder.into_header().unwrap();
+    /// ```
+    pub fn into_header(self) -> Result<Header> {
+        if self.point_format.is_extended && !self.gps_time_type.is_standard() {
+            if !self.week_time_from_file {
+                return Err(Error::StandardGpsTimeRequired(self.point_format));
+            }
+            self.warn_week_time();
+        }
+        if let Some(conflict) = self.return_count_conflict {
+            return Err(Error::ReturnCountConflict(Box::new(conflict)));
+        }
+        for vlr in self.vlrs.iter().chain(&self.evlrs) {
+            vlr.verify_description()?;
+        }
+        self.build()
+    }
+
+    /// Builds a [Header] that was read from a file, warning about, rather than refusing, an
+    /// extended point format with gps week time.
+    pub(super) fn into_header_from_file(self) -> Result<Header> {
+        if self.point_format.is_extended && !self.gps_time_type.is_standard() {
+            self.warn_week_time();
+        }
+        if let Some(conflict) = self.return_count_conflict {
+            log::warn!(
+                "the legacy return counts {:?} don't match the extended return counts {:?}, using the {} counts",
+                conflict.legacy,
+                conflict.extended,
+                if conflict.used_extended { "extended" } else { "legacy" }
+            );
+        }
+        self.build()
+    }
+
+    fn warn_week_time(&self) {
+        log::warn!(
+            "{} should use adjusted standard gps time, but the global encoding says gps week time",
+            self.point_format
+        );
+    }
+
+    fn build(mut self) -> Result<Header> {
+        use crate::{feature::Evlrs, raw::POINT_DATA_START_SIGNATURE};
 
         let n = self.vlr_padding.len();
         if self.version.requires_point_data_start_signature()
@@ -179,72 +741,86 @@ impl Builder {
         {
             self.vlr_padding.extend(&POINT_DATA_START_SIGNATURE);
         }
-        if self.file_source_id != 0 {
-            self.version.verify_support_for::<FileSourceId>()?;
-        }
-        if self.has_synthetic_return_numbers {
-            self.version
-                .verify_support_for::<SyntheticReturnNumbers>()?;
-        }
-        if self.gps_time_type.is_standard() {
-            self.version.verify_support_for::<GpsStandardTime>()?;
+        self.verify_version(self.version)?;
+        if let Some(LazChunks::Fixed(n @ (0 | u32::MAX))) = self.laz_chunks {
+            return Err(Error::InvalidLazChunkSize(n));
         }
-        // TODO check waveforms
-        if !self.version.supports_point_format(self.point_format) {
-            return Err(Error::UnsupportedFormat {
-                version: self.version,
-                format: self.point_format,
-            });
+        let fields = Format {
+            extra_bytes: 0,
+            ..self.point_format
+        };
+        if fields
+            .len()
+            .checked_add(self.point_format.extra_bytes)
+            .is_none()
+        {
+            return Err(Error::TooManyExtraBytes(self.point_format.extra_bytes));
         }
         let mut vlrs = Vec::new();
         let mut evlrs = Vec::new();
         for evlr in self.evlrs {
-            if self.version.supports::<Evlrs>() || evlr.has_large_data() {
+            if self.version.supports::<Evlrs>() {
                 evlrs.push(evlr);
-            } else {
-                log::warn!("moving Evlr to Vlr because version does not support Evlrs: user_id={}, record_id={}, description={}", evlr.user_id, evlr.record_id, evlr.description);
-                vlrs.push(evlr);
+                continue;
+            }
+            match evlr.into_evlr().try_into_vlr() {
+                Ok(vlr) => {
+                    log::warn!("moving Evlr to Vlr because version does not support Evlrs: user_id={}, record_id={}, description={}", vlr.user_id, vlr.record_id, vlr.description);
+                    vlrs.push(vlr);
+                }
+                Err(evlr) => evlrs.push(evlr.0),
             }
         }
         for vlr in self.vlrs {
             if vlr.has_large_data() {
-                evlrs.push(vlr);
+                evlrs.push(vlr.into_evlr().0);
             } else {
                 vlrs.push(vlr);
             }
         }
-        if !evlrs.is_empty() {
-            self.version.verify_support_for::<Evlrs>()?;
-        } else if !self.point_padding.is_empty() {
+        if evlrs.is_empty() && !self.point_padding.is_empty() {
             return Err(Error::PointPaddingNotAllowed);
         }
-        let header = Header {
+        let mut header = Header {
             bounds: self.bounds,
             date: self.date,
+            effective_transforms: None,
             evlrs,
             file_source_id: self.file_source_id,
+            reserved: self.reserved,
             generating_software: self.generating_software,
             gps_time_type: self.gps_time_type,
             guid: self.guid,
+            has_external_waveform_data_packets: self.has_external_waveform_data_packets,
+            has_internal_waveform_data_packets: self.has_internal_waveform_data_packets,
             has_synthetic_return_numbers: self.has_synthetic_return_numbers,
             has_wkt_crs: self.has_wkt_crs || self.point_format.is_extended,
+            laz_chunks: self.laz_chunks,
             number_of_points: self.number_of_points,
             number_of_points_by_return: self.number_of_points_by_return,
             padding: self.padding,
             point_format: self.point_format,
             point_padding: self.point_padding,
+            record_length_deficit: 0,
+            return_count_conflict: self.return_count_conflict,
             start_of_first_evlr: None,
+            start_of_waveform_data_packet_record: self.start_of_waveform_data_packet_record,
             system_identifier: self.system_identifier,
             transforms: self.transforms,
             version: self.version,
             vlr_padding: self.vlr_padding,
             vlrs,
+            offsets: Default::default(),
         };
+        let _ = header.recompute_offsets();
         Ok(header)
     }
 
     /// Returns the minimum supported version for this builder, as determined by its features.
     ///
+    /// Returns `None` if the builder can't be built at that version for some other reason. Use
+    /// [Builder::required_version] to only look at what needs a newer version.
+    ///
     /// # Examples
     ///
     /// ```
@@ -253,16 +829,159 @@ impl Builder {
     /// assert_eq!(Builder::default().minimum_supported_version().unwrap(), Version::new(1, 0));
     /// ```
     pub fn minimum_supported_version(&self) -> Option<Version> {
-        // TODO can we make a validity check that doesn't involve a full
-        // conversion into a header, without duplicating a lot of logic?
-        for minor in [0, 1, 2, 3, 4] {
-            let mut builder = self.clone();
-            builder.version.minor = minor;
-            if builder.into_header().is_ok() {
-                return Some(Version::new(1, minor));
-            }
+        let version = self.required_version();
+        let mut builder = self.clone();
+        builder.version = version;
+        builder.into_header().ok().map(|_| version)
+    }
+
+    /// Returns the lowest las version that supports everything in this builder.
+    ///
+    /// This looks at the point format, the file source id, the global encoding bits, the expected
+    /// point count, and whether any records need to be evlrs. [Builder::into_header] checks the
+    /// builder's version against the same requirements, so it fails with an
+    /// [Error::UnsupportedFeature], [Error::UnsupportedFormat], or [Error::TooManyPoints] exactly
+    /// when the version is lower than this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Format, Builder, Version};
+    ///
+    /// let mut builder = Builder::from((1, 0));
+    /// assert_eq!(Version::new(1, 0), builder.required_version());
+    /// builder.point_format = Format::new(2).unwrap();
+    /// assert_eq!(Version::new(1, 2), builder.required_version());
+    /// builder.has_synthetic_return_numbers = true;
+    /// assert_eq!(Version::new(1, 3), builder.required_version());
+    /// ```
+    pub fn required_version(&self) -> Version {
+        (0..=4)
+            .map(|minor| Version::new(1, minor))
+            .find(|&version| self.verify_version(version).is_ok())
+            .unwrap_or(Version::new(1, 4))
+    }
+
+    /// Raises the version to [Builder::required_version], if it's lower, and returns the change.
+    ///
+    /// The version is never lowered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{header::Adjustment, Builder, Version, Vlr};
+    ///
+    /// let mut builder = Builder::from((1, 2));
+    /// builder.evlrs.push(Vlr {
+    ///     data: vec![0; usize::from(u16::MAX) + 1],
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(
+    ///     Some(Adjustment::Version {
+    ///         from: Version::new(1, 2),
+    ///         to: Version::new(1, 4),
+    ///     }),
+    ///     builder.ensure_version()
+    /// );
+    /// assert_eq!(None, builder.ensure_version());
+    /// ```
+    pub fn ensure_version(&mut self) -> Option<Adjustment> {
+        let required = self.required_version();
+        if self.version.major == 1 && self.version < required {
+            let adjustment = Adjustment::Version {
+                from: self.version,
+                to: required,
+            };
+            self.version = required;
+            Some(adjustment)
+        } else {
+            None
+        }
+    }
+
+    /// Returns an error if `version` can't hold something in this builder.
+    fn verify_version(&self, version: Version) -> Result<()> {
+        use crate::feature::{Evlrs, LargeFiles, SyntheticReturnNumbers, Waveforms, WktCrs};
+
+        if self.file_source_id != 0 {
+            version.verify_support_for::<FileSourceId>()?;
+        }
+        if self.has_synthetic_return_numbers {
+            version.verify_support_for::<SyntheticReturnNumbers>()?;
+        }
+        if self.gps_time_type.is_standard() {
+            version.verify_support_for::<GpsStandardTime>()?;
+        }
+        if self.has_internal_waveform_data_packets || self.has_external_waveform_data_packets {
+            version.verify_support_for::<Waveforms>()?;
+        }
+        if self.has_wkt_crs {
+            version.verify_support_for::<WktCrs>()?;
+        }
+        if let Some(n) = self.expected_number_of_points
+            && n > u64::from(u32::MAX)
+            && !version.supports::<LargeFiles>()
+        {
+            return Err(Error::TooManyPoints { n, version });
+        }
+        if !version.supports_point_format(self.point_format) {
+            return Err(Error::UnsupportedFormat {
+                version,
+                format: self.point_format,
+            });
+        }
+        // Evlrs become vlrs in versions without evlrs if they can, but large records, the
+        // waveform data packets, and point padding need real evlrs.
+        let needs_evlrs = self.vlrs.iter().any(Vlr::has_large_data)
+            || self.evlrs.iter().any(|evlr| !evlr.can_be_vlr());
+        if needs_evlrs || (!self.evlrs.is_empty() && !self.point_padding.is_empty()) {
+            version.verify_support_for::<Evlrs>()?;
+        }
+        Ok(())
+    }
+}
+
+/// Centers a transform on `min..=max`, growing its scale until the range fits in i32.
+pub(crate) fn fit_transform(mut scale: f64, min: f64, max: f64) -> Transform {
+    let offset = ((min + max) / 2.).round();
+    let half_span = (max - offset).max(offset - min);
+    while half_span / scale > f64::from(i32::MAX) {
+        scale *= 10.;
+    }
+    Transform { scale, offset }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder {
+            date: Some(Date::today().into()),
+            file_source_id: 0,
+            reserved: 0,
+            generating_software: String::new(),
+            gps_time_type: GpsTimeType::default(),
+            guid: Uuid::default(),
+            has_external_waveform_data_packets: false,
+            has_internal_waveform_data_packets: false,
+            has_synthetic_return_numbers: false,
+            has_wkt_crs: false,
+            laz_chunks: None,
+            padding: Vec::new(),
+            point_format: Format::default(),
+            point_padding: Vec::new(),
+            start_of_waveform_data_packet_record: None,
+            system_identifier: String::new(),
+            transforms: Vector::default(),
+            version: Version::default(),
+            vlr_padding: Vec::new(),
+            vlrs: Vec::new(),
+            evlrs: Vec::new(),
+            number_of_points_by_return: HashMap::new(),
+            return_count_conflict: None,
+            week_time_from_file: false,
+            number_of_points: 0,
+            bounds: Bounds::default(),
+            expected_number_of_points: None,
         }
-        None
     }
 }
 
@@ -275,32 +994,65 @@ impl<V: Into<Version>> From<V> for Builder {
     }
 }
 
+/// Carries every field of the header over to the builder except `start_of_first_evlr`, which a
+/// writer recomputes. See [Header::into_builder].
 impl From<Header> for Builder {
     fn from(header: Header) -> Builder {
+        let transforms = *header.effective_transforms();
         Builder {
             bounds: header.bounds,
             date: header.date,
             evlrs: header.evlrs,
             file_source_id: header.file_source_id,
+            reserved: header.reserved,
             generating_software: header.generating_software,
             gps_time_type: header.gps_time_type,
             guid: header.guid,
+            has_external_waveform_data_packets: header.has_external_waveform_data_packets,
+            has_internal_waveform_data_packets: header.has_internal_waveform_data_packets,
             has_synthetic_return_numbers: header.has_synthetic_return_numbers,
             has_wkt_crs: header.has_wkt_crs,
+            laz_chunks: header.laz_chunks,
             number_of_points: header.number_of_points,
             number_of_points_by_return: header.number_of_points_by_return,
+            return_count_conflict: None,
+            // Only a header that was read from a file can have both.
+            week_time_from_file: header.point_format.is_extended
+                && !header.gps_time_type.is_standard(),
             padding: header.padding,
             point_format: header.point_format,
             point_padding: header.point_padding,
+            start_of_waveform_data_packet_record: header.start_of_waveform_data_packet_record,
             system_identifier: header.system_identifier,
-            transforms: header.transforms,
+            transforms,
             version: header.version,
             vlr_padding: header.vlr_padding,
             vlrs: header.vlrs,
+            expected_number_of_points: None,
         }
     }
 }
 
+/// Returns the conflict between a raw header's legacy and extended return counts, if there's one.
+///
+/// Legacy counts that are all zero don't conflict, since they're zeroed for extended point
+/// formats and for counts that don't fit.
+fn return_count_conflict(
+    legacy: [u32; 5],
+    extended: [u64; 15],
+    used_extended: bool,
+) -> Option<ReturnCountConflict> {
+    let disagree = legacy
+        .iter()
+        .zip(&extended)
+        .any(|(&legacy, &extended)| u64::from(legacy) != extended);
+    (legacy.iter().any(|&n| n > 0) && disagree).then_some(ReturnCountConflict {
+        legacy,
+        extended,
+        used_extended,
+    })
+}
+
 fn number_of_points_hash_map<T: Copy + Into<u64>>(slice: &[T]) -> HashMap<u8, u64> {
     assert!(slice.len() < u8::MAX as usize);
     slice
@@ -340,8 +1092,77 @@ mod tests {
         assert!(builder.date.is_none());
     }
 
+    #[test]
+    fn default_date_is_today() {
+        assert_eq!(Some(Date::today().into()), Builder::default().date);
+        assert_eq!(Some(Date::today().into()), Builder::from((1, 4)).date);
+    }
+
+    #[test]
+    fn return_count_conflict_only_warns_when_read() {
+        let raw_header = raw::Header {
+            version: (1, 4).into(),
+            number_of_point_records: 42,
+            number_of_points_by_return: [42, 0, 0, 0, 0],
+            large_file: Some(raw::header::LargeFile {
+                number_of_point_records: 42,
+                number_of_points_by_return: [40, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            }),
+            ..Default::default()
+        };
+        let builder = Builder::new(raw_header).unwrap();
+        assert!(matches!(
+            builder.clone().into_header().unwrap_err(),
+            Error::ReturnCountConflict(_)
+        ));
+        let header = builder.into_header_from_file().unwrap();
+        assert_eq!(Some(42), header.number_of_points_by_return(1));
+        assert!(!header.return_count_conflict().unwrap().used_extended);
+    }
+
     // TODO assert wkt properties
 
+    fn builder_with_point_data_record_length(len: u16) -> Result<Builder> {
+        let raw_header = raw::Header {
+            point_data_record_format: 3,
+            point_data_record_length: len,
+            ..Default::default()
+        };
+        Builder::new(raw_header)
+    }
+
+    #[test]
+    fn point_data_record_length_too_small() {
+        for len in [0, 33] {
+            match builder_with_point_data_record_length(len).unwrap_err() {
+                Error::PointDataRecordLengthTooLarge {
+                    format,
+                    len: actual,
+                    minimum,
+                } => {
+                    assert_eq!(Format::new(3).unwrap(), format);
+                    assert_eq!(len, actual);
+                    assert_eq!(34, minimum);
+                }
+                err => panic!("unexpected error: {err}"),
+            }
+        }
+    }
+
+    #[test]
+    fn point_data_record_length_minimum() {
+        let builder = builder_with_point_data_record_length(34).unwrap();
+        assert_eq!(0, builder.point_format.extra_bytes);
+        assert_eq!(34, builder.point_format.len());
+    }
+
+    #[test]
+    fn point_data_record_length_extra_bytes() {
+        let builder = builder_with_point_data_record_length(41).unwrap();
+        assert_eq!(7, builder.point_format.extra_bytes);
+        assert_eq!(41, builder.point_format.len());
+    }
+
     #[test]
     fn evlr_downgrade() {
         let mut builder = Builder::from((1, 2));
@@ -381,4 +1202,335 @@ mod tests {
         let builder = Builder::from((1, 2));
         assert!(builder.into_header().unwrap().vlr_padding().is_empty());
     }
+
+    #[test]
+    fn normalize_consistent() {
+        let mut builder = Builder::from((1, 2));
+        assert!(builder.normalize().is_empty());
+        assert_eq!(Version::new(1, 2), builder.version);
+    }
+
+    #[test]
+    fn normalize_version_for_point_format() {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(7).unwrap();
+        assert_eq!(
+            vec![
+                Adjustment::GpsTimeType,
+                Adjustment::Version {
+                    from: Version::new(1, 2),
+                    to: Version::new(1, 4)
+                }
+            ],
+            builder.normalize()
+        );
+        let header = builder.into_header().unwrap();
+        assert_eq!(GpsTimeType::Standard, header.gps_time_type());
+    }
+
+    #[test]
+    fn normalize_version_for_large_evlr() {
+        let mut builder = Builder::from((1, 2));
+        builder.evlrs.push(Vlr {
+            data: vec![0; u16::MAX as usize + 1],
+            ..Default::default()
+        });
+        builder.evlrs.push(Vlr::default());
+        assert_eq!(
+            vec![Adjustment::Version {
+                from: Version::new(1, 2),
+                to: Version::new(1, 4)
+            }],
+            builder.normalize()
+        );
+        assert_eq!(2, builder.into_header().unwrap().evlrs().len());
+    }
+
+    #[test]
+    fn normalize_small_evlr_to_vlr() {
+        let mut builder = Builder::from((1, 2));
+        builder.evlrs.push(Vlr {
+            user_id: "user".to_string(),
+            record_id: 42,
+            ..Default::default()
+        });
+        assert_eq!(
+            vec![Adjustment::EvlrToVlr {
+                user_id: "user".to_string(),
+                record_id: 42
+            }],
+            builder.normalize()
+        );
+        assert!(builder.evlrs.is_empty());
+        assert_eq!(1, builder.vlrs.len());
+    }
+
+    #[test]
+    fn normalize_large_vlr_to_evlr() {
+        let mut builder = Builder::from((1, 4));
+        builder.vlrs.push(Vlr {
+            record_id: 42,
+            data: vec![0; u16::MAX as usize + 1],
+            ..Default::default()
+        });
+        assert_eq!(
+            vec![Adjustment::VlrToEvlr {
+                user_id: String::new(),
+                record_id: 42
+            }],
+            builder.normalize()
+        );
+        assert!(builder.vlrs.is_empty());
+        assert_eq!(1, builder.evlrs.len());
+    }
+
+    #[test]
+    fn normalize_wkt_crs() {
+        let mut builder = Builder::from((1, 4));
+        builder.vlrs.push(Vlr {
+            user_id: "LASF_Projection".to_string(),
+            record_id: 2112,
+            ..Default::default()
+        });
+        assert_eq!(vec![Adjustment::WktCrs], builder.normalize());
+        assert!(builder.into_header().unwrap().has_wkt_crs());
+    }
+
+    #[test]
+    fn normalize_unresolvable() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_padding = vec![0];
+        assert!(builder.normalize().is_empty());
+        assert!(builder.into_header().is_err());
+    }
+
+    #[test]
+    fn expected_point_count() {
+        let mut builder = Builder::from((1, 2));
+        builder.expected_point_count(u64::from(u32::MAX));
+        assert!(builder.clone().into_header().is_ok());
+        builder.expected_point_count(u64::from(u32::MAX) + 1);
+        assert!(matches!(
+            builder.clone().into_header().unwrap_err(),
+            Error::TooManyPoints { .. }
+        ));
+        assert_eq!(
+            vec![Adjustment::Version {
+                from: Version::new(1, 2),
+                to: Version::new(1, 4)
+            }],
+            builder.normalize()
+        );
+        assert!(builder.into_header().is_ok());
+    }
+
+    #[test]
+    fn fit_format_grows_scale() {
+        let points = [-3e6, 3e6].map(|x| Point {
+            x,
+            color: Some(crate::Color::new(1, 2, 3)),
+            ..Default::default()
+        });
+        let mut builder = Builder::from((1, 2));
+        builder.point_format.is_compressed = cfg!(feature = "laz");
+        builder.fit_format(&points).unwrap();
+        assert_eq!(2, builder.point_format.to_u8().unwrap());
+        assert_eq!(cfg!(feature = "laz"), builder.point_format.is_compressed);
+        assert_eq!(0.01, builder.transforms.x.scale);
+        assert_eq!(0., builder.transforms.x.offset);
+        assert_eq!(0.001, builder.transforms.y.scale);
+        let transforms = builder.into_header().unwrap().transforms;
+        for point in points {
+            assert_eq!(
+                point,
+                Point::new(point.clone().into_raw(&transforms).unwrap(), &transforms)
+            );
+        }
+    }
+
+    #[test]
+    fn infer_point_format_from() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.is_compressed = cfg!(feature = "laz");
+        builder.gps_time_type = GpsTimeType::Week;
+        builder.infer_point_format_from(&Point::default()).unwrap();
+        assert_eq!(0, builder.point_format.to_u8().unwrap());
+        assert_eq!(cfg!(feature = "laz"), builder.point_format.is_compressed);
+        assert_eq!(Version::new(1, 0), builder.version);
+
+        let point = Point {
+            return_number: 7,
+            number_of_returns: 8,
+            ..Default::default()
+        };
+        builder.infer_point_format_from(&point).unwrap();
+        assert_eq!(6, builder.point_format.to_u8().unwrap());
+        assert_eq!(GpsTimeType::Standard, builder.gps_time_type);
+        assert_eq!(Version::new(1, 4), builder.version);
+        assert!(builder.into_header().is_ok());
+
+        let mut builder = Builder::from((1, 0));
+        let point = Point {
+            gps_time: Some(1.),
+            waveform: Some(Default::default()),
+            ..Default::default()
+        };
+        builder.infer_point_format_from(&point).unwrap();
+        assert_eq!(4, builder.point_format.to_u8().unwrap());
+        assert_eq!(Version::new(1, 3), builder.version);
+    }
+
+    #[test]
+    fn required_version_matrix() {
+        fn large() -> Vlr {
+            Vlr {
+                data: vec![0; usize::from(u16::MAX) + 1],
+                ..Default::default()
+            }
+        }
+        type Modify = fn(&mut Builder);
+        let cases: [(&str, u8, Modify); 14] = [
+            ("nothing", 0, |_| {}),
+            ("file source id", 1, |b| b.file_source_id = 1),
+            ("standard gps time", 2, |b| {
+                b.gps_time_type = GpsTimeType::Standard
+            }),
+            ("color", 2, |b| b.point_format = Format::new(2).unwrap()),
+            ("waveform format", 3, |b| {
+                b.point_format = Format::new(4).unwrap()
+            }),
+            ("internal waveforms", 3, |b| {
+                b.has_internal_waveform_data_packets = true
+            }),
+            ("external waveforms", 3, |b| {
+                b.has_external_waveform_data_packets = true
+            }),
+            ("synthetic return numbers", 3, |b| {
+                b.has_synthetic_return_numbers = true
+            }),
+            ("extended format", 4, |b| {
+                b.point_format = Format::new(6).unwrap();
+                b.gps_time_type = GpsTimeType::Standard;
+            }),
+            ("wkt crs", 4, |b| b.has_wkt_crs = true),
+            ("large files", 4, |b| {
+                b.expected_point_count(u64::from(u32::MAX) + 1)
+            }),
+            ("small evlr", 0, |b| b.evlrs.push(Vlr::default())),
+            ("large evlr", 4, |b| b.evlrs.push(large())),
+            ("point padding", 4, |b| {
+                b.evlrs.push(Vlr::default());
+                b.point_padding = vec![0];
+            }),
+        ];
+        for (name, required, modify) in cases {
+            let mut builder = Builder::default();
+            modify(&mut builder);
+            assert_eq!(
+                Version::new(1, required),
+                builder.required_version(),
+                "{name}"
+            );
+            for minor in 0..=4 {
+                let mut builder = builder.clone();
+                builder.version = Version::new(1, minor);
+                assert_eq!(
+                    minor >= required,
+                    builder.clone().into_header().is_ok(),
+                    "{name} in 1.{minor}"
+                );
+                let adjustment = builder.ensure_version();
+                assert_eq!(
+                    minor < required,
+                    adjustment.is_some(),
+                    "{name} in 1.{minor}"
+                );
+                assert_eq!(Version::new(1, minor.max(required)), builder.version);
+            }
+        }
+    }
+
+    #[test]
+    fn too_many_extra_bytes() {
+        let mut builder = Builder::from((1, 4));
+        builder.extra_bytes(u16::MAX - 20);
+        assert!(builder.clone().into_header().is_ok());
+        builder.extra_bytes(u16::MAX - 19);
+        assert!(matches!(
+            builder.into_header().unwrap_err(),
+            Error::TooManyExtraBytes(65516)
+        ));
+    }
+
+    #[test]
+    fn add_extra_attribute() {
+        let mut builder = Builder::from((1, 4));
+        builder.extra_bytes(3);
+        builder
+            .add_extra_attribute(ExtraBytesDescriptor::new("a", ExtraBytesType::U16))
+            .unwrap();
+        builder
+            .add_extra_attribute(ExtraBytesDescriptor::new("b", ExtraBytesType::F64))
+            .unwrap();
+        assert_eq!(1, builder.vlrs.len());
+        let header = builder.into_header().unwrap();
+        assert_eq!(13, header.point_format().extra_bytes);
+        let schema = header.extra_bytes_schema().unwrap();
+        assert_eq!(
+            vec![
+                ExtraBytesType::Undocumented(3),
+                ExtraBytesType::U16,
+                ExtraBytesType::F64
+            ],
+            schema
+                .descriptors()
+                .map(|descriptor| descriptor.data_type)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(5, schema.get("b").unwrap().0);
+    }
+
+    #[test]
+    fn add_extra_attribute_invalid_vlr() {
+        let mut builder = Builder::from((1, 4));
+        builder.vlrs.push(
+            ExtraBytesDescriptor::to_vlr(&[ExtraBytesDescriptor::new("a", ExtraBytesType::U32)])
+                .unwrap(),
+        );
+        assert!(matches!(
+            builder
+                .add_extra_attribute(ExtraBytesDescriptor::new("b", ExtraBytesType::U8))
+                .unwrap_err(),
+            Error::InvalidExtraBytesVlr(_)
+        ));
+    }
+
+    #[test]
+    fn gps_time_type_for_versions_and_formats() {
+        for minor in 0..=4 {
+            let version = Version::new(1, minor);
+            for n in 0..=10 {
+                let format = Format::new(n).unwrap();
+                for gps_time_type in [GpsTimeType::Week, GpsTimeType::Standard] {
+                    let mut builder = Builder::from(version);
+                    builder.point_format = format;
+                    builder.gps_time_type = gps_time_type;
+                    let accepted = match (minor, n, gps_time_type) {
+                        (_, 6..=10, GpsTimeType::Week) => false,
+                        (0 | 1, _, GpsTimeType::Standard) => false,
+                        (0 | 1, 0 | 1, GpsTimeType::Week) => true,
+                        (2, 0..=3, _) => true,
+                        (3, 0..=5, _) => true,
+                        (4, _, _) => true,
+                        _ => false,
+                    };
+                    assert_eq!(
+                        accepted,
+                        builder.into_header().is_ok(),
+                        "{version}, {format}, {gps_time_type:?}"
+                    );
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,134 @@
+use crate::{Error, Result};
+use chrono::{Datelike, NaiveDate, Utc};
+use std::fmt;
+
+/// A file creation date, as the header stores it: a year and a day of the year.
+///
+/// The day of the year starts at one, and is checked against the year, so the 366th day is only
+/// valid in leap years:
+///
+/// ```
+/// use las::header::Date;
+/// use chrono::NaiveDate;
+///
+/// let date = Date::new(2024, 366).unwrap();
+/// assert_eq!(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(), NaiveDate::from(date));
+/// assert!(Date::new(2023, 366).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Date {
+    year: u16,
+    day_of_year: u16,
+}
+
+impl Date {
+    /// Creates a date, or returns an error if the day isn't in the year.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::header::Date;
+    /// assert!(Date::new(2015, 53).is_ok());
+    /// assert!(Date::new(2015, 0).is_err());
+    /// ```
+    pub fn new(year: u16, day_of_year: u16) -> Result<Date> {
+        if NaiveDate::from_yo_opt(i32::from(year), u32::from(day_of_year)).is_some() {
+            Ok(Date { year, day_of_year })
+        } else {
+            Err(Error::InvalidDate {
+                year: i32::from(year),
+                day_of_year: u32::from(day_of_year),
+            })
+        }
+    }
+
+    /// Returns today's date, in utc.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::header::Date;
+    /// assert!(Date::today().year() >= 2024);
+    /// ```
+    pub fn today() -> Date {
+        Date::try_from(Utc::now().date_naive()).expect("this year fits in a u16")
+    }
+
+    /// Returns the year.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::header::Date;
+    /// assert_eq!(2015, Date::new(2015, 53).unwrap().year());
+    /// ```
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Returns the day of the year, starting at one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::header::Date;
+    /// assert_eq!(53, Date::new(2015, 53).unwrap().day_of_year());
+    /// ```
+    pub fn day_of_year(&self) -> u16 {
+        self.day_of_year
+    }
+}
+
+impl From<Date> for NaiveDate {
+    fn from(date: Date) -> NaiveDate {
+        NaiveDate::from_yo_opt(i32::from(date.year), u32::from(date.day_of_year))
+            .expect("dates are checked when they're created")
+    }
+}
+
+impl TryFrom<NaiveDate> for Date {
+    type Error = Error;
+
+    fn try_from(date: NaiveDate) -> Result<Date> {
+        u16::try_from(date.year())
+            .map(|year| Date {
+                year,
+                day_of_year: date.ordinal() as u16,
+            })
+            .map_err(|_| Error::InvalidDate {
+                year: date.year(),
+                day_of_year: date.ordinal(),
+            })
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NaiveDate::from(*self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leap_years() {
+        for (year, valid) in [(1900, false), (2000, true), (2023, false), (2024, true)] {
+            assert_eq!(valid, Date::new(year, 366).is_ok());
+            assert!(Date::new(year, 365).is_ok());
+            assert!(Date::new(year, 367).is_err());
+        }
+    }
+
+    #[test]
+    fn naive_date() {
+        let naive_date = NaiveDate::from_ymd_opt(2015, 2, 22).unwrap();
+        let date = Date::try_from(naive_date).unwrap();
+        assert_eq!(Date::new(2015, 53).unwrap(), date);
+        assert_eq!(naive_date, NaiveDate::from(date));
+        assert_eq!("2015-02-22", date.to_string());
+        assert!(Date::try_from(NaiveDate::from_ymd_opt(-1, 1, 1).unwrap()).is_err());
+    }
+}
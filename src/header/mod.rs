@@ -59,74 +59,432 @@
 //! assert_eq!(b"LASF", &raw_header.file_signature);
 //! ```
 
-pub use self::builder::Builder;
+pub(crate) use self::builder::fit_transform;
+pub use self::{
+    builder::{Adjustment, Builder},
+    date::Date,
+    system_identifier::SystemIdentifier,
+};
 use crate::{
-    point::Format, raw, utils::FromLasStr, Bounds, Error, GpsTimeType, Point, Result, Transform,
-    Vector, Version, Vlr,
+    feature::FileSourceId,
+    index,
+    point::Format,
+    raw,
+    reader::{ReadOptions, Warning},
+    utils::FromLasStr,
+    vlr::{LazVlrInfo, UserDataDictionary},
+    Bounds, Error, GpsTimeType, Point, Result, Transform, Vector, Version, Vlr, VlrRecord,
 };
-use chrono::{Datelike, NaiveDate, Utc};
+use chrono::NaiveDate;
 use std::{
     cmp::Ordering,
     collections::HashMap,
+    fmt,
     io::{Read, Seek, SeekFrom, Write},
-    iter::Chain,
-    slice::Iter,
+    iter::Enumerate,
+    ops::{Deref, DerefMut},
+    slice::{Iter, IterMut},
 };
 use thiserror::Error;
 use uuid::Uuid;
 
 mod builder;
+mod date;
+mod system_identifier;
+
+/// The size of the standard part of a (non-extended) vlr.
+const VLR_HEADER_SIZE: u64 = 54;
+
+/// Where the header size is in the raw header.
+const HEADER_SIZE_OFFSET: u64 = 94;
+
+/// Where the point data record format is in the raw header, with the record length after it.
+const POINT_DATA_RECORD_FORMAT_OFFSET: u64 = 104;
+
+/// The size of the standard part of an evlr.
+const EVLR_HEADER_SIZE: u64 = 60;
+
+/// The shortest record of an unknown point format that's plausible, the length of format zero.
+const MIN_UNKNOWN_RECORD_LENGTH: u16 = 20;
 
 /// Metadata describing the layout, source, and interpretation of the points.
 ///
 /// Headers include *all* las metadata, including regular and extended variable length records and
 /// any file padding (e.g. extra bytes after the header).
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
-    bounds: Bounds,
+    pub(crate) bounds: Bounds,
     date: Option<NaiveDate>,
+    effective_transforms: Option<Vector<Transform>>,
     pub(crate) evlrs: Vec<Vlr>,
     file_source_id: u16,
+    reserved: u16,
     generating_software: String,
     gps_time_type: GpsTimeType,
     guid: Uuid,
+    has_external_waveform_data_packets: bool,
+    has_internal_waveform_data_packets: bool,
     has_synthetic_return_numbers: bool,
     pub(crate) has_wkt_crs: bool,
+    laz_chunks: Option<LazChunks>,
     number_of_points: u64,
     number_of_points_by_return: HashMap<u8, u64>,
     padding: Vec<u8>,
     point_format: Format,
     point_padding: Vec<u8>,
+    record_length_deficit: u16,
+    pub(super) return_count_conflict: Option<ReturnCountConflict>,
     start_of_first_evlr: Option<u64>,
+    start_of_waveform_data_packet_record: Option<u64>,
     system_identifier: String,
     transforms: Vector<Transform>,
     version: Version,
     vlr_padding: Vec<u8>,
     pub(crate) vlrs: Vec<Vlr>,
+    offsets: Offsets,
+}
+
+/// How a header's point data record length compares to the length its point format needs.
+///
+/// Returned by [Header::record_length_consistency].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordLengthStatus {
+    /// The records are exactly as long as the point format.
+    Exact,
+
+    /// The records are longer than the point format, and the surplus is read as extra bytes.
+    Surplus(u16),
+
+    /// The records are this many bytes shorter than the point format.
+    ///
+    /// These files are refused unless
+    /// [ReadOptions::read_short_records](crate::reader::ReadOptions::read_short_records) is set,
+    /// in which case the missing bytes at the end of each record are read as zeros.
+    Deficit(u16),
+}
+
+/// A las 1.4 raw header's legacy and extended return counts, when they disagree.
+///
+/// Extended point formats use the extended counts, and the others use the legacy counts. Returned
+/// by [Header::return_count_conflict]. Once read, the header only holds the counts it used, so it
+/// writes both back consistently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReturnCountConflict {
+    /// The legacy counts of returns one through five.
+    pub legacy: [u32; 5],
+
+    /// The extended counts of returns one through fifteen.
+    pub extended: [u64; 15],
+
+    /// Whether the header uses the extended counts.
+    pub used_extended: bool,
 }
 
-/// An iterator over a header's variable length records.
+/// How a [Writer](crate::Writer) splits compressed points into laz chunks.
+///
+/// Set with [Builder::laz_chunks]. Small chunks are quicker to seek to and to read on their own,
+/// e.g. for a spatial query, and large chunks compress a little better.
+///
+/// # Examples
 ///
-/// Get this iterator via [Header::vlrs] or [Header::evlrs].
+/// ```
+/// use las::{header::LazChunks, Builder};
+///
+/// let mut builder = Builder::from((1, 4));
+/// builder.point_format.is_compressed = true;
+/// builder.laz_chunks = Some(LazChunks::Fixed(10_000));
+/// let header = builder.into_header().unwrap();
+/// assert_eq!(Some(LazChunks::Fixed(10_000)), header.laz_chunks());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LazChunks {
+    /// Every chunk has this many points, except maybe the last one.
+    Fixed(u32),
+
+    /// Chunks end when `Writer::finish_laz_chunk` is called, e.g. at the end of each tile or
+    /// flight line.
+    Variable,
+}
+
+/// The parts of a header that are worked out from its size, vlrs, evlrs, and padding.
+///
+/// Returned by [Header::offsets] and [Header::recompute_offsets]. The values aren't checked
+/// against the sizes of the raw header's fields until the header is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Offsets {
+    /// The size of the header, including any extra header bytes.
+    pub header_size: u64,
+
+    /// The offset from the start of the file to the first point.
+    pub offset_to_point_data: u64,
+
+    /// The number of variable length records.
+    pub number_of_vlrs: u64,
+
+    /// The number of extended variable length records.
+    pub number_of_evlrs: u64,
+
+    /// The offset to the first evlr, if it's known.
+    ///
+    /// This is `None` if there aren't any evlrs, or if they'll be written right after the points
+    /// and their start is worked out from the number of points.
+    pub start_of_first_evlr: Option<u64>,
+}
+
+/// What [Header::recompute_offsets] changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OffsetChanges {
+    /// The offsets before they were recomputed.
+    pub before: Offsets,
+
+    /// The recomputed offsets.
+    pub after: Offsets,
+}
+
+impl OffsetChanges {
+    /// Returns true if nothing changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// let mut header = Header::default();
+    /// assert!(header.recompute_offsets().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.before == self.after
+    }
+
+    /// Returns how many bytes the point data moved, negative if it moved towards the start.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, Vlr};
+    /// let mut header = Header::default();
+    /// header.vlrs_mut().push(Vlr::default());
+    /// assert_eq!(54, header.recompute_offsets().point_data_shift());
+    /// ```
+    pub fn point_data_shift(&self) -> i64 {
+        self.after.offset_to_point_data as i64 - self.before.offset_to_point_data as i64
+    }
+}
+
+/// An iterator over a header's vlrs and then its evlrs, with the list each one is in.
+///
+/// Get this iterator via [Header::all_vlrs].
+#[derive(Debug)]
+pub struct Vlrs<'a> {
+    vlrs: Enumerate<Iter<'a, Vlr>>,
+    evlrs: Enumerate<Iter<'a, Vlr>>,
+}
+
+/// A mutable iterator over a header's vlrs and then its evlrs.
+///
+/// Get this iterator via [Header::all_vlrs_mut].
+#[derive(Debug)]
+pub struct VlrsMut<'a> {
+    vlrs: Enumerate<IterMut<'a, Vlr>>,
+    evlrs: Enumerate<IterMut<'a, Vlr>>,
+}
+
+/// A vlr or evlr of a header, with where it is.
+///
+/// Dereferences to the [Vlr].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VlrRef<'a> {
+    /// The record.
+    pub vlr: &'a Vlr,
+
+    /// Whether the record is one of the header's evlrs, rather than one of its vlrs.
+    pub is_extended: bool,
+
+    /// The record's index in its list.
+    pub index: usize,
+}
+
+/// A mutable vlr or evlr of a header, with where it is.
+///
+/// Dereferences to the [Vlr].
 #[derive(Debug)]
-pub struct Vlrs<'a>(Chain<Iter<'a, Vlr>, Iter<'a, Vlr>>);
+pub struct VlrMut<'a> {
+    /// The record.
+    pub vlr: &'a mut Vlr,
+
+    /// Whether the record is one of the header's evlrs, rather than one of its vlrs.
+    pub is_extended: bool,
+
+    /// The record's index in its list.
+    pub index: usize,
+}
+
+/// A way that two headers' point records differ, so raw point bytes can't be copied between them.
+///
+/// Returned by [Header::layout_compatible_with].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Incompatibility {
+    /// The point formats have different attributes.
+    ///
+    /// Extra bytes and compression are ignored, since those are covered by the record length and
+    /// don't change the raw point bytes.
+    PointFormat {
+        /// This header's point format.
+        this: Format,
+
+        /// The other header's point format.
+        other: Format,
+    },
+
+    /// The point data record lengths, including extra bytes, are different.
+    PointDataRecordLength {
+        /// This header's record length.
+        this: u16,
+
+        /// The other header's record length.
+        other: u16,
+    },
+
+    /// The scales or offsets are different, so the stored integers would mean different
+    /// coordinates.
+    Transforms {
+        /// This header's transforms.
+        this: Vector<Transform>,
+
+        /// The other header's transforms.
+        other: Vector<Transform>,
+    },
+
+    /// The gps times are stored relative to different epochs.
+    GpsTimeType {
+        /// This header's gps time type.
+        this: GpsTimeType,
+
+        /// The other header's gps time type.
+        other: GpsTimeType,
+    },
+}
 
 impl Header {
     /// Reads all header, vlr and evlr data from file and returns the complete header.
-    pub fn new<R: Read + Seek>(mut read: R) -> Result<Self> {
-        let raw_header = raw::Header::read_from(read.by_ref())?;
+    ///
+    /// A point data record length that's too short for the point format is an error. Use
+    /// [ReadOptions::read_short_records](crate::reader::ReadOptions::read_short_records) to read
+    /// those files anyway.
+    pub fn new<R: Read + Seek>(read: R) -> Result<Self> {
+        Header::read_from(read, &ReadOptions::default(), true).map(|(header, _)| header)
+    }
+
+    /// Reads a header as a reader with these options would, returning the header and what a
+    /// lenient read repaired.
+    ///
+    /// If `read_evlrs` is false, reading stops at the start of the points, so the stream never
+    /// seeks backwards, and the header has no evlrs.
+    pub(crate) fn read_from<R: Read + Seek>(
+        mut read: R,
+        options: &ReadOptions,
+        read_evlrs: bool,
+    ) -> Result<(Self, Vec<Warning>)> {
+        let allow_short_records = options.reads_short_records();
+        let allow_unknown_formats = options.reads_unknown_formats();
+        let mut raw_header =
+            raw::Header::read_from(read.by_ref()).map_err(|err| read_failed(&mut read, err))?;
+        // The vlrs start at the header size, so it can't point inside the standard header.
+        if raw_header.header_size < raw_header.version.header_size() {
+            return Err(at(
+                HEADER_SIZE_OFFSET,
+                Error::HeaderSizeTooSmall {
+                    version: raw_header.version,
+                    header_size: raw_header.header_size,
+                    minimum: raw_header.version.header_size(),
+                },
+            ));
+        }
+        let warnings = if options.is_lenient() {
+            repair(&mut raw_header, stream_len(read.by_ref()))
+        } else {
+            Vec::new()
+        };
         let mut position = u64::from(raw_header.header_size);
         let number_of_variable_length_records = raw_header.number_of_variable_length_records;
         let offset_to_point_data = u64::from(raw_header.offset_to_point_data);
         let offset_to_end_of_points = raw_header.offset_to_end_of_points();
         let evlr = raw_header.evlr;
+        let version = raw_header.version;
+        let mut record_length_deficit = 0;
+        if allow_short_records
+            && let Ok(format) = Format::new(raw_header.point_data_record_format)
+            && !format.is_compressed
+            && raw_header.point_data_record_length < format.len()
+        {
+            record_length_deficit = format.len() - raw_header.point_data_record_length;
+            log::warn!(
+                "the point data record length {} is {} bytes too short for {}, the missing bytes are read as zeros",
+                raw_header.point_data_record_length,
+                record_length_deficit,
+                format
+            );
+            raw_header.point_data_record_length = format.len();
+        }
 
-        let mut builder = Builder::new(raw_header)?;
+        let unknown_format = (allow_unknown_formats
+            && matches!(
+                Format::new(raw_header.point_data_record_format),
+                Err(Error::InvalidPointFormatNumber(_))
+            )
+            && raw_header.point_data_record_length >= MIN_UNKNOWN_RECORD_LENGTH
+            && stream_len(read.by_ref()).is_none_or(|end| offset_to_end_of_points <= end))
+        .then(|| {
+            Format::unknown(
+                raw_header.point_data_record_format,
+                raw_header.point_data_record_length,
+            )
+        });
+        if unknown_format.is_some() {
+            // Every plausible record holds a format zero point, which is replaced once the rest of
+            // the header is read.
+            raw_header.point_data_record_format = 0;
+        }
+        let mut builder = Builder::new(raw_header).map_err(|err| match err {
+            Error::InvalidPointFormatNumber(_) => at(POINT_DATA_RECORD_FORMAT_OFFSET, err),
+            Error::PointDataRecordLengthTooLarge { .. } => {
+                at(POINT_DATA_RECORD_FORMAT_OFFSET + 1, err)
+            }
+            err => err,
+        })?;
 
-        for _ in 0..number_of_variable_length_records {
-            let vlr = raw::Vlr::read_from(read.by_ref(), false).map(Vlr::new)?;
-            position += vlr.len(false) as u64;
-            builder.vlrs.push(vlr);
+        if let Some(format) = unknown_format {
+            builder.point_format = format;
+        }
+
+        // Every vlr needs at least its header, so don't trust a count that couldn't fit.
+        if u64::from(number_of_variable_length_records) * VLR_HEADER_SIZE
+            > offset_to_point_data.saturating_sub(position)
+        {
+            return Err(Error::TooManyVlrs(
+                number_of_variable_length_records as usize,
+            ));
+        }
+        for index in 0..number_of_variable_length_records {
+            let mut raw_vlr = raw::Vlr::read_header_from(read.by_ref(), false)
+                .map_err(|err| vlr_read_failed(&mut read, index, false, err))?;
+            let len = u64::from(raw_vlr.record_length_after_header);
+            let available = offset_to_point_data.saturating_sub(position);
+            if VLR_HEADER_SIZE + len > available {
+                return Err(Error::VlrPastPointData {
+                    index,
+                    len,
+                    remaining: available.saturating_sub(VLR_HEADER_SIZE),
+                });
+            }
+            raw_vlr
+                .read_data_from(read.by_ref())
+                .map_err(|err| vlr_read_failed(&mut read, index, false, err))?;
+            position += VLR_HEADER_SIZE + len;
+            builder.vlrs.push(Vlr::read_in(raw_vlr, builder.version));
         }
         match position.cmp(&offset_to_point_data) {
             Ordering::Less => {
@@ -142,6 +500,25 @@ impl Header {
                 ))
             }
         }
+        if !read_evlrs {
+            return header_from_file(builder, record_length_deficit)
+                .map(|header| (header, warnings));
+        }
+        let mut start_of_points = offset_to_point_data;
+        if version.requires_point_data_start_signature()
+            && !builder.point_format.is_compressed
+            && !builder
+                .vlr_padding
+                .ends_with(&raw::POINT_DATA_START_SIGNATURE)
+            && starts_with_point_data_start_signature(
+                read.by_ref(),
+                offset_to_point_data,
+                offset_to_end_of_points,
+            )?
+        {
+            builder.vlr_padding.extend(&raw::POINT_DATA_START_SIGNATURE);
+            start_of_points += 2;
+        }
 
         let _ = read.seek(SeekFrom::Start(offset_to_end_of_points))?;
         if let Some(evlr) = evlr {
@@ -172,28 +549,53 @@ impl Header {
                     }
                 }
             }
-            let _ = read.seek(SeekFrom::Start(evlr.start_of_first_evlr))?;
-            builder
-                .evlrs
-                .push(raw::Vlr::read_from(read.by_ref(), true).map(Vlr::new)?);
-        }
-
-        let _ = read.seek(SeekFrom::Start(offset_to_point_data))?;
-
-        if let Some(version) = builder.minimum_supported_version() {
-            if version > builder.version {
-                log::warn!(
-                    "upgrading las version to {} (from {})",
-                    version,
-                    builder.version
-                );
-                builder.version = version;
+            // The evlrs are at the end of the file, so their counts and lengths are checked
+            // against what's left of the stream. If the stream can't tell us its length, the
+            // records are read as declared.
+            let end = stream_len(read.by_ref());
+            let mut position = evlr.start_of_first_evlr;
+            let _ = read.seek(SeekFrom::Start(position))?;
+            if let Some(end) = end
+                && u64::from(evlr.number_of_evlrs) * EVLR_HEADER_SIZE > end.saturating_sub(position)
+            {
+                return Err(Error::TooManyEvlrs(evlr.number_of_evlrs as usize));
+            }
+            for index in 0..evlr.number_of_evlrs {
+                let mut raw_vlr = raw::Vlr::read_header_from(read.by_ref(), true)
+                    .map_err(|err| vlr_read_failed(&mut read, index, true, err))?;
+                let len = u64::from(raw_vlr.record_length_after_header);
+                position += EVLR_HEADER_SIZE;
+                if let Some(end) = end {
+                    let remaining = end.saturating_sub(position);
+                    if len > remaining {
+                        return Err(Error::EvlrPastEnd {
+                            index,
+                            len,
+                            remaining,
+                        });
+                    }
+                }
+                position = position.saturating_add(len);
+                if options.max_evlr_len_bytes().is_some_and(|max| len > max) {
+                    let _ = read.seek(SeekFrom::Start(position))?;
+                    continue;
+                }
+                raw_vlr
+                    .read_data_from(read.by_ref())
+                    .map_err(|err| vlr_read_failed(&mut read, index, true, err))?;
+                builder.evlrs.push(Vlr::read_in(raw_vlr, builder.version));
             }
         }
-        builder.into_header()
+
+        let _ = read.seek(SeekFrom::Start(start_of_points))?;
+        header_from_file(builder, record_length_deficit).map(|header| (header, warnings))
     }
+
     /// Creates a new header from a raw header.
     ///
+    /// As when reading a file, an extended point format with gps week time is logged as a warning
+    /// instead of being refused.
+    ///
     /// # Examples
     ///
     /// ```
@@ -202,7 +604,29 @@ impl Header {
     /// let header = Header::from_raw(raw_header).unwrap();
     /// ```
     pub fn from_raw(raw_header: raw::Header) -> Result<Header> {
-        Builder::new(raw_header).and_then(|b| b.into_header())
+        Builder::new(raw_header).and_then(|b| b.into_header_from_file())
+    }
+
+    /// Converts this header into a [Builder], e.g. to modify it before writing.
+    ///
+    /// Everything the header holds carries over to the builder, so `header.into_builder()
+    /// .into_header()` gives back an equal header, with two exceptions: the start of the first evlr
+    /// is dropped, because a [Writer](crate::Writer) recomputes it when it closes, and so is a
+    /// [RecordLengthStatus::Deficit], because points are always written at full length. Reserved bits
+    /// of the global encoding are not represented by a header, so they are never preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// let header = Header::default();
+    /// let mut builder = header.clone().into_builder();
+    /// builder.file_source_id = 42;
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(42, header.file_source_id());
+    /// ```
+    pub fn into_builder(self) -> Builder {
+        Builder::from(self)
     }
 
     /// Clears this header's point counts and bounds.
@@ -223,9 +647,47 @@ impl Header {
     pub fn clear(&mut self) {
         self.number_of_points = 0;
         self.number_of_points_by_return = Default::default();
+        self.return_count_conflict = None;
         self.bounds = Default::default();
     }
 
+    /// Returns a copy of this header, ready to describe new points with the same layout.
+    ///
+    /// The point counts and bounds are cleared, and so are the vlrs and evlrs that describe the
+    /// old points: spatial indices and copc info.
+    pub(crate) fn to_empty(&self) -> Header {
+        let mut header = self.clone();
+        header.apply_effective_transforms();
+        header.clear();
+        header.record_length_deficit = 0;
+        let describes_points = |vlr: &Vlr| {
+            #[cfg(feature = "laz")]
+            if vlr.user_id == crate::copc::USER_ID {
+                return true;
+            }
+            vlr.user_id == index::USER_ID && vlr.record_id == index::RECORD_ID
+        };
+        header.vlrs.retain(|vlr| !describes_points(vlr));
+        header.evlrs.retain(|vlr| !describes_points(vlr));
+        header
+    }
+
+    /// Makes the effective transforms this header's own, so it describes the points as they're
+    /// read.
+    pub(crate) fn apply_effective_transforms(&mut self) {
+        if let Some(transforms) = self.effective_transforms.take() {
+            self.transforms = transforms;
+        }
+    }
+
+    /// Returns an empty copy of this header, like [Header::to_empty], with a system identifier
+    /// for the process that's making a new file from it.
+    pub(crate) fn to_derived(&self, system_identifier: &SystemIdentifier) -> Header {
+        let mut header = self.to_empty();
+        header.system_identifier = system_identifier.to_string();
+        header
+    }
+
     /// Adds a point to this header, incrementing the point counts and growing the bounds.
     ///
     /// # Examples
@@ -248,6 +710,69 @@ impl Header {
         self.bounds.grow(point);
     }
 
+    /// Returns an error if this header's version can't count `number_of_points` more points.
+    ///
+    /// `number_of_points_by_return` holds how many of the new points have each return number.
+    /// Versions without large file support can count at most `u32::MAX` points in total and for
+    /// each return number, and only return numbers up to five.
+    pub(crate) fn verify_room_for(
+        &self,
+        number_of_points: u64,
+        number_of_points_by_return: impl IntoIterator<Item = (u8, u64)>,
+    ) -> Result<()> {
+        use crate::feature::LargeFiles;
+
+        if self.version.supports::<LargeFiles>() {
+            return Ok(());
+        }
+        let n = self.number_of_points + number_of_points;
+        if n > u64::from(u32::MAX) {
+            return Err(Error::TooManyPoints {
+                n,
+                version: self.version,
+            });
+        }
+        for (return_number, count) in number_of_points_by_return {
+            if return_number > 5 {
+                return Err(Error::ReturnNumber {
+                    return_number,
+                    version: Some(self.version),
+                });
+            } else if return_number > 0 {
+                let n = self.number_of_points_by_return(return_number).unwrap_or(0) + count;
+                if n > u64::from(u32::MAX) {
+                    return Err(Error::TooManyPoints {
+                        n,
+                        version: self.version,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the point counts and bounds of a batch of points that were tallied elsewhere.
+    pub(crate) fn add_points(
+        &mut self,
+        number_of_points: u64,
+        number_of_points_by_return: &HashMap<u8, u64>,
+        bounds: &Bounds,
+    ) {
+        self.number_of_points += number_of_points;
+        for (&return_number, &count) in number_of_points_by_return {
+            *self
+                .number_of_points_by_return
+                .entry(return_number)
+                .or_insert(0) += count;
+        }
+        self.bounds.min.x = self.bounds.min.x.min(bounds.min.x);
+        self.bounds.min.y = self.bounds.min.y.min(bounds.min.y);
+        self.bounds.min.z = self.bounds.min.z.min(bounds.min.z);
+        self.bounds.max.x = self.bounds.max.x.max(bounds.max.x);
+        self.bounds.max.y = self.bounds.max.y.max(bounds.max.y);
+        self.bounds.max.z = self.bounds.max.z.max(bounds.max.z);
+    }
+
     /// Returns this header's file source id.
     ///
     /// For airborne data, this is often the flight line number.
@@ -262,6 +787,26 @@ impl Header {
         self.file_source_id
     }
 
+    /// Returns the two bytes that later versions use for the file source id, which are reserved
+    /// in las 1.0.
+    ///
+    /// Some las 1.0 writers put other values here, so they're kept as they were read instead of
+    /// being taken as a file source id. Always zero for other versions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    /// let mut builder = Builder::from((1, 0));
+    /// builder.reserved = 42;
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(42, header.reserved());
+    /// assert_eq!(0, header.file_source_id());
+    /// ```
+    pub fn reserved(&self) -> u16 {
+        self.reserved
+    }
+
     /// Returns the gps time type.
     ///
     /// This affects what the gps time values on points means.
@@ -306,6 +851,67 @@ impl Header {
         self.has_wkt_crs
     }
 
+    /// Returns how a writer splits this header's points into laz chunks, if it's been set.
+    ///
+    /// This isn't stored in the file, so it's `None` for a header that was read. Then a writer
+    /// uses the laszip default, unless
+    /// [WriteOptions::laz_chunk_size](crate::writer::WriteOptions::laz_chunk_size) says otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert_eq!(None, Header::default().laz_chunks());
+    /// ```
+    pub fn laz_chunks(&self) -> Option<LazChunks> {
+        self.laz_chunks
+    }
+
+    /// Returns true if the global encoding says the waveform data packets are in this file.
+    ///
+    /// Only supported in las 1.3 and later. Waveform data packets themselves are not read or
+    /// written by this library.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert!(!Header::default().has_internal_waveform_data_packets());
+    /// ```
+    pub fn has_internal_waveform_data_packets(&self) -> bool {
+        self.has_internal_waveform_data_packets
+    }
+
+    /// Returns true if the global encoding says the waveform data packets are in an auxiliary
+    /// `.wdp` file.
+    ///
+    /// Only supported in las 1.3 and later.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert!(!Header::default().has_external_waveform_data_packets());
+    /// ```
+    pub fn has_external_waveform_data_packets(&self) -> bool {
+        self.has_external_waveform_data_packets
+    }
+
+    /// Returns the offset from the start of the file to the waveform data packet record.
+    ///
+    /// Only las 1.3 and later have this field. It is carried through as-is, so it is up to you to
+    /// keep it pointing at the right place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert_eq!(None, Header::default().start_of_waveform_data_packet_record());
+    /// ```
+    pub fn start_of_waveform_data_packet_record(&self) -> Option<u64> {
+        self.start_of_waveform_data_packet_record
+    }
+
     /// Returns this header's guid.
     ///
     /// # Examples
@@ -344,6 +950,21 @@ impl Header {
         &self.system_identifier
     }
 
+    /// Returns what this header's system identifier says made the points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{header::SystemIdentifier, Builder};
+    /// let mut builder = Builder::default();
+    /// builder.system_identifier = SystemIdentifier::Merge.to_string();
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(SystemIdentifier::Merge, header.system_identifier_kind());
+    /// ```
+    pub fn system_identifier_kind(&self) -> SystemIdentifier {
+        SystemIdentifier::from(self.system_identifier.as_str())
+    }
+
     /// Returns this header's generating software.
     ///
     /// # Examples
@@ -370,6 +991,18 @@ impl Header {
         self.date
     }
 
+    /// Returns this header's file creation date as a year and day of the year.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{header::Date, Header};
+    /// assert_eq!(Date::today(), Header::default().creation_date().unwrap());
+    /// ```
+    pub fn creation_date(&self) -> Option<Date> {
+        self.date.and_then(|date| Date::try_from(date).ok())
+    }
+
     /// Returns this header's padding.
     ///
     /// These are bytes that are after the header but before the vlr. Not recommended to use.
@@ -384,6 +1017,21 @@ impl Header {
         &self.padding
     }
 
+    /// Returns the bytes between the end of the standard header and the header size.
+    ///
+    /// Las 1.4 allows these for user-defined content. They're the same bytes as
+    /// [Header::padding], and they're written back out as they were read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert!(Header::default().extra_header_bytes().is_empty());
+    /// ```
+    pub fn extra_header_bytes(&self) -> &[u8] {
+        &self.padding
+    }
+
     /// Returns this header's point format.
     ///
     /// Point formats are used to describe the attributes and extra bytes of each point.
@@ -399,6 +1047,39 @@ impl Header {
         &self.point_format
     }
 
+    /// Returns the length of each point record, as stored in the file.
+    ///
+    /// This is the point format's length, unless short records were read with
+    /// [ReadOptions::read_short_records](crate::reader::ReadOptions::read_short_records).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert_eq!(20, Header::default().point_data_record_length());
+    /// ```
+    pub fn point_data_record_length(&self) -> u16 {
+        self.point_format.len() - self.record_length_deficit
+    }
+
+    /// Returns how the point data record length compares to what the point format needs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{header::RecordLengthStatus, Header};
+    /// assert_eq!(RecordLengthStatus::Exact, Header::default().record_length_consistency());
+    /// ```
+    pub fn record_length_consistency(&self) -> RecordLengthStatus {
+        if self.record_length_deficit > 0 {
+            RecordLengthStatus::Deficit(self.record_length_deficit)
+        } else if self.point_format.extra_bytes > 0 {
+            RecordLengthStatus::Surplus(self.point_format.extra_bytes)
+        } else {
+            RecordLengthStatus::Exact
+        }
+    }
+
     pub(crate) fn point_format_mut(&mut self) -> &mut Format {
         &mut self.point_format
     }
@@ -420,6 +1101,57 @@ impl Header {
         &self.transforms
     }
 
+    /// Returns the transforms that points are read with.
+    ///
+    /// These are the header's [transforms](Header::transforms), unless a reader was opened with
+    /// [ReadOptions::override_transforms](crate::reader::ReadOptions::override_transforms).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::ReadOptions, Reader, Transform, Vector};
+    ///
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(reader.header().transforms(), reader.header().effective_transforms());
+    ///
+    /// let z = Transform { scale: 0.01, offset: 30. };
+    /// let options = ReadOptions::default().override_transforms(Vector { x: None, y: None, z: Some(z) });
+    /// let file = std::fs::File::open("tests/data/autzen.las").unwrap();
+    /// let reader = Reader::with_options(std::io::BufReader::new(file), options).unwrap();
+    /// assert_eq!(z, reader.header().effective_transforms().z);
+    /// assert_ne!(z, reader.header().transforms().z);
+    /// ```
+    pub fn effective_transforms(&self) -> &Vector<Transform> {
+        self.effective_transforms
+            .as_ref()
+            .unwrap_or(&self.transforms)
+    }
+
+    /// Reads points with other transforms on some axes, and moves the bounds to match.
+    pub(crate) fn override_transforms(&mut self, overrides: Vector<Option<Transform>>) {
+        if overrides == Vector::default() {
+            return;
+        }
+        let old = self.transforms;
+        let new = Vector {
+            x: overrides.x.unwrap_or(old.x),
+            y: overrides.y.unwrap_or(old.y),
+            z: overrides.z.unwrap_or(old.z),
+        };
+        let bounds = &mut self.bounds;
+        for (min, max, old, new) in [
+            (&mut bounds.min.x, &mut bounds.max.x, old.x, new.x),
+            (&mut bounds.min.y, &mut bounds.max.y, old.y, new.y),
+            (&mut bounds.min.z, &mut bounds.max.z, old.z, new.z),
+        ] {
+            let moved = |value: f64| new.offset + (value - old.offset) / old.scale * new.scale;
+            let (a, b) = (moved(*min), moved(*max));
+            *min = a.min(b);
+            *max = a.max(b);
+        }
+        self.effective_transforms = Some(new);
+    }
+
     /// Returns the bounds of this header.
     ///
     /// The bounds describe the min and max values in each dimension.
@@ -462,6 +1194,19 @@ impl Header {
         self.number_of_points_by_return.get(&n).copied()
     }
 
+    /// Returns the legacy and extended return counts of the raw header this was read from, if
+    /// they disagreed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert!(Header::default().return_count_conflict().is_none());
+    /// ```
+    pub fn return_count_conflict(&self) -> Option<ReturnCountConflict> {
+        self.return_count_conflict
+    }
+
     /// Returns a reference to this header's vlr padding.
     ///
     /// These are bytes after the vlrs but before the points. Again, not recommended for use.
@@ -476,6 +1221,28 @@ impl Header {
         &self.vlr_padding
     }
 
+    /// Returns the number of vlr padding bytes that could hold new vlrs.
+    ///
+    /// This doesn't count the point data start signature required by las 1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    /// let mut builder = Builder::from((1, 0));
+    /// builder.vlr_padding(100);
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(102, header.vlr_padding().len());
+    /// assert_eq!(100, header.vlr_padding_len());
+    /// ```
+    pub fn vlr_padding_len(&self) -> usize {
+        if self.version.requires_point_data_start_signature() {
+            self.vlr_padding.len().saturating_sub(2)
+        } else {
+            self.vlr_padding.len()
+        }
+    }
+
     /// Returns a reference to this header's point padding.
     ///
     /// These are the bytes after the points but before eof/any evlrs. Not recommended.
@@ -505,35 +1272,205 @@ impl Header {
         &self.vlrs
     }
 
-    /// Returns a reference to header's extended variable length records.
+    /// Returns a mutable reference to this header's variable length records.
+    ///
+    /// Call [Header::recompute_offsets] after adding, removing, or resizing records, so that the
+    /// header's offsets match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, Vlr};
+    /// let mut header = Header::default();
+    /// header.vlrs_mut().push(Vlr::default());
+    /// assert_eq!(1, header.vlrs().len());
+    /// ```
+    pub fn vlrs_mut(&mut self) -> &mut Vec<Vlr> {
+        &mut self.vlrs
+    }
+
+    /// Returns a reference to header's extended variable length records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Vlr, Builder};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.evlrs.push(Vlr::default());
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(1, header.evlrs().len());
+    /// ```
+    pub fn evlrs(&self) -> &Vec<Vlr> {
+        &self.evlrs
+    }
+
+    /// Returns a mutable reference to this header's extended variable length records.
+    ///
+    /// As with [Header::vlrs_mut], call [Header::recompute_offsets] after changing them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Vlr};
+    /// let mut header = Builder::from((1, 4)).into_header().unwrap();
+    /// header.evlrs_mut().push(Vlr::default());
+    /// assert_eq!(1, header.recompute_offsets().after.number_of_evlrs);
+    /// ```
+    pub fn evlrs_mut(&mut self) -> &mut Vec<Vlr> {
+        &mut self.evlrs
+    }
+
+    /// Returns an iterator over all this header's vlrs, regular then extended.
+    ///
+    /// Each record comes with whether it's an evlr and its index in its list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Vlr, Builder};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.vlrs.push(Vlr::default());
+    /// builder.evlrs.push(Vlr::default());
+    /// let header = builder.into_header().unwrap();
+    /// let vlrs: Vec<_> = header.all_vlrs().map(|vlr| (vlr.is_extended, vlr.index)).collect();
+    /// assert_eq!(vec![(false, 0), (true, 0)], vlrs);
+    /// ```
+    pub fn all_vlrs(&self) -> Vlrs<'_> {
+        Vlrs {
+            vlrs: self.vlrs.iter().enumerate(),
+            evlrs: self.evlrs.iter().enumerate(),
+        }
+    }
+
+    /// Returns a mutable iterator over all this header's vlrs, regular then extended.
+    ///
+    /// As with [Header::vlrs_mut], call [Header::recompute_offsets] after resizing records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Vlr, Builder};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.vlrs.push(Vlr::default());
+    /// builder.evlrs.push(Vlr::default());
+    /// let mut header = builder.into_header().unwrap();
+    /// for mut vlr in header.all_vlrs_mut() {
+    ///     vlr.record_id = if vlr.is_extended { 2 } else { 1 };
+    /// }
+    /// assert_eq!(2, header.evlrs()[0].record_id);
+    /// ```
+    pub fn all_vlrs_mut(&mut self) -> VlrsMut<'_> {
+        VlrsMut {
+            vlrs: self.vlrs.iter_mut().enumerate(),
+            evlrs: self.evlrs.iter_mut().enumerate(),
+        }
+    }
+
+    /// Finds the first vlr or evlr with a user id and record id, looking in the vlrs first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let vlr = reader.header().find_vlr("LASF_Projection", 34735).unwrap();
+    /// assert!(!vlr.is_extended);
+    /// assert!(reader.header().find_vlr("LASF_Projection", 1).is_none());
+    /// ```
+    pub fn find_vlr(&self, user_id: &str, record_id: u16) -> Option<VlrRef<'_>> {
+        self.all_vlrs()
+            .find(|vlr| vlr.user_id == user_id && vlr.record_id == record_id)
+    }
+
+    /// Removes every vlr and evlr for which a function returns true, and returns how many were
+    /// removed.
+    ///
+    /// As with [Header::vlrs_mut], call [Header::recompute_offsets] afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Vlr, Builder};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.vlrs.push(Vlr::default());
+    /// builder.evlrs.push(Vlr::default());
+    /// let mut header = builder.into_header().unwrap();
+    /// assert_eq!(1, header.remove_vlrs(|vlr| vlr.is_extended));
+    /// assert_eq!(1, header.all_vlrs().count());
+    /// ```
+    pub fn remove_vlrs<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(VlrRef<'_>) -> bool,
+    {
+        let removed: Vec<(bool, usize)> = self
+            .all_vlrs()
+            .filter(|&vlr| f(vlr))
+            .map(|vlr| (vlr.is_extended, vlr.index))
+            .collect();
+        // Back to front, so the indices of the records still to remove don't move.
+        for &(is_extended, index) in removed.iter().rev() {
+            let _ = if is_extended {
+                self.evlrs.remove(index)
+            } else {
+                self.vlrs.remove(index)
+            };
+        }
+        removed.len()
+    }
+
+    /// Finds and decodes a typed record, from the vlrs or the evlrs.
+    ///
+    /// Returns `Ok(None)` if there's no such record, and an error if it doesn't decode. See
+    /// [VlrRecord] for an example.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{index::Lax, Header};
+    /// assert!(Header::default().get_record::<Lax>().unwrap().is_none());
+    /// ```
+    pub fn get_record<T: VlrRecord>(&self) -> Result<Option<T>> {
+        self.all_vlrs()
+            .find(|vlr| T::matches(vlr))
+            .map(|vlr| T::decode(&vlr.data))
+            .transpose()
+    }
+
+    /// Returns the labels for the points' user data values, if the file has them.
+    ///
+    /// Returns an error if the [UserDataDictionary] is there but doesn't decode.
     ///
     /// # Examples
     ///
     /// ```
-    /// use las::{Vlr, Builder};
-    /// let mut builder = Builder::from((1, 4));
-    /// builder.evlrs.push(Vlr::default());
-    /// let header = builder.into_header().unwrap();
-    /// assert_eq!(1, header.evlrs().len());
+    /// use las::Header;
+    /// assert!(Header::default().user_data_dictionary().unwrap().is_none());
     /// ```
-    pub fn evlrs(&self) -> &Vec<Vlr> {
-        &self.evlrs
+    pub fn user_data_dictionary(&self) -> Result<Option<UserDataDictionary>> {
+        self.get_record()
     }
 
-    /// Returns an iterator over all this header's vlrs, both extended and regular.
+    /// Returns what the laszip vlr says about how the points are compressed.
+    ///
+    /// Returns `None` if the points aren't compressed, or if the laszip vlr is missing or can't
+    /// be read.
     ///
     /// # Examples
     ///
     /// ```
-    /// use las::{Vlr, Builder};
-    /// let mut builder = Builder::from((1, 4));
-    /// builder.vlrs.push(Vlr::default());
-    /// builder.evlrs.push(Vlr::default());
-    /// let header = builder.into_header().unwrap();
-    /// assert_eq!(2, header.all_vlrs().count());
+    /// use las::Reader;
+    ///
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert!(reader.header().laz_vlr_info().is_none());
     /// ```
-    pub fn all_vlrs(&self) -> Vlrs<'_> {
-        Vlrs(self.vlrs.iter().chain(&self.evlrs))
+    pub fn laz_vlr_info(&self) -> Option<LazVlrInfo> {
+        if !self.point_format.is_compressed {
+            return None;
+        }
+        self.vlrs
+            .iter()
+            .find_map(LazVlrInfo::from_vlr)
+            .and_then(|info| info.ok())
     }
 
     /// Returns whether or not this header has any CRS (E)VLRs
@@ -554,6 +1491,66 @@ impl Header {
         false
     }
 
+    /// Checks whether raw point records can be copied from `other` into this header's data as-is.
+    ///
+    /// The point formats, record lengths, transforms, and gps time types must match. Otherwise,
+    /// every difference is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{header::Incompatibility, Builder, Transform};
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// assert!(header.layout_compatible_with(&header).is_ok());
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.transforms.x = Transform { scale: 0.01, offset: 0. };
+    /// let other = builder.into_header().unwrap();
+    /// let incompatibilities = header.layout_compatible_with(&other).unwrap_err();
+    /// assert!(matches!(incompatibilities[..], [Incompatibility::Transforms { .. }]));
+    /// ```
+    pub fn layout_compatible_with(
+        &self,
+        other: &Header,
+    ) -> std::result::Result<(), Vec<Incompatibility>> {
+        let mut incompatibilities = Vec::new();
+        let attributes = |format: &Format| Format {
+            extra_bytes: 0,
+            is_compressed: false,
+            ..*format
+        };
+        if attributes(&self.point_format) != attributes(&other.point_format) {
+            incompatibilities.push(Incompatibility::PointFormat {
+                this: self.point_format,
+                other: other.point_format,
+            });
+        }
+        if self.point_format.len() != other.point_format.len() {
+            incompatibilities.push(Incompatibility::PointDataRecordLength {
+                this: self.point_format.len(),
+                other: other.point_format.len(),
+            });
+        }
+        if self.effective_transforms() != other.effective_transforms() {
+            incompatibilities.push(Incompatibility::Transforms {
+                this: *self.effective_transforms(),
+                other: *other.effective_transforms(),
+            });
+        }
+        if self.gps_time_type != other.gps_time_type {
+            incompatibilities.push(Incompatibility::GpsTimeType {
+                this: self.gps_time_type,
+                other: other.gps_time_type,
+            });
+        }
+        if incompatibilities.is_empty() {
+            Ok(())
+        } else {
+            Err(incompatibilities)
+        }
+    }
+
     /// Converts this header into a raw header.
     ///
     /// # Examples
@@ -562,19 +1559,24 @@ impl Header {
     /// use las::Header;
     /// let raw_header = Header::default().into_raw().unwrap();
     /// ```
-    pub fn into_raw(self) -> Result<raw::Header> {
+    pub fn into_raw(mut self) -> Result<raw::Header> {
+        self.apply_effective_transforms();
         // Scale the bounding box properly
         let bounds = self.bounds.adapt(&self.transforms)?;
         Ok(raw::Header {
             file_signature: raw::LASF,
-            file_source_id: self.file_source_id,
+            file_source_id: if self.version.supports::<FileSourceId>() {
+                self.file_source_id
+            } else {
+                self.reserved
+            },
             global_encoding: self.global_encoding(),
             guid: *self.guid.as_bytes(),
             version: self.version,
             system_identifier: self.system_identifier_raw()?,
             generating_software: self.generating_software_raw()?,
-            file_creation_day_of_year: self.date.map_or(0, |d| d.ordinal() as u16),
-            file_creation_year: self.date.map_or(0, |d| d.year() as u16),
+            file_creation_day_of_year: self.creation_date().map_or(0, |d| d.day_of_year()),
+            file_creation_year: self.creation_date().map_or(0, |d| d.year()),
             header_size: self.header_size()?,
             offset_to_point_data: self.offset_to_point_data()?,
             number_of_variable_length_records: self.number_of_variable_length_records()?,
@@ -594,8 +1596,7 @@ impl Header {
             min_y: bounds.min.y,
             max_z: bounds.max.z,
             min_z: bounds.min.z,
-            // FIXME waveforms
-            start_of_waveform_data_packet_record: None,
+            start_of_waveform_data_packet_record: self.start_of_waveform_data_packet_record,
             evlr: self.evlr()?,
             large_file: self.large_file()?,
             padding: self.padding,
@@ -619,10 +1620,9 @@ impl Header {
             .into_raw()
             .and_then(|raw_header| raw_header.write_to(&mut write))?;
         for vlr in self.vlrs() {
-            (*vlr)
-                .clone()
-                .into_raw(false)
-                .and_then(|raw_vlr| raw_vlr.write_to(&mut write))?;
+            let mut raw_vlr = vlr.clone().into_raw(false)?;
+            raw_vlr.reserved = vlr.reserved_for(self.version());
+            raw_vlr.write_to(&mut write)?;
         }
         if !self.vlr_padding().is_empty() {
             write.write_all(self.vlr_padding())?;
@@ -630,12 +1630,99 @@ impl Header {
         Ok(())
     }
 
+    /// Returns this header's offsets, as of when it was built or its offsets were last
+    /// recomputed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// let header = Header::default();
+    /// assert_eq!(227, header.offsets().offset_to_point_data);
+    /// ```
+    pub fn offsets(&self) -> Offsets {
+        self.offsets
+    }
+
+    /// Returns where the data of one of the vlrs starts, from the start of the header, as of the
+    /// last time the offsets were worked out.
+    pub(crate) fn vlr_data_offset(&self, index: usize) -> u64 {
+        let before: u64 = self.vlrs[..index]
+            .iter()
+            .map(|vlr| VLR_HEADER_SIZE + vlr.data.len() as u64)
+            .sum();
+        self.offsets.header_size + before + VLR_HEADER_SIZE
+    }
+
+    /// Works out this header's offsets again from its current size, vlrs, evlrs, and padding, and
+    /// returns what changed.
+    ///
+    /// A known start of the first evlr moves along with the point data. A [Writer](crate::Writer)
+    /// recomputes the offsets when it's created and when it closes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, Vlr};
+    ///
+    /// let mut header = Header::default();
+    /// header.vlrs_mut().push(Vlr { data: vec![0; 10], ..Default::default() });
+    /// let changes = header.recompute_offsets();
+    /// assert_eq!(227, changes.before.offset_to_point_data);
+    /// assert_eq!(291, changes.after.offset_to_point_data);
+    /// assert_eq!(changes.after, header.offsets());
+    /// ```
+    pub fn recompute_offsets(&mut self) -> OffsetChanges {
+        let before = self.offsets;
+        self.offsets = self.compute_offsets();
+        self.start_of_first_evlr = self.offsets.start_of_first_evlr;
+        OffsetChanges {
+            before,
+            after: self.offsets,
+        }
+    }
+
     pub(crate) fn set_start_of_first_evlr(&mut self, start_of_first_evlr: u64) {
+        let _ = self.recompute_offsets();
         self.start_of_first_evlr = Some(start_of_first_evlr);
+        if !self.evlrs.is_empty() {
+            self.offsets.start_of_first_evlr = Some(start_of_first_evlr);
+        }
+    }
+
+    fn compute_offsets(&self) -> Offsets {
+        let header_size = u64::from(self.version.header_size()) + self.padding.len() as u64;
+        let offset_to_point_data = header_size
+            + self
+                .vlrs
+                .iter()
+                .map(|vlr| vlr.total_len(false) as u64)
+                .sum::<u64>()
+            + self.vlr_padding.len() as u64;
+        let start_of_first_evlr = if self.evlrs.is_empty() {
+            None
+        } else {
+            self.start_of_first_evlr.map(|start| {
+                (start + offset_to_point_data).saturating_sub(self.offsets.offset_to_point_data)
+            })
+        };
+        Offsets {
+            header_size,
+            offset_to_point_data,
+            number_of_vlrs: self.vlrs.len() as u64,
+            number_of_evlrs: self.evlrs.len() as u64,
+            start_of_first_evlr,
+        }
     }
 
     fn global_encoding(&self) -> u16 {
         let mut bits = self.gps_time_type.into();
+        if self.has_internal_waveform_data_packets {
+            bits |= 2;
+        }
+        if self.has_external_waveform_data_packets {
+            bits |= 4;
+        }
         if self.has_synthetic_return_numbers {
             bits |= 8;
         }
@@ -662,22 +1749,14 @@ impl Header {
     }
 
     fn header_size(&self) -> Result<u16> {
-        let header_size = self.version.header_size() as usize + self.padding.len();
-        if header_size > u16::MAX as usize {
-            Err(Error::HeaderTooLarge(header_size))
-        } else {
-            Ok(header_size as u16)
-        }
+        let header_size = self.compute_offsets().header_size;
+        u16::try_from(header_size).map_err(|_| Error::HeaderTooLarge(header_size as usize))
     }
 
     fn offset_to_point_data(&self) -> Result<u32> {
-        let vlr_len = self.vlrs.iter().fold(0, |acc, vlr| acc + vlr.len(false));
-        let offset = self.header_size()? as usize + vlr_len + self.vlr_padding.len();
-        if offset > u32::MAX as usize {
-            Err(Error::OffsetToPointDataTooLarge(offset))
-        } else {
-            Ok(offset as u32)
-        }
+        let _ = self.header_size()?;
+        let offset = self.compute_offsets().offset_to_point_data;
+        u32::try_from(offset).map_err(|_| Error::OffsetToPointDataTooLarge(offset as usize))
     }
 
     fn number_of_variable_length_records(&self) -> Result<u32> {
@@ -741,13 +1820,14 @@ impl Header {
         } else if n > u32::MAX as usize {
             Err(Error::TooManyEvlrs(n))
         } else {
-            let start_of_first_evlr = if let Some(start_of_fist_evlr) = self.start_of_first_evlr {
-                start_of_fist_evlr
-            } else {
-                self.point_data_len()
-                    + self.point_padding.len() as u64
-                    + u64::from(self.offset_to_point_data()?)
-            };
+            let start_of_first_evlr =
+                if let Some(start_of_first_evlr) = self.compute_offsets().start_of_first_evlr {
+                    start_of_first_evlr
+                } else {
+                    self.point_data_len()
+                        + self.point_padding.len() as u64
+                        + u64::from(self.offset_to_point_data()?)
+                };
             Ok(Some(raw::header::Evlr {
                 start_of_first_evlr,
                 number_of_evlrs: n as u32,
@@ -780,28 +1860,39 @@ impl Header {
 
 impl Default for Header {
     fn default() -> Header {
-        Header {
+        let mut header = Header {
             bounds: Default::default(),
-            date: Some(Utc::now().date_naive()),
+            date: Some(Date::today().into()),
+            effective_transforms: None,
             evlrs: Vec::new(),
             file_source_id: 0,
+            reserved: 0,
             generating_software: format!("las-rs {}", env!("CARGO_PKG_VERSION")),
             gps_time_type: GpsTimeType::Week,
             guid: Default::default(),
+            has_external_waveform_data_packets: false,
+            has_internal_waveform_data_packets: false,
             has_synthetic_return_numbers: false,
             has_wkt_crs: false,
+            laz_chunks: None,
             number_of_points: 0,
             number_of_points_by_return: HashMap::new(),
             padding: Vec::new(),
             point_format: Default::default(),
             point_padding: Vec::new(),
+            record_length_deficit: 0,
+            return_count_conflict: None,
             start_of_first_evlr: None,
+            start_of_waveform_data_packet_record: None,
             system_identifier: "las-rs".to_string(),
             transforms: Default::default(),
             version: Default::default(),
             vlr_padding: Vec::new(),
             vlrs: Vec::new(),
-        }
+            offsets: Default::default(),
+        };
+        header.offsets = header.compute_offsets();
+        header
     }
 }
 
@@ -813,17 +1904,479 @@ impl<V: Into<Version>> From<V> for Header {
     }
 }
 
+/// A multi-line summary of the header.
+///
+/// Vlrs and evlrs are listed by user id, record id, and data length; their data aren't printed.
+///
+/// # Examples
+///
+/// ```
+/// use las::Header;
+/// let header = Header::default();
+/// assert!(header.to_string().starts_with("las 1.2, point format 0, 20 byte records\n"));
+/// ```
+impl fmt::Display for RecordLengthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordLengthStatus::Exact => write!(f, "exact"),
+            RecordLengthStatus::Surplus(n) => write!(f, "{} extra bytes", n),
+            RecordLengthStatus::Deficit(n) => write!(f, "{} bytes short, read as zeros", n),
+        }
+    }
+}
+
+impl fmt::Display for Header {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "las {}, {}, {} byte records",
+            self.version,
+            self.point_format,
+            self.point_data_record_length()
+        )?;
+        writeln!(f, "record length: {}", self.record_length_consistency())?;
+        writeln!(f, "points: {}", self.number_of_points)?;
+        writeln!(f, "bounds: {}", self.bounds)?;
+        writeln!(
+            f,
+            "transforms: x {}, y {}, z {}",
+            self.transforms.x, self.transforms.y, self.transforms.z
+        )?;
+        let crs = if self.get_wkt_crs_bytes().is_some() {
+            "wkt"
+        } else if self.has_crs_vlrs() {
+            "geotiff"
+        } else {
+            "none"
+        };
+        writeln!(f, "crs: {}", crs)?;
+        if self.has_crs_vlrs() {
+            writeln!(f, "crs bounds: {}", self.crs_bounds_plausibility())?;
+        }
+        if let Some(info) = self.laz_vlr_info() {
+            writeln!(f, "compression: {}", info)?;
+        }
+        for (name, vlrs) in [("vlrs", &self.vlrs), ("evlrs", &self.evlrs)] {
+            writeln!(f, "{}: {}", name, vlrs.len())?;
+            for vlr in vlrs {
+                writeln!(
+                    f,
+                    "  {} {} ({} bytes)",
+                    vlr.user_id,
+                    vlr.record_id,
+                    vlr.data.len()
+                )?;
+            }
+        }
+        match self.date {
+            Some(date) => writeln!(f, "date: {}", date)?,
+            None => writeln!(f, "date: none")?,
+        }
+        write!(f, "generating software: {}", self.generating_software)
+    }
+}
+
 impl<'a> Iterator for Vlrs<'a> {
-    type Item = &'a Vlr;
-    fn next(&mut self) -> Option<&'a Vlr> {
-        self.0.next()
+    type Item = VlrRef<'a>;
+    fn next(&mut self) -> Option<VlrRef<'a>> {
+        if let Some((index, vlr)) = self.vlrs.next() {
+            Some(VlrRef {
+                vlr,
+                is_extended: false,
+                index,
+            })
+        } else {
+            self.evlrs.next().map(|(index, vlr)| VlrRef {
+                vlr,
+                is_extended: true,
+                index,
+            })
+        }
+    }
+}
+
+impl<'a> Iterator for VlrsMut<'a> {
+    type Item = VlrMut<'a>;
+    fn next(&mut self) -> Option<VlrMut<'a>> {
+        if let Some((index, vlr)) = self.vlrs.next() {
+            Some(VlrMut {
+                vlr,
+                is_extended: false,
+                index,
+            })
+        } else {
+            self.evlrs.next().map(|(index, vlr)| VlrMut {
+                vlr,
+                is_extended: true,
+                index,
+            })
+        }
+    }
+}
+
+impl Deref for VlrRef<'_> {
+    type Target = Vlr;
+    fn deref(&self) -> &Vlr {
+        self.vlr
+    }
+}
+
+impl Deref for VlrMut<'_> {
+    type Target = Vlr;
+    fn deref(&self) -> &Vlr {
+        self.vlr
+    }
+}
+
+impl DerefMut for VlrMut<'_> {
+    fn deref_mut(&mut self) -> &mut Vlr {
+        self.vlr
+    }
+}
+
+/// Returns true if the point data starts with the las 1.0 point data start signature, even though
+/// the offset to point data doesn't count it.
+///
+/// Some las 1.0 writers do this, and it shifts every point by two bytes. Since the first point
+/// could start with the same two bytes, the signature is only trusted when it makes the point
+/// data run exactly to the end of the stream.
+fn starts_with_point_data_start_signature<R: Read + Seek>(
+    mut read: R,
+    offset_to_point_data: u64,
+    offset_to_end_of_points: u64,
+) -> Result<bool> {
+    let end = read.seek(SeekFrom::End(0))?;
+    if offset_to_end_of_points.checked_add(2) != Some(end) {
+        return Ok(false);
+    }
+    let _ = read.seek(SeekFrom::Start(offset_to_point_data))?;
+    let mut signature = [0; 2];
+    read.read_exact(&mut signature)?;
+    Ok(signature == raw::POINT_DATA_START_SIGNATURE)
+}
+
+/// Repairs what a lenient read can in a raw header, returning what was repaired.
+///
+/// `end` is the length of the file, if it's known.
+fn repair(raw_header: &mut raw::Header, end: Option<u64>) -> Vec<Warning> {
+    use crate::utils::AsLasStr;
+
+    let mut warnings = Vec::new();
+    for (field, bytes) in [
+        ("system_identifier", &mut raw_header.system_identifier),
+        ("generating_software", &mut raw_header.generating_software),
+    ] {
+        if bytes.as_ref().as_las_str().is_err() {
+            warnings.push(Warning::NotAscii {
+                field,
+                original: bytes.as_ref().as_las_string_lossy(),
+            });
+            for byte in bytes.iter_mut().filter(|byte| !byte.is_ascii()) {
+                *byte = b'?';
+            }
+        }
+    }
+    let scale = Transform::default().scale;
+    for (axis, scale_factor) in [
+        ('x', &mut raw_header.x_scale_factor),
+        ('y', &mut raw_header.y_scale_factor),
+        ('z', &mut raw_header.z_scale_factor),
+    ] {
+        if *scale_factor == 0. {
+            warnings.push(Warning::ZeroScale { axis, scale });
+            *scale_factor = scale;
+        }
+    }
+    let (year, day_of_year) = (
+        raw_header.file_creation_year,
+        raw_header.file_creation_day_of_year,
+    );
+    if (year, day_of_year) != (0, 0) && Date::new(year, day_of_year).is_err() {
+        warnings.push(Warning::InvalidDate { year, day_of_year });
+    }
+    // Compressed points are smaller than their records, so only uncompressed ones can be counted.
+    let record_length = u64::from(raw_header.point_data_record_length);
+    if let Some(end) = end
+        && Format::new(raw_header.point_data_record_format)
+            .is_ok_and(|format| !format.is_compressed)
+        && record_length > 0
+    {
+        let end = raw_header
+            .evlr
+            .as_ref()
+            .map_or(end, |evlr| evlr.start_of_first_evlr.min(end));
+        let fit = end.saturating_sub(u64::from(raw_header.offset_to_point_data)) / record_length;
+        let declared = raw_header.number_of_point_records();
+        if fit < declared {
+            warnings.push(Warning::TooManyPoints { declared, fit });
+            if raw_header.number_of_point_records > 0 {
+                raw_header.number_of_point_records = fit as u32;
+            }
+            if let Some(large_file) = raw_header.large_file.as_mut() {
+                large_file.number_of_point_records = fit;
+            }
+        }
+    }
+    for warning in &warnings {
+        log::warn!("{warning}");
+    }
+    warnings
+}
+
+/// Builds the header of a file, upgrading its version if it's too low for what's in it.
+fn header_from_file(mut builder: Builder, record_length_deficit: u16) -> Result<Header> {
+    if let Some(version) = builder.minimum_supported_version()
+        && version > builder.version
+    {
+        log::warn!(
+            "upgrading las version to {} (from {})",
+            version,
+            builder.version
+        );
+        builder.version = version;
+    }
+    let mut header = builder.into_header_from_file()?;
+    header.record_length_deficit = record_length_deficit;
+    Ok(header)
+}
+
+/// Wraps an error with the stream position where reading stopped, if the stream can tell.
+fn read_failed<R: Seek>(mut read: R, err: Error) -> Error {
+    match read.stream_position() {
+        Ok(offset) => at(offset, err),
+        Err(_) => err,
+    }
+}
+
+/// Wraps an error from reading a vlr or evlr with its index and the stream position where
+/// reading stopped, if the stream can tell.
+fn vlr_read_failed<R: Seek>(mut read: R, index: u32, extended: bool, err: Error) -> Error {
+    let Ok(offset) = read.stream_position() else {
+        return err;
+    };
+    let source = Box::new(err);
+    if extended {
+        Error::EvlrReadFailed {
+            index,
+            offset,
+            source,
+        }
+    } else {
+        Error::VlrReadFailed {
+            index,
+            offset,
+            source,
+        }
+    }
+}
+
+fn at(offset: u64, err: Error) -> Error {
+    Error::ReadFailed {
+        offset,
+        source: Box::new(err),
     }
 }
 
+/// Returns the length of the stream, leaving its position unchanged, or `None` if the stream
+/// can't seek to its end.
+pub(crate) fn stream_len<R: Seek>(mut read: R) -> Option<u64> {
+    let position = read.stream_position().ok()?;
+    let end = read.seek(SeekFrom::End(0)).ok();
+    let _ = read.seek(SeekFrom::Start(position)).ok()?;
+    end
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn fixture(path: &str) -> Header {
+        let file = std::fs::File::open(path).unwrap();
+        Header::new(std::io::BufReader::new(file)).unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let header = fixture("tests/data/autzen.las");
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(header, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn display_autzen() {
+        let expected = "las 1.2, point format 1, 28 byte records
+record length: exact
+points: 106
+bounds: (635616.31, 848977.79, 407.35) to (638864.6, 853362.37, 536.84)
+transforms: x `0.01 * x + -0`, y `0.01 * x + -0`, z `0.01 * x + -0`
+crs: geotiff
+crs bounds: consistent
+vlrs: 4
+  liblas 2112 (720 bytes)
+  LASF_Projection 34735 (64 bytes)
+  LASF_Projection 34737 (47 bytes)
+  liblas 2112 (720 bytes)
+evlrs: 0
+date: none
+generating software: TerraScan";
+        assert_eq!(expected, fixture("tests/data/autzen.las").to_string());
+    }
+
+    #[test]
+    fn display_extrabytes() {
+        let expected = "las 1.2, point format 3 with 27 extra bytes (compressed), 61 byte records
+record length: 27 extra bytes
+points: 1065
+bounds: (635619.85, 848899.7000000001, 406.59000000000003) to (638982.55, 853535.43, 586.38)
+transforms: x `0.01 * x + 0`, y `0.01 * x + 0`, z `0.01 * x + 0`
+crs: none
+compression: laszip 2.2r0, point-wise chunked, 50000 point chunks: point10 v2, gpstime11 v2, rgb12 v2, byte v2
+vlrs: 2
+  LASF_Spec 4 (960 bytes)
+  laszip encoded 22204 (58 bytes)
+evlrs: 0
+date: 2015-02-22
+generating software: las2las (version 140915)";
+        assert_eq!(expected, fixture("tests/data/extrabytes.laz").to_string());
+    }
+
+    #[test]
+    fn display_evlrs_and_wkt() {
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            user_id: "evlr".to_string(),
+            record_id: 7,
+            data: vec![0; 100_000],
+            ..Default::default()
+        });
+        let mut header = builder.into_header().unwrap();
+        header.set_wkt_crs(b"GEOGCS[]".to_vec()).unwrap();
+        let display = header.to_string();
+        assert!(display.contains("\ncrs: wkt\ncrs bounds: unknown\n"));
+        assert!(display.contains("\nevlrs: 1\n  evlr 7 (100000 bytes)\n"));
+    }
+
+    fn header_bytes_with_vlrs() -> Vec<u8> {
+        let mut builder = Builder::from((1, 2));
+        for _ in 0..2 {
+            builder.vlrs.push(Vlr {
+                data: vec![42; 10],
+                ..Default::default()
+            });
+        }
+        let mut bytes = Vec::new();
+        builder.into_header().unwrap().write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn vlrs_fit() {
+        let bytes = header_bytes_with_vlrs();
+        let header = Header::new(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(2, header.vlrs().len());
+    }
+
+    #[test]
+    fn vlr_past_point_data() {
+        let mut bytes = header_bytes_with_vlrs();
+        // The second vlr's record length, which sits 20 bytes into its header.
+        let offset = 227 + 64 + 20;
+        bytes[offset..offset + 2].copy_from_slice(&11u16.to_le_bytes());
+        bytes.extend([0; 100]);
+        match Header::new(std::io::Cursor::new(bytes)).unwrap_err() {
+            Error::VlrPastPointData {
+                index,
+                len,
+                remaining,
+            } => {
+                assert_eq!(1, index);
+                assert_eq!(11, len);
+                assert_eq!(10, remaining);
+            }
+            err => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn offset_to_point_data_before_vlrs_end() {
+        let mut bytes = header_bytes_with_vlrs();
+        bytes[96..100].copy_from_slice(&(227u32 + 64 + 60).to_le_bytes());
+        bytes.extend([0; 100]);
+        assert!(matches!(
+            Header::new(std::io::Cursor::new(bytes)).unwrap_err(),
+            Error::VlrPastPointData {
+                index: 1,
+                len: 10,
+                remaining: 6,
+            }
+        ));
+    }
+
+    #[test]
+    fn absurd_number_of_vlrs() {
+        let mut bytes = header_bytes_with_vlrs();
+        bytes[100..104].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Header::new(std::io::Cursor::new(bytes)).unwrap_err(),
+            Error::TooManyVlrs(n) if n == u32::MAX as usize
+        ));
+    }
+
+    /// Returns a las 1.4 file with no points and one evlr with ten bytes of data.
+    fn file_bytes_with_evlr() -> Vec<u8> {
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            data: vec![42; 10],
+            ..Default::default()
+        });
+        crate::synthetic::fixture::write(builder, Vec::<Point>::new())
+    }
+
+    #[test]
+    fn evlr_fits() {
+        let bytes = file_bytes_with_evlr();
+        let header = Header::new(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(vec![42; 10], header.evlrs()[0].data);
+    }
+
+    #[test]
+    fn evlr_past_end() {
+        let mut bytes = file_bytes_with_evlr();
+        // The evlr's record length, which sits 20 bytes into its header.
+        let offset = bytes.len() - 70 + 20;
+        bytes[offset..offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            Header::new(std::io::Cursor::new(bytes)).unwrap_err(),
+            Error::EvlrPastEnd {
+                index: 0,
+                len: u64::MAX,
+                remaining: 10,
+            }
+        ));
+    }
+
+    #[test]
+    fn absurd_number_of_evlrs() {
+        let mut bytes = file_bytes_with_evlr();
+        bytes[243..247].copy_from_slice(&1_000_000_000u32.to_le_bytes());
+        assert!(matches!(
+            Header::new(std::io::Cursor::new(bytes)).unwrap_err(),
+            Error::TooManyEvlrs(1_000_000_000)
+        ));
+    }
+
+    #[test]
+    fn raw_vlr_length_past_end() {
+        let mut bytes = file_bytes_with_evlr();
+        let start = bytes.len() - 70;
+        bytes[start + 20..start + 28].copy_from_slice(&u64::MAX.to_le_bytes());
+        let err = raw::Vlr::read_from(&bytes[start..], true).unwrap_err();
+        assert!(
+            matches!(err, Error::Io(ref err) if err.kind() == std::io::ErrorKind::UnexpectedEof)
+        );
+    }
+
     #[test]
     fn number_of_points_by_return_zero_return_number() {
         let mut header = Header::default();
@@ -916,6 +2469,7 @@ mod tests {
         let header = Header::from_raw(raw_header).unwrap();
         assert_eq!(42, header.number_of_points());
         assert_eq!(42, header.number_of_points_by_return(1).unwrap());
+        assert!(!header.return_count_conflict().unwrap().used_extended);
     }
 
     #[test]
@@ -957,6 +2511,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn room_for_legacy_point_counts() {
+        let mut header = Header::from((1, 2));
+        header.number_of_points = u64::from(u32::MAX) - 1;
+        assert!(header.verify_room_for(1, []).is_ok());
+        assert!(matches!(
+            header.verify_room_for(2, []).unwrap_err(),
+            Error::TooManyPoints { n, .. } if n == u64::from(u32::MAX) + 1
+        ));
+        let _ = header
+            .number_of_points_by_return
+            .insert(1, u64::from(u32::MAX));
+        assert!(header.verify_room_for(1, [(2, 1)]).is_ok());
+        assert!(header.verify_room_for(1, [(1, 1)]).is_err());
+        assert!(header.verify_room_for(1, [(6, 1)]).is_err());
+        assert!(header.verify_room_for(1, [(0, 1)]).is_ok());
+    }
+
+    #[test]
+    fn room_for_large_point_counts() {
+        let mut header = Header::from((1, 4));
+        header.number_of_points = u64::from(u32::MAX);
+        assert!(header.verify_room_for(1, [(6, 1)]).is_ok());
+    }
+
+    #[test]
+    fn gps_time_type_bit() {
+        for gps_time_type in [GpsTimeType::Week, GpsTimeType::Standard] {
+            let mut builder = Builder::from((1, 4));
+            builder.gps_time_type = gps_time_type;
+            let raw_header = builder.into_header().unwrap().into_raw().unwrap();
+            assert_eq!(
+                gps_time_type.is_standard(),
+                raw_header.global_encoding & 1 == 1
+            );
+            let header = Header::from_raw(raw_header).unwrap();
+            assert_eq!(gps_time_type, header.gps_time_type());
+        }
+    }
+
+    #[test]
+    fn read_extended_format_with_week_time() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        builder.gps_time_type = GpsTimeType::Standard;
+        let mut raw_header = builder.into_header().unwrap().into_raw().unwrap();
+        raw_header.global_encoding &= !1;
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        raw_header.write_to(&mut cursor).unwrap();
+        cursor.set_position(0);
+        let header = Header::new(cursor).unwrap();
+        assert_eq!(GpsTimeType::Week, header.gps_time_type());
+        let header = header.into_builder().into_header().unwrap();
+        assert_eq!(GpsTimeType::Week, header.gps_time_type());
+    }
+
     #[test]
     fn wkt_bit() {
         let mut header = Header::from((1, 4));
@@ -988,4 +2598,149 @@ mod tests {
         builder.vlr_padding = vec![0; u32::MAX as usize - 226];
         assert!(builder.into_header().unwrap().into_raw().is_err());
     }
+
+    #[test]
+    fn layout_compatible() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(3).unwrap();
+        let header = builder.clone().into_header().unwrap();
+        builder.point_format.is_compressed = true;
+        builder.generating_software = "something else".to_string();
+        let other = builder.into_header().unwrap();
+        assert_eq!(Ok(()), header.layout_compatible_with(&other));
+    }
+
+    #[test]
+    fn layout_different_scale() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(3).unwrap();
+        let header = builder.clone().into_header().unwrap();
+        builder.transforms.z.scale = 0.01;
+        let other = builder.into_header().unwrap();
+        assert_eq!(
+            Err(vec![Incompatibility::Transforms {
+                this: *header.transforms(),
+                other: *other.transforms(),
+            }]),
+            header.layout_compatible_with(&other)
+        );
+    }
+
+    #[test]
+    fn layout_different_extra_bytes() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        builder.gps_time_type = GpsTimeType::Standard;
+        builder.point_format.extra_bytes = 4;
+        let header = builder.clone().into_header().unwrap();
+        builder.point_format.extra_bytes = 8;
+        let other = builder.into_header().unwrap();
+        assert_eq!(
+            Err(vec![Incompatibility::PointDataRecordLength {
+                this: 34,
+                other: 38
+            }]),
+            header.layout_compatible_with(&other)
+        );
+    }
+
+    #[test]
+    fn layout_everything_different() {
+        let header = Builder::from((1, 4)).into_header().unwrap();
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(1).unwrap();
+        builder.transforms.x.offset = 100.;
+        builder.gps_time_type = GpsTimeType::Standard;
+        let other = builder.into_header().unwrap();
+        assert_eq!(
+            Err(vec![
+                Incompatibility::PointFormat {
+                    this: Format::new(0).unwrap(),
+                    other: Format::new(1).unwrap(),
+                },
+                Incompatibility::PointDataRecordLength {
+                    this: 20,
+                    other: 28
+                },
+                Incompatibility::Transforms {
+                    this: *header.transforms(),
+                    other: *other.transforms(),
+                },
+                Incompatibility::GpsTimeType {
+                    this: GpsTimeType::Week,
+                    other: GpsTimeType::Standard,
+                },
+            ]),
+            header.layout_compatible_with(&other)
+        );
+    }
+
+    /// A header with a vlr and an evlr that share an id, between two other vlrs.
+    fn shared_id() -> Header {
+        let vlr = |user_id: &str, record_id, data: u8| Vlr {
+            user_id: user_id.to_string(),
+            record_id,
+            data: vec![data],
+            ..Default::default()
+        };
+        let mut builder = Builder::from((1, 4));
+        builder.vlrs.push(vlr("other", 1, 0));
+        builder.vlrs.push(vlr("shared", 42, 1));
+        builder.evlrs.push(vlr("shared", 42, 2));
+        builder.evlrs.push(vlr("other", 2, 3));
+        builder.into_header().unwrap()
+    }
+
+    #[test]
+    fn all_vlrs_provenance() {
+        let header = shared_id();
+        let shared: Vec<(bool, usize, u8)> = header
+            .all_vlrs()
+            .filter(|vlr| vlr.user_id == "shared")
+            .map(|vlr| (vlr.is_extended, vlr.index, vlr.data[0]))
+            .collect();
+        assert_eq!(vec![(false, 1, 1), (true, 0, 2)], shared);
+        assert_eq!(
+            Some(VlrRef {
+                vlr: &header.vlrs()[1],
+                is_extended: false,
+                index: 1,
+            }),
+            header.find_vlr("shared", 42)
+        );
+        let evlr = header.find_vlr("other", 2).unwrap();
+        assert!(evlr.is_extended);
+        assert_eq!(1, evlr.index);
+    }
+
+    #[test]
+    fn all_vlrs_mut_provenance() {
+        let mut header = shared_id();
+        for mut vlr in header.all_vlrs_mut() {
+            if vlr.user_id == "shared" {
+                vlr.data = vec![u8::from(vlr.is_extended); vlr.index + 1];
+            }
+        }
+        assert_eq!(vec![0, 0], header.vlrs()[1].data);
+        assert_eq!(vec![1], header.evlrs()[0].data);
+    }
+
+    #[test]
+    fn remove_vlrs_from_both_lists() {
+        let mut header = shared_id();
+        assert_eq!(2, header.remove_vlrs(|vlr| vlr.user_id == "shared"));
+        let left: Vec<(bool, usize, u8)> = header
+            .all_vlrs()
+            .map(|vlr| (vlr.is_extended, vlr.index, vlr.data[0]))
+            .collect();
+        assert_eq!(vec![(false, 0, 0), (true, 0, 3)], left);
+
+        let mut header = shared_id();
+        assert_eq!(
+            1,
+            header.remove_vlrs(|vlr| vlr.is_extended && vlr.index == 0)
+        );
+        assert_eq!(vec![1], header.find_vlr("shared", 42).unwrap().data);
+        assert_eq!(3, header.all_vlrs().count());
+    }
 }
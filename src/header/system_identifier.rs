@@ -0,0 +1,171 @@
+use crate::{
+    utils::{AsLasStr, FromLasStr},
+    Result,
+};
+use std::fmt;
+
+/// What a header's system identifier says made the points.
+///
+/// The las spec has conventional values for files that derive from others, e.g. `MERGE` for a
+/// file made by merging several. Anything else is the name of the sensor, or, if it starts with
+/// `OTHER`, some other source. The string is kept as it is, so it's written back unchanged:
+///
+/// ```
+/// use las::header::SystemIdentifier;
+///
+/// assert_eq!(SystemIdentifier::Merge, "MERGE".parse().unwrap());
+/// let sensor: SystemIdentifier = "Riegl VQ-1560i".parse().unwrap();
+/// assert_eq!(SystemIdentifier::Sensor("Riegl VQ-1560i".to_string()), sensor);
+/// assert_eq!("Riegl VQ-1560i", sensor.to_string());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SystemIdentifier {
+    /// The hardware system that collected the points.
+    Sensor(String),
+
+    /// `MERGE`, made by merging several files.
+    Merge,
+
+    /// `EXTRACTION`, made from some of another file's points.
+    Extraction,
+
+    /// `MODIFICATION`, made by changing another file's points.
+    Modification,
+
+    /// `TRANSFORMATION`, made by changing another file's coordinates.
+    Transformation,
+
+    /// Some other source, starting with `OTHER`.
+    Other(String),
+}
+
+impl SystemIdentifier {
+    /// Reads a system identifier from the header field.
+    ///
+    /// Characters that aren't ascii are replaced, like when a header is read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::header::SystemIdentifier;
+    /// let mut bytes = [0; 32];
+    /// bytes[..10].copy_from_slice(b"EXTRACTION");
+    /// assert_eq!(SystemIdentifier::Extraction, SystemIdentifier::from_bytes(&bytes));
+    /// ```
+    pub fn from_bytes(bytes: &[u8; 32]) -> SystemIdentifier {
+        SystemIdentifier::from(bytes.as_ref().as_las_string_lossy().as_str())
+    }
+
+    /// Writes this system identifier into a header field.
+    ///
+    /// Returns an error if it isn't ascii or is longer than 32 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::header::SystemIdentifier;
+    /// let bytes = SystemIdentifier::Merge.to_bytes().unwrap();
+    /// assert_eq!(b"MERGE\0", &bytes[..6]);
+    /// assert!(SystemIdentifier::Sensor("x".repeat(33)).to_bytes().is_err());
+    /// ```
+    pub fn to_bytes(&self) -> Result<[u8; 32]> {
+        let mut bytes = [0; 32];
+        bytes.as_mut().from_las_str(&self.to_string())?;
+        Ok(bytes)
+    }
+
+    /// Returns true if this is one of the spec's values for a file made from others.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::header::SystemIdentifier;
+    /// assert!(SystemIdentifier::Merge.is_derived());
+    /// assert!(!SystemIdentifier::Sensor("Leica ALS80".to_string()).is_derived());
+    /// ```
+    pub fn is_derived(&self) -> bool {
+        matches!(
+            self,
+            SystemIdentifier::Merge
+                | SystemIdentifier::Extraction
+                | SystemIdentifier::Modification
+                | SystemIdentifier::Transformation
+        )
+    }
+}
+
+impl From<&str> for SystemIdentifier {
+    fn from(s: &str) -> SystemIdentifier {
+        match s {
+            "MERGE" => SystemIdentifier::Merge,
+            "EXTRACTION" => SystemIdentifier::Extraction,
+            "MODIFICATION" => SystemIdentifier::Modification,
+            "TRANSFORMATION" => SystemIdentifier::Transformation,
+            _ if s.starts_with("OTHER") => SystemIdentifier::Other(s.to_string()),
+            _ => SystemIdentifier::Sensor(s.to_string()),
+        }
+    }
+}
+
+impl std::str::FromStr for SystemIdentifier {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<SystemIdentifier, Self::Err> {
+        Ok(SystemIdentifier::from(s))
+    }
+}
+
+impl fmt::Display for SystemIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemIdentifier::Sensor(s) | SystemIdentifier::Other(s) => f.write_str(s),
+            SystemIdentifier::Merge => f.write_str("MERGE"),
+            SystemIdentifier::Extraction => f.write_str("EXTRACTION"),
+            SystemIdentifier::Modification => f.write_str("MODIFICATION"),
+            SystemIdentifier::Transformation => f.write_str("TRANSFORMATION"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for s in [
+            "MERGE",
+            "EXTRACTION",
+            "MODIFICATION",
+            "TRANSFORMATION",
+            "OTHER",
+            "OTHER: hand digitized",
+            "Riegl VQ-1560i",
+            "merge",
+            "",
+        ] {
+            let system_identifier = SystemIdentifier::from(s);
+            assert_eq!(s, system_identifier.to_string());
+            assert_eq!(
+                system_identifier,
+                SystemIdentifier::from_bytes(&system_identifier.to_bytes().unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(
+            SystemIdentifier::Other("OTHER".to_string()),
+            SystemIdentifier::from("OTHER")
+        );
+        assert_eq!(
+            SystemIdentifier::Sensor("merge".to_string()),
+            SystemIdentifier::from("merge")
+        );
+        assert_eq!(
+            SystemIdentifier::Transformation,
+            SystemIdentifier::from("TRANSFORMATION")
+        );
+    }
+}
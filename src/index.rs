@@ -0,0 +1,588 @@
+//! Read, build, and write [LAStools](https://lastools.github.io/) spatial indices.
+//!
+//! `lasindex` writes a quadtree over the xy extent of a file, where each cell lists the intervals
+//! of point indices that fall inside of it. The index lives in a `.lax` file next to the las or laz
+//! file, or is appended to the file itself as an evlr.
+//!
+//! A [Reader] picks up the index automatically, and uses it in [Reader::points_in_bounds]. A file
+//! without one can be indexed with [Indexer::index], and the index written to a `.lax` file with
+//! [Lax::write_to]:
+//!
+//! ```
+//! use las::{index::Indexer, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! assert!(!reader.has_spatial_index());
+//! let lax = Indexer::index(&mut reader).unwrap();
+//! let mut sidecar = Vec::new();
+//! lax.write_to(&mut sidecar).unwrap();
+//! let mut reader = reader.with_spatial_index(lax);
+//! let points = reader.points_in_bounds(reader.header().bounds()).unwrap();
+//! assert_eq!(106, points.len());
+//! ```
+
+use crate::{Bounds, Error, Reader, Result, Vlr, VlrRecord};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{Read, Seek, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// The user id of the evlr that holds an appended spatial index.
+pub const USER_ID: &str = "LAStools";
+
+/// The record id of the evlr that holds an appended spatial index.
+pub const RECORD_ID: u16 = 30;
+
+const INDEX_SIGNATURE: [u8; 4] = *b"LASX";
+const QUADTREE_SIGNATURE: [u8; 4] = *b"LASS";
+const QUADTREE_HEADER_SIGNATURE: [u8; 4] = *b"LASQ";
+const INTERVAL_SIGNATURE: [u8; 4] = *b"LASV";
+const QUADTREE_TYPE: u32 = 0;
+
+/// The number of levels in a written quadtree when no cell size is given.
+const DEFAULT_LEVELS: u32 = 8;
+
+/// The most levels a written quadtree can have.
+const MAX_LEVELS: u32 = 12;
+
+/// Where a [Writer](crate::Writer) puts the spatial index that it builds.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IndexTarget {
+    /// A `.lax` file at this path.
+    Sidecar(PathBuf),
+
+    /// An evlr appended to the file, which requires las 1.4.
+    Evlr,
+}
+
+/// A LAStools spatial index, as stored in a `.lax` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lax {
+    levels: u32,
+    min_x: f32,
+    max_x: f32,
+    min_y: f32,
+    max_y: f32,
+    cells: Vec<Cell>,
+}
+
+/// A quadtree cell and the intervals of points inside of it.
+#[derive(Clone, Debug, PartialEq)]
+struct Cell {
+    index: i32,
+    min_x: f64,
+    max_x: f64,
+    min_y: f64,
+    max_y: f64,
+    intervals: Vec<Range<u64>>,
+}
+
+impl Lax {
+    /// Reads a spatial index.
+    ///
+    /// The quadtree's `LASQ` version block, which `lasindex` writes after the spatial type, is
+    /// optional.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use las::index::Lax;
+    ///
+    /// let lax = Lax::read_from(File::open("points.lax").unwrap()).unwrap();
+    /// ```
+    pub fn read_from<R: Read>(mut read: R) -> Result<Lax> {
+        read_signature(&mut read, INDEX_SIGNATURE)?;
+        let _version = read.read_u32::<LittleEndian>()?;
+
+        read_signature(&mut read, QUADTREE_SIGNATURE)?;
+        let spatial_type = read.read_u32::<LittleEndian>()?;
+        if spatial_type != QUADTREE_TYPE {
+            return Err(Error::InvalidLax(format!(
+                "unsupported spatial type: {spatial_type}"
+            )));
+        }
+        let mut levels = [0; 4];
+        read.read_exact(&mut levels)?;
+        if levels == QUADTREE_HEADER_SIGNATURE {
+            let _version = read.read_u32::<LittleEndian>()?;
+            read.read_exact(&mut levels)?;
+        }
+        let levels = u32::from_le_bytes(levels);
+        // Cell indices can't be decoded past this many levels.
+        if levels > 15 {
+            return Err(Error::InvalidLax(format!("too many levels: {levels}")));
+        }
+        let level_index = read.read_u32::<LittleEndian>()?;
+        let _implicit_levels = read.read_u32::<LittleEndian>()?;
+        if level_index != 0 {
+            return Err(Error::InvalidLax(format!(
+                "unsupported level index: {level_index}"
+            )));
+        }
+        let mut lax = Lax {
+            levels,
+            min_x: read.read_f32::<LittleEndian>()?,
+            max_x: read.read_f32::<LittleEndian>()?,
+            min_y: read.read_f32::<LittleEndian>()?,
+            max_y: read.read_f32::<LittleEndian>()?,
+            cells: Vec::new(),
+        };
+
+        read_signature(&mut read, INTERVAL_SIGNATURE)?;
+        let _version = read.read_u32::<LittleEndian>()?;
+        let number_of_cells = read.read_u32::<LittleEndian>()?;
+        for _ in 0..number_of_cells {
+            let cell_index = read.read_i32::<LittleEndian>()?;
+            let number_of_intervals = read.read_u32::<LittleEndian>()?;
+            let _number_of_points = read.read_u32::<LittleEndian>()?;
+            let mut cell = lax.cell(cell_index)?;
+            for _ in 0..number_of_intervals {
+                let start = read.read_u32::<LittleEndian>()?;
+                let end = read.read_u32::<LittleEndian>()?;
+                if end < start {
+                    return Err(Error::InvalidLax(format!(
+                        "interval ends before it starts: {start}..={end}"
+                    )));
+                }
+                cell.intervals.push(u64::from(start)..u64::from(end) + 1);
+            }
+            lax.cells.push(cell);
+        }
+        Ok(lax)
+    }
+
+    /// Reads a spatial index from a path.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use las::index::Lax;
+    /// let lax = Lax::from_path("points.lax").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Lax> {
+        File::open(path)
+            .map_err(Error::from)
+            .and_then(|file| Lax::read_from(std::io::BufReader::new(file)))
+    }
+
+    /// Reads a spatial index from an appended evlr.
+    ///
+    /// Returns `None` if the vlr isn't a spatial index.
+    pub fn from_vlr(vlr: &Vlr) -> Option<Result<Lax>> {
+        Lax::matches(vlr).then(|| Lax::decode(&vlr.data))
+    }
+
+    /// Writes this spatial index.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use las::index::Lax;
+    /// let lax = Lax::from_path("points.lax").unwrap();
+    /// let mut bytes = Vec::new();
+    /// lax.write_to(&mut bytes).unwrap();
+    /// ```
+    pub fn write_to<W: Write>(&self, mut write: W) -> Result<()> {
+        write.write_all(&INDEX_SIGNATURE)?;
+        write.write_u32::<LittleEndian>(0)?;
+
+        write.write_all(&QUADTREE_SIGNATURE)?;
+        write.write_u32::<LittleEndian>(QUADTREE_TYPE)?;
+        write.write_all(&QUADTREE_HEADER_SIGNATURE)?;
+        write.write_u32::<LittleEndian>(0)?;
+        write.write_u32::<LittleEndian>(self.levels)?;
+        write.write_u32::<LittleEndian>(0)?;
+        write.write_u32::<LittleEndian>(0)?;
+        write.write_f32::<LittleEndian>(self.min_x)?;
+        write.write_f32::<LittleEndian>(self.max_x)?;
+        write.write_f32::<LittleEndian>(self.min_y)?;
+        write.write_f32::<LittleEndian>(self.max_y)?;
+
+        write.write_all(&INTERVAL_SIGNATURE)?;
+        write.write_u32::<LittleEndian>(0)?;
+        write.write_u32::<LittleEndian>(u32::try_from(self.cells.len())?)?;
+        for cell in &self.cells {
+            write.write_i32::<LittleEndian>(cell.index)?;
+            write.write_u32::<LittleEndian>(u32::try_from(cell.intervals.len())?)?;
+            let number_of_points: u64 = cell
+                .intervals
+                .iter()
+                .map(|interval| interval.end - interval.start)
+                .sum();
+            write.write_u32::<LittleEndian>(u32::try_from(number_of_points)?)?;
+            for interval in &cell.intervals {
+                write.write_u32::<LittleEndian>(u32::try_from(interval.start)?)?;
+                write.write_u32::<LittleEndian>(u32::try_from(interval.end - 1)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts this spatial index into the evlr that LAStools appends to a file.
+    pub fn into_vlr(self) -> Result<Vlr> {
+        self.to_vlr()
+    }
+
+    /// Returns the sorted, non-overlapping ranges of point indices in the cells that overlap the
+    /// bounds in xy.
+    ///
+    /// Points in these ranges might still be outside of the bounds, but every point inside of the
+    /// bounds is in one of these ranges.
+    pub fn intervals(&self, bounds: &Bounds) -> Vec<Range<u64>> {
+        let mut intervals: Vec<Range<u64>> = self
+            .cells
+            .iter()
+            .filter(|cell| {
+                cell.min_x <= bounds.max.x
+                    && bounds.min.x <= cell.max_x
+                    && cell.min_y <= bounds.max.y
+                    && bounds.min.y <= cell.max_y
+            })
+            .flat_map(|cell| cell.intervals.iter().cloned())
+            .collect();
+        intervals.sort_by_key(|interval| interval.start);
+        let mut merged: Vec<Range<u64>> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+                _ => merged.push(interval),
+            }
+        }
+        merged
+    }
+
+    /// Builds a cell from its index, which counts up through the cells of every level.
+    ///
+    /// The bounds are computed in `f32`, the same way `lasindex` assigns points to cells. Cells on
+    /// the edge of the quadtree reach out to infinity, so points that strayed just outside of the
+    /// quadtree are still found.
+    fn cell(&self, cell_index: i32) -> Result<Cell> {
+        let invalid = || Error::InvalidLax(format!("invalid cell index: {cell_index}"));
+        let mut level_index = u64::try_from(cell_index).map_err(|_| invalid())?;
+        let mut level = 0;
+        while level_index >= 1 << (2 * level) {
+            level_index -= 1 << (2 * level);
+            level += 1;
+            if level > self.levels {
+                return Err(invalid());
+            }
+        }
+        let (mut min_x, mut max_x, mut min_y, mut max_y) =
+            (self.min_x, self.max_x, self.min_y, self.max_y);
+        while level > 0 {
+            level -= 1;
+            let mid_x = (min_x + max_x) / 2.;
+            let mid_y = (min_y + max_y) / 2.;
+            let quadrant = (level_index >> (2 * level)) & 3;
+            if quadrant & 1 == 1 {
+                min_x = mid_x;
+            } else {
+                max_x = mid_x;
+            }
+            if quadrant & 2 == 2 {
+                min_y = mid_y;
+            } else {
+                max_y = mid_y;
+            }
+        }
+        let edge = |value: f32, edge: f32, infinity: f64| {
+            if value == edge {
+                infinity
+            } else {
+                f64::from(value)
+            }
+        };
+        Ok(Cell {
+            index: cell_index,
+            min_x: edge(min_x, self.min_x, f64::NEG_INFINITY),
+            max_x: edge(max_x, self.max_x, f64::INFINITY),
+            min_y: edge(min_y, self.min_y, f64::NEG_INFINITY),
+            max_y: edge(max_y, self.max_y, f64::INFINITY),
+            intervals: Vec::new(),
+        })
+    }
+}
+
+impl VlrRecord for Lax {
+    const USER_ID: &'static str = USER_ID;
+    const RECORD_ID: u16 = RECORD_ID;
+    const DESCRIPTION: &'static str = "LAX spatial indexing (LASX)";
+
+    fn decode(data: &[u8]) -> Result<Lax> {
+        Lax::read_from(data)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.write_to(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// Builds a quadtree spatial index, from a file's points or as points stream through a writer.
+///
+/// # Examples
+///
+/// ```
+/// use las::{index::Indexer, Reader};
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let lax = Indexer::index(&mut reader).unwrap();
+/// let reader = reader.with_spatial_index(lax);
+/// assert!(reader.has_spatial_index());
+/// ```
+#[derive(Debug)]
+pub struct Indexer {
+    levels: u32,
+    min_x: f32,
+    max_x: f32,
+    min_y: f32,
+    max_y: f32,
+    cells: BTreeMap<u32, Vec<Range<u64>>>,
+}
+
+impl Indexer {
+    /// Builds a spatial index over every point of a reader, like `lasindex`.
+    ///
+    /// The quadtree covers the header's bounds and has eight levels. The reader is read from the
+    /// start, and is left after its last point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{index::Indexer, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let lax = Indexer::index(&mut reader).unwrap();
+    /// let bounds = reader.header().bounds();
+    /// assert_eq!(vec![0..106], lax.intervals(&bounds));
+    /// ```
+    pub fn index<R: Read + Seek + Send>(reader: &mut Reader<R>) -> Result<Lax> {
+        let mut indexer = Indexer::new(&reader.header().bounds(), None);
+        reader.seek(0)?;
+        for result in reader.indexed_points() {
+            let (index, point) = result?;
+            indexer.add(index, point.x, point.y);
+        }
+        indexer.into_lax()
+    }
+
+    /// Creates an indexer whose quadtree covers the bounds in xy.
+    ///
+    /// Like `lasindex`, the quadtree is square and snapped to multiples of the cell size. If there
+    /// is no cell size, the quadtree has eight levels.
+    pub(crate) fn new(bounds: &Bounds, cell_size: Option<f64>) -> Indexer {
+        let extent = (bounds.max.x - bounds.min.x).max(bounds.max.y - bounds.min.y);
+        let cell_size = cell_size
+            .unwrap_or(extent / f64::from(1 << DEFAULT_LEVELS))
+            .max(f64::EPSILON * extent.abs().max(1.));
+        let min_x = cell_size * (bounds.min.x / cell_size).floor();
+        let min_y = cell_size * (bounds.min.y / cell_size).floor();
+        let extent = (cell_size * (bounds.max.x / cell_size).ceil() - min_x)
+            .max(cell_size * (bounds.max.y / cell_size).ceil() - min_y);
+        let mut levels = 0;
+        let mut size = cell_size;
+        while size < extent && levels < MAX_LEVELS {
+            size *= 2.;
+            levels += 1;
+        }
+        let size = size.max(extent);
+        Indexer {
+            levels,
+            min_x: min_x as f32,
+            max_x: (min_x + size) as f32,
+            min_y: min_y as f32,
+            max_y: (min_y + size) as f32,
+            cells: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a point, which must come right after the previously added point.
+    pub(crate) fn add(&mut self, index: u64, x: f64, y: f64) {
+        let (mut min_x, mut max_x, mut min_y, mut max_y) =
+            (self.min_x, self.max_x, self.min_y, self.max_y);
+        let mut level_index = 0;
+        for _ in 0..self.levels {
+            level_index <<= 2;
+            let mid_x = (min_x + max_x) / 2.;
+            let mid_y = (min_y + max_y) / 2.;
+            if x < f64::from(mid_x) {
+                max_x = mid_x;
+            } else {
+                min_x = mid_x;
+                level_index |= 1;
+            }
+            if y < f64::from(mid_y) {
+                max_y = mid_y;
+            } else {
+                min_y = mid_y;
+                level_index |= 2;
+            }
+        }
+        let level_offset: u32 = (0..self.levels).map(|level| 1 << (2 * level)).sum();
+        let intervals = self.cells.entry(level_offset + level_index).or_default();
+        match intervals.last_mut() {
+            Some(interval) if interval.end == index => interval.end += 1,
+            _ => intervals.push(index..index + 1),
+        }
+    }
+
+    /// Finishes the spatial index.
+    pub(crate) fn into_lax(self) -> Result<Lax> {
+        let mut lax = Lax {
+            levels: self.levels,
+            min_x: self.min_x,
+            max_x: self.max_x,
+            min_y: self.min_y,
+            max_y: self.max_y,
+            cells: Vec::with_capacity(self.cells.len()),
+        };
+        for (cell_index, intervals) in self.cells {
+            let mut cell = lax.cell(i32::try_from(cell_index)?)?;
+            cell.intervals = intervals;
+            lax.cells.push(cell);
+        }
+        Ok(lax)
+    }
+}
+
+fn read_signature<R: Read>(mut read: R, expected: [u8; 4]) -> Result<()> {
+    let mut signature = [0; 4];
+    read.read_exact(&mut signature)?;
+    if signature == expected {
+        Ok(())
+    } else {
+        Err(Error::InvalidLax(format!(
+            "expected signature {:?}, found {:?}",
+            String::from_utf8_lossy(&expected),
+            String::from_utf8_lossy(&signature)
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vector;
+
+    /// Lays out a one level index byte by byte, the way `lasindex` writes it.
+    fn lax(cells: &[(i32, &[(u32, u32)])]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(INDEX_SIGNATURE);
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(QUADTREE_SIGNATURE);
+        bytes.extend(QUADTREE_TYPE.to_le_bytes());
+        bytes.extend(QUADTREE_HEADER_SIGNATURE);
+        bytes.extend(0u32.to_le_bytes());
+        for n in [1u32, 0, 0] {
+            bytes.extend(n.to_le_bytes());
+        }
+        for n in [0f32, 10., 0., 10.] {
+            bytes.extend(n.to_le_bytes());
+        }
+        bytes.extend(INTERVAL_SIGNATURE);
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend((cells.len() as u32).to_le_bytes());
+        for (cell_index, intervals) in cells {
+            bytes.extend(cell_index.to_le_bytes());
+            bytes.extend((intervals.len() as u32).to_le_bytes());
+            let number_of_points: u32 = intervals.iter().map(|(start, end)| end - start + 1).sum();
+            bytes.extend(number_of_points.to_le_bytes());
+            for (start, end) in *intervals {
+                bytes.extend(start.to_le_bytes());
+                bytes.extend(end.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    fn bounds(min_x: f64, max_x: f64, min_y: f64, max_y: f64) -> Bounds {
+        Bounds {
+            min: Vector {
+                x: min_x,
+                y: min_y,
+                z: f64::NEG_INFINITY,
+            },
+            max: Vector {
+                x: max_x,
+                y: max_y,
+                z: f64::INFINITY,
+            },
+        }
+    }
+
+    #[test]
+    fn quadrants() {
+        // Level one cells start at index 1, and go lower left, lower right, upper left, upper right.
+        let bytes = lax(&[
+            (1, &[(0, 9)]),
+            (2, &[(10, 19)]),
+            (3, &[(20, 29)]),
+            (4, &[(30, 39)]),
+        ]);
+        let lax = Lax::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(vec![0..10], lax.intervals(&bounds(1., 2., 1., 2.)));
+        assert_eq!(vec![10..20], lax.intervals(&bounds(6., 7., 1., 2.)));
+        assert_eq!(vec![20..30], lax.intervals(&bounds(1., 2., 6., 7.)));
+        assert_eq!(vec![30..40], lax.intervals(&bounds(6., 7., 6., 7.)));
+        assert_eq!(vec![0..40], lax.intervals(&bounds(4., 6., 4., 6.)));
+        assert_eq!(vec![10..20, 30..40], lax.intervals(&bounds(6., 7., 1., 7.)));
+        // Edge cells reach past the quadtree.
+        assert_eq!(vec![30..40], lax.intervals(&bounds(11., 12., 11., 12.)));
+    }
+
+    #[test]
+    fn merge_intervals() {
+        let bytes = lax(&[(1, &[(0, 9), (20, 24)]), (2, &[(10, 19), (22, 29)])]);
+        let lax = Lax::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(vec![0..30], lax.intervals(&bounds(0., 10., 0., 10.)));
+    }
+
+    #[test]
+    fn without_quadtree_header() {
+        let mut bytes = lax(&[(1, &[(0, 9)])]);
+        let start = bytes
+            .windows(4)
+            .position(|window| window == QUADTREE_HEADER_SIGNATURE)
+            .unwrap();
+        let _ = bytes.drain(start..start + 8);
+        let lax = Lax::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(vec![0..10], lax.intervals(&bounds(1., 2., 1., 2.)));
+    }
+
+    #[test]
+    fn write_roundtrip() {
+        let bytes = lax(&[(1, &[(0, 9)]), (4, &[(10, 19)])]);
+        let mut written = Vec::new();
+        Lax::read_from(bytes.as_slice())
+            .unwrap()
+            .write_to(&mut written)
+            .unwrap();
+        assert_eq!(bytes, written);
+    }
+
+    #[test]
+    fn invalid_cell_index() {
+        let bytes = lax(&[(5, &[(0, 9)])]);
+        assert!(Lax::read_from(bytes.as_slice()).is_err());
+        let bytes = lax(&[(-1, &[(0, 9)])]);
+        assert!(Lax::read_from(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn invalid_signature() {
+        let mut bytes = lax(&[]);
+        bytes[0] = b'X';
+        assert!(Lax::read_from(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = lax(&[(1, &[(0, 9)])]);
+        assert!(Lax::read_from(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -1,8 +1,60 @@
 //! Utility functions for working with laszip compressed data.
 
-use crate::{Error, Header, Result, Vlr};
-use laz::{LazItemRecordBuilder, LazItemType, LazVlr};
-use std::io::Cursor;
+use crate::{header::SystemIdentifier, Error, Header, Reader, Result, Vlr, VlrRecord, Writer};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use laz::{laszip::ChunkTable, LazItemRecordBuilder, LazItemType, LazVlr, LazVlrBuilder};
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+/// The number of compressed bytes copied at a time by [concat_laz].
+const COPY_BUFFER_SIZE: usize = 1 << 20;
+
+/// Options for [concat_laz_with_options].
+///
+/// # Examples
+///
+/// ```
+/// use las::laz::ConcatOptions;
+/// let options = ConcatOptions::default().recompress_incompatible();
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConcatOptions {
+    recompress_incompatible: bool,
+    system_identifier: SystemIdentifier,
+}
+
+impl ConcatOptions {
+    /// Decompresses and recompresses every point if the inputs' chunks can't be copied as they
+    /// are, instead of returning an error.
+    pub fn recompress_incompatible(mut self) -> ConcatOptions {
+        self.recompress_incompatible = true;
+        self
+    }
+
+    /// Sets the output's system identifier, which defaults to [SystemIdentifier::Merge].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{header::SystemIdentifier, laz::ConcatOptions};
+    /// let options = ConcatOptions::default().system_identifier(SystemIdentifier::Extraction);
+    /// ```
+    pub fn system_identifier(mut self, system_identifier: SystemIdentifier) -> ConcatOptions {
+        self.system_identifier = system_identifier;
+        self
+    }
+}
+
+impl Default for ConcatOptions {
+    fn default() -> ConcatOptions {
+        ConcatOptions {
+            recompress_incompatible: false,
+            system_identifier: SystemIdentifier::Merge,
+        }
+    }
+}
 
 /// Returns true if this [Vlr] is the laszip Vlr.
 ///
@@ -21,7 +73,290 @@ use std::io::Cursor;
 /// }
 /// ```
 pub fn is_laszip_vlr(vlr: &Vlr) -> bool {
-    vlr.user_id == LazVlr::USER_ID && vlr.record_id == LazVlr::RECORD_ID
+    LazVlr::matches(vlr)
+}
+
+/// Concatenates compressed files by copying their compressed chunks, without recompressing.
+///
+/// Every input must have the same point layout (see [Header::layout_compatible_with]) and the
+/// same laszip items as the first one. The output takes everything else from the first input's
+/// header, except its spatial index and COPC records, which wouldn't describe the merged points.
+/// Its point counts and bounds cover all the inputs. Returns the header that was written.
+///
+/// # Examples
+///
+/// ```
+/// use las::Reader;
+///
+/// let inputs = vec![
+///     Reader::from_path("tests/data/autzen.laz").unwrap(),
+///     Reader::from_path("tests/data/autzen.laz").unwrap(),
+/// ];
+/// let path = std::env::temp_dir().join("concat_laz_example.laz");
+/// let header = las::concat_laz(inputs, std::fs::File::create(&path).unwrap()).unwrap();
+/// assert_eq!(2 * 106, header.number_of_points());
+/// # std::fs::remove_file(path).unwrap();
+/// ```
+pub fn concat_laz<R, W>(inputs: Vec<Reader<R>>, dest: W) -> Result<Header>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    concat_laz_with_options(inputs, dest, ConcatOptions::default())
+}
+
+/// Concatenates compressed files with options.
+///
+/// If the inputs can't be copied as they are, and [ConcatOptions::recompress_incompatible] is
+/// set, every point is decompressed and written through a [Writer] instead.
+///
+/// # Examples
+///
+/// ```
+/// use las::{laz::ConcatOptions, Reader};
+///
+/// let inputs = vec![
+///     Reader::from_path("tests/data/autzen.laz").unwrap(),
+///     Reader::from_path("tests/data/autzen.laz").unwrap(),
+/// ];
+/// let path = std::env::temp_dir().join("concat_laz_with_options_example.laz");
+/// let options = ConcatOptions::default().recompress_incompatible();
+/// let header =
+///     las::laz::concat_laz_with_options(inputs, std::fs::File::create(&path).unwrap(), options)
+///         .unwrap();
+/// assert_eq!(2 * 106, header.number_of_points());
+/// # std::fs::remove_file(path).unwrap();
+/// ```
+pub fn concat_laz_with_options<R, W>(
+    inputs: Vec<Reader<R>>,
+    dest: W,
+    options: ConcatOptions,
+) -> Result<Header>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let first = inputs.first().ok_or(Error::NoInputs)?.header();
+    match inputs
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, input)| verify_chunks_compatible(first, index, input.header()))
+    {
+        Ok(()) => copy_chunks(inputs, dest, &options.system_identifier),
+        Err(_) if options.recompress_incompatible => {
+            recompress(inputs, dest, &options.system_identifier)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+fn verify_chunks_compatible(first: &Header, index: usize, header: &Header) -> Result<()> {
+    first
+        .layout_compatible_with(header)
+        .map_err(|incompatibilities| Error::IncompatibleLayout {
+            index,
+            incompatibilities,
+        })?;
+    if !header.point_format().is_compressed || first.laz_vlr()?.items() != header.laz_vlr()?.items()
+    {
+        return Err(Error::LazItemsDoNotMatch(index));
+    }
+    Ok(())
+}
+
+/// Returns the first input's header, emptied of points and of records that describe them.
+fn copy_chunks<R, W>(
+    mut inputs: Vec<Reader<R>>,
+    mut dest: W,
+    system_identifier: &SystemIdentifier,
+) -> Result<Header>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek,
+{
+    let mut header = inputs[0].header().to_derived(system_identifier);
+    let laz_vlr = header.laz_vlr()?;
+    let mut fixed_size = !laz_vlr.uses_variable_size_chunks();
+    let mut chunk_table = ChunkTable::default();
+    let mut chunk_starts = Vec::with_capacity(inputs.len());
+    let last = inputs.len() - 1;
+    for (i, input) in inputs.iter_mut().enumerate() {
+        let input_header = input.header();
+        let number_of_points = input_header.number_of_points();
+        let number_of_points_by_return: HashMap<u8, u64> = (0..=u8::MAX)
+            .filter_map(|n| {
+                input_header
+                    .number_of_points_by_return(n)
+                    .map(|count| (n, count))
+            })
+            .collect();
+        header.verify_room_for(
+            number_of_points,
+            number_of_points_by_return
+                .iter()
+                .map(|(&return_number, &count)| (return_number, count)),
+        )?;
+        if number_of_points > 0 {
+            header.add_points(
+                number_of_points,
+                &number_of_points_by_return,
+                &input_header.bounds(),
+            );
+        }
+        // A partial chunk can only be the very last one in a fixed size chunk table.
+        let input_chunk_size = input_header.laz_vlr()?.chunk_size();
+        if input_chunk_size != laz_vlr.chunk_size()
+            || (i < last && number_of_points % u64::from(input_chunk_size) != 0)
+        {
+            fixed_size = false;
+        }
+        let (start, input_chunk_table) = input.chunk_table()?;
+        chunk_table.extend(&input_chunk_table);
+        let len: u64 = input_chunk_table
+            .as_ref()
+            .iter()
+            .map(|entry| entry.byte_count)
+            .sum();
+        chunk_starts.push((start, len));
+    }
+    if !fixed_size
+        && !laz_vlr.uses_variable_size_chunks()
+        && let Some(vlr) = header.vlrs.iter_mut().find(|vlr| is_laszip_vlr(vlr))
+    {
+        // The chunk size sits after the compressor, coder, version, and options. Patching it in
+        // place keeps the rest of the record as the first input wrote it.
+        vlr.data[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+    }
+    let laz_vlr = header.laz_vlr()?;
+
+    let mut chunk_table_bytes = Vec::new();
+    chunk_table.write_to(&mut chunk_table_bytes, &laz_vlr)?;
+    let mut header_bytes = Vec::new();
+    header.write_to(&mut header_bytes)?;
+    let offset_to_chunk_table = dest.stream_position()?
+        + header_bytes.len() as u64
+        + ChunkTable::OFFSET_SIZE as u64
+        + chunk_starts.iter().map(|(_, len)| len).sum::<u64>();
+    header.set_start_of_first_evlr(
+        offset_to_chunk_table
+            + chunk_table_bytes.len() as u64
+            + header.point_padding().len() as u64,
+    );
+
+    header.write_to(&mut dest)?;
+    dest.write_i64::<LittleEndian>(offset_to_chunk_table.try_into()?)?;
+    let mut buffer = vec![0; COPY_BUFFER_SIZE];
+    for (input, (start, len)) in inputs.iter_mut().zip(chunk_starts) {
+        let mut offset = 0;
+        while offset < len {
+            let n = usize::try_from(len - offset)
+                .unwrap_or(usize::MAX)
+                .min(buffer.len());
+            input.read_at(start + offset, &mut buffer[..n])?;
+            dest.write_all(&buffer[..n])?;
+            offset += n as u64;
+        }
+    }
+    dest.write_all(&chunk_table_bytes)?;
+    dest.write_all(header.point_padding())?;
+    for evlr in header.evlrs() {
+        evlr.clone().into_raw(true)?.write_to(&mut dest)?;
+    }
+    Ok(header)
+}
+
+fn recompress<R, W>(
+    inputs: Vec<Reader<R>>,
+    dest: W,
+    system_identifier: &SystemIdentifier,
+) -> Result<Header>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let mut header = inputs[0].header().to_derived(system_identifier);
+    header.point_format_mut().is_compressed = true;
+    let mut writer = Writer::new(dest, header)?;
+    for mut input in inputs {
+        input.seek(0)?;
+        for point in input.points() {
+            writer.write_point(point?)?;
+        }
+    }
+    let _ = writer.close()?;
+    Ok(writer.header().clone())
+}
+
+/// Reads the chunk table of compressed points, with the point count of every chunk filled in.
+///
+/// The source must be at the start of the point data, where the offset to the chunk table is.
+/// The number of chunks and their sizes are checked against the length of the source before
+/// anything is allocated for them.
+pub(crate) fn read_chunk_table<R: Read + Seek>(mut read: R, header: &Header) -> Result<ChunkTable> {
+    let vlr = header.laz_vlr()?;
+    let start = read.stream_position()?;
+    let end = read.seek(SeekFrom::End(0))?;
+    let _ = read.seek(SeekFrom::Start(start))?;
+    let offset = chunk_table_offset(&mut read, start, end)?;
+    let _ = read.seek(SeekFrom::Start(offset + 4))?;
+    let number_of_chunks = read.read_u32::<LittleEndian>()?;
+    // Every chunk takes up at least a byte somewhere after the start of the points, even if its
+    // entry in the compressed table doesn't.
+    if u64::from(number_of_chunks) > end - start {
+        return Err(Error::InvalidChunkTable(format!(
+            "{number_of_chunks} chunks don't fit in {} bytes",
+            end - start
+        )));
+    }
+    let _ = read.seek(SeekFrom::Start(start))?;
+    let mut chunk_table = ChunkTable::read_from(read, &vlr)?;
+    let chunk_bytes = offset - start - ChunkTable::OFFSET_SIZE as u64;
+    let _ = chunk_table
+        .as_ref()
+        .iter()
+        .try_fold(0u64, |bytes, entry| {
+            bytes
+                .checked_add(entry.byte_count)
+                .filter(|&bytes| bytes <= chunk_bytes)
+        })
+        .ok_or_else(|| {
+            Error::InvalidChunkTable(format!(
+                "the chunks don't fit in the {chunk_bytes} bytes before the chunk table"
+            ))
+        })?;
+    if !vlr.uses_variable_size_chunks() && !chunk_table.is_empty() {
+        // Fixed size chunk tables don't store point counts, so the last chunk holds whatever is
+        // left over.
+        let full = u64::from(vlr.chunk_size()) * (chunk_table.len() as u64 - 1);
+        if let Some(mut last) = chunk_table.pop() {
+            last.point_count = header.number_of_points().saturating_sub(full);
+            chunk_table.push(last);
+        }
+    }
+    Ok(chunk_table)
+}
+
+/// Reads the offset to the chunk table, which must be between the points and the end of the source.
+///
+/// Like laszip, an offset that doesn't point past the start of the points is looked for again at
+/// the end of the source.
+fn chunk_table_offset<R: Read + Seek>(mut read: R, start: u64, end: u64) -> Result<u64> {
+    let mut offset = read.read_i64::<LittleEndian>()?;
+    if offset <= start as i64 {
+        let _ = read.seek(SeekFrom::End(-8))?;
+        offset = read.read_i64::<LittleEndian>()?;
+        if offset <= start as i64 {
+            return Err(laz::LasZipError::MissingChunkTable.into());
+        }
+    }
+    u64::try_from(offset)
+        .ok()
+        .filter(|&offset| {
+            offset >= start + ChunkTable::OFFSET_SIZE as u64 && offset.saturating_add(8) <= end
+        })
+        .ok_or_else(|| {
+            Error::InvalidChunkTable(format!("the chunk table offset {offset} is out of bounds"))
+        })
 }
 
 impl Header {
@@ -39,6 +374,12 @@ impl Header {
     /// header.add_laz_vlr().unwrap();
     /// ```
     pub fn add_laz_vlr(&mut self) -> Result<()> {
+        self.add_laz_vlr_with_chunk_size(None)
+    }
+
+    /// Adds a new laszip vlr, with a fixed number of points per chunk if one is given.
+    pub(crate) fn add_laz_vlr_with_chunk_size(&mut self, chunk_size: Option<u32>) -> Result<()> {
+        self.vlrs.retain(|vlr| !is_laszip_vlr(vlr));
         let point_format = self.point_format();
         let mut laz_items = LazItemRecordBuilder::new();
         if !point_format.is_extended {
@@ -70,16 +411,13 @@ impl Header {
                 let _ = laz_items.add_item(LazItemType::Byte14(point_format.extra_bytes));
             }
         }
-        let laz_vlr = LazVlr::from_laz_items(laz_items.build());
-        let mut cursor = Cursor::new(Vec::<u8>::new());
-        laz_vlr.write_to(&mut cursor)?;
-        let vlr = Vlr {
-            user_id: LazVlr::USER_ID.to_owned(),
-            record_id: LazVlr::RECORD_ID,
-            description: LazVlr::DESCRIPTION.to_owned(),
-            data: cursor.into_inner(),
+        let laz_vlr = match chunk_size {
+            Some(chunk_size) => LazVlrBuilder::new(laz_items.build())
+                .with_fixed_chunk_size(chunk_size)
+                .build(),
+            None => LazVlr::from_laz_items(laz_items.build()),
         };
-        self.vlrs.push(vlr);
+        self.vlrs.push(laz_vlr.to_vlr()?);
         Ok(())
     }
 
@@ -111,6 +449,22 @@ impl TryFrom<&Vlr> for LazVlr {
     type Error = Error;
 
     fn try_from(vlr: &Vlr) -> Result<LazVlr> {
-        LazVlr::from_buffer(&vlr.data).map_err(Error::from)
+        LazVlr::decode(&vlr.data)
+    }
+}
+
+impl VlrRecord for LazVlr {
+    const USER_ID: &'static str = LazVlr::USER_ID;
+    const RECORD_ID: u16 = LazVlr::RECORD_ID;
+    const DESCRIPTION: &'static str = LazVlr::DESCRIPTION;
+
+    fn decode(data: &[u8]) -> Result<LazVlr> {
+        LazVlr::from_buffer(data).map_err(Error::from)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.write_to(&mut data)?;
+        Ok(data)
     }
 }
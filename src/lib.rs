@@ -105,6 +105,45 @@
 //! writer.write(point).unwrap();
 //! ```
 //!
+//! With the `arrow` feature, the `arrow` module converts points to and from
+//! [Apache Arrow](https://arrow.apache.org) record batches, and `Reader::into_record_batches`
+//! streams a reader's points as a `RecordBatchReader`.
+//!
+//! With the `digest` feature, `WriteOptions::point_data_digest` stores a SHA-256 digest of the
+//! point records in a vlr, and `verify_digest` checks a file's points against it.
+//!
+//! With the `parallel` feature, `Writer::write_par_iter` serializes points from a
+//! [rayon](https://docs.rs/rayon) parallel iterator.
+//!
+//! With the `mmap` feature, `Reader::open_mmap` decodes points straight from a read-only memory
+//! mapping of the file, without the copy through a `BufReader`.
+//!
+//! With the `proj` feature, a `proj::Proj` is a `writer::Reprojection`, so a
+//! `writer::Reproject` writer can reproject points with [PROJ](https://proj.org).
+//!
+//! With the `tokio` feature, `tokio::Reader` and `tokio::Writer` read and write las data from
+//! asynchronous sources and sinks.
+//!
+//! With the `rgb` feature, [Color] converts to and from the [rgb](https://docs.rs/rgb) crate's
+//! types.
+//!
+//! With the `serde` feature, [Header], [Builder], [Vlr], [Point], and the types they're made of,
+//! like [Bounds] and [Transform], can be serialized and deserialized, as can
+//! [reader::DensityGrid].
+//!
+//! With the `test-support` feature, the `strategy` module generates random points and headers
+//! for fuzzing and property tests, and `roundtrip` writes points to an in-memory file and reads
+//! them back, which is the recommended way to check that your points survive the trip:
+//!
+//! ```
+//! # #[cfg(feature = "test-support")] {
+//! use las::{Builder, Point};
+//!
+//! let outcome = las::roundtrip(&[Point::default()], Builder::default()).unwrap();
+//! assert!(outcome.is_lossless(), "{:?}", outcome.first_divergence());
+//! # }
+//! ```
+//!
 //! # Compression
 //!
 //! The [laz](https://laszip.org/) compression format is the de-facto standard for compression las data.
@@ -140,8 +179,20 @@
 //!
 //! - `.laz`: compressed
 //! - `.las`: not compressed
+//!
+//! # no_std
+//!
+//! The default `std` feature is implied by every other feature. With `default-features = false`
+//! and no other features, the crate is `no_std` (it still needs `alloc`) and only the byte level
+//! layer is built: [raw::Header], [raw::Point], and [raw::Vlr] with their `from_bytes` and
+//! `to_bytes` functions, [Transform], [Bounds], [Version], and the point format types. The std
+//! build's `read_from` and `write_to` go through the same code. Error variants that carry std
+//! types, e.g. [std::io::Error], only exist with the `std` feature.
 
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
+// Crate-private helpers in the alloc layer are there for the std modules.
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
 #![deny(
     elided_lifetimes_in_paths,
     explicit_outlives_requirements,
@@ -172,51 +223,165 @@
     warnings
 )]
 
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
 #[cfg(feature = "laz")]
 pub mod copc;
 #[cfg(feature = "laz")]
 pub mod laz;
 
+#[cfg(feature = "std")]
+pub mod convert;
+#[cfg(feature = "std")]
 pub mod crs;
+#[cfg(feature = "std")]
+pub mod edit;
 pub mod feature;
+#[cfg(feature = "std")]
+pub mod geotiff;
+#[cfg(feature = "std")]
 pub mod header;
+#[cfg(feature = "std")]
+pub mod index;
 pub mod point;
 pub mod raw;
+#[cfg(feature = "std")]
 pub mod reader;
+#[cfg(all(feature = "std", any(test, feature = "test-support")))]
+pub mod strategy;
+#[cfg(feature = "std")]
+pub mod summary;
+#[cfg(feature = "std")]
+pub mod synthetic;
+#[cfg(feature = "std")]
+pub mod thin;
+#[cfg(feature = "std")]
+pub mod tile;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+#[cfg(feature = "std")]
+pub mod validate;
+#[cfg(feature = "std")]
 pub mod vlr;
+#[cfg(feature = "std")]
+pub mod waveform;
+#[cfg(feature = "std")]
 pub mod writer;
 
+#[cfg(feature = "std")]
+mod attribute;
 mod bounds;
 mod color;
+#[cfg(feature = "std")]
+mod color_status;
+#[cfg(feature = "std")]
+mod compare;
+#[cfg(feature = "std")]
+mod compatibility;
+#[cfg(feature = "std")]
+mod dedup;
+#[cfg(feature = "std")]
+mod digest;
+#[cfg(feature = "std")]
+mod embedded;
 mod error;
+#[cfg(feature = "std")]
+mod extract;
+#[cfg(feature = "std")]
+mod gps_time;
+#[cfg(feature = "std")]
 mod gps_time_type;
+#[cfg(feature = "std")]
+mod merge;
+#[cfg(not(feature = "std"))]
+mod prelude;
+#[cfg(feature = "std")]
+mod progress;
+#[cfg(feature = "std")]
+mod record;
+#[cfg(feature = "std")]
+mod remap;
+#[cfg(feature = "std")]
+mod requantize;
+#[cfg(feature = "std")]
+mod rng;
+#[cfg(all(feature = "std", any(test, feature = "test-support")))]
+mod roundtrip;
+#[cfg(feature = "std")]
+mod scan_angle;
+#[cfg(feature = "std")]
+mod sort;
+#[cfg(feature = "std")]
+mod split;
+#[cfg(feature = "std")]
+mod statistics;
 mod transform;
 mod utils;
 mod vector;
 mod version;
 
-#[cfg(feature = "laz")]
-pub use crate::copc::CopcEntryReader;
+#[cfg(feature = "digest")]
+pub use crate::digest::{verify_digest, DigestStatus};
+#[cfg(all(feature = "std", any(test, feature = "test-support")))]
+pub use crate::roundtrip::{roundtrip, RoundtripOutcome};
+#[cfg(feature = "std")]
+pub use crate::{
+    attribute::{copy_with_attribute, AttributeReport},
+    color_status::{color_status, drop_zero_color, ColorDrop, ColorStatus},
+    compare::{
+        verify_equal, CompareMode, ComparisonReport, FieldDifference, PointDifference,
+        MAX_POINT_DIFFERENCES,
+    },
+    dedup::{dedup, dedup_sorted, DedupKey, DedupReport},
+    digest::{Digest, PointDataDigest},
+    embedded::Embedded,
+    extract::extract_range,
+    gps_time::{GpsTime, GpsTimestamp},
+    gps_time_type::GpsTimeType,
+    header::{Builder, Header},
+    merge::{merge, MergeOptions, MergeReport},
+    point::Point,
+    progress::ProgressEvent,
+    reader::{read_header, Reader},
+    record::LasPointRecord,
+    remap::{remap_classifications, ClassificationMap, RemapReport},
+    requantize::{requantize, RequantizeReport},
+    scan_angle::{
+        diagnose_scan_angles, repair_scan_angles, ScanAngleDiagnosis, ScanAngleRepair,
+        ScanAngleReport, MIN_DIAGNOSED_POINTS, MIN_EXTENDED_SPREAD, MIN_LEGACY_SPREAD,
+    },
+    sort::{sort, SortKey, SortOptions, DEFAULT_SORT_MEMORY_BUDGET},
+    split::{split_by, split_flight_lines},
+    statistics::Statistics,
+    vlr::{insert_vlr_in_place, Evlr, Vlr, VlrRecord},
+    writer::Writer,
+};
 pub use crate::{
     bounds::Bounds,
     color::Color,
     error::Error,
     feature::Feature,
-    gps_time_type::GpsTimeType,
-    header::{Builder, Header},
-    point::Point,
-    reader::Reader,
-    transform::Transform,
+    transform::{RoundingMode, Transform},
     vector::Vector,
     version::Version,
-    vlr::Vlr,
-    writer::Writer,
 };
+#[cfg(feature = "laz")]
+pub use crate::{copc::CopcEntryReader, laz::concat_laz};
 #[allow(deprecated)]
+#[cfg(feature = "std")]
 pub use {reader::Read, writer::Write};
 
 /// Crate-specific result type.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 #[cfg(test)]
 use criterion as _;
+#[cfg(all(test, not(feature = "std")))]
+use crs_definitions as _;
+#[cfg(all(test, not(feature = "serde")))]
+use serde_json as _;
+#[cfg(all(test, not(feature = "tokio")))]
+use tokio as _;
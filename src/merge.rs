@@ -0,0 +1,377 @@
+use crate::{
+    header::{Incompatibility, SystemIdentifier},
+    Bounds, Error, Header, Point, Reader, Result, Transform, Vector, Vlr, Writer,
+};
+use std::io::{Read, Seek, Write};
+
+/// Options for [merge].
+///
+/// # Examples
+///
+/// ```
+/// use las::{MergeOptions, Transform, Vector};
+/// let transform = Transform { scale: 0.01, offset: 0. };
+/// let options = MergeOptions::default().transforms(Vector { x: transform, y: transform, z: transform });
+/// ```
+#[derive(Clone, Debug)]
+pub struct MergeOptions {
+    transforms: Option<Vector<Transform>>,
+    system_identifier: SystemIdentifier,
+}
+
+impl MergeOptions {
+    /// Sets the merged file's scales and offsets, instead of picking them from the inputs.
+    pub fn transforms(mut self, transforms: Vector<Transform>) -> MergeOptions {
+        self.transforms = Some(transforms);
+        self
+    }
+
+    /// Sets the merged header's system identifier, which defaults to [SystemIdentifier::Merge].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{header::SystemIdentifier, MergeOptions};
+    /// let options = MergeOptions::default().system_identifier(SystemIdentifier::Extraction);
+    /// ```
+    pub fn system_identifier(mut self, system_identifier: SystemIdentifier) -> MergeOptions {
+        self.system_identifier = system_identifier;
+        self
+    }
+}
+
+impl Default for MergeOptions {
+    fn default() -> MergeOptions {
+        MergeOptions {
+            transforms: None,
+            system_identifier: SystemIdentifier::Merge,
+        }
+    }
+}
+
+/// What [merge] wrote.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergeReport {
+    /// The number of points written.
+    pub points: u64,
+
+    /// The scales and offsets that the points were written with.
+    pub transforms: Vector<Transform>,
+
+    /// Vlrs from later inputs that were dropped, because they share a user id and record id with
+    /// a different vlr that's already in the merged header.
+    pub dropped_vlrs: Vec<Vlr>,
+}
+
+/// Merges several inputs into one file, re-quantizing every point to a common set of scales and
+/// offsets.
+///
+/// Unless [MergeOptions::transforms] sets them, the transforms are picked from the inputs: each
+/// axis gets the finest scale of any input, and the first input's offset, or an offset at the
+/// middle of the merged bounds if the first input's can't store them. The header is the first
+/// input's, with vlrs from the later inputs added if they aren't already there. The bounds and
+/// point counts are computed as the points are written.
+///
+/// Returns [Error::NoInputs] if there are no readers, [Error::IncompatibleLayout] if an input's
+/// point format or gps time type differ from the first input's, [Error::CrsConflict] if an
+/// input's crs differs, and [Error::BoundsNotRepresentable] if the transforms can't store the
+/// merged bounds. These are all checked before anything is written.
+///
+/// # Examples
+///
+/// ```
+/// use las::{MergeOptions, Reader};
+/// use std::io::Cursor;
+///
+/// let readers = vec![
+///     Reader::from_path("tests/data/autzen.las").unwrap(),
+///     Reader::from_path("tests/data/autzen.las").unwrap(),
+/// ];
+/// let report = las::merge(readers, Cursor::new(Vec::new()), MergeOptions::default()).unwrap();
+/// assert_eq!(212, report.points);
+/// ```
+pub fn merge<R, W>(readers: Vec<Reader<R>>, dest: W, options: MergeOptions) -> Result<MergeReport>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let first = readers.first().ok_or(Error::NoInputs)?.header();
+    let first_crs_vlrs = crs_vlrs(first);
+    let mut builder = first.to_derived(&options.system_identifier).into_builder();
+    let mut bounds = Bounds::default();
+    let mut dropped_vlrs = Vec::new();
+    for (index, reader) in readers.iter().enumerate() {
+        let other = reader.header();
+        if let Err(mut incompatibilities) = first.layout_compatible_with(other) {
+            incompatibilities.retain(|incompatibility| {
+                !matches!(incompatibility, Incompatibility::Transforms { .. })
+            });
+            if !incompatibilities.is_empty() {
+                return Err(Error::IncompatibleLayout {
+                    index,
+                    incompatibilities,
+                });
+            }
+        }
+        if crs_vlrs(other) != first_crs_vlrs {
+            return Err(Error::CrsConflict(index));
+        }
+        // Files without points can have zeroed bounds, which shouldn't grow the union.
+        if other.number_of_points() > 0 {
+            let other = other.bounds();
+            for corner in [other.min, other.max] {
+                bounds.grow(&Point {
+                    x: corner.x,
+                    y: corner.y,
+                    z: corner.z,
+                    ..Default::default()
+                });
+            }
+        }
+        let other = other.to_empty();
+        for (vlrs, others) in [
+            (&mut builder.vlrs, other.vlrs()),
+            (&mut builder.evlrs, other.evlrs()),
+        ] {
+            for vlr in others {
+                if vlr.is_projection() || vlrs.contains(vlr) {
+                    continue;
+                }
+                if vlrs
+                    .iter()
+                    .any(|v| v.user_id == vlr.user_id && v.record_id == vlr.record_id)
+                {
+                    dropped_vlrs.push(vlr.clone());
+                } else {
+                    vlrs.push(vlr.clone());
+                }
+            }
+        }
+    }
+
+    let transforms = match options.transforms {
+        Some(transforms) => transforms,
+        None => {
+            let scales = |f: fn(&Vector<Transform>) -> Transform| {
+                readers
+                    .iter()
+                    .map(|reader| f(reader.header().effective_transforms()).scale)
+                    .fold(f64::INFINITY, f64::min)
+            };
+            let first = *first.effective_transforms();
+            Vector {
+                x: pick(scales(|t| t.x), first.x.offset, bounds.min.x, bounds.max.x),
+                y: pick(scales(|t| t.y), first.y.offset, bounds.min.y, bounds.max.y),
+                z: pick(scales(|t| t.z), first.z.offset, bounds.min.z, bounds.max.z),
+            }
+        }
+    };
+    if bounds.min.x <= bounds.max.x {
+        for (axis, transform, min, max) in [
+            ('x', transforms.x, bounds.min.x, bounds.max.x),
+            ('y', transforms.y, bounds.min.y, bounds.max.y),
+            ('z', transforms.z, bounds.min.z, bounds.max.z),
+        ] {
+            for value in [min, max] {
+                if transform.inverse(value).is_err() {
+                    return Err(Error::BoundsNotRepresentable {
+                        axis,
+                        value,
+                        transform,
+                    });
+                }
+            }
+        }
+    }
+    for vlr in &dropped_vlrs {
+        log::warn!(
+            "dropping a {} {} vlr that differs from one already in the merged header",
+            vlr.user_id,
+            vlr.record_id
+        );
+    }
+
+    builder.transforms = transforms;
+    let mut writer = Writer::new(dest, builder.into_header()?)?;
+    let mut points = 0;
+    for mut reader in readers {
+        for point in reader.points() {
+            writer.write_point(point?)?;
+            points += 1;
+        }
+    }
+    let _ = writer.into_inner()?;
+    Ok(MergeReport {
+        points,
+        transforms,
+        dropped_vlrs,
+    })
+}
+
+fn crs_vlrs(header: &Header) -> Vec<Vlr> {
+    header
+        .all_vlrs()
+        .filter(|vlr| vlr.is_projection())
+        .map(|vlr| vlr.vlr.clone())
+        .collect()
+}
+
+/// Keeps the offset if it can store both bounds at this scale, and otherwise centers it on them.
+fn pick(scale: f64, offset: f64, min: f64, max: f64) -> Transform {
+    let transform = Transform { scale, offset };
+    if min > max || (transform.inverse(min).is_ok() && transform.inverse(max).is_ok()) {
+        transform
+    } else {
+        Transform {
+            scale,
+            offset: ((min + max) / 2. / scale).round() * scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{synthetic::fixture, Builder};
+    use std::{fs::File, io::Cursor};
+
+    fn transforms(scale: f64, offset: f64) -> Vector<Transform> {
+        let transform = Transform { scale, offset };
+        Vector {
+            x: transform,
+            y: transform,
+            z: transform,
+        }
+    }
+
+    fn input(transforms: Vector<Transform>, xs: &[f64], vlrs: Vec<Vlr>) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 4));
+        builder.transforms = transforms;
+        builder.transforms.y.offset = 0.;
+        builder.transforms.z.offset = 0.;
+        builder.vlrs = vlrs;
+        let points = xs.iter().map(|&x| Point {
+            x,
+            ..Default::default()
+        });
+        fixture::reader(fixture::write(builder, points))
+    }
+
+    fn vlr(user_id: &str, record_id: u16, data: Vec<u8>) -> Vlr {
+        Vlr {
+            user_id: user_id.to_string(),
+            record_id,
+            data,
+            ..Default::default()
+        }
+    }
+
+    fn run(name: &str, readers: Vec<Reader<Cursor<Vec<u8>>>>) -> (MergeReport, Reader) {
+        let path = std::env::temp_dir().join(format!("las-rs-merge-{name}.las"));
+        let report = merge(
+            readers,
+            File::create(&path).unwrap(),
+            MergeOptions::default(),
+        )
+        .unwrap();
+        let reader = Reader::from_path(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        (report, reader)
+    }
+
+    #[test]
+    fn requantize() {
+        let readers = vec![
+            input(transforms(0.1, 0.), &[1.5, -2.5], vec![]),
+            input(transforms(0.01, 5.), &[3.25], vec![]),
+        ];
+        let (report, reader) = run("requantize", readers);
+        assert_eq!(3, report.points);
+        assert_eq!(transforms(0.01, 0.), report.transforms);
+        assert_eq!(transforms(0.01, 0.), *reader.header().transforms());
+        let bounds = reader.header().bounds();
+        assert_eq!((-2.5, 3.25), (bounds.min.x, bounds.max.x));
+        let xs: Vec<f64> = reader.into_points().unwrap().iter().map(|p| p.x).collect();
+        assert_eq!(vec![1.5, -2.5, 3.25], xs);
+    }
+
+    #[test]
+    fn recenter() {
+        let readers = vec![
+            input(transforms(0.01, 0.), &[0.], vec![]),
+            input(transforms(0.01, 3e7), &[3e7], vec![]),
+        ];
+        let (report, reader) = run("recenter", readers);
+        assert_eq!(1.5e7, report.transforms.x.offset);
+        assert_eq!(0., report.transforms.y.offset);
+        let xs: Vec<f64> = reader.into_points().unwrap().iter().map(|p| p.x).collect();
+        assert_eq!(vec![0., 3e7], xs);
+    }
+
+    #[test]
+    fn accepted_transforms() {
+        let readers = vec![
+            input(transforms(0.01, 0.), &[1.], vec![]),
+            input(transforms(0.01, 5e7), &[5e7], vec![]),
+        ];
+        let options = MergeOptions::default().transforms(transforms(0.001, 0.));
+        assert!(matches!(
+            merge(readers, Cursor::new(Vec::new()), options).unwrap_err(),
+            Error::BoundsNotRepresentable { axis: 'x', .. }
+        ));
+    }
+
+    #[test]
+    fn vlrs() {
+        let readers = vec![
+            input(transforms(0.01, 0.), &[1.], vec![vlr("a", 1, vec![1])]),
+            input(
+                transforms(0.01, 0.),
+                &[2.],
+                vec![
+                    vlr("a", 1, vec![1]),
+                    vlr("a", 2, vec![2]),
+                    vlr("a", 1, vec![3]),
+                ],
+            ),
+        ];
+        let (report, reader) = run("vlrs", readers);
+        assert_eq!(vec![vlr("a", 1, vec![3])], report.dropped_vlrs);
+        assert_eq!(
+            &vec![vlr("a", 1, vec![1]), vlr("a", 2, vec![2])],
+            reader.header().vlrs()
+        );
+    }
+
+    #[test]
+    fn conflicts() {
+        let crs = |data| vlr("LASF_Projection", 2112, data);
+        let readers = vec![
+            input(transforms(0.01, 0.), &[1.], vec![crs(vec![1])]),
+            input(transforms(0.01, 0.), &[2.], vec![crs(vec![2])]),
+        ];
+        assert!(matches!(
+            merge(readers, Cursor::new(Vec::new()), MergeOptions::default()).unwrap_err(),
+            Error::CrsConflict(1)
+        ));
+
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = crate::point::Format::new(6).unwrap();
+        builder.gps_time_type = crate::GpsTimeType::Standard;
+        let other = fixture::reader(fixture::write(builder, Vec::<Point>::new()));
+        let readers = vec![input(transforms(0.01, 0.), &[1.], vec![]), other];
+        assert!(matches!(
+            merge(readers, Cursor::new(Vec::new()), MergeOptions::default()).unwrap_err(),
+            Error::IncompatibleLayout { index: 1, .. }
+        ));
+        assert!(matches!(
+            merge(
+                Vec::<Reader<Cursor<Vec<u8>>>>::new(),
+                Cursor::new(Vec::new()),
+                MergeOptions::default()
+            )
+            .unwrap_err(),
+            Error::NoInputs
+        ));
+    }
+}
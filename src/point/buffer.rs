@@ -0,0 +1,308 @@
+use crate::{
+    point::{Classification, ExtraBytes, ScanDirection},
+    raw::point::Waveform,
+    Color, Point,
+};
+
+/// Points stored column by column, e.g. for analytics that work on one attribute at a time.
+///
+/// Each column holds one value per point, in order. The optional columns, like gps times and
+/// colors, are empty if the points don't have them. The extra bytes of every point are stored one
+/// after the other, all in one vector.
+///
+/// Fill a buffer with [Reader::read_batch](crate::Reader::read_batch), which reuses the buffer's
+/// columns, so reading batch after batch doesn't allocate for each point:
+///
+/// ```
+/// use las::{point::PointBuffer, Reader};
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut buffer = PointBuffer::new();
+/// let mut sum = 0.;
+/// while reader.read_batch(&mut buffer, 50).unwrap() > 0 {
+///     sum += buffer.zs.iter().sum::<f64>();
+/// }
+/// assert!(sum > 0.);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PointBuffer {
+    /// The x coordinates.
+    pub xs: Vec<f64>,
+
+    /// The y coordinates.
+    pub ys: Vec<f64>,
+
+    /// The z coordinates.
+    pub zs: Vec<f64>,
+
+    /// The intensities.
+    pub intensities: Vec<u16>,
+
+    /// The return numbers.
+    pub return_numbers: Vec<u8>,
+
+    /// The numbers of returns.
+    pub numbers_of_returns: Vec<u8>,
+
+    /// The scan directions.
+    pub scan_directions: Vec<ScanDirection>,
+
+    /// The edge of flight line flags.
+    pub is_edge_of_flight_line: Vec<bool>,
+
+    /// The classifications.
+    pub classifications: Vec<Classification>,
+
+    /// The synthetic flags.
+    pub is_synthetic: Vec<bool>,
+
+    /// The key-point flags.
+    pub is_key_point: Vec<bool>,
+
+    /// The withheld flags.
+    pub is_withheld: Vec<bool>,
+
+    /// The overlap flags.
+    pub is_overlap: Vec<bool>,
+
+    /// The scanner channels.
+    pub scanner_channels: Vec<u8>,
+
+    /// The scan angles, in degrees.
+    pub scan_angles: Vec<f32>,
+
+    /// The user data.
+    pub user_data: Vec<u8>,
+
+    /// The point source ids.
+    pub point_source_ids: Vec<u16>,
+
+    /// The gps times, if the points have them.
+    pub gps_times: Vec<f64>,
+
+    /// The colors, if the points have them.
+    pub colors: Vec<Color>,
+
+    /// The waveforms, if the points have them.
+    pub waveforms: Vec<Waveform>,
+
+    /// The near infrared values, if the points have them.
+    pub nirs: Vec<u16>,
+
+    /// The extra bytes of every point, one point after the other.
+    pub extra_bytes: Vec<u8>,
+
+    len: usize,
+}
+
+impl PointBuffer {
+    /// Creates an empty buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::PointBuffer;
+    /// assert!(PointBuffer::new().is_empty());
+    /// ```
+    pub fn new() -> PointBuffer {
+        PointBuffer::default()
+    }
+
+    /// Returns the number of points in this buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::PointBuffer, Point};
+    /// let mut buffer = PointBuffer::new();
+    /// buffer.push(&Point::default());
+    /// assert_eq!(1, buffer.len());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if this buffer has no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::PointBuffer;
+    /// assert!(PointBuffer::new().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every point, keeping the columns' allocations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::PointBuffer, Point};
+    /// let mut buffer = PointBuffer::new();
+    /// buffer.push(&Point::default());
+    /// buffer.clear();
+    /// assert!(buffer.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.xs.clear();
+        self.ys.clear();
+        self.zs.clear();
+        self.intensities.clear();
+        self.return_numbers.clear();
+        self.numbers_of_returns.clear();
+        self.scan_directions.clear();
+        self.is_edge_of_flight_line.clear();
+        self.classifications.clear();
+        self.is_synthetic.clear();
+        self.is_key_point.clear();
+        self.is_withheld.clear();
+        self.is_overlap.clear();
+        self.scanner_channels.clear();
+        self.scan_angles.clear();
+        self.user_data.clear();
+        self.point_source_ids.clear();
+        self.gps_times.clear();
+        self.colors.clear();
+        self.waveforms.clear();
+        self.nirs.clear();
+        self.extra_bytes.clear();
+        self.len = 0;
+    }
+
+    /// Adds a point to the end of every column.
+    ///
+    /// A buffer should only hold points of one point format, so that its optional columns line up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::PointBuffer, Point};
+    /// let mut buffer = PointBuffer::new();
+    /// buffer.push(&Point { x: 1., gps_time: Some(2.), ..Default::default() });
+    /// assert_eq!([1.], buffer.xs[..]);
+    /// assert_eq!([2.], buffer.gps_times[..]);
+    /// assert!(buffer.colors.is_empty());
+    /// ```
+    pub fn push(&mut self, point: &Point) {
+        self.xs.push(point.x);
+        self.ys.push(point.y);
+        self.zs.push(point.z);
+        self.intensities.push(point.intensity);
+        self.return_numbers.push(point.return_number);
+        self.numbers_of_returns.push(point.number_of_returns);
+        self.scan_directions.push(point.scan_direction);
+        self.is_edge_of_flight_line
+            .push(point.is_edge_of_flight_line);
+        self.classifications.push(point.classification);
+        self.is_synthetic.push(point.is_synthetic);
+        self.is_key_point.push(point.is_key_point);
+        self.is_withheld.push(point.is_withheld);
+        self.is_overlap.push(point.is_overlap);
+        self.scanner_channels.push(point.scanner_channel);
+        self.scan_angles.push(point.scan_angle);
+        self.user_data.push(point.user_data);
+        self.point_source_ids.push(point.point_source_id);
+        self.gps_times.extend(point.gps_time);
+        self.colors.extend(point.color);
+        self.waveforms.extend(point.waveform);
+        self.nirs.extend(point.nir);
+        self.extra_bytes.extend_from_slice(&point.extra_bytes);
+        self.len += 1;
+    }
+
+    /// Returns the extra bytes of the point at `index`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::PointBuffer, Point};
+    /// let mut buffer = PointBuffer::new();
+    /// buffer.push(&Point { extra_bytes: vec![1, 2].into(), ..Default::default() });
+    /// buffer.push(&Point { extra_bytes: vec![3, 4].into(), ..Default::default() });
+    /// assert_eq!(Some(&[3, 4][..]), buffer.point_extra_bytes(1));
+    /// ```
+    pub fn point_extra_bytes(&self, index: usize) -> Option<&[u8]> {
+        if index >= self.len {
+            return None;
+        }
+        let n = self.extra_bytes.len() / self.len;
+        Some(&self.extra_bytes[index * n..(index + 1) * n])
+    }
+
+    /// Returns the point at `index`, built from the columns.
+    ///
+    /// The point's [original_flags](Point::original_flags) aren't kept in the buffer, so they're
+    /// `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::PointBuffer, Point};
+    /// let mut buffer = PointBuffer::new();
+    /// buffer.push(&Point { z: 3., ..Default::default() });
+    /// assert_eq!(3., buffer.point(0).unwrap().z);
+    /// assert_eq!(None, buffer.point(1));
+    /// ```
+    pub fn point(&self, index: usize) -> Option<Point> {
+        let extra_bytes = self.point_extra_bytes(index)?;
+        Some(Point {
+            x: self.xs[index],
+            y: self.ys[index],
+            z: self.zs[index],
+            intensity: self.intensities[index],
+            return_number: self.return_numbers[index],
+            number_of_returns: self.numbers_of_returns[index],
+            scan_direction: self.scan_directions[index],
+            is_edge_of_flight_line: self.is_edge_of_flight_line[index],
+            classification: self.classifications[index],
+            is_synthetic: self.is_synthetic[index],
+            is_key_point: self.is_key_point[index],
+            is_withheld: self.is_withheld[index],
+            is_overlap: self.is_overlap[index],
+            original_flags: None,
+            scanner_channel: self.scanner_channels[index],
+            scan_angle: self.scan_angles[index],
+            user_data: self.user_data[index],
+            point_source_id: self.point_source_ids[index],
+            gps_time: self.gps_times.get(index).copied(),
+            color: self.colors.get(index).copied(),
+            waveform: self.waveforms.get(index).copied(),
+            nir: self.nirs.get(index).copied(),
+            extra_bytes: ExtraBytes::from(extra_bytes),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    #[test]
+    fn read_batch() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let points = reader.read_points(106).unwrap();
+        reader.seek(0).unwrap();
+        let mut buffer = PointBuffer::new();
+        let mut read = Vec::new();
+        while reader.read_batch(&mut buffer, 40).unwrap() > 0 {
+            assert_eq!(buffer.xs.len(), buffer.len());
+            assert_eq!(buffer.gps_times.len(), buffer.len());
+            read.extend((0..buffer.len()).map(|i| buffer.point(i).unwrap()));
+        }
+        assert_eq!(points, read);
+    }
+
+    #[test]
+    fn optional_columns() {
+        let mut buffer = PointBuffer::new();
+        buffer.push(&Point::default());
+        assert!(buffer.gps_times.is_empty());
+        assert!(buffer.colors.is_empty());
+        assert!(buffer.waveforms.is_empty());
+        assert!(buffer.nirs.is_empty());
+        assert_eq!(Some(Point::default()), buffer.point(0));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{point::Error, Result};
+use crate::{point::Error, raw::point::OVERLAP_CLASSIFICATION_CODE, Result};
 
 /// The ASPRS classification table.
 ///
@@ -28,7 +28,12 @@ use crate::{point::Error, Result};
 /// use las::point::Classification;
 /// assert!(Classification::new(12).is_err());
 /// ```
+///
+/// [Classification::from_legacy] and [Classification::to_legacy] do this mapping for the five-bit
+/// codes of the legacy point formats, which only go up to 31. The extended point formats have
+/// the whole 0–255 range, where 23–63 are reserved and 64–255 are user definable.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum Classification {
     #[default]
@@ -50,6 +55,10 @@ pub enum Classification {
     WireStructureConnector,
     BridgeDeck,
     HighNoise,
+    OverheadStructure,
+    IgnoredGround,
+    Snow,
+    TemporalExclusion,
     Reserved(u8),
     UserDefinable(u8),
 }
@@ -87,10 +96,167 @@ impl Classification {
             16 => Classification::WireStructureConnector,
             17 => Classification::BridgeDeck,
             18 => Classification::HighNoise,
-            19..=63 => Classification::Reserved(n),
+            19 => Classification::OverheadStructure,
+            20 => Classification::IgnoredGround,
+            21 => Classification::Snow,
+            22 => Classification::TemporalExclusion,
+            23..=63 => Classification::Reserved(n),
             64..=255 => Classification::UserDefinable(n),
         })
     }
+
+    /// Creates a classification from the five-bit code of a legacy point format, and returns it
+    /// with whether the point is overlap.
+    ///
+    /// Overlap points have code 12, and become unclassified overlap points. Codes above 31 don't
+    /// fit in five bits, so they're an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// assert_eq!((Classification::Ground, false), Classification::from_legacy(2).unwrap());
+    /// assert_eq!((Classification::Unclassified, true), Classification::from_legacy(12).unwrap());
+    /// assert!(Classification::from_legacy(32).is_err());
+    /// ```
+    pub fn from_legacy(code: u8) -> Result<(Classification, bool)> {
+        match code {
+            OVERLAP_CLASSIFICATION_CODE => Ok((Classification::Unclassified, true)),
+            0..=31 => Ok((Classification::new(code)?, false)),
+            _ => Err(Error::InvalidClassification(code)),
+        }
+    }
+
+    /// Returns the five-bit code of this classification in a legacy point format, where overlap
+    /// points have code 12.
+    ///
+    /// Classifications above 31 don't fit in five bits, so they're an error, even for overlap
+    /// points, since the class would be lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// assert_eq!(2, Classification::Ground.to_legacy(false).unwrap());
+    /// assert_eq!(12, Classification::Ground.to_legacy(true).unwrap());
+    /// assert!(Classification::UserDefinable(64).to_legacy(false).is_err());
+    /// ```
+    pub fn to_legacy(self, is_overlap: bool) -> Result<u8> {
+        let code = u8::from(self);
+        if code > 31 {
+            Err(Error::InvalidClassification(code))
+        } else if is_overlap {
+            Ok(OVERLAP_CLASSIFICATION_CODE)
+        } else {
+            Ok(code)
+        }
+    }
+
+    /// Returns the name of this classification in the ASPRS table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// assert_eq!("Low Vegetation", Classification::LowVegetation.name());
+    /// assert_eq!("User Definable", Classification::UserDefinable(100).name());
+    /// ```
+    pub fn name(self) -> &'static str {
+        match self {
+            Classification::CreatedNeverClassified => "Created, Never Classified",
+            Classification::Unclassified => "Unclassified",
+            Classification::Ground => "Ground",
+            Classification::LowVegetation => "Low Vegetation",
+            Classification::MediumVegetation => "Medium Vegetation",
+            Classification::HighVegetation => "High Vegetation",
+            Classification::Building => "Building",
+            Classification::LowPoint => "Low Point (Noise)",
+            Classification::ModelKeyPoint => "Model Key-Point",
+            Classification::Water => "Water",
+            Classification::Rail => "Rail",
+            Classification::RoadSurface => "Road Surface",
+            Classification::WireGuard => "Wire - Guard (Shield)",
+            Classification::WireConductor => "Wire - Conductor (Phase)",
+            Classification::TransmissionTower => "Transmission Tower",
+            Classification::WireStructureConnector => "Wire-Structure Connector (Insulator)",
+            Classification::BridgeDeck => "Bridge Deck",
+            Classification::HighNoise => "High Noise",
+            Classification::OverheadStructure => "Overhead Structure",
+            Classification::IgnoredGround => "Ignored Ground",
+            Classification::Snow => "Snow",
+            Classification::TemporalExclusion => "Temporal Exclusion",
+            Classification::Reserved(_) => "Reserved",
+            Classification::UserDefinable(_) => "User Definable",
+        }
+    }
+
+    /// Returns true if this is ground.
+    ///
+    /// [Classification::IgnoredGround], for ground near breaklines, isn't, since it's left out
+    /// of ground models.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// assert!(Classification::Ground.is_ground());
+    /// assert!(!Classification::IgnoredGround.is_ground());
+    /// ```
+    pub fn is_ground(self) -> bool {
+        self == Classification::Ground
+    }
+
+    /// Returns true if this is noise, a low point or high noise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// assert!(Classification::LowPoint.is_noise());
+    /// assert!(Classification::HighNoise.is_noise());
+    /// assert!(!Classification::Water.is_noise());
+    /// ```
+    pub fn is_noise(self) -> bool {
+        matches!(self, Classification::LowPoint | Classification::HighNoise)
+    }
+
+    /// Returns true if this classification is reserved for future ASPRS definition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// assert!(Classification::new(40).unwrap().is_reserved());
+    /// assert!(!Classification::new(64).unwrap().is_reserved());
+    /// ```
+    pub fn is_reserved(self) -> bool {
+        matches!(self, Classification::Reserved(_))
+    }
+
+    /// Returns true if this classification is user definable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// assert!(Classification::new(200).unwrap().is_user_definable());
+    /// ```
+    pub fn is_user_definable(self) -> bool {
+        matches!(self, Classification::UserDefinable(_))
+    }
+
+    /// Returns true if the legacy point formats can store this classification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// assert!(Classification::Snow.is_legacy());
+    /// assert!(!Classification::UserDefinable(64).is_legacy());
+    /// ```
+    pub fn is_legacy(self) -> bool {
+        u8::from(self) <= 31
+    }
 }
 
 impl From<Classification> for u8 {
@@ -114,7 +280,40 @@ impl From<Classification> for u8 {
             Classification::WireStructureConnector => 16,
             Classification::BridgeDeck => 17,
             Classification::HighNoise => 18,
+            Classification::OverheadStructure => 19,
+            Classification::IgnoredGround => 20,
+            Classification::Snow => 21,
+            Classification::TemporalExclusion => 22,
             Classification::Reserved(n) | Classification::UserDefinable(n) => n,
         }
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for n in (0..=255).filter(|&n| n != OVERLAP_CLASSIFICATION_CODE) {
+            let classification = Classification::new(n).unwrap();
+            assert_eq!(n, u8::from(classification));
+            assert_eq!(
+                n <= 31,
+                Classification::from_legacy(n)
+                    .is_ok_and(|legacy| legacy == (classification, false))
+            );
+            assert_eq!(n <= 31, classification.to_legacy(false).ok() == Some(n));
+        }
+    }
+
+    #[test]
+    fn reserved() {
+        assert_eq!(
+            (23..=63).collect::<Vec<u8>>(),
+            (0..=255)
+                .filter(|&n| Classification::new(n).is_ok_and(Classification::is_reserved))
+                .collect::<Vec<_>>()
+        );
+    }
+}
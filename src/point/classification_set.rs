@@ -0,0 +1,155 @@
+use super::Classification;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::raw::point::OVERLAP_CLASSIFICATION_CODE;
+use core::fmt;
+
+/// A set of classifications, used to filter points as they're read.
+///
+/// Sets can be built from a single classification, a slice or array of classifications, or a
+/// closure:
+///
+/// ```
+/// use las::point::{Classification, ClassificationSet};
+///
+/// let ground = ClassificationSet::from(Classification::Ground);
+/// assert!(ground.contains(Classification::Ground));
+/// assert!(!ground.contains(Classification::Water));
+///
+/// let wet = ClassificationSet::from([Classification::Ground, Classification::Water]);
+/// assert!(wet.contains(Classification::Water));
+///
+/// let user = ClassificationSet::from(|c| matches!(c, Classification::UserDefinable(_)));
+/// assert!(user.contains(Classification::UserDefinable(64)));
+/// ```
+///
+/// Files without the overlap bit store overlap points with code 12, which is read as
+/// [Classification::Unclassified], so that's the class those points are matched against.
+/// Withheld, synthetic, and key-point flags don't change which class a point is in.
+pub struct ClassificationSet(Matches);
+
+enum Matches {
+    Codes(Box<[bool; 256]>),
+    Predicate(Box<dyn Fn(Classification) -> bool + Send + Sync>),
+}
+
+impl ClassificationSet {
+    /// Returns true if this set contains the classification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{Classification, ClassificationSet};
+    /// let set = ClassificationSet::from(Classification::Ground);
+    /// assert!(set.contains(Classification::Ground));
+    /// ```
+    pub fn contains(&self, classification: Classification) -> bool {
+        match &self.0 {
+            Matches::Codes(codes) => codes[usize::from(u8::from(classification))],
+            Matches::Predicate(predicate) => predicate(classification),
+        }
+    }
+
+    /// Returns true if this set contains a raw classification code, as stored in a point record.
+    pub(crate) fn contains_code(&self, code: u8) -> bool {
+        let code = if code == OVERLAP_CLASSIFICATION_CODE {
+            u8::from(Classification::Unclassified)
+        } else {
+            code
+        };
+        match &self.0 {
+            Matches::Codes(codes) => codes[usize::from(code)],
+            Matches::Predicate(predicate) => {
+                Classification::new(code).map(predicate).unwrap_or_default()
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ClassificationSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Matches::Codes(codes) => f
+                .debug_set()
+                .entries(
+                    (0..=u8::MAX)
+                        .filter(|&code| codes[usize::from(code)])
+                        .filter_map(|code| Classification::new(code).ok()),
+                )
+                .finish(),
+            Matches::Predicate(_) => f.write_str("ClassificationSet(<predicate>)"),
+        }
+    }
+}
+
+impl FromIterator<Classification> for ClassificationSet {
+    fn from_iter<I: IntoIterator<Item = Classification>>(iter: I) -> ClassificationSet {
+        let mut codes = Box::new([false; 256]);
+        for classification in iter {
+            codes[usize::from(u8::from(classification))] = true;
+        }
+        ClassificationSet(Matches::Codes(codes))
+    }
+}
+
+impl From<Classification> for ClassificationSet {
+    fn from(classification: Classification) -> ClassificationSet {
+        core::iter::once(classification).collect()
+    }
+}
+
+impl From<&[Classification]> for ClassificationSet {
+    fn from(classifications: &[Classification]) -> ClassificationSet {
+        classifications.iter().copied().collect()
+    }
+}
+
+impl<const N: usize> From<[Classification; N]> for ClassificationSet {
+    fn from(classifications: [Classification; N]) -> ClassificationSet {
+        classifications.into_iter().collect()
+    }
+}
+
+impl<const N: usize> From<&[Classification; N]> for ClassificationSet {
+    fn from(classifications: &[Classification; N]) -> ClassificationSet {
+        classifications.iter().copied().collect()
+    }
+}
+
+impl<F> From<F> for ClassificationSet
+where
+    F: Fn(Classification) -> bool + Send + Sync + 'static,
+{
+    fn from(predicate: F) -> ClassificationSet {
+        ClassificationSet(Matches::Predicate(Box::new(predicate)))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlap_code_is_unclassified() {
+        let set = ClassificationSet::from(Classification::Unclassified);
+        assert!(set.contains_code(OVERLAP_CLASSIFICATION_CODE));
+        let set = ClassificationSet::from(|c| c == Classification::Unclassified);
+        assert!(set.contains_code(OVERLAP_CLASSIFICATION_CODE));
+    }
+
+    #[test]
+    fn slice() {
+        let classifications: &[Classification] =
+            &[Classification::Ground, Classification::UserDefinable(200)];
+        let set = ClassificationSet::from(classifications);
+        assert!(set.contains_code(2));
+        assert!(set.contains_code(200));
+        assert!(!set.contains_code(1));
+    }
+
+    #[test]
+    fn debug() {
+        let set = ClassificationSet::from([Classification::Ground, Classification::Water]);
+        assert_eq!("{Ground, Water}", format!("{:?}", set));
+    }
+}
@@ -0,0 +1,544 @@
+use crate::{
+    utils::{AsLasStr, FromLasStr},
+    Error, Result, Vlr,
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+/// The number of bytes in each descriptor of the extra bytes vlr.
+const DESCRIPTOR_LEN: usize = 192;
+
+/// The type of an extra bytes attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtraBytesType {
+    /// Bytes with no documented meaning, with the number of bytes.
+    Undocumented(u8),
+
+    /// An unsigned byte.
+    U8,
+
+    /// A signed byte.
+    I8,
+
+    /// An unsigned short.
+    U16,
+
+    /// A signed short.
+    I16,
+
+    /// An unsigned long.
+    U32,
+
+    /// A signed long.
+    I32,
+
+    /// An unsigned long long.
+    U64,
+
+    /// A signed long long.
+    I64,
+
+    /// A float.
+    F32,
+
+    /// A double.
+    F64,
+
+    /// One of the deprecated two or three value arrays, with its data type from 11 to 30.
+    ///
+    /// Arrays can't be decoded or encoded, but they keep their place and size in the extra bytes.
+    Array(u8),
+}
+
+/// Describes one attribute in a point's extra bytes, as one entry of the extra bytes vlr.
+///
+/// The vlr's entries describe the extra bytes in order. `no_data`, `min`, and `max` are stored
+/// values, before the scale and offset are applied. The deprecated two and three value types are
+/// read as [ExtraBytesType::Array], and only their first no data, min, max, scale, and offset
+/// values are kept.
+///
+/// # Examples
+///
+/// ```
+/// use las::point::{ExtraBytesDescriptor, ExtraBytesType};
+///
+/// let mut descriptor = ExtraBytesDescriptor::new("HeightAboveGround", ExtraBytesType::I32);
+/// descriptor.scale = Some(0.01);
+/// let mut bytes = [0; 4];
+/// assert!(descriptor.encode(12.34, &mut bytes));
+/// assert_eq!(1234, i32::from_le_bytes(bytes));
+/// assert_eq!(Some(12.34), descriptor.decode(&bytes));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtraBytesDescriptor {
+    /// The type of the attribute.
+    pub data_type: ExtraBytesType,
+
+    /// The name of the attribute, up to 32 bytes of ascii.
+    pub name: String,
+
+    /// A description of the attribute, up to 32 bytes of ascii.
+    pub description: String,
+
+    /// The stored value that means there's no data.
+    pub no_data: Option<f64>,
+
+    /// The smallest stored value.
+    pub min: Option<f64>,
+
+    /// The largest stored value.
+    pub max: Option<f64>,
+
+    /// The scale that's applied to stored values.
+    pub scale: Option<f64>,
+
+    /// The offset that's added to stored values, after they're scaled.
+    pub offset: Option<f64>,
+}
+
+impl ExtraBytesType {
+    fn new(data_type: u8, options: u8) -> Result<ExtraBytesType> {
+        Ok(match data_type {
+            0 => ExtraBytesType::Undocumented(options),
+            1 => ExtraBytesType::U8,
+            2 => ExtraBytesType::I8,
+            3 => ExtraBytesType::U16,
+            4 => ExtraBytesType::I16,
+            5 => ExtraBytesType::U32,
+            6 => ExtraBytesType::I32,
+            7 => ExtraBytesType::U64,
+            8 => ExtraBytesType::I64,
+            9 => ExtraBytesType::F32,
+            10 => ExtraBytesType::F64,
+            11..=30 => ExtraBytesType::Array(data_type),
+            _ => {
+                return Err(Error::InvalidExtraBytesVlr(format!(
+                    "unsupported data type {data_type}"
+                )));
+            }
+        })
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            ExtraBytesType::Undocumented(_) => 0,
+            ExtraBytesType::U8 => 1,
+            ExtraBytesType::I8 => 2,
+            ExtraBytesType::U16 => 3,
+            ExtraBytesType::I16 => 4,
+            ExtraBytesType::U32 => 5,
+            ExtraBytesType::I32 => 6,
+            ExtraBytesType::U64 => 7,
+            ExtraBytesType::I64 => 8,
+            ExtraBytesType::F32 => 9,
+            ExtraBytesType::F64 => 10,
+            ExtraBytesType::Array(data_type) => data_type,
+        }
+    }
+
+    /// Returns the type of one value, which is the type itself unless it's an array.
+    fn element(self) -> ExtraBytesType {
+        match self {
+            ExtraBytesType::Array(data_type) => {
+                ExtraBytesType::new((data_type - 1) % 10 + 1, 0).expect("a single value type")
+            }
+            _ => self,
+        }
+    }
+
+    /// Returns the number of bytes that a value of this type takes up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::ExtraBytesType;
+    /// assert_eq!(4, ExtraBytesType::F32.len());
+    /// assert_eq!(3, ExtraBytesType::Undocumented(3).len());
+    /// assert_eq!(24, ExtraBytesType::Array(30).len());
+    /// ```
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(self) -> usize {
+        match self {
+            ExtraBytesType::Undocumented(n) => usize::from(n),
+            ExtraBytesType::U8 | ExtraBytesType::I8 => 1,
+            ExtraBytesType::U16 | ExtraBytesType::I16 => 2,
+            ExtraBytesType::U32 | ExtraBytesType::I32 | ExtraBytesType::F32 => 4,
+            ExtraBytesType::U64 | ExtraBytesType::I64 | ExtraBytesType::F64 => 8,
+            ExtraBytesType::Array(data_type) => {
+                usize::from((data_type - 1) / 10 + 1) * self.element().len()
+            }
+        }
+    }
+}
+
+impl ExtraBytesDescriptor {
+    /// The user id of the extra bytes vlr.
+    pub const USER_ID: &'static str = "LASF_Spec";
+
+    /// The record id of the extra bytes vlr.
+    pub const RECORD_ID: u16 = 4;
+
+    /// Creates a descriptor with no description, no data value, bounds, scale, or offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{ExtraBytesDescriptor, ExtraBytesType};
+    /// let descriptor = ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32);
+    /// assert_eq!(None, descriptor.scale);
+    /// ```
+    pub fn new(name: &str, data_type: ExtraBytesType) -> ExtraBytesDescriptor {
+        ExtraBytesDescriptor {
+            data_type,
+            name: name.to_string(),
+            description: String::new(),
+            no_data: None,
+            min: None,
+            max: None,
+            scale: None,
+            offset: None,
+        }
+    }
+
+    /// Returns the number of extra bytes that this attribute takes up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{ExtraBytesDescriptor, ExtraBytesType};
+    /// assert_eq!(2, ExtraBytesDescriptor::new("a", ExtraBytesType::U16).len());
+    /// ```
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.data_type.len()
+    }
+
+    /// Reads the descriptors from an extra bytes vlr.
+    ///
+    /// Returns `None` if the vlr isn't an extra bytes vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::ExtraBytesDescriptor, Vlr};
+    /// assert!(ExtraBytesDescriptor::from_vlr(&Vlr::default()).is_none());
+    /// ```
+    pub fn from_vlr(vlr: &Vlr) -> Option<Result<Vec<ExtraBytesDescriptor>>> {
+        if vlr.user_id == Self::USER_ID && vlr.record_id == Self::RECORD_ID {
+            Some(ExtraBytesDescriptor::read_all(&vlr.data))
+        } else {
+            None
+        }
+    }
+
+    /// Creates an extra bytes vlr from descriptors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{ExtraBytesDescriptor, ExtraBytesType};
+    /// let descriptors = [ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32)];
+    /// let vlr = ExtraBytesDescriptor::to_vlr(&descriptors).unwrap();
+    /// assert_eq!(192, vlr.data.len());
+    /// assert_eq!(descriptors[..], ExtraBytesDescriptor::from_vlr(&vlr).unwrap().unwrap()[..]);
+    /// ```
+    pub fn to_vlr(descriptors: &[ExtraBytesDescriptor]) -> Result<Vlr> {
+        let mut data = Vec::with_capacity(descriptors.len() * DESCRIPTOR_LEN);
+        for descriptor in descriptors {
+            descriptor.write_to(&mut data)?;
+        }
+        Ok(Vlr {
+            user_id: Self::USER_ID.to_string(),
+            record_id: Self::RECORD_ID,
+            description: "Extra bytes".to_string(),
+            data,
+            reserved: None,
+        })
+    }
+
+    pub(crate) fn read_all(data: &[u8]) -> Result<Vec<ExtraBytesDescriptor>> {
+        if !data.len().is_multiple_of(DESCRIPTOR_LEN) {
+            return Err(Error::InvalidExtraBytesVlr(format!(
+                "{} bytes isn't a whole number of descriptors",
+                data.len()
+            )));
+        }
+        data.chunks_exact(DESCRIPTOR_LEN)
+            .map(ExtraBytesDescriptor::read_from)
+            .collect()
+    }
+
+    fn read_from(data: &[u8]) -> Result<ExtraBytesDescriptor> {
+        let mut read = &data[2..];
+        let data_type = read.read_u8()?;
+        let options = read.read_u8()?;
+        let data_type = ExtraBytesType::new(data_type, options)?;
+        let name = (&data[4..36]).as_las_string_lossy();
+        let stored = |offset: usize| {
+            let bytes = data[offset..offset + 8].try_into().expect("eight bytes");
+            match data_type.element() {
+                ExtraBytesType::U8
+                | ExtraBytesType::U16
+                | ExtraBytesType::U32
+                | ExtraBytesType::U64 => u64::from_le_bytes(bytes) as f64,
+                ExtraBytesType::F32 | ExtraBytesType::F64 => f64::from_le_bytes(bytes),
+                _ => i64::from_le_bytes(bytes) as f64,
+            }
+        };
+        let float = |offset: usize| {
+            f64::from_le_bytes(data[offset..offset + 8].try_into().expect("eight bytes"))
+        };
+        let documented = !matches!(data_type, ExtraBytesType::Undocumented(_));
+        let option =
+            |bit: u8, value: f64| (documented && options & (1 << bit) != 0).then_some(value);
+        Ok(ExtraBytesDescriptor {
+            data_type,
+            name,
+            description: (&data[160..192]).as_las_string_lossy(),
+            no_data: option(0, stored(40)),
+            min: option(1, stored(64)),
+            max: option(2, stored(88)),
+            scale: option(3, float(112)),
+            offset: option(4, float(136)),
+        })
+    }
+
+    fn write_to(&self, data: &mut Vec<u8>) -> Result<()> {
+        let start = data.len();
+        data.resize(start + DESCRIPTOR_LEN, 0);
+        let descriptor = &mut data[start..];
+        descriptor[2] = self.data_type.to_u8();
+        descriptor[3] = if let ExtraBytesType::Undocumented(n) = self.data_type {
+            n
+        } else {
+            [self.no_data, self.min, self.max, self.scale, self.offset]
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| value.is_some())
+                .fold(0, |options, (bit, _)| options | 1 << bit)
+        };
+        (&mut descriptor[4..36]).from_las_str(&self.name)?;
+        for (offset, value) in [(40, self.no_data), (64, self.min), (88, self.max)] {
+            if let Some(value) = value {
+                let mut write = &mut descriptor[offset..offset + 8];
+                match self.data_type.element() {
+                    ExtraBytesType::U8
+                    | ExtraBytesType::U16
+                    | ExtraBytesType::U32
+                    | ExtraBytesType::U64 => write.write_u64::<LittleEndian>(value as u64)?,
+                    ExtraBytesType::F32 | ExtraBytesType::F64 => {
+                        write.write_f64::<LittleEndian>(value)?
+                    }
+                    _ => write.write_i64::<LittleEndian>(value as i64)?,
+                }
+            }
+        }
+        for (offset, value) in [(112, self.scale), (136, self.offset)] {
+            if let Some(value) = value {
+                descriptor[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+            }
+        }
+        (&mut descriptor[160..192]).from_las_str(&self.description)
+    }
+
+    /// Decodes a value from this attribute's bytes, with the scale and offset applied.
+    ///
+    /// Returns `None` if there aren't enough bytes, if the bytes are undocumented or an array, or
+    /// if the stored value is the no data value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{ExtraBytesDescriptor, ExtraBytesType};
+    /// let mut descriptor = ExtraBytesDescriptor::new("a", ExtraBytesType::U8);
+    /// descriptor.no_data = Some(255.);
+    /// descriptor.offset = Some(100.);
+    /// assert_eq!(Some(142.), descriptor.decode(&[42]));
+    /// assert_eq!(None, descriptor.decode(&[255]));
+    /// ```
+    pub fn decode(&self, bytes: &[u8]) -> Option<f64> {
+        let bytes = bytes.get(..self.len())?;
+        let stored = match self.data_type {
+            ExtraBytesType::Undocumented(_) | ExtraBytesType::Array(_) => return None,
+            ExtraBytesType::U8 => f64::from(bytes[0]),
+            ExtraBytesType::I8 => f64::from(bytes[0] as i8),
+            ExtraBytesType::U16 => f64::from(u16::from_le_bytes(bytes.try_into().ok()?)),
+            ExtraBytesType::I16 => f64::from(i16::from_le_bytes(bytes.try_into().ok()?)),
+            ExtraBytesType::U32 => f64::from(u32::from_le_bytes(bytes.try_into().ok()?)),
+            ExtraBytesType::I32 => f64::from(i32::from_le_bytes(bytes.try_into().ok()?)),
+            ExtraBytesType::U64 => u64::from_le_bytes(bytes.try_into().ok()?) as f64,
+            ExtraBytesType::I64 => i64::from_le_bytes(bytes.try_into().ok()?) as f64,
+            ExtraBytesType::F32 => f64::from(f32::from_le_bytes(bytes.try_into().ok()?)),
+            ExtraBytesType::F64 => f64::from_le_bytes(bytes.try_into().ok()?),
+        };
+        if self.no_data == Some(stored) {
+            None
+        } else {
+            Some(stored * self.scale.unwrap_or(1.) + self.offset.unwrap_or(0.))
+        }
+    }
+
+    /// Encodes a value into this attribute's bytes, removing the scale and offset.
+    ///
+    /// Integer types are rounded to the nearest stored value. Returns false, and leaves the bytes
+    /// alone, if the value isn't finite, can't be stored in the type, or if there aren't enough
+    /// bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{ExtraBytesDescriptor, ExtraBytesType};
+    /// let descriptor = ExtraBytesDescriptor::new("a", ExtraBytesType::I8);
+    /// let mut bytes = [0];
+    /// assert!(descriptor.encode(-3., &mut bytes));
+    /// assert_eq!([253], bytes);
+    /// assert!(!descriptor.encode(300., &mut bytes));
+    /// ```
+    pub fn encode(&self, value: f64, bytes: &mut [u8]) -> bool {
+        let stored = (value - self.offset.unwrap_or(0.)) / self.scale.unwrap_or(1.);
+        self.encode_stored(stored, bytes)
+    }
+
+    /// Encodes the no data value, returning false if there isn't one.
+    pub(crate) fn encode_no_data(&self, bytes: &mut [u8]) -> bool {
+        self.no_data
+            .is_some_and(|no_data| self.encode_stored(no_data, bytes))
+    }
+
+    fn encode_stored(&self, stored: f64, bytes: &mut [u8]) -> bool {
+        let Some(bytes) = bytes.get_mut(..self.len()) else {
+            return false;
+        };
+        if !stored.is_finite() {
+            return false;
+        }
+        let rounded = stored.round();
+        let in_range = |min: f64, max: f64| rounded >= min && rounded <= max;
+        match self.data_type {
+            ExtraBytesType::Undocumented(_) => false,
+            ExtraBytesType::U8 if in_range(0., f64::from(u8::MAX)) => {
+                bytes[0] = rounded as u8;
+                true
+            }
+            ExtraBytesType::I8 if in_range(f64::from(i8::MIN), f64::from(i8::MAX)) => {
+                bytes[0] = rounded as i8 as u8;
+                true
+            }
+            ExtraBytesType::U16 if in_range(0., f64::from(u16::MAX)) => {
+                bytes.copy_from_slice(&(rounded as u16).to_le_bytes());
+                true
+            }
+            ExtraBytesType::I16 if in_range(f64::from(i16::MIN), f64::from(i16::MAX)) => {
+                bytes.copy_from_slice(&(rounded as i16).to_le_bytes());
+                true
+            }
+            ExtraBytesType::U32 if in_range(0., f64::from(u32::MAX)) => {
+                bytes.copy_from_slice(&(rounded as u32).to_le_bytes());
+                true
+            }
+            ExtraBytesType::I32 if in_range(f64::from(i32::MIN), f64::from(i32::MAX)) => {
+                bytes.copy_from_slice(&(rounded as i32).to_le_bytes());
+                true
+            }
+            // The maximums aren't floats, and round up to 2^64 and 2^63, so those are excluded.
+            ExtraBytesType::U64 if (0. ..2f64.powi(64)).contains(&rounded) => {
+                bytes.copy_from_slice(&(rounded as u64).to_le_bytes());
+                true
+            }
+            ExtraBytesType::I64 if (-2f64.powi(63)..2f64.powi(63)).contains(&rounded) => {
+                bytes.copy_from_slice(&(rounded as i64).to_le_bytes());
+                true
+            }
+            ExtraBytesType::F32 if stored.abs() <= f64::from(f32::MAX) => {
+                bytes.copy_from_slice(&(stored as f32).to_le_bytes());
+                true
+            }
+            ExtraBytesType::F64 => {
+                bytes.copy_from_slice(&stored.to_le_bytes());
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut descriptor = ExtraBytesDescriptor::new("HeightAboveGround", ExtraBytesType::I32);
+        descriptor.description = "Height above ground".to_string();
+        descriptor.no_data = Some(f64::from(i32::MIN));
+        descriptor.min = Some(-5.);
+        descriptor.scale = Some(0.001);
+        descriptor.offset = Some(10.);
+        let descriptors = [
+            ExtraBytesDescriptor::new("", ExtraBytesType::Undocumented(3)),
+            descriptor,
+            ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32),
+        ];
+        let vlr = ExtraBytesDescriptor::to_vlr(&descriptors).unwrap();
+        assert_eq!(3 * DESCRIPTOR_LEN, vlr.data.len());
+        assert_eq!(0b1_1011, vlr.data[DESCRIPTOR_LEN + 3]);
+        assert_eq!(3, vlr.data[3]);
+        let read = ExtraBytesDescriptor::from_vlr(&vlr).unwrap().unwrap();
+        assert_eq!(descriptors[..], read[..]);
+    }
+
+    #[test]
+    fn arrays() {
+        let mut descriptor = ExtraBytesDescriptor::new("Position", ExtraBytesType::Array(30));
+        descriptor.scale = Some(0.01);
+        let vlr = ExtraBytesDescriptor::to_vlr(&[descriptor.clone()]).unwrap();
+        let read = ExtraBytesDescriptor::from_vlr(&vlr).unwrap().unwrap();
+        assert_eq!(descriptor, read[0]);
+        assert_eq!(24, read[0].len());
+        assert_eq!(4, ExtraBytesType::Array(13).len());
+        assert_eq!(None, descriptor.decode(&[0; 24]));
+        assert!(!descriptor.encode(1., &mut [0; 24]));
+
+        let file = std::fs::File::open("tests/data/extrabytes.laz").unwrap();
+        let header = crate::Header::new(std::io::BufReader::new(file)).unwrap();
+        let schema = header.extra_bytes_schema().unwrap();
+        assert_eq!(usize::from(header.point_format().extra_bytes), schema.len());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut vlr =
+            ExtraBytesDescriptor::to_vlr(&[ExtraBytesDescriptor::new("a", ExtraBytesType::U8)])
+                .unwrap();
+        vlr.data[2] = 31;
+        assert!(matches!(
+            ExtraBytesDescriptor::from_vlr(&vlr).unwrap().unwrap_err(),
+            Error::InvalidExtraBytesVlr(_)
+        ));
+        let _ = vlr.data.pop();
+        assert!(matches!(
+            ExtraBytesDescriptor::from_vlr(&vlr).unwrap().unwrap_err(),
+            Error::InvalidExtraBytesVlr(_)
+        ));
+        let descriptor = ExtraBytesDescriptor::new(&"a".repeat(33), ExtraBytesType::U8);
+        assert!(ExtraBytesDescriptor::to_vlr(&[descriptor]).is_err());
+    }
+
+    #[test]
+    fn encode_limits() {
+        let mut bytes = [0; 8];
+        let u16 = ExtraBytesDescriptor::new("", ExtraBytesType::U16);
+        assert!(u16.encode(65_535.4, &mut bytes));
+        assert_eq!(Some(65_535.), u16.decode(&bytes));
+        assert!(!u16.encode(65_535.5, &mut bytes));
+        assert!(!u16.encode(-0.5, &mut bytes));
+        assert!(!u16.encode(f64::NAN, &mut bytes));
+        let u64 = ExtraBytesDescriptor::new("", ExtraBytesType::U64);
+        assert!(!u64.encode(2f64.powi(64), &mut bytes));
+        assert!(u64.encode(2f64.powi(63), &mut bytes));
+        let f32 = ExtraBytesDescriptor::new("", ExtraBytesType::F32);
+        assert!(!f32.encode(1e39, &mut bytes));
+        assert!(f32.encode(0.5, &mut bytes));
+        assert_eq!(Some(0.5), f32.decode(&bytes));
+        assert!(!u16.encode(1., &mut [0]));
+    }
+}
@@ -0,0 +1,475 @@
+use super::{
+    Classification, ExtraBytesDescriptor, ExtraBytesSchema, ExtraBytesType, ScanDirection,
+};
+use crate::{Color, Error, Header, Point, Result};
+
+/// A point attribute that can be read or written as a number.
+///
+/// Generic tools, like format converters, statistics, or filters, can work through a list of
+/// dimensions with [Point::value] and [Point::set_value] instead of matching on every field. Flags
+/// are zero or one, the scan direction is zero from right to left and one from left to right, and
+/// the classification is its code. A reader can be told which dimensions to decode with
+/// [ReadOptions::dimensions](crate::reader::ReadOptions::dimensions).
+///
+/// # Examples
+///
+/// ```
+/// use las::{point::Dimension, Point};
+/// let point = Point { intensity: 42, ..Default::default() };
+/// assert_eq!(Some(42.), point.value(&Dimension::Intensity));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Dimension {
+    /// [Point::x].
+    X,
+
+    /// [Point::y].
+    Y,
+
+    /// [Point::z].
+    Z,
+
+    /// [Point::intensity].
+    Intensity,
+
+    /// [Point::return_number].
+    ReturnNumber,
+
+    /// [Point::number_of_returns].
+    NumberOfReturns,
+
+    /// [Point::scan_direction].
+    ScanDirection,
+
+    /// [Point::is_edge_of_flight_line].
+    EdgeOfFlightLine,
+
+    /// [Point::classification].
+    Classification,
+
+    /// [Point::is_synthetic].
+    Synthetic,
+
+    /// [Point::is_key_point].
+    KeyPoint,
+
+    /// [Point::is_withheld].
+    Withheld,
+
+    /// [Point::is_overlap].
+    Overlap,
+
+    /// [Point::scanner_channel].
+    ScannerChannel,
+
+    /// [Point::scan_angle], in degrees.
+    ScanAngle,
+
+    /// [Point::user_data].
+    UserData,
+
+    /// [Point::point_source_id].
+    PointSourceId,
+
+    /// [Point::gps_time].
+    GpsTime,
+
+    /// The red channel of [Point::color].
+    Red,
+
+    /// The green channel of [Point::color].
+    Green,
+
+    /// The blue channel of [Point::color].
+    Blue,
+
+    /// [Point::nir].
+    Nir,
+
+    /// A named attribute in [Point::extra_bytes], with its scale and offset applied.
+    Extra {
+        /// Where the attribute starts in the extra bytes.
+        start: usize,
+
+        /// The attribute's descriptor.
+        descriptor: ExtraBytesDescriptor,
+    },
+}
+
+impl Dimension {
+    /// Returns the dimension for a named extra bytes attribute, if the schema has it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{Dimension, ExtraBytesDescriptor, ExtraBytesSchema, ExtraBytesType};
+    /// let schema = ExtraBytesSchema::new(vec![ExtraBytesDescriptor::new("a", ExtraBytesType::U8)]);
+    /// assert_eq!("a", Dimension::extra(&schema, "a").unwrap().name());
+    /// assert!(Dimension::extra(&schema, "b").is_none());
+    /// ```
+    pub fn extra(schema: &ExtraBytesSchema, name: &str) -> Option<Dimension> {
+        schema
+            .get(name)
+            .map(|(start, descriptor)| Dimension::Extra {
+                start,
+                descriptor: descriptor.clone(),
+            })
+    }
+
+    /// Returns the dimension's name, which is the descriptor's name for an extra bytes attribute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Dimension;
+    /// assert_eq!("GpsTime", Dimension::GpsTime.name());
+    /// ```
+    pub fn name(&self) -> &str {
+        match self {
+            Dimension::X => "X",
+            Dimension::Y => "Y",
+            Dimension::Z => "Z",
+            Dimension::Intensity => "Intensity",
+            Dimension::ReturnNumber => "ReturnNumber",
+            Dimension::NumberOfReturns => "NumberOfReturns",
+            Dimension::ScanDirection => "ScanDirection",
+            Dimension::EdgeOfFlightLine => "EdgeOfFlightLine",
+            Dimension::Classification => "Classification",
+            Dimension::Synthetic => "Synthetic",
+            Dimension::KeyPoint => "KeyPoint",
+            Dimension::Withheld => "Withheld",
+            Dimension::Overlap => "Overlap",
+            Dimension::ScannerChannel => "ScannerChannel",
+            Dimension::ScanAngle => "ScanAngle",
+            Dimension::UserData => "UserData",
+            Dimension::PointSourceId => "PointSourceId",
+            Dimension::GpsTime => "GpsTime",
+            Dimension::Red => "Red",
+            Dimension::Green => "Green",
+            Dimension::Blue => "Blue",
+            Dimension::Nir => "Nir",
+            Dimension::Extra { descriptor, .. } => &descriptor.name,
+        }
+    }
+
+    fn invalid(&self, value: f64) -> Error {
+        Error::InvalidDimensionValue {
+            dimension: self.name().to_string(),
+            value,
+        }
+    }
+}
+
+impl Header {
+    /// Returns the dimensions of this header's points, in the order they're stored.
+    ///
+    /// The standard dimensions are the ones the point format has, followed by the named
+    /// attributes of the extra bytes schema. Undocumented extra bytes aren't dimensions. Returns
+    /// an error if the extra bytes vlr is invalid, see [Header::extra_bytes_schema].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let dimensions = reader.header().dimensions().unwrap();
+    /// assert!(dimensions.contains(&Dimension::GpsTime));
+    /// assert!(!dimensions.contains(&Dimension::Red));
+    /// ```
+    pub fn dimensions(&self) -> Result<Vec<Dimension>> {
+        let format = self.point_format();
+        let mut dimensions = vec![
+            Dimension::X,
+            Dimension::Y,
+            Dimension::Z,
+            Dimension::Intensity,
+            Dimension::ReturnNumber,
+            Dimension::NumberOfReturns,
+        ];
+        if format.is_extended {
+            dimensions.extend([
+                Dimension::Synthetic,
+                Dimension::KeyPoint,
+                Dimension::Withheld,
+                Dimension::Overlap,
+                Dimension::ScannerChannel,
+                Dimension::ScanDirection,
+                Dimension::EdgeOfFlightLine,
+                Dimension::Classification,
+                Dimension::UserData,
+                Dimension::ScanAngle,
+            ]);
+        } else {
+            dimensions.extend([
+                Dimension::ScanDirection,
+                Dimension::EdgeOfFlightLine,
+                Dimension::Classification,
+                Dimension::Synthetic,
+                Dimension::KeyPoint,
+                Dimension::Withheld,
+                Dimension::ScanAngle,
+                Dimension::UserData,
+            ]);
+        }
+        dimensions.push(Dimension::PointSourceId);
+        if format.has_gps_time {
+            dimensions.push(Dimension::GpsTime);
+        }
+        if format.has_color {
+            dimensions.extend([Dimension::Red, Dimension::Green, Dimension::Blue]);
+        }
+        if format.has_nir {
+            dimensions.push(Dimension::Nir);
+        }
+        let schema = self.extra_bytes_schema()?;
+        dimensions.extend(
+            schema
+                .attributes()
+                .filter(|(_, descriptor)| {
+                    !matches!(
+                        descriptor.data_type,
+                        ExtraBytesType::Undocumented(_) | ExtraBytesType::Array(_)
+                    )
+                })
+                .map(|(start, descriptor)| Dimension::Extra {
+                    start,
+                    descriptor: descriptor.clone(),
+                }),
+        );
+        Ok(dimensions)
+    }
+}
+
+impl Point {
+    /// Returns the value of a dimension.
+    ///
+    /// Returns `None` if the point doesn't have the dimension, e.g. a color channel of a point
+    /// without a color, or an extra bytes attribute that's past the end of the point's extra bytes
+    /// or has its no data value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, Point};
+    /// let point = Point { is_withheld: true, ..Default::default() };
+    /// assert_eq!(Some(1.), point.value(&Dimension::Withheld));
+    /// assert_eq!(None, point.value(&Dimension::GpsTime));
+    /// ```
+    pub fn value(&self, dimension: &Dimension) -> Option<f64> {
+        let flag = |flag: bool| Some(f64::from(u8::from(flag)));
+        match dimension {
+            Dimension::X => Some(self.x),
+            Dimension::Y => Some(self.y),
+            Dimension::Z => Some(self.z),
+            Dimension::Intensity => Some(f64::from(self.intensity)),
+            Dimension::ReturnNumber => Some(f64::from(self.return_number)),
+            Dimension::NumberOfReturns => Some(f64::from(self.number_of_returns)),
+            Dimension::ScanDirection => flag(self.scan_direction == ScanDirection::LeftToRight),
+            Dimension::EdgeOfFlightLine => flag(self.is_edge_of_flight_line),
+            Dimension::Classification => Some(f64::from(u8::from(self.classification))),
+            Dimension::Synthetic => flag(self.is_synthetic),
+            Dimension::KeyPoint => flag(self.is_key_point),
+            Dimension::Withheld => flag(self.is_withheld),
+            Dimension::Overlap => flag(self.is_overlap),
+            Dimension::ScannerChannel => Some(f64::from(self.scanner_channel)),
+            Dimension::ScanAngle => Some(f64::from(self.scan_angle)),
+            Dimension::UserData => Some(f64::from(self.user_data)),
+            Dimension::PointSourceId => Some(f64::from(self.point_source_id)),
+            Dimension::GpsTime => self.gps_time,
+            Dimension::Red => self.color.map(|color| f64::from(color.red)),
+            Dimension::Green => self.color.map(|color| f64::from(color.green)),
+            Dimension::Blue => self.color.map(|color| f64::from(color.blue)),
+            Dimension::Nir => self.nir.map(f64::from),
+            Dimension::Extra { start, descriptor } => {
+                descriptor.decode(self.extra_bytes.get(*start..)?)
+            }
+        }
+    }
+
+    /// Sets the value of a dimension.
+    ///
+    /// Setting a color channel of a point without a color gives it a black color first. Extra
+    /// bytes that are too short for the attribute are padded with zeros. Returns
+    /// [Error::InvalidDimensionValue] if the field can't hold the value exactly, e.g. a fraction
+    /// or a negative number for an integer field, anything but zero or one for a flag, or a gps
+    /// time or scan angle that isn't finite, and [Error::ExtraAttributeNotRepresentable] if an
+    /// extra bytes attribute can't store it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, Point};
+    /// let mut point = Point::default();
+    /// point.set_value(&Dimension::Green, 1000.).unwrap();
+    /// assert_eq!(1000, point.color.unwrap().green);
+    /// assert!(point.set_value(&Dimension::Intensity, -1.).is_err());
+    /// ```
+    pub fn set_value(&mut self, dimension: &Dimension, value: f64) -> Result<()> {
+        let integer = |max: u16| {
+            (value.fract() == 0. && value >= 0. && value <= f64::from(max))
+                .then_some(value as u16)
+                .ok_or_else(|| dimension.invalid(value))
+        };
+        let byte = || integer(u16::from(u8::MAX)).map(|n| n as u8);
+        let flag = || integer(1).map(|n| n == 1);
+        let finite = || {
+            value
+                .is_finite()
+                .then_some(value)
+                .ok_or_else(|| dimension.invalid(value))
+        };
+        match dimension {
+            Dimension::X => self.x = value,
+            Dimension::Y => self.y = value,
+            Dimension::Z => self.z = value,
+            Dimension::Intensity => self.intensity = integer(u16::MAX)?,
+            Dimension::ReturnNumber => self.return_number = byte()?,
+            Dimension::NumberOfReturns => self.number_of_returns = byte()?,
+            Dimension::ScanDirection => {
+                self.scan_direction = if flag()? {
+                    ScanDirection::LeftToRight
+                } else {
+                    ScanDirection::RightToLeft
+                }
+            }
+            Dimension::EdgeOfFlightLine => self.is_edge_of_flight_line = flag()?,
+            Dimension::Classification => self.classification = Classification::new(byte()?)?,
+            Dimension::Synthetic => self.is_synthetic = flag()?,
+            Dimension::KeyPoint => self.is_key_point = flag()?,
+            Dimension::Withheld => self.is_withheld = flag()?,
+            Dimension::Overlap => self.is_overlap = flag()?,
+            Dimension::ScannerChannel => self.scanner_channel = byte()?,
+            Dimension::ScanAngle => {
+                let scan_angle = finite()? as f32;
+                if !scan_angle.is_finite() {
+                    return Err(dimension.invalid(value));
+                }
+                self.scan_angle = scan_angle;
+            }
+            Dimension::UserData => self.user_data = byte()?,
+            Dimension::PointSourceId => self.point_source_id = integer(u16::MAX)?,
+            Dimension::GpsTime => self.gps_time = Some(finite()?),
+            Dimension::Red | Dimension::Green | Dimension::Blue => {
+                let n = integer(u16::MAX)?;
+                let color = self.color.get_or_insert_with(Color::default);
+                match dimension {
+                    Dimension::Red => color.red = n,
+                    Dimension::Green => color.green = n,
+                    _ => color.blue = n,
+                }
+            }
+            Dimension::Nir => self.nir = Some(integer(u16::MAX)?),
+            Dimension::Extra { start, descriptor } => {
+                let end = start + descriptor.len();
+                if self.extra_bytes.len() < end {
+                    let mut extra_bytes = super::ExtraBytes::zeroed(end);
+                    extra_bytes[..self.extra_bytes.len()].copy_from_slice(&self.extra_bytes);
+                    self.extra_bytes = extra_bytes;
+                }
+                if !descriptor.encode(value, &mut self.extra_bytes[*start..]) {
+                    return Err(Error::ExtraAttributeNotRepresentable {
+                        name: descriptor.name.clone(),
+                        value,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, Builder};
+
+    #[test]
+    fn roundtrip_every_dimension() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(8).unwrap();
+        builder.point_format.extra_bytes = 5;
+        builder.gps_time_type = crate::GpsTimeType::Standard;
+        builder.vlrs.push(
+            ExtraBytesDescriptor::to_vlr(&[
+                ExtraBytesDescriptor::new("", ExtraBytesType::Undocumented(1)),
+                ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32),
+            ])
+            .unwrap(),
+        );
+        let header = builder.into_header().unwrap();
+        let dimensions = header.dimensions().unwrap();
+        assert_eq!(23, dimensions.len());
+        assert_eq!("Amplitude", dimensions.last().unwrap().name());
+
+        let mut point = Point::default();
+        for (i, dimension) in dimensions.iter().enumerate() {
+            let value = match dimension {
+                Dimension::ScanDirection
+                | Dimension::EdgeOfFlightLine
+                | Dimension::Synthetic
+                | Dimension::KeyPoint
+                | Dimension::Withheld
+                | Dimension::Overlap => 1.,
+                Dimension::Classification => 6.,
+                _ => i as f64,
+            };
+            point.set_value(dimension, value).unwrap();
+            assert_eq!(Some(value), point.value(dimension), "{}", dimension.name());
+        }
+        assert_eq!(5, point.extra_bytes.len());
+    }
+
+    #[test]
+    fn refuses_values_a_field_cannot_hold() {
+        let mut point = Point::default();
+        for (dimension, value) in [
+            (Dimension::Intensity, 0.5),
+            (Dimension::UserData, 256.),
+            (Dimension::Withheld, 2.),
+            (Dimension::Red, f64::NAN),
+        ] {
+            assert!(matches!(
+                point.set_value(&dimension, value),
+                Err(Error::InvalidDimensionValue { .. })
+            ));
+        }
+        assert_eq!(Point::default(), point);
+    }
+
+    #[test]
+    fn refuses_non_finite_scan_angles_and_gps_times() {
+        let mut point = Point::default();
+        for (dimension, value) in [
+            (Dimension::ScanAngle, f64::NAN),
+            (Dimension::ScanAngle, f64::INFINITY),
+            (Dimension::ScanAngle, f64::MAX),
+            (Dimension::GpsTime, f64::NAN),
+            (Dimension::GpsTime, f64::NEG_INFINITY),
+        ] {
+            assert!(matches!(
+                point.set_value(&dimension, value),
+                Err(Error::InvalidDimensionValue { .. })
+            ));
+        }
+        assert_eq!(Point::default(), point);
+        point.set_value(&Dimension::ScanAngle, -12.5).unwrap();
+        point.set_value(&Dimension::GpsTime, 1e9).unwrap();
+        assert_eq!(-12.5, point.scan_angle);
+        assert_eq!(Some(1e9), point.gps_time);
+    }
+
+    #[test]
+    fn missing_values() {
+        let point = Point::default();
+        let schema =
+            ExtraBytesSchema::new(vec![ExtraBytesDescriptor::new("a", ExtraBytesType::U8)]);
+        for dimension in [
+            Dimension::GpsTime,
+            Dimension::Blue,
+            Dimension::Nir,
+            Dimension::extra(&schema, "a").unwrap(),
+        ] {
+            assert_eq!(None, point.value(&dimension));
+        }
+    }
+}
@@ -0,0 +1,201 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// The number of extra bytes that are stored without a heap allocation.
+const INLINE_CAPACITY: usize = 16;
+
+/// A point's extra bytes.
+///
+/// Up to sixteen extra bytes are stored inline, so most points don't need a heap allocation for
+/// them. Extra bytes dereference to a byte slice:
+///
+/// ```
+/// use las::point::ExtraBytes;
+/// let mut extra_bytes = ExtraBytes::from(vec![1, 2, 3]);
+/// assert_eq!(3, extra_bytes.len());
+/// extra_bytes[0] = 42;
+/// assert_eq!([42, 2, 3], extra_bytes[..]);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "Vec<u8>", into = "Vec<u8>")
+)]
+pub struct ExtraBytes(Storage);
+
+#[derive(Clone)]
+enum Storage {
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_CAPACITY],
+    },
+    Heap(Vec<u8>),
+}
+
+impl ExtraBytes {
+    /// Creates empty extra bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::ExtraBytes;
+    /// assert!(ExtraBytes::new().is_empty());
+    /// ```
+    pub fn new() -> ExtraBytes {
+        ExtraBytes::zeroed(0)
+    }
+
+    /// Creates `len` zeroed extra bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::ExtraBytes;
+    /// assert_eq!([0, 0], ExtraBytes::zeroed(2)[..]);
+    /// ```
+    pub fn zeroed(len: usize) -> ExtraBytes {
+        if len <= INLINE_CAPACITY {
+            ExtraBytes(Storage::Inline {
+                len: len as u8,
+                bytes: [0; INLINE_CAPACITY],
+            })
+        } else {
+            ExtraBytes(Storage::Heap(vec![0; len]))
+        }
+    }
+
+    /// Sets the length to `len`, for bytes that are about to be overwritten.
+    ///
+    /// Heap storage is reused, so reading the same number of bytes again doesn't allocate. If the
+    /// length changes, the bytes are zeroed.
+    pub(crate) fn set_len(&mut self, len: usize) {
+        match &mut self.0 {
+            Storage::Heap(bytes) if bytes.len() == len => {}
+            Storage::Heap(bytes) if len > INLINE_CAPACITY => {
+                bytes.clear();
+                bytes.resize(len, 0);
+            }
+            _ => *self = ExtraBytes::zeroed(len),
+        }
+    }
+
+    /// Returns true if these extra bytes are stored without a heap allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::ExtraBytes;
+    /// assert!(ExtraBytes::zeroed(16).is_inline());
+    /// assert!(!ExtraBytes::zeroed(17).is_inline());
+    /// ```
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Storage::Inline { .. })
+    }
+}
+
+impl Default for ExtraBytes {
+    fn default() -> ExtraBytes {
+        ExtraBytes::new()
+    }
+}
+
+impl Deref for ExtraBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.0 {
+            Storage::Inline { len, bytes } => &bytes[..usize::from(*len)],
+            Storage::Heap(bytes) => bytes,
+        }
+    }
+}
+
+impl DerefMut for ExtraBytes {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match &mut self.0 {
+            Storage::Inline { len, bytes } => &mut bytes[..usize::from(*len)],
+            Storage::Heap(bytes) => bytes,
+        }
+    }
+}
+
+impl fmt::Debug for ExtraBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for ExtraBytes {
+    fn eq(&self, other: &ExtraBytes) -> bool {
+        **self == **other
+    }
+}
+
+impl PartialEq<[u8]> for ExtraBytes {
+    fn eq(&self, other: &[u8]) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<Vec<u8>> for ExtraBytes {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        **self == **other
+    }
+}
+
+impl From<&[u8]> for ExtraBytes {
+    fn from(slice: &[u8]) -> ExtraBytes {
+        let mut extra_bytes = ExtraBytes::zeroed(slice.len());
+        extra_bytes.copy_from_slice(slice);
+        extra_bytes
+    }
+}
+
+impl From<Vec<u8>> for ExtraBytes {
+    fn from(vec: Vec<u8>) -> ExtraBytes {
+        if vec.len() <= INLINE_CAPACITY {
+            ExtraBytes::from(vec.as_slice())
+        } else {
+            ExtraBytes(Storage::Heap(vec))
+        }
+    }
+}
+
+impl From<ExtraBytes> for Vec<u8> {
+    fn from(extra_bytes: ExtraBytes) -> Vec<u8> {
+        match extra_bytes.0 {
+            Storage::Inline { .. } => extra_bytes.to_vec(),
+            Storage::Heap(bytes) => bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_and_heap_are_equal() {
+        let bytes: Vec<u8> = (0..20).collect();
+        let heap = ExtraBytes::from(bytes.clone());
+        assert!(!heap.is_inline());
+        let mut inline = ExtraBytes::from(&bytes[..10]);
+        assert!(inline.is_inline());
+        assert_ne!(heap, inline);
+        assert_eq!(bytes[..10], inline[..]);
+        inline[9] = 42;
+        assert_eq!(42, inline[9]);
+        assert_eq!(bytes, Vec::from(heap));
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(ExtraBytes::new(), ExtraBytes::from(Vec::new()));
+        assert!(ExtraBytes::default().is_empty());
+    }
+}
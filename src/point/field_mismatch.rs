@@ -0,0 +1,34 @@
+use std::fmt;
+
+/// A way that a point doesn't match a point format.
+///
+/// Returned by [Point::mismatches](crate::Point::mismatches).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldMismatch {
+    /// The format has this field, but the point doesn't.
+    Missing(&'static str),
+
+    /// The point has this field, but the format doesn't.
+    Unexpected(&'static str),
+
+    /// The point has a different number of extra bytes than the format.
+    ExtraBytes {
+        /// The number of extra bytes in the format.
+        expected: u16,
+
+        /// The number of extra bytes on the point.
+        actual: usize,
+    },
+}
+
+impl fmt::Display for FieldMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldMismatch::Missing(field) => write!(f, "missing {}", field),
+            FieldMismatch::Unexpected(field) => write!(f, "unexpected {}", field),
+            FieldMismatch::ExtraBytes { expected, actual } => {
+                write!(f, "{} extra bytes, expected {}", actual, expected)
+            }
+        }
+    }
+}
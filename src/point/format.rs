@@ -1,5 +1,7 @@
+#[cfg(feature = "std")]
+use crate::Point;
 use crate::{point::Error, Result};
-use std::fmt;
+use core::fmt;
 
 const TIME_FORMATS: &[u8] = &[1, 3, 4, 5, 6, 7, 8, 9, 10];
 const COLOR_FORMATS: &[u8] = &[2, 3, 5, 7, 8, 10];
@@ -54,6 +56,7 @@ fn point_format_id_uncompressed_to_compressed(point_format_id: u8) -> u8 {
 /// assert!(format.to_u8().is_err());
 /// ```
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Format {
     /// Does this point format include gps time?
     pub has_gps_time: bool,
@@ -69,6 +72,8 @@ pub struct Format {
     pub extra_bytes: u16,
     /// Is this point format compressed?
     pub is_compressed: bool,
+    /// The point format number, if it's one this crate doesn't know, see [Format::unknown].
+    pub unknown: Option<u8>,
 }
 
 #[allow(clippy::len_without_is_empty)]
@@ -103,10 +108,112 @@ impl Format {
                 is_extended: n >= 6,
                 extra_bytes: 0,
                 is_compressed,
+                unknown: None,
             })
         }
     }
 
+    /// Creates a point format that this crate doesn't know, with records of this length.
+    ///
+    /// Nothing is known about the records' fields, so the whole record counts as extra bytes.
+    /// Points in an unknown format can only be read as raw records, see
+    /// [ReadOptions::read_unknown_formats](crate::reader::ReadOptions::read_unknown_formats).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Format;
+    /// let format = Format::unknown(11, 40);
+    /// assert_eq!(40, format.len());
+    /// assert_eq!(11, format.to_u8().unwrap());
+    /// ```
+    pub fn unknown(n: u8, record_length: u16) -> Format {
+        Format {
+            extra_bytes: record_length,
+            unknown: Some(n),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the smallest point format that can hold every one of these points.
+    ///
+    /// A format has gps time, color, waveforms, or near infrared if any point does, so mixed
+    /// points get the superset. Points that need more than formats zero through five can hold
+    /// (more than five returns, a scanner channel, the overlap bit, a class over 31, or a scan
+    /// angle outside [-90, 90]) get an extended format, as does everything if `require_extended` is
+    /// true. The format's extra bytes are the points' extra bytes length, which must be the same for
+    /// every point. With no points, this is format zero, or six if `require_extended`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Format, Color, Point};
+    /// let points = [
+    ///     Point { gps_time: Some(1.), ..Default::default() },
+    ///     Point { color: Some(Color::new(1, 2, 3)), ..Default::default() },
+    /// ];
+    /// let format = Format::smallest_for(points.iter(), false).unwrap();
+    /// assert_eq!(Format::new(3).unwrap(), format);
+    /// let format = Format::smallest_for(points.iter(), true).unwrap();
+    /// assert_eq!(Format::new(7).unwrap(), format);
+    /// ```
+    // Eliding the lifetime in argument position impl Trait isn't stable yet.
+    #[allow(single_use_lifetimes)]
+    #[cfg(feature = "std")]
+    pub fn smallest_for<'a>(
+        points: impl Iterator<Item = &'a Point>,
+        require_extended: bool,
+    ) -> Result<Format> {
+        let mut format = Format {
+            is_extended: require_extended,
+            ..Default::default()
+        };
+        let mut extra_bytes = None;
+        for (index, point) in points.enumerate() {
+            let len = point.extra_bytes.len();
+            match extra_bytes {
+                None => extra_bytes = Some(len),
+                Some(expected) if expected != len => {
+                    return Err(Error::InconsistentExtraBytes {
+                        index,
+                        expected,
+                        len,
+                    });
+                }
+                Some(_) => {}
+            }
+            format.has_gps_time |= point.gps_time.is_some();
+            format.has_color |= point.color.is_some();
+            format.has_waveform |= point.waveform.is_some();
+            format.has_nir |= point.nir.is_some();
+            format.is_extended |= point.return_number > 5
+                || point.number_of_returns > 5
+                || point.scanner_channel > 0
+                || point.is_overlap
+                || point.original_flags.is_some()
+                || u8::from(point.classification) > 31
+                || !(-90. ..=90.).contains(&point.scan_angle);
+        }
+        let extra_bytes = extra_bytes.unwrap_or(0);
+        format.extra_bytes =
+            u16::try_from(extra_bytes).map_err(|_| Error::TooManyExtraBytes(u16::MAX))?;
+        // Only extended formats have near infrared, and they always have gps time. Waveforms need
+        // gps time too, and the only extended format with color and waveforms also has near
+        // infrared.
+        if format.has_nir {
+            format.is_extended = true;
+            format.has_color = true;
+        }
+        if format.is_extended || format.has_waveform {
+            format.has_gps_time = true;
+        }
+        if format.is_extended && format.has_color && format.has_waveform {
+            format.has_nir = true;
+        }
+        format.verify()?;
+        Ok(format)
+    }
+
     /// Converts this point format into an extended format.
     ///
     /// "Extended" formats can contain more information per point, and must have gps time.
@@ -127,6 +234,79 @@ impl Format {
         self.is_extended = true;
     }
 
+    /// Returns true if this is one of the extended point formats, six through ten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Format;
+    /// assert!(!Format::new(3).unwrap().is_extended());
+    /// assert!(Format::new(6).unwrap().is_extended());
+    /// ```
+    pub fn is_extended(&self) -> bool {
+        self.is_extended
+    }
+
+    /// Returns the extended point format that can hold everything in this one.
+    ///
+    /// This is the las 1.4 mapping: formats zero and one become six, two and three become seven,
+    /// four becomes nine, and five becomes ten. Extended formats are returned as-is. Extra bytes
+    /// and compression are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Format;
+    /// let format = Format::new(3).unwrap().to_extended().unwrap();
+    /// assert_eq!(Format::new(7).unwrap(), format);
+    /// ```
+    pub fn to_extended(&self) -> Result<Format> {
+        self.verify()?;
+        Ok(Format {
+            has_gps_time: true,
+            is_extended: true,
+            has_nir: self.has_nir || (self.has_color && self.has_waveform),
+            ..*self
+        })
+    }
+
+    /// Returns the legacy point format for this one.
+    ///
+    /// This is the las 1.4 mapping: format six becomes one, seven becomes three, and nine becomes
+    /// four. Formats eight and ten have NIR, which no legacy format can hold, so they are an
+    /// error. Legacy formats are returned as-is. Extra bytes and compression are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Format;
+    /// let format = Format::new(7).unwrap().to_legacy().unwrap();
+    /// assert_eq!(Format::new(3).unwrap(), format);
+    /// assert!(Format::new(8).unwrap().to_legacy().is_err());
+    /// ```
+    pub fn to_legacy(&self) -> Result<Format> {
+        self.verify()?;
+        if self.has_nir {
+            Err(Error::NoLegacyFormat(*self))
+        } else {
+            Ok(Format {
+                is_extended: false,
+                ..*self
+            })
+        }
+    }
+
+    /// Returns an error if this format's attributes don't map onto a point format number.
+    fn verify(&self) -> Result<()> {
+        Format {
+            is_compressed: false,
+            ..*self
+        }
+        .to_u8()
+        .map(|_| ())
+        .map_err(|_| Error::InvalidPointFormat(*self))
+    }
+
     /// Returns this point format's length.
     ///
     /// # Examples
@@ -139,6 +319,9 @@ impl Format {
     /// assert_eq!(28, format.len());
     /// ```
     pub fn len(&self) -> u16 {
+        if self.unknown.is_some() {
+            return self.extra_bytes;
+        }
         let mut len = if self.is_extended { 22 } else { 20 } + self.extra_bytes;
         if self.has_gps_time {
             len += 8;
@@ -171,7 +354,9 @@ impl Format {
     /// assert_eq!(6, format.to_u8().unwrap());
     /// ```
     pub fn to_u8(&self) -> Result<u8> {
-        if !cfg!(feature = "laz") && self.is_compressed {
+        if let Some(n) = self.unknown {
+            Ok(n)
+        } else if !cfg!(feature = "laz") && self.is_compressed {
             Err(Error::InvalidPointFormat(*self))
         } else if self.is_extended {
             if self.has_gps_time {
@@ -231,17 +416,47 @@ impl Format {
     }
 }
 
+/// Formats the point format as `point format <n>`, noting any extra bytes and compression.
+///
+/// # Examples
+///
+/// ```
+/// use las::point::Format;
+/// let mut format = Format::new(3).unwrap();
+/// assert_eq!("point format 3", format.to_string());
+/// format.extra_bytes = 4;
+/// format.is_compressed = true;
+/// assert_eq!("point format 3 with 4 extra bytes (compressed)", format.to_string());
+/// ```
 impl fmt::Display for Format {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Ok(n) = self.to_u8() {
-            write!(f, "point format {}", n)
+        if let Some(n) = self.unknown {
+            return write!(
+                f,
+                "unknown point format {} with {} byte records",
+                n, self.extra_bytes
+            );
+        }
+        let uncompressed = Format {
+            is_compressed: false,
+            ..*self
+        };
+        if let Ok(n) = uncompressed.to_u8() {
+            write!(f, "point format {}", n)?;
         } else {
-            write!(f, "point format that does not map onto a code: {:?}", self)
+            return write!(f, "point format that does not map onto a code: {:?}", self);
+        }
+        if self.extra_bytes > 0 {
+            write!(f, " with {} extra bytes", self.extra_bytes)?;
+        }
+        if self.is_compressed {
+            write!(f, " (compressed)")?;
         }
+        Ok(())
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -434,4 +649,206 @@ mod tests {
             assert!(format.to_u8().is_err());
         }
     }
+
+    #[test]
+    fn legacy_and_extended() {
+        // (format, extended, legacy)
+        let table = [
+            (0, 6, Some(0)),
+            (1, 6, Some(1)),
+            (2, 7, Some(2)),
+            (3, 7, Some(3)),
+            (4, 9, Some(4)),
+            (5, 10, Some(5)),
+            (6, 6, Some(1)),
+            (7, 7, Some(3)),
+            (8, 8, None),
+            (9, 9, Some(4)),
+            (10, 10, None),
+        ];
+        for (n, extended, legacy) in table {
+            let format = Format::new(n).unwrap();
+            assert_eq!(n >= 6, format.is_extended());
+            assert_eq!(
+                extended,
+                format.to_extended().unwrap().to_u8().unwrap(),
+                "format {n} to extended"
+            );
+            assert_eq!(
+                legacy,
+                format.to_legacy().ok().map(|f| f.to_u8().unwrap()),
+                "format {n} to legacy"
+            );
+        }
+    }
+
+    #[test]
+    fn legacy_and_extended_keep_extra_bytes_and_compression() {
+        let format = Format {
+            extra_bytes: 3,
+            is_compressed: true,
+            ..Format::new(2).unwrap()
+        };
+        let extended = format.to_extended().unwrap();
+        assert_eq!(3, extended.extra_bytes);
+        assert!(extended.is_compressed);
+        let legacy = extended.to_legacy().unwrap();
+        assert_eq!(3, legacy.extra_bytes);
+        assert!(legacy.is_compressed);
+        assert!(legacy.has_gps_time);
+    }
+
+    #[test]
+    fn no_legacy_format_for_nir() {
+        assert!(matches!(
+            Format::new(10).unwrap().to_legacy().unwrap_err(),
+            Error::NoLegacyFormat(_)
+        ));
+    }
+
+    mod smallest_for {
+        use super::*;
+        use crate::{point::Classification, raw::point::Waveform, Color};
+
+        fn smallest(points: &[Point]) -> u8 {
+            Format::smallest_for(points.iter(), false)
+                .unwrap()
+                .to_u8()
+                .unwrap()
+        }
+
+        fn gps() -> Point {
+            Point {
+                gps_time: Some(1.),
+                ..Default::default()
+            }
+        }
+
+        fn color() -> Point {
+            Point {
+                color: Some(Color::new(1, 2, 3)),
+                ..Default::default()
+            }
+        }
+
+        fn waveform() -> Point {
+            Point {
+                waveform: Some(Waveform::default()),
+                ..Default::default()
+            }
+        }
+
+        fn nir() -> Point {
+            Point {
+                nir: Some(1),
+                ..Default::default()
+            }
+        }
+
+        fn many_returns() -> Point {
+            Point {
+                return_number: 6,
+                number_of_returns: 6,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn legacy() {
+            assert_eq!(0, smallest(&[]));
+            assert_eq!(0, smallest(&[Point::default()]));
+            assert_eq!(1, smallest(&[Point::default(), gps()]));
+            assert_eq!(2, smallest(&[color(), Point::default()]));
+            assert_eq!(3, smallest(&[gps(), color()]));
+            assert_eq!(4, smallest(&[waveform()]));
+            assert_eq!(5, smallest(&[waveform(), color()]));
+        }
+
+        #[test]
+        fn extended() {
+            assert_eq!(6, smallest(&[many_returns()]));
+            assert_eq!(6, smallest(&[many_returns(), gps()]));
+            assert_eq!(7, smallest(&[many_returns(), color()]));
+            assert_eq!(8, smallest(&[nir()]));
+            assert_eq!(9, smallest(&[waveform(), many_returns()]));
+            assert_eq!(10, smallest(&[waveform(), color(), many_returns()]));
+            assert_eq!(10, smallest(&[waveform(), nir()]));
+        }
+
+        #[test]
+        fn legacy_limits() {
+            for point in [
+                Point {
+                    scanner_channel: 1,
+                    ..Default::default()
+                },
+                Point {
+                    is_overlap: true,
+                    ..Default::default()
+                },
+                Point {
+                    classification: Classification::new(32).unwrap(),
+                    ..Default::default()
+                },
+                Point {
+                    scan_angle: -120.,
+                    ..Default::default()
+                },
+            ] {
+                assert_eq!(6, smallest(&[point]));
+            }
+            assert_eq!(
+                0,
+                smallest(&[Point {
+                    return_number: 5,
+                    number_of_returns: 5,
+                    classification: Classification::new(31).unwrap(),
+                    scan_angle: 90.,
+                    ..Default::default()
+                }])
+            );
+        }
+
+        #[test]
+        fn require_extended() {
+            let format = Format::smallest_for([].iter(), true).unwrap();
+            assert_eq!(6, format.to_u8().unwrap());
+            let format = Format::smallest_for([color(), waveform()].iter(), true).unwrap();
+            assert_eq!(10, format.to_u8().unwrap());
+        }
+
+        #[test]
+        fn extra_bytes() {
+            let point = Point {
+                extra_bytes: vec![1, 2, 3].into(),
+                ..gps()
+            };
+            let format =
+                Format::smallest_for([point.clone(), point.clone()].iter(), false).unwrap();
+            assert_eq!(3, format.extra_bytes);
+            assert_eq!(31, format.len());
+            match Format::smallest_for([point.clone(), point, gps()].iter(), false).unwrap_err() {
+                Error::InconsistentExtraBytes {
+                    index,
+                    expected,
+                    len,
+                } => {
+                    assert_eq!(2, index);
+                    assert_eq!(3, expected);
+                    assert_eq!(0, len);
+                }
+                err => panic!("unexpected error: {err}"),
+            }
+        }
+    }
+
+    #[test]
+    fn legacy_and_extended_invalid() {
+        let format = Format {
+            has_nir: true,
+            ..Default::default()
+        };
+        assert!(format.to_extended().is_err());
+        assert!(format.to_legacy().is_err());
+    }
 }
@@ -12,16 +12,44 @@
 //! Point coordinates (x, y, and z) are stored as f64, and are the final coordinates after the
 //! scale and offset from the header are applied.
 
+#[cfg(feature = "std")]
+mod buffer;
 mod classification;
+mod classification_set;
+#[cfg(feature = "std")]
+mod descriptor;
+#[cfg(feature = "std")]
+mod dimension;
+mod extra_bytes;
+#[cfg(feature = "std")]
+mod field_mismatch;
 mod format;
 mod scan_direction;
-
-pub use self::{classification::Classification, format::Format, scan_direction::ScanDirection};
-use crate::{raw, raw::point::Waveform, Color, Error, Result, Transform, Vector};
-use thiserror::Error;
+#[cfg(feature = "std")]
+mod schema;
+
+#[cfg(feature = "std")]
+pub use self::{
+    buffer::PointBuffer,
+    descriptor::{ExtraBytesDescriptor, ExtraBytesType},
+    dimension::Dimension,
+    field_mismatch::FieldMismatch,
+    schema::{ExtraAttributeValue, ExtraBytesSchema},
+};
+pub use self::{
+    classification::Classification, classification_set::ClassificationSet, extra_bytes::ExtraBytes,
+    format::Format, scan_direction::ScanDirection,
+};
+use crate::Error;
+#[cfg(feature = "std")]
+use crate::{
+    raw, raw::point::Waveform, Color, GpsTime, GpsTimeType, GpsTimestamp, Result, Transform, Vector,
+};
 
 /// A three dimensional point.
+#[cfg(feature = "std")]
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     /// The x coordinate, as a float.
     pub x: f64,
@@ -66,6 +94,17 @@ pub struct Point {
     /// Is this an overlap point?
     pub is_overlap: bool,
 
+    /// The flag bytes this point was read with, if its other fields can't reproduce them.
+    ///
+    /// Point formats six through ten have an overlap bit, but some files still use the legacy
+    /// overlap class (12) in the classification byte. That class is read as
+    /// [Classification::Unclassified], so the original bytes are kept here. They're written as
+    /// they were as long as none of the point's flag fields have changed; if one has, the flags
+    /// are built from the fields and the legacy overlap class is lost. Every other flag bit has
+    /// a field, so this is `None` for all other points, including every point in formats zero
+    /// through five.
+    pub original_flags: Option<raw::point::Flags>,
+
     /// The channel of the scanner, used only in multi-channel systems.
     pub scanner_channel: u8,
 
@@ -73,14 +112,22 @@ pub struct Point {
     ///
     /// This is supposed to include the roll of the aircraft, if applicable. Zero degrees is nadir,
     /// -90° is to the left.
+    ///
+    /// This is always in degrees, whatever the point format. When written, the angle is rounded
+    /// to whole degrees and saturated to [-90, 90] for point formats zero through five, and
+    /// rounded to 0.006° and saturated to [-180, 180] for point formats six through ten. See
+    /// [ScanAngle](crate::raw::point::ScanAngle) for the stored values.
     pub scan_angle: f32,
 
     /// Used at the user's discretion.
+    ///
+    /// Las 1.0 calls this the file marker.
     pub user_data: u8,
 
     /// The file from which this point originated.
     ///
-    /// This number corresponds to a file source ID.
+    /// This number corresponds to a file source ID. Las 1.0 calls this the user bit field, and
+    /// files of that version often store the flight line number here.
     pub point_source_id: u16,
 
     /// The time at which the point was acquired.
@@ -98,9 +145,10 @@ pub struct Point {
     /// This point's extra bytes.
     ///
     /// These can have structure and meaning, but for now they don't.
-    pub extra_bytes: Vec<u8>,
+    pub extra_bytes: ExtraBytes,
 }
 
+#[cfg(feature = "std")]
 impl Point {
     /// Creates a point from a raw point.
     ///
@@ -112,29 +160,31 @@ impl Point {
     /// let raw_point = raw::Point::default();
     /// let point = Point::new(raw_point, &Default::default());
     /// ```
-    pub fn new(mut raw_point: raw::Point, transforms: &Vector<Transform>) -> Point {
-        let is_overlap = raw_point.flags.is_overlap();
-        raw_point.flags.clear_overlap_class();
-
+    pub fn new(raw_point: raw::Point, transforms: &Vector<Transform>) -> Point {
+        let fields = FlagFields::from(raw_point.flags);
+        let original_flags = match raw_point.flags {
+            raw::point::Flags::ThreeByte(..) if fields.to_flags() != raw_point.flags => {
+                Some(raw_point.flags)
+            }
+            _ => None,
+        };
         Point {
             x: transforms.x.direct(raw_point.x),
             y: transforms.y.direct(raw_point.y),
             z: transforms.z.direct(raw_point.z),
             intensity: raw_point.intensity,
-            return_number: raw_point.flags.return_number(),
-            number_of_returns: raw_point.flags.number_of_returns(),
-            scan_direction: raw_point.flags.scan_direction(),
-            is_edge_of_flight_line: raw_point.flags.is_edge_of_flight_line(),
-            classification: raw_point
-                .flags
-                .to_classification()
-                .expect("Overlap classification should have been cleared"),
-            is_synthetic: raw_point.flags.is_synthetic(),
-            is_key_point: raw_point.flags.is_key_point(),
-            is_withheld: raw_point.flags.is_withheld(),
-            is_overlap,
+            return_number: fields.return_number,
+            number_of_returns: fields.number_of_returns,
+            scan_direction: fields.scan_direction,
+            is_edge_of_flight_line: fields.is_edge_of_flight_line,
+            classification: fields.classification,
+            is_synthetic: fields.is_synthetic,
+            is_key_point: fields.is_key_point,
+            is_withheld: fields.is_withheld,
+            is_overlap: fields.is_overlap,
+            original_flags,
             scan_angle: raw_point.scan_angle.into(),
-            scanner_channel: raw_point.flags.scanner_channel(),
+            scanner_channel: fields.scanner_channel,
             user_data: raw_point.user_data,
             point_source_id: raw_point.point_source_id,
             gps_time: raw_point.gps_time,
@@ -154,10 +204,30 @@ impl Point {
     /// let raw_point = point.into_raw(&Default::default()).unwrap();
     /// ```
     pub fn into_raw(self, transforms: &Vector<Transform>) -> Result<raw::Point> {
-        Ok(raw::Point {
+        let mut raw_point = self.to_raw_without_extra_bytes(transforms)?;
+        raw_point.extra_bytes = self.extra_bytes;
+        Ok(raw_point)
+    }
+
+    /// Creates a raw las point from this point, leaving the extra bytes empty.
+    pub(crate) fn to_raw_without_extra_bytes(
+        &self,
+        transforms: &Vector<Transform>,
+    ) -> Result<raw::Point> {
+        self.to_raw_at(Vector {
             x: transforms.x.inverse(self.x)?,
             y: transforms.y.inverse(self.y)?,
             z: transforms.z.inverse(self.z)?,
+        })
+    }
+
+    /// Creates a raw las point from this point with already transformed coordinates, leaving the
+    /// extra bytes empty.
+    pub(crate) fn to_raw_at(&self, xyz: Vector<i32>) -> Result<raw::Point> {
+        Ok(raw::Point {
+            x: xyz.x,
+            y: xyz.y,
+            z: xyz.z,
             intensity: self.intensity,
             flags: self.flags()?,
             scan_angle: self.scan_angle.into(),
@@ -167,12 +237,14 @@ impl Point {
             color: self.color,
             waveform: self.waveform,
             nir: self.nir,
-            extra_bytes: self.extra_bytes,
+            extra_bytes: ExtraBytes::new(),
         })
     }
 
     /// Creates the flags bytes for use in a raw point.
     ///
+    /// Returns [Point::original_flags] if they still match the point's fields.
+    ///
     /// # Examples
     ///
     /// ```
@@ -181,6 +253,12 @@ impl Point {
     /// assert_eq!((1, 0, 0), point.flags().unwrap().into());
     /// ```
     pub fn flags(&self) -> Result<raw::point::Flags> {
+        let fields = FlagFields::from(self);
+        if let Some(original_flags) = self.original_flags
+            && FlagFields::from(original_flags) == fields
+        {
+            return Ok(original_flags);
+        }
         if self.return_number > 15 {
             Err(Error::ReturnNumber {
                 return_number: self.return_number,
@@ -194,31 +272,7 @@ impl Point {
         } else if self.scanner_channel > 3 {
             Err(Error::InvalidScannerChannel(self.scanner_channel))
         } else {
-            let a = (self.number_of_returns << 4) + self.return_number;
-            let mut b = self.scanner_channel << 4;
-            if self.is_synthetic {
-                b += 1;
-            }
-            if self.is_key_point {
-                b += 2;
-            }
-            if self.is_withheld {
-                b += 4;
-            }
-            if self.is_overlap {
-                b += 8;
-            }
-            if self.scan_direction == ScanDirection::LeftToRight {
-                b += 64;
-            }
-            if self.is_edge_of_flight_line {
-                b += 128;
-            }
-            Ok(raw::point::Flags::ThreeByte(
-                a,
-                b,
-                self.classification.into(),
-            ))
+            Ok(fields.to_flags())
         }
     }
 
@@ -243,17 +297,467 @@ impl Point {
     /// assert!(point.matches(&format));
     /// ```
     pub fn matches(&self, format: &Format) -> bool {
-        self.gps_time.is_some() == format.has_gps_time
-            && self.color.is_some() == format.has_color
-            && self.waveform.is_some() == format.has_waveform
-            && self.nir.is_some() == format.has_nir
-            && self.extra_bytes.len() == format.extra_bytes as usize
+        self.mismatches(format).is_empty()
     }
+
+    /// Returns every way that this point doesn't match the point format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{FieldMismatch, Format};
+    /// use las::Point;
+    ///
+    /// let format = Format::new(1).unwrap();
+    /// let point = Point { color: Some(Default::default()), ..Default::default() };
+    /// assert_eq!(
+    ///     vec![FieldMismatch::Missing("gps_time"), FieldMismatch::Unexpected("color")],
+    ///     point.mismatches(&format)
+    /// );
+    /// ```
+    pub fn mismatches(&self, format: &Format) -> Vec<FieldMismatch> {
+        let mut mismatches = Vec::new();
+        for (field, has_field, expected) in [
+            ("gps_time", self.gps_time.is_some(), format.has_gps_time),
+            ("color", self.color.is_some(), format.has_color),
+            ("waveform", self.waveform.is_some(), format.has_waveform),
+            ("nir", self.nir.is_some(), format.has_nir),
+        ] {
+            match (has_field, expected) {
+                (false, true) => mismatches.push(FieldMismatch::Missing(field)),
+                (true, false) => mismatches.push(FieldMismatch::Unexpected(field)),
+                _ => {}
+            }
+        }
+        if self.extra_bytes.len() != usize::from(format.extra_bytes) {
+            mismatches.push(FieldMismatch::ExtraBytes {
+                expected: format.extra_bytes,
+                actual: self.extra_bytes.len(),
+            });
+        }
+        mismatches
+    }
+
+    /// Returns this point's gps time as a [GpsTime], if it has one.
+    ///
+    /// Returns an error if the gps time is NaN or infinite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Point;
+    /// let mut point = Point::default();
+    /// assert!(point.typed_gps_time().unwrap().is_none());
+    /// point.gps_time = Some(42.);
+    /// assert_eq!(42., f64::from(point.typed_gps_time().unwrap().unwrap()));
+    /// point.gps_time = Some(f64::NAN);
+    /// assert!(point.typed_gps_time().is_err());
+    /// ```
+    pub fn typed_gps_time(&self) -> Result<Option<GpsTime>> {
+        self.gps_time.map(GpsTime::new).transpose()
+    }
+
+    /// Returns this point's gps time as a [GpsTimestamp], if it has one.
+    ///
+    /// The gps time type is set for the whole file, see [Header::gps_time_type](crate::Header::gps_time_type).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTimeType, Point};
+    /// let point = Point { gps_time: Some(42.), ..Default::default() };
+    /// let timestamp = point.gps_timestamp(GpsTimeType::Week).unwrap().unwrap();
+    /// assert_eq!(GpsTimeType::Week, timestamp.gps_time_type());
+    /// ```
+    pub fn gps_timestamp(&self, gps_time_type: GpsTimeType) -> Result<Option<GpsTimestamp>> {
+        Ok(self
+            .typed_gps_time()?
+            .map(|gps_time| GpsTimestamp::new(gps_time, gps_time_type)))
+    }
+
+    /// Sets this point's gps time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{GpsTime, Point};
+    /// let mut point = Point::default();
+    /// point.set_gps_time(Some(GpsTime::new(42.).unwrap()));
+    /// assert_eq!(Some(42.), point.gps_time);
+    /// ```
+    pub fn set_gps_time(&mut self, gps_time: Option<GpsTime>) {
+        self.gps_time = gps_time.map(f64::from);
+    }
+}
+
+/// The fields of a point that are stored in its flag bytes.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FlagFields {
+    return_number: u8,
+    number_of_returns: u8,
+    scan_direction: ScanDirection,
+    is_edge_of_flight_line: bool,
+    classification: Classification,
+    is_synthetic: bool,
+    is_key_point: bool,
+    is_withheld: bool,
+    is_overlap: bool,
+    scanner_channel: u8,
 }
 
-#[cfg(test)]
+#[cfg(feature = "std")]
+impl FlagFields {
+    /// Packs these fields into three byte flags, without checking their ranges.
+    fn to_flags(self) -> raw::point::Flags {
+        let a = (self.number_of_returns << 4) + self.return_number;
+        let mut b = self.scanner_channel << 4;
+        if self.is_synthetic {
+            b += 1;
+        }
+        if self.is_key_point {
+            b += 2;
+        }
+        if self.is_withheld {
+            b += 4;
+        }
+        if self.is_overlap {
+            b += 8;
+        }
+        if self.scan_direction == ScanDirection::LeftToRight {
+            b += 64;
+        }
+        if self.is_edge_of_flight_line {
+            b += 128;
+        }
+        raw::point::Flags::ThreeByte(a, b, self.classification.into())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<raw::point::Flags> for FlagFields {
+    fn from(mut flags: raw::point::Flags) -> FlagFields {
+        let is_overlap = flags.is_overlap();
+        flags.clear_overlap_class();
+        FlagFields {
+            return_number: flags.return_number(),
+            number_of_returns: flags.number_of_returns(),
+            scan_direction: flags.scan_direction(),
+            is_edge_of_flight_line: flags.is_edge_of_flight_line(),
+            classification: flags
+                .to_classification()
+                .expect("Overlap classification should have been cleared"),
+            is_synthetic: flags.is_synthetic(),
+            is_key_point: flags.is_key_point(),
+            is_withheld: flags.is_withheld(),
+            is_overlap,
+            scanner_channel: flags.scanner_channel(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<&Point> for FlagFields {
+    fn from(point: &Point) -> FlagFields {
+        FlagFields {
+            return_number: point.return_number,
+            number_of_returns: point.number_of_returns,
+            scan_direction: point.scan_direction,
+            is_edge_of_flight_line: point.is_edge_of_flight_line,
+            classification: point.classification,
+            is_synthetic: point.is_synthetic,
+            is_key_point: point.is_key_point,
+            is_withheld: point.is_withheld,
+            is_overlap: point.is_overlap,
+            scanner_channel: point.scanner_channel,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
+    use crate::raw::point::OVERLAP_CLASSIFICATION_CODE;
+
+    fn roundtrip_scan_angle(format: u8, scan_angle: f32) -> f32 {
+        let format = Format::new(format).unwrap();
+        let point = Point {
+            scan_angle,
+            gps_time: format.has_gps_time.then_some(0.),
+            ..Default::default()
+        };
+        let mut bytes = Vec::new();
+        point
+            .into_raw(&Default::default())
+            .unwrap()
+            .write_to(&mut bytes, &format)
+            .unwrap();
+        let raw_point = raw::Point::read_from(&bytes[..], &format).unwrap();
+        Point::new(raw_point, &Default::default()).scan_angle
+    }
+
+    fn roundtrip_record(format: &Format, record: &[u8]) -> Vec<u8> {
+        let raw_point = raw::Point::read_from(record, format).unwrap();
+        let point = Point::new(raw_point, &Default::default());
+        let mut bytes = Vec::new();
+        point
+            .into_raw(&Default::default())
+            .unwrap()
+            .write_to(&mut bytes, format)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let point = Point {
+            x: 1.,
+            classification: Classification::Ground,
+            gps_time: Some(2.),
+            color: Some(Color::new(3, 4, 5)),
+            extra_bytes: vec![6, 7].into(),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&point).unwrap();
+        assert_eq!(point, serde_json::from_str(&json).unwrap());
+    }
+    #[test]
+    fn flag_bytes_roundtrip_legacy() {
+        let format = Format::new(0).unwrap();
+        let mut record = vec![0; usize::from(format.len())];
+        for a in 0..=u8::MAX {
+            for b in 0..=u8::MAX {
+                record[14] = a;
+                record[15] = b;
+                record[17] = a ^ b;
+                assert_eq!(
+                    record,
+                    roundtrip_record(&format, &record),
+                    "{a:#010b} {b:#010b}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn flag_bytes_roundtrip_extended() {
+        let format = Format::new(6).unwrap();
+        let mut record = vec![0; usize::from(format.len())];
+        for b in 0..=u8::MAX {
+            for c in 0..=u8::MAX {
+                for a in [0, 0b0001_0001, b ^ c, 0xFF] {
+                    record[14] = a;
+                    record[15] = b;
+                    record[16] = c;
+                    record[17] = b ^ c;
+                    assert_eq!(
+                        record,
+                        roundtrip_record(&format, &record),
+                        "{a:#010b} {b:#010b} {c:#010b}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn original_flags() {
+        let format = Format::new(6).unwrap();
+        let mut record = vec![0; usize::from(format.len())];
+        record[16] = OVERLAP_CLASSIFICATION_CODE;
+        let raw_point = raw::Point::read_from(&record[..], &format).unwrap();
+        let mut point = Point::new(raw_point, &Default::default());
+        assert_eq!(Classification::Unclassified, point.classification);
+        assert!(!point.is_overlap);
+        assert_eq!(
+            Some(raw::point::Flags::ThreeByte(
+                0,
+                0,
+                OVERLAP_CLASSIFICATION_CODE
+            )),
+            point.original_flags
+        );
+        point.is_synthetic = true;
+        assert_eq!(
+            raw::point::Flags::ThreeByte(0, 1, 1),
+            point.flags().unwrap()
+        );
+
+        record[16] = 2;
+        let raw_point = raw::Point::read_from(&record[..], &format).unwrap();
+        assert_eq!(
+            None,
+            Point::new(raw_point, &Default::default()).original_flags
+        );
+    }
+
+    #[test]
+    fn scan_angle_legacy() {
+        for (scan_angle, expected) in [
+            (0., 0.),
+            (90., 90.),
+            (-90., -90.),
+            (45.4, 45.),
+            (-45.6, -46.),
+            (91., 90.),
+            (-1000., -90.),
+        ] {
+            assert_eq!(expected, roundtrip_scan_angle(1, scan_angle));
+        }
+    }
+
+    #[test]
+    fn scan_angle_extended() {
+        for (scan_angle, expected) in [
+            (0., 0.),
+            (0.006, 0.006),
+            (90., 90.),
+            (-90., -90.),
+            (180., 180.),
+            (-180., -180.),
+            (180.1, 180.),
+            (-1000., -180.),
+        ] {
+            assert!((expected - roundtrip_scan_angle(6, scan_angle)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn scan_angle_same_degrees() {
+        for scan_angle in [-90., -12., 0., 33., 90.] {
+            assert_eq!(
+                roundtrip_scan_angle(1, scan_angle),
+                roundtrip_scan_angle(6, scan_angle).round()
+            );
+        }
+    }
+
+    #[test]
+    fn mismatches_missing() {
+        let point = Point::default();
+        for (format, field) in [
+            (
+                Format {
+                    has_gps_time: true,
+                    ..Default::default()
+                },
+                "gps_time",
+            ),
+            (
+                Format {
+                    has_color: true,
+                    ..Default::default()
+                },
+                "color",
+            ),
+            (
+                Format {
+                    has_waveform: true,
+                    ..Default::default()
+                },
+                "waveform",
+            ),
+            (
+                Format {
+                    has_nir: true,
+                    ..Default::default()
+                },
+                "nir",
+            ),
+        ] {
+            assert_eq!(
+                vec![FieldMismatch::Missing(field)],
+                point.mismatches(&format)
+            );
+        }
+    }
+
+    #[test]
+    fn mismatches_unexpected() {
+        let format = Format::default();
+        for (point, field) in [
+            (
+                Point {
+                    gps_time: Some(0.),
+                    ..Default::default()
+                },
+                "gps_time",
+            ),
+            (
+                Point {
+                    color: Some(Default::default()),
+                    ..Default::default()
+                },
+                "color",
+            ),
+            (
+                Point {
+                    waveform: Some(Default::default()),
+                    ..Default::default()
+                },
+                "waveform",
+            ),
+            (
+                Point {
+                    nir: Some(0),
+                    ..Default::default()
+                },
+                "nir",
+            ),
+        ] {
+            assert_eq!(
+                vec![FieldMismatch::Unexpected(field)],
+                point.mismatches(&format)
+            );
+        }
+    }
+
+    #[test]
+    fn mismatches_extra_bytes() {
+        let format = Format {
+            extra_bytes: 4,
+            ..Default::default()
+        };
+        let point = Point {
+            extra_bytes: vec![0; 2].into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![FieldMismatch::ExtraBytes {
+                expected: 4,
+                actual: 2
+            }],
+            point.mismatches(&format)
+        );
+    }
+
+    #[test]
+    fn mismatches_all() {
+        let format = Format {
+            extra_bytes: 1,
+            ..Format::new(3).unwrap()
+        };
+        let point = Point {
+            nir: Some(0),
+            waveform: Some(Default::default()),
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![
+                FieldMismatch::Missing("gps_time"),
+                FieldMismatch::Missing("color"),
+                FieldMismatch::Unexpected("waveform"),
+                FieldMismatch::Unexpected("nir"),
+                FieldMismatch::ExtraBytes {
+                    expected: 1,
+                    actual: 0
+                },
+            ],
+            point.mismatches(&format)
+        );
+        assert!(!point.matches(&format));
+    }
 
     #[test]
     fn flags_invalid_return_number() {
@@ -0,0 +1,408 @@
+use super::{ExtraBytesDescriptor, ExtraBytesType, Point};
+use crate::{Error, Header, Result};
+
+/// The attributes in a point's extra bytes, as the extra bytes vlr describes them.
+///
+/// Each attribute starts where the one before it ends, so the schema knows where in the extra
+/// bytes every attribute lives. Use [Point::extra_attribute] and [Point::set_extra_attribute] to
+/// work with the values.
+///
+/// # Examples
+///
+/// ```
+/// use las::point::{ExtraBytesDescriptor, ExtraBytesSchema, ExtraBytesType};
+///
+/// let schema = ExtraBytesSchema::new(vec![
+///     ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32),
+///     ExtraBytesDescriptor::new("Deviation", ExtraBytesType::U16),
+/// ]);
+/// assert_eq!(6, schema.len());
+/// assert_eq!(4, schema.get("Deviation").unwrap().0);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtraBytesSchema {
+    attributes: Vec<(usize, ExtraBytesDescriptor)>,
+}
+
+/// A number that an extra bytes attribute can be read as, or written from.
+///
+/// Values are decoded as floats, with the scale and offset applied, and then converted. Integers
+/// only take values that they hold exactly.
+pub trait ExtraAttributeValue: Copy {
+    /// Converts a decoded value, returning `None` if this type can't hold it exactly.
+    fn from_f64(value: f64) -> Option<Self>;
+
+    /// Converts to a value to encode.
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! integer_value {
+    ($($t:ty),*) => {
+        $(
+            impl ExtraAttributeValue for $t {
+                fn from_f64(value: f64) -> Option<$t> {
+                    (value.fract() == 0. && value >= <$t>::MIN as f64 && value <= <$t>::MAX as f64)
+                        .then_some(value as $t)
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+integer_value!(u8, i8, u16, i16, u32, i32, u64, i64);
+
+impl ExtraAttributeValue for f32 {
+    fn from_f64(value: f64) -> Option<f32> {
+        Some(value as f32)
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl ExtraAttributeValue for f64 {
+    fn from_f64(value: f64) -> Option<f64> {
+        Some(value)
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl ExtraBytesSchema {
+    /// Creates a schema from descriptors, in the order their attributes are in the extra bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::ExtraBytesSchema;
+    /// assert!(ExtraBytesSchema::new(Vec::new()).is_empty());
+    /// ```
+    pub fn new(descriptors: Vec<ExtraBytesDescriptor>) -> ExtraBytesSchema {
+        let mut start = 0;
+        let attributes = descriptors
+            .into_iter()
+            .map(|descriptor| {
+                let attribute = (start, descriptor);
+                start += attribute.1.len();
+                attribute
+            })
+            .collect();
+        ExtraBytesSchema { attributes }
+    }
+
+    /// Returns where an attribute starts in the extra bytes, and its descriptor.
+    ///
+    /// If more than one attribute has the name, the first one is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{ExtraBytesDescriptor, ExtraBytesSchema, ExtraBytesType};
+    /// let schema = ExtraBytesSchema::new(vec![ExtraBytesDescriptor::new("a", ExtraBytesType::U8)]);
+    /// assert_eq!(0, schema.get("a").unwrap().0);
+    /// assert!(schema.get("b").is_none());
+    /// ```
+    pub fn get(&self, name: &str) -> Option<(usize, &ExtraBytesDescriptor)> {
+        self.attributes
+            .iter()
+            .find(|(_, descriptor)| descriptor.name == name)
+            .map(|(start, descriptor)| (*start, descriptor))
+    }
+
+    /// Returns the attributes' descriptors, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{ExtraBytesDescriptor, ExtraBytesSchema, ExtraBytesType};
+    /// let schema = ExtraBytesSchema::new(vec![ExtraBytesDescriptor::new("a", ExtraBytesType::U8)]);
+    /// assert_eq!(1, schema.descriptors().count());
+    /// ```
+    pub fn descriptors(&self) -> impl Iterator<Item = &ExtraBytesDescriptor> {
+        self.attributes.iter().map(|(_, descriptor)| descriptor)
+    }
+
+    /// Returns where each attribute starts in the extra bytes, and its descriptor, in order.
+    pub(crate) fn attributes(&self) -> impl Iterator<Item = (usize, &ExtraBytesDescriptor)> {
+        self.attributes
+            .iter()
+            .map(|(start, descriptor)| (*start, descriptor))
+    }
+
+    /// Returns the number of extra bytes that the attributes take up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{ExtraBytesDescriptor, ExtraBytesSchema, ExtraBytesType};
+    /// let schema = ExtraBytesSchema::new(vec![ExtraBytesDescriptor::new("a", ExtraBytesType::F64)]);
+    /// assert_eq!(8, schema.len());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.attributes
+            .last()
+            .map_or(0, |(start, descriptor)| start + descriptor.len())
+    }
+
+    /// Returns the first named attribute that doesn't fit in these extra bytes.
+    pub(crate) fn missing(&self, extra_bytes: &[u8]) -> Option<&ExtraBytesDescriptor> {
+        self.attributes
+            .iter()
+            .find(|(start, descriptor)| {
+                !matches!(descriptor.data_type, ExtraBytesType::Undocumented(_))
+                    && start + descriptor.len() > extra_bytes.len()
+            })
+            .map(|(_, descriptor)| descriptor)
+    }
+
+    /// Returns true if there are no attributes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::ExtraBytesSchema;
+    /// assert!(ExtraBytesSchema::default().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+}
+
+impl Header {
+    /// Returns the schema of the points' extra bytes, from the extra bytes vlr or evlr.
+    ///
+    /// The schema is empty if there's no extra bytes vlr. Returns an error if the vlr is invalid,
+    /// or describes more bytes than the points have.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert!(reader.header().extra_bytes_schema().unwrap().is_empty());
+    /// ```
+    pub fn extra_bytes_schema(&self) -> Result<ExtraBytesSchema> {
+        let descriptors = self
+            .vlrs()
+            .iter()
+            .chain(self.evlrs())
+            .find_map(ExtraBytesDescriptor::from_vlr)
+            .transpose()?
+            .unwrap_or_default();
+        let schema = ExtraBytesSchema::new(descriptors);
+        let extra_bytes = usize::from(self.point_format().extra_bytes);
+        if schema.len() > extra_bytes {
+            return Err(Error::InvalidExtraBytesVlr(format!(
+                "the descriptors take up {} bytes, but the points only have {extra_bytes}",
+                schema.len()
+            )));
+        }
+        Ok(schema)
+    }
+}
+
+impl Point {
+    /// Decodes an extra bytes attribute, with its scale and offset applied.
+    ///
+    /// Returns `None` if the point has the attribute's no data value. Returns
+    /// [Error::ExtraAttributeNotFound] if the schema has no attribute with the name, or the
+    /// point's extra bytes are too short to hold it, and
+    /// [Error::ExtraAttributeNotRepresentable] if the type can't hold the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{
+    ///     point::{ExtraBytesDescriptor, ExtraBytesSchema, ExtraBytesType},
+    ///     Point,
+    /// };
+    ///
+    /// let mut descriptor = ExtraBytesDescriptor::new("HeightAboveGround", ExtraBytesType::I32);
+    /// descriptor.scale = Some(0.01);
+    /// let schema = ExtraBytesSchema::new(vec![descriptor]);
+    /// let point = Point { extra_bytes: 1234i32.to_le_bytes().to_vec().into(), ..Default::default() };
+    /// assert_eq!(Some(12.34), point.extra_attribute::<f64>(&schema, "HeightAboveGround").unwrap());
+    /// assert!(point.extra_attribute::<i32>(&schema, "HeightAboveGround").is_err());
+    /// ```
+    pub fn extra_attribute<T: ExtraAttributeValue>(
+        &self,
+        schema: &ExtraBytesSchema,
+        name: &str,
+    ) -> Result<Option<T>> {
+        let (start, descriptor) = schema
+            .get(name)
+            .filter(|(start, descriptor)| start + descriptor.len() <= self.extra_bytes.len())
+            .ok_or_else(|| Error::ExtraAttributeNotFound(name.to_string()))?;
+        descriptor
+            .decode(&self.extra_bytes[start..])
+            .map(|value| {
+                T::from_f64(value).ok_or_else(|| Error::ExtraAttributeNotRepresentable {
+                    name: name.to_string(),
+                    value,
+                })
+            })
+            .transpose()
+    }
+
+    /// Encodes an extra bytes attribute, removing its scale and offset.
+    ///
+    /// If the point's extra bytes are shorter than the schema, they're padded with zeros first.
+    /// Returns [Error::ExtraAttributeNotFound] if the schema has no attribute with the name, and
+    /// [Error::ExtraAttributeNotRepresentable] if the attribute's type can't store the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{
+    ///     point::{ExtraBytesDescriptor, ExtraBytesSchema, ExtraBytesType},
+    ///     Point,
+    /// };
+    ///
+    /// let schema = ExtraBytesSchema::new(vec![
+    ///     ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32),
+    ///     ExtraBytesDescriptor::new("Deviation", ExtraBytesType::U8),
+    /// ]);
+    /// let mut point = Point::default();
+    /// point.set_extra_attribute(&schema, "Deviation", 7u8).unwrap();
+    /// assert_eq!([0, 0, 0, 0, 7], point.extra_bytes[..]);
+    /// assert!(point.set_extra_attribute(&schema, "Deviation", 300).is_err());
+    /// ```
+    pub fn set_extra_attribute<T: ExtraAttributeValue>(
+        &mut self,
+        schema: &ExtraBytesSchema,
+        name: &str,
+        value: T,
+    ) -> Result<()> {
+        let (start, descriptor) = schema
+            .get(name)
+            .ok_or_else(|| Error::ExtraAttributeNotFound(name.to_string()))?;
+        if self.extra_bytes.len() < schema.len() {
+            let mut extra_bytes = super::ExtraBytes::zeroed(schema.len());
+            extra_bytes[..self.extra_bytes.len()].copy_from_slice(&self.extra_bytes);
+            self.extra_bytes = extra_bytes;
+        }
+        let value = value.to_f64();
+        if descriptor.encode(value, &mut self.extra_bytes[start..]) {
+            Ok(())
+        } else {
+            Err(Error::ExtraAttributeNotRepresentable {
+                name: name.to_string(),
+                value,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Builder;
+
+    fn schema() -> ExtraBytesSchema {
+        let mut height = ExtraBytesDescriptor::new("HeightAboveGround", ExtraBytesType::I32);
+        height.scale = Some(0.01);
+        height.offset = Some(100.);
+        height.no_data = Some(f64::from(i32::MIN));
+        ExtraBytesSchema::new(vec![
+            ExtraBytesDescriptor::new("", ExtraBytesType::Undocumented(2)),
+            ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32),
+            height,
+        ])
+    }
+
+    #[test]
+    fn roundtrip() {
+        let schema = schema();
+        let mut point = Point::default();
+        point
+            .set_extra_attribute(&schema, "Amplitude", 1.5f32)
+            .unwrap();
+        point
+            .set_extra_attribute(&schema, "HeightAboveGround", 112.5)
+            .unwrap();
+        assert_eq!(10, point.extra_bytes.len());
+        assert_eq!(
+            Some(1.5),
+            point.extra_attribute::<f32>(&schema, "Amplitude").unwrap()
+        );
+        assert_eq!(
+            Some(112.5),
+            point
+                .extra_attribute::<f64>(&schema, "HeightAboveGround")
+                .unwrap()
+        );
+        assert_eq!(
+            1250,
+            i32::from_le_bytes(point.extra_bytes[6..].try_into().unwrap())
+        );
+    }
+
+    #[test]
+    fn no_data() {
+        let schema = schema();
+        let mut point = Point {
+            extra_bytes: vec![0; 10].into(),
+            ..Default::default()
+        };
+        point.extra_bytes[6..].copy_from_slice(&i32::MIN.to_le_bytes());
+        assert_eq!(
+            None,
+            point
+                .extra_attribute::<f64>(&schema, "HeightAboveGround")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let schema = schema();
+        let mut point = Point::default();
+        assert!(matches!(
+            point.extra_attribute::<f32>(&schema, "Amplitude"),
+            Err(Error::ExtraAttributeNotFound(_))
+        ));
+        assert!(matches!(
+            point.set_extra_attribute(&schema, "Intensity", 1),
+            Err(Error::ExtraAttributeNotFound(_))
+        ));
+        assert!(matches!(
+            point.set_extra_attribute(&schema, "HeightAboveGround", 1e12),
+            Err(Error::ExtraAttributeNotRepresentable { .. })
+        ));
+        point
+            .set_extra_attribute(&schema, "HeightAboveGround", 100.25)
+            .unwrap();
+        assert!(matches!(
+            point.extra_attribute::<i64>(&schema, "HeightAboveGround"),
+            Err(Error::ExtraAttributeNotRepresentable { .. })
+        ));
+    }
+
+    #[test]
+    fn header() {
+        let mut builder = Builder::from((1, 4));
+        builder.extra_bytes(6);
+        builder.vlrs.push(
+            ExtraBytesDescriptor::to_vlr(&schema().descriptors().cloned().collect::<Vec<_>>())
+                .unwrap(),
+        );
+        assert!(builder
+            .clone()
+            .into_header()
+            .unwrap()
+            .extra_bytes_schema()
+            .is_err());
+        builder.extra_bytes(10);
+        assert_eq!(
+            schema(),
+            builder.into_header().unwrap().extra_bytes_schema().unwrap()
+        );
+    }
+}
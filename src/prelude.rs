@@ -0,0 +1,8 @@
+//! The parts of std's prelude that come from `alloc`, for `no_std` builds.
+
+pub(crate) use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
@@ -0,0 +1,97 @@
+//! Progress reports from readers and writers.
+
+/// How far a [Reader](crate::Reader) or [Writer](crate::Writer) has got, as passed to its
+/// progress callback.
+///
+/// # Examples
+///
+/// ```
+/// use las::Reader;
+/// use std::sync::{Arc, Mutex};
+///
+/// let events = Arc::new(Mutex::new(Vec::new()));
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let sink = Arc::clone(&events);
+/// reader.on_progress(50, move |event| sink.lock().unwrap().push(event));
+/// let _ = reader.points().count();
+///
+/// let events = events.lock().unwrap();
+/// assert_eq!(vec![50, 100, 106], events.iter().map(|event| event.points).collect::<Vec<_>>());
+/// assert_eq!(Some(106), events[0].total_points);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProgressEvent {
+    /// The number of points read or written so far.
+    ///
+    /// For a reader, this is the index of the next point, so withheld points that are left out
+    /// still count.
+    pub points: u64,
+
+    /// The number of points in the file, if it's known.
+    ///
+    /// Readers know this from the header. Writers don't know how many points will be written, so
+    /// it's `None`.
+    pub total_points: Option<u64>,
+
+    /// The number of bytes of point records read or written so far, if it's known.
+    ///
+    /// This is `None` for compressed points, whose size isn't known until they're compressed.
+    pub bytes: Option<u64>,
+}
+
+/// Calls a progress callback every so many points.
+pub(crate) struct Progress {
+    callback: Box<dyn FnMut(ProgressEvent) + Send>,
+    every: u64,
+    last: u64,
+}
+
+impl Progress {
+    /// Creates a progress reporter that calls `callback` every `every` points, where zero is the
+    /// same as one.
+    pub(crate) fn new(
+        every: u64,
+        callback: impl FnMut(ProgressEvent) + Send + 'static,
+    ) -> Progress {
+        Progress {
+            callback: Box::new(callback),
+            every: every.max(1),
+            last: 0,
+        }
+    }
+
+    /// Calls the callback if the points have crossed a multiple of `every`, or reached the total.
+    pub(crate) fn update(&mut self, points: u64, total_points: Option<u64>, bytes: Option<u64>) {
+        if points == self.last {
+            return;
+        }
+        let crossed = points / self.every != self.last / self.every;
+        self.last = points;
+        if crossed || total_points == Some(points) {
+            (self.callback)(ProgressEvent {
+                points,
+                total_points,
+                bytes,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn update() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let mut progress = Progress::new(10, move |event: ProgressEvent| {
+            sink.lock().unwrap().push(event.points)
+        });
+        for points in [1, 9, 10, 10, 15, 25, 26, 3, 12, 27] {
+            progress.update(points, Some(27), None);
+        }
+        assert_eq!(vec![10, 25, 3, 12, 27], *events.lock().unwrap());
+    }
+}
@@ -0,0 +1,143 @@
+//! Little-endian decoding and encoding of byte slices.
+//!
+//! The `from_bytes` and `to_bytes` functions of the raw structures are built on these, and so are
+//! their `read_from` and `write_to` functions, which read into and write out of a buffer.
+
+/// Takes values off of the front of a byte slice.
+///
+/// Callers check the slice's length up front, so running out of bytes is a bug.
+#[derive(Debug)]
+pub(crate) struct Decoder<'a>(&'a [u8]);
+
+/// Puts values onto the front of a byte slice.
+///
+/// Callers size the slice up front, so running out of room is a bug.
+#[derive(Debug)]
+pub(crate) struct Encoder<'a>(&'a mut [u8]);
+
+impl<'a> Decoder<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Decoder<'a> {
+        Decoder(bytes)
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> &'a [u8] {
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        bytes
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> [u8; N] {
+        self.bytes(N).try_into().expect("the slice has N bytes")
+    }
+
+    pub(crate) fn u8(&mut self) -> u8 {
+        self.array::<1>()[0]
+    }
+
+    pub(crate) fn i8(&mut self) -> i8 {
+        i8::from_le_bytes(self.array())
+    }
+
+    pub(crate) fn u16(&mut self) -> u16 {
+        u16::from_le_bytes(self.array())
+    }
+
+    pub(crate) fn i16(&mut self) -> i16 {
+        i16::from_le_bytes(self.array())
+    }
+
+    pub(crate) fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.array())
+    }
+
+    pub(crate) fn i32(&mut self) -> i32 {
+        i32::from_le_bytes(self.array())
+    }
+
+    pub(crate) fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.array())
+    }
+
+    pub(crate) fn f32(&mut self) -> f32 {
+        f32::from_le_bytes(self.array())
+    }
+
+    pub(crate) fn f64(&mut self) -> f64 {
+        f64::from_le_bytes(self.array())
+    }
+}
+
+impl<'a> Encoder<'a> {
+    pub(crate) fn new(bytes: &'a mut [u8]) -> Encoder<'a> {
+        Encoder(bytes)
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        let (head, rest) = core::mem::take(&mut self.0).split_at_mut(bytes.len());
+        head.copy_from_slice(bytes);
+        self.0 = rest;
+    }
+
+    pub(crate) fn u8(&mut self, n: u8) {
+        self.bytes(&[n]);
+    }
+
+    pub(crate) fn i8(&mut self, n: i8) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn u16(&mut self, n: u16) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn i16(&mut self, n: i16) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn u32(&mut self, n: u32) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn i32(&mut self, n: i32) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn u64(&mut self, n: u64) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn f32(&mut self, n: f32) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    pub(crate) fn f64(&mut self, n: f64) {
+        self.bytes(&n.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut bytes = [0; 15];
+        let mut encoder = Encoder::new(&mut bytes);
+        encoder.u8(1);
+        encoder.i16(-2);
+        encoder.u32(3);
+        encoder.f64(4.5);
+        let mut decoder = Decoder::new(&bytes);
+        assert_eq!(1, decoder.u8());
+        assert_eq!(-2, decoder.i16());
+        assert_eq!(3, decoder.u32());
+        assert_eq!(4.5, decoder.f64());
+        assert!(decoder.bytes(0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn decoder_too_short() {
+        let _ = Decoder::new(&[0; 3]).u32();
+    }
+}
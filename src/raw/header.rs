@@ -1,11 +1,16 @@
 //! Raw file metadata.
 
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::{
     feature::{Evlrs, LargeFiles, Waveforms},
-    raw::LASF,
-    utils, Error, Result, Version,
+    raw::{
+        bytes::{Decoder, Encoder},
+        LASF,
+    },
+    utils, Bounds, Error, Result, Transform, Vector, Version,
 };
-use byteorder::{LittleEndian, ReadBytesExt};
+#[cfg(feature = "std")]
 use std::io::{Read, Write};
 
 /// A las header.
@@ -241,7 +246,8 @@ impl Header {
     ///
     /// Generally very permissive, but will throw an error if a couple of things are true:
     ///
-    /// - The file signature is not exactly "LASF".
+    /// - The file signature is not exactly "LASF". Gzip and zip archives get their own errors,
+    ///   [Error::GzippedInput] and [Error::ZippedInput], since they're often mistaken for las files.
     /// - The point data format is not recognized. Note that version mismatches *are* allowed (e.g.
     ///   color points for las 1.1).
     /// - The point data record length is less than the minimum length of the point data format.
@@ -254,69 +260,184 @@ impl Header {
     /// let mut file = File::open("tests/data/autzen.las").unwrap();
     /// let header = Header::read_from(&mut file).unwrap();
     /// ```
+    #[cfg(feature = "std")]
     pub fn read_from<R: Read>(mut read: R) -> Result<Header> {
-        let mut header = Header::default();
-        read.read_exact(&mut header.file_signature)?;
+        let mut bytes = vec![0; usize::from(Version::new(1, 0).header_size())];
+        read.read_exact(&mut bytes[..LASF.len()])?;
+        let file_signature = bytes[..LASF.len()].try_into().expect("four bytes");
+        if file_signature != LASF {
+            return Err(signature_error(file_signature));
+        }
+        read.read_exact(&mut bytes[LASF.len()..])?;
+        let version = Version::new(bytes[24], bytes[25]);
+        let len = fields_len(version) + padding_len(version, header_size(&bytes));
+        let start = bytes.len();
+        bytes.resize(len, 0);
+        read.read_exact(&mut bytes[start..])?;
+        Header::decode(&bytes)
+    }
+
+    /// Reads a raw header from a byte slice.
+    ///
+    /// The slice must hold at least as many bytes as the size of the header for its version (227,
+    /// 235, or 375 bytes), or as the `header_size` field if that is larger. Any bytes after the
+    /// header are ignored. Otherwise, this behaves exactly like [Header::read_from].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::Header;
+    /// let bytes = Header::default().to_bytes().unwrap();
+    /// let header = Header::from_bytes(&bytes).unwrap();
+    /// assert_eq!(Header::default(), header);
+    /// assert!(Header::from_bytes(&bytes[..100]).is_err());
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Header> {
+        let minimum = usize::from(Version::new(1, 0).header_size());
+        if bytes.len() < minimum {
+            return Err(Error::HeaderTooShort {
+                expected: minimum,
+                len: bytes.len(),
+            });
+        }
+        let version = Version::new(bytes[24], bytes[25]);
+        let expected = usize::from(version.header_size().max(header_size(bytes)));
+        if bytes.len() < expected {
+            return Err(Error::HeaderTooShort {
+                expected,
+                len: bytes.len(),
+            });
+        }
+        Header::decode(bytes)
+    }
+
+    /// Decodes a header from a slice that holds its fields and padding.
+    fn decode(bytes: &[u8]) -> Result<Header> {
+        let mut decoder = Decoder::new(bytes);
+        let mut header = Header {
+            file_signature: decoder.array(),
+            ..Default::default()
+        };
         if header.file_signature != LASF {
-            return Err(Error::InvalidFileSignature(header.file_signature));
+            return Err(signature_error(header.file_signature));
         }
-        header.file_source_id = read.read_u16::<LittleEndian>()?;
-        header.global_encoding = read.read_u16::<LittleEndian>()?;
-        read.read_exact(&mut header.guid)?;
-        let version_major = read.read_u8()?;
-        let version_minor = read.read_u8()?;
+        header.file_source_id = decoder.u16();
+        header.global_encoding = decoder.u16();
+        header.guid = decoder.array();
+        let version_major = decoder.u8();
+        let version_minor = decoder.u8();
         header.version = Version::new(version_major, version_minor);
-        read.read_exact(&mut header.system_identifier)?;
-        read.read_exact(&mut header.generating_software)?;
-        header.file_creation_day_of_year = read.read_u16::<LittleEndian>()?;
-        header.file_creation_year = read.read_u16::<LittleEndian>()?;
-        header.header_size = read.read_u16::<LittleEndian>()?;
-        header.offset_to_point_data = read.read_u32::<LittleEndian>()?;
-        header.number_of_variable_length_records = read.read_u32::<LittleEndian>()?;
-        header.point_data_record_format = read.read_u8()?;
-        header.point_data_record_length = read.read_u16::<LittleEndian>()?;
-        header.number_of_point_records = read.read_u32::<LittleEndian>()?;
+        header.system_identifier = decoder.array();
+        header.generating_software = decoder.array();
+        header.file_creation_day_of_year = decoder.u16();
+        header.file_creation_year = decoder.u16();
+        header.header_size = decoder.u16();
+        header.offset_to_point_data = decoder.u32();
+        header.number_of_variable_length_records = decoder.u32();
+        header.point_data_record_format = decoder.u8();
+        header.point_data_record_length = decoder.u16();
+        header.number_of_point_records = decoder.u32();
         for n in &mut header.number_of_points_by_return {
-            *n = read.read_u32::<LittleEndian>()?;
+            *n = decoder.u32();
         }
-        header.x_scale_factor = read.read_f64::<LittleEndian>()?;
-        header.y_scale_factor = read.read_f64::<LittleEndian>()?;
-        header.z_scale_factor = read.read_f64::<LittleEndian>()?;
-        header.x_offset = read.read_f64::<LittleEndian>()?;
-        header.y_offset = read.read_f64::<LittleEndian>()?;
-        header.z_offset = read.read_f64::<LittleEndian>()?;
-        header.max_x = read.read_f64::<LittleEndian>()?;
-        header.min_x = read.read_f64::<LittleEndian>()?;
-        header.max_y = read.read_f64::<LittleEndian>()?;
-        header.min_y = read.read_f64::<LittleEndian>()?;
-        header.max_z = read.read_f64::<LittleEndian>()?;
-        header.min_z = read.read_f64::<LittleEndian>()?;
+        header.x_scale_factor = decoder.f64();
+        header.y_scale_factor = decoder.f64();
+        header.z_scale_factor = decoder.f64();
+        header.x_offset = decoder.f64();
+        header.y_offset = decoder.f64();
+        header.z_offset = decoder.f64();
+        header.max_x = decoder.f64();
+        header.min_x = decoder.f64();
+        header.max_y = decoder.f64();
+        header.min_y = decoder.f64();
+        header.max_z = decoder.f64();
+        header.min_z = decoder.f64();
         header.start_of_waveform_data_packet_record = if header.version.supports::<Waveforms>() {
-            utils::some_or_none_if_zero(read.read_u64::<LittleEndian>()?)
+            utils::some_or_none_if_zero(decoder.u64())
         } else {
             None
         };
         header.evlr = if header.version.supports::<Evlrs>() {
-            Evlr::read_from(&mut read)?.into_option()
+            Evlr::decode(&mut decoder).into_option()
         } else {
             None
         };
         header.large_file = if header.version.supports::<LargeFiles>() {
-            Some(LargeFile::read_from(&mut read)?)
+            Some(LargeFile::decode(&mut decoder))
         } else {
             None
         };
-        header.padding = if header.header_size > header.version.header_size() {
-            let mut bytes = vec![0; (header.header_size - header.version.header_size()) as usize];
-            read.read_exact(&mut bytes)?;
-            bytes
-        } else {
-            Vec::new()
-        };
+        header.padding = decoder
+            .bytes(padding_len(header.version, header.header_size))
+            .to_vec();
         Ok(header)
     }
 
-    fn number_of_point_records(&self) -> u64 {
+    /// Writes this raw header to a new vector of bytes.
+    ///
+    /// The output is exactly what [Header::write_to] would write.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::Header;
+    /// let bytes = Header::default().to_bytes().unwrap();
+    /// assert_eq!(227, bytes.len());
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![0; fields_len(self.version) + self.padding.len()];
+        let mut encoder = Encoder::new(&mut bytes);
+        encoder.bytes(&self.file_signature);
+        encoder.u16(self.file_source_id);
+        encoder.u16(self.global_encoding);
+        encoder.bytes(&self.guid);
+        encoder.u8(self.version.major);
+        encoder.u8(self.version.minor);
+        encoder.bytes(&self.system_identifier);
+        encoder.bytes(&self.generating_software);
+        encoder.u16(self.file_creation_day_of_year);
+        encoder.u16(self.file_creation_year);
+        encoder.u16(self.header_size);
+        encoder.u32(self.offset_to_point_data);
+        encoder.u32(self.number_of_variable_length_records);
+        encoder.u8(self.point_data_record_format);
+        encoder.u16(self.point_data_record_length);
+        encoder.u32(self.number_of_point_records);
+        for n in &self.number_of_points_by_return {
+            encoder.u32(*n);
+        }
+        encoder.f64(self.x_scale_factor);
+        encoder.f64(self.y_scale_factor);
+        encoder.f64(self.z_scale_factor);
+        encoder.f64(self.x_offset);
+        encoder.f64(self.y_offset);
+        encoder.f64(self.z_offset);
+        encoder.f64(self.max_x);
+        encoder.f64(self.min_x);
+        encoder.f64(self.max_y);
+        encoder.f64(self.min_y);
+        encoder.f64(self.max_z);
+        encoder.f64(self.min_z);
+        if self.version.supports::<Waveforms>() {
+            encoder.u64(self.start_of_waveform_data_packet_record.unwrap_or(0));
+        }
+        if self.version.supports::<Evlrs>() {
+            let evlr = self.evlr.unwrap_or_default();
+            encoder.u64(evlr.start_of_first_evlr);
+            encoder.u32(evlr.number_of_evlrs);
+        }
+        if self.version.supports::<LargeFiles>() {
+            let large_file = self.large_file.unwrap_or_default();
+            encoder.u64(large_file.number_of_point_records);
+            for n in &large_file.number_of_points_by_return {
+                encoder.u64(*n);
+            }
+        }
+        encoder.bytes(&self.padding);
+        Ok(bytes)
+    }
+
+    pub(crate) fn number_of_point_records(&self) -> u64 {
         // In LAS 1.4 R15, number_of_point_records is a legacy header field.
         // This code needs to handle the case where the legacy field is 0 but the non-legacy field is specified.
         // From https://www.asprs.org/wp-content/uploads/2019/07/LAS_1_4_r15.pdf
@@ -346,6 +467,60 @@ impl Header {
             .unwrap_or(legacy_number_of_point_records)
     }
 
+    /// Returns the scales and offsets of the x, y, and z coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::Header;
+    /// let header = Header { x_scale_factor: 0.01, x_offset: 100., ..Default::default() };
+    /// let transforms = header.transforms();
+    /// assert_eq!(0.01, transforms.x.scale);
+    /// assert_eq!(100., transforms.x.offset);
+    /// ```
+    pub fn transforms(&self) -> Vector<Transform> {
+        Vector {
+            x: Transform {
+                scale: self.x_scale_factor,
+                offset: self.x_offset,
+            },
+            y: Transform {
+                scale: self.y_scale_factor,
+                offset: self.y_offset,
+            },
+            z: Transform {
+                scale: self.z_scale_factor,
+                offset: self.z_offset,
+            },
+        }
+    }
+
+    /// Returns the bounds of the points, as they're stored in the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::Header;
+    /// let header = Header { min_x: -1., max_x: 1., ..Default::default() };
+    /// let bounds = header.bounds();
+    /// assert_eq!(-1., bounds.min.x);
+    /// assert_eq!(1., bounds.max.x);
+    /// ```
+    pub fn bounds(&self) -> Bounds {
+        Bounds {
+            min: Vector {
+                x: self.min_x,
+                y: self.min_y,
+                z: self.min_z,
+            },
+            max: Vector {
+                x: self.max_x,
+                y: self.max_y,
+                z: self.max_z,
+            },
+        }
+    }
+
     /// Returns the uncompressed total file offset to the first byte *after* all of the points.
     ///
     /// # Examples
@@ -370,60 +545,9 @@ impl Header {
     /// let header = Header::default();
     /// header.write_to(&mut cursor).unwrap();
     /// ```
+    #[cfg(feature = "std")]
     pub fn write_to<W: Write>(&self, mut write: W) -> Result<()> {
-        use byteorder::WriteBytesExt;
-
-        write.write_all(&self.file_signature)?;
-        write.write_u16::<LittleEndian>(self.file_source_id)?;
-        write.write_u16::<LittleEndian>(self.global_encoding)?;
-        write.write_all(&self.guid)?;
-        write.write_u8(self.version.major)?;
-        write.write_u8(self.version.minor)?;
-        write.write_all(&self.system_identifier)?;
-        write.write_all(&self.generating_software)?;
-        write.write_u16::<LittleEndian>(self.file_creation_day_of_year)?;
-        write.write_u16::<LittleEndian>(self.file_creation_year)?;
-        write.write_u16::<LittleEndian>(self.header_size)?;
-        write.write_u32::<LittleEndian>(self.offset_to_point_data)?;
-        write.write_u32::<LittleEndian>(self.number_of_variable_length_records)?;
-        write.write_u8(self.point_data_record_format)?;
-        write.write_u16::<LittleEndian>(self.point_data_record_length)?;
-        write.write_u32::<LittleEndian>(self.number_of_point_records)?;
-        for n in &self.number_of_points_by_return {
-            write.write_u32::<LittleEndian>(*n)?;
-        }
-        write.write_f64::<LittleEndian>(self.x_scale_factor)?;
-        write.write_f64::<LittleEndian>(self.y_scale_factor)?;
-        write.write_f64::<LittleEndian>(self.z_scale_factor)?;
-        write.write_f64::<LittleEndian>(self.x_offset)?;
-        write.write_f64::<LittleEndian>(self.y_offset)?;
-        write.write_f64::<LittleEndian>(self.z_offset)?;
-        write.write_f64::<LittleEndian>(self.max_x)?;
-        write.write_f64::<LittleEndian>(self.min_x)?;
-        write.write_f64::<LittleEndian>(self.max_y)?;
-        write.write_f64::<LittleEndian>(self.min_y)?;
-        write.write_f64::<LittleEndian>(self.max_z)?;
-        write.write_f64::<LittleEndian>(self.min_z)?;
-        if self.version.supports::<Waveforms>() {
-            write.write_u64::<LittleEndian>(
-                self.start_of_waveform_data_packet_record.unwrap_or(0),
-            )?;
-        }
-        if self.version.supports::<Evlrs>() {
-            let evlr = self.evlr.unwrap_or_default();
-            write.write_u64::<LittleEndian>(evlr.start_of_first_evlr)?;
-            write.write_u32::<LittleEndian>(evlr.number_of_evlrs)?;
-        }
-        if self.version.supports::<LargeFiles>() {
-            let large_file = self.large_file.unwrap_or_default();
-            write.write_u64::<LittleEndian>(large_file.number_of_point_records)?;
-            for n in &large_file.number_of_points_by_return {
-                write.write_u64::<LittleEndian>(*n)?;
-            }
-        }
-        if !self.padding.is_empty() {
-            write.write_all(&self.padding)?;
-        }
+        write.write_all(&self.to_bytes()?)?;
         Ok(())
     }
 }
@@ -471,11 +595,11 @@ impl Default for Header {
 }
 
 impl Evlr {
-    fn read_from<R: Read>(mut read: R) -> Result<Evlr> {
-        Ok(Evlr {
-            start_of_first_evlr: read.read_u64::<LittleEndian>()?,
-            number_of_evlrs: read.read_u32::<LittleEndian>()?,
-        })
+    fn decode(decoder: &mut Decoder<'_>) -> Evlr {
+        Evlr {
+            start_of_first_evlr: decoder.u64(),
+            number_of_evlrs: decoder.u32(),
+        }
     }
 
     fn into_option(self) -> Option<Evlr> {
@@ -488,20 +612,55 @@ impl Evlr {
 }
 
 impl LargeFile {
-    fn read_from<R: Read>(mut read: R) -> Result<LargeFile> {
-        let number_of_point_records = read.read_u64::<LittleEndian>()?;
+    fn decode(decoder: &mut Decoder<'_>) -> LargeFile {
+        let number_of_point_records = decoder.u64();
         let mut number_of_points_by_return = [0; 15];
         for n in &mut number_of_points_by_return {
-            *n = read.read_u64::<LittleEndian>()?
+            *n = decoder.u64();
         }
-        Ok(LargeFile {
+        LargeFile {
             number_of_point_records,
             number_of_points_by_return,
-        })
+        }
     }
 }
 
-#[cfg(test)]
+/// Returns the `header_size` field of an encoded header that's at least as long as a las 1.0
+/// header.
+fn header_size(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes([bytes[94], bytes[95]])
+}
+
+/// Returns the number of bytes in the padding after a header's fields.
+fn padding_len(version: Version, header_size: u16) -> usize {
+    usize::from(header_size.saturating_sub(version.header_size()))
+}
+
+/// Returns the number of bytes in an encoded header, not counting its padding.
+fn fields_len(version: Version) -> usize {
+    let mut len = usize::from(Version::new(1, 0).header_size());
+    if version.supports::<Waveforms>() {
+        len += 8;
+    }
+    if version.supports::<Evlrs>() {
+        len += 12;
+    }
+    if version.supports::<LargeFiles>() {
+        len += 128;
+    }
+    len
+}
+
+/// Returns the error for a file that doesn't start with "LASF".
+fn signature_error(file_signature: [u8; 4]) -> Error {
+    match file_signature {
+        [0x1f, 0x8b, ..] => Error::GzippedInput,
+        [b'P', b'K', 3, 4] => Error::ZippedInput,
+        _ => Error::InvalidFileSignature(file_signature),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
     use std::io::Cursor;
@@ -530,8 +689,7 @@ mod tests {
         buff.write_all(&u64::MAX.to_le_bytes()).unwrap();
         buff.write_all(&0_u32.to_le_bytes()).unwrap();
 
-        buff.set_position(0);
-        let evlr = Evlr::read_from(buff).unwrap();
+        let evlr = Evlr::decode(&mut Decoder::new(buff.get_ref()));
         assert!(evlr.into_option().is_none());
     }
 
@@ -542,8 +700,7 @@ mod tests {
         buff.write_all(&u64::MAX.to_le_bytes()).unwrap();
         buff.write_all(&1_u32.to_le_bytes()).unwrap();
 
-        buff.set_position(0);
-        let evlr = Evlr::read_from(buff).unwrap();
+        let evlr = Evlr::decode(&mut Decoder::new(buff.get_ref()));
         assert!(evlr.into_option().is_some());
     }
 
@@ -572,6 +729,116 @@ mod tests {
         };
     }
 
+    #[test]
+    fn bytes_match_io() {
+        for path in [
+            "tests/data/32-1-472-150-76.laz",
+            "tests/data/autzen.copc.laz",
+            "tests/data/autzen.las",
+            "tests/data/autzen.laz",
+            "tests/data/extrabytes.laz",
+        ] {
+            let bytes = std::fs::read(path).unwrap();
+            let header = Header::read_from(bytes.as_slice()).unwrap();
+            assert_eq!(header, Header::from_bytes(&bytes).unwrap());
+
+            let mut written = Vec::new();
+            header.write_to(&mut written).unwrap();
+            assert_eq!(written, header.to_bytes().unwrap());
+            assert_eq!(usize::from(header.header_size), written.len());
+            assert_eq!(&bytes[..written.len()], written.as_slice());
+        }
+    }
+
+    #[test]
+    fn from_bytes_too_short() {
+        for minor in 0..=4 {
+            let header = Header {
+                version: Version::new(1, minor),
+                ..Default::default()
+            };
+            let bytes = header.to_bytes().unwrap();
+            let expected = usize::from(header.version.header_size());
+            assert_eq!(expected, bytes.len());
+            match Header::from_bytes(&bytes[..expected - 1]).unwrap_err() {
+                Error::HeaderTooShort { expected: e, len } => {
+                    assert_eq!(expected, e);
+                    assert_eq!(expected - 1, len);
+                }
+                err => panic!("unexpected error: {err}"),
+            }
+        }
+        assert!(matches!(
+            Header::from_bytes(&[]).unwrap_err(),
+            Error::HeaderTooShort {
+                expected: 227,
+                len: 0
+            }
+        ));
+    }
+
+    #[test]
+    fn from_bytes_padding() {
+        let header = Header {
+            header_size: 230,
+            padding: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let bytes = header.to_bytes().unwrap();
+        assert_eq!(header, Header::from_bytes(&bytes).unwrap());
+        assert!(Header::from_bytes(&bytes[..229]).is_err());
+    }
+
+    #[test]
+    fn from_bytes_invalid_file_signature() {
+        let header = Header {
+            file_signature: *b"ABCD",
+            ..Default::default()
+        };
+        assert!(matches!(
+            Header::from_bytes(&header.to_bytes().unwrap()).unwrap_err(),
+            Error::InvalidFileSignature(_)
+        ));
+    }
+
+    #[test]
+    fn from_bytes_gzipped() {
+        for file_signature in [[0x1f, 0x8b, 8, 0], [0x1f, 0x8b, 8, 8]] {
+            let header = Header {
+                file_signature,
+                ..Default::default()
+            };
+            assert!(matches!(
+                Header::from_bytes(&header.to_bytes().unwrap()).unwrap_err(),
+                Error::GzippedInput
+            ));
+        }
+    }
+
+    #[test]
+    fn from_bytes_zipped() {
+        let header = Header {
+            file_signature: *b"PK\x03\x04",
+            ..Default::default()
+        };
+        assert!(matches!(
+            Header::from_bytes(&header.to_bytes().unwrap()).unwrap_err(),
+            Error::ZippedInput
+        ));
+    }
+
+    #[test]
+    fn from_bytes_almost_gzipped() {
+        let header = Header {
+            file_signature: [0x1f, 0x8c, 8, 0],
+            ..Default::default()
+        };
+        assert!(matches!(
+            Header::from_bytes(&header.to_bytes().unwrap()).unwrap_err(),
+            Error::InvalidFileSignature([0x1f, 0x8c, 8, 0])
+        ));
+    }
+
     roundtrip!(las_1_0, 0);
     roundtrip!(las_1_1, 1);
     roundtrip!(las_1_2, 2);
@@ -35,15 +35,59 @@
 //! Header::read_from(&mut cursor).unwrap();
 //! Vlr::read_from(&mut cursor, false).unwrap();
 //! ```
+//!
+//! They also have `from_bytes` and `to_bytes` functions that work on byte slices, which are what
+//! `read_from` and `write_to` use underneath. These are available without the `std` feature.
 
+mod bytes;
 pub mod header;
 pub mod point;
 pub mod vlr;
 
 pub use self::{header::Header, point::Point, vlr::Vlr};
+use crate::{
+    utils::{self, FromLasStr},
+    Result,
+};
 
 /// The file magic number used for all las files.
 pub const LASF: [u8; 4] = *b"LASF";
 
 /// The point data start signature required by las 1.0.
 pub const POINT_DATA_START_SIGNATURE: [u8; 2] = [0xCC, 0xDD];
+
+/// Returns the string in a nul-filled las string field, e.g. [Header::system_identifier].
+///
+/// Returns an error if there's anything but nul after the first nul, or if the string isn't
+/// ascii.
+///
+/// # Examples
+///
+/// ```
+/// use las::raw;
+/// assert_eq!("las-rs", raw::las_str(b"las-rs\0\0").unwrap());
+/// assert!(raw::las_str(b"las\0rs").is_err());
+/// ```
+pub fn las_str(bytes: &[u8]) -> Result<&str> {
+    utils::las_str(bytes)
+}
+
+/// Writes a string into a las string field, filling the rest of the field with nul.
+///
+/// Returns an error if the string isn't ascii or doesn't fit.
+///
+/// # Examples
+///
+/// ```
+/// use las::raw;
+/// let mut bytes = [1; 8];
+/// raw::write_las_str(&mut bytes, "las-rs").unwrap();
+/// assert_eq!(b"las-rs\0\0", &bytes);
+/// assert!(raw::write_las_str(&mut bytes, "too long for it").is_err());
+/// ```
+pub fn write_las_str(bytes: &mut [u8], s: &str) -> Result<()> {
+    utils::check_las_str(s, bytes.len())?;
+    bytes.fill(0);
+    let mut bytes = bytes;
+    bytes.from_las_str(s)
+}
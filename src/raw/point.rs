@@ -1,13 +1,24 @@
 //! Defines raw las points and some enums required to handle the various point formats.
 
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::{
-    point::{Classification, Format, ScanDirection},
-    Color, Error, Result,
+    point::{Classification, ExtraBytes, Format, ScanDirection},
+    raw::bytes::{Decoder, Encoder},
+    utils, Color, Error, Result,
 };
+use num_traits::float::FloatCore;
+#[cfg(feature = "std")]
 use std::io::{Read, Write};
 
+/// The length of a point in the largest format, format 10, not counting its extra bytes.
+const MAX_LEN_WITHOUT_EXTRA_BYTES: usize = 67;
+#[cfg(feature = "std")]
+const WAVEFORM_LEN: usize = 29;
 const SCAN_ANGLE_SCALE_FACTOR: f32 = 0.006;
-const OVERLAP_CLASSIFICATION_CODE: u8 = 12;
+const MAX_SCAN_ANGLE_RANK: f32 = 90.;
+const MAX_SCALED_SCAN_ANGLE: f32 = 30_000.;
+pub(crate) const OVERLAP_CLASSIFICATION_CODE: u8 = 12;
 
 /// A raw point.
 ///
@@ -240,10 +251,11 @@ pub struct Point {
     pub nir: Option<u16>,
 
     #[allow(missing_docs)]
-    pub extra_bytes: Vec<u8>,
+    pub extra_bytes: ExtraBytes,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct Waveform {
     /// This value plus 99 is the Record ID of the Waveform Packet Descriptor and indicates the
@@ -307,6 +319,22 @@ pub struct Waveform {
 }
 
 /// Scan angle can be stored as a i8 (rank) or i16 (scaled).
+///
+/// Point formats zero through five store the scan angle rank in whole degrees, from -90 to 90.
+/// Point formats six through ten store the scaled scan angle in 0.006 degree increments, from
+/// -30,000 to 30,000 (-180 to 180 degrees).
+///
+/// Converting from degrees or from the other encoding rounds to the nearest representable value
+/// and saturates at the ends of the valid range:
+///
+/// ```
+/// use las::raw::point::ScanAngle;
+/// assert_eq!(45, i8::from(ScanAngle::from(45.4)));
+/// assert_eq!(90, i8::from(ScanAngle::from(120.)));
+/// assert_eq!(30_000, i16::from(ScanAngle::from(200.)));
+/// ```
+///
+/// Values that are already in the target encoding are kept as-is, even if they are out of range.
 #[derive(Clone, Copy, Debug)]
 #[allow(missing_docs)]
 pub enum ScanAngle {
@@ -334,6 +362,7 @@ pub enum ScanAngle {
 /// assert!(three_byte.to_two_bytes().is_err());
 /// ```
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Flags {
     /// Two byte flags, used for point formats zero through five.
     TwoByte(u8, u8),
@@ -356,56 +385,92 @@ impl Point {
     /// let point = Point::read_from(file, &Format::new(1).unwrap()).unwrap();
     /// ```
     #[allow(clippy::field_reassign_with_default)]
-    pub fn read_from<R: Read>(mut read: R, format: &Format) -> Result<Point> {
-        use crate::utils;
-        use byteorder::{LittleEndian, ReadBytesExt};
+    #[cfg(feature = "std")]
+    pub fn read_from<R: Read>(read: R, format: &Format) -> Result<Point> {
+        let mut point = Point::default();
+        point.read_into(read, format)?;
+        Ok(point)
+    }
+
+    /// Overwrites every field of this point with one read from a `Read`, reusing the extra
+    /// bytes' heap allocation if there is one.
+    #[cfg(feature = "std")]
+    pub(crate) fn read_into<R: Read>(&mut self, mut read: R, format: &Format) -> Result<()> {
+        if let Some(n) = format.unknown {
+            return Err(Error::UnknownPointFormat(n));
+        }
+        let mut buffer = [0; MAX_LEN_WITHOUT_EXTRA_BYTES];
+        let buffer = &mut buffer[..len_without_extra_bytes(format)];
+        read.read_exact(buffer)?;
+        self.decode_without_extra_bytes(Decoder::new(buffer), format);
+        self.extra_bytes.set_len(usize::from(format.extra_bytes));
+        read.read_exact(&mut self.extra_bytes)?;
+        Ok(())
+    }
 
+    /// Reads a raw point from a byte slice.
+    ///
+    /// The slice must hold at least `format.len()` bytes. Any bytes after the point are ignored.
+    /// Otherwise, this behaves exactly like [Point::read_from].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::Point;
+    /// use las::point::Format;
+    /// let format = Format::default();
+    /// let bytes = Point::default().to_bytes(&format).unwrap();
+    /// assert_eq!(Point::default(), Point::from_bytes(&bytes, &format).unwrap());
+    /// assert!(Point::from_bytes(&bytes[..19], &format).is_err());
+    /// ```
+    pub fn from_bytes(bytes: &[u8], format: &Format) -> Result<Point> {
+        let expected = usize::from(format.len());
+        if bytes.len() < expected {
+            return Err(Error::PointTooShort {
+                expected,
+                len: bytes.len(),
+            });
+        }
+        if let Some(n) = format.unknown {
+            return Err(Error::UnknownPointFormat(n));
+        }
+        let mut decoder = Decoder::new(bytes);
         let mut point = Point::default();
-        point.x = read.read_i32::<LittleEndian>()?;
-        point.y = read.read_i32::<LittleEndian>()?;
-        point.z = read.read_i32::<LittleEndian>()?;
-        point.intensity = read.read_u16::<LittleEndian>()?;
-        point.flags = if format.is_extended {
-            Flags::ThreeByte(read.read_u8()?, read.read_u8()?, read.read_u8()?)
-        } else {
-            Flags::TwoByte(read.read_u8()?, read.read_u8()?)
-        };
-        if format.is_extended {
-            point.user_data = read.read_u8()?;
-            point.scan_angle = ScanAngle::Scaled(read.read_i16::<LittleEndian>()?);
-        } else {
-            point.scan_angle = ScanAngle::Rank(read.read_i8()?);
-            point.user_data = read.read_u8()?;
-        };
-        point.point_source_id = read.read_u16::<LittleEndian>()?;
-        point.gps_time = if format.has_gps_time {
-            Some(read.read_f64::<LittleEndian>()?)
-        } else {
-            None
-        };
-        point.color = if format.has_color {
-            let red = read.read_u16::<LittleEndian>()?;
-            let green = read.read_u16::<LittleEndian>()?;
-            let blue = read.read_u16::<LittleEndian>()?;
-            Some(Color::new(red, green, blue))
-        } else {
-            None
-        };
-        point.waveform = if format.has_waveform {
-            Some(Waveform::read_from(&mut read)?)
-        } else {
-            None
-        };
-        point.nir = if format.has_nir {
-            utils::some_or_none_if_zero(read.read_u16::<LittleEndian>()?)
-        } else {
-            None
-        };
-        point.extra_bytes.resize(format.extra_bytes as usize, 0);
-        read.read_exact(&mut point.extra_bytes)?;
+        point.decode_without_extra_bytes(
+            Decoder::new(decoder.bytes(len_without_extra_bytes(format))),
+            format,
+        );
+        point.extra_bytes.set_len(usize::from(format.extra_bytes));
+        point
+            .extra_bytes
+            .copy_from_slice(decoder.bytes(usize::from(format.extra_bytes)));
         Ok(point)
     }
 
+    /// Writes this raw point to a new vector of bytes.
+    ///
+    /// The output is exactly what [Point::write_to] would write.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::Point;
+    /// use las::point::Format;
+    /// let bytes = Point::default().to_bytes(&Format::default()).unwrap();
+    /// assert_eq!(20, bytes.len());
+    /// ```
+    pub fn to_bytes(&self, format: &Format) -> Result<Vec<u8>> {
+        assert_eq!(format.extra_bytes as usize, self.extra_bytes.len());
+        if let Some(n) = format.unknown {
+            return Err(Error::UnknownPointFormat(n));
+        }
+        let mut bytes = vec![0; usize::from(format.len())];
+        let (fields, extra_bytes) = bytes.split_at_mut(len_without_extra_bytes(format));
+        self.encode_without_extra_bytes(Encoder::new(fields), format)?;
+        extra_bytes.copy_from_slice(&self.extra_bytes);
+        Ok(bytes)
+    }
+
     /// Writes a raw pont.
     ///
     /// # Examples
@@ -420,79 +485,154 @@ impl Point {
     /// let point = Point::default();
     /// point.write_to(cursor, &Format::default()).unwrap();
     /// ```
+    #[cfg(feature = "std")]
     pub fn write_to<W: Write>(&self, mut write: W, format: &Format) -> Result<()> {
-        use byteorder::{LittleEndian, WriteBytesExt};
         assert_eq!(format.extra_bytes as usize, self.extra_bytes.len());
+        self.write_without_extra_bytes_to(&mut write, format)?;
+        write.write_all(&self.extra_bytes)?;
+        Ok(())
+    }
+
+    /// Writes everything but the extra bytes, so that they can be written from somewhere else.
+    #[cfg(feature = "std")]
+    pub(crate) fn write_without_extra_bytes_to<W: Write>(
+        &self,
+        mut write: W,
+        format: &Format,
+    ) -> Result<()> {
+        if let Some(n) = format.unknown {
+            return Err(Error::UnknownPointFormat(n));
+        }
+        let mut buffer = [0; MAX_LEN_WITHOUT_EXTRA_BYTES];
+        let buffer = &mut buffer[..len_without_extra_bytes(format)];
+        self.encode_without_extra_bytes(Encoder::new(buffer), format)?;
+        write.write_all(buffer)?;
+        Ok(())
+    }
+
+    /// Decodes everything but the extra bytes, which are left alone.
+    fn decode_without_extra_bytes(&mut self, mut decoder: Decoder<'_>, format: &Format) {
+        self.x = decoder.i32();
+        self.y = decoder.i32();
+        self.z = decoder.i32();
+        self.intensity = decoder.u16();
+        self.flags = if format.is_extended {
+            Flags::ThreeByte(decoder.u8(), decoder.u8(), decoder.u8())
+        } else {
+            Flags::TwoByte(decoder.u8(), decoder.u8())
+        };
+        if format.is_extended {
+            self.user_data = decoder.u8();
+            self.scan_angle = ScanAngle::Scaled(decoder.i16());
+        } else {
+            self.scan_angle = ScanAngle::Rank(decoder.i8());
+            self.user_data = decoder.u8();
+        };
+        self.point_source_id = decoder.u16();
+        self.gps_time = if format.has_gps_time {
+            Some(decoder.f64())
+        } else {
+            None
+        };
+        self.color = if format.has_color {
+            let red = decoder.u16();
+            let green = decoder.u16();
+            let blue = decoder.u16();
+            Some(Color::new(red, green, blue))
+        } else {
+            None
+        };
+        self.nir = if format.has_nir {
+            utils::some_or_none_if_zero(decoder.u16())
+        } else {
+            None
+        };
+        self.waveform = if format.has_waveform {
+            Some(Waveform::decode(&mut decoder))
+        } else {
+            None
+        };
+    }
 
-        write.write_i32::<LittleEndian>(self.x)?;
-        write.write_i32::<LittleEndian>(self.y)?;
-        write.write_i32::<LittleEndian>(self.z)?;
-        write.write_u16::<LittleEndian>(self.intensity)?;
+    fn encode_without_extra_bytes(&self, mut encoder: Encoder<'_>, format: &Format) -> Result<()> {
+        encoder.i32(self.x);
+        encoder.i32(self.y);
+        encoder.i32(self.z);
+        encoder.u16(self.intensity);
         if format.is_extended {
             let (a, b, c) = self.flags.into();
-            write.write_u8(a)?;
-            write.write_u8(b)?;
-            write.write_u8(c)?;
+            encoder.u8(a);
+            encoder.u8(b);
+            encoder.u8(c);
         } else {
             let (a, b) = self.flags.to_two_bytes()?;
-            write.write_u8(a)?;
-            write.write_u8(b)?;
+            encoder.u8(a);
+            encoder.u8(b);
         }
         if format.is_extended {
-            write.write_u8(self.user_data)?;
-            write.write_i16::<LittleEndian>(self.scan_angle.into())?;
+            encoder.u8(self.user_data);
+            encoder.i16(self.scan_angle.into());
         } else {
-            write.write_i8(self.scan_angle.into())?;
-            write.write_u8(self.user_data)?;
+            encoder.i8(self.scan_angle.into());
+            encoder.u8(self.user_data);
         }
-        write.write_u16::<LittleEndian>(self.point_source_id)?;
+        encoder.u16(self.point_source_id);
         if format.has_gps_time {
-            write.write_f64::<LittleEndian>(self.gps_time.unwrap_or(0.0))?;
+            encoder.f64(self.gps_time.unwrap_or(0.0));
         }
         if format.has_color {
             let color = self.color.unwrap_or_default();
-            write.write_u16::<LittleEndian>(color.red)?;
-            write.write_u16::<LittleEndian>(color.green)?;
-            write.write_u16::<LittleEndian>(color.blue)?;
+            encoder.u16(color.red);
+            encoder.u16(color.green);
+            encoder.u16(color.blue);
         }
         if format.has_nir {
-            write.write_u16::<LittleEndian>(self.nir.unwrap_or(0))?;
+            encoder.u16(self.nir.unwrap_or(0));
         }
         if format.has_waveform {
-            self.waveform.unwrap_or_default().write_to(&mut write)?;
+            self.waveform.unwrap_or_default().encode(&mut encoder);
         }
-        write.write_all(&self.extra_bytes)?;
         Ok(())
     }
 }
 
 impl Waveform {
-    fn read_from<R: Read>(mut read: R) -> Result<Waveform> {
-        use byteorder::{LittleEndian, ReadBytesExt};
-        Ok(Waveform {
-            wave_packet_descriptor_index: read.read_u8()?,
-            byte_offset_to_waveform_data: read.read_u64::<LittleEndian>()?,
-            waveform_packet_size_in_bytes: read.read_u32::<LittleEndian>()?,
-            return_point_waveform_location: read.read_f32::<LittleEndian>()?,
-            x_t: read.read_f32::<LittleEndian>()?,
-            y_t: read.read_f32::<LittleEndian>()?,
-            z_t: read.read_f32::<LittleEndian>()?,
-        })
-    }
-
-    fn write_to<W: Write>(&self, mut write: W) -> Result<()> {
-        use byteorder::{LittleEndian, WriteBytesExt};
-        write.write_u8(self.wave_packet_descriptor_index)?;
-        write.write_u64::<LittleEndian>(self.byte_offset_to_waveform_data)?;
-        write.write_u32::<LittleEndian>(self.waveform_packet_size_in_bytes)?;
-        write.write_f32::<LittleEndian>(self.return_point_waveform_location)?;
-        write.write_f32::<LittleEndian>(self.x_t)?;
-        write.write_f32::<LittleEndian>(self.y_t)?;
-        write.write_f32::<LittleEndian>(self.z_t)?;
-        Ok(())
+    /// Reads a waveform packet from the front of a byte slice, or returns `None` if it's too
+    /// short.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Waveform> {
+        let bytes = bytes.get(..WAVEFORM_LEN)?;
+        Some(Waveform::decode(&mut Decoder::new(bytes)))
+    }
+
+    fn decode(decoder: &mut Decoder<'_>) -> Waveform {
+        Waveform {
+            wave_packet_descriptor_index: decoder.u8(),
+            byte_offset_to_waveform_data: decoder.u64(),
+            waveform_packet_size_in_bytes: decoder.u32(),
+            return_point_waveform_location: decoder.f32(),
+            x_t: decoder.f32(),
+            y_t: decoder.f32(),
+            z_t: decoder.f32(),
+        }
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) {
+        encoder.u8(self.wave_packet_descriptor_index);
+        encoder.u64(self.byte_offset_to_waveform_data);
+        encoder.u32(self.waveform_packet_size_in_bytes);
+        encoder.f32(self.return_point_waveform_location);
+        encoder.f32(self.x_t);
+        encoder.f32(self.y_t);
+        encoder.f32(self.z_t);
     }
 }
 
+/// The length of a point in any known format, not counting its extra bytes.
+fn len_without_extra_bytes(format: &Format) -> usize {
+    usize::from(format.len() - format.extra_bytes)
+}
+
 impl Flags {
     /// Returns the return number.
     ///
@@ -607,7 +747,7 @@ impl Flags {
     /// ```
     pub fn is_overlap(&self) -> bool {
         match *self {
-            Flags::TwoByte(_, b) => b & 0b1111 == OVERLAP_CLASSIFICATION_CODE,
+            Flags::TwoByte(_, b) => b & 0b1_1111 == OVERLAP_CLASSIFICATION_CODE,
             Flags::ThreeByte(_, b, _) => b & 8 == 8,
         }
     }
@@ -796,7 +936,9 @@ impl From<ScanAngle> for i8 {
     fn from(scan_angle: ScanAngle) -> i8 {
         match scan_angle {
             ScanAngle::Rank(n) => n,
-            ScanAngle::Scaled(_) => f32::from(scan_angle).round() as i8,
+            ScanAngle::Scaled(_) => FloatCore::round(f32::from(scan_angle))
+                .clamp(-MAX_SCAN_ANGLE_RANK, MAX_SCAN_ANGLE_RANK)
+                as i8,
         }
     }
 }
@@ -821,7 +963,10 @@ impl From<ScanAngle> for f32 {
 
 impl From<f32> for ScanAngle {
     fn from(n: f32) -> ScanAngle {
-        ScanAngle::Scaled((n / SCAN_ANGLE_SCALE_FACTOR) as i16)
+        ScanAngle::Scaled(
+            FloatCore::round(n / SCAN_ANGLE_SCALE_FACTOR)
+                .clamp(-MAX_SCALED_SCAN_ANGLE, MAX_SCALED_SCAN_ANGLE) as i16,
+        )
     }
 }
 
@@ -831,7 +976,7 @@ impl PartialEq for ScanAngle {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -846,7 +991,7 @@ mod tests {
                     let mut format = Format::new($format).unwrap();
                     format.extra_bytes = 1;
                     let mut point = Point::default();
-                    point.extra_bytes = vec![42];
+                    point.extra_bytes = vec![42].into();
                     if format.has_color {
                         point.color = Some(Color::new(0, 0, 0));
                     }
@@ -860,6 +1005,10 @@ mod tests {
                     point.write_to(&mut cursor, &format).unwrap();
                     cursor.set_position(0);
                     assert_eq!(point, Point::read_from(cursor, &format).unwrap());
+                    let bytes = point.to_bytes(&format).unwrap();
+                    assert_eq!(usize::from(format.len()), bytes.len());
+                    assert_eq!(point, Point::from_bytes(&bytes, &format).unwrap());
+                    assert!(Point::from_bytes(&bytes[..bytes.len() - 1], &format).is_err());
                 }
             }
         };
@@ -877,6 +1026,24 @@ mod tests {
     roundtrip!(format_9, 9);
     roundtrip!(format_10, 10);
 
+    #[test]
+    fn nir_before_waveform() {
+        let format = Format::new(10).unwrap();
+        let point = Point {
+            gps_time: Some(0.),
+            color: Some(Color::default()),
+            nir: Some(0x0102),
+            waveform: Some(Waveform {
+                wave_packet_descriptor_index: 3,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let bytes = point.to_bytes(&format).unwrap();
+        assert_eq!([0x02, 0x01, 3], bytes[36..39]);
+        assert_eq!(point, Point::from_bytes(&bytes, &format).unwrap());
+    }
+
     #[test]
     fn return_number() {
         assert_eq!((0, 0, 0), Flags::TwoByte(0, 0).into());
@@ -915,6 +1082,25 @@ mod tests {
         assert_eq!(15_000i16, i16::from(ScanAngle::Rank(90)));
     }
 
+    #[test]
+    fn scan_angle_saturates() {
+        assert_eq!(90i8, i8::from(ScanAngle::Scaled(30_000)));
+        assert_eq!(-90i8, i8::from(ScanAngle::Scaled(-30_000)));
+        assert_eq!(90i8, i8::from(ScanAngle::Scaled(i16::MAX)));
+        assert_eq!(30_000i16, i16::from(ScanAngle::from(180.1)));
+        assert_eq!(-30_000i16, i16::from(ScanAngle::from(-1e9)));
+        assert_eq!(127i8, i8::from(ScanAngle::Rank(127)));
+        assert_eq!(i16::MAX, i16::from(ScanAngle::Scaled(i16::MAX)));
+    }
+
+    #[test]
+    fn scan_angle_rounds() {
+        assert_eq!(1i16, i16::from(ScanAngle::from(0.005)));
+        assert_eq!(-1i16, i16::from(ScanAngle::from(-0.005)));
+        assert_eq!(45i8, i8::from(ScanAngle::from(45.4)));
+        assert_eq!(46i8, i8::from(ScanAngle::from(45.6)));
+    }
+
     #[test]
     fn is_synthetic() {
         assert!(!Flags::TwoByte(0, 0).is_synthetic());
@@ -1,8 +1,17 @@
 //! Variable length records, both extended and regular.
 
-use crate::Result;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{
+    raw::bytes::{Decoder, Encoder},
+    Error, Result,
+};
+#[cfg(feature = "std")]
 use std::io::{Read, Write};
 
+/// The length of the standard part of an evlr, which is the longer of the two.
+const MAX_HEADER_LEN: usize = 60;
+
 /// A raw variable length record.
 #[derive(Debug, Default, PartialEq)]
 pub struct Vlr {
@@ -68,26 +77,85 @@ impl Vlr {
     /// // If the second parameter were true, it would be read as an extended vlr.
     /// let vlr = Vlr::read_from(file, false).unwrap();
     /// ```
-    #[allow(clippy::field_reassign_with_default)]
+    #[cfg(feature = "std")]
     pub fn read_from<R: Read>(mut read: R, extended: bool) -> Result<Vlr> {
-        use byteorder::{LittleEndian, ReadBytesExt};
-
-        let mut vlr = Vlr::default();
-        vlr.reserved = read.read_u16::<LittleEndian>()?;
-        read.read_exact(&mut vlr.user_id)?;
-        vlr.record_id = read.read_u16::<LittleEndian>()?;
-        vlr.record_length_after_header = if extended {
-            RecordLength::Evlr(read.read_u64::<LittleEndian>()?)
-        } else {
-            RecordLength::Vlr(read.read_u16::<LittleEndian>()?)
-        };
-        read.read_exact(&mut vlr.description)?;
-        vlr.data
-            .resize(usize::from(vlr.record_length_after_header), 0);
-        read.read_exact(&mut vlr.data)?;
+        let mut vlr = Vlr::read_header_from(&mut read, extended)?;
+        vlr.read_data_from(read)?;
+        Ok(vlr)
+    }
+
+    /// Reads a raw VLR or EVLR from a byte slice.
+    ///
+    /// The slice must hold the standard part of the record (54 bytes for a vlr, 60 for an evlr)
+    /// and all of its data. Any bytes after the record are ignored. Otherwise, this behaves
+    /// exactly like [Vlr::read_from].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::Vlr;
+    /// let vlr = Vlr::default();
+    /// let bytes = vlr.to_bytes().unwrap();
+    /// assert_eq!(vlr, Vlr::from_bytes(&bytes, false).unwrap());
+    /// assert!(Vlr::from_bytes(&bytes[..50], false).is_err());
+    /// ```
+    pub fn from_bytes(bytes: &[u8], extended: bool) -> Result<Vlr> {
+        let header_size = header_len(extended);
+        if bytes.len() < header_size {
+            return Err(Error::VlrTooShort {
+                expected: header_size as u64,
+                len: bytes.len(),
+            });
+        }
+        let mut vlr = Vlr::decode_header(&bytes[..header_size], extended);
+        let expected = header_size as u64 + u64::from(vlr.record_length_after_header);
+        if (bytes.len() as u64) < expected {
+            return Err(Error::VlrTooShort {
+                expected,
+                len: bytes.len(),
+            });
+        }
+        vlr.data = bytes[header_size..expected as usize].to_vec();
         Ok(vlr)
     }
 
+    /// Reads the standard part of a VLR or EVLR, leaving the data empty.
+    #[cfg(feature = "std")]
+    pub(crate) fn read_header_from<R: Read>(mut read: R, extended: bool) -> Result<Vlr> {
+        let mut buffer = [0; MAX_HEADER_LEN];
+        let buffer = &mut buffer[..header_len(extended)];
+        read.read_exact(buffer)?;
+        Ok(Vlr::decode_header(buffer, extended))
+    }
+
+    fn decode_header(bytes: &[u8], extended: bool) -> Vlr {
+        let mut decoder = Decoder::new(bytes);
+        Vlr {
+            reserved: decoder.u16(),
+            user_id: decoder.array(),
+            record_id: decoder.u16(),
+            record_length_after_header: if extended {
+                RecordLength::Evlr(decoder.u64())
+            } else {
+                RecordLength::Vlr(decoder.u16())
+            },
+            description: decoder.array(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Reads the data of the record, replacing whatever data it had.
+    #[cfg(feature = "std")]
+    pub(crate) fn read_data_from<R: Read>(&mut self, read: R) -> Result<()> {
+        let len = u64::from(self.record_length_after_header);
+        self.data.clear();
+        let n = read.take(len).read_to_end(&mut self.data)?;
+        if (n as u64) < len {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(())
+    }
+
     /// Writes a raw VLR.
     ///
     /// # Examples
@@ -99,19 +167,61 @@ impl Vlr {
     /// let vlr = Vlr::default();
     /// vlr.write_to(cursor).unwrap();
     /// ```
+    #[cfg(feature = "std")]
     pub fn write_to<W: Write>(&self, mut write: W) -> Result<()> {
-        use byteorder::{LittleEndian, WriteBytesExt};
-        write.write_u16::<LittleEndian>(self.reserved)?;
-        write.write_all(&self.user_id)?;
-        write.write_u16::<LittleEndian>(self.record_id)?;
-        match self.record_length_after_header {
-            RecordLength::Vlr(n) => write.write_u16::<LittleEndian>(n)?,
-            RecordLength::Evlr(n) => write.write_u64::<LittleEndian>(n)?,
-        }
-        write.write_all(&self.description)?;
+        let mut buffer = [0; MAX_HEADER_LEN];
+        let buffer = &mut buffer[..self.header_len()];
+        self.encode_header(buffer);
+        write.write_all(buffer)?;
         write.write_all(&self.data)?;
         Ok(())
     }
+
+    /// Writes this raw VLR or EVLR to a new vector of bytes.
+    ///
+    /// The output is exactly what [Vlr::write_to] would write.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::Vlr;
+    /// assert_eq!(54, Vlr::default().to_bytes().unwrap().len());
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![0; self.header_len() + self.data.len()];
+        let (header, data) = bytes.split_at_mut(self.header_len());
+        self.encode_header(header);
+        data.copy_from_slice(&self.data);
+        Ok(bytes)
+    }
+
+    fn header_len(&self) -> usize {
+        header_len(matches!(
+            self.record_length_after_header,
+            RecordLength::Evlr(_)
+        ))
+    }
+
+    fn encode_header(&self, bytes: &mut [u8]) {
+        let mut encoder = Encoder::new(bytes);
+        encoder.u16(self.reserved);
+        encoder.bytes(&self.user_id);
+        encoder.u16(self.record_id);
+        match self.record_length_after_header {
+            RecordLength::Vlr(n) => encoder.u16(n),
+            RecordLength::Evlr(n) => encoder.u64(n),
+        }
+        encoder.bytes(&self.description);
+    }
+}
+
+/// Returns the length of the standard part of a vlr or evlr.
+fn header_len(extended: bool) -> usize {
+    if extended {
+        MAX_HEADER_LEN
+    } else {
+        54
+    }
 }
 
 impl From<RecordLength> for u64 {
@@ -135,7 +245,7 @@ impl Default for RecordLength {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
     use std::io::Cursor;
@@ -160,4 +270,28 @@ mod tests {
         cursor.set_position(0);
         assert_eq!(evlr, Vlr::read_from(cursor, true).unwrap());
     }
+
+    #[test]
+    fn bytes_roundtrip() {
+        for extended in [false, true] {
+            let vlr = Vlr {
+                record_length_after_header: if extended {
+                    RecordLength::Evlr(3)
+                } else {
+                    RecordLength::Vlr(3)
+                },
+                data: vec![1, 2, 3],
+                ..Default::default()
+            };
+            let bytes = vlr.to_bytes().unwrap();
+            assert_eq!(vlr, Vlr::from_bytes(&bytes, extended).unwrap());
+            match Vlr::from_bytes(&bytes[..bytes.len() - 1], extended).unwrap_err() {
+                Error::VlrTooShort { expected, len } => {
+                    assert_eq!(bytes.len() as u64, expected);
+                    assert_eq!(bytes.len() - 1, len);
+                }
+                err => panic!("unexpected error: {err}"),
+            }
+        }
+    }
 }
@@ -0,0 +1,220 @@
+use crate::{Header, Result};
+#[cfg(feature = "digest")]
+use sha2::{Digest as _, Sha256};
+
+/// Caps on how much [Reader::read_budgeted](crate::Reader::read_budgeted) reads at once.
+///
+/// Bytes are counted as decompressed point records, so they're the same for a las file and the
+/// laz file made from it. The default has no caps, and reads every remaining point.
+///
+/// # Examples
+///
+/// ```
+/// use las::reader::Budget;
+/// let budget = Budget::default().points(2_000_000).bytes(100 << 20);
+/// assert_eq!(Some(2_000_000), budget.max_points());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Budget {
+    points: Option<u64>,
+    bytes: Option<u64>,
+}
+
+/// Where a [budgeted read](crate::Reader::read_budgeted) stopped, to pick up from with
+/// [Reader::resume](crate::Reader::resume).
+///
+/// With the `digest` feature, tokens hold a fingerprint of the header they came from, so they only
+/// resume readers over the same file.
+///
+/// # Examples
+///
+/// ```
+/// use las::{reader::Budget, Reader};
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let (_, token) = reader.read_budgeted(Budget::default().points(10)).unwrap();
+/// assert_eq!(10, token.index());
+/// assert!(!token.is_finished());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResumeToken {
+    index: u64,
+    pub(super) chunk: Option<(usize, u64)>,
+    finished: bool,
+    #[cfg(feature = "digest")]
+    fingerprint: u64,
+}
+
+impl Budget {
+    /// Caps the number of points read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Budget;
+    /// assert_eq!(Some(10), Budget::default().points(10).max_points());
+    /// ```
+    pub fn points(mut self, points: u64) -> Budget {
+        self.points = Some(points);
+        self
+    }
+
+    /// Caps the number of decompressed point record bytes read.
+    ///
+    /// At least one point is read, even if its record is larger than the cap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Budget;
+    /// assert_eq!(Some(1 << 20), Budget::default().bytes(1 << 20).max_bytes());
+    /// ```
+    pub fn bytes(mut self, bytes: u64) -> Budget {
+        self.bytes = Some(bytes);
+        self
+    }
+
+    /// Returns the cap on points, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Budget;
+    /// assert_eq!(None, Budget::default().max_points());
+    /// ```
+    pub fn max_points(&self) -> Option<u64> {
+        self.points
+    }
+
+    /// Returns the cap on bytes, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Budget;
+    /// assert_eq!(None, Budget::default().max_bytes());
+    /// ```
+    pub fn max_bytes(&self) -> Option<u64> {
+        self.bytes
+    }
+
+    /// Returns the number of points that fit, for records of `record_length` bytes.
+    pub(super) fn limit(&self, record_length: u64) -> u64 {
+        let by_bytes = self
+            .bytes
+            .map(|bytes| (bytes / record_length.max(1)).max(1))
+            .unwrap_or(u64::MAX);
+        self.points.unwrap_or(u64::MAX).min(by_bytes)
+    }
+}
+
+impl ResumeToken {
+    pub(super) fn new(
+        header: &Header,
+        index: u64,
+        chunk: Option<(usize, u64)>,
+    ) -> Result<ResumeToken> {
+        Ok(ResumeToken {
+            index,
+            chunk,
+            finished: index >= header.number_of_points(),
+            #[cfg(feature = "digest")]
+            fingerprint: fingerprint(header)?,
+        })
+    }
+
+    /// Returns the index of the next point to read, counting withheld points that were skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::Budget, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let (_, token) = reader.read_budgeted(Budget::default()).unwrap();
+    /// assert_eq!(106, token.index());
+    /// ```
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns the laz chunk that holds the next point, or `None` if the points aren't compressed
+    /// or there are none left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::Budget, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let (_, token) = reader.read_budgeted(Budget::default().points(10)).unwrap();
+    /// assert_eq!(None, token.chunk());
+    /// ```
+    pub fn chunk(&self) -> Option<usize> {
+        self.chunk.map(|(chunk, _)| chunk)
+    }
+
+    /// Returns how far into its [chunk](ResumeToken::chunk) the next point is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::Budget, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let (_, token) = reader.read_budgeted(Budget::default().points(10)).unwrap();
+    /// assert_eq!(None, token.offset_in_chunk());
+    /// ```
+    pub fn offset_in_chunk(&self) -> Option<u64> {
+        self.chunk.map(|(_, offset)| offset)
+    }
+
+    /// Returns true if there were no points left to read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::Budget, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let (_, token) = reader.read_budgeted(Budget::default()).unwrap();
+    /// assert!(token.is_finished());
+    /// ```
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns true if this token came from a reader with this header.
+    ///
+    /// Without the `digest` feature, there's no fingerprint to check, so every token matches.
+    #[cfg(feature = "digest")]
+    pub(super) fn matches(&self, header: &Header) -> Result<bool> {
+        Ok(self.fingerprint == fingerprint(header)?)
+    }
+
+    #[cfg(not(feature = "digest"))]
+    pub(super) fn matches(&self, _: &Header) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+/// Hashes the raw header, which has the counts, bounds, offsets, and guid, so two files are
+/// unlikely to share a fingerprint.
+#[cfg(feature = "digest")]
+fn fingerprint(header: &Header) -> Result<u64> {
+    let mut bytes = Vec::new();
+    header.clone().into_raw()?.write_to(&mut bytes)?;
+    let digest = Sha256::digest(bytes);
+    Ok(u64::from_le_bytes(digest[..8].try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit() {
+        assert_eq!(u64::MAX, Budget::default().limit(20));
+        assert_eq!(5, Budget::default().points(5).limit(20));
+        assert_eq!(3, Budget::default().points(5).bytes(60).limit(20));
+        assert_eq!(1, Budget::default().bytes(10).limit(20));
+        assert_eq!(0, Budget::default().points(0).bytes(100).limit(20));
+    }
+}
@@ -0,0 +1,135 @@
+use super::{ReadPoints, SkippedChunk};
+use crate::{compatibility::Layout, raw, Header, Point, Result, Transform, Vector};
+use laz::laszip::ChunkTable;
+
+/// The transforms that keep a record's integer coordinates as they are.
+const IDENTITY: Vector<Transform> = Vector {
+    x: Transform {
+        scale: 1.,
+        offset: 0.,
+    },
+    y: Transform {
+        scale: 1.,
+        offset: 0.,
+    },
+    z: Transform {
+        scale: 1.,
+        offset: 0.,
+    },
+};
+
+/// Reads the legacy points of a file that was written in laszip's las 1.4 compatibility mode as
+/// the extended points they came from.
+pub(crate) struct PointReader<P> {
+    inner: P,
+    header: Header,
+    layout: Layout,
+    record: Vec<u8>,
+}
+
+impl<P: ReadPoints> PointReader<P> {
+    pub(crate) fn new(inner: P, header: Header, layout: Layout) -> PointReader<P> {
+        PointReader {
+            record: vec![0; usize::from(inner.header().point_format().len())],
+            inner,
+            header,
+            layout,
+        }
+    }
+
+    fn restore_all(&self, points: &mut [Point]) -> Result<()> {
+        for point in points {
+            self.layout.restore_point(point)?;
+        }
+        Ok(())
+    }
+}
+
+impl<P: ReadPoints> ReadPoints for PointReader<P> {
+    fn read_point(&mut self) -> Result<Option<Point>> {
+        let mut point = self.inner.read_point()?;
+        if let Some(point) = &mut point {
+            self.layout.restore_point(point)?;
+        }
+        Ok(point)
+    }
+
+    fn read_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        let read = self.inner.read_point_into(point)?;
+        if read {
+            self.layout.restore_point(point)?;
+        }
+        Ok(read)
+    }
+
+    fn read_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        let start = points.len();
+        let read = self.inner.read_points(n, points)?;
+        self.restore_all(&mut points[start..])?;
+        Ok(read)
+    }
+
+    fn read_record(&mut self, record: &mut [u8]) -> Result<bool> {
+        if !self.inner.read_record(&mut self.record)? {
+            return Ok(false);
+        }
+        let raw_point =
+            raw::Point::read_from(self.record.as_slice(), self.inner.header().point_format())?;
+        let mut point = Point::new(raw_point, &IDENTITY);
+        self.layout.restore_point(&mut point)?;
+        point
+            .into_raw(&IDENTITY)?
+            .write_to(record, self.header.point_format())?;
+        Ok(true)
+    }
+
+    fn seek(&mut self, index: u64) -> Result<()> {
+        self.inner.seek(index)
+    }
+
+    fn skip(&mut self, n: u64) -> Result<()> {
+        self.inner.skip(n)
+    }
+
+    fn index(&self) -> u64 {
+        self.inner.index()
+    }
+
+    fn point_offset(&self, _: u64) -> Option<u64> {
+        None
+    }
+
+    fn header(&self) -> &Header {
+        &self.header
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        self.inner.read_at(offset, buf)
+    }
+
+    fn chunk_table(&mut self) -> Result<(u64, ChunkTable)> {
+        self.inner.chunk_table()
+    }
+
+    fn seek_in_chunk(&mut self, chunk_index: usize, offset: u64) -> Result<Option<u64>> {
+        self.inner.seek_in_chunk(chunk_index, offset)
+    }
+
+    fn skipped_chunks(&self) -> &[SkippedChunk] {
+        self.inner.skipped_chunks()
+    }
+
+    fn missing_chunk_table(&self) -> bool {
+        self.inner.missing_chunk_table()
+    }
+
+    fn number_of_chunks(&mut self) -> Result<usize> {
+        self.inner.number_of_chunks()
+    }
+
+    fn read_chunk(&mut self, chunk_index: usize) -> Result<Vec<Point>> {
+        let mut points = self.inner.read_chunk(chunk_index)?;
+        self.restore_all(&mut points)?;
+        Ok(points)
+    }
+}
@@ -0,0 +1,340 @@
+use super::{OwnedPoints, Reader};
+use crate::{crs::Crs, Bounds, Error, Header, Point, Result};
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+/// Several las or laz files that are read as one point cloud.
+///
+/// Only the headers are read up front. Each file is opened when its points are needed, so a
+/// dataset can span more files than could be open at once. Unlike a
+/// [MultiReader](super::MultiReader), the files don't need to share a point layout.
+///
+/// # Examples
+///
+/// ```
+/// use las::reader::Dataset;
+///
+/// let dataset = Dataset::from_paths(["tests/data/autzen.las", "tests/data/autzen.las"]).unwrap();
+/// assert_eq!(212, dataset.number_of_points());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Dataset {
+    paths: Vec<PathBuf>,
+    headers: Vec<Header>,
+}
+
+impl Dataset {
+    /// Creates a dataset from the las and laz files in a directory, sorted by path.
+    ///
+    /// Files are picked by their extension, ignoring case. Subdirectories aren't searched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Dataset;
+    /// let dataset = Dataset::open_dir("tests/data").unwrap();
+    /// assert!(dataset.paths().iter().any(|path| path.ends_with("autzen.las")));
+    /// ```
+    pub fn open_dir<P: AsRef<Path>>(dir: P) -> Result<Dataset> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_las = path.extension().is_some_and(|extension| {
+                extension.eq_ignore_ascii_case("las") || extension.eq_ignore_ascii_case("laz")
+            });
+            if is_las && path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Dataset::from_paths(paths)
+    }
+
+    /// Creates a dataset from a list of files, which are read in the order they're given.
+    ///
+    /// Returns [Error::NoInputs] if there are no files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Dataset;
+    /// let dataset = Dataset::from_paths(["tests/data/autzen.las"]).unwrap();
+    /// assert!(Dataset::from_paths(Vec::<&str>::new()).is_err());
+    /// ```
+    pub fn from_paths<I, P>(paths: I) -> Result<Dataset>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut dataset = Dataset {
+            paths: Vec::new(),
+            headers: Vec::new(),
+        };
+        for path in paths {
+            let path = path.as_ref();
+            let header = Header::new(BufReader::new(File::open(path)?))?;
+            dataset.paths.push(path.to_path_buf());
+            dataset.headers.push(header);
+        }
+        if dataset.paths.is_empty() {
+            return Err(Error::NoInputs);
+        }
+        Ok(dataset)
+    }
+
+    /// Returns the paths of the files, in the order they're read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Dataset;
+    /// let dataset = Dataset::from_paths(["tests/data/autzen.las"]).unwrap();
+    /// assert_eq!(1, dataset.paths().len());
+    /// ```
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Returns the headers of the files, in the same order as the paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Dataset;
+    /// let dataset = Dataset::from_paths(["tests/data/autzen.las"]).unwrap();
+    /// assert_eq!(106, dataset.headers()[0].number_of_points());
+    /// ```
+    pub fn headers(&self) -> &[Header] {
+        &self.headers
+    }
+
+    /// Returns the number of points in all of the files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Dataset;
+    /// let dataset = Dataset::from_paths(["tests/data/autzen.las"]).unwrap();
+    /// assert_eq!(106, dataset.number_of_points());
+    /// ```
+    pub fn number_of_points(&self) -> u64 {
+        self.headers.iter().map(Header::number_of_points).sum()
+    }
+
+    /// Returns bounds that cover the header bounds of every file with points.
+    ///
+    /// If no file has points, the bounds are the default, empty ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Dataset;
+    /// let dataset = Dataset::from_paths(["tests/data/autzen.las"]).unwrap();
+    /// assert_eq!(dataset.headers()[0].bounds(), dataset.bounds());
+    /// ```
+    pub fn bounds(&self) -> Bounds {
+        let mut bounds = Bounds::default();
+        for header in self
+            .headers
+            .iter()
+            .filter(|header| header.number_of_points() > 0)
+        {
+            let other = header.bounds();
+            bounds.min.x = bounds.min.x.min(other.min.x);
+            bounds.min.y = bounds.min.y.min(other.min.y);
+            bounds.min.z = bounds.min.z.min(other.min.z);
+            bounds.max.x = bounds.max.x.max(other.max.x);
+            bounds.max.y = bounds.max.y.max(other.max.y);
+            bounds.max.z = bounds.max.z.max(other.max.z);
+        }
+        bounds
+    }
+
+    /// Returns the coordinate reference system that all of the files share.
+    ///
+    /// Returns [Error::CrsConflict] with the index of the first file whose crs is different from
+    /// the first file's, including when only one of them has a crs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Dataset;
+    /// let dataset = Dataset::from_paths(["tests/data/autzen.las", "tests/data/autzen.las"]).unwrap();
+    /// assert_eq!(2994, dataset.crs().unwrap().unwrap().epsg().unwrap().horizontal);
+    /// ```
+    pub fn crs(&self) -> Result<Option<Crs>> {
+        let first = self.headers[0].crs();
+        for (index, header) in self.headers.iter().enumerate().skip(1) {
+            if header.crs() != first {
+                return Err(Error::CrsConflict(index));
+            }
+        }
+        Ok(first)
+    }
+
+    /// Returns an iterator over the points of every file, one file after another.
+    ///
+    /// If a file can't be opened, the error is returned once and iteration moves on to the next
+    /// file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Dataset;
+    /// let dataset = Dataset::from_paths(["tests/data/autzen.las", "tests/data/autzen.las"]).unwrap();
+    /// assert_eq!(212, dataset.points().count());
+    /// ```
+    pub fn points(&self) -> DatasetPoints<'_> {
+        DatasetPoints {
+            paths: &self.paths,
+            points: None,
+        }
+    }
+
+    /// Reads the points inside of the bounds from every file.
+    ///
+    /// Files whose header bounds don't intersect the query bounds aren't opened. The rest are
+    /// read with [Reader::points_in_bounds], so their spatial indices are used if they have any.
+    /// Points are returned file by file, in the order they appear in each file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::Dataset;
+    ///
+    /// let dataset = Dataset::from_paths(["tests/data/autzen.las", "tests/data/autzen.las"]).unwrap();
+    /// let mut bounds = dataset.bounds();
+    /// bounds.max.x = (bounds.min.x + bounds.max.x) / 2.;
+    /// let points = dataset.query(&bounds).unwrap();
+    /// assert!(points.iter().all(|point| point.x <= bounds.max.x));
+    /// ```
+    pub fn query(&self, bounds: &Bounds) -> Result<Vec<Point>> {
+        let mut points = Vec::new();
+        for path in self.paths_in_bounds(bounds) {
+            let mut reader = Reader::from_path(path)?;
+            points.extend(reader.points_in_bounds(*bounds)?);
+        }
+        Ok(points)
+    }
+
+    /// Returns the paths of the files whose header bounds intersect the bounds.
+    ///
+    /// Files without points never intersect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::Dataset, Bounds};
+    ///
+    /// let dataset = Dataset::from_paths(["tests/data/autzen.las"]).unwrap();
+    /// assert_eq!(1, dataset.paths_in_bounds(&dataset.bounds()).count());
+    /// assert_eq!(0, dataset.paths_in_bounds(&Bounds::default()).count());
+    /// ```
+    pub fn paths_in_bounds<'a>(&'a self, bounds: &'a Bounds) -> impl Iterator<Item = &'a Path> {
+        self.paths
+            .iter()
+            .zip(&self.headers)
+            .filter(|(_, header)| {
+                header.number_of_points() > 0 && intersects(&header.bounds(), bounds)
+            })
+            .map(|(path, _)| path.as_path())
+    }
+}
+
+/// An iterator over the points of every file in a [Dataset].
+///
+/// This struct is created by [Dataset::points].
+#[allow(missing_debug_implementations)]
+pub struct DatasetPoints<'a> {
+    paths: &'a [PathBuf],
+    points: Option<OwnedPoints>,
+}
+
+impl Iterator for DatasetPoints<'_> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(points) = &mut self.points {
+                match points.next() {
+                    Some(result) => return Some(result),
+                    None => self.points = None,
+                }
+            }
+            let (path, rest) = self.paths.split_first()?;
+            self.paths = rest;
+            match Reader::from_path(path) {
+                Ok(reader) => self.points = Some(reader.into_point_iter()),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+fn intersects(a: &Bounds, b: &Bounds) -> bool {
+    a.min.x <= b.max.x
+        && b.min.x <= a.max.x
+        && a.min.y <= b.max.y
+        && b.min.y <= a.max.y
+        && a.min.z <= b.max.z
+        && b.min.z <= a.max.z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dataset;
+    use crate::{Bounds, Error, Vector};
+
+    #[test]
+    fn skips_files_outside_query() {
+        let dataset = Dataset::from_paths(["tests/data/autzen.las"]).unwrap();
+        let bounds = dataset.bounds();
+        let outside = Bounds {
+            min: Vector {
+                x: bounds.max.x + 1.,
+                ..bounds.min
+            },
+            max: Vector {
+                x: bounds.max.x + 2.,
+                ..bounds.max
+            },
+        };
+        assert_eq!(0, dataset.paths_in_bounds(&outside).count());
+        assert!(dataset.query(&outside).unwrap().is_empty());
+        assert_eq!(106, dataset.query(&bounds).unwrap().len());
+    }
+
+    #[test]
+    fn crs_conflict() {
+        let dataset =
+            Dataset::from_paths(["tests/data/autzen.las", "tests/data/waveform.las"]).unwrap();
+        assert!(matches!(dataset.crs(), Err(Error::CrsConflict(1))));
+    }
+
+    #[test]
+    fn open_dir() {
+        let dir = std::env::temp_dir().join("las-rs-dataset-open-dir");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let _ = std::fs::copy("tests/data/autzen.las", dir.join("b.LAS")).unwrap();
+        let _ = std::fs::copy("tests/data/autzen.las", dir.join("a.las")).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a las file").unwrap();
+        let dataset = Dataset::open_dir(&dir).unwrap();
+        assert_eq!(vec![dir.join("a.las"), dir.join("b.LAS")], dataset.paths());
+        assert_eq!(212, dataset.points().count());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_file_is_an_error_once() {
+        let mut dataset = Dataset::from_paths(["tests/data/autzen.las"]).unwrap();
+        dataset.paths.insert(0, "tests/data/missing.las".into());
+        let results = dataset.points().collect::<Vec<_>>();
+        assert_eq!(107, results.len());
+        assert!(results[0].is_err());
+        assert!(results[1..].iter().all(|result| result.is_ok()));
+    }
+}
@@ -0,0 +1,158 @@
+use super::{PointReader, ReadPoints};
+use crate::{rng::Rng, Point, Result};
+use std::{
+    fs::File,
+    io::{BufReader, Seek},
+};
+
+/// Which points [Reader::points_decimated](super::Reader::points_decimated) keeps.
+///
+/// Points are picked by their index in the file, so the same points are picked no matter where
+/// reading starts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Decimation {
+    /// Every nth point, starting with the first.
+    ///
+    /// `EveryNth(0)` is the same as `EveryNth(1)`.
+    EveryNth(u64),
+
+    /// A pseudo-random fraction of the points.
+    ///
+    /// The same seed always picks the same points.
+    Fraction {
+        /// The chance that each point is kept, from zero to one.
+        fraction: f64,
+
+        /// The seed for the random picks.
+        seed: u64,
+    },
+
+    /// At most this many points, spread evenly through the file.
+    MaxPoints(u64),
+}
+
+impl Decimation {
+    /// Returns the first index at or after `index` that this decimation keeps.
+    fn next_index(&self, index: u64, number_of_points: u64) -> Option<u64> {
+        let next = match *self {
+            Decimation::EveryNth(n) => {
+                let n = n.max(1);
+                index.div_ceil(n).checked_mul(n)?
+            }
+            Decimation::Fraction { fraction, seed } => {
+                if fraction <= 0. {
+                    return None;
+                }
+                (index..number_of_points).find(|&i| random(seed, i) < fraction)?
+            }
+            Decimation::MaxPoints(0) => return None,
+            Decimation::MaxPoints(max) => {
+                if max >= number_of_points {
+                    index
+                } else {
+                    // The ith of `max` picks is point `i * number_of_points / max`.
+                    let (index, max, number_of_points) = (
+                        u128::from(index),
+                        u128::from(max),
+                        u128::from(number_of_points),
+                    );
+                    let i = (index * max).div_ceil(number_of_points);
+                    u64::try_from(i * number_of_points / max).ok()?
+                }
+            }
+        };
+        (next < number_of_points).then_some(next)
+    }
+}
+
+/// Returns a number in `[0, 1)` that depends only on the seed and the index.
+fn random(seed: u64, index: u64) -> f64 {
+    let n = Rng::new(seed ^ Rng::new(index).next_u64()).next_u64();
+    (n >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// An iterator over a decimated subset of the points in a `Reader`.
+///
+/// This struct is generally created by calling `points_decimated()` on `Reader`.
+#[allow(missing_debug_implementations)]
+pub struct DecimatedPointIterator<'a, R: std::io::Read + Seek + Send = BufReader<File>> {
+    pub(super) point_reader: &'a mut PointReader<R>,
+    pub(super) decimation: Decimation,
+}
+
+impl<R: std::io::Read + Seek + Send> Iterator for DecimatedPointIterator<'_, R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.point_reader.index();
+        let number_of_points = self.point_reader.header().number_of_points();
+        let next = self.decimation.next_index(index, number_of_points)?;
+        if let Err(err) = self.point_reader.skip(next - index) {
+            return Some(Err(err));
+        }
+        self.point_reader.read_point().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indices(decimation: Decimation, number_of_points: u64) -> Vec<u64> {
+        let mut indices = Vec::new();
+        let mut index = 0;
+        while let Some(next) = decimation.next_index(index, number_of_points) {
+            indices.push(next);
+            index = next + 1;
+        }
+        indices
+    }
+
+    #[test]
+    fn every_nth() {
+        assert_eq!(vec![0, 3, 6, 9], indices(Decimation::EveryNth(3), 11));
+        assert_eq!(vec![0, 1, 2], indices(Decimation::EveryNth(0), 3));
+        assert_eq!(vec![0], indices(Decimation::EveryNth(u64::MAX), 3));
+        assert_eq!(Some(6), Decimation::EveryNth(3).next_index(4, 11));
+    }
+
+    #[test]
+    fn max_points() {
+        assert_eq!(
+            vec![0, 3, 6, 10, 13, 16],
+            indices(Decimation::MaxPoints(6), 20)
+        );
+        assert_eq!(vec![0, 1, 2], indices(Decimation::MaxPoints(5), 3));
+        assert!(indices(Decimation::MaxPoints(0), 3).is_empty());
+        assert_eq!(1000, indices(Decimation::MaxPoints(1000), 1_000_003).len());
+    }
+
+    #[test]
+    fn fraction() {
+        let decimation = Decimation::Fraction {
+            fraction: 0.25,
+            seed: 42,
+        };
+        let picked = indices(decimation, 10_000);
+        assert_eq!(picked, indices(decimation, 10_000));
+        assert!((2300..2700).contains(&picked.len()));
+        let other = indices(
+            Decimation::Fraction {
+                fraction: 0.25,
+                seed: 43,
+            },
+            10_000,
+        );
+        assert_ne!(picked, other);
+        let none = Decimation::Fraction {
+            fraction: 0.,
+            seed: 42,
+        };
+        assert!(indices(none, 100).is_empty());
+        let all = Decimation::Fraction {
+            fraction: 1.,
+            seed: 42,
+        };
+        assert_eq!(100, indices(all, 100).len());
+    }
+}
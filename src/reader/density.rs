@@ -0,0 +1,397 @@
+use crate::{Bounds, Error, Point, Result};
+
+/// The most cells a density grid can have, which is two gigabytes of counts.
+const MAX_CELLS: usize = 1 << 28;
+
+/// Options for [Reader::density_grid_with_options](crate::Reader::density_grid_with_options).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DensityGridOptions {
+    last_returns_only: bool,
+}
+
+/// Point counts on a regular grid over a file's xy bounds.
+///
+/// The grid is north-up, like a raster: the origin is the upper left corner, `(min x, max y)`,
+/// columns go east, and rows go south. Counts are stored row by row.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DensityGrid {
+    origin: (f64, f64),
+    cell_size: f64,
+    width: usize,
+    height: usize,
+    counts: Vec<u64>,
+    points_outside: u64,
+}
+
+impl DensityGridOptions {
+    /// Counts only last returns, the points whose return number is their number of returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::DensityGridOptions, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let options = DensityGridOptions::default().last_returns_only();
+    /// let grid = reader.density_grid_with_options(10., options).unwrap();
+    /// assert!(grid.counts().iter().sum::<u64>() < 106);
+    /// ```
+    pub fn last_returns_only(mut self) -> DensityGridOptions {
+        self.last_returns_only = true;
+        self
+    }
+
+    pub(crate) fn counts(&self, point: &Point) -> bool {
+        !self.last_returns_only || point.return_number == point.number_of_returns
+    }
+}
+
+impl DensityGrid {
+    /// Creates an empty grid that covers the xy extent of the bounds.
+    ///
+    /// There's always at least one row and one column, unless the bounds are empty. Returns an
+    /// error if the grid would have more than [MAX_CELLS] cells.
+    pub(crate) fn new(bounds: &Bounds, cell_size: f64) -> Result<DensityGrid> {
+        let cells = |min: f64, max: f64| {
+            if min <= max {
+                (((max - min) / cell_size).ceil() as usize).max(1)
+            } else {
+                0
+            }
+        };
+        let width = cells(bounds.min.x, bounds.max.x);
+        let height = cells(bounds.min.y, bounds.max.y);
+        let len = width
+            .checked_mul(height)
+            .filter(|&len| len <= MAX_CELLS)
+            .ok_or(Error::DensityGridTooLarge { width, height })?;
+        Ok(DensityGrid {
+            origin: (bounds.min.x, bounds.max.y),
+            cell_size,
+            width,
+            height,
+            counts: vec![0; len],
+            points_outside: 0,
+        })
+    }
+
+    /// Counts a point in its cell.
+    ///
+    /// Points on the east or south edge go in the last column or row.
+    pub(crate) fn add(&mut self, point: &Point) {
+        let column = (point.x - self.origin.0) / self.cell_size;
+        let row = (self.origin.1 - point.y) / self.cell_size;
+        let index = |n: f64, len: usize| {
+            let len = len as f64;
+            if (0. ..=len).contains(&n) {
+                Some(n.floor().min(len - 1.) as usize)
+            } else {
+                None
+            }
+        };
+        match (index(column, self.width), index(row, self.height)) {
+            (Some(column), Some(row)) => self.counts[row * self.width + column] += 1,
+            _ => self.points_outside += 1,
+        }
+    }
+
+    /// Returns the upper left corner of the grid, as `(x, y)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let bounds = reader.header().bounds();
+    /// let grid = reader.density_grid(10.).unwrap();
+    /// assert_eq!((bounds.min.x, bounds.max.y), grid.origin());
+    /// ```
+    pub fn origin(&self) -> (f64, f64) {
+        self.origin
+    }
+
+    /// Returns the width and height of each cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(10., reader.density_grid(10.).unwrap().cell_size());
+    /// ```
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
+    }
+
+    /// Returns the number of columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let grid = reader.density_grid(10.).unwrap();
+    /// assert_eq!(grid.counts().len(), grid.width() * grid.height());
+    /// ```
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert!(reader.density_grid(10.).unwrap().height() > 0);
+    /// ```
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the point counts, row by row from the north.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let grid = reader.density_grid(10.).unwrap();
+    /// assert_eq!(106, grid.counts().iter().sum::<u64>());
+    /// ```
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Returns the point count of one cell, or `None` if it's outside the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let grid = reader.density_grid(10.).unwrap();
+    /// assert_eq!(Some(grid.counts()[0]), grid.count(0, 0));
+    /// assert_eq!(None, grid.count(grid.height(), 0));
+    /// ```
+    pub fn count(&self, row: usize, column: usize) -> Option<u64> {
+        if row < self.height && column < self.width {
+            Some(self.counts[row * self.width + column])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of points that weren't counted because they're outside the header's
+    /// bounds.
+    ///
+    /// This is zero unless the header's bounds are wrong.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(0, reader.density_grid(10.).unwrap().points_outside());
+    /// ```
+    pub fn points_outside(&self) -> u64 {
+        self.points_outside
+    }
+
+    /// Returns the density of one cell in points per square unit, or `None` if it's outside the
+    /// grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let grid = reader.density_grid(10.).unwrap();
+    /// assert_eq!(grid.count(0, 0).unwrap() as f64 / 100., grid.density(0, 0).unwrap());
+    /// ```
+    pub fn density(&self, row: usize, column: usize) -> Option<f64> {
+        self.count(row, column).map(|count| self.to_density(count))
+    }
+
+    /// Returns the lowest density of any cell, or `None` if the grid has no cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let grid = reader.density_grid(10.).unwrap();
+    /// assert!(grid.min_density().unwrap() <= grid.mean_density().unwrap());
+    /// ```
+    pub fn min_density(&self) -> Option<f64> {
+        self.counts
+            .iter()
+            .min()
+            .map(|&count| self.to_density(count))
+    }
+
+    /// Returns the highest density of any cell, or `None` if the grid has no cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let grid = reader.density_grid(10.).unwrap();
+    /// assert!(grid.max_density().unwrap() >= grid.mean_density().unwrap());
+    /// ```
+    pub fn max_density(&self) -> Option<f64> {
+        self.counts
+            .iter()
+            .max()
+            .map(|&count| self.to_density(count))
+    }
+
+    /// Returns the mean density over every cell, empty ones included, or `None` if the grid has
+    /// no cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert!(reader.density_grid(10.).unwrap().mean_density().unwrap() > 0.);
+    /// ```
+    pub fn mean_density(&self) -> Option<f64> {
+        if self.counts.is_empty() {
+            None
+        } else {
+            let total: u64 = self.counts.iter().sum();
+            Some(self.to_density(total) / self.counts.len() as f64)
+        }
+    }
+
+    fn to_density(&self, count: u64) -> f64 {
+        count as f64 / (self.cell_size * self.cell_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{reader::DensityGridOptions, synthetic::fixture, Builder, Error, Point, Reader};
+    use std::io::Cursor;
+
+    /// Four points per square unit on a 10 by 6 extent, with every other point a last return.
+    fn uniform() -> Reader<Cursor<Vec<u8>>> {
+        let mut points = Vec::new();
+        for i in 0..20 {
+            for j in 0..12 {
+                points.push(Point {
+                    x: 0.25 + 0.5 * f64::from(i),
+                    y: 0.25 + 0.5 * f64::from(j),
+                    return_number: 1,
+                    number_of_returns: if (i + j) % 2 == 0 { 1 } else { 2 },
+                    ..Default::default()
+                });
+            }
+        }
+        // The corners, exactly on the bounds.
+        for (x, y) in [(0., 0.), (10., 6.), (10., 0.), (0., 6.)] {
+            points.push(Point {
+                x,
+                y,
+                ..Default::default()
+            });
+        }
+        fixture::reader(fixture::write(Builder::default(), points))
+    }
+
+    #[test]
+    fn uniform_density() {
+        let grid = uniform().density_grid(2.).unwrap();
+        assert_eq!((0., 6.), grid.origin());
+        assert_eq!(5, grid.width());
+        assert_eq!(3, grid.height());
+        assert_eq!(0, grid.points_outside());
+        assert_eq!(244, grid.counts().iter().sum::<u64>());
+        for row in 0..3 {
+            for column in 0..5 {
+                let corners = match (row, column) {
+                    (0 | 2, 0 | 4) => 1,
+                    _ => 0,
+                };
+                assert_eq!(Some(16 + corners), grid.count(row, column));
+            }
+        }
+        assert_eq!(Some(4.), grid.min_density());
+        assert_eq!(Some(4.25), grid.max_density());
+        assert_eq!(Some(244. / 15. / 4.), grid.mean_density());
+    }
+
+    #[test]
+    fn max_edge_in_last_cell() {
+        let grid = uniform().density_grid(2.).unwrap();
+        // (10, 0) is the south east corner, and (0, 0) the south west.
+        assert_eq!(Some(17), grid.count(2, 4));
+        assert_eq!(Some(17), grid.count(2, 0));
+        assert_eq!(None, grid.count(3, 0));
+        assert_eq!(None, grid.count(0, 5));
+    }
+
+    #[test]
+    fn last_returns_only() {
+        let options = DensityGridOptions::default().last_returns_only();
+        let grid = uniform().density_grid_with_options(2., options).unwrap();
+        // Half of the uniform points, and the corners, whose return number and number of returns
+        // are both zero.
+        assert_eq!(124, grid.counts().iter().sum::<u64>());
+        assert_eq!(Some(2.), grid.min_density());
+    }
+
+    #[test]
+    fn rereads_from_the_start() {
+        let mut reader = uniform();
+        let _ = reader.read_points(100).unwrap();
+        assert_eq!(
+            244,
+            reader
+                .density_grid(2.)
+                .unwrap()
+                .counts()
+                .iter()
+                .sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn invalid_cell_size() {
+        for cell_size in [0., -1., f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                uniform().density_grid(cell_size).unwrap_err(),
+                Error::InvalidCellSize(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn too_many_cells() {
+        for cell_size in [1e-4, f64::MIN_POSITIVE] {
+            assert!(matches!(
+                uniform().density_grid(cell_size).unwrap_err(),
+                Error::DensityGridTooLarge { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let mut reader = fixture::reader(fixture::write(Builder::default(), Vec::<Point>::new()));
+        let grid = reader.density_grid(1.).unwrap();
+        assert!(grid.counts().is_empty());
+        assert_eq!(None, grid.mean_density());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let grid = uniform().density_grid(2.).unwrap();
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(grid, serde_json::from_str(&json).unwrap());
+    }
+}
@@ -0,0 +1,237 @@
+use super::Reader;
+use crate::{raw, Result, Version, Vlr};
+use std::io::{Read, Seek, SeekFrom};
+
+/// The size of an evlr's standard part, before its data.
+const EVLR_HEADER_SIZE: u64 = 60;
+
+/// Reads one evlr's data straight from the file, without holding it in memory.
+///
+/// Created by [Reader::evlr_reader]. Each read seeks the reader's stream, so wrap this in a
+/// [BufReader](std::io::BufReader) for many small reads. Reading points in between is fine, since
+/// the point cursor doesn't move.
+#[allow(missing_debug_implementations)]
+pub struct EvlrReader<'a, R: Read + Seek> {
+    reader: &'a mut Reader<R>,
+    start: u64,
+    len: u64,
+    position: u64,
+}
+
+impl<R: Read + Seek + Send> EvlrReader<'_, R> {
+    /// Returns the number of bytes of data in the evlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Reader, Writer};
+    /// use std::io::{Cursor, Write};
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    /// let mut evlr = writer.begin_evlr("my software", 1, "").unwrap();
+    /// evlr.write_all(&[42; 100]).unwrap();
+    /// evlr.finish().unwrap();
+    ///
+    /// let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// let evlr = reader.evlr_reader("my software", 1).unwrap().unwrap();
+    /// assert_eq!(100, evlr.len());
+    /// ```
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the evlr has no data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Reader, Writer};
+    /// use std::io::Cursor;
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    /// writer.begin_evlr("my software", 1, "").unwrap().finish().unwrap();
+    ///
+    /// let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// assert!(reader.evlr_reader("my software", 1).unwrap().unwrap().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<R: Read + Seek + Send> Read for EvlrReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = buf
+            .len()
+            .min(usize::try_from(self.len.saturating_sub(self.position)).unwrap_or(usize::MAX));
+        self.reader
+            .read_at(self.start + self.position, &mut buf[..n])
+            .map_err(std::io::Error::other)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Read + Seek + Send> Seek for EvlrReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => self.len.checked_add_signed(n),
+            SeekFrom::Current(n) => self.position.checked_add_signed(n),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+impl<R: Read + Seek + Send> Reader<R> {
+    /// Returns a reader over the data of the first evlr with this user id and record id, or
+    /// `None` if the file doesn't have one.
+    ///
+    /// The evlrs are found from the file itself, so this works for evlrs that are too big to
+    /// hold in memory, which can be left out of the header with
+    /// [ReadOptions::max_evlr_len](super::ReadOptions::max_evlr_len).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::ReadOptions, Builder, Reader, Writer};
+    /// use std::io::{Cursor, Read, Write};
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    /// let mut evlr = writer.begin_evlr("my software", 1, "big record").unwrap();
+    /// evlr.write_all(b"lots of data").unwrap();
+    /// evlr.finish().unwrap();
+    ///
+    /// let options = ReadOptions::default().max_evlr_len(4);
+    /// let mut reader = Reader::with_options(writer.into_inner().unwrap(), options).unwrap();
+    /// assert!(reader.header().evlrs().is_empty());
+    /// let mut data = String::new();
+    /// reader
+    ///     .evlr_reader("my software", 1)
+    ///     .unwrap()
+    ///     .unwrap()
+    ///     .read_to_string(&mut data)
+    ///     .unwrap();
+    /// assert_eq!("lots of data", data);
+    /// ```
+    pub fn evlr_reader(
+        &mut self,
+        user_id: &str,
+        record_id: u16,
+    ) -> Result<Option<EvlrReader<'_, R>>> {
+        let version = self.header().version();
+        if version < Version::new(1, 4) {
+            return Ok(None);
+        }
+        // The evlr fields come right after the fields of a las 1.3 header.
+        let mut bytes = [0; 12];
+        self.read_at(u64::from(Version::new(1, 3).header_size()), &mut bytes)?;
+        let mut position = u64::from_le_bytes(bytes[..8].try_into().expect("eight bytes"));
+        let number_of_evlrs = u32::from_le_bytes(bytes[8..].try_into().expect("four bytes"));
+        let mut bytes = [0; EVLR_HEADER_SIZE as usize];
+        for _ in 0..number_of_evlrs {
+            self.read_at(position, &mut bytes)?;
+            let raw_vlr = raw::Vlr::read_header_from(bytes.as_slice(), true)?;
+            let len = u64::from(raw_vlr.record_length_after_header);
+            let vlr = Vlr::read_in(raw_vlr, version);
+            position += EVLR_HEADER_SIZE;
+            if vlr.user_id == user_id && vlr.record_id == record_id {
+                return Ok(Some(EvlrReader {
+                    reader: self,
+                    start: position,
+                    len,
+                    position: 0,
+                }));
+            }
+            position = position.saturating_add(len);
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::ReadOptions, synthetic::fixture, Builder, Point};
+    use std::io::Cursor;
+
+    fn file(compressed: bool) -> Vec<u8> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.is_compressed = compressed;
+        builder.evlrs.push(Vlr {
+            user_id: "small".to_string(),
+            record_id: 2,
+            data: vec![1, 2, 3],
+            ..Default::default()
+        });
+        builder.evlrs.push(Vlr {
+            user_id: "big".to_string(),
+            record_id: 1,
+            data: (0..1000u32).flat_map(u32::to_le_bytes).collect(),
+            ..Default::default()
+        });
+        let points = (0..10).map(|i| Point {
+            x: f64::from(i),
+            ..Default::default()
+        });
+        fixture::write(builder, points)
+    }
+
+    fn check(compressed: bool) {
+        let options = ReadOptions::default().max_evlr_len(100);
+        let mut reader = Reader::with_options(Cursor::new(file(compressed)), options).unwrap();
+        let evlrs = reader.header().evlrs();
+        assert_eq!(1, evlrs.len());
+        assert_eq!("small", evlrs[0].user_id);
+
+        let _ = reader.read_point().unwrap().unwrap();
+        let mut evlr = reader.evlr_reader("big", 1).unwrap().unwrap();
+        assert_eq!(4000, evlr.len());
+        let mut bytes = [0; 4];
+        assert_eq!(400, evlr.seek(SeekFrom::Start(400)).unwrap());
+        evlr.read_exact(&mut bytes).unwrap();
+        assert_eq!(100, u32::from_le_bytes(bytes));
+        let _ = evlr.seek(SeekFrom::End(-4)).unwrap();
+        evlr.read_exact(&mut bytes).unwrap();
+        assert_eq!(999, u32::from_le_bytes(bytes));
+        assert_eq!(0, evlr.read(&mut bytes).unwrap());
+        assert!(evlr.seek(SeekFrom::Current(-5000)).is_err());
+        assert_eq!(1., reader.read_point().unwrap().unwrap().x);
+
+        let mut data = Vec::new();
+        let _ = reader
+            .evlr_reader("small", 2)
+            .unwrap()
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(vec![1, 2, 3], data);
+        assert!(reader.evlr_reader("big", 2).unwrap().is_none());
+    }
+
+    #[test]
+    fn uncompressed() {
+        check(false);
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn compressed() {
+        check(true);
+    }
+
+    #[test]
+    fn no_evlrs() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        assert!(reader.evlr_reader("big", 1).unwrap().is_none());
+    }
+}
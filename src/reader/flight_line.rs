@@ -0,0 +1,425 @@
+use super::{PointReader, ReadPoints};
+use crate::{Error, Point, Result};
+use std::{
+    fs::File,
+    io::{BufReader, Seek},
+};
+
+/// How [Reader::flight_lines](super::Reader::flight_lines) finds where one flight line ends and
+/// the next begins.
+///
+/// A new flight line starts wherever any of the chosen criteria is met. By default, a flight line
+/// ends when the point source id changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SegmentationOptions {
+    source_id: bool,
+    time_gap: Option<f64>,
+    edge_of_flight_line: bool,
+}
+
+/// A run of consecutive points from one flight line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlightLine {
+    /// The point source id of the first point.
+    pub source_id: u16,
+
+    /// The index of the first point in the file.
+    pub start_index: u64,
+
+    /// The points.
+    pub points: Vec<Point>,
+}
+
+/// An iterator over the flight lines in a `Reader`.
+///
+/// This struct is generally created by calling `flight_lines()` on `Reader`. As an iterator, it
+/// reads each flight line into memory; use [FlightLineIterator::next_points] to stream them
+/// instead. Iteration stops after the first error.
+#[allow(missing_debug_implementations)]
+pub struct FlightLineIterator<'a, R: std::io::Read + Seek + Send = BufReader<File>> {
+    point_reader: &'a mut PointReader<R>,
+    options: SegmentationOptions,
+    next: Option<(u64, Point)>,
+    segment: Option<Segment>,
+    error: Option<Error>,
+    done: bool,
+}
+
+/// The points of one flight line, read as they're iterated.
+///
+/// Created by [FlightLineIterator::next_points]. Dropping this early is fine: the rest of the
+/// flight line is skipped when the next one is asked for.
+#[allow(missing_debug_implementations)]
+pub struct FlightLinePoints<'s, 'a, R: std::io::Read + Seek + Send = BufReader<File>> {
+    flight_lines: &'s mut FlightLineIterator<'a, R>,
+
+    /// The point source id of the first point.
+    pub source_id: u16,
+
+    /// The index of the first point in the file.
+    pub start_index: u64,
+}
+
+/// The flight line that's being read.
+struct Segment {
+    first: Option<Point>,
+    previous: Option<Previous>,
+}
+
+/// What's needed from the last point to tell whether the next one starts a new flight line.
+struct Previous {
+    source_id: u16,
+    gps_time: Option<f64>,
+    edge_of_flight_line: bool,
+}
+
+impl SegmentationOptions {
+    /// Starts a new flight line when the point source id changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::SegmentationOptions;
+    /// let options = SegmentationOptions::default().source_id(false).time_gap(10.);
+    /// ```
+    pub fn source_id(mut self, source_id: bool) -> SegmentationOptions {
+        self.source_id = source_id;
+        self
+    }
+
+    /// Starts a new flight line when the gps time jumps forward by more than this many seconds.
+    ///
+    /// Points without a gps time never start a flight line this way. If the gps times aren't
+    /// sorted, gaps don't mean anything, so a warning is logged and the points are split by
+    /// source id alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::SegmentationOptions;
+    /// let options = SegmentationOptions::default().time_gap(10.);
+    /// ```
+    pub fn time_gap(mut self, seconds: f64) -> SegmentationOptions {
+        self.time_gap = Some(seconds);
+        self
+    }
+
+    /// Starts a new flight line after each point with the edge of flight line flag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::SegmentationOptions;
+    /// let options = SegmentationOptions::default().edge_of_flight_line(true);
+    /// ```
+    pub fn edge_of_flight_line(mut self, edge_of_flight_line: bool) -> SegmentationOptions {
+        self.edge_of_flight_line = edge_of_flight_line;
+        self
+    }
+
+    /// Returns the time gap, if flight lines are split on one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::SegmentationOptions;
+    /// assert_eq!(None, SegmentationOptions::default().time_gap_seconds());
+    /// ```
+    pub fn time_gap_seconds(&self) -> Option<f64> {
+        self.time_gap
+    }
+
+    fn starts_new(&self, previous: &Previous, point: &Point) -> bool {
+        (self.source_id && previous.source_id != point.point_source_id)
+            || (self.edge_of_flight_line && previous.edge_of_flight_line)
+            || matches!(
+                (self.time_gap, previous.gps_time, point.gps_time),
+                (Some(gap), Some(previous), Some(time)) if time - previous > gap
+            )
+    }
+}
+
+impl Default for SegmentationOptions {
+    fn default() -> SegmentationOptions {
+        SegmentationOptions {
+            source_id: true,
+            time_gap: None,
+            edge_of_flight_line: false,
+        }
+    }
+}
+
+impl Previous {
+    fn new(point: &Point) -> Previous {
+        Previous {
+            source_id: point.point_source_id,
+            gps_time: point.gps_time,
+            edge_of_flight_line: point.is_edge_of_flight_line,
+        }
+    }
+}
+
+impl<'a, R: std::io::Read + Seek + Send> FlightLineIterator<'a, R> {
+    pub(super) fn new(
+        point_reader: &'a mut PointReader<R>,
+        options: SegmentationOptions,
+    ) -> FlightLineIterator<'a, R> {
+        FlightLineIterator {
+            point_reader,
+            options,
+            next: None,
+            segment: None,
+            error: None,
+            done: false,
+        }
+    }
+
+    /// Starts the next flight line, returning its points as an iterator instead of a vector.
+    ///
+    /// Returns `None` when there are no points left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::SegmentationOptions, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut flight_lines = reader.flight_lines(SegmentationOptions::default()).unwrap();
+    /// while let Some(points) = flight_lines.next_points() {
+    ///     let source_id = points.source_id;
+    ///     for point in points {
+    ///         assert_eq!(source_id, point.unwrap().point_source_id);
+    ///     }
+    /// }
+    /// ```
+    pub fn next_points(&mut self) -> Option<FlightLinePoints<'_, 'a, R>> {
+        // Skip whatever's left of the last flight line.
+        while self.next_in_segment().is_some() {}
+        if self.done {
+            return None;
+        }
+        let (start_index, first) = match self.next.take() {
+            Some(next) => next,
+            None => {
+                let index = self.point_reader.index();
+                match self.point_reader.read_point() {
+                    // Withheld points may have been skipped on the way.
+                    Ok(Some(point)) => (self.point_reader.index() - 1, point),
+                    Ok(None) => {
+                        self.done = true;
+                        return None;
+                    }
+                    Err(err) => {
+                        // The error is handed out by a flight line with nothing else in it.
+                        self.done = true;
+                        self.error = Some(err);
+                        return Some(FlightLinePoints {
+                            flight_lines: self,
+                            source_id: 0,
+                            start_index: index,
+                        });
+                    }
+                }
+            }
+        };
+        let source_id = first.point_source_id;
+        self.segment = Some(Segment {
+            first: Some(first),
+            previous: None,
+        });
+        Some(FlightLinePoints {
+            flight_lines: self,
+            source_id,
+            start_index,
+        })
+    }
+
+    fn next_in_segment(&mut self) -> Option<Result<Point>> {
+        let segment = self.segment.as_mut()?;
+        let point = if let Some(first) = segment.first.take() {
+            first
+        } else {
+            match self.point_reader.read_point() {
+                Ok(Some(point)) => {
+                    let index = self.point_reader.index() - 1;
+                    let previous = segment
+                        .previous
+                        .as_ref()
+                        .expect("set after the first point");
+                    if self.options.starts_new(previous, &point) {
+                        self.next = Some((index, point));
+                        self.segment = None;
+                        return None;
+                    }
+                    point
+                }
+                Ok(None) => {
+                    self.segment = None;
+                    return None;
+                }
+                Err(err) => {
+                    self.segment = None;
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        };
+        segment.previous = Some(Previous::new(&point));
+        Some(Ok(point))
+    }
+}
+
+impl<R: std::io::Read + Seek + Send> Iterator for FlightLineIterator<'_, R> {
+    type Item = Result<FlightLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let flight_line = self.next_points()?;
+        let source_id = flight_line.source_id;
+        let start_index = flight_line.start_index;
+        match flight_line.collect::<Result<Vec<_>>>() {
+            Ok(points) => Some(Ok(FlightLine {
+                source_id,
+                start_index,
+                points,
+            })),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl<R: std::io::Read + Seek + Send> Iterator for FlightLinePoints<'_, '_, R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.flight_lines.error.take() {
+            return Some(Err(err));
+        }
+        self.flight_lines.next_in_segment()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SegmentationOptions;
+    use crate::{point::Format, synthetic::fixture, Builder, Point, Reader};
+    use std::io::Cursor;
+
+    /// Two strips of five points, ten seconds apart, with the edge flag on the last point of each.
+    fn two_strips(source_ids: [u16; 2], shuffle_times: bool) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(1).unwrap();
+        let mut points = Vec::new();
+        for (strip, source_id) in source_ids.into_iter().enumerate() {
+            for i in 0..5 {
+                let mut gps_time = 100. * strip as f64 + f64::from(i);
+                if shuffle_times && i % 2 == 1 {
+                    gps_time -= 1.5;
+                }
+                points.push(Point {
+                    x: f64::from(i),
+                    point_source_id: source_id,
+                    gps_time: Some(gps_time),
+                    is_edge_of_flight_line: i == 4,
+                    ..Default::default()
+                });
+            }
+        }
+        fixture::reader(fixture::write(builder, points))
+    }
+
+    fn segments(
+        mut reader: Reader<Cursor<Vec<u8>>>,
+        options: SegmentationOptions,
+    ) -> Vec<(u16, u64, usize)> {
+        reader
+            .flight_lines(options)
+            .unwrap()
+            .map(|flight_line| {
+                let flight_line = flight_line.unwrap();
+                (
+                    flight_line.source_id,
+                    flight_line.start_index,
+                    flight_line.points.len(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn source_id() {
+        let options = SegmentationOptions::default();
+        assert_eq!(
+            vec![(1, 0, 5), (2, 5, 5)],
+            segments(two_strips([1, 2], false), options)
+        );
+        assert_eq!(
+            vec![(1, 0, 10)],
+            segments(two_strips([1, 1], false), options)
+        );
+    }
+
+    #[test]
+    fn time_gap() {
+        let options = SegmentationOptions::default().source_id(false).time_gap(5.);
+        assert_eq!(
+            vec![(1, 0, 5), (1, 5, 5)],
+            segments(two_strips([1, 1], false), options)
+        );
+        let options = options.time_gap(500.);
+        assert_eq!(
+            vec![(1, 0, 10)],
+            segments(two_strips([1, 1], false), options)
+        );
+    }
+
+    #[test]
+    fn edge_of_flight_line() {
+        let options = SegmentationOptions::default()
+            .source_id(false)
+            .edge_of_flight_line(true);
+        assert_eq!(
+            vec![(1, 0, 5), (1, 5, 5)],
+            segments(two_strips([1, 1], false), options)
+        );
+    }
+
+    #[test]
+    fn unsorted_times_fall_back_to_source_id() {
+        let options = SegmentationOptions::default().source_id(false).time_gap(1.);
+        assert_eq!(
+            vec![(1, 0, 5), (2, 5, 5)],
+            segments(two_strips([1, 2], true), options)
+        );
+    }
+
+    #[test]
+    fn streamed() {
+        let mut reader = two_strips([1, 2], false);
+        let mut flight_lines = reader.flight_lines(SegmentationOptions::default()).unwrap();
+        {
+            // Only read the first point of the first flight line.
+            let mut points = flight_lines.next_points().unwrap();
+            assert_eq!((1, 0), (points.source_id, points.start_index));
+            assert_eq!(0., points.next().unwrap().unwrap().x);
+        }
+
+        let points = flight_lines.next_points().unwrap();
+        assert_eq!((2, 5), (points.source_id, points.start_index));
+        let points = points.collect::<crate::Result<Vec<_>>>().unwrap();
+        assert_eq!(5, points.len());
+        assert!(points.iter().all(|point| point.point_source_id == 2));
+        assert!(flight_lines.next_points().is_none());
+        assert!(flight_lines.next().is_none());
+    }
+
+    #[test]
+    fn starts_at_the_current_position() {
+        let mut reader = two_strips([1, 2], false);
+        reader.seek(3).unwrap();
+        let flight_lines: Vec<_> = reader
+            .flight_lines(SegmentationOptions::default().time_gap(5.))
+            .unwrap()
+            .map(|flight_line| flight_line.unwrap().start_index)
+            .collect();
+        assert_eq!(vec![3, 5], flight_lines);
+    }
+}
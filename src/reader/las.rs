@@ -1,5 +1,5 @@
-use super::ReadPoints;
-use crate::{raw, Header, Point, Result};
+use super::{read_into_point, ReadPoints, SkippedChunk, UNCOMPRESSED_CHUNK_SIZE};
+use crate::{raw, Error, Header, Point, Result};
 use std::io::{Read, Seek, SeekFrom};
 
 pub(crate) struct PointReader<R: Read + Seek> {
@@ -7,31 +7,85 @@ pub(crate) struct PointReader<R: Read + Seek> {
     header: Header,
     index: u64,
     start: u64,
+    record_len: u64,
 }
 
 impl<R: Read + Seek> PointReader<R> {
     pub(crate) fn new(mut read: R, header: Header) -> Result<PointReader<R>> {
         Ok(PointReader {
             start: read.stream_position()?,
+            record_len: u64::from(header.point_data_record_length()),
             read,
             header,
             index: 0,
         })
     }
+
+    fn read_raw_point_into(&mut self, raw_point: &mut raw::Point) -> Result<()> {
+        let format = self.header.point_format();
+        if self.record_len == u64::from(format.len()) {
+            return raw_point.read_into(&mut self.read, format);
+        }
+        // A short record fills the start of the point, and the rest is zeros.
+        let mut bytes = vec![0; usize::from(format.len())];
+        self.read
+            .read_exact(&mut bytes[..self.record_len as usize])?;
+        raw_point.read_into(bytes.as_slice(), format)
+    }
+
+    fn read_failed(&self, index: u64, err: Error) -> Error {
+        Error::PointReadFailed {
+            index,
+            offset: Some(self.start + index * self.record_len),
+            source: Box::new(err),
+        }
+    }
 }
 
-impl<R: Read + Seek> ReadPoints for PointReader<R> {
+impl<R: Read + Seek + Send> ReadPoints for PointReader<R> {
     fn read_point(&mut self) -> Result<Option<Point>> {
         if self.index < self.header.number_of_points() {
+            let index = self.index;
             self.index += 1;
-            raw::Point::read_from(&mut self.read, self.header.point_format())
-                .map(|p| Point::new(p, self.header.transforms()))
-                .map(Some)
+            let mut raw_point = raw::Point::default();
+            self.read_raw_point_into(&mut raw_point)
+                .map(|()| Some(Point::new(raw_point, self.header.effective_transforms())))
+                .map_err(|err| self.read_failed(index, err))
         } else {
             Ok(None)
         }
     }
 
+    fn read_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        if self.index < self.header.number_of_points() {
+            let index = self.index;
+            self.index += 1;
+            let transforms = *self.header.effective_transforms();
+            read_into_point(point, &transforms, |raw_point| {
+                self.read_raw_point_into(raw_point)
+            })
+            .map_err(|err| self.read_failed(index, err))?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn read_record(&mut self, record: &mut [u8]) -> Result<bool> {
+        if self.index < self.header.number_of_points() {
+            let index = self.index;
+            self.index += 1;
+            let n = record.len().min(self.record_len as usize);
+            self.read
+                .read_exact(&mut record[..n])
+                .map_err(|err| self.read_failed(index, err.into()))?;
+            record[n..].fill(0);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
     fn read_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
         let points_left = self.header.number_of_points() - self.index;
         let n = points_left.min(n);
@@ -50,15 +104,75 @@ impl<R: Read + Seek> ReadPoints for PointReader<R> {
         Ok(count)
     }
 
+    fn skip(&mut self, n: u64) -> Result<()> {
+        self.seek(self.header.number_of_points().min(self.index + n))
+    }
+
     fn seek(&mut self, index: u64) -> Result<()> {
         self.index = index;
-        let _ = self.read.seek(SeekFrom::Start(
-            self.start + index * u64::from(self.header.point_format().len()),
-        ))?;
+        let _ = self
+            .read
+            .seek(SeekFrom::Start(self.start + index * self.record_len))?;
         Ok(())
     }
 
+    fn index(&self) -> u64 {
+        self.index
+    }
+
+    fn point_offset(&self, index: u64) -> Option<u64> {
+        if index < self.header.number_of_points() {
+            Some(self.start + index * self.record_len)
+        } else {
+            None
+        }
+    }
+
     fn header(&self) -> &Header {
         &self.header
     }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        let _ = self.read.seek(SeekFrom::Start(offset))?;
+        let result = self.read.read_exact(buf);
+        self.seek(self.index)?;
+        result.map_err(Into::into)
+    }
+
+    fn skipped_chunks(&self) -> &[SkippedChunk] {
+        &[]
+    }
+
+    #[cfg(feature = "laz")]
+    fn chunk_table(&mut self) -> Result<(u64, ::laz::laszip::ChunkTable)> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "an uncompressed file doesn't have a chunk table",
+        )
+        .into())
+    }
+
+    fn number_of_chunks(&mut self) -> Result<usize> {
+        let chunks = self
+            .header
+            .number_of_points()
+            .div_ceil(UNCOMPRESSED_CHUNK_SIZE);
+        Ok(usize::try_from(chunks)?)
+    }
+
+    fn read_chunk(&mut self, chunk_index: usize) -> Result<Vec<Point>> {
+        let chunks = self.number_of_chunks()?;
+        if chunk_index >= chunks {
+            return Err(Error::ChunkOutOfRange {
+                index: chunk_index,
+                chunks,
+            });
+        }
+        let index = self.index;
+        self.seek(chunk_index as u64 * UNCOMPRESSED_CHUNK_SIZE)?;
+        let mut points = Vec::new();
+        let result = self.read_points(UNCOMPRESSED_CHUNK_SIZE, &mut points);
+        self.seek(index)?;
+        result.map(|_| points)
+    }
 }
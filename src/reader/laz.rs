@@ -1,69 +1,635 @@
-use super::ReadPoints;
-use crate::{raw, Header, Point, Result};
-use laz::LazDecompressor;
-use std::io::{Cursor, Read, Seek};
+use super::{read_into_point, ReadPoints, SkippedChunk};
+use crate::{header::stream_len, raw, vlr::LazCompressor, Error, Header, Point, Result};
+use laz::{
+    laszip::ChunkTable,
+    record::{
+        LayeredPointRecordDecompressor, RecordDecompressor, SequentialPointRecordDecompressor,
+    },
+    LasZipError, LazDecompressor, LazItem,
+};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+/// The most points that are decompressed at once when skipping points.
+const SKIP_BATCH_SIZE: u64 = 4096;
 
 pub(crate) struct PointReader<D: LazDecompressor> {
     buffer: Cursor<Vec<u8>>,
     decompressor: D,
     header: Header,
     index: u64,
+    start: u64,
+    /// The number of points in each chunk, if the chunks are all the same size.
+    fixed_chunk_size: Option<u64>,
+    /// The chunks from the chunk table, which is only read once.
+    chunks: Option<Vec<Chunk>>,
+    chunk_reader: Option<ChunkReader>,
+}
+
+/// Decompresses one chunk at a time, so that a chunk that fails can be skipped.
+///
+/// Each chunk is read into memory and decompressed on its own, so a corrupt chunk can't run on
+/// into the next one.
+struct ChunkReader {
+    chunks: Vec<Chunk>,
+    items: Vec<LazItem>,
+    current: Option<usize>,
+    points: Cursor<Vec<u8>>,
+    skipped_chunks: Vec<SkippedChunk>,
+    /// Whether a chunk that fails is skipped, or its error returned.
+    skip_failed: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Chunk {
+    first_point: u64,
+    point_count: u64,
+    offset: u64,
+    byte_count: u64,
+}
+
+/// A decompressor that lends out its source, for reads that skip decompression.
+pub(crate) trait Decompressor: LazDecompressor {
+    type Source: Read + Seek;
+
+    fn source(&mut self) -> &mut Self::Source;
+}
+
+#[cfg(feature = "laz-parallel")]
+impl<R: Read + Seek> Decompressor for laz::ParLasZipDecompressor<R> {
+    type Source = R;
+
+    fn source(&mut self) -> &mut R {
+        self.get_mut()
+    }
+}
+
+#[cfg(not(feature = "laz-parallel"))]
+impl<R: Read + Seek> Decompressor for ChunkDecompressor<R> {
+    type Source = R;
+
+    fn source(&mut self) -> &mut R {
+        self.records.get_mut()
+    }
+}
+
+/// Decompresses the chunks one after the other, and seeks through the chunk table.
+///
+/// This is what laz's `LasZipDecompressor` does, but that can only be used with a `'static`
+/// source.
+#[cfg(not(feature = "laz-parallel"))]
+pub(crate) struct ChunkDecompressor<R: Read + Seek> {
+    records: Records<R>,
+    items: Vec<LazItem>,
+    /// The number of points in each chunk and where it starts, or `None` for point-wise files,
+    /// which are one chunk without a chunk table.
+    chunks: Option<Vec<(u64, u64)>>,
+    /// The chunk being decompressed.
+    chunk: usize,
+    /// The number of points left in that chunk.
+    chunk_points_left: u64,
+}
+
+#[cfg(not(feature = "laz-parallel"))]
+impl<R: Read + Seek> ChunkDecompressor<R> {
+    /// Creates a decompressor for the points at the source's position, which is left at the first
+    /// chunk.
+    fn new(mut read: R, header: &Header) -> Result<ChunkDecompressor<R>> {
+        let vlr = header.laz_vlr()?;
+        let chunks = if is_point_wise(header) {
+            None
+        } else {
+            let mut offset = read.stream_position()? + ChunkTable::OFFSET_SIZE as u64;
+            let chunk_table = crate::laz::read_chunk_table(&mut read, header)?;
+            let _ = read.seek(SeekFrom::Start(offset))?;
+            Some(
+                chunk_table
+                    .as_ref()
+                    .iter()
+                    .map(|entry| {
+                        let chunk = (entry.point_count, offset);
+                        offset += entry.byte_count;
+                        chunk
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        };
+        Ok(ChunkDecompressor {
+            records: Records::new(vlr.items(), read)?,
+            items: vlr.items().clone(),
+            chunk_points_left: chunks.as_ref().map_or(u64::MAX, |chunks| {
+                chunks.first().map_or(0, |&(point_count, _)| point_count)
+            }),
+            chunks,
+            chunk: 0,
+        })
+    }
+
+    /// Starts decompressing a chunk at its offset.
+    fn start_chunk(&mut self, chunk: usize, point_count: u64, offset: u64) -> laz::Result<()> {
+        let source = self.records.get_mut();
+        if source.stream_position()? != offset {
+            let _ = source.seek(SeekFrom::Start(offset))?;
+        }
+        self.records.restart(&self.items)?;
+        self.chunk = chunk;
+        self.chunk_points_left = point_count;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "laz-parallel"))]
+impl<R: Read + Seek> LazDecompressor for ChunkDecompressor<R> {
+    fn decompress_one(&mut self, point: &mut [u8]) -> laz::Result<()> {
+        while self.chunk_points_left == 0 {
+            let chunk = self.chunk + 1;
+            let &(point_count, offset) = self
+                .chunks
+                .as_ref()
+                .and_then(|chunks| chunks.get(chunk))
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        "the chunk table ends before the last point",
+                    )
+                })?;
+            if self.source().stream_position()? > offset {
+                // Points that decompress past the end of their chunk are corrupt.
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("laz chunk {} runs past its end", self.chunk),
+                )
+                .into());
+            }
+            self.start_chunk(chunk, point_count, offset)?;
+        }
+        self.records.decompress_next(point)?;
+        self.chunk_points_left -= 1;
+        Ok(())
+    }
+
+    fn decompress_many(&mut self, points: &mut [u8]) -> laz::Result<()> {
+        let record_size = self.records.record_size();
+        points
+            .chunks_exact_mut(record_size)
+            .try_for_each(|point| self.decompress_one(point))
+    }
+
+    fn seek(&mut self, index: u64) -> laz::Result<()> {
+        let chunks = self.chunks.as_ref().ok_or(LasZipError::MissingChunkTable)?;
+        let mut first_point = 0;
+        let found = chunks
+            .iter()
+            .enumerate()
+            .find_map(|(chunk, &(point_count, offset))| {
+                first_point += point_count;
+                (index < first_point).then_some((chunk, point_count, offset))
+            });
+        let Some((chunk, point_count, offset)) = found else {
+            // Past the last point, so the next point fails to decompress.
+            let number_of_chunks = chunks.len();
+            let _ = self.source().seek(SeekFrom::End(0))?;
+            self.chunk = number_of_chunks;
+            self.chunk_points_left = 0;
+            return Ok(());
+        };
+        self.start_chunk(chunk, point_count, offset)?;
+        let mut record = vec![0; self.records.record_size()];
+        for _ in first_point - point_count..index {
+            self.decompress_one(&mut record)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(feature = "laz-parallel")]
-impl<R: Read + Seek> PointReader<laz::ParLasZipDecompressor<R>> {
+impl<R: Read + Seek + Send> PointReader<laz::ParLasZipDecompressor<R>> {
     pub(crate) fn new(
-        read: R,
+        mut read: R,
         header: Header,
+        skip_corrupt_chunks: bool,
     ) -> Result<PointReader<laz::ParLasZipDecompressor<R>>> {
-        let decompressor = laz::ParLasZipDecompressor::new(read, header.laz_vlr()?)?;
+        let start = read.stream_position()?;
+        let vlr = header.laz_vlr()?;
+        let fixed_chunk_size =
+            (!vlr.uses_variable_size_chunks()).then(|| u64::from(vlr.chunk_size()));
+        let decompressor = laz::ParLasZipDecompressor::new(read, vlr)?;
         let buffer = Cursor::new(vec![0u8; header.point_format().len().into()]);
-        Ok(PointReader {
+        PointReader {
             decompressor,
             header,
             buffer,
             index: 0,
-        })
+            start,
+            fixed_chunk_size,
+            chunks: None,
+            chunk_reader: None,
+        }
+        .skip_corrupt_chunks(skip_corrupt_chunks)
     }
 }
 
 #[cfg(not(feature = "laz-parallel"))]
-impl<R: Read + Seek + Send> PointReader<laz::LasZipDecompressor<'_, R>> {
+impl<R: Read + Seek + Send> PointReader<ChunkDecompressor<R>> {
     pub(crate) fn new(
-        read: R,
+        mut read: R,
         header: Header,
-    ) -> Result<PointReader<laz::LasZipDecompressor<'static, R>>> {
-        let decompressor = laz::LasZipDecompressor::new(read, header.laz_vlr()?)?;
+        skip_corrupt_chunks: bool,
+    ) -> Result<PointReader<ChunkDecompressor<R>>> {
+        let start = read.stream_position()?;
+        let vlr = header.laz_vlr()?;
+        let fixed_chunk_size =
+            (!vlr.uses_variable_size_chunks()).then(|| u64::from(vlr.chunk_size()));
+        let decompressor = ChunkDecompressor::new(read, &header)?;
         let buffer = Cursor::new(vec![0u8; header.point_format().len().into()]);
-        Ok(PointReader {
+        PointReader {
             decompressor,
             header,
             buffer,
             index: 0,
+            start,
+            fixed_chunk_size,
+            chunks: None,
+            chunk_reader: None,
+        }
+        .skip_corrupt_chunks(skip_corrupt_chunks)
+    }
+}
+
+impl<D: Decompressor + Send> PointReader<D> {
+    fn skip_corrupt_chunks(mut self, skip_corrupt_chunks: bool) -> Result<PointReader<D>> {
+        if skip_corrupt_chunks {
+            self.use_chunk_reader(true)?;
+        }
+        Ok(self)
+    }
+
+    /// Switches to decompressing one chunk at a time, found through the chunk table.
+    fn use_chunk_reader(&mut self, skip_failed: bool) -> Result<()> {
+        let chunks = self.chunks()?.to_vec();
+        self.chunk_reader = Some(ChunkReader {
+            chunks,
+            items: self.header.laz_vlr()?.items().clone(),
+            current: None,
+            points: Cursor::new(Vec::new()),
+            skipped_chunks: Vec::new(),
+            skip_failed,
+        });
+        Ok(())
+    }
+
+    /// Returns the chunks, reading the chunk table the first time.
+    fn chunks(&mut self) -> Result<&[Chunk]> {
+        if self.chunks.is_none() {
+            let (mut offset, chunk_table) = self.read_chunk_table()?;
+            let mut first_point = 0;
+            let chunks = chunk_table
+                .as_ref()
+                .iter()
+                .map(|entry| {
+                    let chunk = Chunk {
+                        first_point,
+                        point_count: entry.point_count,
+                        offset,
+                        byte_count: entry.byte_count,
+                    };
+                    first_point += entry.point_count;
+                    offset += entry.byte_count;
+                    chunk
+                })
+                .collect();
+            self.chunks = Some(chunks);
+        }
+        Ok(self.chunks.as_deref().unwrap_or_default())
+    }
+
+    fn read_chunk_table(&mut self) -> Result<(u64, ChunkTable)> {
+        let source = self.decompressor.source();
+        let position = source.stream_position()?;
+        let _ = source.seek(SeekFrom::Start(self.start))?;
+        let result = crate::laz::read_chunk_table(&mut *source, &self.header);
+        let _ = source.seek(SeekFrom::Start(position))?;
+        Ok((self.start + ChunkTable::OFFSET_SIZE as u64, result?))
+    }
+
+    fn decompress_chunk(&mut self, items: &[LazItem], chunk: &Chunk) -> Result<Vec<u8>> {
+        self.read_compressed(items, chunk)?.decompress_records()
+    }
+
+    fn read_compressed(&mut self, items: &[LazItem], chunk: &Chunk) -> Result<CompressedChunk> {
+        let end = stream_len(self.decompressor.source());
+        if end.is_some_and(|end| chunk.offset.saturating_add(chunk.byte_count) > end) {
+            return Err(Error::InvalidChunkTable(format!(
+                "chunk at {} with {} bytes runs past the end of the file",
+                chunk.offset, chunk.byte_count
+            )));
+        }
+        let mut bytes = vec![0; usize::try_from(chunk.byte_count)?];
+        self.read_at(chunk.offset, &mut bytes)?;
+        Ok(CompressedChunk {
+            items: items.to_vec(),
+            bytes,
+            point_count: chunk.point_count,
         })
     }
+
+    /// Finds a chunk through the chunk table, and reads its compressed bytes.
+    fn compressed_chunk(&mut self, chunk_index: usize) -> Result<CompressedChunk> {
+        let chunks = self.chunks()?;
+        let chunk = *chunks.get(chunk_index).ok_or(Error::ChunkOutOfRange {
+            index: chunk_index,
+            chunks: chunks.len(),
+        })?;
+        let items = self.header.laz_vlr()?.items().clone();
+        self.read_compressed(&items, &chunk)
+    }
+
+    /// Makes sure the chunk holding the next point is decompressed, skipping any chunks that fail.
+    ///
+    /// Returns the index one past the end of that chunk, or `None` if there are no points left.
+    fn load_chunk(&mut self, chunk_reader: &mut ChunkReader) -> Result<Option<u64>> {
+        let point_len = u64::from(self.header.point_format().len());
+        while self.index < self.header.number_of_points() {
+            if let Some(chunk) = chunk_reader
+                .current
+                .map(|index| &chunk_reader.chunks[index])
+                && (chunk.first_point..chunk.first_point + chunk.point_count).contains(&self.index)
+            {
+                return Ok(Some(chunk.first_point + chunk.point_count));
+            }
+            let index = chunk_reader
+                .chunks
+                .partition_point(|chunk| chunk.first_point + chunk.point_count <= self.index);
+            let chunk = chunk_reader.chunks.get(index).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "the chunk table ends before the last point",
+                )
+            })?;
+            let chunk_end = chunk.first_point + chunk.point_count;
+            match self.decompress_chunk(&chunk_reader.items, chunk) {
+                Ok(bytes) => {
+                    chunk_reader.points = Cursor::new(bytes);
+                    chunk_reader
+                        .points
+                        .set_position((self.index - chunk.first_point) * point_len);
+                    chunk_reader.current = Some(index);
+                    return Ok(Some(chunk_end));
+                }
+                Err(error) if !chunk_reader.skip_failed => return Err(error),
+                Err(error) => {
+                    log::warn!(
+                        "skipping points {}..{chunk_end} in laz chunk {index}: {error}",
+                        self.index
+                    );
+                    chunk_reader.skipped_chunks.push(SkippedChunk {
+                        index,
+                        points: self.index..chunk_end,
+                        error,
+                    });
+                    chunk_reader.current = None;
+                    self.index = chunk_end;
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads up to `n` points, skipping the rest of any chunk that fails to decompress.
+    fn read_points_skipping(
+        &mut self,
+        chunk_reader: &mut ChunkReader,
+        n: u64,
+        points: &mut Vec<Point>,
+    ) -> Result<u64> {
+        let mut count = 0;
+        while count < n
+            && let Some(chunk_end) = self.load_chunk(chunk_reader)?
+        {
+            let end = chunk_end.min(self.index + n - count);
+            while self.index < end {
+                let point =
+                    raw::Point::read_from(&mut chunk_reader.points, self.header.point_format())
+                        .map(|raw_point| {
+                            Point::new(raw_point, self.header.effective_transforms())
+                        })?;
+                points.push(point);
+                self.index += 1;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    fn read_record_skipping(
+        &mut self,
+        chunk_reader: &mut ChunkReader,
+        record: &mut [u8],
+    ) -> Result<bool> {
+        if self.load_chunk(chunk_reader)?.is_some() {
+            chunk_reader.points.read_exact(record)?;
+            self.index += 1;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+/// The decompressor for the records of a chunk, which depends on the version of the laz items.
+///
+/// The decompressors are kept by value rather than boxed as trait objects, since those would need
+/// a `'static` source.
+enum Records<R: Read + Seek> {
+    Sequential(SequentialPointRecordDecompressor<'static, R>),
+    Layered(LayeredPointRecordDecompressor<'static, R>),
+}
+
+impl<R: Read + Seek> Records<R> {
+    /// Makes a decompressor for the records of one chunk, starting at the source's position.
+    fn new(items: &Vec<LazItem>, input: R) -> Result<Records<R>> {
+        let mut records = match items.first().map(|item| item.version()) {
+            Some(1 | 2) => Records::Sequential(SequentialPointRecordDecompressor::new(input)),
+            Some(3 | 4) => Records::Layered(LayeredPointRecordDecompressor::new(input)),
+            _ => {
+                let item = items.first().ok_or(LasZipError::MissingChunkTable)?;
+                return Err(LasZipError::UnsupportedLazItemVersion(
+                    item.item_type(),
+                    item.version(),
+                )
+                .into());
+            }
+        };
+        records.set_fields_from(items)?;
+        Ok(records)
+    }
+
+    fn set_fields_from(&mut self, items: &Vec<LazItem>) -> laz::Result<()> {
+        match self {
+            Records::Sequential(decompressor) => decompressor.set_fields_from(items),
+            Records::Layered(decompressor) => decompressor.set_fields_from(items),
+        }
+    }
+
+    /// Gets ready to decompress a new chunk, starting at the source's position.
+    fn restart(&mut self, items: &Vec<LazItem>) -> laz::Result<()> {
+        match self {
+            Records::Sequential(decompressor) => decompressor.reset(),
+            Records::Layered(decompressor) => decompressor.reset(),
+        }
+        self.set_fields_from(items)
+    }
+
+    fn record_size(&self) -> usize {
+        match self {
+            Records::Sequential(decompressor) => decompressor.record_size(),
+            Records::Layered(decompressor) => decompressor.record_size(),
+        }
+    }
+
+    fn record_count(&self) -> u64 {
+        match self {
+            Records::Sequential(decompressor) => decompressor.record_count(),
+            Records::Layered(decompressor) => decompressor.record_count(),
+        }
+    }
+
+    fn decompress_next(&mut self, point: &mut [u8]) -> std::io::Result<()> {
+        match self {
+            Records::Sequential(decompressor) => decompressor.decompress_next(point),
+            Records::Layered(decompressor) => decompressor.decompress_next(point),
+        }
+    }
+
+    fn decompress_many(&mut self, points: &mut [u8]) -> std::io::Result<()> {
+        match self {
+            Records::Sequential(decompressor) => decompressor.decompress_many(points),
+            Records::Layered(decompressor) => decompressor.decompress_many(points),
+        }
+    }
+
+    fn get_mut(&mut self) -> &mut R {
+        match self {
+            Records::Sequential(decompressor) => decompressor.get_mut(),
+            Records::Layered(decompressor) => decompressor.get_mut(),
+        }
+    }
 }
 
 impl<D> ReadPoints for PointReader<D>
 where
-    D: LazDecompressor + Send,
+    D: Decompressor + Send,
 {
     fn read_point(&mut self) -> Result<Option<Point>> {
+        if self.chunk_reader.is_some() {
+            let mut points = Vec::with_capacity(1);
+            let _ = self.read_points(1, &mut points)?;
+            return Ok(points.pop());
+        }
         if self.index < self.header.number_of_points() {
             self.index += 1;
+            // The parallel decompressor fills the whole buffer, so it must hold just one point.
+            self.buffer
+                .get_mut()
+                .resize(self.header.point_format().len().into(), 0u8);
             self.decompressor.decompress_one(self.buffer.get_mut())?;
             self.buffer.set_position(0);
             raw::Point::read_from(&mut self.buffer, self.header.point_format())
-                .map(|raw_point| Point::new(raw_point, self.header.transforms()))
+                .map(|raw_point| Point::new(raw_point, self.header.effective_transforms()))
                 .map(Some)
         } else {
             Ok(None)
         }
     }
 
+    fn read_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        if self.chunk_reader.is_some() {
+            return Ok(match self.read_point()? {
+                Some(next) => {
+                    *point = next;
+                    true
+                }
+                None => false,
+            });
+        }
+        if self.index < self.header.number_of_points() {
+            self.index += 1;
+            self.buffer
+                .get_mut()
+                .resize(self.header.point_format().len().into(), 0u8);
+            self.decompressor.decompress_one(self.buffer.get_mut())?;
+            self.buffer.set_position(0);
+            let transforms = *self.header.effective_transforms();
+            read_into_point(point, &transforms, |raw_point| {
+                raw_point.read_into(&mut self.buffer, self.header.point_format())
+            })?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn read_record(&mut self, record: &mut [u8]) -> Result<bool> {
+        if let Some(mut chunk_reader) = self.chunk_reader.take() {
+            let result = self.read_record_skipping(&mut chunk_reader, record);
+            self.chunk_reader = Some(chunk_reader);
+            return result;
+        }
+        if self.index < self.header.number_of_points() {
+            self.index += 1;
+            self.decompressor.decompress_one(record)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn skip(&mut self, n: u64) -> Result<()> {
+        let target = self.header.number_of_points().min(self.index + n);
+        if let Some(chunk_reader) = &mut self.chunk_reader {
+            match chunk_reader
+                .current
+                .map(|index| &chunk_reader.chunks[index])
+            {
+                Some(chunk)
+                    if (chunk.first_point..chunk.first_point + chunk.point_count)
+                        .contains(&target) =>
+                {
+                    let point_len = u64::from(self.header.point_format().len());
+                    chunk_reader
+                        .points
+                        .set_position((target - chunk.first_point) * point_len);
+                }
+                _ => chunk_reader.current = None,
+            }
+            self.index = target;
+            return Ok(());
+        }
+        if let Some(chunk_size) = self.fixed_chunk_size
+            && target / chunk_size > self.index / chunk_size
+        {
+            // Seeking jumps straight to the chunk, and only decompresses points within it.
+            return self.seek(target);
+        }
+        let point_len = usize::from(self.header.point_format().len());
+        while self.index < target {
+            let count = (target - self.index).min(SKIP_BATCH_SIZE);
+            self.buffer
+                .get_mut()
+                .resize(usize::try_from(count)? * point_len, 0u8);
+            self.decompressor.decompress_many(self.buffer.get_mut())?;
+            self.index += count;
+        }
+        Ok(())
+    }
+
     fn read_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
         let points_left = self.header.number_of_points() - self.index;
         let n = points_left.min(n);
+        if let Some(mut chunk_reader) = self.chunk_reader.take() {
+            let result = self.read_points_skipping(&mut chunk_reader, n, points);
+            self.chunk_reader = Some(chunk_reader);
+            return result;
+        }
 
         let resize = usize::try_from(n * u64::from(self.header.point_format().len()))?;
         self.buffer.get_mut().resize(resize, 0u8);
@@ -75,7 +641,7 @@ where
 
         for _ in 0..n {
             let point = raw::Point::read_from(&mut self.buffer, self.header.point_format())
-                .map(|raw_point| Point::new(raw_point, self.header.transforms()))?;
+                .map(|raw_point| Point::new(raw_point, self.header.effective_transforms()))?;
             self.index += 1;
             points.push(point);
         }
@@ -83,12 +649,656 @@ where
     }
 
     fn seek(&mut self, index: u64) -> Result<()> {
+        if self.chunk_reader.is_none() && self.fixed_chunk_size.is_none() {
+            // The laz decompressors find a point within its chunk as if every chunk had the same
+            // size, so variable size chunks are found through the chunk table instead.
+            self.use_chunk_reader(false)?;
+        }
         self.index = index;
-        self.decompressor.seek(index)?;
+        if let Some(chunk_reader) = &mut self.chunk_reader {
+            chunk_reader.current = None;
+        } else {
+            self.decompressor.seek(index)?;
+        }
         Ok(())
     }
 
+    fn index(&self) -> u64 {
+        self.index
+    }
+
+    fn point_offset(&self, _: u64) -> Option<u64> {
+        None
+    }
+
     fn header(&self) -> &Header {
         &self.header
     }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        let source = self.decompressor.source();
+        let position = source.stream_position()?;
+        let _ = source.seek(SeekFrom::Start(offset))?;
+        let result = source.read_exact(buf);
+        let _ = source.seek(SeekFrom::Start(position))?;
+        result.map_err(Into::into)
+    }
+
+    fn chunk_table(&mut self) -> Result<(u64, ChunkTable)> {
+        self.read_chunk_table()
+    }
+
+    fn seek_in_chunk(&mut self, chunk_index: usize, offset: u64) -> Result<Option<u64>> {
+        let chunks = self.chunks()?;
+        let chunk = *chunks.get(chunk_index).ok_or(Error::ChunkOutOfRange {
+            index: chunk_index,
+            chunks: chunks.len(),
+        })?;
+        if offset >= chunk.point_count {
+            return Err(Error::ResumeTokenMismatch);
+        }
+        match &mut self.chunk_reader {
+            Some(chunk_reader) => chunk_reader.current = None,
+            None => self.use_chunk_reader(false)?,
+        }
+        self.index = chunk.first_point + offset;
+        Ok(Some(self.index))
+    }
+
+    fn skipped_chunks(&self) -> &[SkippedChunk] {
+        self.chunk_reader
+            .as_ref()
+            .map_or(&[], |chunk_reader| &chunk_reader.skipped_chunks)
+    }
+
+    fn number_of_chunks(&mut self) -> Result<usize> {
+        Ok(self.chunks()?.len())
+    }
+
+    fn read_chunk(&mut self, chunk_index: usize) -> Result<Vec<Point>> {
+        self.compressed_chunk(chunk_index)?.decompress(&self.header)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn read_compressed_chunk(&mut self, chunk_index: usize) -> Result<Option<CompressedChunk>> {
+        self.compressed_chunk(chunk_index).map(Some)
+    }
+}
+
+/// The compressed bytes of one chunk, which can be decompressed on any thread.
+pub(crate) struct CompressedChunk {
+    items: Vec<LazItem>,
+    bytes: Vec<u8>,
+    point_count: u64,
+}
+
+impl CompressedChunk {
+    /// Wraps the bytes of a chunk that were read some other way, e.g. asynchronously.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn new(items: Vec<LazItem>, bytes: Vec<u8>, point_count: u64) -> CompressedChunk {
+        CompressedChunk {
+            items,
+            bytes,
+            point_count,
+        }
+    }
+
+    fn decompress_records(self) -> Result<Vec<u8>> {
+        let mut decompressor = Records::new(&self.items, Cursor::new(self.bytes))?;
+        let mut records = vec![0; usize::try_from(self.point_count)? * decompressor.record_size()];
+        decompressor.decompress_many(&mut records)?;
+        Ok(records)
+    }
+
+    /// Decompresses the chunk's points, with the header of the file they're from.
+    pub(crate) fn decompress(self, header: &Header) -> Result<Vec<Point>> {
+        let format = header.point_format();
+        self.decompress_records()?
+            .chunks_exact(usize::from(format.len()))
+            .map(|record| {
+                raw::Point::read_from(record, format)
+                    .map(|raw_point| Point::new(raw_point, header.effective_transforms()))
+            })
+            .collect()
+    }
+}
+
+/// Decompresses a file that has no usable chunk table, one chunk after the other.
+///
+/// Some writers never get to write the chunk table, or leave its offset as -1. The start of each
+/// chunk is noted as it's reached, so seeking goes back to the nearest known chunk and decompresses
+/// forward from there.
+pub(crate) struct SequentialPointReader<R: Read + Seek> {
+    records: Records<R>,
+    items: Vec<LazItem>,
+    /// The number of points in each chunk, if the chunks are all the same size.
+    fixed_chunk_size: Option<u64>,
+    header: Header,
+    index: u64,
+    /// The number of points left in the current chunk.
+    chunk_points_left: u64,
+    /// The first point and offset of each chunk that's been reached.
+    chunks: Vec<(u64, u64)>,
+    buffer: Cursor<Vec<u8>>,
+}
+
+impl<R: Read + Seek + Send> SequentialPointReader<R> {
+    fn new(mut read: R, header: Header) -> Result<SequentialPointReader<R>> {
+        let vlr = header.laz_vlr()?;
+        if vlr.uses_variable_size_chunks()
+            && header
+                .laz_vlr_info()
+                .is_some_and(|info| info.compressor != LazCompressor::LayeredChunked)
+        {
+            // Only layered chunks say how many points they hold.
+            return Err(LasZipError::MissingChunkTable.into());
+        }
+        // Skip the offset to the chunk table.
+        let _ = read.seek(SeekFrom::Current(ChunkTable::OFFSET_SIZE as i64))?;
+        Ok(SequentialPointReader {
+            records: Records::new(vlr.items(), read)?,
+            items: vlr.items().clone(),
+            fixed_chunk_size: (!vlr.uses_variable_size_chunks())
+                .then(|| u64::from(vlr.chunk_size())),
+            header,
+            index: 0,
+            chunk_points_left: 0,
+            chunks: Vec::new(),
+            buffer: Cursor::new(Vec::new()),
+        })
+    }
+
+    fn decompress_one(&mut self, out: &mut [u8]) -> Result<()> {
+        if self.chunk_points_left == 0 {
+            let offset = self.records.get_mut().stream_position()?;
+            if self.index > 0 {
+                self.records.restart(&self.items)?;
+            }
+            if self
+                .chunks
+                .last()
+                .is_none_or(|&(first, _)| first < self.index)
+            {
+                self.chunks.push((self.index, offset));
+            }
+            self.records.decompress_next(out)?;
+            self.chunk_points_left = self
+                .fixed_chunk_size
+                .unwrap_or_else(|| self.records.record_count());
+        } else {
+            self.records.decompress_next(out)?;
+        }
+        self.chunk_points_left = self.chunk_points_left.saturating_sub(1);
+        self.index += 1;
+        Ok(())
+    }
+
+    /// Decompresses the next `n` points into the buffer, which is left at its start.
+    fn decompress_into_buffer(&mut self, n: u64) -> Result<()> {
+        let point_len = usize::from(self.header.point_format().len());
+        let mut buffer = std::mem::take(self.buffer.get_mut());
+        buffer.resize(usize::try_from(n)? * point_len, 0);
+        let result = buffer
+            .chunks_exact_mut(point_len)
+            .try_for_each(|point| self.decompress_one(point));
+        self.buffer = Cursor::new(buffer);
+        result
+    }
+}
+
+impl<R: Read + Seek + Send> ReadPoints for SequentialPointReader<R> {
+    fn read_point(&mut self) -> Result<Option<Point>> {
+        let mut points = Vec::with_capacity(1);
+        let _ = self.read_points(1, &mut points)?;
+        Ok(points.pop())
+    }
+
+    fn read_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        if self.index >= self.header.number_of_points() {
+            return Ok(false);
+        }
+        self.decompress_into_buffer(1)?;
+        let transforms = *self.header.effective_transforms();
+        read_into_point(point, &transforms, |raw_point| {
+            raw_point.read_into(&mut self.buffer, self.header.point_format())
+        })?;
+        Ok(true)
+    }
+
+    fn read_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        let n = n.min(self.header.number_of_points().saturating_sub(self.index));
+        self.decompress_into_buffer(n)?;
+        if let Ok(n) = usize::try_from(n) {
+            points.reserve(n);
+        }
+        for _ in 0..n {
+            let point = raw::Point::read_from(&mut self.buffer, self.header.point_format())
+                .map(|raw_point| Point::new(raw_point, self.header.effective_transforms()))?;
+            points.push(point);
+        }
+        Ok(n)
+    }
+
+    fn read_record(&mut self, record: &mut [u8]) -> Result<bool> {
+        if self.index < self.header.number_of_points() {
+            self.decompress_one(record)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn seek(&mut self, index: u64) -> Result<()> {
+        let chunk = self
+            .chunks
+            .partition_point(|&(first_point, _)| first_point <= index);
+        if let Some(&(first_point, offset)) = chunk.checked_sub(1).map(|i| &self.chunks[i])
+            && (index < self.index || first_point > self.index)
+        {
+            let _ = self.records.get_mut().seek(SeekFrom::Start(offset))?;
+            self.records.restart(&self.items)?;
+            self.index = first_point;
+            self.chunk_points_left = 0;
+        }
+        self.skip(index - self.index.min(index))
+    }
+
+    fn skip(&mut self, n: u64) -> Result<()> {
+        let target = self.header.number_of_points().min(self.index + n);
+        while self.index < target {
+            self.decompress_into_buffer((target - self.index).min(SKIP_BATCH_SIZE))?;
+        }
+        Ok(())
+    }
+
+    fn index(&self) -> u64 {
+        self.index
+    }
+
+    fn point_offset(&self, _: u64) -> Option<u64> {
+        None
+    }
+
+    fn header(&self) -> &Header {
+        &self.header
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        let source = self.records.get_mut();
+        let position = source.stream_position()?;
+        let _ = source.seek(SeekFrom::Start(offset))?;
+        let result = source.read_exact(buf);
+        let _ = source.seek(SeekFrom::Start(position))?;
+        result.map_err(Into::into)
+    }
+
+    fn chunk_table(&mut self) -> Result<(u64, ChunkTable)> {
+        Err(LasZipError::MissingChunkTable.into())
+    }
+
+    fn skipped_chunks(&self) -> &[SkippedChunk] {
+        &[]
+    }
+
+    fn missing_chunk_table(&self) -> bool {
+        true
+    }
+
+    fn number_of_chunks(&mut self) -> Result<usize> {
+        Err(LasZipError::MissingChunkTable.into())
+    }
+
+    fn read_chunk(&mut self, _: usize) -> Result<Vec<Point>> {
+        Err(LasZipError::MissingChunkTable.into())
+    }
+
+    #[cfg(feature = "parallel")]
+    fn read_compressed_chunk(&mut self, _: usize) -> Result<Option<CompressedChunk>> {
+        Err(LasZipError::MissingChunkTable.into())
+    }
+}
+
+/// Returns true if the file at `start` has a chunk table that fits its points, leaving `read` at
+/// `start`.
+///
+/// Like laszip, an offset that doesn't point past the start of the points is looked for again at
+/// the end of the file.
+fn has_chunk_table<R: Read + Seek>(read: &mut R, header: &Header) -> Result<bool> {
+    let start = read.stream_position()?;
+    let vlr = header.laz_vlr()?;
+    if is_point_wise(header) {
+        return Ok(true);
+    }
+    let chunk_table = crate::laz::read_chunk_table(&mut *read, header);
+    let _ = read.seek(SeekFrom::Start(start))?;
+    let Ok(chunk_table) = chunk_table else {
+        return Ok(false);
+    };
+    let number_of_points = header.number_of_points();
+    let points_fit = if vlr.uses_variable_size_chunks() {
+        chunk_table
+            .as_ref()
+            .iter()
+            .map(|entry| entry.point_count)
+            .sum::<u64>()
+            == number_of_points
+    } else {
+        chunk_table.len() as u64 == number_of_points.div_ceil(u64::from(vlr.chunk_size().max(1)))
+    };
+    Ok(points_fit)
+}
+
+fn is_point_wise(header: &Header) -> bool {
+    header
+        .laz_vlr_info()
+        .is_some_and(|info| info.compressor == LazCompressor::PointWise)
+}
+
+#[cfg(feature = "laz-parallel")]
+type Decompressing<R> = PointReader<laz::ParLasZipDecompressor<R>>;
+
+#[cfg(not(feature = "laz-parallel"))]
+type Decompressing<R> = PointReader<ChunkDecompressor<R>>;
+
+/// The point reader for a compressed file, through the chunk table or, without one, in order.
+type Resolved<R> = Either<Decompressing<R>, SequentialPointReader<R>>;
+
+/// Builds the decompressor the first time points are needed.
+///
+/// Setting up a decompressor reads the chunk table, which is wasted work if only the header is
+/// ever looked at.
+pub(crate) struct LazyPointReader<R: Read + Seek> {
+    state: State<R>,
+}
+
+#[allow(clippy::large_enum_variant)]
+enum State<R: Read + Seek> {
+    Pending {
+        read: R,
+        header: Header,
+        skip_corrupt_chunks: bool,
+        sequential: bool,
+    },
+    Ready(Resolved<R>),
+    Failed(Header),
+}
+
+impl<R: Read + Seek + Send> LazyPointReader<R> {
+    /// Creates a reader that decompresses the chunks in order, without looking for the chunk table,
+    /// if `sequential` is true.
+    pub(crate) fn new(
+        read: R,
+        header: Header,
+        skip_corrupt_chunks: bool,
+        sequential: bool,
+    ) -> Result<LazyPointReader<R>> {
+        // Parsing the laszip vlr is cheap, and it keeps bad vlrs failing when the reader is made.
+        let _ = header.laz_vlr()?;
+        Ok(LazyPointReader {
+            state: State::Pending {
+                read,
+                header,
+                skip_corrupt_chunks,
+                sequential,
+            },
+        })
+    }
+
+    fn ready(&mut self) -> Result<&mut Resolved<R>> {
+        if let State::Pending { header, .. } = &self.state {
+            let fallback = State::Failed(header.clone());
+            if let State::Pending {
+                mut read,
+                header,
+                skip_corrupt_chunks,
+                sequential,
+            } = std::mem::replace(&mut self.state, fallback)
+            {
+                let chunk_table = if sequential {
+                    // Point-wise files have no chunks, so there's no chunk table to look for.
+                    is_point_wise(&header)
+                } else {
+                    has_chunk_table(&mut read, &header)?
+                };
+                self.state = State::Ready(if chunk_table {
+                    Either::Left(PointReader::new(read, header, skip_corrupt_chunks)?)
+                } else {
+                    if !sequential {
+                        log::warn!(
+                            "the laz file has no chunk table, so its chunks are read in order"
+                        );
+                    }
+                    Either::Right(SequentialPointReader::new(read, header)?)
+                });
+            }
+        }
+        match &mut self.state {
+            State::Ready(point_reader) => Ok(point_reader),
+            State::Pending { .. } | State::Failed(_) => {
+                Err(std::io::Error::other("the laz decompressor could not be created").into())
+            }
+        }
+    }
+
+    /// Returns the point reader, if it's been made.
+    fn get(&self) -> Option<&Resolved<R>> {
+        match &self.state {
+            State::Ready(point_reader) => Some(point_reader),
+            State::Pending { .. } | State::Failed(_) => None,
+        }
+    }
+}
+
+impl<R: Read + Seek + Send> ReadPoints for LazyPointReader<R> {
+    fn read_point(&mut self) -> Result<Option<Point>> {
+        self.ready()?.read_point()
+    }
+
+    fn read_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        self.ready()?.read_point_into(point)
+    }
+
+    fn read_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        self.ready()?.read_points(n, points)
+    }
+
+    fn read_record(&mut self, record: &mut [u8]) -> Result<bool> {
+        self.ready()?.read_record(record)
+    }
+
+    fn seek(&mut self, index: u64) -> Result<()> {
+        self.ready()?.seek(index)
+    }
+
+    fn skip(&mut self, n: u64) -> Result<()> {
+        self.ready()?.skip(n)
+    }
+
+    fn index(&self) -> u64 {
+        self.get().map_or(0, |point_reader| point_reader.index())
+    }
+
+    fn point_offset(&self, index: u64) -> Option<u64> {
+        self.get()
+            .and_then(|point_reader| point_reader.point_offset(index))
+    }
+
+    fn header(&self) -> &Header {
+        match &self.state {
+            State::Pending { header, .. } | State::Failed(header) => header,
+            State::Ready(point_reader) => point_reader.header(),
+        }
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        if let State::Pending { read, .. } = &mut self.state {
+            let position = read.stream_position()?;
+            let _ = read.seek(SeekFrom::Start(offset))?;
+            let result = read.read_exact(buf);
+            let _ = read.seek(SeekFrom::Start(position))?;
+            result.map_err(Into::into)
+        } else {
+            self.ready()?.read_at(offset, buf)
+        }
+    }
+
+    fn chunk_table(&mut self) -> Result<(u64, ChunkTable)> {
+        self.ready()?.chunk_table()
+    }
+
+    fn seek_in_chunk(&mut self, chunk_index: usize, offset: u64) -> Result<Option<u64>> {
+        self.ready()?.seek_in_chunk(chunk_index, offset)
+    }
+
+    fn skipped_chunks(&self) -> &[SkippedChunk] {
+        self.get()
+            .map_or(&[], |point_reader| point_reader.skipped_chunks())
+    }
+
+    fn missing_chunk_table(&self) -> bool {
+        self.get()
+            .is_some_and(|point_reader| point_reader.missing_chunk_table())
+    }
+
+    fn number_of_chunks(&mut self) -> Result<usize> {
+        self.ready()?.number_of_chunks()
+    }
+
+    fn read_chunk(&mut self, chunk_index: usize) -> Result<Vec<Point>> {
+        self.ready()?.read_chunk(chunk_index)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn read_compressed_chunk(&mut self, chunk_index: usize) -> Result<Option<CompressedChunk>> {
+        self.ready()?.read_compressed_chunk(chunk_index)
+    }
+}
+
+/// One of two point readers, which is chosen once and then dispatched to statically, rather than
+/// through a trait object for every point.
+pub(crate) enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+impl<A: ReadPoints, B: ReadPoints> ReadPoints for Either<A, B> {
+    fn read_point(&mut self) -> Result<Option<Point>> {
+        match self {
+            Either::Left(point_reader) => point_reader.read_point(),
+            Either::Right(point_reader) => point_reader.read_point(),
+        }
+    }
+
+    fn read_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        match self {
+            Either::Left(point_reader) => point_reader.read_point_into(point),
+            Either::Right(point_reader) => point_reader.read_point_into(point),
+        }
+    }
+
+    fn read_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        match self {
+            Either::Left(point_reader) => point_reader.read_points(n, points),
+            Either::Right(point_reader) => point_reader.read_points(n, points),
+        }
+    }
+
+    fn read_record(&mut self, record: &mut [u8]) -> Result<bool> {
+        match self {
+            Either::Left(point_reader) => point_reader.read_record(record),
+            Either::Right(point_reader) => point_reader.read_record(record),
+        }
+    }
+
+    fn seek(&mut self, index: u64) -> Result<()> {
+        match self {
+            Either::Left(point_reader) => point_reader.seek(index),
+            Either::Right(point_reader) => point_reader.seek(index),
+        }
+    }
+
+    fn skip(&mut self, n: u64) -> Result<()> {
+        match self {
+            Either::Left(point_reader) => point_reader.skip(n),
+            Either::Right(point_reader) => point_reader.skip(n),
+        }
+    }
+
+    fn index(&self) -> u64 {
+        match self {
+            Either::Left(point_reader) => point_reader.index(),
+            Either::Right(point_reader) => point_reader.index(),
+        }
+    }
+
+    fn point_offset(&self, index: u64) -> Option<u64> {
+        match self {
+            Either::Left(point_reader) => point_reader.point_offset(index),
+            Either::Right(point_reader) => point_reader.point_offset(index),
+        }
+    }
+
+    fn header(&self) -> &Header {
+        match self {
+            Either::Left(point_reader) => point_reader.header(),
+            Either::Right(point_reader) => point_reader.header(),
+        }
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        match self {
+            Either::Left(point_reader) => point_reader.read_at(offset, buf),
+            Either::Right(point_reader) => point_reader.read_at(offset, buf),
+        }
+    }
+
+    fn chunk_table(&mut self) -> Result<(u64, ChunkTable)> {
+        match self {
+            Either::Left(point_reader) => point_reader.chunk_table(),
+            Either::Right(point_reader) => point_reader.chunk_table(),
+        }
+    }
+
+    fn seek_in_chunk(&mut self, chunk_index: usize, offset: u64) -> Result<Option<u64>> {
+        match self {
+            Either::Left(point_reader) => point_reader.seek_in_chunk(chunk_index, offset),
+            Either::Right(point_reader) => point_reader.seek_in_chunk(chunk_index, offset),
+        }
+    }
+
+    fn skipped_chunks(&self) -> &[SkippedChunk] {
+        match self {
+            Either::Left(point_reader) => point_reader.skipped_chunks(),
+            Either::Right(point_reader) => point_reader.skipped_chunks(),
+        }
+    }
+
+    fn missing_chunk_table(&self) -> bool {
+        match self {
+            Either::Left(point_reader) => point_reader.missing_chunk_table(),
+            Either::Right(point_reader) => point_reader.missing_chunk_table(),
+        }
+    }
+
+    fn number_of_chunks(&mut self) -> Result<usize> {
+        match self {
+            Either::Left(point_reader) => point_reader.number_of_chunks(),
+            Either::Right(point_reader) => point_reader.number_of_chunks(),
+        }
+    }
+
+    fn read_chunk(&mut self, chunk_index: usize) -> Result<Vec<Point>> {
+        match self {
+            Either::Left(point_reader) => point_reader.read_chunk(chunk_index),
+            Either::Right(point_reader) => point_reader.read_chunk(chunk_index),
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    fn read_compressed_chunk(&mut self, chunk_index: usize) -> Result<Option<CompressedChunk>> {
+        match self {
+            Either::Left(point_reader) => point_reader.read_compressed_chunk(chunk_index),
+            Either::Right(point_reader) => point_reader.read_compressed_chunk(chunk_index),
+        }
+    }
 }
@@ -0,0 +1,93 @@
+//! Reading points straight from a memory-mapped file.
+
+use super::Mmap;
+use crate::{Reader, Result};
+use std::{fs::File, io::Cursor, path::Path};
+
+impl Reader<Cursor<Mmap>> {
+    /// Creates a new reader that decodes points straight from a memory mapping of a file.
+    ///
+    /// This skips the copy through a `BufReader` that [Reader::from_path] makes, which is what
+    /// uncompressed files gain the most from, e.g. with [Reader::read_batch]. Compressed files
+    /// can be read too. Like [Reader::from_path], a `.lax` or `.wdp` file next to it is used if
+    /// there is one.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be changed, by this process or any other, while the reader is alive,
+    /// since the mapped bytes would change under the reader. See [Mmap::map].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = unsafe { Reader::open_mmap("tests/data/autzen.las") }.unwrap();
+    /// assert_eq!(106, reader.header().number_of_points());
+    /// ```
+    // Mapping a file can't be made safe, see above.
+    #[allow(unsafe_code)]
+    pub unsafe fn open_mmap<P: AsRef<Path>>(path: P) -> Result<Reader<Cursor<Mmap>>> {
+        let file = File::open(path.as_ref())?;
+        // SAFETY: passed on to the caller.
+        let mmap = unsafe { Mmap::map(&file) }?;
+        let reader = Reader::new(Cursor::new(mmap))?.with_wdp(path.as_ref().with_extension("wdp"));
+        let lax_path = path.as_ref().with_extension("lax");
+        if reader.lax.is_none() && lax_path.is_file() {
+            Ok(reader.with_lax(lax_path))
+        } else {
+            Ok(reader)
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(unsafe_code)]
+mod tests {
+    use crate::{point::PointBuffer, Reader};
+
+    fn check(path: &str) {
+        let expected = Reader::from_path(path).unwrap().into_points().unwrap();
+        let reader = unsafe { Reader::open_mmap(path) }.unwrap();
+        assert_eq!(expected, reader.into_points().unwrap());
+    }
+
+    #[test]
+    fn las() {
+        check("tests/data/autzen.las");
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn laz() {
+        check("tests/data/autzen.laz");
+    }
+
+    #[test]
+    fn read_batch() {
+        let mut expected = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut reader = unsafe { Reader::open_mmap("tests/data/autzen.las") }.unwrap();
+        let mut buffer = PointBuffer::default();
+        for _ in 0..3 {
+            let mut expected_buffer = PointBuffer::default();
+            assert_eq!(
+                expected.read_batch(&mut expected_buffer, 50).unwrap(),
+                reader.read_batch(&mut buffer, 50).unwrap()
+            );
+            assert_eq!(expected_buffer, buffer);
+        }
+    }
+
+    #[test]
+    fn empty_file() {
+        let path = std::env::temp_dir().join("las-rs-mmap-empty.las");
+        let _ = std::fs::File::create(&path).unwrap();
+        let result = unsafe { Reader::open_mmap(&path) };
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn missing_file() {
+        assert!(unsafe { Reader::open_mmap("tests/data/not-a-file.las") }.is_err());
+    }
+}
@@ -49,308 +49,3118 @@
 //! ```
 //!
 
+mod budget;
+#[cfg(feature = "laz")]
+mod compatibility;
+mod dataset;
+mod decimation;
+mod density;
+mod evlr;
+mod flight_line;
 mod las;
 #[cfg(feature = "laz")]
-mod laz;
+pub(crate) mod laz;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod multi;
+mod non_seekable;
+mod read_at;
+mod skippable;
+mod view;
+mod warning;
 
-use crate::{Error, Header, Point, Result};
+use self::skippable::Skippable;
+pub use self::{
+    budget::{Budget, ResumeToken},
+    dataset::{Dataset, DatasetPoints},
+    decimation::{DecimatedPointIterator, Decimation},
+    density::{DensityGrid, DensityGridOptions},
+    evlr::EvlrReader,
+    flight_line::{FlightLine, FlightLineIterator, FlightLinePoints, SegmentationOptions},
+    multi::{MultiPointIterator, MultiReader, MultiReaderOptions, SourcedPointIterator},
+    non_seekable::NonSeekable,
+    read_at::{ReadAt, ReadAtSource, DEFAULT_BLOCK_SIZE},
+    view::{FilteredPointIterator, RawPointView, RecordIterator},
+    warning::Warning,
+};
+#[cfg(feature = "digest")]
+use crate::Digest;
+use crate::{
+    header::{RecordLengthStatus, ReturnCountConflict},
+    index::Lax,
+    point::{ClassificationSet, Dimension, Format, PointBuffer},
+    progress::Progress,
+    raw,
+    waveform::{self, Internal, Samples, Wdp},
+    Bounds, Embedded, Error, Header, LasPointRecord, Point, ProgressEvent, Result, Transform,
+    Vector, Version,
+};
+#[cfg(feature = "mmap")]
+pub use memmap2::Mmap;
 use std::{
     fs::File,
     io::{BufReader, Seek},
-    path::Path,
+    ops::Range,
+    path::{Path, PathBuf},
 };
 
-trait ReadPoints {
+trait ReadPoints: Send {
     fn read_point(&mut self) -> Result<Option<Point>>;
+
+    /// Overwrites `point` with the next point, returning false if there are none left.
+    fn read_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        match self.read_point()? {
+            Some(next) => {
+                *point = next;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
     fn read_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64>;
+
+    /// Reads the next point record's bytes into `record`, returning false if there are none left.
+    fn read_record(&mut self, record: &mut [u8]) -> Result<bool>;
+
     fn seek(&mut self, index: u64) -> Result<()>;
+
+    /// Moves past the next `n` points as cheaply as possible, stopping at the last point.
+    fn skip(&mut self, n: u64) -> Result<()>;
+    fn index(&self) -> u64;
+    fn point_offset(&self, index: u64) -> Option<u64>;
     fn header(&self) -> &Header;
+
+    /// Fills `buf` from an absolute offset in the source, without moving the point cursor.
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()>;
+
+    /// Returns the offset of the first compressed chunk and the chunk table.
+    ///
+    /// Every entry has its real point count, even for fixed size chunks.
+    #[cfg(feature = "laz")]
+    fn chunk_table(&mut self) -> Result<(u64, ::laz::laszip::ChunkTable)>;
+
+    /// Moves to the point `offset` points into a chunk, found through the chunk table, and returns
+    /// its index, or returns `None` if the points can't be found by chunk.
+    #[cfg(feature = "laz")]
+    fn seek_in_chunk(&mut self, _chunk_index: usize, _offset: u64) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    fn skipped_chunks(&self) -> &[SkippedChunk];
+
+    /// Returns true if the points are compressed, but there's no usable chunk table.
+    fn missing_chunk_table(&self) -> bool {
+        false
+    }
+
+    fn number_of_chunks(&mut self) -> Result<usize>;
+
+    /// Reads every point of one chunk, without moving the point cursor.
+    fn read_chunk(&mut self, chunk_index: usize) -> Result<Vec<Point>>;
+
+    /// Reads the compressed bytes of one chunk, or returns `None` if the points aren't compressed.
+    #[cfg(all(feature = "laz", feature = "parallel"))]
+    fn read_compressed_chunk(
+        &mut self,
+        _chunk_index: usize,
+    ) -> Result<Option<laz::CompressedChunk>> {
+        Ok(None)
+    }
+}
+
+/// A `Read + Seek` that can be sent between threads, used to box up heterogeneous sources.
+///
+/// This is implemented for everything that is `Read + Seek + Send`, so a
+/// `Reader<Box<dyn ReadSeekSend>>` can hold any source:
+///
+/// ```
+/// use std::{fs::File, io::BufReader};
+/// use las::{reader::ReadSeekSend, Reader};
+///
+/// let read: Box<dyn ReadSeekSend> =
+///     Box::new(BufReader::new(File::open("tests/data/autzen.las").unwrap()));
+/// let reader = Reader::new(read).unwrap();
+/// ```
+pub trait ReadSeekSend: std::io::Read + Seek + Send {}
+
+impl<T: std::io::Read + Seek + Send + ?Sized> ReadSeekSend for T {}
+
+/// Both uncompressed and compressed points are read through statically dispatched readers. The
+/// compressed one is boxed, since it's much bigger.
+#[allow(clippy::large_enum_variant)]
+enum Decoder<R: std::io::Read + Seek> {
+    Las(las::PointReader<R>),
+    #[cfg(feature = "laz")]
+    Laz(Box<LazPointReader<R>>),
+}
+
+/// Reads compressed points, or, on the right, the extended points they came from if the file was
+/// written in laszip's las 1.4 compatibility mode.
+#[cfg(feature = "laz")]
+type LazPointReader<R> =
+    laz::Either<laz::LazyPointReader<R>, compatibility::PointReader<laz::LazyPointReader<R>>>;
+
+impl<R: std::io::Read + Seek + Send> ReadPoints for Decoder<R> {
+    fn read_point(&mut self) -> Result<Option<Point>> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.read_point(),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => {
+                let index = point_reader.index();
+                point_reader
+                    .read_point()
+                    .map_err(|err| compressed_read_failed(index, err))
+            }
+        }
+    }
+
+    fn read_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.read_point_into(point),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => {
+                let index = point_reader.index();
+                point_reader
+                    .read_point_into(point)
+                    .map_err(|err| compressed_read_failed(index, err))
+            }
+        }
+    }
+
+    fn read_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.read_points(n, points),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => {
+                let index = point_reader.index();
+                point_reader
+                    .read_points(n, points)
+                    .map_err(|err| compressed_read_failed(index, err))
+            }
+        }
+    }
+
+    fn read_record(&mut self, record: &mut [u8]) -> Result<bool> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.read_record(record),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => {
+                let index = point_reader.index();
+                point_reader
+                    .read_record(record)
+                    .map_err(|err| compressed_read_failed(index, err))
+            }
+        }
+    }
+
+    fn seek(&mut self, index: u64) -> Result<()> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.seek(index),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => point_reader.seek(index),
+        }
+    }
+
+    fn skip(&mut self, n: u64) -> Result<()> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.skip(n),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => point_reader.skip(n),
+        }
+    }
+
+    fn index(&self) -> u64 {
+        match self {
+            Decoder::Las(point_reader) => point_reader.index(),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => point_reader.index(),
+        }
+    }
+
+    fn point_offset(&self, index: u64) -> Option<u64> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.point_offset(index),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => point_reader.point_offset(index),
+        }
+    }
+
+    fn header(&self) -> &Header {
+        match self {
+            Decoder::Las(point_reader) => point_reader.header(),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => point_reader.header(),
+        }
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.read_at(offset, buf),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => point_reader.read_at(offset, buf),
+        }
+    }
+
+    #[cfg(feature = "laz")]
+    fn chunk_table(&mut self) -> Result<(u64, ::laz::laszip::ChunkTable)> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.chunk_table(),
+            Decoder::Laz(point_reader) => point_reader.chunk_table(),
+        }
+    }
+
+    #[cfg(feature = "laz")]
+    fn seek_in_chunk(&mut self, chunk_index: usize, offset: u64) -> Result<Option<u64>> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.seek_in_chunk(chunk_index, offset),
+            Decoder::Laz(point_reader) => point_reader.seek_in_chunk(chunk_index, offset),
+        }
+    }
+
+    fn skipped_chunks(&self) -> &[SkippedChunk] {
+        match self {
+            Decoder::Las(point_reader) => point_reader.skipped_chunks(),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => point_reader.skipped_chunks(),
+        }
+    }
+
+    fn missing_chunk_table(&self) -> bool {
+        match self {
+            Decoder::Las(_) => false,
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => point_reader.missing_chunk_table(),
+        }
+    }
+
+    fn number_of_chunks(&mut self) -> Result<usize> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.number_of_chunks(),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => point_reader.number_of_chunks(),
+        }
+    }
+
+    fn read_chunk(&mut self, chunk_index: usize) -> Result<Vec<Point>> {
+        match self {
+            Decoder::Las(point_reader) => point_reader.read_chunk(chunk_index),
+            #[cfg(feature = "laz")]
+            Decoder::Laz(point_reader) => point_reader.read_chunk(chunk_index),
+        }
+    }
+
+    #[cfg(all(feature = "laz", feature = "parallel"))]
+    fn read_compressed_chunk(
+        &mut self,
+        chunk_index: usize,
+    ) -> Result<Option<laz::CompressedChunk>> {
+        match self {
+            Decoder::Las(_) => Ok(None),
+            Decoder::Laz(point_reader) => point_reader.read_compressed_chunk(chunk_index),
+        }
+    }
+}
+
+/// Reads points from a decoder, leaving out withheld points if asked to.
+///
+/// Indices, seeks, and skips still count every point in the file.
+struct PointReader<R: std::io::Read + Seek> {
+    decoder: Decoder<R>,
+    skip_withheld: bool,
+    skipped_withheld: u64,
+    /// The attributes that are read, see [ReadOptions::dimensions] and
+    /// [ReadOptions::skip_waveforms].
+    dimensions: Option<u16>,
+    record: Vec<u8>,
+    progress: Option<Progress>,
+    /// Stop at the first point that can't be read, see [ReadOptions::recover_truncated].
+    recover_truncated: bool,
+    /// True if reading stopped at a point that couldn't be read.
+    stopped: bool,
+    /// The error that stopped reading, held back so the points read before it can be returned.
+    pending: Option<Error>,
+    lost_points: u64,
+}
+
+impl<R: std::io::Read + Seek + Send> PointReader<R> {
+    /// Returns true if a point record has its withheld flag set.
+    fn is_withheld_record(&self, record: &[u8]) -> bool {
+        if self.decoder.header().point_format().is_extended {
+            record[15] & 0b0000_0100 != 0
+        } else {
+            record[15] & 0b1000_0000 != 0
+        }
+    }
+
+    /// Returns the number of withheld points that have been left out.
+    fn skipped_withheld(&self) -> u64 {
+        self.skipped_withheld
+    }
+
+    /// Removes the withheld points from `points[start..]`, returning how many are left there.
+    fn drop_withheld(&mut self, points: &mut Vec<Point>, start: usize) -> u64 {
+        let mut kept = start;
+        for i in start..points.len() {
+            if points[i].is_withheld {
+                self.skipped_withheld += 1;
+            } else {
+                points.swap(kept, i);
+                kept += 1;
+            }
+        }
+        points.truncate(kept);
+        (kept - start) as u64
+    }
+
+    /// Reads up to `n` points, stopping before the point at index `end`.
+    ///
+    /// Returns the number of points that were read, including withheld points that were left out.
+    fn read_points_before(&mut self, end: u64, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        let n = n.min(end.saturating_sub(self.decoder.index()));
+        let start = points.len();
+        let read = self.decoder.read_points(n, points)?;
+        if self.skip_withheld {
+            let _ = self.drop_withheld(points, start);
+        }
+        self.deselect(points, start);
+        self.report_progress();
+        Ok(read)
+    }
+
+    /// Resets the dimensions that aren't read in `points[start..]`, for points that were decoded
+    /// whole, e.g. a chunk at a time.
+    fn deselect(&self, points: &mut [Point], start: usize) {
+        let Some(dimensions) = self.dimensions else {
+            return;
+        };
+        let selected = |skippable: Skippable| dimensions & skippable.bit() != 0;
+        for point in &mut points[start..] {
+            if !selected(Skippable::Intensity) {
+                point.intensity = 0;
+            }
+            if !selected(Skippable::ScanAngle) {
+                point.scan_angle = 0.;
+            }
+            if !selected(Skippable::UserData) {
+                point.user_data = 0;
+            }
+            if !selected(Skippable::PointSourceId) {
+                point.point_source_id = 0;
+            }
+            if !selected(Skippable::GpsTime) {
+                point.gps_time = None;
+            }
+            if !selected(Skippable::Color) {
+                point.color = None;
+            }
+            if !selected(Skippable::Nir) {
+                point.nir = None;
+            }
+            if !selected(Skippable::Waveform) {
+                point.waveform = None;
+            }
+            if !selected(Skippable::ExtraBytes) {
+                point.extra_bytes = Default::default();
+            }
+        }
+    }
+}
+
+impl<R: std::io::Read + Seek + Send> PointReader<R> {
+    /// Stops reading after a point that couldn't be read, if asked to, and says how many points
+    /// were lost.
+    fn recover<T>(&mut self, result: Result<T>) -> Result<T> {
+        match result {
+            Err(err) if self.recover_truncated && !matches!(err, Error::PointsLost { .. }) => {
+                let index = match &err {
+                    Error::PointReadFailed { index, .. } => *index,
+                    _ => self.decoder.index().saturating_sub(1),
+                };
+                let lost = self
+                    .decoder
+                    .header()
+                    .number_of_points()
+                    .saturating_sub(index);
+                self.stopped = true;
+                self.lost_points = lost;
+                Err(Error::PointsLost {
+                    index,
+                    lost,
+                    source: Box::new(err),
+                })
+            }
+            result => result,
+        }
+    }
+
+    /// Calls the progress callback, if there is one and it's due.
+    fn report_progress(&mut self) {
+        if let Some(progress) = &mut self.progress {
+            let header = self.decoder.header();
+            let points = self.decoder.index();
+            let format = header.point_format();
+            let bytes = (!format.is_compressed).then(|| points * u64::from(format.len()));
+            progress.update(points, Some(header.number_of_points()), bytes);
+        }
+    }
+
+    /// Reads the next point, without reporting progress.
+    fn next_point(&mut self) -> Result<Option<Point>> {
+        if self.dimensions.is_some() {
+            let mut point = Point::default();
+            return Ok(self.next_point_into(&mut point)?.then_some(point));
+        }
+        loop {
+            match self.decoder.read_point()? {
+                Some(point) if self.skip_withheld && point.is_withheld => {
+                    self.skipped_withheld += 1
+                }
+                point => return Ok(point),
+            }
+        }
+    }
+
+    /// Reads the next point into `point`, without reporting progress.
+    fn next_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        if let Some(dimensions) = self.dimensions {
+            let mut record = std::mem::take(&mut self.record);
+            let read = self.next_record(&mut record);
+            let result = match read {
+                Ok(true) => {
+                    let header = self.decoder.header();
+                    view::read_selected(
+                        &record,
+                        header.point_format(),
+                        header.effective_transforms(),
+                        dimensions,
+                        point,
+                    )
+                    .map(|()| true)
+                }
+                read => read,
+            };
+            self.record = record;
+            return result;
+        }
+        while self.decoder.read_point_into(point)? {
+            if !(self.skip_withheld && point.is_withheld) {
+                return Ok(true);
+            }
+            self.skipped_withheld += 1;
+        }
+        Ok(false)
+    }
+
+    /// Reads up to `n` points, without reporting progress.
+    fn next_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        if self.dimensions.is_some() {
+            let mut count = 0;
+            while count < n {
+                match self.next_point()? {
+                    Some(point) => points.push(point),
+                    None => break,
+                }
+                count += 1;
+            }
+            return Ok(count);
+        }
+        if !self.skip_withheld {
+            return self.decoder.read_points(n, points);
+        }
+        let mut count = 0;
+        while count < n {
+            let start = points.len();
+            if self.decoder.read_points(n - count, points)? == 0 {
+                break;
+            }
+            count += self.drop_withheld(points, start);
+        }
+        Ok(count)
+    }
+
+    /// Reads the next point record, without reporting progress.
+    fn next_record(&mut self, record: &mut [u8]) -> Result<bool> {
+        while self.decoder.read_record(record)? {
+            if !(self.skip_withheld && self.is_withheld_record(record)) {
+                return Ok(true);
+            }
+            self.skipped_withheld += 1;
+        }
+        Ok(false)
+    }
+}
+
+impl<R: std::io::Read + Seek + Send> ReadPoints for PointReader<R> {
+    fn read_point(&mut self) -> Result<Option<Point>> {
+        if let Some(err) = self.pending.take() {
+            return Err(err);
+        }
+        if self.stopped {
+            return Ok(None);
+        }
+        let point = self.next_point();
+        self.report_progress();
+        self.recover(point)
+    }
+
+    fn read_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        if let Some(err) = self.pending.take() {
+            return Err(err);
+        }
+        if self.stopped {
+            return Ok(false);
+        }
+        let read = self.next_point_into(point);
+        self.report_progress();
+        self.recover(read)
+    }
+
+    fn read_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        if let Some(err) = self.pending.take() {
+            return Err(err);
+        }
+        if self.stopped {
+            return Ok(0);
+        }
+        let start = points.len();
+        let read = self.next_points(n, points);
+        self.report_progress();
+        match self.recover(read) {
+            // The points before the failure are returned now, and the failure on the next read.
+            Err(err @ Error::PointsLost { .. }) if points.len() > start => {
+                if self.skip_withheld {
+                    let _ = self.drop_withheld(points, start);
+                }
+                self.pending = Some(err);
+                Ok((points.len() - start) as u64)
+            }
+            read => read,
+        }
+    }
+
+    fn read_record(&mut self, record: &mut [u8]) -> Result<bool> {
+        if let Some(err) = self.pending.take() {
+            return Err(err);
+        }
+        if self.stopped {
+            return Ok(false);
+        }
+        let read = self.next_record(record);
+        self.report_progress();
+        self.recover(read)
+    }
+
+    fn seek(&mut self, index: u64) -> Result<()> {
+        self.stopped = false;
+        self.pending = None;
+        self.decoder.seek(index)
+    }
+
+    fn skip(&mut self, n: u64) -> Result<()> {
+        self.decoder.skip(n)
+    }
+
+    fn index(&self) -> u64 {
+        self.decoder.index()
+    }
+
+    fn point_offset(&self, index: u64) -> Option<u64> {
+        self.decoder.point_offset(index)
+    }
+
+    fn header(&self) -> &Header {
+        self.decoder.header()
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        self.decoder.read_at(offset, buf)
+    }
+
+    #[cfg(feature = "laz")]
+    fn chunk_table(&mut self) -> Result<(u64, ::laz::laszip::ChunkTable)> {
+        self.decoder.chunk_table()
+    }
+
+    #[cfg(feature = "laz")]
+    fn seek_in_chunk(&mut self, chunk_index: usize, offset: u64) -> Result<Option<u64>> {
+        self.stopped = false;
+        self.pending = None;
+        self.decoder.seek_in_chunk(chunk_index, offset)
+    }
+
+    fn skipped_chunks(&self) -> &[SkippedChunk] {
+        self.decoder.skipped_chunks()
+    }
+
+    fn missing_chunk_table(&self) -> bool {
+        self.decoder.missing_chunk_table()
+    }
+
+    fn number_of_chunks(&mut self) -> Result<usize> {
+        self.decoder.number_of_chunks()
+    }
+
+    fn read_chunk(&mut self, chunk_index: usize) -> Result<Vec<Point>> {
+        let mut points = self.decoder.read_chunk(chunk_index)?;
+        self.deselect(&mut points, 0);
+        Ok(points)
+    }
+
+    #[cfg(all(feature = "laz", feature = "parallel"))]
+    fn read_compressed_chunk(
+        &mut self,
+        chunk_index: usize,
+    ) -> Result<Option<laz::CompressedChunk>> {
+        self.decoder.read_compressed_chunk(chunk_index)
+    }
+}
+
+/// Wraps an error from decompressing points, starting with the point at `index`.
+#[cfg(feature = "laz")]
+fn compressed_read_failed(index: u64, err: Error) -> Error {
+    Error::PointReadFailed {
+        index,
+        offset: None,
+        source: Box::new(err),
+    }
 }
 
 /// An iterator over of the points in a `Reader`.
 ///
 /// This struct is generally created by calling `points()` on `Reader`.
 #[allow(missing_debug_implementations)]
-pub struct PointIterator<'a> {
-    point_reader: &'a mut dyn ReadPoints,
+pub struct PointIterator<'a, R: std::io::Read + Seek + Send = BufReader<File>> {
+    point_reader: &'a mut PointReader<R>,
+}
+
+impl<R: std::io::Read + Seek + Send> Iterator for PointIterator<'_, R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.point_reader.read_point().transpose()
+    }
+}
+
+/// An iterator over batches of the points in a `Reader`.
+///
+/// This struct is created by [Reader::chunks].
+#[allow(missing_debug_implementations)]
+pub struct PointChunks<'a, R: std::io::Read + Seek + Send = BufReader<File>> {
+    point_reader: &'a mut PointReader<R>,
+    n: u64,
+    done: bool,
+}
+
+impl<R: std::io::Read + Seek + Send> Iterator for PointChunks<'_, R> {
+    type Item = Result<Vec<Point>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut points = Vec::with_capacity(usize::try_from(self.n).unwrap_or(0).min(1 << 16));
+        match self.point_reader.read_points(self.n, &mut points) {
+            Ok(_) if points.is_empty() => {
+                self.done = true;
+                None
+            }
+            Ok(_) => Some(Ok(points)),
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// An iterator over the points in a `Reader`, decompressing laz chunks on the rayon thread pool.
+///
+/// This struct is created by [Reader::points_par].
+#[cfg(feature = "parallel")]
+#[allow(missing_debug_implementations)]
+pub struct ParPointIterator<'a, R: std::io::Read + Seek + Send = BufReader<File>> {
+    point_reader: &'a mut PointReader<R>,
+    next_chunk: usize,
+    chunks: Option<usize>,
+    points: std::vec::IntoIter<Point>,
+}
+
+#[cfg(feature = "parallel")]
+impl<R: std::io::Read + Seek + Send> ParPointIterator<'_, R> {
+    /// Reads the next chunks, one for each thread, and decodes them.
+    fn read_chunks(&mut self) -> Result<Vec<Point>> {
+        let chunks = match self.chunks {
+            Some(chunks) => chunks,
+            None => *self.chunks.insert(self.point_reader.number_of_chunks()?),
+        };
+        let end = chunks.min(self.next_chunk + rayon::current_num_threads().max(1));
+        let mut points = Vec::new();
+        #[cfg(feature = "laz")]
+        {
+            use rayon::prelude::*;
+
+            let mut compressed = Vec::new();
+            for chunk_index in self.next_chunk..end {
+                match self.point_reader.read_compressed_chunk(chunk_index)? {
+                    Some(chunk) => compressed.push(chunk),
+                    None => break,
+                }
+            }
+            if !compressed.is_empty() {
+                let header = self.point_reader.header();
+                let decompressed = compressed
+                    .into_par_iter()
+                    .map(|chunk| chunk.decompress(header))
+                    .collect::<Vec<_>>();
+                for chunk_points in decompressed {
+                    points.extend(chunk_points?);
+                }
+                self.next_chunk = end;
+            }
+        }
+        for chunk_index in self.next_chunk..end {
+            points.extend(self.point_reader.read_chunk(chunk_index)?);
+        }
+        self.next_chunk = end;
+        if self.point_reader.skip_withheld {
+            let len = points.len();
+            points.retain(|point| !point.is_withheld);
+            self.point_reader.skipped_withheld += (len - points.len()) as u64;
+        }
+        self.point_reader.deselect(&mut points, 0);
+        Ok(points)
+    }
 }
 
-impl Iterator for PointIterator<'_> {
-    type Item = Result<Point>;
+#[cfg(feature = "parallel")]
+impl<R: std::io::Read + Seek + Send> Iterator for ParPointIterator<'_, R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(point) = self.points.next() {
+                return Some(Ok(point));
+            }
+            if self.chunks.is_some_and(|chunks| self.next_chunk >= chunks) {
+                return None;
+            }
+            match self.read_chunks() {
+                Ok(points) => self.points = points.into_iter(),
+                Err(err) => {
+                    self.chunks = Some(0);
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the raw bytes of the point records in a `Reader`.
+///
+/// This struct is created by [Reader::raw_records].
+#[allow(missing_debug_implementations)]
+pub struct RawRecords<'a, R: std::io::Read + Seek + Send = BufReader<File>> {
+    point_reader: &'a mut PointReader<R>,
+    len: usize,
+}
+
+impl<R: std::io::Read + Seek + Send> Iterator for RawRecords<'_, R> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = vec![0; self.len];
+        match self.point_reader.read_record(&mut record) {
+            Ok(true) => Some(Ok(record)),
+            Ok(false) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// An iterator that owns a [Reader] and reads its points.
+///
+/// This struct is created by [Reader::into_point_iter]. Unlike [PointIterator], it doesn't borrow
+/// the reader, so it can be moved into another thread.
+#[allow(missing_debug_implementations)]
+pub struct OwnedPoints<R: std::io::Read + Seek + Send = BufReader<File>> {
+    reader: Reader<R>,
+}
+
+impl<R: std::io::Read + Seek + Send> OwnedPoints<R> {
+    /// Returns the header of the reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().into_point_iter();
+    /// assert_eq!(106, points.header().number_of_points());
+    /// ```
+    pub fn header(&self) -> &Header {
+        self.reader.header()
+    }
+
+    /// Returns the reader, positioned after the last point that was read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut points = Reader::from_path("tests/data/autzen.las").unwrap().into_point_iter();
+    /// let _ = points.next().unwrap().unwrap();
+    /// let mut reader = points.into_reader();
+    /// assert_eq!(105, reader.read_points(200).unwrap().len());
+    /// ```
+    pub fn into_reader(self) -> Reader<R> {
+        self.reader
+    }
+}
+
+impl<R: std::io::Read + Seek + Send> Iterator for OwnedPoints<R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.point_reader.read_point().transpose()
+    }
+}
+
+/// An iterator over the points in a `Reader`, along with their zero-based index.
+///
+/// This struct is generally created by calling `indexed_points()` on `Reader`.
+#[allow(missing_debug_implementations)]
+pub struct IndexedPointIterator<'a, R: std::io::Read + Seek + Send = BufReader<File>> {
+    point_reader: &'a mut PointReader<R>,
+}
+
+impl<R: std::io::Read + Seek + Send> Iterator for IndexedPointIterator<'_, R> {
+    type Item = Result<(u64, Point)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let point_reader = &mut *self.point_reader;
+        point_reader
+            .read_point()
+            .map(|point| point.map(|point| (point_reader.index() - 1, point)))
+            .transpose()
+    }
+}
+
+/// An iterator over the points in a `Reader`, along with a value mapped from their user data.
+///
+/// This struct is generally created by calling `map_user_data()` on `Reader`.
+#[allow(missing_debug_implementations)]
+pub struct UserDataIterator<'a, F, R: std::io::Read + Seek + Send = BufReader<File>> {
+    point_reader: &'a mut PointReader<R>,
+    f: F,
+}
+
+impl<T, F: Fn(u8) -> T, R: std::io::Read + Seek + Send> Iterator for UserDataIterator<'_, F, R> {
+    type Item = Result<(Point, T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.point_reader
+            .read_point()
+            .map(|point| {
+                point.map(|point| {
+                    let value = (self.f)(point.user_data);
+                    (point, value)
+                })
+            })
+            .transpose()
+    }
+}
+
+/// An iterator over the points in a `Reader` with certain classifications.
+///
+/// This struct is generally created by calling `points_with_classification()` on `Reader`.
+#[allow(missing_debug_implementations)]
+pub struct ClassifiedPointIterator<'a, R: std::io::Read + Seek + Send = BufReader<File>> {
+    point_reader: &'a mut PointReader<R>,
+    classes: ClassificationSet,
+    format: Format,
+    transforms: Vector<Transform>,
+    record: Vec<u8>,
+}
+
+impl<R: std::io::Read + Seek + Send> Iterator for ClassifiedPointIterator<'_, R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.point_reader.read_record(&mut self.record) {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+            if let Some(n) = self.format.unknown {
+                return Some(Err(Error::UnknownPointFormat(n)));
+            }
+            // x, y, z, and intensity take up the first fourteen bytes, then come the flags.
+            let code = if self.format.is_extended {
+                self.record[16]
+            } else {
+                self.record[15] & 0b0001_1111
+            };
+            if self.classes.contains_code(code) {
+                return Some(point_from_record(
+                    &self.record,
+                    &self.format,
+                    &self.transforms,
+                ));
+            }
+        }
+    }
+}
+
+/// An iterator over the points in a `Reader` with a certain point source id.
+///
+/// This struct is generally created by calling `points_with_source_id()` on `Reader`.
+#[allow(missing_debug_implementations)]
+pub struct SourceIdPointIterator<'a, R: std::io::Read + Seek + Send = BufReader<File>> {
+    point_reader: &'a mut PointReader<R>,
+    source_id: u16,
+    assume_sorted: bool,
+    end: Option<u64>,
+    format: Format,
+    transforms: Vector<Transform>,
+    record: Vec<u8>,
+}
+
+impl<R: std::io::Read + Seek + Send> SourceIdPointIterator<'_, R> {
+    /// The offset of the point source id in a record.
+    fn offset(&self) -> usize {
+        if self.format.is_extended {
+            20
+        } else {
+            18
+        }
+    }
+
+    /// Returns the index of the first point whose source id is at least (or, if `above`, more
+    /// than) the one we're looking for, assuming the points are sorted.
+    fn partition_point(&mut self, above: bool) -> Result<u64> {
+        let (mut low, mut high) = (0, self.point_reader.header().number_of_points());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let Some(offset) = self.point_reader.point_offset(middle) else {
+                break;
+            };
+            let mut bytes = [0; 2];
+            self.point_reader
+                .read_at(offset + self.offset() as u64, &mut bytes)?;
+            let source_id = u16::from_le_bytes(bytes);
+            if source_id < self.source_id || (above && source_id == self.source_id) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Binary searches for the range of points with the source id, seeks to its start, and
+    /// returns its end.
+    fn find_range(&mut self) -> Result<u64> {
+        let start = self.partition_point(false)?;
+        let end = self.partition_point(true)?;
+        if self.point_reader.index() < start {
+            self.point_reader.seek(start)?;
+        }
+        Ok(end)
+    }
+}
+
+impl<R: std::io::Read + Seek + Send> Iterator for SourceIdPointIterator<'_, R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(n) = self.format.unknown {
+            return match self.point_reader.read_record(&mut self.record) {
+                Ok(true) => Some(Err(Error::UnknownPointFormat(n))),
+                Ok(false) => None,
+                Err(err) => Some(Err(err)),
+            };
+        }
+        let end = match self.end {
+            Some(end) => end,
+            None => {
+                let end = if self.assume_sorted && self.point_reader.point_offset(0).is_some() {
+                    match self.find_range() {
+                        Ok(end) => end,
+                        Err(err) => return Some(Err(err)),
+                    }
+                } else {
+                    self.point_reader.header().number_of_points()
+                };
+                self.end = Some(end);
+                end
+            }
+        };
+        while self.point_reader.index() < end {
+            match self.point_reader.read_record(&mut self.record) {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+            let offset = self.offset();
+            let source_id = u16::from_le_bytes([self.record[offset], self.record[offset + 1]]);
+            if source_id == self.source_id {
+                return Some(point_from_record(
+                    &self.record,
+                    &self.format,
+                    &self.transforms,
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Overwrites `point` with a raw point that `read` fills in, reusing the point's extra bytes.
+fn read_into_point(
+    point: &mut Point,
+    transforms: &Vector<Transform>,
+    read: impl FnOnce(&mut raw::Point) -> Result<()>,
+) -> Result<()> {
+    let mut raw_point = raw::Point {
+        extra_bytes: std::mem::take(&mut point.extra_bytes),
+        ..Default::default()
+    };
+    read(&mut raw_point)?;
+    *point = Point::new(raw_point, transforms);
+    Ok(())
+}
+
+fn point_from_record(
+    record: &[u8],
+    format: &Format,
+    transforms: &Vector<Transform>,
+) -> Result<Point> {
+    raw::Point::read_from(record, format).map(|raw_point| Point::new(raw_point, transforms))
+}
+
+/// A trait for objects which read LAS data.
+#[deprecated(
+    since = "0.9.0",
+    note = "This interface has been refactored so that importing `Read` is no longer required"
+)]
+pub trait Read<R: std::io::Read + Seek + Send = BufReader<File>> {
+    /// Returns a reference to this reader's header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let header = reader.header();
+    /// ```
+    fn header(&self) -> &Header;
+
+    /// Reads a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let point = reader.read().unwrap().unwrap();
+    /// ```
+    fn read(&mut self) -> Option<Result<Point>>;
+
+    /// Reads n points.
+    fn read_n(&mut self, n: u64) -> Result<Vec<Point>>;
+
+    /// Reads n points into the vec
+    fn read_n_into(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64>;
+
+    /// Reads all points left into the vec
+    fn read_all_points(&mut self, points: &mut Vec<Point>) -> Result<u64>;
+
+    /// Seeks to the given point number, zero-indexed.
+    ///
+    /// Note that seeking on compressed (LAZ) data can be expensive as the reader
+    /// will have to seek to the closest chunk start and decompress all points up until
+    /// the point seeked to.
+    ///
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// reader.seek(1).unwrap(); // <- seeks to the second point
+    /// let the_second_point = reader.read().unwrap().unwrap();
+    /// ```
+    fn seek(&mut self, position: u64) -> Result<()>;
+
+    /// Returns an iterator over this reader's points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points().collect::<Result<Vec<_>, _>>().unwrap();
+    /// ```
+    fn points(&mut self) -> PointIterator<'_, R>;
+}
+
+/// The most points that [Reader::into_points] allocates for before it starts reading.
+pub const MAX_PREALLOCATED_POINTS: usize = 1 << 20;
+
+/// The number of points in each chunk of an uncompressed file, see [Reader::read_chunk_at].
+///
+/// This is the same as the default laz chunk size.
+pub const UNCOMPRESSED_CHUNK_SIZE: u64 = 50_000;
+
+/// Options for [Reader::with_options].
+///
+/// The defaults are what [Reader::new] uses. Options that read leniently report what they did
+/// through [Reader::summary].
+///
+/// # Examples
+///
+/// ```
+/// use las::{reader::ReadOptions, Reader};
+///
+/// let options = ReadOptions::default().assume_sorted_by_source_id(true);
+/// let file = std::fs::File::open("tests/data/autzen.las").unwrap();
+/// let reader = Reader::with_options(std::io::BufReader::new(file), options).unwrap();
+/// assert!(reader.options().assumes_sorted_by_source_id());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReadOptions {
+    skip_corrupt_chunks: bool,
+    assume_sorted_by_source_id: bool,
+    read_short_records: bool,
+    skip_withheld: bool,
+    read_unknown_formats: bool,
+    lenient: bool,
+    transforms: Vector<Option<Transform>>,
+    dimensions: Option<u16>,
+    skip_waveforms: bool,
+    max_evlr_len: Option<u64>,
+    recover_truncated: bool,
+}
+
+impl ReadOptions {
+    /// Skips the rest of a laz chunk that fails to decompress, instead of returning the error.
+    ///
+    /// Chunks are compressed independently, so reading picks up again at the start of the next
+    /// chunk. The skipped points are listed by [Reader::skipped_chunks]. This has no effect on
+    /// uncompressed files.
+    ///
+    /// Each chunk is read into memory and decompressed on its own, even with the `laz-parallel`
+    /// feature, and the file must have a chunk table.
+    pub fn skip_corrupt_chunks(mut self, skip_corrupt_chunks: bool) -> ReadOptions {
+        self.skip_corrupt_chunks = skip_corrupt_chunks;
+        self
+    }
+
+    /// Promises that the points are sorted by point source id, so that
+    /// [Reader::points_with_source_id] can binary search for them.
+    ///
+    /// The search reads just the two source id bytes of a few records, then reads only the
+    /// matching range. If the points aren't actually sorted, the search may land on the wrong
+    /// range, and matching points outside of it are missed. The points that are returned always
+    /// have the requested source id. This has no effect on compressed files, which are always
+    /// scanned.
+    pub fn assume_sorted_by_source_id(mut self, assume_sorted_by_source_id: bool) -> ReadOptions {
+        self.assume_sorted_by_source_id = assume_sorted_by_source_id;
+        self
+    }
+
+    /// Reads uncompressed files whose point data record length is shorter than the point format,
+    /// instead of refusing them.
+    ///
+    /// Each record's bytes fill the start of the point, and the missing bytes at the end are read
+    /// as zeros. The shortfall is reported by [Header::record_length_consistency] and
+    /// [ReadSummary::record_length_deficit]. Compressed files with short records are still
+    /// refused.
+    pub fn read_short_records(mut self, read_short_records: bool) -> ReadOptions {
+        self.read_short_records = read_short_records;
+        self
+    }
+
+    /// Leaves withheld points out of every way of reading points, instead of returning them.
+    ///
+    /// Point indices, [Reader::seek], and [Reader::point_offset] still count every point in the
+    /// file, including the withheld ones, and so does the header. The number of points left out
+    /// is reported by [ReadSummary::skipped_withheld].
+    pub fn skip_withheld(mut self, skip_withheld: bool) -> ReadOptions {
+        self.skip_withheld = skip_withheld;
+        self
+    }
+
+    /// Opens uncompressed files whose point format number this crate doesn't know, e.g. a future
+    /// format eleven, for raw access.
+    ///
+    /// The header's point format is [Format::unknown](crate::point::Format::unknown). Its header,
+    /// vlrs, and point counts can be used, and its records can be read with
+    /// [Reader::raw_records] or copied with [extract_range](crate::extract_range), but reading
+    /// points returns [Error::UnknownPointFormat]. Withheld points can't be found, so
+    /// [ReadOptions::skip_withheld] has no effect.
+    ///
+    /// The format must still be plausible: records must be at least twenty bytes, and the points
+    /// must fit in the file if its length is known. Otherwise, the format number is refused as
+    /// usual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::ReadOptions;
+    /// assert!(!ReadOptions::default().reads_unknown_formats());
+    /// assert!(ReadOptions::default().read_unknown_formats(true).reads_unknown_formats());
+    /// ```
+    pub fn read_unknown_formats(mut self, read_unknown_formats: bool) -> ReadOptions {
+        self.read_unknown_formats = read_unknown_formats;
+        self
+    }
+
+    /// Repairs or ignores common problems in the headers of files from old or broken software,
+    /// instead of refusing them or failing partway through the points.
+    ///
+    /// Each repair is listed by [Reader::warnings] and logged:
+    ///
+    /// - Bytes that aren't ascii in the system identifier and generating software are replaced with
+    ///   `?`, so the header can be written back.
+    /// - A scale of zero is replaced with the default scale.
+    /// - If an uncompressed file's header has more points than fit in the file, the number of
+    ///   points is cut to what fits.
+    /// - A creation date that isn't a real day is noted, and the header has no date, as usual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::ReadOptions, Reader};
+    ///
+    /// let mut bytes = std::fs::read("tests/data/autzen.las").unwrap();
+    /// bytes.truncate(bytes.len() - 20);
+    /// let options = ReadOptions::default().lenient(true);
+    /// let mut reader = Reader::with_options(std::io::Cursor::new(bytes), options).unwrap();
+    /// assert_eq!(105, reader.header().number_of_points());
+    /// assert_eq!(1, reader.warnings().len());
+    /// assert_eq!(105, reader.points().count());
+    /// ```
+    pub fn lenient(mut self, lenient: bool) -> ReadOptions {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Reads only these dimensions of each point, leaving the others at their defaults.
+    ///
+    /// The coordinates and the flags are always read. The other attributes are skipped over in
+    /// each record, so e.g. `Point::color` is `None` even if the point format has colors, unless
+    /// one of its channels, like [Dimension::Red], is selected. Selecting any extra bytes
+    /// attribute reads all of a point's extra bytes. Raw records are still read whole. A waveform
+    /// isn't a dimension, so it's read unless [ReadOptions::skip_waveforms] is on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, reader::ReadOptions, Reader};
+    ///
+    /// let options = ReadOptions::default().dimensions(&[Dimension::Intensity]);
+    /// let file = std::fs::File::open("tests/data/autzen.las").unwrap();
+    /// let mut reader = Reader::with_options(std::io::BufReader::new(file), options).unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert!(point.intensity > 0);
+    /// assert_eq!(None, point.gps_time);
+    /// ```
+    pub fn dimensions(mut self, dimensions: &[Dimension]) -> ReadOptions {
+        self.dimensions = Some(
+            dimensions
+                .iter()
+                .filter_map(Skippable::of)
+                .fold(0, |bits, skippable| bits | skippable.bit()),
+        );
+        self
+    }
+
+    /// Leaves [Point::waveform] as `None` instead of reading each point's waveform packet
+    /// descriptor.
+    ///
+    /// This has no effect on point formats without waveforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, reader::ReadOptions};
+    ///
+    /// let options = ReadOptions::default()
+    ///     .dimensions(&[Dimension::Intensity])
+    ///     .skip_waveforms(true);
+    /// assert!(options.skips_waveforms());
+    /// ```
+    pub fn skip_waveforms(mut self, skip_waveforms: bool) -> ReadOptions {
+        self.skip_waveforms = skip_waveforms;
+        self
+    }
+
+    /// Leaves evlrs with more than this many bytes of data out of the header, instead of reading
+    /// them into memory when the file is opened.
+    ///
+    /// The left out evlrs can still be read a piece at a time with [Reader::evlr_reader].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::ReadOptions;
+    /// let options = ReadOptions::default().max_evlr_len(1 << 20);
+    /// assert_eq!(Some(1 << 20), options.max_evlr_len_bytes());
+    /// ```
+    pub fn max_evlr_len(mut self, len: u64) -> ReadOptions {
+        self.max_evlr_len = Some(len);
+        self
+    }
+
+    /// Stops reading at the first point that can't be read, e.g. because the file is truncated
+    /// or corrupt, instead of trying every point after it.
+    ///
+    /// The points before it are read as usual. The failure is returned once, as
+    /// [Error::PointsLost] with the number of points that weren't read, and then reading returns
+    /// no more points until the reader is [seeked](Reader::seek). The number of lost points is
+    /// also reported by [ReadSummary::lost_points].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::ReadOptions, Error, Reader};
+    ///
+    /// let mut bytes = std::fs::read("tests/data/autzen.las").unwrap();
+    /// bytes.truncate(bytes.len() - 20);
+    /// let options = ReadOptions::default().recover_truncated(true);
+    /// let mut reader = Reader::with_options(std::io::Cursor::new(bytes), options).unwrap();
+    /// let results: Vec<_> = reader.points().collect();
+    /// assert_eq!(106, results.len());
+    /// assert!(matches!(results[105], Err(Error::PointsLost { index: 105, lost: 1, .. })));
+    /// assert_eq!(1, reader.summary().lost_points);
+    /// ```
+    pub fn recover_truncated(mut self, recover_truncated: bool) -> ReadOptions {
+        self.recover_truncated = recover_truncated;
+        self
+    }
+
+    /// Computes point coordinates with these transforms instead of the header's, on the axes that
+    /// have one.
+    ///
+    /// This fixes files whose header has the wrong scale or offset without rewriting them. The
+    /// header's [transforms](Header::transforms) are still the ones in the file, and
+    /// [Header::effective_transforms] are the ones that points are read with. The header's bounds
+    /// are moved to the new transforms, and files written from the reader's header use them too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::ReadOptions, Reader, Transform, Vector};
+    ///
+    /// let file = std::fs::File::open("tests/data/autzen.las").unwrap();
+    /// let mut reader = Reader::new(std::io::BufReader::new(file)).unwrap();
+    /// let z = reader.header().transforms().z;
+    /// let point = reader.read_point().unwrap().unwrap();
+    ///
+    /// let shifted = Transform { offset: z.offset + 30., ..z };
+    /// let options = ReadOptions::default().override_transforms(Vector { x: None, y: None, z: Some(shifted) });
+    /// let file = std::fs::File::open("tests/data/autzen.las").unwrap();
+    /// let mut reader = Reader::with_options(std::io::BufReader::new(file), options).unwrap();
+    /// assert_eq!(point.z + 30., reader.read_point().unwrap().unwrap().z);
+    /// ```
+    pub fn override_transforms(mut self, transforms: Vector<Option<Transform>>) -> ReadOptions {
+        self.transforms = transforms;
+        self
+    }
+
+    /// Returns the transforms that override the header's, per axis.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::ReadOptions, Vector};
+    /// assert_eq!(Vector::default(), ReadOptions::default().transform_overrides());
+    /// ```
+    pub fn transform_overrides(&self) -> Vector<Option<Transform>> {
+        self.transforms
+    }
+
+    /// Returns true if laz chunks that fail to decompress are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::ReadOptions;
+    /// assert!(!ReadOptions::default().skips_corrupt_chunks());
+    /// assert!(ReadOptions::default().skip_corrupt_chunks(true).skips_corrupt_chunks());
+    /// ```
+    pub fn skips_corrupt_chunks(&self) -> bool {
+        self.skip_corrupt_chunks
+    }
+
+    /// Returns true if the points are assumed to be sorted by point source id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::ReadOptions;
+    /// assert!(!ReadOptions::default().assumes_sorted_by_source_id());
+    /// ```
+    pub fn assumes_sorted_by_source_id(&self) -> bool {
+        self.assume_sorted_by_source_id
+    }
+
+    /// Returns true if point records that are too short for the point format are read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::ReadOptions;
+    /// assert!(!ReadOptions::default().reads_short_records());
+    /// assert!(ReadOptions::default().read_short_records(true).reads_short_records());
+    /// ```
+    pub fn reads_short_records(&self) -> bool {
+        self.read_short_records
+    }
+
+    /// Returns true if withheld points are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::ReadOptions;
+    /// assert!(!ReadOptions::default().skips_withheld());
+    /// assert!(ReadOptions::default().skip_withheld(true).skips_withheld());
+    /// ```
+    pub fn skips_withheld(&self) -> bool {
+        self.skip_withheld
+    }
+
+    /// Returns true if files with unknown point formats are opened for raw access.
+    ///
+    /// See [ReadOptions::read_unknown_formats].
+    pub fn reads_unknown_formats(&self) -> bool {
+        self.read_unknown_formats
+    }
+
+    /// Returns true if problems in the header are repaired or ignored.
+    ///
+    /// See [ReadOptions::lenient].
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Returns true if waveform packet descriptors are left out.
+    ///
+    /// See [ReadOptions::skip_waveforms].
+    pub fn skips_waveforms(&self) -> bool {
+        self.skip_waveforms
+    }
+
+    /// Returns true if reading stops at the first point that can't be read.
+    ///
+    /// See [ReadOptions::recover_truncated].
+    pub fn recovers_truncated(&self) -> bool {
+        self.recover_truncated
+    }
+
+    /// Returns the largest evlr that's read into the header, if there's a limit.
+    ///
+    /// See [ReadOptions::max_evlr_len].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::ReadOptions;
+    /// assert_eq!(None, ReadOptions::default().max_evlr_len_bytes());
+    /// ```
+    pub fn max_evlr_len_bytes(&self) -> Option<u64> {
+        self.max_evlr_len
+    }
+
+    /// Returns true if this dimension is read.
+    ///
+    /// Every dimension is read unless some are selected with [ReadOptions::dimensions].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, reader::ReadOptions};
+    /// assert!(ReadOptions::default().reads_dimension(&Dimension::Red));
+    /// let options = ReadOptions::default().dimensions(&[Dimension::GpsTime]);
+    /// assert!(options.reads_dimension(&Dimension::GpsTime));
+    /// assert!(options.reads_dimension(&Dimension::X));
+    /// assert!(!options.reads_dimension(&Dimension::Red));
+    /// ```
+    pub fn reads_dimension(&self, dimension: &Dimension) -> bool {
+        match (self.dimensions, Skippable::of(dimension)) {
+            (Some(dimensions), Some(skippable)) => dimensions & skippable.bit() != 0,
+            _ => true,
+        }
+    }
+
+    /// Returns the attributes that are read, or `None` if every attribute is read.
+    fn skippables(&self) -> Option<u16> {
+        let waveform = Skippable::Waveform.bit();
+        match (self.dimensions, self.skip_waveforms) {
+            (None, false) => None,
+            (None, true) => Some(Skippable::ALL & !waveform),
+            (Some(dimensions), false) => Some(dimensions | waveform),
+            (Some(dimensions), true) => Some(dimensions),
+        }
+    }
+}
+
+/// What a reader's lenient [ReadOptions] did while reading.
+///
+/// Returned by [Reader::summary].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ReadSummary<'a> {
+    /// The laz chunks that were skipped, see [ReadOptions::skip_corrupt_chunks].
+    pub skipped_chunks: &'a [SkippedChunk],
+
+    /// How many bytes at the end of each point were read as zeros, because the records are too
+    /// short, see [ReadOptions::read_short_records].
+    pub record_length_deficit: u16,
+
+    /// The number of withheld points that were left out, see [ReadOptions::skip_withheld].
+    pub skipped_withheld: u64,
+
+    /// True if the file is compressed, but has no usable chunk table, so its chunks are read in
+    /// order.
+    ///
+    /// Seeking decompresses forward from the nearest chunk that's been reached, and anything that
+    /// needs the chunk table, like [ReadOptions::skip_corrupt_chunks], is unavailable. This is
+    /// only known once points have been read.
+    pub missing_chunk_table: bool,
+
+    /// The number of vlrs and evlrs whose reserved bytes aren't zero, in a las 1.2 or later
+    /// file, see [Vlr::reserved](crate::Vlr::reserved).
+    ///
+    /// These are read, and written back, as they are, so they don't make the summary unclean.
+    pub nonconforming_vlr_reserved: usize,
+
+    /// The header's legacy and extended return counts, if they disagree, see
+    /// [Header::return_count_conflict].
+    ///
+    /// Like [ReadSummary::nonconforming_vlr_reserved], this doesn't make the summary unclean.
+    pub return_count_conflict: Option<ReturnCountConflict>,
+
+    /// The problems in the header that were repaired or ignored, see [ReadOptions::lenient].
+    pub warnings: &'a [Warning],
+
+    /// The number of points that weren't read because reading stopped at one that couldn't be,
+    /// see [ReadOptions::recover_truncated].
+    pub lost_points: u64,
+}
+
+impl ReadSummary<'_> {
+    /// Returns true if every point so far was read as it's stored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let _ = reader.read_points(10).unwrap();
+    /// assert!(reader.summary().is_clean());
+    /// ```
+    pub fn is_clean(&self) -> bool {
+        self.skipped_chunks.is_empty()
+            && self.record_length_deficit == 0
+            && self.skipped_withheld == 0
+            && !self.missing_chunk_table
+            && self.warnings.is_empty()
+            && self.lost_points == 0
+    }
+}
+
+/// A laz chunk, or the end of one, that was skipped because it failed to decompress.
+///
+/// See [ReadOptions::skip_corrupt_chunks].
+#[derive(Debug)]
+pub struct SkippedChunk {
+    /// The index of the chunk in the chunk table.
+    pub index: usize,
+
+    /// The indices of the points that were skipped.
+    pub points: Range<u64>,
+
+    /// The error that the chunk failed with.
+    pub error: Error,
+}
+
+/// Reads the header of the las file at a path, with its vlrs and evlrs, but none of its points.
+///
+/// This is much cheaper than [Reader::from_path] when only the metadata is needed, since no point
+/// reader or decompressor is set up. The header is the same as the reader's. Use [Header::new] to
+/// read a header from any `Read + Seek`.
+///
+/// # Examples
+///
+/// ```
+/// let header = las::read_header("tests/data/autzen.las").unwrap();
+/// assert_eq!(106, header.number_of_points());
+/// ```
+pub fn read_header<P: AsRef<Path>>(path: P) -> Result<Header> {
+    Header::new(BufReader::new(File::open(path)?))
+}
+
+/// Reads LAS data.
+///
+/// A reader is generic over its source, so reading from a `BufReader<File>` or a
+/// `Cursor<Vec<u8>>` is statically dispatched. Use `Reader<Box<dyn ReadSeekSend>>` if you need
+/// to hold readers over different sources in the same collection.
+#[allow(missing_debug_implementations)]
+pub struct Reader<R: std::io::Read + Seek = BufReader<File>> {
+    point_reader: PointReader<R>,
+    lax: Option<Lax>,
+    wdp: Option<Wdp>,
+    options: ReadOptions,
+    warnings: Vec<Warning>,
+}
+
+impl<R: std::io::Read + Seek + Send> Reader<R> {
+    /// Creates a new reader.
+    ///
+    /// This does *not* wrap the `Read` in a `BufRead`, so if you're concerned
+    /// about performance you should do that wrapping yourself (or use
+    /// `from_path`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufReader;
+    /// use std::fs::File;
+    /// # use las::Reader;
+    /// let file = File::open("tests/data/autzen.las").unwrap();
+    /// let reader = Reader::new(BufReader::new(file)).unwrap();
+    /// ```
+    pub fn new(read: R) -> Result<Reader<R>> {
+        Reader::with_options(read, ReadOptions::default())
+    }
+
+    /// Creates a reader for a las file that starts at an offset inside a larger stream.
+    ///
+    /// The file's offsets, e.g. to the points and the evlrs, count from the offset, and the reader
+    /// never seeks before it. See [Embedded].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut bytes = b"preamble".to_vec();
+    /// bytes.extend(std::fs::read("tests/data/autzen.las").unwrap());
+    /// let reader = Reader::new_at(Cursor::new(bytes), 8).unwrap();
+    /// assert_eq!(106, reader.header().number_of_points());
+    /// ```
+    pub fn new_at(read: R, offset: u64) -> Result<Reader<Embedded<R>>> {
+        Reader::new(Embedded::new(read, offset)?)
+    }
+
+    /// Creates a new reader with options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{fs::File, io::BufReader};
+    /// use las::{reader::ReadOptions, Reader};
+    ///
+    /// let file = BufReader::new(File::open("tests/data/autzen.las").unwrap());
+    /// let options = ReadOptions::default().skip_corrupt_chunks(true);
+    /// let reader = Reader::with_options(file, options).unwrap();
+    /// ```
+    pub fn with_options(read: R, options: ReadOptions) -> Result<Reader<R>> {
+        Reader::open(read, options, false)
+    }
+
+    /// Creates a reader that reads its stream in order when `sequential` is true, so it never seeks
+    /// backwards: the evlrs aren't read, and laz chunks are decompressed one after the other.
+    fn open(mut read: R, options: ReadOptions, sequential: bool) -> Result<Reader<R>> {
+        let (mut header, warnings) = Header::read_from(&mut read, &options, !sequential)?;
+        header.override_transforms(options.transforms);
+        let lax = header
+            .all_vlrs()
+            .filter_map(|vlr| Lax::from_vlr(&vlr))
+            .find_map(|lax| lax.ok());
+        let decoder =
+            if header.point_format().is_compressed {
+                #[cfg(feature = "laz")]
+                {
+                    let restored = crate::compatibility::Layout::restore(&header)?;
+                    let point_reader = laz::LazyPointReader::new(
+                        read,
+                        header,
+                        options.skip_corrupt_chunks,
+                        sequential,
+                    )?;
+                    Decoder::Laz(Box::new(match restored {
+                        Some((header, layout)) => laz::Either::Right(
+                            compatibility::PointReader::new(point_reader, header, layout),
+                        ),
+                        None => laz::Either::Left(point_reader),
+                    }))
+                }
+                #[cfg(not(feature = "laz"))]
+                {
+                    return Err(Error::LaszipNotEnabled);
+                }
+            } else {
+                Decoder::Las(las::PointReader::new(read, header)?)
+            };
+        let point_reader = PointReader {
+            skip_withheld: options.skip_withheld
+                && decoder.header().point_format().unknown.is_none(),
+            record: vec![0; usize::from(decoder.header().point_format().len())],
+            decoder,
+            skipped_withheld: 0,
+            dimensions: options.skippables(),
+            progress: None,
+            recover_truncated: options.recover_truncated,
+            stopped: false,
+            pending: None,
+            lost_points: 0,
+        };
+        Ok(Reader {
+            point_reader,
+            lax,
+            wdp: None,
+            options,
+            warnings,
+        })
+    }
+}
+
+impl Reader<BufReader<File>> {
+    /// Creates a new reader from a path.
+    ///
+    /// The underlying `File` is wrapped in a `BufReader` for performance reasons.
+    ///
+    /// If the file doesn't have an appended spatial index, a `.lax` file next to it is used
+    /// instead, if there is one. External waveform data packets are read from the `.wdp` file next
+    /// to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>> {
+        let reader = File::open(path.as_ref())
+            .map_err(Error::from)
+            .and_then(|file| Reader::new(BufReader::new(file)))?
+            .with_wdp(path.as_ref().with_extension("wdp"));
+        let lax_path = path.as_ref().with_extension("lax");
+        if reader.lax.is_none() && lax_path.is_file() {
+            Ok(reader.with_lax(lax_path))
+        } else {
+            Ok(reader)
+        }
+    }
+}
+
+impl<R: std::io::Read + Seek + Send> Reader<R> {
+    /// Returns a reference to this reader's header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let header = reader.header();
+    /// ```
+    pub fn header(&self) -> &Header {
+        self.point_reader.header()
+    }
+
+    /// Reads a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>> {
+        self.point_reader.read_point()
+    }
+
+    /// Reads the next point into an existing point, returning false if there are none left.
+    ///
+    /// Every field of the point is overwritten, so fields that the point format doesn't have,
+    /// like color in format 1, become `None`. If the point's extra bytes are on the heap, their
+    /// allocation is reused, so reading points with more than sixteen extra bytes doesn't allocate
+    /// for each point. When there are no points left, the point is untouched. The point is
+    /// decoded the same way as by [Reader::read_point].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut point = Point::default();
+    /// let mut count = 0;
+    /// while reader.read_point_into(&mut point).unwrap() {
+    ///     count += 1;
+    /// }
+    /// assert_eq!(106, count);
+    /// ```
+    pub fn read_point_into(&mut self, point: &mut Point) -> Result<bool> {
+        self.point_reader.read_point_into(point)
+    }
+
+    /// Reads the next point into an existing raw point, returning false if there are none left.
+    ///
+    /// Like [Reader::read_point_into], every field is overwritten and the extra bytes' heap
+    /// allocation is reused, and the raw point is untouched when there are no points left. The
+    /// coordinates are the record's integers, before any transforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raw, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut raw_point = raw::Point::default();
+    /// let mut count = 0;
+    /// while reader.read_raw_point_into(&mut raw_point).unwrap() {
+    ///     count += 1;
+    /// }
+    /// assert_eq!(106, count);
+    /// ```
+    pub fn read_raw_point_into(&mut self, raw_point: &mut raw::Point) -> Result<bool> {
+        let mut record = std::mem::take(&mut self.point_reader.record);
+        let result = match self.point_reader.read_record(&mut record) {
+            Ok(true) => raw_point
+                .read_into(record.as_slice(), self.header().point_format())
+                .map(|()| true),
+            read => read,
+        };
+        self.point_reader.record = record;
+        result
+    }
+
+    /// Reads the next point record's bytes into `record`, returning false if there are none left.
+    ///
+    /// Nothing is parsed or allocated. Returns an error if `record` isn't as long as the point
+    /// format's records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut record = vec![0; usize::from(reader.header().point_format().len())];
+    /// assert!(reader.read_record_into(&mut record).unwrap());
+    /// assert!(reader.read_record_into(&mut [0; 3]).is_err());
+    /// ```
+    pub fn read_record_into(&mut self, record: &mut [u8]) -> Result<bool> {
+        let expected = usize::from(self.header().point_format().len());
+        if record.len() != expected {
+            return Err(Error::InvalidRecordLength {
+                expected,
+                len: record.len(),
+            });
+        }
+        self.point_reader.read_record(record)
+    }
+
+    /// Reads `n` points into a vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.read_points(10).unwrap();
+    /// assert_eq!(points.len(), 10);
+    /// ```
+    pub fn read_points(&mut self, n: u64) -> Result<Vec<Point>> {
+        let mut points = Vec::with_capacity(n.try_into()?);
+        let _ = self.point_reader.read_points(n, &mut points)?;
+        Ok(points)
+    }
+
+    /// Reads `n` points into a provided vector, returning the number of points read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut points = Vec::new();
+    /// let count = reader.read_points_into(10, &mut points).unwrap();
+    /// ```
+    pub fn read_points_into(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        self.point_reader.read_points(n, points)
+    }
+
+    /// Reads up to `n` points into a columnar buffer, returning the number of points read.
+    ///
+    /// The buffer is cleared first, and its columns keep their allocations, so reading batch after
+    /// batch into the same buffer doesn't allocate for each point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::PointBuffer, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut buffer = PointBuffer::new();
+    /// assert_eq!(100, reader.read_batch(&mut buffer, 100).unwrap());
+    /// assert_eq!(100, buffer.xs.len());
+    /// assert_eq!(6, reader.read_batch(&mut buffer, 100).unwrap());
+    /// ```
+    pub fn read_batch(&mut self, buffer: &mut PointBuffer, n: u64) -> Result<u64> {
+        buffer.clear();
+        let mut point = Point::default();
+        let mut count = 0;
+        while count < n && self.point_reader.read_point_into(&mut point)? {
+            buffer.push(&point);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Reads a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let point = reader.read().unwrap().unwrap();
+    /// ```
+    #[deprecated(
+        since = "0.9.0",
+        note = "Use read_point() instead, which returns a Result<Option<Point>>"
+    )]
+    pub fn read(&mut self) -> Option<Result<Point>> {
+        self.point_reader.read_point().transpose()
+    }
+
+    /// Reads n points into a vector.
+    #[deprecated(since = "0.9.0", note = "Use read_points() instead")]
+    pub fn read_n(&mut self, n: u64) -> Result<Vec<Point>> {
+        self.read_points(n)
+    }
+
+    /// Reads n points into the vec
+    #[deprecated(since = "0.9.0", note = "Use read_points_into() instead")]
+    pub fn read_n_into(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        self.read_points_into(n, points)
+    }
+
+    /// Reads all points into a vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut points = Vec::new();
+    /// let count = reader.read_all_points(&mut points).unwrap();
+    /// assert_eq!(points.len(), count.try_into().unwrap());
+    /// ```
+    pub fn read_all_points_into(&mut self, points: &mut Vec<Point>) -> Result<u64> {
+        let point_count = self.point_reader.header().number_of_points();
+        self.point_reader.read_points(point_count, points)
+    }
+
+    /// Reads all points into a vector.
+    #[deprecated(since = "0.9.0", note = "Use read_all_points_into() instead")]
+    pub fn read_all_points(&mut self, points: &mut Vec<Point>) -> Result<u64> {
+        self.read_all_points_into(points)
+    }
+
+    /// Seeks to the given point number, zero-indexed.
+    ///
+    /// Note that seeking on compressed (LAZ) data can be expensive as the reader
+    /// will have to seek to the closest chunk start and decompress all points up until
+    /// the point seeked to.
+    ///
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// reader.seek(1).unwrap(); // <- seeks to the second point
+    /// let the_second_point = reader.read().unwrap().unwrap();
+    /// ```
+    pub fn seek(&mut self, position: u64) -> Result<()> {
+        self.point_reader.seek(position)
+    }
+
+    /// Reads the point at an index, zero-based, and leaves the reader just after it.
+    ///
+    /// This [seeks](Reader::seek) first, so an uncompressed point is read straight from its
+    /// offset, and a compressed one is found through the chunk table and decompressed from the
+    /// start of its chunk. Returns `None` if the index is past the last point. The point is
+    /// returned even if it's withheld and withheld points are being skipped, since it's still the
+    /// point at that index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let point = reader.read_nth(42).unwrap().unwrap();
+    /// assert_eq!(43, reader.indexed_points().next().unwrap().unwrap().0);
+    /// assert!(reader.read_nth(106).unwrap().is_none());
+    /// ```
+    pub fn read_nth(&mut self, index: u64) -> Result<Option<Point>> {
+        if index >= self.header().number_of_points() {
+            return Ok(None);
+        }
+        self.point_reader.seek(index)?;
+        self.point_reader.decoder.read_point()
+    }
+
+    /// Returns an iterator over batches of `n` of this reader's points.
+    ///
+    /// Every batch is full except maybe the last one, and `chunks(0)` is the same as `chunks(1)`.
+    /// Iteration starts at the reader's current position, and stops after an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let lens: Vec<usize> = reader.chunks(50).map(|points| points.unwrap().len()).collect();
+    /// assert_eq!(vec![50, 50, 6], lens);
+    /// ```
+    pub fn chunks(&mut self, n: u64) -> PointChunks<'_, R> {
+        PointChunks {
+            point_reader: &mut self.point_reader,
+            n: n.max(1),
+            done: false,
+        }
+    }
+
+    /// Returns an iterator over this reader's points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points().collect::<Result<Vec<_>, _>>().unwrap();
+    /// ```
+    pub fn points(&mut self) -> PointIterator<'_, R> {
+        PointIterator {
+            point_reader: &mut self.point_reader,
+        }
+    }
+
+    /// Returns an iterator over this reader's points and their zero-based indices.
+    ///
+    /// Iteration starts at the reader's current position, so the indices pick up where any
+    /// previous reads or seeks left off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// reader.seek(42).unwrap();
+    /// let (index, point) = reader.indexed_points().next().unwrap().unwrap();
+    /// assert_eq!(42, index);
+    /// ```
+    pub fn indexed_points(&mut self) -> IndexedPointIterator<'_, R> {
+        IndexedPointIterator {
+            point_reader: &mut self.point_reader,
+        }
+    }
+
+    /// Returns an iterator over the raw bytes of this reader's point records.
+    ///
+    /// Nothing is parsed, so this works for any point format, including
+    /// [unknown](ReadOptions::read_unknown_formats) ones. Each record is as long as the point
+    /// format, and compressed records are decompressed. Iteration starts at the reader's current
+    /// position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let record = reader.raw_records().next().unwrap().unwrap();
+    /// assert_eq!(usize::from(reader.header().point_format().len()), record.len());
+    /// ```
+    pub fn raw_records(&mut self) -> RawRecords<'_, R> {
+        RawRecords {
+            len: usize::from(self.header().point_format().len()),
+            point_reader: &mut self.point_reader,
+        }
+    }
+
+    /// Digests this reader's raw point records, decompressed if they're compressed.
+    ///
+    /// Only the records are digested, so a patched header doesn't change the digest, and a las
+    /// file and its laz twin have the same one. Points the reader skips, e.g. because of
+    /// [ReadOptions::skip_withheld], are left out. The reader's cursor is left where it was.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Digest, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let digest = reader.point_data_digest(Digest::Sha256).unwrap();
+    /// assert_eq!(digest, reader.point_data_digest(Digest::Sha256).unwrap());
+    /// ```
+    #[cfg(feature = "digest")]
+    pub fn point_data_digest(&mut self, digest: Digest) -> Result<[u8; 32]> {
+        let index = self.point_reader.index();
+        self.seek(0)?;
+        let mut hasher = digest.hasher();
+        let mut record = vec![0; usize::from(self.header().point_format().len())];
+        while self.point_reader.read_record(&mut record)? {
+            hasher.update(&record);
+        }
+        self.seek(index)?;
+        Ok(hasher.finish())
+    }
+
+    /// Reads points until the budget runs out, and returns them with where reading stopped.
+    ///
+    /// Pass the token to [Reader::resume] on a new reader over the same file to carry on from the
+    /// next point. Reading with the default budget reads every remaining point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::Budget, Reader};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let (points, token) = reader.read_budgeted(Budget::default().points(100)).unwrap();
+    /// assert_eq!(100, points.len());
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// reader.resume(token).unwrap();
+    /// let (points, token) = reader.read_budgeted(Budget::default().points(100)).unwrap();
+    /// assert_eq!(6, points.len());
+    /// assert!(token.is_finished());
+    /// ```
+    pub fn read_budgeted(&mut self, budget: Budget) -> Result<(Vec<Point>, ResumeToken)> {
+        let n = budget.limit(u64::from(self.header().point_format().len()));
+        let mut points = Vec::new();
+        let _ = self.read_points_into(n, &mut points)?;
+        let index = self.index();
+        let chunk = self.chunk_position(index);
+        Ok((points, ResumeToken::new(self.header(), index, chunk)?))
+    }
+
+    /// Moves to where a [budgeted read](Reader::read_budgeted) stopped.
+    ///
+    /// Compressed points pick up from the token's chunk, which is found through the chunk table.
+    /// With the `digest` feature, returns [Error::ResumeTokenMismatch] if the token came from a
+    /// different file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::Budget, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let (_, token) = reader.read_budgeted(Budget::default().points(10)).unwrap();
+    /// let mut other = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// other.resume(token).unwrap();
+    /// assert_eq!(reader.read_point().unwrap(), other.read_point().unwrap());
+    /// ```
+    pub fn resume(&mut self, token: ResumeToken) -> Result<()> {
+        if !token.matches(self.header())? {
+            return Err(Error::ResumeTokenMismatch);
+        }
+        #[cfg(feature = "laz")]
+        if let Some((chunk, offset)) = token.chunk
+            && let Some(index) = self.point_reader.seek_in_chunk(chunk, offset)?
+        {
+            return if index == token.index() {
+                Ok(())
+            } else {
+                Err(Error::ResumeTokenMismatch)
+            };
+        }
+        self.seek(token.index())
+    }
+
+    /// Returns the laz chunk holding a point, and how far into the chunk it is.
+    #[cfg(feature = "laz")]
+    fn chunk_position(&mut self, index: u64) -> Option<(usize, u64)> {
+        if !self.header().point_format().is_compressed {
+            return None;
+        }
+        let (_, chunk_table) = self.chunk_table().ok()?;
+        let mut start = 0;
+        for (chunk, entry) in chunk_table.as_ref().iter().enumerate() {
+            if index < start + entry.point_count {
+                return Some((chunk, index - start));
+            }
+            start += entry.point_count;
+        }
+        None
+    }
+
+    #[cfg(not(feature = "laz"))]
+    fn chunk_position(&mut self, _: u64) -> Option<(usize, u64)> {
+        None
+    }
+
+    /// Turns this reader into an iterator over its points, which owns the reader.
+    ///
+    /// This reads the same points, and returns the same errors, as [Reader::points], but the
+    /// iterator can be sent to another thread if the source can. Use [OwnedPoints::into_reader]
+    /// to get the reader back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let points = Reader::from_path("tests/data/autzen.las").unwrap().into_point_iter();
+    /// let handle = std::thread::spawn(move || points.count());
+    /// assert_eq!(106, handle.join().unwrap());
+    /// ```
+    pub fn into_point_iter(self) -> OwnedPoints<R> {
+        OwnedPoints { reader: self }
+    }
+
+    /// Returns an iterator over this reader's points, each with a value mapped from its user data.
+    ///
+    /// Pair it with the file's [UserDataDictionary](crate::vlr::UserDataDictionary), if it has
+    /// one, to label the points. Iteration starts at the reader's current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let dictionary = reader.header().user_data_dictionary().unwrap().unwrap_or_default();
+    /// for result in reader.map_user_data(|value| dictionary.label(value).map(str::to_string)) {
+    ///     let (_, label) = result.unwrap();
+    ///     assert_eq!(None, label);
+    /// }
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let (point, is_odd) = reader.map_user_data(|value| value % 2 == 1).next().unwrap().unwrap();
+    /// assert_eq!(point.user_data % 2 == 1, is_odd);
+    /// ```
+    pub fn map_user_data<T, F: Fn(u8) -> T>(&mut self, f: F) -> UserDataIterator<'_, F, R> {
+        UserDataIterator {
+            point_reader: &mut self.point_reader,
+            f,
+        }
+    }
+
+    /// Returns an iterator over this reader's points that have one of the given classifications.
+    ///
+    /// Each point's classification is checked before the rest of the point is parsed, so points
+    /// that are filtered out are cheap. Withheld and synthetic flags don't affect which points
+    /// match, but withheld points are left out if [ReadOptions::skip_withheld] is set. Iteration
+    /// starts at the reader's current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Classification, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// for point in reader.points_with_classification(Classification::Ground) {
+    ///     assert_eq!(Classification::Ground, point.unwrap().classification);
+    /// }
+    /// ```
+    pub fn points_with_classification(
+        &mut self,
+        classes: impl Into<ClassificationSet>,
+    ) -> ClassifiedPointIterator<'_, R> {
+        let format = *self.header().point_format();
+        let transforms = *self.header().effective_transforms();
+        ClassifiedPointIterator {
+            point_reader: &mut self.point_reader,
+            classes: classes.into(),
+            format,
+            transforms,
+            record: vec![0; usize::from(format.len())],
+        }
+    }
+
+    /// Returns an iterator over this reader's points whose records pass `predicate`.
+    ///
+    /// The predicate sees a [RawPointView] of each record, whose fields are read straight from
+    /// the record's bytes, and only the points that pass are fully parsed. Iteration starts at the
+    /// reader's current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// for point in reader.points_where(|view| view.classification() == 2 && view.return_number() == 1) {
+    ///     assert_eq!(1, point.unwrap().return_number);
+    /// }
+    /// ```
+    pub fn points_where<F: FnMut(RawPointView<'_>) -> bool>(
+        &mut self,
+        predicate: F,
+    ) -> FilteredPointIterator<'_, F, R> {
+        let format = *self.header().point_format();
+        let transforms = *self.header().effective_transforms();
+        FilteredPointIterator {
+            point_reader: &mut self.point_reader,
+            predicate,
+            format,
+            transforms,
+            record: vec![0; usize::from(format.len())],
+        }
+    }
+
+    /// Returns an iterator over this reader's points, decoded straight from their records into
+    /// your own point type.
+    ///
+    /// No [Point] is made along the way, see [LasPointRecord]. Iteration
+    /// starts at the reader's current position, and withheld points are left out if the options
+    /// say to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raw, reader::RawPointView, Header, LasPointRecord, Reader, Result};
+    ///
+    /// struct Intensity(u16);
+    ///
+    /// impl LasPointRecord for Intensity {
+    ///     fn decode(view: RawPointView<'_>, _: &Header) -> Result<Intensity> {
+    ///         Ok(Intensity(view.intensity()))
+    ///     }
+    ///
+    ///     fn encode(&self, _: &Header) -> Result<raw::Point> {
+    ///         Ok(raw::Point { intensity: self.0, ..Default::default() })
+    ///     }
+    /// }
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let intensities = reader.points_as::<Intensity>().collect::<Result<Vec<_>>>().unwrap();
+    /// assert_eq!(106, intensities.len());
+    /// ```
+    pub fn points_as<T: LasPointRecord>(&mut self) -> RecordIterator<'_, T, R> {
+        let len = usize::from(self.header().point_format().len());
+        RecordIterator {
+            point_reader: &mut self.point_reader,
+            record: vec![0; len],
+            record_type: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns an iterator over this reader's points that have the given point source id.
+    ///
+    /// Each point's source id is checked before the rest of the point is parsed. With
+    /// [ReadOptions::assume_sorted_by_source_id], uncompressed files are binary searched for the
+    /// matching points instead of being scanned. Iteration starts at the reader's current
+    /// position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// for point in reader.points_with_source_id(7328) {
+    ///     assert_eq!(7328, point.unwrap().point_source_id);
+    /// }
+    /// ```
+    pub fn points_with_source_id(&mut self, source_id: u16) -> SourceIdPointIterator<'_, R> {
+        let format = *self.header().point_format();
+        let transforms = *self.header().effective_transforms();
+        SourceIdPointIterator {
+            point_reader: &mut self.point_reader,
+            source_id,
+            assume_sorted: self.options.assume_sorted_by_source_id,
+            end: None,
+            format,
+            transforms,
+            record: vec![0; usize::from(format.len())],
+        }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.point_reader.read_point().transpose()
+    /// Returns an iterator over a decimated subset of this reader's points.
+    ///
+    /// Points are picked by their index in the file, starting from the reader's current position.
+    /// The points in between are skipped without being parsed: uncompressed files seek past them,
+    /// and compressed files seek past whole chunks when they can.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::Decimation, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader
+    ///     .points_decimated(Decimation::MaxPoints(10))
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(10, points.len());
+    /// ```
+    pub fn points_decimated(&mut self, decimation: Decimation) -> DecimatedPointIterator<'_, R> {
+        DecimatedPointIterator {
+            point_reader: &mut self.point_reader,
+            decimation,
+        }
     }
-}
 
-/// A trait for objects which read LAS data.
-#[deprecated(
-    since = "0.9.0",
-    note = "This interface has been refactored so that importing `Read` is no longer required"
-)]
-pub trait Read {
-    /// Returns a reference to this reader's header.
+    /// Returns an iterator over this reader's points, grouped into flight lines.
+    ///
+    /// Flight lines are runs of consecutive points, split where the options say, and start at the
+    /// reader's current position. If the options split on time gaps, the gps times are checked
+    /// first, which reads the rest of the points once. If they aren't sorted, a warning is logged
+    /// and the points are split by source id alone. Use [split_flight_lines](crate::split_flight_lines)
+    /// to write each flight line to its own output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::SegmentationOptions, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let options = SegmentationOptions::default().time_gap(1.);
+    /// for flight_line in reader.flight_lines(options).unwrap() {
+    ///     let flight_line = flight_line.unwrap();
+    ///     assert!(flight_line.points.iter().all(|p| p.point_source_id == flight_line.source_id));
+    /// }
+    /// ```
+    pub fn flight_lines(
+        &mut self,
+        mut options: SegmentationOptions,
+    ) -> Result<FlightLineIterator<'_, R>> {
+        if options.time_gap_seconds().is_some() {
+            let start = self.point_reader.index();
+            let skipped_withheld = self.point_reader.skipped_withheld;
+            let mut previous = None;
+            let mut sorted = true;
+            while let Some(point) = self.point_reader.read_point()? {
+                if let (Some(previous), Some(time)) = (previous, point.gps_time)
+                    && time < previous
+                {
+                    sorted = false;
+                    break;
+                }
+                previous = point.gps_time.or(previous);
+            }
+            self.point_reader.seek(start)?;
+            self.point_reader.skipped_withheld = skipped_withheld;
+            if !sorted {
+                log::warn!("the gps times aren't sorted, so flight lines are split by source id");
+                options = SegmentationOptions::default();
+            }
+        }
+        Ok(FlightLineIterator::new(&mut self.point_reader, options))
+    }
+
+    /// Counts points on a grid of square cells over the header's xy bounds.
+    ///
+    /// Every point is read, starting from the first, and the reader is left after the last point.
+    /// Points on the east or south edge of the bounds are counted in the last column or row.
+    /// Returns an error if the cell size isn't a positive number, or if it's so small that the grid
+    /// would have more than 2^28 cells.
     ///
     /// # Examples
     ///
     /// ```
     /// use las::Reader;
-    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let header = reader.header();
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let grid = reader.density_grid(10.).unwrap();
+    /// assert_eq!(106, grid.counts().iter().sum::<u64>());
     /// ```
-    fn header(&self) -> &Header;
+    pub fn density_grid(&mut self, cell_size: f64) -> Result<DensityGrid> {
+        self.density_grid_with_options(cell_size, DensityGridOptions::default())
+    }
 
-    /// Reads a point.
+    /// Counts points on a grid of square cells, with options.
+    ///
+    /// See [Reader::density_grid].
     ///
     /// # Examples
     ///
     /// ```
-    /// # use las::Reader;
+    /// use las::{reader::DensityGridOptions, Reader};
     /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let point = reader.read().unwrap().unwrap();
+    /// let options = DensityGridOptions::default().last_returns_only();
+    /// let grid = reader.density_grid_with_options(10., options).unwrap();
     /// ```
-    fn read(&mut self) -> Option<Result<Point>>;
+    pub fn density_grid_with_options(
+        &mut self,
+        cell_size: f64,
+        options: DensityGridOptions,
+    ) -> Result<DensityGrid> {
+        const CHUNK_SIZE: u64 = 1 << 16;
 
-    /// Reads n points.
-    fn read_n(&mut self, n: u64) -> Result<Vec<Point>>;
+        if !(cell_size.is_finite() && cell_size > 0.) {
+            return Err(Error::InvalidCellSize(cell_size));
+        }
+        let mut grid = DensityGrid::new(&self.header().bounds(), cell_size)?;
+        self.seek(0)?;
+        let mut points = Vec::new();
+        while self.read_points_into(CHUNK_SIZE, &mut points)? > 0 {
+            for point in points.drain(..).filter(|point| options.counts(point)) {
+                grid.add(&point);
+            }
+        }
+        Ok(grid)
+    }
 
-    /// Reads n points into the vec
-    fn read_n_into(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64>;
+    /// Returns the absolute byte offset of a point in the source.
+    ///
+    /// Returns `None` if the index is past the last point, or if the points are compressed, since
+    /// compressed points don't live at a fixed offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let offset = reader.point_offset(0).unwrap();
+    /// let len = u64::from(reader.header().point_format().len());
+    /// assert_eq!(reader.point_offset(1).unwrap(), offset + len);
+    /// assert_eq!(None, reader.point_offset(reader.header().number_of_points()));
+    /// ```
+    pub fn point_offset(&self, index: u64) -> Option<u64> {
+        self.point_reader.point_offset(index)
+    }
 
-    /// Reads all points left into the vec
-    fn read_all_points(&mut self, points: &mut Vec<Point>) -> Result<u64>;
+    /// Uses the spatial index in a `.lax` file for [Reader::points_in_bounds].
+    ///
+    /// If the file can't be read, the reader keeps whatever spatial index it had before, so check
+    /// [Reader::has_spatial_index] if you need to know.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las")
+    ///     .unwrap()
+    ///     .with_lax("tests/data/does-not-exist.lax");
+    /// assert!(!reader.has_spatial_index());
+    /// ```
+    pub fn with_lax<P: AsRef<Path>>(mut self, path: P) -> Reader<R> {
+        match Lax::from_path(path.as_ref()) {
+            Ok(lax) => self.lax = Some(lax),
+            Err(err) => log::debug!(
+                "could not read spatial index {}: {}",
+                path.as_ref().display(),
+                err
+            ),
+        }
+        self
+    }
 
-    /// Seeks to the given point number, zero-indexed.
+    /// Uses a spatial index for [Reader::points_in_bounds], e.g. one that was just built with
+    /// [Indexer::index](crate::index::Indexer::index).
     ///
-    /// Note that seeking on compressed (LAZ) data can be expensive as the reader
-    /// will have to seek to the closest chunk start and decompress all points up until
-    /// the point seeked to.
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{index::Indexer, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let lax = Indexer::index(&mut reader).unwrap();
+    /// assert!(reader.with_spatial_index(lax).has_spatial_index());
+    /// ```
+    pub fn with_spatial_index(mut self, lax: Lax) -> Reader<R> {
+        self.lax = Some(lax);
+        self
+    }
+
+    /// Reads external waveform data packets from this `.wdp` file.
     ///
+    /// [Reader::from_path] already uses the `.wdp` file next to the las file. The file isn't
+    /// opened until a waveform packet is read.
     ///
     /// # Examples
     ///
     /// ```
+    /// use std::{fs::File, io::BufReader};
     /// use las::Reader;
-    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// reader.seek(1).unwrap(); // <- seeks to the second point
-    /// let the_second_point = reader.read().unwrap().unwrap();
+    /// let file = BufReader::new(File::open("tests/data/waveform.las").unwrap());
+    /// let mut reader = Reader::new(file).unwrap().with_wdp("tests/data/waveform.wdp");
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert_eq!(8, reader.waveform_samples(&point).unwrap().len());
     /// ```
-    fn seek(&mut self, position: u64) -> Result<()>;
+    pub fn with_wdp<P: Into<PathBuf>>(mut self, path: P) -> Reader<R> {
+        self.wdp = Some(Wdp::new(path.into()));
+        self
+    }
 
-    /// Returns an iterator over this reader's points.
+    /// Calls `callback` each time another `every` points have been read, and after the last point.
+    ///
+    /// Every way of reading points moves the count along, and points that are read in batches are
+    /// reported once per batch, so the callback can be called less often than asked. Checking
+    /// the count is all that's done for the other points, so a large `every` costs next to nothing.
+    /// Zero is the same as one. This replaces any earlier callback.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use las::Reader;
+    /// use las::Reader;
+    ///
     /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let points = reader.points().collect::<Result<Vec<_>, _>>().unwrap();
+    /// reader.on_progress(25, |event| {
+    ///     eprintln!("read {} of {:?} points", event.points, event.total_points)
+    /// });
+    /// let points = reader.read_points(106).unwrap();
     /// ```
-    fn points(&mut self) -> PointIterator<'_>;
-}
-
-/// Reads LAS data.
-#[allow(missing_debug_implementations)]
-pub struct Reader {
-    point_reader: Box<dyn ReadPoints>,
-}
+    pub fn on_progress(
+        &mut self,
+        every: u64,
+        callback: impl FnMut(ProgressEvent) + Send + 'static,
+    ) {
+        self.point_reader.progress = Some(Progress::new(every, callback));
+    }
 
-impl Reader {
-    /// Creates a new reader.
+    /// Reads the raw bytes of a point's waveform packet.
     ///
-    /// This does *not* wrap the `Read` in a `BufRead`, so if you're concerned
-    /// about performance you should do that wrapping yourself (or use
-    /// `from_path`).
+    /// Depending on the header's global encoding, the packet is read from the waveform data packet
+    /// evlr or from the `.wdp` file. Use the point's waveform packet descriptor to interpret the
+    /// bytes.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::io::BufReader;
-    /// use std::fs::File;
-    /// # use las::Reader;
-    /// let file = File::open("tests/data/autzen.las").unwrap();
-    /// let reader = Reader::new(BufReader::new(file)).unwrap();
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/waveform.las").unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// let samples = reader.waveform_samples(&point).unwrap();
+    /// assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8], samples);
     /// ```
-    pub fn new<R: std::io::Read + Seek + Send + 'static>(mut read: R) -> Result<Reader> {
-        let header = Header::new(&mut read)?;
-        if header.point_format().is_compressed {
-            #[cfg(feature = "laz")]
-            {
-                Ok(Reader {
-                    point_reader: Box::new(laz::PointReader::new(read, header)?),
-                })
-            }
-            #[cfg(not(feature = "laz"))]
-            {
-                Err(Error::LaszipNotEnabled)
+    pub fn waveform_samples(&mut self, point: &Point) -> Result<Vec<u8>> {
+        let waveform = waveform::waveform(point)?;
+        let header = self.point_reader.header();
+        if header.has_internal_waveform_data_packets() {
+            match waveform::internal(header)? {
+                Internal::Evlr(data) => waveform::read_evlr_packet(data, waveform),
+                Internal::Offset(start) => {
+                    waveform::read_packet_at(start, waveform, |offset, buf| {
+                        self.point_reader.read_at(offset, buf)
+                    })
+                }
             }
+        } else if header.has_external_waveform_data_packets() {
+            self.wdp
+                .as_mut()
+                .ok_or(Error::WaveformFileRequired)?
+                .read_packet(waveform)
         } else {
-            Ok(Reader {
-                point_reader: Box::new(las::PointReader::new(read, header)?),
-            })
+            Err(Error::NoWaveformDataPackets)
         }
     }
 
-    /// Creates a new reader from a path.
+    /// Reads a point's waveform packet and decodes it with the packet's descriptor.
     ///
-    /// The underlying `File` is wrapped in a `BufReader` for performance reasons.
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{waveform::Samples, Reader};
+    /// let mut reader = Reader::from_path("tests/data/waveform-internal.las").unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert_eq!(Samples::U8(vec![1, 2, 3, 4]), reader.waveform(&point).unwrap());
+    /// ```
+    pub fn waveform(&mut self, point: &Point) -> Result<Samples> {
+        let index = waveform::waveform(point)?.wave_packet_descriptor_index;
+        let descriptor = self
+            .header()
+            .waveform_packet_descriptor(index)
+            .ok_or(Error::WaveformPacketDescriptorNotFound(index))??;
+        let packet = self.waveform_samples(point)?;
+        descriptor.decode(&packet)
+    }
+
+    /// Returns true if this reader has a spatial index, either appended to the file or from a
+    /// `.lax` file.
     ///
     /// # Examples
     ///
     /// ```
     /// # use las::Reader;
     /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert!(!reader.has_spatial_index());
     /// ```
-    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader> {
-        File::open(path)
-            .map_err(Error::from)
-            .and_then(|file| Reader::new(BufReader::new(file)))
+    pub fn has_spatial_index(&self) -> bool {
+        self.lax.is_some()
     }
 
-    /// Returns a reference to this reader's header.
+    /// Reads all points inside of the bounds, in the order they appear in the file.
+    ///
+    /// If the reader has a spatial index, only the parts of the file that might have points in the
+    /// bounds are read. A COPC file without one uses its hierarchy instead, and only reads the
+    /// nodes whose cubes intersect the bounds. Otherwise, every point is read. The coordinates
+    /// are checked on the raw record, so a point is only decoded if it's inside. Either way, the
+    /// reader is left at an unspecified position, so [seek](Reader::seek) before reading more
+    /// points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Reader, Vector};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut bounds = reader.header().bounds();
+    /// bounds.max.x = (bounds.min.x + bounds.max.x) / 2.;
+    /// let points = reader.points_in_bounds(bounds).unwrap();
+    /// assert!(points.iter().all(|point| point.x <= bounds.max.x));
+    /// ```
+    pub fn points_in_bounds(&mut self, bounds: Bounds) -> Result<Vec<Point>> {
+        let number_of_points = self.header().number_of_points();
+        let intervals = self.candidate_intervals(&bounds);
+        let format = *self.header().point_format();
+        let transforms = *self.header().effective_transforms();
+        let mut record = vec![0; usize::from(format.len())];
+        let mut points = Vec::new();
+        for interval in intervals {
+            let end = interval.end.min(number_of_points);
+            if interval.start >= end {
+                continue;
+            }
+            self.seek(interval.start)?;
+            // Skipping withheld points can read past the end, into the next interval.
+            while self.point_reader.read_record(&mut record)? && self.point_reader.index() <= end {
+                if record_in_bounds(&record, &transforms, &bounds) {
+                    points.push(point_from_record(&record, &format, &transforms)?);
+                }
+                if self.point_reader.index() == end {
+                    break;
+                }
+            }
+        }
+        Ok(points)
+    }
+
+    /// Returns the ranges of point indices that might have points inside of the bounds.
+    fn candidate_intervals(&self, bounds: &Bounds) -> Vec<Range<u64>> {
+        if let Some(lax) = &self.lax {
+            return lax.intervals(bounds);
+        }
+        #[cfg(feature = "laz")]
+        if let Some(intervals) = crate::copc::point_intervals(self.header(), bounds) {
+            return intervals;
+        }
+        std::iter::once(0..self.header().number_of_points()).collect()
+    }
+
+    /// Reads the remaining points into a new vector, consuming the reader.
+    ///
+    /// The vector is allocated up front for the number of points in the header, but never for
+    /// more than [MAX_PREALLOCATED_POINTS], in case the header is wrong.
+    /// If reading fails partway through, the error is returned and the points are dropped.
     ///
     /// # Examples
     ///
     /// ```
     /// use las::Reader;
     /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let header = reader.header();
+    /// let points = reader.into_points().unwrap();
+    /// assert_eq!(106, points.len());
     /// ```
-    pub fn header(&self) -> &Header {
-        self.point_reader.header()
+    pub fn into_points(self) -> Result<Vec<Point>> {
+        self.into_points_with_limit(MAX_PREALLOCATED_POINTS)
     }
 
-    /// Reads a point.
+    /// Reads the remaining points into a new vector, allocating for at most `limit` points up
+    /// front.
+    ///
+    /// The vector still grows past `limit` if there are more points.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use las::Reader;
-    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let point = reader.read_point().unwrap().unwrap();
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.into_points_with_limit(10).unwrap();
+    /// assert_eq!(106, points.len());
     /// ```
-    pub fn read_point(&mut self) -> Result<Option<Point>> {
-        self.point_reader.read_point()
+    pub fn into_points_with_limit(self, limit: usize) -> Result<Vec<Point>> {
+        match self.read_remaining(limit) {
+            (points, None) => Ok(points),
+            (_, Some(err)) => Err(err),
+        }
     }
 
-    /// Reads `n` points into a vector.
+    /// Reads the remaining points into a new vector, keeping the points read before any error.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use las::Reader;
-    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let points = reader.read_points(10).unwrap();
-    /// assert_eq!(points.len(), 10);
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let (points, err) = reader.into_points_lossy();
+    /// assert_eq!(106, points.len());
+    /// assert!(err.is_none());
     /// ```
-    pub fn read_points(&mut self, n: u64) -> Result<Vec<Point>> {
-        let mut points = Vec::with_capacity(n.try_into()?);
-        let _ = self.point_reader.read_points(n, &mut points)?;
-        Ok(points)
+    pub fn into_points_lossy(self) -> (Vec<Point>, Option<Error>) {
+        self.read_remaining(MAX_PREALLOCATED_POINTS)
     }
 
-    /// Reads `n` points into a provided vector, returning the number of points read.
+    /// Returns the options this reader was created with.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use las::Reader;
-    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let mut points = Vec::new();
-    /// let count = reader.read_points_into(10, &mut points).unwrap();
+    /// use las::{reader::ReadOptions, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(&ReadOptions::default(), reader.options());
     /// ```
-    pub fn read_points_into(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
-        self.point_reader.read_points(n, points)
+    pub fn options(&self) -> &ReadOptions {
+        &self.options
     }
 
-    /// Reads a point.
+    /// Returns the problems in the header that were repaired or ignored when this reader was
+    /// opened, see [ReadOptions::lenient].
     ///
     /// # Examples
     ///
     /// ```
-    /// # use las::Reader;
-    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let point = reader.read().unwrap().unwrap();
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert!(reader.warnings().is_empty());
     /// ```
-    #[deprecated(
-        since = "0.9.0",
-        note = "Use read_point() instead, which returns a Result<Option<Point>>"
-    )]
-    pub fn read(&mut self) -> Option<Result<Point>> {
-        self.point_reader.read_point().transpose()
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
     }
 
-    /// Reads n points into a vector.
-    #[deprecated(since = "0.9.0", note = "Use read_points() instead")]
-    pub fn read_n(&mut self, n: u64) -> Result<Vec<Point>> {
-        self.read_points(n)
+    /// Returns what this reader's lenient options have done so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let _ = reader.read_points(10).unwrap();
+    /// assert!(reader.summary().skipped_chunks.is_empty());
+    /// ```
+    pub fn summary(&self) -> ReadSummary<'_> {
+        let record_length_deficit = match self.header().record_length_consistency() {
+            RecordLengthStatus::Deficit(n) => n,
+            _ => 0,
+        };
+        ReadSummary {
+            skipped_chunks: self.point_reader.skipped_chunks(),
+            record_length_deficit,
+            skipped_withheld: self.point_reader.skipped_withheld(),
+            missing_chunk_table: self.point_reader.missing_chunk_table(),
+            nonconforming_vlr_reserved: if self.header().version() >= Version::new(1, 2) {
+                self.header()
+                    .all_vlrs()
+                    .filter(|vlr| vlr.reserved.is_some())
+                    .count()
+            } else {
+                0
+            },
+            return_count_conflict: self.header().return_count_conflict(),
+            warnings: &self.warnings,
+            lost_points: self.point_reader.lost_points,
+        }
     }
 
-    /// Reads n points into the vec
-    #[deprecated(since = "0.9.0", note = "Use read_points_into() instead")]
-    pub fn read_n_into(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
-        self.read_points_into(n, points)
+    /// Returns the laz chunks that were skipped because they failed to decompress.
+    ///
+    /// This is always empty unless [ReadOptions::skip_corrupt_chunks] is set. It's the same as
+    /// [ReadSummary::skipped_chunks].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.read_points(10).unwrap();
+    /// assert!(reader.skipped_chunks().is_empty());
+    /// ```
+    pub fn skipped_chunks(&self) -> &[SkippedChunk] {
+        self.point_reader.skipped_chunks()
     }
 
-    /// Reads all points into a vector.
+    /// Returns the number of chunks that [Reader::read_chunk_at] can read.
+    ///
+    /// For a laz file, this is the length of its chunk table. An uncompressed file is split into
+    /// chunks of [UNCOMPRESSED_CHUNK_SIZE] points.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use las::Reader;
+    /// use las::Reader;
     /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let mut points = Vec::new();
-    /// let count = reader.read_all_points(&mut points).unwrap();
-    /// assert_eq!(points.len(), count.try_into().unwrap());
+    /// assert_eq!(1, reader.number_of_chunks().unwrap());
     /// ```
-    pub fn read_all_points_into(&mut self, points: &mut Vec<Point>) -> Result<u64> {
-        let point_count = self.point_reader.header().number_of_points();
-        self.point_reader.read_points(point_count, points)
+    pub fn number_of_chunks(&mut self) -> Result<usize> {
+        self.point_reader.number_of_chunks()
     }
 
-    /// Reads all points into a vector.
-    #[deprecated(since = "0.9.0", note = "Use read_all_points_into() instead")]
-    pub fn read_all_points(&mut self, points: &mut Vec<Point>) -> Result<u64> {
-        self.read_all_points_into(points)
+    /// Reads all of the points in one chunk, without moving the point cursor.
+    ///
+    /// A laz chunk is found through the chunk table and decompressed on its own, so this returns
+    /// an error if the file has no usable chunk table. The chunks of an uncompressed file are
+    /// blocks of [UNCOMPRESSED_CHUNK_SIZE] points, so the same code can read either. The last
+    /// chunk can be shorter than the others, and withheld points are always included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Error, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(106, reader.read_chunk_at(0).unwrap().len());
+    /// assert!(matches!(
+    ///     reader.read_chunk_at(1).unwrap_err(),
+    ///     Error::ChunkOutOfRange { index: 1, chunks: 1 }
+    /// ));
+    /// ```
+    pub fn read_chunk_at(&mut self, chunk_index: usize) -> Result<Vec<Point>> {
+        self.point_reader.read_chunk(chunk_index)
     }
 
-    /// Seeks to the given point number, zero-indexed.
-    ///
-    /// Note that seeking on compressed (LAZ) data can be expensive as the reader
-    /// will have to seek to the closest chunk start and decompress all points up until
-    /// the point seeked to.
+    /// Returns an iterator over all of the points, decompressing laz chunks on the rayon thread
+    /// pool.
     ///
+    /// Chunks are read a few at a time, one for each thread, and their points are yielded in
+    /// order. Like [Reader::read_chunk_at], this starts at the first point whatever the point
+    /// cursor, doesn't move the cursor, and returns an error for a laz file without a usable chunk
+    /// table. Uncompressed chunks are read on the calling thread. Withheld points are left out if
+    /// the reader [skips them](ReadOptions::skip_withheld), and corrupt chunks are always an
+    /// error.
     ///
     /// # Examples
     ///
     /// ```
     /// use las::Reader;
     /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// reader.seek(1).unwrap(); // <- seeks to the second point
-    /// let the_second_point = reader.read().unwrap().unwrap();
+    /// let points = reader.points_par().collect::<las::Result<Vec<_>>>().unwrap();
+    /// assert_eq!(106, points.len());
     /// ```
-    pub fn seek(&mut self, position: u64) -> Result<()> {
-        self.point_reader.seek(position)
+    #[cfg(feature = "parallel")]
+    pub fn points_par(&mut self) -> ParPointIterator<'_, R> {
+        ParPointIterator {
+            point_reader: &mut self.point_reader,
+            next_chunk: 0,
+            chunks: None,
+            points: Vec::new().into_iter(),
+        }
     }
 
-    /// Returns an iterator over this reader's points.
+    /// Processes the remaining points in chunks, mapping each chunk on the rayon thread pool while
+    /// the next one is read.
+    ///
+    /// `map` gets each chunk of up to `chunk_size` points, and `reduce` folds the results in the
+    /// order the chunks were read. Reading and decompression overlap with `map`, so this is
+    /// fastest when both take a while. If there are no points left, returns `map` of an empty
+    /// slice.
+    ///
+    /// If `map` panics, the panic is passed on to the caller once the chunk being read is done.
+    /// A read error is returned after the chunk being mapped is done.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use las::Reader;
+    /// use las::Reader;
     /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let points = reader.points().collect::<Result<Vec<_>, _>>().unwrap();
+    /// let count = reader.process_par(10, |points| points.len(), |a, b| a + b).unwrap();
+    /// assert_eq!(106, count);
     /// ```
-    pub fn points(&mut self) -> PointIterator<'_> {
-        PointIterator {
-            point_reader: &mut *self.point_reader,
+    #[cfg(feature = "parallel")]
+    pub fn process_par<T, F, G>(&mut self, chunk_size: usize, map: F, mut reduce: G) -> Result<T>
+    where
+        T: Send,
+        F: Fn(&[Point]) -> T + Sync,
+        G: FnMut(T, T) -> T,
+    {
+        let chunk_size = chunk_size.max(1);
+        let n = u64::try_from(chunk_size)?;
+        let mut chunk = Vec::new();
+        let mut next = Vec::new();
+        let _ = self.read_points_into(n, &mut chunk)?;
+        let mut result = None;
+        while !chunk.is_empty() {
+            next.clear();
+            let (read, partial) =
+                rayon::join(|| self.read_points_into(n, &mut next), || map(&chunk));
+            result = Some(match result {
+                Some(result) => reduce(result, partial),
+                None => partial,
+            });
+            let _ = read?;
+            std::mem::swap(&mut chunk, &mut next);
+        }
+        Ok(result.unwrap_or_else(|| map(&[])))
+    }
+
+    /// Returns the index of the next point, counting withheld points that are skipped.
+    pub(crate) fn index(&self) -> u64 {
+        self.point_reader.index()
+    }
+
+    /// Reads up to `n` points, but none at or past index `end`, returning how many were read.
+    ///
+    /// Withheld points that are left out count towards the number read.
+    pub(crate) fn read_points_before(
+        &mut self,
+        end: u64,
+        n: u64,
+        points: &mut Vec<Point>,
+    ) -> Result<u64> {
+        self.point_reader.read_points_before(end, n, points)
+    }
+
+    /// Reads the next point record's bytes into `record`, returning false if there are none left.
+    pub(crate) fn read_record(&mut self, record: &mut [u8]) -> Result<bool> {
+        self.point_reader.read_record(record)
+    }
+
+    /// Fills `buf` from an absolute offset in the source, without moving the point cursor.
+    pub(crate) fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        self.point_reader.read_at(offset, buf)
+    }
+
+    /// Returns the offset of the first compressed chunk and the chunk table.
+    #[cfg(feature = "laz")]
+    pub(crate) fn chunk_table(&mut self) -> Result<(u64, ::laz::laszip::ChunkTable)> {
+        self.point_reader.chunk_table()
+    }
+
+    fn read_remaining(mut self, limit: usize) -> (Vec<Point>, Option<Error>) {
+        const CHUNK_SIZE: u64 = 1 << 16;
+
+        let remaining = self
+            .header()
+            .number_of_points()
+            .saturating_sub(self.point_reader.index());
+        let capacity = usize::try_from(remaining).unwrap_or(usize::MAX).min(limit);
+        let mut points = Vec::with_capacity(capacity);
+        loop {
+            match self.point_reader.read_points(CHUNK_SIZE, &mut points) {
+                Ok(0) => return (points, None),
+                Ok(_) => {}
+                Err(err) => return (points, Some(err)),
+            }
         }
     }
 }
 
+/// Returns true if a point record's coordinates are inside of the bounds, without decoding the
+/// rest of the point.
+fn record_in_bounds(record: &[u8], transforms: &Vector<Transform>, bounds: &Bounds) -> bool {
+    let coordinate = |i: usize| i32::from_le_bytes(record[4 * i..4 * i + 4].try_into().unwrap());
+    let x = transforms.x.direct(coordinate(0));
+    let y = transforms.y.direct(coordinate(1));
+    let z = transforms.z.direct(coordinate(2));
+    bounds.min.x <= x
+        && x <= bounds.max.x
+        && bounds.min.y <= y
+        && y <= bounds.max.y
+        && bounds.min.z <= z
+        && z <= bounds.max.z
+}
+
+#[cfg(feature = "laz")]
+pub(crate) fn contains(bounds: &Bounds, point: &Point) -> bool {
+    bounds.min.x <= point.x
+        && point.x <= bounds.max.x
+        && bounds.min.y <= point.y
+        && point.y <= bounds.max.y
+        && bounds.min.z <= point.z
+        && point.z <= bounds.max.z
+}
+
 #[allow(deprecated)]
-impl Read for Reader {
+impl<R: std::io::Read + Seek + Send> Read<R> for Reader<R> {
     /// Returns a reference to this reader's header.
     fn header(&self) -> &Header {
         self.header()
@@ -379,7 +3189,7 @@ impl Read for Reader {
     }
 
     /// Returns an iterator over this reader's points.
-    fn points(&mut self) -> PointIterator<'_> {
+    fn points(&mut self) -> PointIterator<'_, R> {
         self.points()
     }
 }
@@ -387,22 +3197,1030 @@ impl Read for Reader {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Writer;
+    use crate::{point::Classification, synthetic::fixture, Builder, GpsTimeType};
+    use std::io::Cursor;
+
+    #[test]
+    fn into_point_iter_in_thread() {
+        let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let handle = std::thread::spawn(move || {
+            for point in reader.into_point_iter() {
+                sender.send(point.unwrap()).unwrap();
+            }
+        });
+        let points: Vec<Point> = receiver.iter().collect();
+        handle.join().unwrap();
+        let expected = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .into_points()
+            .unwrap();
+        assert_eq!(expected, points);
+    }
+
+    #[test]
+    fn into_point_iter_errors_like_points() {
+        let mut bytes = fixture::write(Builder::default(), vec![Point::default(); 3]);
+        bytes.truncate(bytes.len() - 10);
+        let mut reader = fixture::reader(bytes.clone());
+        let borrowed: Vec<bool> = reader.points().map(|point| point.is_ok()).collect();
+        let owned: Vec<bool> = fixture::reader(bytes)
+            .into_point_iter()
+            .map(|point| point.is_ok())
+            .collect();
+        assert_eq!(borrowed, owned);
+        assert!(!borrowed[2]);
+    }
+
+    #[test]
+    fn gzipped() {
+        let mut bytes = vec![0x1f, 0x8b, 8, 0];
+        bytes.resize(375, 0);
+        match Reader::new(Cursor::new(bytes)) {
+            Err(Error::ReadFailed { source, .. }) => {
+                assert!(matches!(*source, Error::GzippedInput))
+            }
+            _ => panic!("expected a gzipped input error"),
+        }
+    }
+
+    #[test]
+    fn truncated_vlr() {
+        let mut bytes = std::fs::read("tests/data/autzen.las").unwrap();
+        bytes.truncate(227 + 60);
+        match Reader::new(Cursor::new(bytes)) {
+            Err(Error::VlrReadFailed { index: 0, .. }) => {}
+            Err(err) => panic!("unexpected error: {err}"),
+            Ok(_) => panic!("the vlr should be truncated"),
+        }
+    }
+
+    #[test]
+    fn recover_truncated_with_dimensions() {
+        let mut bytes = std::fs::read("tests/data/autzen.las").unwrap();
+        bytes.truncate(bytes.len() - 20);
+        let options = ReadOptions::default()
+            .recover_truncated(true)
+            .dimensions(&[Dimension::Intensity]);
+        let mut reader = Reader::with_options(Cursor::new(bytes.clone()), options).unwrap();
+        let results: Vec<_> = reader.points().collect();
+        assert_eq!(106, results.len());
+        assert!(results[..105].iter().all(|result| result.is_ok()));
+        match &results[105] {
+            Err(Error::PointsLost {
+                index: 105,
+                lost: 1,
+                source,
+            }) => assert!(matches!(
+                **source,
+                Error::PointReadFailed { index: 105, .. }
+            )),
+            result => panic!("unexpected result: {result:?}"),
+        }
+
+        let mut reader = Reader::with_options(Cursor::new(bytes), options).unwrap();
+        assert_eq!(105, reader.read_points(200).unwrap().len());
+        assert!(matches!(
+            reader.read_points(200),
+            Err(Error::PointsLost { index: 105, .. })
+        ));
+        assert!(reader.read_points(200).unwrap().is_empty());
+        assert_eq!(1, reader.summary().lost_points);
+    }
+
+    #[test]
+    fn recover_truncated_resumes_after_seek() {
+        let mut bytes = std::fs::read("tests/data/autzen.las").unwrap();
+        bytes.truncate(bytes.len() - 20);
+        let options = ReadOptions::default().recover_truncated(true);
+        let mut reader = Reader::with_options(Cursor::new(bytes), options).unwrap();
+        assert_eq!(105, reader.read_points(200).unwrap().len());
+        match reader.read_points(200) {
+            Err(Error::PointsLost {
+                index: 105,
+                lost: 1,
+                source,
+            }) => assert!(matches!(*source, Error::PointReadFailed { index: 105, .. })),
+            result => panic!("unexpected result: {result:?}"),
+        }
+        assert!(reader.read_point().unwrap().is_none());
+        assert!(!reader.summary().is_clean());
+
+        reader.seek(100).unwrap();
+        assert_eq!(5, reader.points().filter(|point| point.is_ok()).count());
+    }
+
+    #[test]
+    fn corrupt_signature() {
+        let mut bytes = std::fs::read("tests/data/autzen.las").unwrap();
+        bytes[0] = b'X';
+        match Reader::new(Cursor::new(bytes)) {
+            Err(Error::ReadFailed { source, .. }) => {
+                assert!(matches!(*source, Error::InvalidFileSignature(_)))
+            }
+            _ => panic!("expected an invalid file signature error"),
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "laz"))]
+    fn laz_not_enabled() {
+        let err = Reader::from_path("tests/data/autzen.laz")
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(err, Error::LaszipNotEnabled));
+        assert!(err.to_string().contains("`laz` feature"));
+    }
 
     #[test]
     fn seek() {
-        let mut writer = Writer::default();
-        writer.write_point(Default::default()).unwrap();
         let point = Point {
             x: 1.,
             y: 2.,
             z: 3.,
             ..Default::default()
         };
-        writer.write_point(point.clone()).unwrap();
-        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let bytes = fixture::write(Builder::default(), [Point::default(), point.clone()]);
+        let mut reader = fixture::reader(bytes);
         reader.seek(1).unwrap();
         assert_eq!(point, reader.read_point().unwrap().unwrap());
         assert!(reader.read_point().unwrap().is_none());
     }
+
+    fn assert_read_nth(path: &str) {
+        let points = Reader::from_path(path).unwrap().into_points().unwrap();
+        let mut reader = Reader::from_path(path).unwrap();
+        for index in [100, 3, 3, 57, 0, 105] {
+            assert_eq!(
+                points[index],
+                reader.read_nth(index as u64).unwrap().unwrap()
+            );
+        }
+        assert!(reader.read_point().unwrap().is_none());
+        assert!(reader.read_nth(106).unwrap().is_none());
+        assert_eq!(points[1], reader.read_nth(1).unwrap().unwrap());
+        assert_eq!(points[2], reader.read_point().unwrap().unwrap());
+    }
+
+    #[test]
+    fn read_nth() {
+        assert_read_nth("tests/data/autzen.las");
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn read_nth_laz() {
+        assert_read_nth("tests/data/autzen.laz");
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn read_nth_variable_laz_chunks() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.is_compressed = true;
+        let options = crate::writer::WriteOptions::default().variable_laz_chunks();
+        let mut writer = crate::Writer::with_options(
+            Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+            options,
+        )
+        .unwrap();
+        let mut i = 0;
+        for chunk_len in [100, 300, 50, 200] {
+            for _ in 0..chunk_len {
+                writer
+                    .write_point(Point {
+                        x: f64::from(i),
+                        ..Default::default()
+                    })
+                    .unwrap();
+                i += 1;
+            }
+            writer.finish_laz_chunk().unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        for index in [100, 250, 399, 0, 649, 400, 450, 99] {
+            assert_eq!(
+                index as f64,
+                reader.read_nth(index).unwrap().unwrap().x,
+                "point {index}"
+            );
+        }
+        reader.seek(449).unwrap();
+        assert_eq!(449., reader.read_point().unwrap().unwrap().x);
+        assert_eq!(450., reader.read_point().unwrap().unwrap().x);
+    }
+
+    #[test]
+    fn read_nth_withheld() {
+        let points = [false, true, false].map(|is_withheld| Point {
+            is_withheld,
+            ..Default::default()
+        });
+        let bytes = fixture::write(Builder::default(), points);
+        let options = ReadOptions::default().skip_withheld(true);
+        let mut reader = Reader::with_options(Cursor::new(bytes), options).unwrap();
+        assert!(reader.read_nth(1).unwrap().unwrap().is_withheld);
+    }
+
+    fn assert_read_header_is_reader_header(path: &str) {
+        let reader = Reader::from_path(path).unwrap();
+        assert_eq!(*reader.header(), read_header(path).unwrap());
+    }
+
+    #[test]
+    fn read_header_is_reader_header() {
+        assert_read_header_is_reader_header("tests/data/autzen.las");
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn read_header_is_laz_reader_header() {
+        assert_read_header_is_reader_header("tests/data/autzen.laz");
+        assert_read_header_is_reader_header("tests/data/autzen.copc.laz");
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn laz_header_before_points() {
+        let mut reader = Reader::from_path("tests/data/autzen.laz").unwrap();
+        assert_eq!(0, reader.point_reader.index());
+        assert!(reader.skipped_chunks().is_empty());
+        let header = reader.header().clone();
+        let point = reader.read_point().unwrap().unwrap();
+        assert_eq!(header, *reader.header());
+        let mut again = Reader::from_path("tests/data/autzen.laz").unwrap();
+        again.seek(0).unwrap();
+        assert_eq!(point, again.read_point().unwrap().unwrap());
+    }
+
+    #[test]
+    fn into_points() {
+        let points: Vec<Point> = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .points()
+            .collect::<Result<_>>()
+            .unwrap();
+        let into_points = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .into_points()
+            .unwrap();
+        assert_eq!(106, into_points.capacity());
+        assert_eq!(points, into_points);
+        let limited = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .into_points_with_limit(10)
+            .unwrap();
+        assert_eq!(points, limited);
+    }
+
+    #[test]
+    fn into_points_after_read() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let _ = reader.read_point().unwrap().unwrap();
+        let points = reader.into_points().unwrap();
+        assert_eq!(105, points.len());
+        assert_eq!(105, points.capacity());
+    }
+
+    #[test]
+    fn into_points_truncated() {
+        let mut bytes = fixture::write(Builder::default(), vec![Point::default(); 2]);
+        let _ = bytes.pop();
+        assert!(fixture::reader(bytes.clone()).into_points().is_err());
+        let (points, err) = fixture::reader(bytes).into_points_lossy();
+        assert_eq!(1, points.len());
+        assert!(err.is_some());
+    }
+
+    #[test]
+    fn waveform_samples_external() {
+        let mut reader = Reader::from_path("tests/data/waveform.las").unwrap();
+        let points: Vec<Point> = reader.points().collect::<Result<_>>().unwrap();
+        assert_eq!(
+            vec![1, 2, 3, 4, 5, 6, 7, 8],
+            reader.waveform_samples(&points[0]).unwrap()
+        );
+        assert_eq!(
+            vec![10, 20, 30, 40, 50, 60, 70, 80],
+            reader.waveform_samples(&points[1]).unwrap()
+        );
+        assert!(matches!(
+            reader.waveform_samples(&points[2]).unwrap_err(),
+            Error::WaveformPacketOutOfBounds {
+                offset: 1000,
+                size: 8,
+                len: 76
+            }
+        ));
+        assert!(matches!(
+            reader.waveform_samples(&points[3]).unwrap_err(),
+            Error::NoWaveform
+        ));
+    }
+
+    #[test]
+    fn waveform_samples_missing_wdp() {
+        let open = || BufReader::new(File::open("tests/data/waveform.las").unwrap());
+        let mut reader = Reader::new(open()).unwrap();
+        let point = reader.read_point().unwrap().unwrap();
+        assert!(matches!(
+            reader.waveform_samples(&point).unwrap_err(),
+            Error::WaveformFileRequired
+        ));
+        let mut reader = Reader::new(open())
+            .unwrap()
+            .with_wdp("tests/data/does-not-exist.wdp");
+        let error = reader.waveform_samples(&point).unwrap_err();
+        assert!(error.to_string().contains("does-not-exist.wdp"));
+        assert!(matches!(error, Error::WaveformFile { .. }));
+    }
+
+    #[test]
+    fn waveform_samples_without_waveforms() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut point = reader.read_point().unwrap().unwrap();
+        assert!(matches!(
+            reader.waveform_samples(&point).unwrap_err(),
+            Error::NoWaveform
+        ));
+        point.waveform = Some(raw::point::Waveform {
+            wave_packet_descriptor_index: 1,
+            ..Default::default()
+        });
+        assert!(matches!(
+            reader.waveform_samples(&point).unwrap_err(),
+            Error::NoWaveformDataPackets
+        ));
+    }
+
+    #[test]
+    fn waveform_internal_at_offset() {
+        let mut reader = Reader::from_path("tests/data/waveform-internal.las").unwrap();
+        let points: Vec<Point> = reader.points().collect::<Result<_>>().unwrap();
+        assert_eq!(
+            Samples::U8(vec![1, 2, 3, 4]),
+            reader.waveform(&points[0]).unwrap()
+        );
+        assert_eq!(
+            Samples::U16(vec![100, 200, 300]),
+            reader.waveform(&points[1]).unwrap()
+        );
+        assert!(matches!(
+            reader.waveform(&points[2]).unwrap_err(),
+            Error::WaveformPacketOutOfBounds {
+                offset: 68,
+                size: 4,
+                len: 70
+            }
+        ));
+        // Reading waveforms doesn't move the point cursor.
+        reader.seek(1).unwrap();
+        let _ = reader.waveform_samples(&points[0]).unwrap();
+        assert_eq!(points[1], reader.read_point().unwrap().unwrap());
+    }
+
+    #[test]
+    fn waveform_internal_evlr() {
+        let mut descriptor = vec![16, 0];
+        descriptor.extend(2u32.to_le_bytes());
+        descriptor.extend([0; 20]);
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(4).unwrap();
+        builder.has_internal_waveform_data_packets = true;
+        builder.vlrs.push(crate::Vlr {
+            user_id: waveform::USER_ID.to_string(),
+            record_id: waveform::FIRST_DESCRIPTOR_RECORD_ID,
+            data: descriptor,
+            ..Default::default()
+        });
+        builder.evlrs.push(crate::Vlr {
+            user_id: waveform::USER_ID.to_string(),
+            record_id: waveform::RECORD_ID,
+            data: vec![0, 0, 1, 0, 2, 0],
+            ..Default::default()
+        });
+        let point = Point {
+            gps_time: Some(0.),
+            waveform: Some(raw::point::Waveform {
+                wave_packet_descriptor_index: 1,
+                byte_offset_to_waveform_data: 62,
+                waveform_packet_size_in_bytes: 4,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut reader = fixture::reader(fixture::write(builder, [point]));
+        let mut point = reader.read_point().unwrap().unwrap();
+        assert_eq!(Samples::U16(vec![1, 2]), reader.waveform(&point).unwrap());
+        point
+            .waveform
+            .as_mut()
+            .unwrap()
+            .wave_packet_descriptor_index = 2;
+        assert!(matches!(
+            reader.waveform(&point).unwrap_err(),
+            Error::WaveformPacketDescriptorNotFound(2)
+        ));
+    }
+
+    #[test]
+    fn reader_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Reader>();
+        assert_send::<Reader<Cursor<Vec<u8>>>>();
+        assert_send::<Reader<Box<dyn ReadSeekSend>>>();
+    }
+
+    #[test]
+    fn boxed_and_generic_read_the_same_points() {
+        let mut generic = Reader::from_path("tests/data/autzen.las").unwrap();
+        let read: Box<dyn ReadSeekSend> =
+            Box::new(BufReader::new(File::open("tests/data/autzen.las").unwrap()));
+        let mut boxed = Reader::new(read).unwrap();
+        assert_eq!(generic.header(), boxed.header());
+        let generic_points = generic.points().collect::<Result<Vec<_>>>().unwrap();
+        let boxed_points = boxed.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(generic_points, boxed_points);
+    }
+
+    #[test]
+    fn borrowed_slice() {
+        let bytes = std::fs::read("tests/data/autzen.las").unwrap();
+        let mut reader = Reader::new(Cursor::new(&bytes[..])).unwrap();
+        let expected = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .into_points()
+            .unwrap();
+        assert_eq!(expected, reader.read_points(106).unwrap());
+        reader.seek(42).unwrap();
+        assert_eq!(expected[42], reader.read_point().unwrap().unwrap());
+    }
+
+    #[test]
+    fn point_data_record_length_too_small() {
+        let mut bytes = fixture::write(Builder::default(), [Point::default()]);
+        bytes[104] = 3;
+        bytes[105..107].copy_from_slice(&20u16.to_le_bytes());
+        match Reader::new(Cursor::new(bytes)) {
+            Err(Error::ReadFailed { offset, source }) => {
+                assert_eq!(105, offset);
+                assert!(matches!(
+                    *source,
+                    Error::PointDataRecordLengthTooLarge {
+                        len: 20,
+                        minimum: 34,
+                        ..
+                    }
+                ));
+            }
+            Err(err) => panic!("unexpected error: {err}"),
+            Ok(_) => panic!("the record length should be too small"),
+        }
+    }
+
+    #[test]
+    fn indexed_points() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let indexed = reader.indexed_points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(reader.header().number_of_points(), indexed.len() as u64);
+        for &i in &[0, 1, 42, indexed.len() - 1] {
+            let (index, ref point) = indexed[i];
+            assert_eq!(i as u64, index);
+
+            reader.seek(index).unwrap();
+            assert_eq!(*point, reader.read_point().unwrap().unwrap());
+
+            let mut file = File::open("tests/data/autzen.las").unwrap();
+            let _ = file
+                .seek(std::io::SeekFrom::Start(
+                    reader.point_offset(index).unwrap(),
+                ))
+                .unwrap();
+            let raw_point =
+                raw::Point::read_from(&mut file, reader.header().point_format()).unwrap();
+            assert_eq!(*point, Point::new(raw_point, reader.header().transforms()));
+        }
+    }
+
+    #[test]
+    fn indexed_points_after_seek_and_chunked_reads() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let _ = reader.read_points(10).unwrap();
+        assert_eq!(10, reader.indexed_points().next().unwrap().unwrap().0);
+        reader.seek(100).unwrap();
+        let mut indexed = reader.indexed_points();
+        assert_eq!(100, indexed.next().unwrap().unwrap().0);
+        assert_eq!(101, indexed.next().unwrap().unwrap().0);
+    }
+
+    #[test]
+    fn read_chunk_at_uncompressed() {
+        let n = 2 * UNCOMPRESSED_CHUNK_SIZE + 7;
+        let points = (0..n).map(|i| Point {
+            x: i as f64,
+            ..Default::default()
+        });
+        let mut reader = fixture::reader(fixture::write(Builder::from((1, 2)), points));
+        let _ = reader.read_points(3).unwrap();
+        assert_eq!(3, reader.number_of_chunks().unwrap());
+        let last = reader.read_chunk_at(2).unwrap();
+        assert_eq!(7, last.len());
+        assert_eq!((2 * UNCOMPRESSED_CHUNK_SIZE) as f64, last[0].x);
+        let first = reader.read_chunk_at(0).unwrap();
+        assert_eq!(UNCOMPRESSED_CHUNK_SIZE as usize, first.len());
+        assert_eq!(
+            (UNCOMPRESSED_CHUNK_SIZE - 1) as f64,
+            first.last().unwrap().x
+        );
+        assert_eq!(3., reader.read_point().unwrap().unwrap().x);
+        assert!(matches!(
+            reader.read_chunk_at(3).unwrap_err(),
+            Error::ChunkOutOfRange {
+                index: 3,
+                chunks: 3
+            }
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn indexed_points_compressed() {
+        let mut reader = Reader::from_path("tests/data/autzen.laz").unwrap();
+        assert_eq!(None, reader.point_offset(0));
+        let _ = reader.read_points(10).unwrap();
+        let (index, point) = reader.indexed_points().next().unwrap().unwrap();
+        assert_eq!(10, index);
+        reader.seek(10).unwrap();
+        assert_eq!(point, reader.read_point().unwrap().unwrap());
+    }
+
+    /// Writes points with a mix of classes and flags, returning the expected count per class.
+    fn mixed_classes(format: u8, compressed: bool) -> (Vec<u8>, Vec<(Classification, usize)>) {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.point_format.is_compressed = compressed;
+        builder.gps_time_type = GpsTimeType::Standard;
+        let classes = [
+            (Classification::Ground, 0),
+            (Classification::Ground, 1),
+            (Classification::Ground, 2),
+            (Classification::Water, 3),
+            (Classification::Ground, 4),
+            (Classification::Unclassified, 5),
+            (Classification::Building, 6),
+            (Classification::Water, 7),
+            (Classification::Reserved(25), 8),
+            (Classification::Ground, 9),
+        ];
+        let points = classes.map(|(classification, i)| Point {
+            classification,
+            is_withheld: i % 2 == 0,
+            is_synthetic: i % 3 == 0,
+            is_key_point: i % 4 == 0,
+            is_overlap: i == 2 || i == 5,
+            gps_time: Some(f64::from(i)),
+            ..Default::default()
+        });
+        // Overlap points in legacy formats are read back as unclassified.
+        let overlap_ground = if Format::new(format).unwrap().is_extended {
+            1
+        } else {
+            0
+        };
+        let counts = vec![
+            (Classification::Ground, 4 + overlap_ground),
+            (Classification::Unclassified, 2 - overlap_ground),
+            (Classification::Water, 2),
+            (Classification::Building, 1),
+            (Classification::Reserved(25), 1),
+            (Classification::LowPoint, 0),
+        ];
+        (fixture::write(builder, points), counts)
+    }
+
+    fn assert_classified_counts(format: u8, compressed: bool) {
+        let (bytes, counts) = mixed_classes(format, compressed);
+        let mut reader = fixture::reader(bytes);
+        for (classification, count) in counts {
+            reader.seek(0).unwrap();
+            let points = reader
+                .points_with_classification(classification)
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(
+                count,
+                points.len(),
+                "{:?} in format {}",
+                classification,
+                format
+            );
+            assert!(points.iter().all(|p| p.classification == classification));
+        }
+
+        reader.seek(0).unwrap();
+        let all = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        reader.seek(0).unwrap();
+        let wet: &[Classification] = &[Classification::Water, Classification::Building];
+        let filtered = reader
+            .points_with_classification(wet)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let expected: Vec<_> = all
+            .iter()
+            .filter(|p| wet.contains(&p.classification))
+            .cloned()
+            .collect();
+        assert_eq!(expected, filtered);
+
+        reader.seek(0).unwrap();
+        let count = reader
+            .points_with_classification(|c| c != Classification::Ground)
+            .count();
+        assert_eq!(
+            all.iter()
+                .filter(|p| p.classification != Classification::Ground)
+                .count(),
+            count
+        );
+    }
+
+    #[test]
+    fn points_with_classification() {
+        for format in [1, 6] {
+            assert_classified_counts(format, false);
+        }
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn points_with_classification_compressed() {
+        for format in [1, 6] {
+            assert_classified_counts(format, true);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn process_par_histogram() {
+        fn histogram(points: &[Point]) -> Vec<u64> {
+            let mut histogram = vec![0; 256];
+            for point in points {
+                histogram[usize::from(u8::from(point.classification))] += 1;
+            }
+            histogram
+        }
+
+        let expected = histogram(
+            &Reader::from_path("tests/data/autzen.las")
+                .unwrap()
+                .into_points()
+                .unwrap(),
+        );
+        for chunk_size in [0, 7, 106, 1000] {
+            let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+            let actual = reader
+                .process_par(chunk_size, histogram, |a, b| {
+                    a.iter().zip(b).map(|(a, b)| a + b).collect()
+                })
+                .unwrap();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn process_par_in_order() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let gps_times = reader
+            .process_par(
+                10,
+                |points| points.iter().map(|p| p.gps_time).collect::<Vec<_>>(),
+                |mut a, b| {
+                    a.extend(b);
+                    a
+                },
+            )
+            .unwrap();
+        let expected: Vec<_> = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .points()
+            .map(|p| p.unwrap().gps_time)
+            .collect();
+        assert_eq!(expected, gps_times);
+        assert_eq!(
+            0,
+            reader
+                .process_par(10, |points| points.len(), |a, b| a + b)
+                .unwrap()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn process_par_panic() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            reader.process_par(
+                10,
+                |points| {
+                    if points[0].gps_time > Some(0.) {
+                        panic!("can't map this chunk");
+                    }
+                },
+                |(), ()| (),
+            )
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn points_decimated() {
+        let points = (0..20).map(|i| Point {
+            x: f64::from(i),
+            ..Default::default()
+        });
+        let mut reader = fixture::reader(fixture::write(Builder::default(), points));
+        let mut indices = |decimation| {
+            reader.seek(0).unwrap();
+            reader
+                .points_decimated(decimation)
+                .map(|point| point.unwrap().x as u64)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![0, 6, 12, 18], indices(Decimation::EveryNth(6)));
+        assert_eq!(vec![0, 4, 8, 12, 16], indices(Decimation::MaxPoints(5)));
+        assert_eq!(20, indices(Decimation::MaxPoints(100)).len());
+        let fraction = Decimation::Fraction {
+            fraction: 0.5,
+            seed: 1,
+        };
+        let picked = indices(fraction);
+        assert!(!picked.is_empty() && picked.len() < 20);
+        assert_eq!(picked, indices(fraction));
+
+        reader.seek(7).unwrap();
+        let points = reader
+            .points_decimated(Decimation::EveryNth(6))
+            .map(|point| point.unwrap().x)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![12., 18.], points);
+    }
+
+    /// Writes points with the given source ids, and their index as x.
+    fn with_source_ids(format: u8, source_ids: &[u16]) -> Vec<u8> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.gps_time_type = GpsTimeType::Standard;
+        let points = source_ids
+            .iter()
+            .enumerate()
+            .map(|(i, &point_source_id)| Point {
+                x: i as f64,
+                point_source_id,
+                gps_time: Some(0.),
+                ..Default::default()
+            });
+        fixture::write(builder, points)
+    }
+
+    fn source_id_indices(reader: &mut Reader<Cursor<Vec<u8>>>, source_id: u16) -> Vec<u64> {
+        reader.seek(0).unwrap();
+        reader
+            .points_with_source_id(source_id)
+            .map(|point| point.unwrap().x as u64)
+            .collect()
+    }
+
+    #[test]
+    fn points_with_source_id_scan() {
+        let source_ids: Vec<u16> = (0..30).map(|i| i % 4).collect();
+        let mut reader = fixture::reader(with_source_ids(1, &source_ids));
+        assert_eq!(
+            vec![1, 5, 9, 13, 17, 21, 25, 29],
+            source_id_indices(&mut reader, 1)
+        );
+        assert_eq!(7, source_id_indices(&mut reader, 3).len());
+        assert!(source_id_indices(&mut reader, 4).is_empty());
+
+        // A wrong hint can miss points, but never returns the wrong ones.
+        let options = ReadOptions::default().assume_sorted_by_source_id(true);
+        let mut reader =
+            Reader::with_options(Cursor::new(with_source_ids(1, &source_ids)), options).unwrap();
+        for source_id in 0..4 {
+            for index in source_id_indices(&mut reader, source_id) {
+                assert_eq!(source_id, source_ids[index as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn points_with_source_id_sorted() {
+        let mut source_ids = vec![1; 5];
+        source_ids.extend([3; 10]);
+        source_ids.extend([4; 3]);
+        source_ids.extend([9; 12]);
+        for format in [1, 6] {
+            let options = ReadOptions::default().assume_sorted_by_source_id(true);
+            let mut reader =
+                Reader::with_options(Cursor::new(with_source_ids(format, &source_ids)), options)
+                    .unwrap();
+            assert_eq!(
+                (5..15).collect::<Vec<_>>(),
+                source_id_indices(&mut reader, 3)
+            );
+            // Only the matching range was read, where a scan reads every point.
+            assert_eq!(15, reader.point_reader.index());
+            let mut scan = fixture::reader(with_source_ids(format, &source_ids));
+            assert_eq!((5..15).collect::<Vec<_>>(), source_id_indices(&mut scan, 3));
+            assert_eq!(30, scan.point_reader.index());
+            assert_eq!(
+                (0..5).collect::<Vec<_>>(),
+                source_id_indices(&mut reader, 1)
+            );
+            assert_eq!(
+                (18..30).collect::<Vec<_>>(),
+                source_id_indices(&mut reader, 9)
+            );
+            for missing in [0, 2, 5, 10] {
+                assert!(source_id_indices(&mut reader, missing).is_empty());
+            }
+
+            reader.seek(16).unwrap();
+            let indices: Vec<u64> = reader
+                .points_with_source_id(4)
+                .map(|point| point.unwrap().x as u64)
+                .collect();
+            assert_eq!(vec![16, 17], indices);
+        }
+    }
+
+    #[test]
+    fn read_point_into_matches_read_point() {
+        for path in ["tests/data/autzen.las", "tests/data/extrabytes.laz"] {
+            #[cfg(not(feature = "laz"))]
+            if path.ends_with(".laz") {
+                continue;
+            }
+            let expected = Reader::from_path(path).unwrap().into_points().unwrap();
+            let mut reader = Reader::from_path(path).unwrap();
+            let mut point = Point::default();
+            for expected in expected {
+                assert!(reader.read_point_into(&mut point).unwrap());
+                assert_eq!(expected, point);
+            }
+            let last = point.clone();
+            assert!(!reader.read_point_into(&mut point).unwrap());
+            assert_eq!(last, point);
+        }
+    }
+
+    #[test]
+    fn read_point_into_clears_missing_fields() {
+        let mut point = Point {
+            color: Some(crate::Color::new(1, 2, 3)),
+            nir: Some(4),
+            gps_time: Some(5.),
+            extra_bytes: vec![6; 20].into(),
+            ..Default::default()
+        };
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        assert!(reader.read_point_into(&mut point).unwrap());
+        assert_eq!(None, point.color);
+        assert_eq!(None, point.nir);
+        assert!(point.gps_time.is_some());
+        assert!(point.extra_bytes.is_empty());
+    }
+
+    #[test]
+    fn chunks() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let expected = reader.read_points(106).unwrap();
+        reader.seek(6).unwrap();
+        let chunks: Vec<Vec<Point>> = reader.chunks(25).map(|chunk| chunk.unwrap()).collect();
+        assert_eq!(4, chunks.len());
+        assert!(chunks.iter().all(|chunk| chunk.len() == 25));
+        assert_eq!(expected[6..], chunks.concat()[..]);
+        assert!(reader.chunks(25).next().is_none());
+        reader.seek(104).unwrap();
+        assert_eq!(2, reader.chunks(0).count());
+    }
+
+    fn assert_read_raw_point_into(path: &str) {
+        let mut expected = Reader::from_path(path).unwrap();
+        let format = *expected.header().point_format();
+        let mut reader = Reader::from_path(path).unwrap();
+        let mut raw_point = raw::Point::default();
+        let mut record = vec![0; usize::from(format.len())];
+        while expected.read_record_into(&mut record).unwrap() {
+            assert!(reader.read_raw_point_into(&mut raw_point).unwrap());
+            assert_eq!(
+                raw::Point::read_from(record.as_slice(), &format).unwrap(),
+                raw_point
+            );
+        }
+        let last = raw_point.clone();
+        assert!(!reader.read_raw_point_into(&mut raw_point).unwrap());
+        assert_eq!(last, raw_point);
+    }
+
+    #[test]
+    fn read_raw_point_into() {
+        assert_read_raw_point_into("tests/data/autzen.las");
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn read_raw_point_into_laz() {
+        assert_read_raw_point_into("tests/data/autzen.laz");
+    }
+
+    #[test]
+    fn map_user_data_with_dictionary() {
+        let mut builder = Builder::from((1, 4));
+        builder
+            .user_data_dictionary(vec![(1, "left".to_string()), (2, "right".to_string())])
+            .unwrap();
+        let points = [2, 1, 5].map(|user_data| Point {
+            user_data,
+            ..Default::default()
+        });
+        let mut reader = fixture::reader(fixture::write(builder, points));
+        let dictionary = reader.header().user_data_dictionary().unwrap().unwrap();
+        let labels: Vec<(u8, Option<String>)> = reader
+            .map_user_data(|value| dictionary.label(value).map(str::to_string))
+            .map(|result| result.map(|(point, label)| (point.user_data, label)))
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (2, Some("right".to_string())),
+                (1, Some("left".to_string())),
+                (5, None)
+            ],
+            labels
+        );
+    }
+
+    #[test]
+    fn map_user_data_without_dictionary() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        assert!(reader.header().user_data_dictionary().unwrap().is_none());
+        let expected: Vec<Point> = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .into_points()
+            .unwrap();
+        let mapped: Vec<(Point, u16)> = reader
+            .map_user_data(u16::from)
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(expected.len(), mapped.len());
+        for (point, (mapped, value)) in expected.into_iter().zip(mapped) {
+            assert_eq!(u16::from(point.user_data), value);
+            assert_eq!(point, mapped);
+        }
+    }
+
+    fn progress_events(path: &str, read: impl FnOnce(&mut Reader)) -> Vec<ProgressEvent> {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = std::sync::Arc::clone(&events);
+        let mut reader = Reader::from_path(path).unwrap();
+        reader.on_progress(40, move |event| sink.lock().unwrap().push(event));
+        read(&mut reader);
+        drop(reader);
+        std::sync::Arc::try_unwrap(events)
+            .unwrap()
+            .into_inner()
+            .unwrap()
+    }
+
+    #[test]
+    fn progress() {
+        let events = progress_events("tests/data/autzen.las", |reader| {
+            while reader.read_point().unwrap().is_some() {}
+        });
+        let points: Vec<u64> = events.iter().map(|event| event.points).collect();
+        assert_eq!(vec![40, 80, 106], points);
+        let len = u64::from(Format::new(1).unwrap().len());
+        assert_eq!(Some(106 * len), events[2].bytes);
+        assert_eq!(Some(106), events[2].total_points);
+
+        let events = progress_events("tests/data/autzen.las", |reader| {
+            let _ = reader.read_points(100).unwrap();
+            reader.seek(0).unwrap();
+            let _ = reader.read_points(10).unwrap();
+        });
+        let points: Vec<u64> = events.iter().map(|event| event.points).collect();
+        assert_eq!(vec![100, 10], points);
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn progress_compressed() {
+        let events = progress_events("tests/data/autzen.laz", |reader| {
+            let _ = reader.points().count();
+        });
+        assert_eq!(106, events.last().unwrap().points);
+        assert!(events.iter().all(|event| event.bytes.is_none()));
+    }
 }
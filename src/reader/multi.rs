@@ -0,0 +1,541 @@
+use super::Reader;
+use crate::{
+    header::{Incompatibility, SystemIdentifier},
+    Bounds, Error, Header, Point, Result, Transform, Vector,
+};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read, Seek},
+};
+
+/// Options for [MultiReader::with_options].
+///
+/// # Examples
+///
+/// ```
+/// use las::reader::MultiReaderOptions;
+/// let options = MultiReaderOptions::default().requantize();
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultiReaderOptions {
+    requantize: bool,
+    system_identifier: SystemIdentifier,
+}
+
+impl MultiReaderOptions {
+    /// Accepts inputs with different scales and offsets, and rounds their points to the first
+    /// input's, instead of returning an error.
+    pub fn requantize(mut self) -> MultiReaderOptions {
+        self.requantize = true;
+        self
+    }
+
+    /// Sets the merged header's system identifier, which defaults to [SystemIdentifier::Merge].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{header::SystemIdentifier, reader::MultiReaderOptions};
+    /// let options = MultiReaderOptions::default().system_identifier(SystemIdentifier::Extraction);
+    /// ```
+    pub fn system_identifier(mut self, system_identifier: SystemIdentifier) -> MultiReaderOptions {
+        self.system_identifier = system_identifier;
+        self
+    }
+}
+
+impl Default for MultiReaderOptions {
+    fn default() -> MultiReaderOptions {
+        MultiReaderOptions {
+            requantize: false,
+            system_identifier: SystemIdentifier::Merge,
+        }
+    }
+}
+
+/// Reads several inputs as one stream of points.
+///
+/// The inputs are read in order. The merged header is the first input's, with every input's
+/// point counts and bounds added in, and a system identifier of `MERGE`. Vlrs, including the
+/// coordinate reference system, come from the first input, and a warning is logged if a later
+/// input's crs vlrs are different.
+///
+/// # Examples
+///
+/// ```
+/// use las::{reader::MultiReader, Reader};
+///
+/// let readers = vec![
+///     Reader::from_path("tests/data/autzen.las").unwrap(),
+///     Reader::from_path("tests/data/autzen.las").unwrap(),
+/// ];
+/// let mut reader = MultiReader::new(readers).unwrap();
+/// assert_eq!(212, reader.header().number_of_points());
+/// assert_eq!(212, reader.points().count());
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct MultiReader<R: Read + Seek = BufReader<File>> {
+    readers: Vec<Reader<R>>,
+    header: Header,
+    starts: Vec<u64>,
+    current: usize,
+    index: u64,
+    requantize: bool,
+}
+
+impl<R: Read + Seek + Send> MultiReader<R> {
+    /// Creates a reader over several inputs, which must share a point layout.
+    ///
+    /// Returns [Error::NoInputs] if there are no readers, and [Error::IncompatibleLayout] if an
+    /// input's point format, scales and offsets, or gps time type differ from the first input's.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::MultiReader, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let reader = MultiReader::new(vec![reader]).unwrap();
+    /// ```
+    pub fn new(readers: Vec<Reader<R>>) -> Result<MultiReader<R>> {
+        MultiReader::with_options(readers, MultiReaderOptions::default())
+    }
+
+    /// Creates a reader over several inputs with options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::{MultiReader, MultiReaderOptions}, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let options = MultiReaderOptions::default().requantize();
+    /// let reader = MultiReader::with_options(vec![reader], options).unwrap();
+    /// ```
+    pub fn with_options(
+        mut readers: Vec<Reader<R>>,
+        options: MultiReaderOptions,
+    ) -> Result<MultiReader<R>> {
+        let first = readers.first().ok_or(Error::NoInputs)?.header();
+        let mut header = first.to_derived(&options.system_identifier);
+        let crs_vlrs = |header: &Header| {
+            header
+                .all_vlrs()
+                .filter(|vlr| vlr.is_projection())
+                .map(|vlr| vlr.vlr.clone())
+                .collect::<Vec<_>>()
+        };
+        let first_crs_vlrs = crs_vlrs(first);
+        let mut starts = Vec::with_capacity(readers.len());
+        for (index, reader) in readers.iter().enumerate() {
+            let other = reader.header();
+            if let Err(mut incompatibilities) = header.layout_compatible_with(other) {
+                if options.requantize {
+                    incompatibilities.retain(|incompatibility| {
+                        !matches!(incompatibility, Incompatibility::Transforms { .. })
+                    });
+                }
+                if !incompatibilities.is_empty() {
+                    return Err(Error::IncompatibleLayout {
+                        index,
+                        incompatibilities,
+                    });
+                }
+            }
+            if crs_vlrs(other) != first_crs_vlrs {
+                log::warn!(
+                    "input {} has a different crs than the first input, which is used for all of them",
+                    index
+                );
+            }
+            starts.push(header.number_of_points());
+            let number_of_points_by_return = (1..=15)
+                .filter_map(|n| other.number_of_points_by_return(n).map(|count| (n, count)))
+                .collect::<HashMap<_, _>>();
+            // Files without points can have zeroed bounds, which shouldn't grow the union.
+            let bounds = if other.number_of_points() > 0 {
+                other.bounds()
+            } else {
+                Bounds::default()
+            };
+            header.add_points(
+                other.number_of_points(),
+                &number_of_points_by_return,
+                &bounds,
+            );
+        }
+        readers[0].seek(0)?;
+        Ok(MultiReader {
+            readers,
+            header,
+            starts,
+            current: 0,
+            index: 0,
+            requantize: options.requantize,
+        })
+    }
+
+    /// Returns the merged header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::MultiReader, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let reader = MultiReader::new(vec![reader]).unwrap();
+    /// assert_eq!(106, reader.header().number_of_points());
+    /// ```
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the inputs' readers.
+    pub fn readers(&self) -> &[Reader<R>] {
+        &self.readers
+    }
+
+    /// Reads the next point, moving on to the next input when one runs out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::MultiReader, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut reader = MultiReader::new(vec![reader]).unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>> {
+        Ok(self.read_point_with_source()?.map(|(_, point)| point))
+    }
+
+    /// Reads `n` points, or fewer if the inputs run out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::MultiReader, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut reader = MultiReader::new(vec![reader]).unwrap();
+    /// let points = reader.read_points(10).unwrap();
+    /// assert_eq!(10, points.len());
+    /// ```
+    pub fn read_points(&mut self, n: u64) -> Result<Vec<Point>> {
+        let mut points = Vec::new();
+        let _ = self.read_points_into(n, &mut points)?;
+        Ok(points)
+    }
+
+    /// Reads up to `n` points onto the end of a vector, returning how many were read.
+    ///
+    /// Reads can cross from one input into the next.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::MultiReader, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut reader = MultiReader::new(vec![reader]).unwrap();
+    /// let mut points = Vec::new();
+    /// assert_eq!(10, reader.read_points_into(10, &mut points).unwrap());
+    /// ```
+    pub fn read_points_into(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        let mut count = 0;
+        while count < n && self.current < self.readers.len() {
+            let start = points.len();
+            let read = self.readers[self.current].read_points_into(n - count, points)?;
+            if read == 0 {
+                self.next_reader()?;
+                continue;
+            }
+            let transforms = self.readers[self.current].header().effective_transforms();
+            if self.requantize {
+                for point in &mut points[start..] {
+                    requantize(self.header.effective_transforms(), transforms, point)?;
+                }
+            }
+            count += read;
+            self.index += read;
+        }
+        Ok(count)
+    }
+
+    /// Seeks to a point by its index across all of the inputs, zero-indexed.
+    ///
+    /// Returns [Error::SeekIndexOutOfBounds] if the index is past the last point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::MultiReader, Reader};
+    /// let readers = vec![
+    ///     Reader::from_path("tests/data/autzen.las").unwrap(),
+    ///     Reader::from_path("tests/data/autzen.las").unwrap(),
+    /// ];
+    /// let mut reader = MultiReader::new(readers).unwrap();
+    /// reader.seek(107).unwrap(); // <- the second point of the second input
+    /// ```
+    pub fn seek(&mut self, index: u64) -> Result<()> {
+        if index > self.header.number_of_points() {
+            return Err(Error::SeekIndexOutOfBounds(index));
+        }
+        // The last input that starts at or before the index, skipping inputs without points.
+        let current = self.starts.partition_point(|&start| start <= index) - 1;
+        self.readers[current].seek(index - self.starts[current])?;
+        self.current = current;
+        self.index = index;
+        Ok(())
+    }
+
+    /// Returns the index, across all of the inputs, of the next point to be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::MultiReader, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut reader = MultiReader::new(vec![reader]).unwrap();
+    /// let _ = reader.read_points(10).unwrap();
+    /// assert_eq!(10, reader.index());
+    /// ```
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns an iterator over the points of all of the inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::MultiReader, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut reader = MultiReader::new(vec![reader]).unwrap();
+    /// let points = reader.points().collect::<Result<Vec<_>, _>>().unwrap();
+    /// ```
+    pub fn points(&mut self) -> MultiPointIterator<'_, R> {
+        MultiPointIterator { reader: self }
+    }
+
+    /// Returns an iterator over the points of all of the inputs, along with the index of the
+    /// input each point came from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reader::MultiReader, Reader};
+    /// let readers = vec![
+    ///     Reader::from_path("tests/data/autzen.las").unwrap(),
+    ///     Reader::from_path("tests/data/autzen.las").unwrap(),
+    /// ];
+    /// let mut reader = MultiReader::new(readers).unwrap();
+    /// reader.seek(106).unwrap();
+    /// let (source, _) = reader.points_with_source().next().unwrap().unwrap();
+    /// assert_eq!(1, source);
+    /// ```
+    pub fn points_with_source(&mut self) -> SourcedPointIterator<'_, R> {
+        SourcedPointIterator { reader: self }
+    }
+
+    fn read_point_with_source(&mut self) -> Result<Option<(usize, Point)>> {
+        while self.current < self.readers.len() {
+            if let Some(mut point) = self.readers[self.current].read_point()? {
+                if self.requantize {
+                    let transforms = self.readers[self.current].header().effective_transforms();
+                    requantize(self.header.effective_transforms(), transforms, &mut point)?;
+                }
+                self.index += 1;
+                return Ok(Some((self.current, point)));
+            }
+            self.next_reader()?;
+        }
+        Ok(None)
+    }
+
+    fn next_reader(&mut self) -> Result<()> {
+        self.current += 1;
+        if let Some(reader) = self.readers.get_mut(self.current) {
+            reader.seek(0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rounds a point's coordinates to the merged scales and offsets, if the point was read with
+/// different ones.
+fn requantize(
+    merged: &Vector<Transform>,
+    transforms: &Vector<Transform>,
+    point: &mut Point,
+) -> Result<()> {
+    if transforms != merged {
+        let round =
+            |transform: &Transform, n: f64| transform.inverse(n).map(|n| transform.direct(n));
+        point.x = round(&merged.x, point.x)?;
+        point.y = round(&merged.y, point.y)?;
+        point.z = round(&merged.z, point.z)?;
+    }
+    Ok(())
+}
+
+/// An iterator over the points in a [MultiReader].
+///
+/// This struct is generally created by calling `points()` on `MultiReader`.
+#[allow(missing_debug_implementations)]
+pub struct MultiPointIterator<'a, R: Read + Seek + Send = BufReader<File>> {
+    reader: &'a mut MultiReader<R>,
+}
+
+impl<R: Read + Seek + Send> Iterator for MultiPointIterator<'_, R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_point().transpose()
+    }
+}
+
+/// An iterator over the points in a [MultiReader], along with the index of their input.
+///
+/// This struct is generally created by calling `points_with_source()` on `MultiReader`.
+#[allow(missing_debug_implementations)]
+pub struct SourcedPointIterator<'a, R: Read + Seek + Send = BufReader<File>> {
+    reader: &'a mut MultiReader<R>,
+}
+
+impl<R: Read + Seek + Send> Iterator for SourcedPointIterator<'_, R> {
+    type Item = Result<(usize, Point)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_point_with_source().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{synthetic::fixture, Builder};
+    use std::io::Cursor;
+
+    /// Writes a file whose points have x from `start` to `start + n`.
+    fn input(start: u32, n: u32, scale: f64) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 2));
+        builder.transforms.x = Transform { scale, offset: 0. };
+        let points = (start..start + n).map(|x| Point {
+            x: f64::from(x),
+            y: f64::from(x) / 2.,
+            return_number: 1,
+            number_of_returns: 1,
+            ..Default::default()
+        });
+        fixture::reader(fixture::write(builder, points))
+    }
+
+    fn fixtures() -> Vec<Reader<Cursor<Vec<u8>>>> {
+        vec![input(0, 3, 0.01), input(3, 0, 0.01), input(3, 4, 0.01)]
+    }
+
+    fn xs(points: &[Point]) -> Vec<f64> {
+        points.iter().map(|point| point.x).collect()
+    }
+
+    #[test]
+    fn merged_header() {
+        let reader = MultiReader::new(fixtures()).unwrap();
+        let header = reader.header();
+        assert_eq!(7, header.number_of_points());
+        assert_eq!(Some(7), header.number_of_points_by_return(1));
+        assert_eq!(0., header.bounds().min.x);
+        assert_eq!(6., header.bounds().max.x);
+        assert_eq!(3., header.bounds().max.y);
+    }
+
+    #[test]
+    fn merged_system_identifier() {
+        let reader = MultiReader::new(fixtures()).unwrap();
+        assert_eq!(
+            SystemIdentifier::Merge,
+            reader.header().system_identifier_kind()
+        );
+        let options = MultiReaderOptions::default()
+            .system_identifier(SystemIdentifier::Other("OTHER: tiles".to_string()));
+        let reader = MultiReader::with_options(fixtures(), options).unwrap();
+        assert_eq!("OTHER: tiles", reader.header().system_identifier());
+    }
+
+    #[test]
+    fn read_in_order() {
+        let mut reader = MultiReader::new(fixtures()).unwrap();
+        let points = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(vec![0., 1., 2., 3., 4., 5., 6.], xs(&points));
+        assert!(reader.read_point().unwrap().is_none());
+    }
+
+    #[test]
+    fn read_points_across_inputs() {
+        let mut reader = MultiReader::new(fixtures()).unwrap();
+        assert_eq!(vec![0., 1.], xs(&reader.read_points(2).unwrap()));
+        assert_eq!(vec![2., 3., 4.], xs(&reader.read_points(3).unwrap()));
+        assert_eq!(vec![5., 6.], xs(&reader.read_points(10).unwrap()));
+        assert!(reader.read_points(10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn points_with_source() {
+        let mut reader = MultiReader::new(fixtures()).unwrap();
+        let sources = reader
+            .points_with_source()
+            .map(|result| result.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0, 0, 0, 2, 2, 2, 2], sources);
+    }
+
+    #[test]
+    fn seek_across_inputs() {
+        let mut reader = MultiReader::new(fixtures()).unwrap();
+        reader.seek(5).unwrap();
+        assert_eq!(5., reader.read_point().unwrap().unwrap().x);
+        reader.seek(1).unwrap();
+        assert_eq!(vec![1., 2., 3.], xs(&reader.read_points(3).unwrap()));
+        assert_eq!(4, reader.index());
+        reader.seek(3).unwrap();
+        let (source, point) = reader.points_with_source().next().unwrap().unwrap();
+        assert_eq!((2, 3.), (source, point.x));
+        reader.seek(7).unwrap();
+        assert!(reader.read_point().unwrap().is_none());
+        assert!(matches!(
+            reader.seek(8).unwrap_err(),
+            Error::SeekIndexOutOfBounds(8)
+        ));
+    }
+
+    #[test]
+    fn no_inputs() {
+        let readers: Vec<Reader<Cursor<Vec<u8>>>> = Vec::new();
+        assert!(matches!(MultiReader::new(readers), Err(Error::NoInputs)));
+    }
+
+    #[test]
+    fn incompatible_layout() {
+        let readers = vec![input(0, 3, 0.01), input(3, 4, 0.5)];
+        match MultiReader::new(readers) {
+            Err(Error::IncompatibleLayout {
+                index,
+                incompatibilities,
+            }) => {
+                assert_eq!(1, index);
+                assert!(matches!(
+                    incompatibilities[..],
+                    [Incompatibility::Transforms { .. }]
+                ));
+            }
+            Err(err) => panic!("unexpected error: {err}"),
+            Ok(_) => panic!("layouts should be incompatible"),
+        }
+    }
+
+    #[test]
+    fn requantize() {
+        let readers = vec![input(0, 1, 2.), input(3, 1, 0.01)];
+        let options = MultiReaderOptions::default().requantize();
+        let mut reader = MultiReader::with_options(readers, options).unwrap();
+        let points = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(vec![0., 4.], xs(&points));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{reader::ReadOptions, Reader, Result};
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+/// A source that can't seek, e.g. stdin, a pipe, or a socket, for a [Reader::non_seekable].
+///
+/// This tracks how many bytes have been read, so it can say where it is. Seeking forwards reads
+/// and drops the bytes in between, and any other seek fails.
+#[derive(Debug)]
+pub struct NonSeekable<R: Read> {
+    read: R,
+    position: u64,
+}
+
+impl<R: Read> NonSeekable<R> {
+    /// Wraps a source that can't seek.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::NonSeekable;
+    /// let source = NonSeekable::new(std::io::stdin());
+    /// ```
+    pub fn new(read: R) -> NonSeekable<R> {
+        NonSeekable { read, position: 0 }
+    }
+
+    /// Returns the wrapped source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::NonSeekable;
+    /// let source = NonSeekable::new(std::io::empty()).into_inner();
+    /// ```
+    pub fn into_inner(self) -> R {
+        self.read
+    }
+}
+
+impl<R: Read> Read for NonSeekable<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.read.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Read> Seek for NonSeekable<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(_) => None,
+        };
+        match target {
+            Some(target) if target >= self.position => {
+                let n = target - self.position;
+                let skipped = std::io::copy(&mut self.by_ref().take(n), &mut std::io::sink())?;
+                if skipped < n {
+                    return Err(ErrorKind::UnexpectedEof.into());
+                }
+                Ok(self.position)
+            }
+            _ => Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                "the source can't seek backwards",
+            )),
+        }
+    }
+}
+
+impl<R: Read + Send> Reader<NonSeekable<R>> {
+    /// Creates a reader for a source that can't seek, which reads it once, from start to end.
+    ///
+    /// The evlrs, which come after the points, aren't read, and laz chunks are decompressed one
+    /// after the other without the chunk table. Points can be read in order, but seeking back to
+    /// an earlier point returns an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let file = std::fs::File::open("tests/data/autzen.las").unwrap();
+    /// let mut reader = Reader::non_seekable(file).unwrap();
+    /// assert_eq!(106, reader.points().count());
+    /// ```
+    pub fn non_seekable(read: R) -> Result<Reader<NonSeekable<R>>> {
+        Reader::open(NonSeekable::new(read), ReadOptions::default(), true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Point;
+    use std::fs::File;
+
+    fn points(reader: &mut Reader<impl Read + Seek + Send>) -> Vec<Point> {
+        reader.points().map(|point| point.unwrap()).collect()
+    }
+
+    #[test]
+    fn las() {
+        let file = File::open("tests/data/autzen.las").unwrap();
+        let mut reader = Reader::non_seekable(file).unwrap();
+        let mut expected = Reader::from_path("tests/data/autzen.las").unwrap();
+        assert_eq!(points(&mut expected), points(&mut reader));
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn laz() {
+        let file = File::open("tests/data/autzen.laz").unwrap();
+        let mut reader = Reader::non_seekable(file).unwrap();
+        let mut expected = Reader::from_path("tests/data/autzen.laz").unwrap();
+        assert_eq!(points(&mut expected), points(&mut reader));
+    }
+
+    #[test]
+    fn seek() {
+        let file = File::open("tests/data/autzen.las").unwrap();
+        let mut reader = Reader::non_seekable(file).unwrap();
+        reader.seek(100).unwrap();
+        assert_eq!(6, reader.points().count());
+        assert!(reader.seek(0).is_err());
+    }
+
+    #[test]
+    fn skip_forward() {
+        let mut source = NonSeekable::new([1, 2, 3, 4].as_slice());
+        assert_eq!(2, source.seek(SeekFrom::Current(2)).unwrap());
+        let mut byte = [0];
+        source.read_exact(&mut byte).unwrap();
+        assert_eq!([3], byte);
+        assert!(source.seek(SeekFrom::Start(0)).is_err());
+        assert!(source.seek(SeekFrom::Start(10)).is_err());
+    }
+}
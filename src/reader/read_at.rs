@@ -0,0 +1,319 @@
+use crate::{Reader, Result};
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+/// The default number of bytes that [ReadAtSource] fetches at a time.
+pub const DEFAULT_BLOCK_SIZE: usize = 64 * 1024;
+
+/// A source that reads bytes at an offset, e.g. a file on a server that answers http range
+/// requests.
+///
+/// Wrap one in a [ReadAtSource] to use it anywhere that takes a seekable source, like
+/// [Reader::from_read_at], `copc::Reader::new`, or [Lax::read_from](crate::index::Lax::read_from).
+/// Only the bytes those actually read are fetched: the header, the vlrs, any index, and the chunks
+/// of the points that are read.
+///
+/// # Examples
+///
+/// ```
+/// use las::{reader::ReadAt, Reader};
+///
+/// /// Pretends each read is a request to a server.
+/// struct Remote {
+///     bytes: Vec<u8>,
+/// }
+///
+/// impl ReadAt for Remote {
+///     fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+///         self.bytes.read_at(offset, buf)
+///     }
+///
+///     fn size(&self) -> std::io::Result<u64> {
+///         Ok(self.bytes.len() as u64)
+///     }
+/// }
+///
+/// let bytes = std::fs::read("tests/data/autzen.las").unwrap();
+/// let mut reader = Reader::from_read_at(Remote { bytes }).unwrap();
+/// assert_eq!(106, reader.points().count());
+/// ```
+pub trait ReadAt {
+    /// Reads bytes starting at an offset into `buf`, and returns how many were read.
+    ///
+    /// This can read fewer bytes than `buf` holds, and reads none at or past the end.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize>;
+
+    /// Returns the number of bytes in the source.
+    fn size(&self) -> std::io::Result<u64>;
+}
+
+impl ReadAt for [u8] {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(self.len());
+        let n = buf.len().min(self.len() - start);
+        buf[..n].copy_from_slice(&self[start..start + n]);
+        Ok(n)
+    }
+
+    fn size(&self) -> std::io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+}
+
+impl ReadAt for Vec<u8> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.as_slice().read_at(offset, buf)
+    }
+
+    fn size(&self) -> std::io::Result<u64> {
+        self.as_slice().size()
+    }
+}
+
+#[cfg(unix)]
+impl ReadAt for std::fs::File {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buf, offset)
+    }
+
+    fn size(&self) -> std::io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+#[cfg(windows)]
+impl ReadAt for std::fs::File {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+    }
+
+    fn size(&self) -> std::io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+/// Reads and seeks a [ReadAt] source, fetching a block of bytes at a time.
+///
+/// Reading a las header takes many small reads, so the last block is kept and small reads are
+/// served from it. Reads at least as big as a block go straight to the source.
+#[derive(Debug)]
+pub struct ReadAtSource<T: ReadAt> {
+    source: T,
+    position: u64,
+    size: Option<u64>,
+    block_size: usize,
+    block: Vec<u8>,
+    block_start: u64,
+}
+
+impl<T: ReadAt> ReadAtSource<T> {
+    /// Wraps a source, fetching [DEFAULT_BLOCK_SIZE] bytes at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::ReadAtSource;
+    /// let source = ReadAtSource::new(vec![1, 2, 3]);
+    /// ```
+    pub fn new(source: T) -> ReadAtSource<T> {
+        ReadAtSource::with_block_size(source, DEFAULT_BLOCK_SIZE)
+    }
+
+    /// Wraps a source, fetching this many bytes at a time.
+    ///
+    /// A block size of zero is the same as one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::ReadAtSource;
+    /// let source = ReadAtSource::with_block_size(vec![1, 2, 3], 1024 * 1024);
+    /// ```
+    pub fn with_block_size(source: T, block_size: usize) -> ReadAtSource<T> {
+        ReadAtSource {
+            source,
+            position: 0,
+            size: None,
+            block_size: block_size.max(1),
+            block: Vec::new(),
+            block_start: 0,
+        }
+    }
+
+    /// Returns the wrapped source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reader::ReadAtSource;
+    /// assert_eq!(vec![1, 2, 3], ReadAtSource::new(vec![1, 2, 3]).into_inner());
+    /// ```
+    pub fn into_inner(self) -> T {
+        self.source
+    }
+
+    /// Fills `buf` from the source, stopping early only at the end.
+    fn fetch(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            match self.source.read_at(offset + n as u64, &mut buf[n..]) {
+                Ok(0) => break,
+                Ok(read) => n += read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(n)
+    }
+}
+
+impl<T: ReadAt> Read for ReadAtSource<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let cached = self.block_start..self.block_start + self.block.len() as u64;
+        if !cached.contains(&self.position) {
+            if buf.len() >= self.block_size {
+                let n = self.fetch(self.position, buf)?;
+                self.position += n as u64;
+                return Ok(n);
+            }
+            let mut block = std::mem::take(&mut self.block);
+            block.resize(self.block_size, 0);
+            let n = self.fetch(self.position, &mut block)?;
+            block.truncate(n);
+            self.block = block;
+            self.block_start = self.position;
+        }
+        let start = (self.position - self.block_start) as usize;
+        let n = buf.len().min(self.block.len() - start);
+        buf[..n].copy_from_slice(&self.block[start..start + n]);
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: ReadAt> Seek for ReadAtSource<T> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => {
+                let size = match self.size {
+                    Some(size) => size,
+                    None => *self.size.insert(self.source.size()?),
+                };
+                size.checked_add_signed(n)
+            }
+            SeekFrom::Current(n) => self.position.checked_add_signed(n),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+impl<T: ReadAt + Send> Reader<ReadAtSource<T>> {
+    /// Creates a reader for a source that reads bytes at an offset.
+    ///
+    /// This wraps the source in a [ReadAtSource] with the default block size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let file = std::fs::File::open("tests/data/autzen.las").unwrap();
+    /// let mut reader = Reader::from_read_at(file).unwrap();
+    /// assert_eq!(106, reader.points().count());
+    /// ```
+    pub fn from_read_at(source: T) -> Result<Reader<ReadAtSource<T>>> {
+        Reader::new(ReadAtSource::new(source))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Point;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Counts how many times the source is read.
+    struct Counted {
+        bytes: Vec<u8>,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl ReadAt for Counted {
+        fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+            let _ = self.reads.fetch_add(1, Ordering::Relaxed);
+            self.bytes.read_at(offset, buf)
+        }
+
+        fn size(&self) -> std::io::Result<u64> {
+            self.bytes.size()
+        }
+    }
+
+    fn points(path: &str) -> (Vec<Point>, usize) {
+        let reads = Arc::new(AtomicUsize::new(0));
+        let source = Counted {
+            bytes: std::fs::read(path).unwrap(),
+            reads: Arc::clone(&reads),
+        };
+        let mut reader = Reader::from_read_at(source).unwrap();
+        let points = reader.points().map(|point| point.unwrap()).collect();
+        (points, reads.load(Ordering::Relaxed))
+    }
+
+    #[test]
+    fn las() {
+        let (points, reads) = points("tests/data/autzen.las");
+        let mut expected = Reader::from_path("tests/data/autzen.las").unwrap();
+        assert_eq!(
+            expected
+                .points()
+                .map(|point| point.unwrap())
+                .collect::<Vec<_>>(),
+            points
+        );
+        assert!(reads < 10, "{reads} reads");
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn laz() {
+        let (points, _) = points("tests/data/autzen.laz");
+        let mut expected = Reader::from_path("tests/data/autzen.laz").unwrap();
+        assert_eq!(
+            expected
+                .points()
+                .map(|point| point.unwrap())
+                .collect::<Vec<_>>(),
+            points
+        );
+    }
+
+    #[test]
+    fn read_and_seek() {
+        let mut source = ReadAtSource::with_block_size((0..100).collect::<Vec<u8>>(), 8);
+        let mut bytes = [0; 3];
+        source.read_exact(&mut bytes).unwrap();
+        assert_eq!([0, 1, 2], bytes);
+        assert_eq!(90, source.seek(SeekFrom::End(-10)).unwrap());
+        let mut bytes = [0; 20];
+        assert_eq!(10, source.read(&mut bytes).unwrap());
+        assert_eq!(99, bytes[9]);
+        assert_eq!(0, source.read(&mut bytes).unwrap());
+        let _ = source.seek(SeekFrom::Start(6)).unwrap();
+        let mut bytes = [0; 4];
+        source.read_exact(&mut bytes).unwrap();
+        assert_eq!([6, 7, 8, 9], bytes);
+        assert!(source.seek(SeekFrom::Current(-20)).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use crate::point::Dimension;
+
+/// An attribute that a reader skips over unless it's selected with
+/// [ReadOptions::dimensions](super::ReadOptions::dimensions), or, for waveforms, unless
+/// [ReadOptions::skip_waveforms](super::ReadOptions::skip_waveforms) is off.
+///
+/// The coordinates and the attributes stored in the flag bytes, like the return numbers and the
+/// classification, are always read.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Skippable {
+    Intensity,
+    ScanAngle,
+    UserData,
+    PointSourceId,
+    GpsTime,
+    Color,
+    Nir,
+    Waveform,
+    ExtraBytes,
+}
+
+impl Skippable {
+    /// Every attribute's bit.
+    pub(crate) const ALL: u16 = (1 << (Skippable::ExtraBytes as u16 + 1)) - 1;
+
+    /// Returns the attribute that a dimension is read with, or `None` if it's always read.
+    ///
+    /// The color channels are read together, and so are all of the extra bytes.
+    pub(crate) fn of(dimension: &Dimension) -> Option<Skippable> {
+        match dimension {
+            Dimension::Intensity => Some(Skippable::Intensity),
+            Dimension::ScanAngle => Some(Skippable::ScanAngle),
+            Dimension::UserData => Some(Skippable::UserData),
+            Dimension::PointSourceId => Some(Skippable::PointSourceId),
+            Dimension::GpsTime => Some(Skippable::GpsTime),
+            Dimension::Red | Dimension::Green | Dimension::Blue => Some(Skippable::Color),
+            Dimension::Nir => Some(Skippable::Nir),
+            Dimension::Extra { .. } => Some(Skippable::ExtraBytes),
+            Dimension::X
+            | Dimension::Y
+            | Dimension::Z
+            | Dimension::ReturnNumber
+            | Dimension::NumberOfReturns
+            | Dimension::ScanDirection
+            | Dimension::EdgeOfFlightLine
+            | Dimension::Classification
+            | Dimension::Synthetic
+            | Dimension::KeyPoint
+            | Dimension::Withheld
+            | Dimension::Overlap
+            | Dimension::ScannerChannel => None,
+        }
+    }
+
+    /// Returns this attribute's bit in a set of selected attributes.
+    pub(crate) fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
@@ -0,0 +1,566 @@
+use super::{point_from_record, PointReader, ReadPoints, Skippable};
+use crate::{
+    point::Format,
+    raw::{
+        self,
+        point::{Flags, ScanAngle, Waveform, OVERLAP_CLASSIFICATION_CODE},
+    },
+    utils, Color, Error, LasPointRecord, Point, Result, Transform, Vector,
+};
+use std::{
+    fs::File,
+    io::{BufReader, Seek},
+    marker::PhantomData,
+};
+
+/// A point record's fields, read straight from its bytes.
+///
+/// Given to the predicate of [Reader::points_where](super::Reader::points_where), so points can
+/// be filtered without parsing every field. Coordinates are the record's integers, before the
+/// header's transforms are applied.
+#[derive(Clone, Copy, Debug)]
+pub struct RawPointView<'a> {
+    record: &'a [u8],
+    format: &'a Format,
+}
+
+impl<'a> RawPointView<'a> {
+    /// Returns the raw bytes of the record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut len = 0;
+    /// let _ = reader.points_where(|view| {
+    ///     len = view.record().len();
+    ///     false
+    /// }).count();
+    /// assert_eq!(usize::from(reader.header().point_format().len()), len);
+    /// ```
+    pub fn record(&self) -> &'a [u8] {
+        self.record
+    }
+
+    /// Returns the raw x coordinate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points_where(|view| view.x() > 0).count();
+    /// ```
+    pub fn x(&self) -> i32 {
+        self.i32_at(0)
+    }
+
+    /// Returns the raw y coordinate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points_where(|view| view.y() > 0).count();
+    /// ```
+    pub fn y(&self) -> i32 {
+        self.i32_at(4)
+    }
+
+    /// Returns the raw z coordinate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points_where(|view| view.z() > 0).count();
+    /// ```
+    pub fn z(&self) -> i32 {
+        self.i32_at(8)
+    }
+
+    /// Returns the intensity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points_where(|view| view.intensity() > 100).count();
+    /// ```
+    pub fn intensity(&self) -> u16 {
+        u16::from_le_bytes([self.record[12], self.record[13]])
+    }
+
+    /// Returns the return number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// for point in reader.points_where(|view| view.return_number() == 1) {
+    ///     assert_eq!(1, point.unwrap().return_number);
+    /// }
+    /// ```
+    pub fn return_number(&self) -> u8 {
+        if self.format.is_extended {
+            self.record[14] & 0b1111
+        } else {
+            self.record[14] & 0b111
+        }
+    }
+
+    /// Returns the number of returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points_where(|view| view.number_of_returns() > 1).count();
+    /// ```
+    pub fn number_of_returns(&self) -> u8 {
+        if self.format.is_extended {
+            self.record[14] >> 4
+        } else {
+            (self.record[14] >> 3) & 0b111
+        }
+    }
+
+    /// Returns the classification code.
+    ///
+    /// Legacy overlap points have the overlap code, twelve, which isn't a
+    /// [Classification](crate::point::Classification), so use [RawPointView::is_overlap] for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Classification, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// for point in reader.points_where(|view| view.classification() == 2) {
+    ///     assert_eq!(Classification::Ground, point.unwrap().classification);
+    /// }
+    /// ```
+    pub fn classification(&self) -> u8 {
+        if self.format.is_extended {
+            self.record[16]
+        } else {
+            self.record[15] & 0b1_1111
+        }
+    }
+
+    /// Returns whether this point is withheld.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points_where(|view| !view.is_withheld()).count();
+    /// ```
+    pub fn is_withheld(&self) -> bool {
+        if self.format.is_extended {
+            self.record[15] & 0b100 != 0
+        } else {
+            self.record[15] & 0b1000_0000 != 0
+        }
+    }
+
+    /// Returns whether this point is overlap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points_where(|view| !view.is_overlap()).count();
+    /// ```
+    pub fn is_overlap(&self) -> bool {
+        if self.format.is_extended {
+            self.record[15] & 0b1000 != 0
+        } else {
+            self.classification() == OVERLAP_CLASSIFICATION_CODE
+        }
+    }
+
+    /// Returns the scan angle, in degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points_where(|view| view.scan_angle().abs() < 10.).count();
+    /// ```
+    pub fn scan_angle(&self) -> f32 {
+        self.raw_scan_angle().into()
+    }
+
+    /// Returns the user data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points_where(|view| view.user_data() == 0).count();
+    /// ```
+    pub fn user_data(&self) -> u8 {
+        self.record[17]
+    }
+
+    /// Returns the point source id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points_where(|view| view.point_source_id() == 7328).count();
+    /// ```
+    pub fn point_source_id(&self) -> u16 {
+        let i = if self.format.is_extended { 20 } else { 18 };
+        u16::from_le_bytes([self.record[i], self.record[i + 1]])
+    }
+
+    /// Returns the gps time, or `None` if the point format doesn't have one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader
+    ///     .points_where(|view| view.gps_time().is_some_and(|gps_time| gps_time > 0.))
+    ///     .count();
+    /// ```
+    pub fn gps_time(&self) -> Option<f64> {
+        if !self.format.has_gps_time {
+            return None;
+        }
+        let i = if self.format.is_extended { 22 } else { 20 };
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.record[i..i + 8]);
+        Some(f64::from_le_bytes(bytes))
+    }
+
+    /// Returns the color, or `None` if the point format doesn't have one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(0, reader.points_where(|view| view.color().is_some()).count());
+    /// ```
+    pub fn color(&self) -> Option<Color> {
+        self.format.has_color.then(|| {
+            let i = self.color_offset();
+            Color::new(self.u16_at(i), self.u16_at(i + 2), self.u16_at(i + 4))
+        })
+    }
+
+    /// Returns the near infrared value, or `None` if the point format doesn't have one or it's
+    /// zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(0, reader.points_where(|view| view.nir().is_some()).count());
+    /// ```
+    pub fn nir(&self) -> Option<u16> {
+        if self.format.has_nir {
+            utils::some_or_none_if_zero(self.u16_at(self.nir_offset()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the waveform packet information, or `None` if the point format doesn't have any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(0, reader.points_where(|view| view.waveform().is_some()).count());
+    /// ```
+    pub fn waveform(&self) -> Option<Waveform> {
+        if !self.format.has_waveform {
+            return None;
+        }
+        let i = self.nir_offset() + if self.format.has_nir { 2 } else { 0 };
+        Waveform::from_bytes(&self.record[i..])
+    }
+
+    /// Returns the extra bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(0, reader.points_where(|view| !view.extra_bytes().is_empty()).count());
+    /// ```
+    pub fn extra_bytes(&self) -> &'a [u8] {
+        &self.record[self.record.len() - usize::from(self.format.extra_bytes)..]
+    }
+
+    fn raw_scan_angle(&self) -> ScanAngle {
+        if self.format.is_extended {
+            ScanAngle::Scaled(self.u16_at(18) as i16)
+        } else {
+            ScanAngle::Rank(self.record[16] as i8)
+        }
+    }
+
+    fn flags(&self) -> Flags {
+        if self.format.is_extended {
+            Flags::ThreeByte(self.record[14], self.record[15], self.record[16])
+        } else {
+            Flags::TwoByte(self.record[14], self.record[15])
+        }
+    }
+
+    fn color_offset(&self) -> usize {
+        let gps_time = if self.format.has_gps_time { 8 } else { 0 };
+        if self.format.is_extended {
+            30
+        } else {
+            20 + gps_time
+        }
+    }
+
+    fn nir_offset(&self) -> usize {
+        self.color_offset() + if self.format.has_color { 6 } else { 0 }
+    }
+
+    fn u16_at(&self, i: usize) -> u16 {
+        u16::from_le_bytes([self.record[i], self.record[i + 1]])
+    }
+
+    fn i32_at(&self, i: usize) -> i32 {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&self.record[i..i + 4]);
+        i32::from_le_bytes(bytes)
+    }
+}
+
+/// Overwrites `point` with a point record, reading only the selected dimensions.
+///
+/// The dimensions that aren't selected are left at their defaults.
+pub(super) fn read_selected(
+    record: &[u8],
+    format: &Format,
+    transforms: &Vector<Transform>,
+    dimensions: u16,
+    point: &mut Point,
+) -> Result<()> {
+    if let Some(n) = format.unknown {
+        return Err(Error::UnknownPointFormat(n));
+    }
+    let view = RawPointView { record, format };
+    let selected = |skippable: Skippable| dimensions & skippable.bit() != 0;
+    let mut extra_bytes = std::mem::take(&mut point.extra_bytes);
+    if selected(Skippable::ExtraBytes) {
+        extra_bytes.set_len(usize::from(format.extra_bytes));
+        extra_bytes.copy_from_slice(view.extra_bytes());
+    } else {
+        extra_bytes.set_len(0);
+    }
+    let raw_point = raw::Point {
+        x: view.x(),
+        y: view.y(),
+        z: view.z(),
+        intensity: if selected(Skippable::Intensity) {
+            view.intensity()
+        } else {
+            0
+        },
+        flags: view.flags(),
+        scan_angle: if selected(Skippable::ScanAngle) {
+            view.raw_scan_angle()
+        } else {
+            ScanAngle::default()
+        },
+        user_data: if selected(Skippable::UserData) {
+            view.user_data()
+        } else {
+            0
+        },
+        point_source_id: if selected(Skippable::PointSourceId) {
+            view.point_source_id()
+        } else {
+            0
+        },
+        gps_time: selected(Skippable::GpsTime)
+            .then(|| view.gps_time())
+            .flatten(),
+        color: selected(Skippable::Color).then(|| view.color()).flatten(),
+        waveform: selected(Skippable::Waveform)
+            .then(|| view.waveform())
+            .flatten(),
+        nir: selected(Skippable::Nir).then(|| view.nir()).flatten(),
+        extra_bytes,
+    };
+    *point = Point::new(raw_point, transforms);
+    Ok(())
+}
+
+/// An iterator over the points in a `Reader` whose records pass a predicate.
+///
+/// This struct is created by [Reader::points_where](super::Reader::points_where).
+#[allow(missing_debug_implementations)]
+pub struct FilteredPointIterator<'a, F, R: std::io::Read + Seek + Send = BufReader<File>> {
+    pub(super) point_reader: &'a mut PointReader<R>,
+    pub(super) predicate: F,
+    pub(super) format: Format,
+    pub(super) transforms: Vector<Transform>,
+    pub(super) record: Vec<u8>,
+}
+
+impl<F, R> Iterator for FilteredPointIterator<'_, F, R>
+where
+    F: FnMut(RawPointView<'_>) -> bool,
+    R: std::io::Read + Seek + Send,
+{
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.point_reader.read_record(&mut self.record) {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+            if let Some(n) = self.format.unknown {
+                return Some(Err(Error::UnknownPointFormat(n)));
+            }
+            let view = RawPointView {
+                record: &self.record,
+                format: &self.format,
+            };
+            if (self.predicate)(view) {
+                return Some(point_from_record(
+                    &self.record,
+                    &self.format,
+                    &self.transforms,
+                ));
+            }
+        }
+    }
+}
+
+/// An iterator over the points in a `Reader`, decoded into your own point type.
+///
+/// This struct is created by [Reader::points_as](super::Reader::points_as).
+#[allow(missing_debug_implementations)]
+pub struct RecordIterator<'a, T, R: std::io::Read + Seek + Send = BufReader<File>> {
+    pub(super) point_reader: &'a mut PointReader<R>,
+    pub(super) record: Vec<u8>,
+    pub(super) record_type: PhantomData<T>,
+}
+
+impl<T, R> Iterator for RecordIterator<'_, T, R>
+where
+    T: LasPointRecord,
+    R: std::io::Read + Seek + Send,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.point_reader.read_record(&mut self.record) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(err) => return Some(Err(err)),
+        }
+        let header = self.point_reader.header();
+        let format = header.point_format();
+        if let Some(n) = format.unknown {
+            return Some(Err(Error::UnknownPointFormat(n)));
+        }
+        let view = RawPointView {
+            record: &self.record,
+            format,
+        };
+        Some(T::decode(view, header))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Classification, synthetic::fixture, Builder, GpsTimeType, Reader};
+    use std::io::Cursor;
+
+    fn reader(format: u8, points: &[Point]) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.gps_time_type = GpsTimeType::Standard;
+        fixture::reader(fixture::write(builder, points))
+    }
+
+    #[test]
+    fn fields() {
+        let point = Point {
+            x: 1.,
+            y: -2.,
+            z: 3.,
+            intensity: 4,
+            return_number: 2,
+            number_of_returns: 3,
+            classification: Classification::Building,
+            is_withheld: true,
+            point_source_id: 5,
+            gps_time: Some(6.),
+            ..Default::default()
+        };
+        for format in [1, 6] {
+            let mut reader = reader(format, std::slice::from_ref(&point));
+            let mut checked = false;
+            let points: Vec<Point> = reader
+                .points_where(|view| {
+                    assert_eq!((1000, -2000, 3000), (view.x(), view.y(), view.z()));
+                    assert_eq!(4, view.intensity());
+                    assert_eq!(2, view.return_number());
+                    assert_eq!(3, view.number_of_returns());
+                    assert_eq!(6, view.classification());
+                    assert!(view.is_withheld());
+                    assert!(!view.is_overlap());
+                    assert_eq!(5, view.point_source_id());
+                    assert_eq!(Some(6.), view.gps_time());
+                    checked = true;
+                    true
+                })
+                .map(|point| point.unwrap())
+                .collect();
+            assert!(checked);
+            assert_eq!(vec![point.clone()], points);
+        }
+    }
+
+    #[test]
+    fn filter() {
+        let points: Vec<Point> = (0..10)
+            .map(|i| Point {
+                x: f64::from(i),
+                is_overlap: i % 3 == 0,
+                gps_time: Some(0.),
+                ..Default::default()
+            })
+            .collect();
+        for format in [1, 6] {
+            let xs: Vec<f64> = reader(format, &points)
+                .points_where(|view| view.is_overlap())
+                .map(|point| point.unwrap().x)
+                .collect();
+            assert_eq!(vec![0., 3., 6., 9.], xs);
+        }
+    }
+}
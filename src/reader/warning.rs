@@ -0,0 +1,71 @@
+use std::fmt;
+
+/// A problem with a file that a lenient reader repaired or ignored, see
+/// [ReadOptions::lenient](crate::reader::ReadOptions::lenient).
+///
+/// A reader's warnings are listed by [Reader::warnings](crate::Reader::warnings).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// A header string has bytes that aren't ascii, which are replaced with `?`.
+    NotAscii {
+        /// The header field, e.g. `system_identifier`.
+        field: &'static str,
+
+        /// The string as it's stored, with the bytes that aren't utf-8 replaced.
+        original: String,
+    },
+
+    /// An axis has a scale of zero, which is replaced with the default scale.
+    ZeroScale {
+        /// The axis, `x`, `y`, or `z`.
+        axis: char,
+
+        /// The scale that's used instead.
+        scale: f64,
+    },
+
+    /// The header has more points than fit in the file, so the number of points is cut to what
+    /// fits.
+    TooManyPoints {
+        /// The number of points in the header.
+        declared: u64,
+
+        /// The number of whole points in the file.
+        fit: u64,
+    },
+
+    /// The creation date isn't a real day, so the header has no date.
+    InvalidDate {
+        /// The year.
+        year: u16,
+
+        /// The day of the year.
+        day_of_year: u16,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::NotAscii { field, original } => {
+                write!(f, "the {field} {original:?} is not ascii")
+            }
+            Warning::ZeroScale { axis, scale } => {
+                write!(f, "the {axis} scale is zero, using {scale}")
+            }
+            Warning::TooManyPoints { declared, fit } => {
+                write!(
+                    f,
+                    "the header has {declared} points, but only {fit} fit in the file"
+                )
+            }
+            Warning::InvalidDate { year, day_of_year } => {
+                write!(
+                    f,
+                    "day {day_of_year} of {year} is not a valid creation date"
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{raw, reader::RawPointView, Header, Result};
+
+/// A point type of your own that's read from and written to point records directly.
+///
+/// [Reader::points_as](crate::Reader::points_as) decodes each record into this type, and
+/// [Writer::write_record](crate::Writer::write_record) encodes it, so there's no [Point](crate::Point)
+/// in between. Only the fields the type has are read, so a type with a few fields reads faster
+/// than a full point.
+///
+/// # Examples
+///
+/// ```
+/// use las::{
+///     raw::{self, point::Flags},
+///     reader::RawPointView,
+///     Builder, Header, LasPointRecord, Reader, Result, Writer,
+/// };
+/// use std::io::Cursor;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct MyPoint {
+///     x: f64,
+///     y: f64,
+///     z: f64,
+///     class: u8,
+/// }
+///
+/// impl LasPointRecord for MyPoint {
+///     fn decode(view: RawPointView<'_>, header: &Header) -> Result<MyPoint> {
+///         let transforms = header.effective_transforms();
+///         Ok(MyPoint {
+///             x: transforms.x.direct(view.x()),
+///             y: transforms.y.direct(view.y()),
+///             z: transforms.z.direct(view.z()),
+///             class: view.classification(),
+///         })
+///     }
+///
+///     fn encode(&self, header: &Header) -> Result<raw::Point> {
+///         let transforms = header.transforms();
+///         Ok(raw::Point {
+///             x: transforms.x.inverse(self.x)?,
+///             y: transforms.y.inverse(self.y)?,
+///             z: transforms.z.inverse(self.z)?,
+///             flags: Flags::ThreeByte(0b0001_0001, 0, self.class),
+///             ..Default::default()
+///         })
+///     }
+/// }
+///
+/// let point = MyPoint { x: 1., y: 2., z: 3., class: 2 };
+/// let header = Builder::from((1, 4)).into_header().unwrap();
+/// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+/// writer.write_record(&point).unwrap();
+///
+/// let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+/// let points = reader.points_as::<MyPoint>().collect::<Result<Vec<_>>>().unwrap();
+/// assert_eq!(vec![point], points);
+/// ```
+pub trait LasPointRecord: Sized {
+    /// Decodes a point record.
+    ///
+    /// The view's coordinates are the record's integers, so apply the header's
+    /// [effective transforms](Header::effective_transforms) to get the real ones.
+    fn decode(view: RawPointView<'_>, header: &Header) -> Result<Self>;
+
+    /// Encodes this point as a raw point, for a file with this header.
+    ///
+    /// Fields that the header's point format doesn't have are left out. The raw point must have
+    /// as many extra bytes as the point format.
+    fn encode(&self, header: &Header) -> Result<raw::Point>;
+}
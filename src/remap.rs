@@ -0,0 +1,275 @@
+use crate::{
+    header::SystemIdentifier, raw, raw::point::Flags, Error, Point, Reader, Result, Writer,
+};
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, Write},
+};
+
+/// A table of what each classification value becomes, for [remap_classifications].
+///
+/// Every value maps to itself until it's [set](ClassificationMap::set).
+///
+/// # Examples
+///
+/// ```
+/// use las::ClassificationMap;
+///
+/// let mut map = ClassificationMap::default();
+/// map.set(19, 17).set(20, 18);
+/// assert_eq!(17, map.get(19));
+/// assert_eq!(2, map.get(2));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClassificationMap {
+    table: [u8; 256],
+}
+
+/// What [remap_classifications] did to the points.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RemapReport {
+    /// The number of points written.
+    pub points: u64,
+
+    /// The number of points whose classification changed.
+    pub points_changed: u64,
+
+    /// The number of points that changed, by their source classification.
+    ///
+    /// Classes that no point changed from aren't in the map.
+    pub changed_by_class: HashMap<u8, u64>,
+}
+
+impl ClassificationMap {
+    /// Maps one classification value to another.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ClassificationMap;
+    /// let mut map = ClassificationMap::default();
+    /// map.set(64, 2);
+    /// assert_eq!(2, map.get(64));
+    /// ```
+    pub fn set(&mut self, from: u8, to: u8) -> &mut ClassificationMap {
+        self.table[usize::from(from)] = to;
+        self
+    }
+
+    /// Returns what a classification value maps to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ClassificationMap;
+    /// assert_eq!(7, ClassificationMap::default().get(7));
+    /// ```
+    pub fn get(&self, from: u8) -> u8 {
+        self.table[usize::from(from)]
+    }
+
+    /// Returns true if every value maps to itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ClassificationMap;
+    /// let mut map = ClassificationMap::default();
+    /// assert!(map.is_identity());
+    /// map.set(1, 2);
+    /// assert!(!map.is_identity());
+    /// ```
+    pub fn is_identity(&self) -> bool {
+        *self == ClassificationMap::default()
+    }
+}
+
+impl Default for ClassificationMap {
+    fn default() -> ClassificationMap {
+        ClassificationMap {
+            table: std::array::from_fn(|i| i as u8),
+        }
+    }
+}
+
+impl From<[u8; 256]> for ClassificationMap {
+    fn from(table: [u8; 256]) -> ClassificationMap {
+        ClassificationMap { table }
+    }
+}
+
+/// Copies a reader's points to a destination, mapping each point's classification through a
+/// table.
+///
+/// The table is applied to the stored value: the five classification bits of the legacy point
+/// formats, or the whole classification byte of the extended ones. Only that value changes, the
+/// rest of the points and the header, vlrs included, are kept. A value mapped to the overlap class
+/// (12) is read back as an unclassified overlap point, like any other overlap point.
+///
+/// Legacy formats can only hold classes up to 31. If the map sends one of those to a larger
+/// value, this returns [Error::ClassificationNotRepresentable] before writing anything.
+///
+/// # Examples
+///
+/// ```
+/// use las::{ClassificationMap, Reader};
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut map = ClassificationMap::default();
+/// map.set(2, 8);
+/// let report = las::remap_classifications(reader, Cursor::new(Vec::new()), &map).unwrap();
+/// assert_eq!(106, report.points);
+/// ```
+pub fn remap_classifications<R, W>(
+    mut reader: Reader<R>,
+    dest: W,
+    map: &ClassificationMap,
+) -> Result<RemapReport>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let format = *reader.header().point_format();
+    if !format.is_extended
+        && let Some(from) = (0..32).find(|&from| map.get(from) > 31)
+    {
+        return Err(Error::ClassificationNotRepresentable {
+            from,
+            to: map.get(from),
+            format,
+        });
+    }
+
+    let transforms = *reader.header().effective_transforms();
+    let mut writer = Writer::new(
+        dest,
+        reader.header().to_derived(&SystemIdentifier::Modification),
+    )?;
+    let mut report = RemapReport::default();
+    let mut record = vec![0; usize::from(format.len())];
+    while reader.read_record(&mut record)? {
+        let mut raw_point = raw::Point::read_from(record.as_slice(), &format)?;
+        let from = match raw_point.flags {
+            Flags::TwoByte(_, ref mut b) => {
+                let from = *b & 0b0001_1111;
+                *b = (*b & 0b1110_0000) | map.get(from);
+                from
+            }
+            Flags::ThreeByte(_, _, ref mut c) => {
+                let from = *c;
+                *c = map.get(from);
+                from
+            }
+        };
+        if map.get(from) != from {
+            report.points_changed += 1;
+            *report.changed_by_class.entry(from).or_insert(0) += 1;
+        }
+        writer.write_point(Point::new(raw_point, &transforms))?;
+        report.points += 1;
+    }
+    let _ = writer.close()?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        point::{Classification, Format},
+        synthetic::fixture,
+        Builder, GpsTimeType,
+    };
+    use std::{fs::File, io::Cursor};
+
+    const CLASSES: [u8; 6] = [1, 2, 2, 19, 20, 20];
+
+    fn source(format: u8) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.gps_time_type = GpsTimeType::Standard;
+        let points = CLASSES.iter().enumerate().map(|(i, &class)| Point {
+            x: i as f64,
+            classification: Classification::new(class).unwrap(),
+            is_synthetic: i % 2 == 0,
+            gps_time: Some(i as f64),
+            ..Default::default()
+        });
+        fixture::reader(fixture::write(builder, points))
+    }
+
+    fn run(name: &str, format: u8, map: &ClassificationMap) -> Result<(RemapReport, Vec<Point>)> {
+        let path = std::env::temp_dir().join(format!("las-rs-remap-{name}.las"));
+        let report = remap_classifications(source(format), File::create(&path)?, map);
+        let points = report
+            .is_ok()
+            .then(|| Reader::from_path(&path).unwrap().into_points().unwrap());
+        std::fs::remove_file(&path)?;
+        Ok((report?, points.unwrap()))
+    }
+
+    fn vendor_map() -> ClassificationMap {
+        let mut map = ClassificationMap::default();
+        let _ = map.set(19, 17).set(20, 18).set(5, 6);
+        map
+    }
+
+    fn check(name: &str, format: u8, map: &ClassificationMap, expected: [u8; 6]) {
+        let (report, points) = run(name, format, map).unwrap();
+        assert_eq!(6, report.points);
+        assert_eq!(3, report.points_changed);
+        assert_eq!(HashMap::from([(19, 1), (20, 2)]), report.changed_by_class);
+        for (i, (point, class)) in points.iter().zip(expected).enumerate() {
+            assert_eq!(Classification::new(class).unwrap(), point.classification);
+            assert_eq!(i as f64, point.x);
+            assert_eq!(i % 2 == 0, point.is_synthetic);
+        }
+    }
+
+    #[test]
+    fn legacy() {
+        check("legacy", 1, &vendor_map(), [1, 2, 2, 17, 18, 18]);
+    }
+
+    #[test]
+    fn extended() {
+        let mut map = vendor_map();
+        let _ = map.set(20, 64);
+        check("extended", 6, &map, [1, 2, 2, 17, 64, 64]);
+    }
+
+    #[test]
+    fn unrepresentable_on_legacy() {
+        let mut map = vendor_map();
+        let _ = map.set(20, 64);
+        let path = std::env::temp_dir().join("las-rs-remap-unrepresentable.las");
+        let err = remap_classifications(source(1), File::create(&path).unwrap(), &map).unwrap_err();
+        assert!(std::fs::read(&path).unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            err,
+            Error::ClassificationNotRepresentable {
+                from: 20,
+                to: 64,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn unreachable_source_value_on_legacy() {
+        // Legacy points can't have class 40, so where it maps to doesn't matter.
+        let mut map = vendor_map();
+        let _ = map.set(40, 200);
+        check("unreachable", 1, &map, [1, 2, 2, 17, 18, 18]);
+    }
+
+    #[test]
+    fn identity() {
+        assert!(ClassificationMap::default().is_identity());
+        let (report, points) = run("identity", 1, &ClassificationMap::default()).unwrap();
+        assert_eq!(0, report.points_changed);
+        assert_eq!(source(1).into_points().unwrap(), points);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{
+    header::SystemIdentifier, raw, Error, Point, Reader, Result, Transform, Vector, Writer,
+};
+use std::io::{Read, Seek, Write};
+
+/// What [requantize] did to the coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RequantizeReport {
+    /// The number of points written.
+    pub points: u64,
+
+    /// The number of points with a coordinate that moved by more than half of the new scale.
+    ///
+    /// Rounding to the nearest stored integer never moves a coordinate that far, so this is only
+    /// more than zero through floating point error, when the old and new scales aren't whole
+    /// multiples of each other.
+    pub points_shifted: u64,
+
+    /// The furthest any coordinate moved, per axis.
+    pub max_displacement: Vector<f64>,
+}
+
+/// How the stored integers along one axis are mapped to the new transform.
+#[derive(Clone, Copy, Debug)]
+enum Axis {
+    /// The new scale divides the old one: `new = old * factor + shift`, which is exact.
+    Finer { factor: i64, shift: i64 },
+
+    /// The old scale divides the new one: `new = round((old + shift) / divisor)`, rounded once.
+    Coarser { divisor: i64, shift: i64 },
+
+    /// The scales aren't whole multiples, so the coordinate goes through a float.
+    Float,
+}
+
+/// Copies a reader's points to a destination with new scales and offsets.
+///
+/// The stored integers are mapped straight to the new transforms. If the old and new scales are
+/// whole multiples of each other, and the offsets differ by a whole number of units, there's no
+/// float rounding at all: a change to a finer scale is exact, and a change to a coarser one rounds
+/// once, half away from zero. Otherwise, each coordinate is computed and rounded again.
+///
+/// Everything else about the points and the header, vlrs included, is kept. Returns an error
+/// before writing anything if the new transforms can't represent the source's bounds.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Reader, Transform, Vector};
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let transform = Transform { scale: 0.01, offset: 0. };
+/// let transforms = Vector { x: transform, y: transform, z: transform };
+/// let report = las::requantize(reader, Cursor::new(Vec::new()), transforms).unwrap();
+/// assert_eq!(106, report.points);
+/// assert!(report.max_displacement.x <= 0.005 + 1e-9);
+/// ```
+pub fn requantize<R, W>(
+    mut reader: Reader<R>,
+    dest: W,
+    new_transforms: Vector<Transform>,
+) -> Result<RequantizeReport>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let old_transforms = *reader.header().effective_transforms();
+    if reader.header().number_of_points() > 0 {
+        let bounds = reader.header().bounds();
+        for (axis, transform, min, max) in [
+            ('x', new_transforms.x, bounds.min.x, bounds.max.x),
+            ('y', new_transforms.y, bounds.min.y, bounds.max.y),
+            ('z', new_transforms.z, bounds.min.z, bounds.max.z),
+        ] {
+            for value in [min, max] {
+                if transform.inverse(value).is_err() {
+                    return Err(Error::BoundsNotRepresentable {
+                        axis,
+                        value,
+                        transform,
+                    });
+                }
+            }
+        }
+    }
+    let axes = Vector {
+        x: Axis::new(old_transforms.x, new_transforms.x),
+        y: Axis::new(old_transforms.y, new_transforms.y),
+        z: Axis::new(old_transforms.z, new_transforms.z),
+    };
+
+    let format = *reader.header().point_format();
+    let mut builder = reader
+        .header()
+        .to_derived(&SystemIdentifier::Transformation)
+        .into_builder();
+    builder.transforms = new_transforms;
+    let mut writer = Writer::new(dest, builder.into_header()?)?;
+    let mut report = RequantizeReport::default();
+    let mut record = vec![0; usize::from(format.len())];
+    while reader.read_record(&mut record)? {
+        let raw_point = raw::Point::read_from(record.as_slice(), &format)?;
+        let (x, y, z) = (raw_point.x, raw_point.y, raw_point.z);
+        let mut point = Point::new(raw_point, &old_transforms);
+        let mut shifted = false;
+        for (coordinate, stored, old, new, axis, max_displacement) in [
+            (
+                &mut point.x,
+                x,
+                old_transforms.x,
+                new_transforms.x,
+                axes.x,
+                &mut report.max_displacement.x,
+            ),
+            (
+                &mut point.y,
+                y,
+                old_transforms.y,
+                new_transforms.y,
+                axes.y,
+                &mut report.max_displacement.y,
+            ),
+            (
+                &mut point.z,
+                z,
+                old_transforms.z,
+                new_transforms.z,
+                axes.z,
+                &mut report.max_displacement.z,
+            ),
+        ] {
+            let value = new.direct(axis.map(stored, old, new)?);
+            let displacement = (value - old.direct(stored)).abs();
+            *max_displacement = max_displacement.max(displacement);
+            shifted |= displacement > 0.5 * new.scale.abs() * (1. + 1e-9);
+            *coordinate = value;
+        }
+        writer.write_point(point)?;
+        report.points += 1;
+        report.points_shifted += u64::from(shifted);
+    }
+    let _ = writer.close()?;
+    Ok(report)
+}
+
+impl Axis {
+    fn new(old: Transform, new: Transform) -> Axis {
+        if let Some(factor) = whole(old.scale / new.scale).filter(|&factor| factor > 0)
+            && let Some(shift) = whole((old.offset - new.offset) / new.scale)
+        {
+            Axis::Finer { factor, shift }
+        } else if let Some(divisor) = whole(new.scale / old.scale).filter(|&divisor| divisor > 0)
+            && let Some(shift) = whole((old.offset - new.offset) / old.scale)
+        {
+            Axis::Coarser { divisor, shift }
+        } else {
+            Axis::Float
+        }
+    }
+
+    /// Maps an integer stored with the old transform to one stored with the new transform.
+    fn map(self, stored: i32, old: Transform, new: Transform) -> Result<i32> {
+        let n = i64::from(stored);
+        let mapped = match self {
+            Axis::Finer { factor, shift } => {
+                n.checked_mul(factor).and_then(|n| n.checked_add(shift))
+            }
+            Axis::Coarser { divisor, shift } => {
+                n.checked_add(shift).map(|n| divide_rounded(n, divisor))
+            }
+            Axis::Float => return new.inverse(old.direct(stored)),
+        };
+        mapped
+            .and_then(|n| i32::try_from(n).ok())
+            .ok_or(Error::InvalidInverseTransform {
+                n: old.direct(stored),
+                transform: new,
+            })
+    }
+}
+
+/// Returns `n` as an integer, if it's a whole number that's small enough to store.
+fn whole(n: f64) -> Option<i64> {
+    let rounded = n.round();
+    ((n - rounded).abs() <= 1e-9 * rounded.abs().max(1.) && rounded.abs() < f64::from(i32::MAX))
+        .then_some(rounded as i64)
+}
+
+/// Divides, rounding half way cases away from zero.
+fn divide_rounded(n: i64, divisor: i64) -> i64 {
+    let quotient = n / divisor;
+    if 2 * (n % divisor).abs() >= divisor {
+        quotient + n.signum()
+    } else {
+        quotient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, synthetic::fixture, Builder};
+    use std::{fs::File, io::Cursor};
+
+    fn transforms(scale: f64, offset: f64) -> Vector<Transform> {
+        let transform = Transform { scale, offset };
+        Vector {
+            x: transform,
+            y: transform,
+            z: transform,
+        }
+    }
+
+    fn source(xs: &[f64]) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(1).unwrap();
+        builder.transforms = transforms(0.001, 0.);
+        let points = xs.iter().enumerate().map(|(i, &x)| Point {
+            x,
+            y: 2.,
+            z: -3.,
+            intensity: i as u16,
+            gps_time: Some(i as f64),
+            ..Default::default()
+        });
+        fixture::reader(fixture::write(builder, points))
+    }
+
+    fn run(name: &str, xs: &[f64], new: Vector<Transform>) -> (RequantizeReport, Vec<Point>) {
+        let path = std::env::temp_dir().join(format!("las-rs-requantize-{name}.las"));
+        let report = requantize(source(xs), File::create(&path).unwrap(), new).unwrap();
+        let reader = Reader::from_path(&path).unwrap();
+        assert_eq!(new, *reader.header().transforms());
+        let points = reader.into_points().unwrap();
+        std::fs::remove_file(path).unwrap();
+        (report, points)
+    }
+
+    #[test]
+    fn lossless() {
+        let xs = [1.234, 123.456, -5.001, 0.];
+        let new = transforms(0.001, 100.);
+        let (report, points) = run("lossless", &xs, new);
+        assert_eq!(4, report.points);
+        assert_eq!(0, report.points_shifted);
+        assert!(report.max_displacement.x < 1e-9);
+        assert!(report.max_displacement.z < 1e-9);
+        let original = source(&xs).into_points().unwrap();
+        for (expected, point) in original.into_iter().zip(points) {
+            assert!((expected.x - point.x).abs() < 1e-9);
+            assert_eq!(expected.intensity, point.intensity);
+            assert_eq!(expected.gps_time, point.gps_time);
+            let raw = point.into_raw(&new).unwrap();
+            assert_eq!(new.x.inverse(expected.x).unwrap(), raw.x);
+        }
+    }
+
+    #[test]
+    fn lossy() {
+        let (report, points) = run("lossy", &[1.234, 1.005, -1.005, 2.], transforms(0.01, 0.));
+        assert_eq!(0, report.points_shifted);
+        assert!((report.max_displacement.x - 0.005).abs() < 1e-9);
+        assert!(report.max_displacement.y < 1e-9);
+        let xs: Vec<f64> = points.into_iter().map(|point| point.x).collect();
+        // 1.005 is stored as 1005, so it's rounded half way up, without a float in between.
+        assert_eq!(vec![1.23, 1.01, -1.01, 2.], xs);
+    }
+
+    #[test]
+    fn not_whole_multiples() {
+        let (report, points) = run("float", &[1.234, 2.], transforms(0.003, 0.));
+        assert_eq!(0, report.points_shifted);
+        assert!(report.max_displacement.x <= 0.0015 + 1e-9);
+        assert!((points[0].x - 1.233).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bounds_not_representable() {
+        let err = requantize(
+            source(&[1000.]),
+            Cursor::new(Vec::new()),
+            transforms(1e-9, 0.),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::BoundsNotRepresentable { axis: 'x', value, .. } if value == 1000.
+        ));
+    }
+
+    #[test]
+    fn divide_rounded_half_away_from_zero() {
+        assert_eq!(101, divide_rounded(1005, 10));
+        assert_eq!(-101, divide_rounded(-1005, 10));
+        assert_eq!(100, divide_rounded(1004, 10));
+        assert_eq!(-100, divide_rounded(-1004, 10));
+    }
+}
@@ -0,0 +1,88 @@
+// The generator is public through the strategy module, which needs the `test-support` feature.
+#![cfg_attr(not(any(test, feature = "test-support")), allow(unreachable_pub))]
+
+/// A small, seeded, non-cryptographic random number generator (splitmix64).
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "test-support")] {
+/// use las::strategy::Rng;
+/// assert_eq!(Rng::new(1).next_u64(), Rng::new(1).next_u64());
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+// Most of the helpers are only used by the strategy module, too.
+#[cfg_attr(not(any(test, feature = "test-support")), allow(dead_code))]
+impl Rng {
+    /// Creates a generator from a seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "test-support")] {
+    /// use las::strategy::Rng;
+    /// let rng = Rng::new(42);
+    /// # }
+    /// ```
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    /// Returns the next random number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "test-support")] {
+    /// use las::strategy::Rng;
+    /// let mut rng = Rng::new(42);
+    /// assert_ne!(rng.next_u64(), rng.next_u64());
+    /// # }
+    /// ```
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `min..=max`.
+    pub(crate) fn between(&mut self, min: i64, max: i64) -> i64 {
+        let span = (max - min) as u64 + 1;
+        min + (self.next_u64() % span) as i64
+    }
+
+    pub(crate) fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    pub(crate) fn u8(&mut self) -> u8 {
+        self.next_u64() as u8
+    }
+
+    pub(crate) fn u16(&mut self) -> u16 {
+        self.next_u64() as u16
+    }
+
+    /// Returns a float with a short binary expansion, so it survives any round trip exactly.
+    pub(crate) fn f32(&mut self) -> f32 {
+        self.between(-(1 << 20), 1 << 20) as f32 / 1024.
+    }
+
+    pub(crate) fn lowercase(&mut self, max_len: i64) -> String {
+        (0..self.between(0, max_len))
+            .map(|_| char::from(b'a' + self.between(0, 25) as u8))
+            .collect()
+    }
+
+    /// Returns a float in `0..1`.
+    pub(crate) fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
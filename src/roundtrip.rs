@@ -0,0 +1,178 @@
+// Only public with the `test-support` feature.
+#![cfg_attr(not(feature = "test-support"), allow(dead_code, unreachable_pub))]
+
+use crate::{
+    compare::{self, FieldDifference},
+    Builder, CompareMode, ComparisonReport, Header, Point, PointDifference, Reader, Result, Writer,
+};
+use std::io::Cursor;
+
+/// What [roundtrip] read back, and how it differs from what was written.
+#[derive(Clone, Debug)]
+pub struct RoundtripOutcome {
+    /// The bytes of the file.
+    pub bytes: Vec<u8>,
+
+    /// The header as the writer wrote it, after the last point, with its bounds rounded out to
+    /// the transforms like they are in the file.
+    pub written_header: Header,
+
+    /// The header that was read back.
+    pub header: Header,
+
+    /// The points that were read back.
+    pub points: Vec<Point>,
+
+    /// The differences between the points that were written and the ones read back, and between
+    /// the two headers.
+    ///
+    /// Coordinates are compared as the integers they're stored as, see [CompareMode::Quantized].
+    pub report: ComparisonReport,
+}
+
+impl RoundtripOutcome {
+    /// Returns true if the points and the header were read back as they were written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "test-support")] {
+    /// use las::{Builder, Point};
+    /// let outcome = las::roundtrip(&[Point::default()], Builder::default()).unwrap();
+    /// assert!(outcome.is_lossless());
+    /// # }
+    /// ```
+    pub fn is_lossless(&self) -> bool {
+        self.report.is_equal()
+    }
+
+    /// Returns the first point that was read back differently, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "test-support")] {
+    /// use las::{Builder, Point};
+    /// let point = Point { scan_angle: 1.5, ..Default::default() };
+    /// let outcome = las::roundtrip(&[point], Builder::default()).unwrap();
+    /// assert_eq!("scan_angle", outcome.first_divergence().unwrap().fields[0].field);
+    /// # }
+    /// ```
+    pub fn first_divergence(&self) -> Option<&PointDifference> {
+        self.report.point_differences.first()
+    }
+}
+
+/// Writes points to an in-memory file with a header from this builder, reads them back, and
+/// compares the two.
+///
+/// The file is laz if the builder's point format is compressed. This is the way to check that
+/// your points, point format, and options survive a trip through a file: it returns an error if
+/// the header can't be built or the points can't be written or read, and otherwise reports
+/// every point that came back different. Needs the `test-support` feature.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "test-support")] {
+/// use las::{point::Format, Builder, Point};
+///
+/// let mut builder = Builder::from((1, 2));
+/// builder.point_format = Format::new(1).unwrap();
+/// let point = Point { x: 1.0004, gps_time: Some(42.), ..Default::default() };
+/// let outcome = las::roundtrip(&[point], builder).unwrap();
+/// assert!(outcome.is_lossless());
+/// assert_eq!(1., outcome.points[0].x);
+/// # }
+/// ```
+pub fn roundtrip(points: &[Point], builder: Builder) -> Result<RoundtripOutcome> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header()?)?;
+    for point in points {
+        writer.write_point_ref(point)?;
+    }
+    let mut written_header = writer.header().clone();
+    written_header.bounds = written_header.bounds.adapt(written_header.transforms())?;
+    let bytes = writer.into_inner()?.into_inner();
+    let reader = Reader::new(Cursor::new(bytes.clone()))?;
+    let header = reader.header().clone();
+    let read = reader.into_points()?;
+
+    let mode = CompareMode::Quantized;
+    let mut report = compare::compare_points(points, &read, &written_header, &header, mode)?;
+    let mut header_differences = compare::header_differences(&written_header, &header, mode);
+    if header_differences.is_empty() && written_header != header {
+        header_differences.push(FieldDifference::new("header", &written_header, &header));
+    }
+    report.header_differences.extend(header_differences);
+    Ok(RoundtripOutcome {
+        bytes,
+        written_header,
+        header,
+        points: read,
+        report,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lossless() {
+        let points: Vec<Point> = (0..10)
+            .map(|i| Point {
+                x: f64::from(i),
+                intensity: i as u16,
+                ..Default::default()
+            })
+            .collect();
+        let outcome = roundtrip(&points, Builder::default()).unwrap();
+        assert!(outcome.is_lossless(), "{:?}", outcome.report);
+        assert_eq!(10, outcome.report.points_compared);
+        assert_eq!(points, outcome.points);
+    }
+
+    #[test]
+    fn first_divergence() {
+        // Scan angles are rounded to whole degrees in legacy formats.
+        let points: Vec<Point> = (0..5)
+            .map(|i| Point {
+                scan_angle: if i < 3 { 1. } else { 1.5 },
+                ..Default::default()
+            })
+            .collect();
+        let outcome = roundtrip(&points, Builder::default()).unwrap();
+        assert!(!outcome.is_lossless());
+        assert_eq!(2, outcome.report.differing_points);
+        let divergence = outcome.first_divergence().unwrap();
+        assert_eq!(3, divergence.index);
+        assert_eq!("scan_angle", divergence.fields[0].field);
+    }
+
+    #[test]
+    fn point_that_does_not_fit() {
+        let point = Point {
+            gps_time: Some(42.),
+            ..Default::default()
+        };
+        assert!(roundtrip(&[point], Builder::default()).is_err());
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn laz() {
+        use crate::point::Format;
+
+        let mut builder = Builder::default();
+        builder.point_format = Format::new(3).unwrap();
+        builder.point_format.is_compressed = true;
+        let point = Point {
+            gps_time: Some(42.),
+            color: Some(Default::default()),
+            ..Default::default()
+        };
+        let outcome = roundtrip(&[point], builder).unwrap();
+        assert!(outcome.is_lossless(), "{:?}", outcome.report);
+        assert!(outcome.header.point_format().is_compressed);
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    header::SystemIdentifier,
+    raw::{self, point::ScanAngle},
+    Point, Reader, Result, Writer,
+};
+use std::io::{Read, Seek, Write};
+
+/// The fewest points that [diagnose_scan_angles] will diagnose.
+pub const MIN_DIAGNOSED_POINTS: u64 = 1000;
+
+/// The widest scan angle, in whole degrees, that a file's ranks must reach to look legacy scaled.
+///
+/// Real scanners sweep well past five degrees, so a narrower spread isn't enough to tell the
+/// encodings apart.
+pub const MIN_LEGACY_SPREAD: i16 = 5;
+
+/// The widest stored scan angle that a file must reach to look extended scaled.
+///
+/// This is three degrees in 0.006 degree increments, more than five times the widest valid rank.
+pub const MIN_EXTENDED_SPREAD: i16 = 500;
+
+/// Which encoding a file's stored scan angles look like they're in, see [diagnose_scan_angles].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScanAngleDiagnosis {
+    /// The stored values look like whole degrees, from -90 to 90.
+    LooksLegacyScaled,
+
+    /// The stored values look like 0.006 degree increments.
+    LooksExtendedScaled,
+
+    /// The stored values could be either.
+    Ambiguous,
+}
+
+/// The stored scan angles of a file, see [diagnose_scan_angles].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScanAngleReport {
+    /// The number of points read.
+    pub points: u64,
+
+    /// The smallest stored scan angle, or zero if there are no points.
+    pub min: i16,
+
+    /// The largest stored scan angle, or zero if there are no points.
+    pub max: i16,
+
+    /// True if the point format stores extended, 0.006 degree, scan angles.
+    pub is_extended: bool,
+
+    /// Which encoding the stored values look like they're in.
+    pub diagnosis: ScanAngleDiagnosis,
+}
+
+/// What [repair_scan_angles] did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScanAngleRepair {
+    /// The diagnosis of the source.
+    pub report: ScanAngleReport,
+
+    /// True if the scan angles were rescaled, false if they were copied as they are.
+    pub rescaled: bool,
+}
+
+impl ScanAngleReport {
+    /// Returns true if the stored values confidently look like the other encoding than the
+    /// point format's.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut reader = las::Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let report = las::diagnose_scan_angles(&mut reader).unwrap();
+    /// assert!(!report.is_mis_scaled());
+    /// ```
+    pub fn is_mis_scaled(&self) -> bool {
+        match self.diagnosis {
+            ScanAngleDiagnosis::LooksLegacyScaled => self.is_extended,
+            ScanAngleDiagnosis::LooksExtendedScaled => !self.is_extended,
+            ScanAngleDiagnosis::Ambiguous => false,
+        }
+    }
+}
+
+/// Reads every point's stored scan angle and diagnoses which encoding they look like.
+///
+/// A point format's scan angles are either whole degree ranks, for formats zero through five, or
+/// 0.006 degree increments, for formats six through ten. Converters that forget to rescale leave
+/// extended formats holding ranks, so every angle is about 167 times too small.
+///
+/// The heuristic only looks at the widest stored value, and is deliberately conservative:
+///
+/// - Fewer than [MIN_DIAGNOSED_POINTS] points are [ScanAngleDiagnosis::Ambiguous].
+/// - A widest value from [MIN_LEGACY_SPREAD] to 90 is [ScanAngleDiagnosis::LooksLegacyScaled],
+///   since an extended file would have to sweep no more than half a degree.
+/// - A widest value of [MIN_EXTENDED_SPREAD] or more is
+///   [ScanAngleDiagnosis::LooksExtendedScaled], since no rank can be that large.
+/// - Anything else is [ScanAngleDiagnosis::Ambiguous].
+///
+/// A legacy rank can't hold [MIN_EXTENDED_SPREAD], so legacy formats are never diagnosed as
+/// extended scaled. The reader is read from the start, and is left back at the start.
+///
+/// # Examples
+///
+/// ```
+/// use las::ScanAngleDiagnosis;
+/// let mut reader = las::Reader::from_path("tests/data/autzen.las").unwrap();
+/// let report = las::diagnose_scan_angles(&mut reader).unwrap();
+/// assert_eq!(106, report.points);
+/// assert_eq!(ScanAngleDiagnosis::Ambiguous, report.diagnosis);
+/// ```
+pub fn diagnose_scan_angles<R>(reader: &mut Reader<R>) -> Result<ScanAngleReport>
+where
+    R: Read + Seek + Send,
+{
+    let format = *reader.header().point_format();
+    let mut record = vec![0; usize::from(format.len())];
+    let mut points = 0;
+    let mut min = i16::MAX;
+    let mut max = i16::MIN;
+    reader.seek(0)?;
+    while reader.read_record(&mut record)? {
+        let stored = stored(raw::Point::read_from(record.as_slice(), &format)?.scan_angle);
+        min = min.min(stored);
+        max = max.max(stored);
+        points += 1;
+    }
+    reader.seek(0)?;
+    if points == 0 {
+        min = 0;
+        max = 0;
+    }
+    let widest = min.unsigned_abs().max(max.unsigned_abs());
+    let diagnosis = if points < MIN_DIAGNOSED_POINTS {
+        ScanAngleDiagnosis::Ambiguous
+    } else if (MIN_LEGACY_SPREAD.unsigned_abs()..=90).contains(&widest) {
+        ScanAngleDiagnosis::LooksLegacyScaled
+    } else if widest >= MIN_EXTENDED_SPREAD.unsigned_abs() {
+        ScanAngleDiagnosis::LooksExtendedScaled
+    } else {
+        ScanAngleDiagnosis::Ambiguous
+    };
+    Ok(ScanAngleReport {
+        points,
+        min,
+        max,
+        is_extended: format.is_extended,
+        diagnosis,
+    })
+}
+
+/// Copies a reader's points to a destination, rescaling the scan angles if they're confidently
+/// mis-scaled.
+///
+/// The source is diagnosed with [diagnose_scan_angles] first. If [ScanAngleReport::is_mis_scaled],
+/// every stored rank is converted to 0.006 degree increments, otherwise the points are copied as
+/// they are. Everything else about the points and the header is kept.
+///
+/// # Examples
+///
+/// ```
+/// use las::Reader;
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let repair = las::repair_scan_angles(reader, Cursor::new(Vec::new())).unwrap();
+/// assert!(!repair.rescaled);
+/// ```
+pub fn repair_scan_angles<R, W>(mut reader: Reader<R>, dest: W) -> Result<ScanAngleRepair>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let report = diagnose_scan_angles(&mut reader)?;
+    let rescaled = report.is_mis_scaled();
+    let format = *reader.header().point_format();
+    let transforms = *reader.header().effective_transforms();
+    let mut writer = Writer::new(
+        dest,
+        reader.header().to_derived(&SystemIdentifier::Modification),
+    )?;
+    let mut record = vec![0; usize::from(format.len())];
+    while reader.read_record(&mut record)? {
+        let mut raw_point = raw::Point::read_from(record.as_slice(), &format)?;
+        if rescaled {
+            raw_point.scan_angle = ScanAngle::from(f32::from(stored(raw_point.scan_angle)));
+        }
+        writer.write_point(Point::new(raw_point, &transforms))?;
+    }
+    let _ = writer.close()?;
+    Ok(ScanAngleRepair { report, rescaled })
+}
+
+fn stored(scan_angle: ScanAngle) -> i16 {
+    match scan_angle {
+        ScanAngle::Rank(n) => i16::from(n),
+        ScanAngle::Scaled(n) => n,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        point::Format,
+        synthetic::{self, fixture},
+        Builder, GpsTimeType,
+    };
+    use std::{fs::File, io::Cursor};
+
+    /// Writes a synthetic flight strip, optionally forgetting to rescale the scan angles.
+    fn source(format: u8, forget_to_rescale: bool, count: usize) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.gps_time_type = GpsTimeType::Standard;
+        let format = builder.point_format;
+        let points = synthetic::terrain(fixture::BOUNDS, 1., 42)
+            .format(format)
+            .take(count)
+            .map(|mut point| {
+                if forget_to_rescale {
+                    point.scan_angle =
+                        f32::from(ScanAngle::Scaled(point.scan_angle.round() as i16));
+                }
+                point
+            });
+        fixture::reader(fixture::write(builder, points))
+    }
+
+    #[test]
+    fn extended_scaled() {
+        let report = diagnose_scan_angles(&mut source(6, false, 2000)).unwrap();
+        assert_eq!(ScanAngleDiagnosis::LooksExtendedScaled, report.diagnosis);
+        assert!(report.is_extended);
+        assert!(!report.is_mis_scaled());
+    }
+
+    #[test]
+    fn legacy_scaled() {
+        let report = diagnose_scan_angles(&mut source(1, false, 2000)).unwrap();
+        assert_eq!(ScanAngleDiagnosis::LooksLegacyScaled, report.diagnosis);
+        assert!(!report.is_mis_scaled());
+    }
+
+    #[test]
+    fn never_rescaled() {
+        let report = diagnose_scan_angles(&mut source(6, true, 2000)).unwrap();
+        assert_eq!(ScanAngleDiagnosis::LooksLegacyScaled, report.diagnosis);
+        assert!(report.is_mis_scaled());
+        assert!(report.max <= 90);
+    }
+
+    #[test]
+    fn too_few_points() {
+        let report = diagnose_scan_angles(&mut source(6, true, 999)).unwrap();
+        assert_eq!(999, report.points);
+        assert_eq!(ScanAngleDiagnosis::Ambiguous, report.diagnosis);
+        assert!(!report.is_mis_scaled());
+    }
+
+    #[test]
+    fn narrow_spread() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        builder.gps_time_type = GpsTimeType::Standard;
+        let points = (0..2000).map(|i| Point {
+            scan_angle: f32::from(ScanAngle::Scaled(i % 5 - 2)),
+            gps_time: Some(0.),
+            ..Default::default()
+        });
+        let mut reader = fixture::reader(fixture::write(builder, points));
+        let report = diagnose_scan_angles(&mut reader).unwrap();
+        assert_eq!((-2, 2), (report.min, report.max));
+        assert_eq!(ScanAngleDiagnosis::Ambiguous, report.diagnosis);
+    }
+
+    #[test]
+    fn leaves_reader_at_start() {
+        let mut reader = source(6, false, 10);
+        let _ = reader.read_points(5).unwrap();
+        let _ = diagnose_scan_angles(&mut reader).unwrap();
+        assert_eq!(10, reader.read_points(100).unwrap().len());
+    }
+
+    fn repair(name: &str, reader: Reader<Cursor<Vec<u8>>>) -> (ScanAngleRepair, Vec<Point>) {
+        let path = std::env::temp_dir().join(format!("las-rs-repair-scan-angles-{name}.las"));
+        let repair = repair_scan_angles(reader, File::create(&path).unwrap()).unwrap();
+        let points = Reader::from_path(&path).unwrap().into_points().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        (repair, points)
+    }
+
+    #[test]
+    fn repairs_never_rescaled() {
+        let (repair, points) = repair("never-rescaled", source(6, true, 2000));
+        assert!(repair.rescaled);
+        let expected = source(6, false, 2000).into_points().unwrap();
+        assert_eq!(expected.len(), points.len());
+        for (expected, point) in expected.iter().zip(&points) {
+            assert!((expected.scan_angle.round() - point.scan_angle).abs() < 0.006);
+            assert_eq!(expected.x, point.x);
+        }
+    }
+
+    #[test]
+    fn copies_correctly_scaled() {
+        let (repair, points) = repair("correctly-scaled", source(6, false, 2000));
+        assert!(!repair.rescaled);
+        assert_eq!(source(6, false, 2000).into_points().unwrap(), points);
+    }
+}
@@ -0,0 +1,630 @@
+use crate::{header::SystemIdentifier, Bounds, Header, Point, Reader, Result, Writer};
+#[cfg(feature = "laz")]
+use crate::{writer::WriteOptions, Error};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::File,
+    io::{Read, Seek, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The default memory budget of a sort, in bytes.
+pub const DEFAULT_SORT_MEMORY_BUDGET: usize = 256 * 1024 * 1024;
+
+/// The bits of each coordinate in a morton code.
+const MORTON_BITS: u32 = 21;
+
+/// The bits of each coordinate in a hilbert code.
+const HILBERT_BITS: u32 = 32;
+
+/// Tells apart the scratch files of sorts that run at the same time.
+static SORT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The order that [sort] puts points in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// By gps time, earliest first. Points without a gps time sort as time zero.
+    #[default]
+    GpsTime,
+
+    /// By the morton code of the point's position inside the header's bounds, so points that are
+    /// close together in space end up close together in the file.
+    Morton,
+
+    /// By the hilbert code of the point's xy position inside the header's bounds.
+    ///
+    /// Like [SortKey::Morton], but the curve never jumps between cells that aren't neighbors, so
+    /// runs of points along it are more compact, e.g. for tiles or laz chunks.
+    Hilbert,
+}
+
+impl SortKey {
+    /// Returns how many bits of a code each level of the curve takes, and how many levels there
+    /// are, or `None` if this key isn't a space filling curve.
+    #[cfg(feature = "laz")]
+    fn curve(self) -> Option<(u32, u32)> {
+        match self {
+            SortKey::GpsTime => None,
+            SortKey::Morton => Some((3, MORTON_BITS)),
+            SortKey::Hilbert => Some((2, HILBERT_BITS)),
+        }
+    }
+}
+
+/// Options for [sort].
+#[derive(Clone, Debug)]
+pub struct SortOptions {
+    memory_budget: usize,
+    scratch_dir: PathBuf,
+    system_identifier: SystemIdentifier,
+    #[cfg(feature = "laz")]
+    laz_chunk_level: Option<u32>,
+}
+
+impl SortOptions {
+    /// Sets roughly how many bytes of points are held in memory at once.
+    ///
+    /// The points are sorted in runs that fit in the budget, and every run but the last is
+    /// spilled to a scratch file. At least one point is always read in each run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::SortOptions;
+    /// let options = SortOptions::default().memory_budget(1 << 20);
+    /// assert_eq!(1 << 20, options.memory_budget_bytes());
+    /// ```
+    pub fn memory_budget(mut self, bytes: usize) -> SortOptions {
+        self.memory_budget = bytes;
+        self
+    }
+
+    /// Sets the directory where runs are spilled, which defaults to [std::env::temp_dir].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::SortOptions;
+    /// let options = SortOptions::default().scratch_dir("scratch");
+    /// assert_eq!("scratch", options.scratch_dir_path().to_str().unwrap());
+    /// ```
+    pub fn scratch_dir<P: Into<PathBuf>>(mut self, path: P) -> SortOptions {
+        self.scratch_dir = path.into();
+        self
+    }
+
+    /// Sets the output's system identifier, which defaults to [SystemIdentifier::Modification].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{header::SystemIdentifier, SortOptions};
+    /// let options = SortOptions::default().system_identifier(SystemIdentifier::Merge);
+    /// ```
+    pub fn system_identifier(mut self, system_identifier: SystemIdentifier) -> SortOptions {
+        self.system_identifier = system_identifier;
+        self
+    }
+
+    /// Ends the output's laz chunks where the sort key's curve crosses into a new cell at this
+    /// level, so each chunk holds the points of one contiguous part of the curve.
+    ///
+    /// At level `n`, the curve is cut into `4^n` hilbert or `8^n` morton cells, so higher levels
+    /// make smaller chunks. The output must be compressed, and the key must be a curve, not
+    /// [SortKey::GpsTime].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::SortOptions;
+    /// let options = SortOptions::default().align_laz_chunks(6);
+    /// ```
+    #[cfg(feature = "laz")]
+    pub fn align_laz_chunks(mut self, level: u32) -> SortOptions {
+        self.laz_chunk_level = Some(level);
+        self
+    }
+
+    /// Returns the memory budget, in bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{SortOptions, DEFAULT_SORT_MEMORY_BUDGET};
+    /// assert_eq!(
+    ///     DEFAULT_SORT_MEMORY_BUDGET,
+    ///     SortOptions::default().memory_budget_bytes()
+    /// );
+    /// ```
+    pub fn memory_budget_bytes(&self) -> usize {
+        self.memory_budget
+    }
+
+    /// Returns the directory where runs are spilled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::SortOptions;
+    /// assert_eq!(std::env::temp_dir(), SortOptions::default().scratch_dir_path());
+    /// ```
+    pub fn scratch_dir_path(&self) -> &Path {
+        &self.scratch_dir
+    }
+}
+
+impl Default for SortOptions {
+    fn default() -> SortOptions {
+        SortOptions {
+            memory_budget: DEFAULT_SORT_MEMORY_BUDGET,
+            scratch_dir: std::env::temp_dir(),
+            system_identifier: SystemIdentifier::Modification,
+            #[cfg(feature = "laz")]
+            laz_chunk_level: None,
+        }
+    }
+}
+
+/// Copies a reader's points to a destination, sorted by a key, without holding them all in
+/// memory.
+///
+/// This is an external merge sort: runs of points that fit in the
+/// [memory budget](SortOptions::memory_budget) are sorted and spilled to uncompressed las files in
+/// the [scratch directory](SortOptions::scratch_dir), and then the runs are merged into the
+/// destination. If every point fits in one run, nothing is spilled. The scratch files are removed
+/// whether or not the sort succeeds. Points with the same key keep their order.
+///
+/// The output has the source's layout and vlrs, without spatial indices or COPC info, and holds
+/// exactly the source's points, so its counts are the same as the source's.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Reader, SortKey, SortOptions};
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let header = las::sort(
+///     reader,
+///     Cursor::new(Vec::new()),
+///     SortKey::Morton,
+///     SortOptions::default(),
+/// )
+/// .unwrap();
+/// assert_eq!(106, header.number_of_points());
+/// ```
+pub fn sort<R, W>(reader: Reader<R>, dest: W, key: SortKey, options: SortOptions) -> Result<Header>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    sort_in_runs(reader, dest, key, &options).map(|(header, _)| header)
+}
+
+/// Sorts, and returns the number of runs too.
+fn sort_in_runs<R, W>(
+    mut reader: Reader<R>,
+    dest: W,
+    key: SortKey,
+    options: &SortOptions,
+) -> Result<(Header, usize)>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let header = reader.header().to_derived(&options.system_identifier);
+    let bounds = reader.header().bounds();
+    let bytes_per_point =
+        size_of::<(u64, Point)>() + usize::from(header.point_format().extra_bytes);
+    let run_len = (options.memory_budget / bytes_per_point).max(1);
+    let mut scratch = Scratch::new(&options.scratch_dir);
+    let mut run = Vec::new();
+    let mut next = reader.read_point()?;
+    loop {
+        while let Some(point) = next.take() {
+            run.push((sort_key(&point, key, &bounds), point));
+            if run.len() == run_len {
+                break;
+            }
+            next = reader.read_point()?;
+        }
+        if next.is_none() {
+            next = reader.read_point()?;
+        }
+        let is_last = next.is_none();
+        run.sort_by_key(|(key, _)| *key);
+        if is_last && scratch.paths.is_empty() {
+            let mut writer = SortedWriter::new(dest, header, key, options)?;
+            for (code, point) in run {
+                writer.write_point(code, point)?;
+            }
+            return Ok((writer.close()?, 1));
+        }
+        scratch.spill(&header, run.drain(..))?;
+        if is_last {
+            break;
+        }
+    }
+    let writer = SortedWriter::new(dest, header, key, options)?;
+    let header = merge(&scratch.paths, writer, key, &bounds)?;
+    Ok((header, scratch.paths.len()))
+}
+
+fn merge<W>(
+    paths: &[PathBuf],
+    mut writer: SortedWriter<W>,
+    key: SortKey,
+    bounds: &Bounds,
+) -> Result<Header>
+where
+    W: Write + Seek + Send,
+{
+    let mut runs = Vec::with_capacity(paths.len());
+    let mut heads = BinaryHeap::with_capacity(paths.len());
+    let mut points = Vec::with_capacity(paths.len());
+    for (i, path) in paths.iter().enumerate() {
+        let mut run = Reader::from_path(path)?;
+        let point = run.read_point()?;
+        if let Some(point) = &point {
+            heads.push(Reverse((sort_key(point, key, bounds), i)));
+        }
+        points.push(point);
+        runs.push(run);
+    }
+    while let Some(Reverse((code, i))) = heads.pop() {
+        let next = runs[i].read_point()?;
+        if let Some(point) = &next {
+            heads.push(Reverse((sort_key(point, key, bounds), i)));
+        }
+        if let Some(point) = std::mem::replace(&mut points[i], next) {
+            writer.write_point(code, point)?;
+        }
+    }
+    writer.close()
+}
+
+/// Writes sorted points, ending laz chunks between parts of the curve if the options ask for it.
+struct SortedWriter<W: Write + Seek + Send> {
+    writer: Writer<W>,
+    /// How far to shift a code to get the part of the curve that it's in.
+    #[cfg(feature = "laz")]
+    chunk_shift: Option<u32>,
+    #[cfg(feature = "laz")]
+    part: Option<u64>,
+}
+
+impl<W: Write + Seek + Send> SortedWriter<W> {
+    #[cfg_attr(not(feature = "laz"), allow(unused_variables))]
+    fn new(
+        dest: W,
+        header: Header,
+        key: SortKey,
+        options: &SortOptions,
+    ) -> Result<SortedWriter<W>> {
+        #[cfg(feature = "laz")]
+        if let Some(level) = options.laz_chunk_level {
+            let (bits, levels) = key.curve().ok_or_else(|| Error::InvalidWriteOption {
+                option: "align_laz_chunks",
+                reason: "gps time order isn't a space filling curve".to_string(),
+            })?;
+            let options = WriteOptions::default().variable_laz_chunks();
+            return Ok(SortedWriter {
+                writer: Writer::with_options(dest, header, options)?,
+                chunk_shift: Some(bits * (levels - level.min(levels))),
+                part: None,
+            });
+        }
+        Ok(SortedWriter {
+            writer: Writer::new(dest, header)?,
+            #[cfg(feature = "laz")]
+            chunk_shift: None,
+            #[cfg(feature = "laz")]
+            part: None,
+        })
+    }
+
+    #[cfg_attr(not(feature = "laz"), allow(unused_variables))]
+    fn write_point(&mut self, code: u64, point: Point) -> Result<()> {
+        #[cfg(feature = "laz")]
+        if let Some(shift) = self.chunk_shift {
+            let part = code.checked_shr(shift).unwrap_or(0);
+            if self.part.is_some_and(|last| last != part) {
+                self.writer.finish_laz_chunk()?;
+            }
+            self.part = Some(part);
+        }
+        self.writer.write_point(point)
+    }
+
+    fn close(mut self) -> Result<Header> {
+        let _ = self.writer.close()?;
+        Ok(self.writer.header().clone())
+    }
+}
+
+fn sort_key(point: &Point, key: SortKey, bounds: &Bounds) -> u64 {
+    match key {
+        SortKey::GpsTime => {
+            // Flips the bits so that the unsigned integers sort like the floats.
+            let bits = point.gps_time.unwrap_or(0.).to_bits();
+            if bits >> 63 == 1 {
+                !bits
+            } else {
+                bits | 1 << 63
+            }
+        }
+        SortKey::Morton => {
+            let x = cell(point.x, bounds.min.x, bounds.max.x, MORTON_BITS);
+            let y = cell(point.y, bounds.min.y, bounds.max.y, MORTON_BITS);
+            let z = cell(point.z, bounds.min.z, bounds.max.z, MORTON_BITS);
+            (0..u64::from(MORTON_BITS)).fold(0, |code, bit| {
+                code | (x >> bit & 1) << (3 * bit)
+                    | (y >> bit & 1) << (3 * bit + 1)
+                    | (z >> bit & 1) << (3 * bit + 2)
+            })
+        }
+        SortKey::Hilbert => hilbert(
+            cell(point.x, bounds.min.x, bounds.max.x, HILBERT_BITS),
+            cell(point.y, bounds.min.y, bounds.max.y, HILBERT_BITS),
+        ),
+    }
+}
+
+/// Returns the cell, out of `2^bits`, that a value falls in between the bounds.
+fn cell(value: f64, min: f64, max: f64, bits: u32) -> u64 {
+    let max_cell = ((1u64 << bits) - 1) as f64;
+    if max > min {
+        ((value - min) / (max - min) * max_cell).clamp(0., max_cell) as u64
+    } else {
+        0
+    }
+}
+
+/// Returns the distance along a hilbert curve of a cell, with [HILBERT_BITS] bits per axis.
+fn hilbert(mut x: u64, mut y: u64) -> u64 {
+    let max = (1u64 << HILBERT_BITS) - 1;
+    let mut code = 0;
+    let mut s = 1u64 << (HILBERT_BITS - 1);
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        code += s * s * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = max - x;
+                y = max - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s >>= 1;
+    }
+    code
+}
+
+/// The spilled runs of a sort, which are removed when this is dropped.
+#[derive(Debug)]
+struct Scratch {
+    dir: PathBuf,
+    id: String,
+    paths: Vec<PathBuf>,
+}
+
+impl Scratch {
+    fn new(dir: &Path) -> Scratch {
+        Scratch {
+            dir: dir.to_path_buf(),
+            id: format!(
+                "{}-{}",
+                std::process::id(),
+                SORT_ID.fetch_add(1, Ordering::Relaxed)
+            ),
+            paths: Vec::new(),
+        }
+    }
+
+    fn spill(&mut self, header: &Header, run: impl Iterator<Item = (u64, Point)>) -> Result<()> {
+        let path = self
+            .dir
+            .join(format!("las-rs-sort-{}-{}.las", self.id, self.paths.len()));
+        let file = File::create(&path)?;
+        self.paths.push(path);
+        let mut builder = header.clone().into_builder();
+        builder.point_format.is_compressed = false;
+        let mut writer = Writer::new(file, builder.into_header()?)?;
+        for (_, point) in run {
+            writer.write_point(point)?;
+        }
+        writer.close().map(|_| ())
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            if let Err(err) = std::fs::remove_file(path) {
+                log::warn!("could not remove {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, rng::Rng, synthetic::fixture, Builder, Error};
+    use std::io::Cursor;
+
+    fn shuffled() -> Vec<u8> {
+        let format = Format::new(1).unwrap();
+        let mut points = fixture::points(format, 1000);
+        let mut rng = Rng::new(7);
+        for i in (1..points.len()).rev() {
+            points.swap(i, (rng.next_u64() % (i as u64 + 1)) as usize);
+        }
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = format;
+        fixture::write(builder, points)
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("las-rs-sort-{name}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn run(name: &str, bytes: Vec<u8>, key: SortKey, budget: usize) -> (Header, usize, Vec<Point>) {
+        let dir = scratch_dir(name);
+        let path = dir.join("sorted.las");
+        let options = SortOptions::default()
+            .memory_budget(budget)
+            .scratch_dir(&dir);
+        let reader = Reader::new(Cursor::new(bytes)).unwrap();
+        let (header, runs) =
+            sort_in_runs(reader, File::create(&path).unwrap(), key, &options).unwrap();
+        let points = Reader::from_path(&path).unwrap().into_points().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(0, std::fs::read_dir(&dir).unwrap().count());
+        (header, runs, points)
+    }
+
+    fn check(name: &str, key: SortKey) {
+        let bytes = shuffled();
+        let source = Reader::new(Cursor::new(bytes.clone())).unwrap();
+        let source_header = source.header().clone();
+        let mut expected = source.into_points().unwrap();
+        let bounds = source_header.bounds();
+        let (header, runs, points) = run(name, bytes, key, 100 * size_of::<(u64, Point)>());
+        assert_eq!(10, runs);
+        assert_eq!(source_header.number_of_points(), header.number_of_points());
+        for n in 1..=5 {
+            assert_eq!(
+                source_header.number_of_points_by_return(n),
+                header.number_of_points_by_return(n)
+            );
+        }
+        assert!(points
+            .windows(2)
+            .all(|pair| sort_key(&pair[0], key, &bounds) <= sort_key(&pair[1], key, &bounds)));
+        expected.sort_by_key(|point| sort_key(point, key, &bounds));
+        assert_eq!(expected, points);
+    }
+
+    #[test]
+    fn gps_time() {
+        check("gps-time", SortKey::GpsTime);
+    }
+
+    #[test]
+    fn morton() {
+        check("morton", SortKey::Morton);
+    }
+
+    #[test]
+    fn hilbert() {
+        check("hilbert", SortKey::Hilbert);
+    }
+
+    #[test]
+    fn hilbert_neighbors() {
+        // Every step along a small curve moves to a neighboring cell.
+        let n = 1u64 << 4;
+        let shift = HILBERT_BITS - 4;
+        let mut cells: Vec<(u64, u64, u64)> = (0..n)
+            .flat_map(|x| (0..n).map(move |y| (super::hilbert(x << shift, y << shift), x, y)))
+            .collect();
+        cells.sort();
+        assert!(cells.windows(2).all(|pair| {
+            let (_, x0, y0) = pair[0];
+            let (_, x1, y1) = pair[1];
+            x0.abs_diff(x1) + y0.abs_diff(y1) == 1
+        }));
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn align_laz_chunks() {
+        let reader = fixture::reader(shuffled());
+        let bounds = reader.header().bounds();
+        let dir = scratch_dir("align");
+        let path = dir.join("aligned.laz");
+        let mut builder = reader.header().clone().into_builder();
+        builder.point_format.is_compressed = true;
+        let bytes = fixture::write(builder, reader.into_points().unwrap());
+        let options = SortOptions::default()
+            .scratch_dir(&dir)
+            .memory_budget(100 * size_of::<(u64, Point)>())
+            .align_laz_chunks(1);
+        let reader = Reader::new(Cursor::new(bytes.clone())).unwrap();
+        let header = sort(
+            reader,
+            File::create(&path).unwrap(),
+            SortKey::Hilbert,
+            options,
+        )
+        .unwrap();
+        assert_eq!(1000, header.number_of_points());
+
+        let mut reader = Reader::from_path(&path).unwrap();
+        let chunks = reader.number_of_chunks().unwrap();
+        assert_eq!(4, chunks);
+        let mut total = 0;
+        for chunk in 0..chunks {
+            let points = reader.read_chunk_at(chunk).unwrap();
+            let quadrant = |point: &Point| sort_key(point, SortKey::Hilbert, &bounds) >> 62;
+            assert!(points
+                .iter()
+                .all(|point| quadrant(point) == quadrant(&points[0])));
+            total += points.len();
+        }
+        assert_eq!(1000, total);
+        std::fs::remove_file(&path).unwrap();
+
+        let reader = Reader::new(Cursor::new(bytes)).unwrap();
+        let options = SortOptions::default().align_laz_chunks(1);
+        assert!(matches!(
+            sort(reader, Cursor::new(Vec::new()), SortKey::GpsTime, options).unwrap_err(),
+            Error::InvalidWriteOption { .. }
+        ));
+    }
+
+    #[test]
+    fn one_run() {
+        let (header, runs, points) = run("one-run", shuffled(), SortKey::GpsTime, usize::MAX);
+        assert_eq!(1, runs);
+        assert_eq!(1000, header.number_of_points());
+        assert_eq!(1000, points.len());
+    }
+
+    #[test]
+    fn negative_times() {
+        let bounds = Bounds::default();
+        let times = [-2., -0.5, 0., 1.5];
+        let keys: Vec<u64> = times
+            .iter()
+            .map(|&time| {
+                let point = Point {
+                    gps_time: Some(time),
+                    ..Default::default()
+                };
+                sort_key(&point, SortKey::GpsTime, &bounds)
+            })
+            .collect();
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn cleans_up_on_error() {
+        let mut bytes = shuffled();
+        bytes.truncate(bytes.len() - 10);
+        let dir = scratch_dir("error");
+        let options = SortOptions::default()
+            .memory_budget(100 * size_of::<(u64, Point)>())
+            .scratch_dir(&dir);
+        let reader = Reader::new(Cursor::new(bytes)).unwrap();
+        let err = sort(reader, Cursor::new(Vec::new()), SortKey::GpsTime, options).unwrap_err();
+        assert!(matches!(err, Error::PointReadFailed { .. }));
+        assert_eq!(0, std::fs::read_dir(&dir).unwrap().count());
+    }
+}
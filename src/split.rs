@@ -0,0 +1,307 @@
+use crate::{
+    header::SystemIdentifier, reader::SegmentationOptions, Header, Point, Reader, Result, Writer,
+};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    io::{Read, Seek, Write},
+};
+
+/// Splits a reader's points into several outputs, in one pass.
+///
+/// `router` picks an output key for each point, or returns `None` to drop the point. The first
+/// time a key comes up, `make_dest` opens a destination for it, and a writer is created with the
+/// source's layout. Spatial indices and COPC info aren't copied, since they wouldn't describe the
+/// split points.
+///
+/// One writer stays open for each distinct key until every point has been read, so memory use
+/// (and open files, if the destinations are files) grows with the number of keys.
+///
+/// Returns each key with the header of its output, in the order the keys first came up. If
+/// anything fails, every writer that was opened is still closed, so each output is a valid file
+/// with the points that were written before the error, and then the error is returned.
+///
+/// # Examples
+///
+/// ```
+/// use las::Reader;
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let outputs = las::split_by(
+///     reader,
+///     |point| Some(point.return_number == point.number_of_returns),
+///     |_| Ok(Cursor::new(Vec::new())),
+/// )
+/// .unwrap();
+/// let total: u64 = outputs.iter().map(|(_, header)| header.number_of_points()).sum();
+/// assert_eq!(106, total);
+/// ```
+pub fn split_by<R, K, W>(
+    mut reader: Reader<R>,
+    mut router: impl FnMut(&Point) -> Option<K>,
+    mut make_dest: impl FnMut(&K) -> Result<W>,
+) -> Result<Vec<(K, Header)>>
+where
+    R: Read + Seek + Send,
+    K: Eq + Hash + Clone,
+    W: Write + Seek + Send,
+{
+    let header = reader.header().to_derived(&SystemIdentifier::Extraction);
+    let mut outputs = Vec::new();
+    let mut result = route(
+        &mut reader,
+        &header,
+        &mut router,
+        &mut make_dest,
+        &mut outputs,
+    );
+    let mut headers = Vec::with_capacity(outputs.len());
+    for (key, mut writer) in outputs {
+        if let Err(err) = writer.close() {
+            if result.is_ok() {
+                result = Err(err);
+            } else {
+                log::warn!("could not close a split output: {}", err);
+            }
+        }
+        headers.push((key, writer.header().clone()));
+    }
+    result.map(|()| headers)
+}
+
+/// Writes each of a reader's flight lines to its own output.
+///
+/// The flight lines are found as [Reader::flight_lines] finds them, so if the options split on
+/// time gaps and the gps times aren't sorted, a warning is logged and the points are split by
+/// source id alone. A flight line is a run of consecutive points, so a source id that comes up
+/// again after another one starts a new flight line, and a new output. Use [split_by] with the
+/// point source id to gather every point of a source id into one output instead.
+///
+/// `make_dest` is called with the source id and start index of each flight line, and a writer is
+/// created with the source's layout. Only one output is open at a time. Returns the source id,
+/// start index, and header of each output. If anything fails, the open output is still closed,
+/// so it's a valid file with the points that were written before the error.
+///
+/// # Examples
+///
+/// ```
+/// use las::{reader::SegmentationOptions, Reader};
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let outputs = las::split_flight_lines(reader, SegmentationOptions::default(), |_, _| {
+///     Ok(Cursor::new(Vec::new()))
+/// })
+/// .unwrap();
+/// let total: u64 = outputs.iter().map(|(_, _, header)| header.number_of_points()).sum();
+/// assert_eq!(106, total);
+/// ```
+pub fn split_flight_lines<R, W>(
+    mut reader: Reader<R>,
+    options: SegmentationOptions,
+    mut make_dest: impl FnMut(u16, u64) -> Result<W>,
+) -> Result<Vec<(u16, u64, Header)>>
+where
+    R: Read + Seek + Send,
+    W: Write + Seek + Send,
+{
+    let header = reader.header().to_derived(&SystemIdentifier::Extraction);
+    let mut flight_lines = reader.flight_lines(options)?;
+    let mut outputs = Vec::new();
+    while let Some(points) = flight_lines.next_points() {
+        let (source_id, start_index) = (points.source_id, points.start_index);
+        let mut writer: Option<Writer<W>> = None;
+        let mut result = Ok(());
+        for point in points {
+            result = point.and_then(|point| {
+                let writer = match &mut writer {
+                    Some(writer) => writer,
+                    None => writer.insert(Writer::new(
+                        make_dest(source_id, start_index)?,
+                        header.clone(),
+                    )?),
+                };
+                writer.write_point(point)
+            });
+            if result.is_err() {
+                break;
+            }
+        }
+        if let Some(mut writer) = writer {
+            if let Err(err) = writer.close() {
+                if result.is_ok() {
+                    result = Err(err);
+                } else {
+                    log::warn!("could not close a flight line output: {}", err);
+                }
+            }
+            outputs.push((source_id, start_index, writer.header().clone()));
+        }
+        result?;
+    }
+    Ok(outputs)
+}
+
+fn route<R, K, W>(
+    reader: &mut Reader<R>,
+    header: &Header,
+    router: &mut impl FnMut(&Point) -> Option<K>,
+    make_dest: &mut impl FnMut(&K) -> Result<W>,
+    outputs: &mut Vec<(K, Writer<W>)>,
+) -> Result<()>
+where
+    R: Read + Seek + Send,
+    K: Eq + Hash + Clone,
+    W: Write + Seek + Send,
+{
+    let mut indices = HashMap::new();
+    for point in reader.points() {
+        let point = point?;
+        let Some(key) = router(&point) else {
+            continue;
+        };
+        let index = match indices.get(&key) {
+            Some(&index) => index,
+            None => {
+                let writer = Writer::new(make_dest(&key)?, header.clone())?;
+                outputs.push((key.clone(), writer));
+                let _ = indices.insert(key, outputs.len() - 1);
+                outputs.len() - 1
+            }
+        };
+        outputs[index].1.write_point(point)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{synthetic::fixture, Error};
+    use std::{fs::File, path::PathBuf};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    enum Position {
+        Single,
+        First,
+        Last,
+    }
+
+    fn position(point: &Point) -> Option<Position> {
+        if point.number_of_returns == 1 {
+            Some(Position::Single)
+        } else if point.return_number == 1 {
+            Some(Position::First)
+        } else if point.return_number == point.number_of_returns {
+            Some(Position::Last)
+        } else {
+            None
+        }
+    }
+
+    fn path(name: &str, position: Position) -> PathBuf {
+        std::env::temp_dir().join(format!("las-rs-split-{}-{:?}.las", name, position))
+    }
+
+    #[test]
+    fn by_return_position() {
+        let points = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .into_points()
+            .unwrap();
+        let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let outputs = split_by(reader, position, |&key| {
+            Ok(File::create(path("position", key))?)
+        })
+        .unwrap();
+        assert_eq!(3, outputs.len());
+        let mut total = 0;
+        for (key, header) in outputs {
+            let expected: Vec<Point> = points
+                .iter()
+                .filter(|point| position(point) == Some(key))
+                .cloned()
+                .collect();
+            assert!(!expected.is_empty());
+            let path = path("position", key);
+            let reader = Reader::from_path(&path).unwrap();
+            assert_eq!(
+                header.number_of_points(),
+                reader.header().number_of_points()
+            );
+            assert_eq!(expected.len() as u64, header.number_of_points());
+            let bounds = header.bounds();
+            for point in &expected {
+                assert!(bounds.min.x <= point.x && point.x <= bounds.max.x);
+                assert!(bounds.min.z <= point.z && point.z <= bounds.max.z);
+            }
+            assert_eq!(expected, reader.into_points().unwrap());
+            total += expected.len();
+            std::fs::remove_file(path).unwrap();
+        }
+        assert!(total < points.len(), "intermediate returns are dropped");
+    }
+
+    #[test]
+    fn closes_outputs_on_error() {
+        let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut opened = Vec::new();
+        let result = split_by(reader, position, |&key| {
+            if key == Position::Last {
+                Err(Error::ClosedWriter)
+            } else {
+                opened.push(key);
+                Ok(File::create(path("error", key))?)
+            }
+        });
+        assert!(matches!(result, Err(Error::ClosedWriter)));
+        assert!(!opened.is_empty());
+        for key in opened {
+            let path = path("error", key);
+            let reader = Reader::from_path(&path).unwrap();
+            let number_of_points = reader.header().number_of_points();
+            assert_eq!(number_of_points, reader.into_points().unwrap().len() as u64);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn flight_lines() {
+        let mut builder = crate::Builder::from((1, 2));
+        builder.point_format = crate::point::Format::new(1).unwrap();
+        let points = [1, 1, 2, 2, 2, 1]
+            .into_iter()
+            .enumerate()
+            .map(|(i, source_id)| Point {
+                point_source_id: source_id,
+                gps_time: Some(i as f64),
+                ..Default::default()
+            });
+        let reader = fixture::reader(fixture::write(builder, points));
+        let path = |start_index: u64| {
+            std::env::temp_dir().join(format!("las-rs-split-flight-line-{start_index}.las"))
+        };
+        let outputs = split_flight_lines(reader, SegmentationOptions::default(), |_, start| {
+            Ok(File::create(path(start))?)
+        })
+        .unwrap();
+        let lines: Vec<_> = outputs
+            .iter()
+            .map(|(source_id, start_index, header)| {
+                (*source_id, *start_index, header.number_of_points())
+            })
+            .collect();
+        assert_eq!(vec![(1, 0, 2), (2, 2, 3), (1, 5, 1)], lines);
+        for (source_id, start_index, _) in outputs {
+            let points = Reader::from_path(path(start_index))
+                .unwrap()
+                .into_points()
+                .unwrap();
+            assert!(points
+                .iter()
+                .all(|point| point.point_source_id == source_id));
+            std::fs::remove_file(path(start_index)).unwrap();
+        }
+    }
+}
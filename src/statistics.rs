@@ -0,0 +1,326 @@
+use crate::{Bounds, Point};
+use std::collections::HashMap;
+
+/// The number of return numbers that [Statistics] keeps bounds for, one through fifteen.
+const RETURN_NUMBERS: usize = 15;
+
+/// Point counts and bounds, accumulated one point at a time.
+///
+/// The bounds follow [Bounds::grow], so they include every point that was added. With
+/// [Statistics::with_grouped_bounds], bounds are also kept for each classification and each
+/// return number, which costs one [Bounds] per distinct group. Statistics of separate chunks can
+/// be combined with [Statistics::merge], e.g. in the reduce step of `Reader::process_par`.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Reader, Statistics};
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut statistics = Statistics::with_grouped_bounds();
+/// for point in reader.points() {
+///     statistics.add_point(&point.unwrap());
+/// }
+/// assert_eq!(106, statistics.number_of_points());
+/// assert_eq!(reader.header().bounds(), statistics.bounds());
+/// assert!(statistics.bounds_by_classification().contains_key(&1));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    number_of_points: u64,
+    bounds: Bounds,
+    grouped: Option<GroupedBounds>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct GroupedBounds {
+    by_classification: HashMap<u8, Bounds>,
+    by_return_number: [Option<Bounds>; RETURN_NUMBERS],
+}
+
+impl Statistics {
+    /// Creates empty statistics that only keep the overall counts and bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Statistics;
+    /// assert_eq!(0, Statistics::new().number_of_points());
+    /// ```
+    pub fn new() -> Statistics {
+        Statistics::default()
+    }
+
+    /// Creates empty statistics that also keep bounds by classification and return number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Statistics};
+    /// let mut statistics = Statistics::with_grouped_bounds();
+    /// statistics.add_point(&Point { return_number: 2, ..Default::default() });
+    /// assert!(statistics.bounds_by_return_number()[1].is_some());
+    /// ```
+    pub fn with_grouped_bounds() -> Statistics {
+        Statistics {
+            grouped: Some(GroupedBounds::default()),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Statistics};
+    /// let mut statistics = Statistics::new();
+    /// statistics.add_point(&Point { x: 1., ..Default::default() });
+    /// assert_eq!(1., statistics.bounds().max.x);
+    /// ```
+    pub fn add_point(&mut self, point: &Point) {
+        self.number_of_points += 1;
+        self.bounds.grow(point);
+        if let Some(grouped) = &mut self.grouped {
+            grouped
+                .by_classification
+                .entry(u8::from(point.classification))
+                .or_default()
+                .grow(point);
+            if let Some(bounds) = usize::from(point.return_number)
+                .checked_sub(1)
+                .and_then(|i| grouped.by_return_number.get_mut(i))
+            {
+                bounds.get_or_insert_with(Bounds::default).grow(point);
+            }
+        }
+    }
+
+    /// Adds the points of other statistics, as if they had been added to these.
+    ///
+    /// Grouped bounds are only kept if both have them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Statistics};
+    /// let mut a = Statistics::new();
+    /// a.add_point(&Point { x: 1., ..Default::default() });
+    /// let mut b = Statistics::new();
+    /// b.add_point(&Point { x: 2., ..Default::default() });
+    /// a.merge(b);
+    /// assert_eq!(2, a.number_of_points());
+    /// assert_eq!(2., a.bounds().max.x);
+    /// ```
+    pub fn merge(&mut self, other: Statistics) {
+        self.number_of_points += other.number_of_points;
+        union(&mut self.bounds, &other.bounds);
+        self.grouped = match (self.grouped.take(), other.grouped) {
+            (Some(mut grouped), Some(other)) => {
+                for (classification, bounds) in other.by_classification {
+                    union(
+                        grouped.by_classification.entry(classification).or_default(),
+                        &bounds,
+                    );
+                }
+                for (bounds, other) in grouped
+                    .by_return_number
+                    .iter_mut()
+                    .zip(other.by_return_number)
+                {
+                    if let Some(other) = other {
+                        union(bounds.get_or_insert_with(Bounds::default), &other);
+                    }
+                }
+                Some(grouped)
+            }
+            _ => None,
+        };
+    }
+
+    /// Returns the number of points added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Statistics;
+    /// assert_eq!(0, Statistics::new().number_of_points());
+    /// ```
+    pub fn number_of_points(&self) -> u64 {
+        self.number_of_points
+    }
+
+    /// Returns the bounds of every point added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Statistics};
+    /// assert_eq!(Bounds::default(), Statistics::new().bounds());
+    /// ```
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// Returns the bounds of the points of each classification.
+    ///
+    /// This is empty unless these statistics keep [grouped
+    /// bounds](Statistics::with_grouped_bounds).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Statistics};
+    /// let mut statistics = Statistics::with_grouped_bounds();
+    /// statistics.add_point(&Point::default());
+    /// assert_eq!(1, statistics.bounds_by_classification().len());
+    /// assert!(Statistics::new().bounds_by_classification().is_empty());
+    /// ```
+    pub fn bounds_by_classification(&self) -> HashMap<u8, Bounds> {
+        self.grouped
+            .as_ref()
+            .map(|grouped| grouped.by_classification.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the bounds of the points of each return number, with return number one first.
+    ///
+    /// Points with a return number of zero aren't in any of them, and every entry is `None` unless
+    /// these statistics keep [grouped bounds](Statistics::with_grouped_bounds).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Statistics};
+    /// let mut statistics = Statistics::with_grouped_bounds();
+    /// statistics.add_point(&Point { return_number: 1, ..Default::default() });
+    /// assert!(statistics.bounds_by_return_number()[0].is_some());
+    /// assert!(statistics.bounds_by_return_number()[1].is_none());
+    /// ```
+    pub fn bounds_by_return_number(&self) -> [Option<Bounds>; RETURN_NUMBERS] {
+        self.grouped
+            .as_ref()
+            .map(|grouped| grouped.by_return_number)
+            .unwrap_or_default()
+    }
+}
+
+/// Grows bounds to encompass other bounds, unless they're empty.
+fn union(bounds: &mut Bounds, other: &Bounds) {
+    if other.min.x > other.max.x {
+        return;
+    }
+    for corner in [other.min, other.max] {
+        bounds.grow(&Point {
+            x: corner.x,
+            y: corner.y,
+            z: corner.z,
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Classification, rng::Rng, synthetic, Vector};
+
+    fn bounds(min: f64, max: f64) -> Bounds {
+        Bounds {
+            min: Vector {
+                x: min,
+                y: min,
+                z: min,
+            },
+            max: Vector {
+                x: max,
+                y: max,
+                z: max,
+            },
+        }
+    }
+
+    /// Noise in the corner from (0, 0, 0) to (10, 10, 10), and everything else from 50 to 100.
+    fn points() -> Vec<Point> {
+        let mut rng = Rng::new(3);
+        let mut points: Vec<Point> = synthetic::uniform(bounds(50., 100.), 500, 1).collect();
+        for point in &mut points {
+            point.classification = Classification::Ground;
+            point.return_number = 1;
+        }
+        points.extend(
+            synthetic::uniform(bounds(0., 10.), 20, 2).map(|point| Point {
+                classification: Classification::LowPoint,
+                return_number: 2,
+                ..point
+            }),
+        );
+        // The corner itself, so the expected bounds are exact.
+        for corner in [0., 10.] {
+            points.push(Point {
+                x: corner,
+                y: corner,
+                z: corner,
+                classification: Classification::LowPoint,
+                return_number: 2,
+                number_of_returns: 2,
+                ..Default::default()
+            });
+        }
+        for i in (1..points.len()).rev() {
+            points.swap(i, (rng.next_u64() % (i as u64 + 1)) as usize);
+        }
+        points
+    }
+
+    fn check(statistics: &Statistics) {
+        let noise = statistics.bounds_by_classification()[&7];
+        assert_eq!(bounds(0., 10.), noise);
+        let ground = statistics.bounds_by_classification()[&2];
+        assert!(ground.min.x >= 50.);
+        let by_return_number = statistics.bounds_by_return_number();
+        assert_eq!(Some(noise), by_return_number[1]);
+        assert!(by_return_number[2..].iter().all(Option::is_none));
+        assert_eq!(0., statistics.bounds().min.x);
+        assert!(statistics.bounds().max.x > 90.);
+    }
+
+    #[test]
+    fn grouped_bounds() {
+        let mut statistics = Statistics::with_grouped_bounds();
+        for point in points() {
+            statistics.add_point(&point);
+        }
+        assert_eq!(522, statistics.number_of_points());
+        check(&statistics);
+    }
+
+    #[test]
+    fn merge() {
+        let points = points();
+        let mut whole = Statistics::with_grouped_bounds();
+        for point in &points {
+            whole.add_point(point);
+        }
+        let mut merged = Statistics::with_grouped_bounds();
+        for chunk in points.chunks(50) {
+            let mut statistics = Statistics::with_grouped_bounds();
+            for point in chunk {
+                statistics.add_point(point);
+            }
+            merged.merge(statistics);
+        }
+        check(&merged);
+        assert_eq!(whole, merged);
+    }
+
+    #[test]
+    fn merge_without_groups() {
+        let mut statistics = Statistics::with_grouped_bounds();
+        statistics.add_point(&Point::default());
+        statistics.merge(Statistics::new());
+        assert!(statistics.bounds_by_classification().is_empty());
+        assert_eq!(1, statistics.number_of_points());
+        assert_eq!(bounds(0., 0.), statistics.bounds());
+    }
+}
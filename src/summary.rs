@@ -0,0 +1,347 @@
+//! Summarize the points of a file, like `lasinfo`.
+//!
+//! ```
+//! use las::{summary::Summary, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let summary = Summary::from_reader(&mut reader).unwrap();
+//! assert_eq!(106, summary.number_of_points);
+//! println!("{}", summary);
+//! ```
+
+use crate::{Point, Reader, Result};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{Read, Seek},
+};
+
+/// The number of return numbers that are counted, zero through fifteen.
+const RETURN_NUMBERS: usize = 16;
+
+/// The smallest, largest, and mean value of one of the points' dimensions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dimension {
+    /// The smallest value.
+    pub min: f64,
+
+    /// The largest value.
+    pub max: f64,
+
+    /// The mean value.
+    pub mean: f64,
+}
+
+/// A summary of a file's points, see [Summary::from_reader].
+///
+/// The dimensions are `None` if there are no points with them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Summary {
+    /// The number of points.
+    pub number_of_points: u64,
+
+    /// The x coordinates.
+    pub x: Option<Dimension>,
+
+    /// The y coordinates.
+    pub y: Option<Dimension>,
+
+    /// The z coordinates.
+    pub z: Option<Dimension>,
+
+    /// The intensities.
+    pub intensity: Option<Dimension>,
+
+    /// The scan angles, in degrees.
+    pub scan_angle: Option<Dimension>,
+
+    /// The user data.
+    pub user_data: Option<Dimension>,
+
+    /// The point source ids.
+    pub point_source_id: Option<Dimension>,
+
+    /// The gps times, of the points that have them.
+    pub gps_time: Option<Dimension>,
+
+    /// The number of points of each classification.
+    pub classifications: BTreeMap<u8, u64>,
+
+    /// The number of points of each return number, from zero to fifteen.
+    pub return_numbers: [u64; RETURN_NUMBERS],
+
+    /// The number of points per square unit of the xy bounds, or `None` if the bounds have no
+    /// area.
+    pub density: Option<f64>,
+
+    /// The number of withheld points.
+    pub withheld: u64,
+
+    /// The number of synthetic points.
+    pub synthetic: u64,
+
+    /// The number of key-points.
+    pub key_point: u64,
+
+    /// The number of overlap points.
+    pub overlap: u64,
+
+    /// The number of points with a gps time.
+    pub with_gps_time: u64,
+
+    /// The number of points with a color.
+    pub with_color: u64,
+
+    /// The number of points with a near infrared value.
+    pub with_nir: u64,
+
+    /// The number of points with waveform information.
+    pub with_waveform: u64,
+
+    /// The number of points with extra bytes.
+    pub with_extra_bytes: u64,
+}
+
+impl Summary {
+    /// Reads every point and summarizes them.
+    ///
+    /// The reader is read from the start, and is left back at the start.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{summary::Summary, Reader};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let summary = Summary::from_reader(&mut reader).unwrap();
+    /// assert_eq!(reader.header().bounds().max.x, summary.x.unwrap().max);
+    /// assert_eq!(106, summary.with_gps_time);
+    /// ```
+    pub fn from_reader<R: Read + Seek + Send>(reader: &mut Reader<R>) -> Result<Summary> {
+        let mut summary = Summary::default();
+        let mut accumulators = [Accumulator::default(); 8];
+        let mut point = Point::default();
+        reader.seek(0)?;
+        while reader.read_point_into(&mut point)? {
+            summary.add_point(&point);
+            for (accumulator, value) in accumulators.iter_mut().zip([
+                Some(point.x),
+                Some(point.y),
+                Some(point.z),
+                Some(f64::from(point.intensity)),
+                Some(f64::from(point.scan_angle)),
+                Some(f64::from(point.user_data)),
+                Some(f64::from(point.point_source_id)),
+                point.gps_time,
+            ]) {
+                if let Some(value) = value {
+                    accumulator.add(value);
+                }
+            }
+        }
+        reader.seek(0)?;
+        let [x, y, z, intensity, scan_angle, user_data, point_source_id, gps_time] =
+            accumulators.map(|accumulator| accumulator.dimension());
+        if let (Some(x), Some(y)) = (x, y) {
+            let area = (x.max - x.min) * (y.max - y.min);
+            summary.density = (area > 0.).then(|| summary.number_of_points as f64 / area);
+        }
+        Ok(Summary {
+            x,
+            y,
+            z,
+            intensity,
+            scan_angle,
+            user_data,
+            point_source_id,
+            gps_time,
+            ..summary
+        })
+    }
+
+    fn add_point(&mut self, point: &Point) {
+        self.number_of_points += 1;
+        *self
+            .classifications
+            .entry(u8::from(point.classification))
+            .or_default() += 1;
+        if let Some(count) = self
+            .return_numbers
+            .get_mut(usize::from(point.return_number))
+        {
+            *count += 1;
+        }
+        for (count, has) in [
+            (&mut self.withheld, point.is_withheld),
+            (&mut self.synthetic, point.is_synthetic),
+            (&mut self.key_point, point.is_key_point),
+            (&mut self.overlap, point.is_overlap),
+            (&mut self.with_gps_time, point.gps_time.is_some()),
+            (&mut self.with_color, point.color.is_some()),
+            (&mut self.with_nir, point.nir.is_some()),
+            (&mut self.with_waveform, point.waveform.is_some()),
+            (&mut self.with_extra_bytes, !point.extra_bytes.is_empty()),
+        ] {
+            if has {
+                *count += 1;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Accumulator {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: u64,
+}
+
+impl Default for Accumulator {
+    fn default() -> Accumulator {
+        Accumulator {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.,
+            count: 0,
+        }
+    }
+}
+
+impl Accumulator {
+    fn add(&mut self, value: f64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn dimension(self) -> Option<Dimension> {
+        (self.count > 0).then(|| Dimension {
+            min: self.min,
+            max: self.max,
+            mean: self.sum / self.count as f64,
+        })
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "points: {}", self.number_of_points)?;
+        writeln!(f, "{:<16}{:>18}{:>18}{:>18}", "", "min", "max", "mean")?;
+        for (name, dimension) in [
+            ("x", self.x),
+            ("y", self.y),
+            ("z", self.z),
+            ("intensity", self.intensity),
+            ("scan angle", self.scan_angle),
+            ("user data", self.user_data),
+            ("point source id", self.point_source_id),
+            ("gps time", self.gps_time),
+        ] {
+            if let Some(dimension) = dimension {
+                writeln!(
+                    f,
+                    "{:<16}{:>18.3}{:>18.3}{:>18.3}",
+                    name, dimension.min, dimension.max, dimension.mean
+                )?;
+            }
+        }
+        match self.density {
+            Some(density) => writeln!(f, "density: {:.3} points per square unit", density)?,
+            None => writeln!(f, "density: none")?,
+        }
+        writeln!(f, "classifications:")?;
+        for (classification, count) in &self.classifications {
+            writeln!(f, "  {:>3}: {}", classification, count)?;
+        }
+        writeln!(f, "return numbers:")?;
+        for (return_number, count) in self.return_numbers.iter().enumerate() {
+            if *count > 0 {
+                writeln!(f, "  {:>3}: {}", return_number, count)?;
+            }
+        }
+        writeln!(
+            f,
+            "flags: {} withheld, {} synthetic, {} key-point, {} overlap",
+            self.withheld, self.synthetic, self.key_point, self.overlap
+        )?;
+        write!(
+            f,
+            "points with: {} gps time, {} color, {} nir, {} waveform, {} extra bytes",
+            self.with_gps_time,
+            self.with_color,
+            self.with_nir,
+            self.with_waveform,
+            self.with_extra_bytes
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        point::{Classification, Format},
+        synthetic::fixture,
+        Builder, Color,
+    };
+    use std::io::Cursor;
+
+    fn reader(points: Vec<Point>) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(3).unwrap();
+        fixture::reader(fixture::write(builder, points))
+    }
+
+    #[test]
+    fn summary() {
+        let point = Point {
+            gps_time: Some(1.),
+            color: Some(Color::default()),
+            ..Default::default()
+        };
+        let mut reader = reader(vec![
+            Point {
+                x: 2.,
+                y: 4.,
+                return_number: 1,
+                classification: Classification::Ground,
+                ..point.clone()
+            },
+            Point {
+                z: 3.,
+                gps_time: Some(3.),
+                return_number: 2,
+                is_withheld: true,
+                ..point
+            },
+        ]);
+        let summary = Summary::from_reader(&mut reader).unwrap();
+        assert_eq!(2, summary.number_of_points);
+        assert_eq!(
+            Some(Dimension {
+                min: 0.,
+                max: 2.,
+                mean: 1.
+            }),
+            summary.x
+        );
+        assert_eq!(2., summary.gps_time.unwrap().mean);
+        assert_eq!(Some(0.25), summary.density);
+        assert_eq!(BTreeMap::from([(0, 1), (2, 1)]), summary.classifications);
+        assert_eq!([0, 1, 1], summary.return_numbers[..3]);
+        assert_eq!(1, summary.withheld);
+        assert_eq!(2, summary.with_color);
+        assert_eq!(0, summary.with_nir);
+        assert_eq!(2., reader.read_point().unwrap().unwrap().x);
+    }
+
+    #[test]
+    fn empty() {
+        let summary = Summary::from_reader(&mut reader(Vec::new())).unwrap();
+        assert_eq!(None, summary.x);
+        assert_eq!(None, summary.density);
+        assert!(summary.to_string().starts_with("points: 0\n"));
+    }
+}
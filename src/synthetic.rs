@@ -0,0 +1,552 @@
+//! Made up point clouds, for benchmarks, examples, and tests.
+//!
+//! Every generator is seeded, and gives the same points for the same arguments. The points match
+//! [Format::default] unless [SyntheticPoints::format] picks another format, and their coordinates
+//! are whole millimetres inside the bounds, so they're read back unchanged through the default
+//! transforms.
+//!
+//! ```
+//! use las::{point::Format, synthetic, Bounds, Builder, Vector, Writer};
+//!
+//! let bounds = Bounds {
+//!     min: Vector { x: 0., y: 0., z: 100. },
+//!     max: Vector { x: 100., y: 100., z: 130. },
+//! };
+//! let format = Format::new(3).unwrap();
+//! let mut builder = Builder::from((1, 2));
+//! builder.point_format = format;
+//! let path = std::env::temp_dir().join("las-rs-synthetic-terrain.las");
+//! let mut writer = Writer::from_path(&path, builder.into_header().unwrap()).unwrap();
+//! for point in synthetic::terrain(bounds, 2., 42).format(format) {
+//!     writer.write_point(point).unwrap();
+//! }
+//! writer.close().unwrap();
+//! assert!(writer.header().number_of_points() > 20_000);
+//! # std::fs::remove_file(path).unwrap();
+//! ```
+
+use crate::{
+    point::{Classification, ExtraBytes, Format, ScanDirection},
+    raw::point::{ScanAngle, Waveform},
+    rng::Rng,
+    Bounds, Color, Point,
+};
+use std::f64::consts::TAU;
+
+/// The precision of the generated coordinates, which is also the default scale.
+const MILLIMETRE: f64 = 0.001;
+
+/// The seconds from one pulse to the next.
+const PULSE_INTERVAL: f64 = 1e-5;
+
+/// The seconds from the end of one flight strip to the start of the next.
+const STRIP_GAP: f64 = 60.;
+
+/// The widest scan angle, in degrees.
+const MAX_SCAN_ANGLE: f64 = 15.;
+
+/// An iterator over generated points.
+///
+/// Created by [terrain], [flight_strips], and [uniform].
+#[derive(Clone, Debug)]
+pub struct SyntheticPoints {
+    rng: Rng,
+    format: Format,
+    bounds: Bounds,
+    shape: Shape,
+    pulse: u64,
+    /// The rest of the current pulse's returns, last return first.
+    returns: Vec<Point>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Shape {
+    Uniform {
+        count: u64,
+    },
+    Terrain {
+        columns: u64,
+        rows: u64,
+        /// The number of flight strips, or `None` if the points don't come from strips.
+        strips: Option<u64>,
+        /// The phases of the waves that make up the ground surface.
+        phases: [f64; 4],
+    },
+}
+
+/// Returns points on a rolling surface with noise, some of them under vegetation.
+///
+/// There's one pulse for about every `1 / density` square units, in scan lines along x. Most
+/// pulses hit the ground, and some hit vegetation first and have two or three returns. A few
+/// low noise points fall below the ground. Ground, vegetation, and noise points are classified
+/// as such, and gps times, if the format has them, go up from zero in pulse order.
+///
+/// # Examples
+///
+/// ```
+/// use las::{synthetic, Bounds, Vector};
+///
+/// let bounds = Bounds {
+///     min: Vector { x: 0., y: 0., z: 0. },
+///     max: Vector { x: 10., y: 10., z: 10. },
+/// };
+/// let points: Vec<_> = synthetic::terrain(bounds, 1., 42).collect();
+/// assert!(points.len() >= 100);
+/// assert_eq!(points, synthetic::terrain(bounds, 1., 42).collect::<Vec<_>>());
+/// ```
+pub fn terrain(bounds: Bounds, density: f64, seed: u64) -> SyntheticPoints {
+    SyntheticPoints::terrain(bounds, density, None, seed)
+}
+
+/// Returns [terrain] points, flown in parallel strips along x.
+///
+/// Each strip covers a band of y, and has its own point source id, starting at one. The gps times
+/// jump by a minute between strips, the scan direction swaps from one scan line to the next, and
+/// the first and last pulse of each scan line are on the edge of the flight line.
+///
+/// # Examples
+///
+/// ```
+/// use las::{synthetic, Bounds, Vector};
+///
+/// let bounds = Bounds {
+///     min: Vector { x: 0., y: 0., z: 0. },
+///     max: Vector { x: 10., y: 10., z: 10. },
+/// };
+/// let points: Vec<_> = synthetic::flight_strips(bounds, 1., 3, 42).collect();
+/// assert_eq!(1, points.first().unwrap().point_source_id);
+/// assert_eq!(3, points.last().unwrap().point_source_id);
+/// ```
+pub fn flight_strips(bounds: Bounds, density: f64, strips: u16, seed: u64) -> SyntheticPoints {
+    SyntheticPoints::terrain(bounds, density, Some(u64::from(strips.max(1))), seed)
+}
+
+/// Returns `count` single return points, spread evenly through the bounds.
+///
+/// The points are unclassified, and gps times, if the format has them, go up from zero.
+///
+/// # Examples
+///
+/// ```
+/// use las::{synthetic, Bounds, Vector};
+///
+/// let bounds = Bounds {
+///     min: Vector { x: 0., y: 0., z: 0. },
+///     max: Vector { x: 1., y: 1., z: 1. },
+/// };
+/// assert_eq!(1000, synthetic::uniform(bounds, 1000, 42).count());
+/// ```
+pub fn uniform(bounds: Bounds, count: u64, seed: u64) -> SyntheticPoints {
+    SyntheticPoints::new(bounds, Shape::Uniform { count }, Rng::new(seed))
+}
+
+impl SyntheticPoints {
+    fn new(bounds: Bounds, shape: Shape, rng: Rng) -> SyntheticPoints {
+        SyntheticPoints {
+            rng,
+            format: Format::default(),
+            bounds,
+            shape,
+            pulse: 0,
+            returns: Vec::new(),
+        }
+    }
+
+    fn terrain(bounds: Bounds, density: f64, strips: Option<u64>, seed: u64) -> SyntheticPoints {
+        let mut rng = Rng::new(seed);
+        let phases = [rng.unit(), rng.unit(), rng.unit(), rng.unit()];
+        let (columns, rows) = if density > 0. && density.is_finite() {
+            let spacing = density.sqrt().recip();
+            let cells = |min: f64, max: f64| (((max - min) / spacing).ceil() as u64).max(1);
+            (
+                cells(bounds.min.x, bounds.max.x),
+                cells(bounds.min.y, bounds.max.y).max(strips.unwrap_or(1)),
+            )
+        } else {
+            (0, 0)
+        };
+        let shape = Shape::Terrain {
+            columns,
+            rows,
+            strips,
+            phases,
+        };
+        SyntheticPoints::new(bounds, shape, rng)
+    }
+
+    /// Generates points that match this format.
+    ///
+    /// Gps times, colors, near infrared, and waveforms are only filled in if the format has them,
+    /// and extra bytes are zeros.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Format, synthetic, Bounds, Vector};
+    ///
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 0., y: 0., z: 0. },
+    ///     max: Vector { x: 1., y: 1., z: 1. },
+    /// };
+    /// let format = Format::new(8).unwrap();
+    /// let mut points = synthetic::uniform(bounds, 10, 42).format(format);
+    /// assert!(points.all(|point| point.matches(&format)));
+    /// ```
+    pub fn format(mut self, format: Format) -> SyntheticPoints {
+        self.format = format;
+        self
+    }
+
+    fn generate_pulse(&mut self) -> bool {
+        match self.shape {
+            Shape::Uniform { count } => {
+                if self.pulse >= count {
+                    return false;
+                }
+                let (u, v, w) = (self.rng.unit(), self.rng.unit(), self.rng.unit());
+                let (x, y, z) = (
+                    self.coordinate(0, u),
+                    self.coordinate(1, v),
+                    self.coordinate(2, w),
+                );
+                let mut point = self.point(x, y, z, Classification::Unclassified, 0);
+                point.return_number = 1;
+                point.number_of_returns = 1;
+                self.returns.push(point);
+            }
+            Shape::Terrain {
+                columns,
+                rows,
+                strips,
+                phases,
+            } => {
+                if self.pulse >= columns.saturating_mul(rows) {
+                    return false;
+                }
+                let row = self.pulse / columns;
+                let left_to_right = row.is_multiple_of(2);
+                let column = if left_to_right {
+                    self.pulse % columns
+                } else {
+                    columns - 1 - self.pulse % columns
+                };
+                let u = (column as f64 + self.rng.unit()) / columns as f64;
+                let v = (row as f64 + self.rng.unit()) / rows as f64;
+                let (x, y) = (self.coordinate(0, u), self.coordinate(1, v));
+                let height = 0.5
+                    + 0.25
+                        * (TAU * (1.3 * u + phases[0])).sin()
+                        * (TAU * (0.9 * v + phases[1])).cos()
+                    + 0.15 * (TAU * (3.1 * u + 2.3 * v + phases[2])).sin()
+                    + 0.05 * (TAU * (7.7 * u - 5.3 * v + phases[3])).sin()
+                    + 0.01 * (self.rng.unit() - 0.5);
+                // The ground stays in the bottom half of the bounds, under the vegetation.
+                let ground = 0.5 * height;
+                let strip = strips.map(|strips| row * strips / rows);
+                let source_id = strip.map_or(0, |strip| strip as u16 + 1);
+                let r = self.rng.unit();
+                let mut returns = if r < 0.001 {
+                    let z = self.rng.unit() * ground;
+                    vec![(z, Classification::LowPoint)]
+                } else if r < 0.3 {
+                    let top = ground + (0.2 + 0.8 * self.rng.unit()) * (1. - ground);
+                    let mut returns = vec![(top, vegetation(top - ground))];
+                    if self.rng.unit() < 0.5 {
+                        let middle = ground + self.rng.unit() * (top - ground);
+                        returns.push((middle, vegetation(middle - ground)));
+                    }
+                    returns.push((ground, Classification::Ground));
+                    returns
+                } else {
+                    vec![(ground, Classification::Ground)]
+                };
+                let number_of_returns = returns.len() as u8;
+                let scan_angle =
+                    MAX_SCAN_ANGLE * (2. * (column as f64 + 0.5) / columns as f64 - 1.);
+                let gps_time_offset = strip.map_or(0., |strip| strip as f64 * STRIP_GAP);
+                for (i, (z, classification)) in returns.drain(..).enumerate().rev() {
+                    let z = self.coordinate(2, z);
+                    let mut point = self.point(x, y, z, classification, i as u8);
+                    point.return_number = i as u8 + 1;
+                    point.number_of_returns = number_of_returns;
+                    point.point_source_id = source_id;
+                    point.scan_angle = self.scan_angle(scan_angle);
+                    if strips.is_some() {
+                        point.scan_direction = if left_to_right {
+                            ScanDirection::LeftToRight
+                        } else {
+                            ScanDirection::RightToLeft
+                        };
+                        point.is_edge_of_flight_line = column == 0 || column == columns - 1;
+                    }
+                    if let Some(gps_time) = point.gps_time.as_mut() {
+                        *gps_time += gps_time_offset;
+                    }
+                    self.returns.push(point);
+                }
+            }
+        }
+        self.pulse += 1;
+        true
+    }
+
+    /// Returns a coordinate `fraction` of the way through the bounds, rounded to a millimetre.
+    fn coordinate(&self, axis: usize, fraction: f64) -> f64 {
+        let (min, max) = match axis {
+            0 => (self.bounds.min.x, self.bounds.max.x),
+            1 => (self.bounds.min.y, self.bounds.max.y),
+            _ => (self.bounds.min.z, self.bounds.max.z),
+        };
+        let value = ((min + fraction * (max - min)) / MILLIMETRE).round() * MILLIMETRE;
+        if value > max && value - MILLIMETRE >= min {
+            value - MILLIMETRE
+        } else if value < min && value + MILLIMETRE <= max {
+            value + MILLIMETRE
+        } else {
+            value
+        }
+    }
+
+    fn scan_angle(&self, degrees: f64) -> f32 {
+        if self.format.is_extended {
+            f32::from(ScanAngle::Scaled((degrees / 0.006).round() as i16))
+        } else {
+            degrees.round() as f32
+        }
+    }
+
+    /// Makes a point with the fields that this format has.
+    fn point(&mut self, x: f64, y: f64, z: f64, classification: Classification, i: u8) -> Point {
+        let format = self.format;
+        let is_vegetation = matches!(
+            classification,
+            Classification::LowVegetation
+                | Classification::MediumVegetation
+                | Classification::HighVegetation
+        );
+        let jitter = (self.rng.unit() * 4000.) as u16;
+        let base = if is_vegetation { 100 } else { 400 };
+        Point {
+            x,
+            y,
+            z,
+            intensity: (base + (self.rng.unit() * 400.) as u16) / (u16::from(i) + 1),
+            classification,
+            gps_time: format
+                .has_gps_time
+                .then_some(self.pulse as f64 * PULSE_INTERVAL),
+            color: format.has_color.then(|| match classification {
+                Classification::Ground => Color::new(35_000 + jitter, 28_000 + jitter, 20_000),
+                Classification::LowPoint => Color::new(30_000, 30_000, 30_000),
+                _ if is_vegetation => Color::new(15_000, 35_000 + jitter, 12_000),
+                _ => Color::new(40_000 + jitter, 40_000 + jitter, 40_000 + jitter),
+            }),
+            nir: format.has_nir.then(|| {
+                if is_vegetation {
+                    45_000 + jitter
+                } else {
+                    20_000 + jitter
+                }
+            }),
+            waveform: format.has_waveform.then(Waveform::default),
+            extra_bytes: ExtraBytes::zeroed(usize::from(format.extra_bytes)),
+            ..Default::default()
+        }
+    }
+}
+
+/// Classifies vegetation by its height above the ground, as a fraction of the bounds' height.
+fn vegetation(height: f64) -> Classification {
+    if height > 0.3 {
+        Classification::HighVegetation
+    } else if height > 0.1 {
+        Classification::MediumVegetation
+    } else {
+        Classification::LowVegetation
+    }
+}
+
+impl Iterator for SyntheticPoints {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        if self.returns.is_empty() && !self.generate_pulse() {
+            return None;
+        }
+        self.returns.pop()
+    }
+}
+
+/// In-memory files for the crate's unit tests.
+#[cfg(test)]
+pub(crate) mod fixture {
+    use crate::{
+        point::Format, writer::WriteOptions, Bounds, Builder, Point, Reader, Vector, Writer,
+    };
+    use std::{borrow::Borrow, io::Cursor};
+
+    /// The bounds of synthetic test points.
+    pub(crate) const BOUNDS: Bounds = Bounds {
+        min: Vector {
+            x: 0.,
+            y: 0.,
+            z: 0.,
+        },
+        max: Vector {
+            x: 100.,
+            y: 100.,
+            z: 10.,
+        },
+    };
+
+    /// Writes points to an in-memory file.
+    pub(crate) fn write<P: Borrow<Point>>(
+        builder: Builder,
+        points: impl IntoIterator<Item = P>,
+    ) -> Vec<u8> {
+        write_with_options(builder, WriteOptions::default(), points)
+    }
+
+    /// Writes points to an in-memory file with write options.
+    pub(crate) fn write_with_options<P: Borrow<Point>>(
+        builder: Builder,
+        options: WriteOptions,
+        points: impl IntoIterator<Item = P>,
+    ) -> Vec<u8> {
+        let mut writer = Writer::with_options(
+            Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+            options,
+        )
+        .unwrap();
+        for point in points {
+            writer.write_point_ref(point.borrow()).unwrap();
+        }
+        writer.into_inner().unwrap().into_inner()
+    }
+
+    /// Returns `count` [uniform](super::uniform) points in a format, inside [BOUNDS].
+    pub(crate) fn points(format: Format, count: u64) -> Vec<Point> {
+        super::uniform(BOUNDS, count, 42).format(format).collect()
+    }
+
+    /// Opens an in-memory file.
+    pub(crate) fn reader(bytes: impl Into<Vec<u8>>) -> Reader<Cursor<Vec<u8>>> {
+        Reader::new(Cursor::new(bytes.into())).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Builder, Vector};
+    use fixture;
+
+    fn bounds() -> Bounds {
+        Bounds {
+            min: Vector {
+                x: 1000.,
+                y: 2000.,
+                z: 10.,
+            },
+            max: Vector {
+                x: 1050.,
+                y: 2040.,
+                z: 40.,
+            },
+        }
+    }
+
+    fn generators() -> Vec<SyntheticPoints> {
+        vec![
+            terrain(bounds(), 1.5, 7),
+            flight_strips(bounds(), 1.5, 4, 7),
+            uniform(bounds(), 500, 7),
+        ]
+    }
+
+    #[test]
+    fn deterministic() {
+        for (a, b) in generators().into_iter().zip(generators()) {
+            let a: Vec<Point> = a.collect();
+            let b: Vec<Point> = b.collect();
+            assert_eq!(a.first(), b.first());
+            assert_eq!(a.last(), b.last());
+            assert_eq!(a, b);
+        }
+        let a: Vec<Point> = terrain(bounds(), 1.5, 7).collect();
+        let b: Vec<Point> = terrain(bounds(), 1.5, 8).collect();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn inside_bounds() {
+        for points in generators() {
+            for point in points {
+                assert!((1000. ..=1050.).contains(&point.x));
+                assert!((2000. ..=2040.).contains(&point.y));
+                assert!((10. ..=40.).contains(&point.z));
+            }
+        }
+    }
+
+    #[test]
+    fn terrain_returns() {
+        let points: Vec<Point> = terrain(bounds(), 1.5, 7)
+            .format(Format::new(1).unwrap())
+            .collect();
+        assert!(points.len() > 3000);
+        assert!(points.iter().any(|point| point.number_of_returns == 3));
+        for class in [
+            Classification::Ground,
+            Classification::LowVegetation,
+            Classification::HighVegetation,
+        ] {
+            assert!(points.iter().any(|point| point.classification == class));
+        }
+        for pulse in points.chunk_by(|a, b| a.gps_time == b.gps_time) {
+            let n = pulse.len() as u8;
+            for (i, point) in pulse.iter().enumerate() {
+                assert_eq!(i as u8 + 1, point.return_number);
+                assert_eq!(n, point.number_of_returns);
+            }
+            assert!(pulse.windows(2).all(|pair| pair[0].z >= pair[1].z));
+        }
+        assert!(points
+            .windows(2)
+            .all(|pair| pair[0].gps_time <= pair[1].gps_time));
+    }
+
+    #[test]
+    fn flight_strip_source_ids() {
+        let points: Vec<Point> = flight_strips(bounds(), 1.5, 4, 7)
+            .format(Format::new(1).unwrap())
+            .collect();
+        let mut source_ids: Vec<u16> = points.iter().map(|point| point.point_source_id).collect();
+        source_ids.dedup();
+        assert_eq!(vec![1, 2, 3, 4], source_ids);
+        assert!(points.iter().any(|point| point.is_edge_of_flight_line));
+    }
+
+    #[test]
+    fn roundtrip_every_format() {
+        for n in 0..=10 {
+            let mut format = Format::new(n).unwrap();
+            format.extra_bytes = 3;
+            let mut builder = Builder::from((1, 4));
+            builder.point_format = format;
+            builder.gps_time_type = crate::GpsTimeType::Standard;
+            let points: Vec<Point> = terrain(bounds(), 0.5, 7).format(format).collect();
+            assert!(points.iter().all(|point| point.matches(&format)));
+            let reader = fixture::reader(fixture::write(builder, &points));
+            assert_eq!(points, reader.into_points().unwrap());
+        }
+    }
+
+    #[test]
+    fn no_density() {
+        assert_eq!(0, terrain(bounds(), 0., 7).count());
+        assert_eq!(0, terrain(bounds(), f64::NAN, 7).count());
+        assert_eq!(0, uniform(bounds(), 0, 7).count());
+    }
+}
@@ -0,0 +1,233 @@
+//! Thin points as they're read, e.g. before processing a dense file.
+//!
+//! [Thin] adds thinning adapters to any iterator of points, like [Reader::points](crate::Reader::points):
+//!
+//! ```
+//! use las::{thin::Thin, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let points = reader
+//!     .points()
+//!     .voxel_grid(100.)
+//!     .unwrap()
+//!     .collect::<las::Result<Vec<_>>>()
+//!     .unwrap();
+//! assert!(points.len() < 106);
+//! ```
+//!
+//! Errors from the underlying iterator are passed through, and don't count as points.
+
+use crate::{rng::Rng, Error, Point, Result};
+use std::collections::HashSet;
+
+/// Thinning adapters for iterators of points.
+pub trait Thin: Iterator<Item = Result<Point>> + Sized {
+    /// Keeps every nth point, starting with the first.
+    ///
+    /// `every_nth(0)` is the same as `every_nth(1)`, which keeps every point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{thin::Thin, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(11, reader.points().every_nth(10).count());
+    /// ```
+    fn every_nth(self, n: usize) -> EveryNth<Self> {
+        EveryNth {
+            points: self,
+            n: n.max(1),
+            index: 0,
+        }
+    }
+
+    /// Keeps each point with a chance of `fraction`, picked with a seeded generator.
+    ///
+    /// The same seed always keeps the same points of the same input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{thin::Thin, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let n = reader.points().random_fraction(0.5, 42).count();
+    /// assert!(n > 0 && n < 106);
+    /// ```
+    fn random_fraction(self, fraction: f64, seed: u64) -> RandomFraction<Self> {
+        RandomFraction {
+            points: self,
+            fraction,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Keeps the first point in each cube of a grid with this cell size.
+    ///
+    /// The grid starts at the origin. One cell is remembered for each point that's kept, so
+    /// memory use grows with the number of occupied cells. Returns an error if the cell size
+    /// isn't positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{thin::Thin, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(1, reader.points().voxel_grid(1e6).unwrap().count());
+    /// assert!(reader.points().voxel_grid(0.).is_err());
+    /// ```
+    fn voxel_grid(self, cell_size: f64) -> Result<VoxelGrid<Self>> {
+        if cell_size > 0. && cell_size.is_finite() {
+            Ok(VoxelGrid {
+                points: self,
+                cell_size,
+                cells: HashSet::new(),
+            })
+        } else {
+            Err(Error::InvalidCellSize(cell_size))
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Point>>> Thin for I {}
+
+/// Keeps every nth point, see [Thin::every_nth].
+#[derive(Debug)]
+pub struct EveryNth<I> {
+    points: I,
+    n: usize,
+    index: usize,
+}
+
+impl<I: Iterator<Item = Result<Point>>> Iterator for EveryNth<I> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        loop {
+            let point = self.points.next()?;
+            if point.is_err() {
+                return Some(point);
+            }
+            let keep = self.index.is_multiple_of(self.n);
+            self.index += 1;
+            if keep {
+                return Some(point);
+            }
+        }
+    }
+}
+
+/// Keeps a random fraction of the points, see [Thin::random_fraction].
+#[derive(Debug)]
+pub struct RandomFraction<I> {
+    points: I,
+    fraction: f64,
+    rng: Rng,
+}
+
+impl<I: Iterator<Item = Result<Point>>> Iterator for RandomFraction<I> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        loop {
+            let point = self.points.next()?;
+            if point.is_err() || self.rng.unit() < self.fraction {
+                return Some(point);
+            }
+        }
+    }
+}
+
+/// Keeps one point per grid cell, see [Thin::voxel_grid].
+#[derive(Debug)]
+pub struct VoxelGrid<I> {
+    points: I,
+    cell_size: f64,
+    cells: HashSet<(i64, i64, i64)>,
+}
+
+impl<I: Iterator<Item = Result<Point>>> Iterator for VoxelGrid<I> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        loop {
+            let point = match self.points.next()? {
+                Ok(point) => point,
+                Err(err) => return Some(Err(err)),
+            };
+            let cell = |value: f64| (value / self.cell_size).floor() as i64;
+            if self
+                .cells
+                .insert((cell(point.x), cell(point.y), cell(point.z)))
+            {
+                return Some(Ok(point));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(xs: &[f64]) -> impl Iterator<Item = Result<Point>> + '_ {
+        xs.iter().map(|&x| {
+            Ok(Point {
+                x,
+                ..Default::default()
+            })
+        })
+    }
+
+    fn xs(points: impl Iterator<Item = Result<Point>>) -> Vec<f64> {
+        points.map(|point| point.unwrap().x).collect()
+    }
+
+    #[test]
+    fn every_nth() {
+        let input = [0., 1., 2., 3., 4., 5., 6.];
+        assert_eq!(vec![0., 3., 6.], xs(points(&input).every_nth(3)));
+        assert_eq!(input.to_vec(), xs(points(&input).every_nth(0)));
+    }
+
+    #[test]
+    fn random_fraction() {
+        let input: Vec<f64> = (0..10_000).map(f64::from).collect();
+        let picked = xs(points(&input).random_fraction(0.25, 42));
+        assert_eq!(picked, xs(points(&input).random_fraction(0.25, 42)));
+        assert_ne!(picked, xs(points(&input).random_fraction(0.25, 43)));
+        assert!((2300..2700).contains(&picked.len()));
+        assert!(xs(points(&input).random_fraction(0., 42)).is_empty());
+        assert_eq!(input, xs(points(&input).random_fraction(1., 42)));
+    }
+
+    #[test]
+    fn voxel_grid() {
+        let input = [0.1, 0.9, 1., -0.1, -0.9, 2.5, 1.5];
+        assert_eq!(
+            vec![0.1, 1., -0.1, 2.5],
+            xs(points(&input).voxel_grid(1.).unwrap())
+        );
+        for cell_size in [0., -1., f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                points(&input).voxel_grid(cell_size).err(),
+                Some(Error::InvalidCellSize(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn errors_pass_through() {
+        let input = vec![
+            Ok(Point::default()),
+            Err(Error::ClosedWriter),
+            Ok(Point::default()),
+        ];
+        let results: Vec<bool> = input
+            .into_iter()
+            .voxel_grid(1.)
+            .unwrap()
+            .map(|point| point.is_ok())
+            .collect();
+        assert_eq!(vec![true, false], results);
+    }
+}
@@ -0,0 +1,395 @@
+//! Partition a file's points into many files, by a grid or by an attribute.
+//!
+//! A [Tiler] reads every point once, and writes each one to the file for its [TileKey]:
+//!
+//! ```
+//! use las::{tile::{Partition, Tiler}, Reader};
+//!
+//! let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let dir = std::env::temp_dir();
+//! let tiles = Tiler::new(Partition::Classification)
+//!     .tile(reader, |key| dir.join(format!("las-rs-tile-doc-{key}.las")))
+//!     .unwrap();
+//! let total: u64 = tiles.iter().map(|(_, header)| header.number_of_points()).sum();
+//! assert_eq!(106, total);
+//! # for (key, _) in tiles {
+//! #     std::fs::remove_file(dir.join(format!("las-rs-tile-doc-{key}.las"))).unwrap();
+//! # }
+//! ```
+//!
+//! Unlike [split_by](crate::split_by), which keeps a writer open for every key, a tiler closes the
+//! least recently used file when too many are open, and appends to it again if more of its
+//! points come up.
+
+use crate::{header::SystemIdentifier, Error, Header, Point, Reader, Result, Writer};
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{BufWriter, Read, Seek},
+    path::PathBuf,
+};
+
+/// The default number of tile files that a [Tiler] keeps open at once.
+pub const DEFAULT_MAX_OPEN_FILES: usize = 64;
+
+/// How points are partitioned into tiles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Partition {
+    /// A regular grid in xy, with square tiles of this size that start at the origin.
+    Grid(f64),
+
+    /// One tile per classification.
+    Classification,
+
+    /// One tile per point source id.
+    PointSourceId,
+}
+
+/// The tile that a point belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TileKey {
+    /// A grid tile, which holds points from `column * size` to `(column + 1) * size` in x, and
+    /// the same for rows in y.
+    Grid {
+        /// The column, counting in x.
+        column: i64,
+
+        /// The row, counting in y.
+        row: i64,
+    },
+
+    /// The tile for a classification.
+    Classification(u8),
+
+    /// The tile for a point source id.
+    PointSourceId(u16),
+}
+
+impl Partition {
+    /// Returns the tile that a point belongs to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{tile::{Partition, TileKey}, Point};
+    /// let point = Point { x: 150., y: -20., ..Default::default() };
+    /// assert_eq!(TileKey::Grid { column: 1, row: -1 }, Partition::Grid(100.).key(&point));
+    /// ```
+    pub fn key(&self, point: &Point) -> TileKey {
+        match *self {
+            Partition::Grid(size) => TileKey::Grid {
+                column: (point.x / size).floor() as i64,
+                row: (point.y / size).floor() as i64,
+            },
+            Partition::Classification => TileKey::Classification(point.classification.into()),
+            Partition::PointSourceId => TileKey::PointSourceId(point.point_source_id),
+        }
+    }
+}
+
+impl fmt::Display for TileKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileKey::Grid { column, row } => write!(f, "{column}_{row}"),
+            TileKey::Classification(classification) => write!(f, "class_{classification}"),
+            TileKey::PointSourceId(point_source_id) => write!(f, "source_{point_source_id}"),
+        }
+    }
+}
+
+/// Writes a reader's points to one file per tile.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tiler {
+    partition: Partition,
+    max_open_files: usize,
+}
+
+impl Tiler {
+    /// Creates a tiler for this partition, which keeps up to [DEFAULT_MAX_OPEN_FILES] open.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::tile::{Partition, Tiler};
+    /// let tiler = Tiler::new(Partition::Grid(100.));
+    /// ```
+    pub fn new(partition: Partition) -> Tiler {
+        Tiler {
+            partition,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+        }
+    }
+
+    /// Sets how many tile files can be open at once, which is at least one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::tile::{Partition, Tiler};
+    /// let tiler = Tiler::new(Partition::PointSourceId).max_open_files(8);
+    /// ```
+    pub fn max_open_files(mut self, max_open_files: usize) -> Tiler {
+        self.max_open_files = max_open_files.max(1);
+        self
+    }
+
+    /// Reads every point and writes it to its tile's file.
+    ///
+    /// `make_path` gives the path for each tile, which is created the first time the tile comes
+    /// up. The tiles have the source's layout and vlrs, without spatial indices or copc info.
+    /// When the open file limit is reached, the least recently written tile is closed, and it's
+    /// appended to if it comes up again. Compressed files can't be appended to, so laz tiles
+    /// need a limit that's more than the number of tiles.
+    ///
+    /// Returns each tile with the header of its file, in the order the tiles first came up. If
+    /// anything fails, every open file is still closed before the error is returned. Returns
+    /// [Error::InvalidCellSize] before reading anything if a grid's tile size isn't positive and
+    /// finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{tile::{Partition, Tiler}, Reader};
+    ///
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let dir = std::env::temp_dir();
+    /// let tiles = Tiler::new(Partition::Grid(100.))
+    ///     .max_open_files(2)
+    ///     .tile(reader, |key| dir.join(format!("las-rs-tile-grid-doc-{key}.las")))
+    ///     .unwrap();
+    /// # for (key, _) in tiles {
+    /// #     std::fs::remove_file(dir.join(format!("las-rs-tile-grid-doc-{key}.las"))).unwrap();
+    /// # }
+    /// ```
+    pub fn tile<R: Read + Seek + Send>(
+        &self,
+        mut reader: Reader<R>,
+        mut make_path: impl FnMut(&TileKey) -> PathBuf,
+    ) -> Result<Vec<(TileKey, Header)>> {
+        if let Partition::Grid(size) = self.partition
+            && !(size > 0. && size.is_finite())
+        {
+            return Err(Error::InvalidCellSize(size));
+        }
+        let header = reader.header().to_derived(&SystemIdentifier::Extraction);
+        let mut tiles = Tiles {
+            tiles: Vec::new(),
+            indices: HashMap::new(),
+            clock: 0,
+        };
+        let mut result = Ok(());
+        for point in reader.points() {
+            result = point.and_then(|point| {
+                let key = self.partition.key(&point);
+                tiles
+                    .writer(key, &header, self.max_open_files, &mut make_path)?
+                    .write_point(point)
+            });
+            if result.is_err() {
+                break;
+            }
+        }
+        for tile in &mut tiles.tiles {
+            if let Err(err) = tile.close() {
+                if result.is_ok() {
+                    result = Err(err);
+                } else {
+                    log::warn!("could not close a tile: {}", err);
+                }
+            }
+        }
+        result.map(|()| {
+            tiles
+                .tiles
+                .into_iter()
+                .map(|tile| (tile.key, tile.header))
+                .collect()
+        })
+    }
+}
+
+struct Tiles {
+    tiles: Vec<Tile>,
+    indices: HashMap<TileKey, usize>,
+    clock: u64,
+}
+
+struct Tile {
+    key: TileKey,
+    path: PathBuf,
+    header: Header,
+    writer: Option<Writer<BufWriter<File>>>,
+    last_used: u64,
+}
+
+impl Tiles {
+    fn writer(
+        &mut self,
+        key: TileKey,
+        header: &Header,
+        max_open_files: usize,
+        make_path: &mut impl FnMut(&TileKey) -> PathBuf,
+    ) -> Result<&mut Writer<BufWriter<File>>> {
+        self.clock += 1;
+        let index = match self.indices.get(&key) {
+            Some(&index) => index,
+            None => {
+                self.tiles.push(Tile {
+                    key,
+                    path: make_path(&key),
+                    header: header.clone(),
+                    writer: None,
+                    last_used: 0,
+                });
+                let _ = self.indices.insert(key, self.tiles.len() - 1);
+                self.tiles.len() - 1
+            }
+        };
+        if self.tiles[index].writer.is_none() {
+            let open = self.tiles.iter().filter(|tile| tile.writer.is_some());
+            if open.count() >= max_open_files
+                && let Some(oldest) = self
+                    .tiles
+                    .iter_mut()
+                    .filter(|tile| tile.writer.is_some())
+                    .min_by_key(|tile| tile.last_used)
+            {
+                oldest.close()?;
+            }
+            let tile = &mut self.tiles[index];
+            tile.writer = Some(if tile.last_used == 0 {
+                Writer::from_path(&tile.path, header.clone())?
+            } else {
+                Writer::append(&tile.path)?
+            });
+        }
+        let tile = &mut self.tiles[index];
+        tile.last_used = self.clock;
+        Ok(tile.writer.as_mut().expect("the tile was just opened"))
+    }
+}
+
+impl Tile {
+    fn close(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            let _ = writer.close()?;
+            self.header = writer.header().clone();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Classification, synthetic::fixture, Builder};
+    use std::path::Path;
+
+    fn source(points: &[(f64, f64, u16)]) -> Reader<std::io::Cursor<Vec<u8>>> {
+        let points = points.iter().map(|&(x, y, point_source_id)| Point {
+            x,
+            y,
+            point_source_id,
+            ..Default::default()
+        });
+        fixture::reader(fixture::write(Builder::from((1, 2)), points))
+    }
+
+    fn path(name: &str, key: &TileKey) -> PathBuf {
+        std::env::temp_dir().join(format!("las-rs-tile-{name}-{key}.las"))
+    }
+
+    fn read(path: &Path) -> Vec<Point> {
+        let points = Reader::from_path(path).unwrap().into_points().unwrap();
+        std::fs::remove_file(path).unwrap();
+        points
+    }
+
+    #[test]
+    fn keys() {
+        let point = Point {
+            x: -0.5,
+            y: 250.,
+            classification: Classification::Ground,
+            point_source_id: 7,
+            ..Default::default()
+        };
+        assert_eq!(
+            TileKey::Grid { column: -1, row: 2 },
+            Partition::Grid(100.).key(&point)
+        );
+        assert_eq!(
+            TileKey::Classification(2),
+            Partition::Classification.key(&point)
+        );
+        assert_eq!(
+            TileKey::PointSourceId(7),
+            Partition::PointSourceId.key(&point)
+        );
+        assert_eq!("-1_2", TileKey::Grid { column: -1, row: 2 }.to_string());
+    }
+
+    #[test]
+    fn reopen() {
+        let points = [
+            (1., 1., 1),
+            (2., 2., 2),
+            (3., 3., 3),
+            (4., 4., 1),
+            (5., 5., 2),
+        ];
+        let tiles = Tiler::new(Partition::PointSourceId)
+            .max_open_files(1)
+            .tile(source(&points), |key| path("reopen", key))
+            .unwrap();
+        let keys: Vec<TileKey> = tiles.iter().map(|(key, _)| *key).collect();
+        assert_eq!(
+            vec![
+                TileKey::PointSourceId(1),
+                TileKey::PointSourceId(2),
+                TileKey::PointSourceId(3)
+            ],
+            keys
+        );
+        for (key, header) in tiles {
+            let TileKey::PointSourceId(id) = key else {
+                panic!("not a point source id tile");
+            };
+            let expected: Vec<f64> = points
+                .iter()
+                .filter(|point| point.2 == id)
+                .map(|point| point.0)
+                .collect();
+            assert_eq!(expected.len() as u64, header.number_of_points());
+            let xs: Vec<f64> = read(&path("reopen", &key)).iter().map(|p| p.x).collect();
+            assert_eq!(expected, xs);
+        }
+    }
+
+    #[test]
+    fn grid() {
+        let points = [(1., 1., 0), (15., 1., 0), (-1., 1., 0), (11., 2., 0)];
+        let tiles = Tiler::new(Partition::Grid(10.))
+            .tile(source(&points), |key| path("grid", key))
+            .unwrap();
+        assert_eq!(3, tiles.len());
+        assert_eq!(TileKey::Grid { column: 1, row: 0 }, tiles[1].0);
+        assert_eq!(2, tiles[1].1.number_of_points());
+        for (key, _) in tiles {
+            let _ = read(&path("grid", &key));
+        }
+    }
+
+    #[test]
+    fn invalid_size() {
+        for size in [0., -1., f64::NAN] {
+            assert!(matches!(
+                Tiler::new(Partition::Grid(size))
+                    .tile(source(&[]), |key| path("invalid", key))
+                    .unwrap_err(),
+                Error::InvalidCellSize(_)
+            ));
+        }
+    }
+}
@@ -0,0 +1,963 @@
+//! Read and write las data asynchronously, with [tokio](https://tokio.rs).
+//!
+//! The header, vlrs, and points are encoded and decoded by the same code as the synchronous
+//! [Reader](crate::Reader) and [Writer](crate::Writer), and only the reads and writes of the bytes
+//! are asynchronous:
+//!
+//! ```
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! use las::tokio::{Reader, Writer};
+//! use std::io::Cursor;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").await.unwrap();
+//! let points = reader.read_points(10).await.unwrap();
+//!
+//! let header = reader.header().clone().into_builder().into_header().unwrap();
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), header).await.unwrap();
+//! for point in points {
+//!     writer.write_point(point).await.unwrap();
+//! }
+//! let _ = writer.close().await.unwrap();
+//! # });
+//! ```
+
+#[cfg(feature = "laz")]
+use crate::{compatibility::Layout, reader::laz::CompressedChunk};
+use crate::{
+    raw,
+    reader::ReadOptions,
+    writer::{WriteOptions, WriteSummary},
+    Error, Header, Point, Result,
+};
+#[cfg(feature = "laz")]
+use laz::{laszip::ChunkTable, LazItem};
+use std::{
+    io::{ErrorKind, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+use tokio::{
+    fs::File,
+    io::{
+        AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt, BufReader,
+        BufWriter,
+    },
+};
+
+/// The most points that [Reader::read_points] allocates for before it starts reading.
+const MAX_PREALLOCATED_POINTS: usize = 1 << 20;
+
+/// The most bytes of uncompressed points that are fetched at once.
+const MAX_FETCH_LEN: u64 = 1 << 24;
+
+/// How many written bytes are held before they're written to the sink.
+const SPOOL_CAPACITY: usize = 1 << 16;
+
+/// The byte offset of the header size, which is followed by the offset to the point data.
+const HEADER_SIZE_OFFSET: usize = 94;
+
+/// Reads las data from an asynchronous source.
+///
+/// The header, vlrs, and evlrs are read when the reader is made. Compressed points are read a
+/// chunk at a time, through the chunk table, and decompressed on the calling task.
+#[allow(missing_debug_implementations)]
+pub struct Reader<R: AsyncRead + AsyncSeek + Unpin> {
+    read: R,
+    header: Header,
+    /// The length of the source.
+    len: u64,
+    /// Where the points start.
+    start: u64,
+    /// The next point to read.
+    index: u64,
+    #[cfg(feature = "laz")]
+    compressed: Option<Compressed>,
+}
+
+/// The chunks of compressed points, and the one that was decompressed last.
+#[cfg(feature = "laz")]
+struct Compressed {
+    items: Vec<LazItem>,
+    chunks: Vec<Chunk>,
+    /// The chunk that was decompressed last, and its points that haven't been read.
+    decompressed: Option<(usize, Vec<Point>)>,
+    /// The header of the extended points, if the file was written in laszip's las 1.4
+    /// compatibility mode.
+    restored: Option<(Header, Layout)>,
+}
+
+#[cfg(feature = "laz")]
+#[derive(Clone, Copy)]
+struct Chunk {
+    first_point: u64,
+    point_count: u64,
+    offset: u64,
+    byte_count: u64,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> Reader<R> {
+    /// Creates a new reader, reading the header, vlrs, and evlrs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Reader;
+    /// let file = tokio::fs::File::open("tests/data/autzen.las").await.unwrap();
+    /// let reader = Reader::new(tokio::io::BufReader::new(file)).await.unwrap();
+    /// assert_eq!(106, reader.header().number_of_points());
+    /// # });
+    /// ```
+    pub async fn new(mut read: R) -> Result<Reader<R>> {
+        let len = read.seek(SeekFrom::End(0)).await?;
+        let mut fetched = Fetched::new(len);
+        let first = fetched
+            .fetch(
+                &mut read,
+                0,
+                u64::from(crate::Version::new(1, 0).header_size()),
+            )
+            .await?;
+        let offset_to_point_data = first
+            .get(HEADER_SIZE_OFFSET + 2..HEADER_SIZE_OFFSET + 6)
+            .map_or(0, |bytes| {
+                u32::from_le_bytes(bytes.try_into().expect("four bytes"))
+            });
+        // The two bytes after the vlrs might be the point data start signature of a las 1.0 file.
+        let prefix_len = (u64::from(offset_to_point_data) + 2)
+            .max(u64::from(crate::Version::new(1, 4).header_size()));
+        let prefix = fetched.fetch(&mut read, 0, prefix_len).await?;
+        let raw_header = raw::Header::from_bytes(prefix).ok();
+        if let Some(raw_header) = &raw_header
+            && let Some(evlr) = raw_header.evlr
+            && evlr.number_of_evlrs > 0
+        {
+            // Uncompressed points can be followed by padding before the evlrs.
+            let start = evlr
+                .start_of_first_evlr
+                .min(raw_header.offset_to_end_of_points());
+            let _ = fetched
+                .fetch(&mut read, start, len.saturating_sub(start))
+                .await?;
+        }
+        let (header, _) = Header::read_from(&mut fetched, &ReadOptions::default(), true)?;
+        let start = fetched.position;
+        if header.point_format().is_compressed {
+            #[cfg(feature = "laz")]
+            {
+                let end_of_chunks = raw_header
+                    .and_then(|raw_header| raw_header.evlr)
+                    .filter(|evlr| evlr.number_of_evlrs > 0)
+                    .map_or(len, |evlr| evlr.start_of_first_evlr);
+                let compressed =
+                    Compressed::new(&mut read, fetched, &header, start, end_of_chunks).await?;
+                return Ok(Reader {
+                    read,
+                    header,
+                    len,
+                    start,
+                    index: 0,
+                    compressed: Some(compressed),
+                });
+            }
+            #[cfg(not(feature = "laz"))]
+            {
+                return Err(Error::LaszipNotEnabled);
+            }
+        }
+        Ok(Reader {
+            read,
+            header,
+            len,
+            start,
+            index: 0,
+            #[cfg(feature = "laz")]
+            compressed: None,
+        })
+    }
+
+    /// Returns this reader's header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").await.unwrap();
+    /// let header = reader.header();
+    /// # });
+    /// ```
+    pub fn header(&self) -> &Header {
+        #[cfg(feature = "laz")]
+        if let Some((header, _)) = self
+            .compressed
+            .as_ref()
+            .and_then(|compressed| compressed.restored.as_ref())
+        {
+            return header;
+        }
+        &self.header
+    }
+
+    /// Reads the next point, or returns `None` if there are no points left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").await.unwrap();
+    /// let point = reader.read_point().await.unwrap().unwrap();
+    /// # });
+    /// ```
+    pub async fn read_point(&mut self) -> Result<Option<Point>> {
+        let mut points = self.read_points(1).await?;
+        Ok(points.pop())
+    }
+
+    /// Reads up to `n` points.
+    ///
+    /// Uncompressed points are read with one read from the source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").await.unwrap();
+    /// let points = reader.read_points(42).await.unwrap();
+    /// assert_eq!(42, points.len());
+    /// # });
+    /// ```
+    pub async fn read_points(&mut self, n: u64) -> Result<Vec<Point>> {
+        let n = n.min(self.header.number_of_points().saturating_sub(self.index));
+        let mut points = Vec::with_capacity(
+            usize::try_from(n)
+                .unwrap_or(usize::MAX)
+                .min(MAX_PREALLOCATED_POINTS),
+        );
+        #[cfg(feature = "laz")]
+        if let Some(compressed) = &mut self.compressed {
+            while (points.len() as u64) < n {
+                let read = compressed
+                    .read_points(&mut self.read, &self.header, self.index, n, &mut points)
+                    .await?;
+                self.index += read;
+            }
+            return Ok(points);
+        }
+        let format = self.header.point_format();
+        let transforms = self.header.effective_transforms();
+        let record_len = u64::from(self.header.point_data_record_length()).max(1);
+        let end = self.index + n;
+        while self.index < end {
+            // The point count comes from the header, so the fetch is capped by what the source
+            // holds, and the points are fetched a bounded batch at a time.
+            let Some(offset) = self
+                .index
+                .checked_mul(record_len)
+                .and_then(|len| len.checked_add(self.start))
+            else {
+                return Err(read_failed(
+                    self.index,
+                    None,
+                    std::io::Error::from(ErrorKind::UnexpectedEof).into(),
+                ));
+            };
+            let count = (end - self.index)
+                .min((MAX_FETCH_LEN / record_len).max(1))
+                .min(self.len.saturating_sub(offset) / record_len)
+                .max(1);
+            let bytes = fetch(&mut self.read, offset, count * record_len)
+                .await
+                .map_err(|err| read_failed(self.index, Some(offset), err))?;
+            for (i, record) in bytes.chunks_exact(usize::try_from(record_len)?).enumerate() {
+                let index = self.index + i as u64;
+                let point = raw::Point::read_from(record, format)
+                    .map(|raw_point| Point::new(raw_point, transforms))
+                    .map_err(|err| read_failed(index, Some(offset + i as u64 * record_len), err))?;
+                points.push(point);
+            }
+            self.index += count;
+        }
+        Ok(points)
+    }
+
+    /// Reads all the points that are left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").await.unwrap();
+    /// assert_eq!(106, reader.into_points().await.unwrap().len());
+    /// # });
+    /// ```
+    pub async fn into_points(mut self) -> Result<Vec<Point>> {
+        self.read_points(u64::MAX).await
+    }
+
+    /// Seeks to a point, so it's the next one read.
+    ///
+    /// Nothing is read until the next point is. Seeking in compressed points decompresses the
+    /// chunk the point is in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").await.unwrap();
+    /// reader.seek(1).unwrap(); // <- seeks to the second point
+    /// let the_second_point = reader.read_point().await.unwrap().unwrap();
+    /// # });
+    /// ```
+    pub fn seek(&mut self, index: u64) -> Result<()> {
+        #[cfg(feature = "laz")]
+        if let Some(compressed) = &mut self.compressed
+            && index < self.index
+        {
+            // The points before the index have been moved out of the decompressed chunk.
+            compressed.decompressed = None;
+        }
+        self.index = index;
+        Ok(())
+    }
+
+    /// Returns the source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").await.unwrap();
+    /// let source = reader.into_inner();
+    /// # });
+    /// ```
+    pub fn into_inner(self) -> R {
+        self.read
+    }
+}
+
+impl Reader<BufReader<File>> {
+    /// Creates a new reader from a path, wrapping the file in a `BufReader`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").await.unwrap();
+    /// # });
+    /// ```
+    pub async fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>> {
+        let file = File::open(path).await?;
+        Reader::new(BufReader::new(file)).await
+    }
+}
+
+#[cfg(feature = "laz")]
+impl Compressed {
+    /// Reads the chunk table, whose offset is at the start of the points.
+    ///
+    /// The chunk table is found and checked by the same code as the synchronous reader's, which
+    /// reads it from the bytes fetched here.
+    async fn new<R: AsyncRead + AsyncSeek + Unpin>(
+        read: &mut R,
+        mut fetched: Fetched,
+        header: &Header,
+        start: u64,
+        end_of_chunks: u64,
+    ) -> Result<Compressed> {
+        let len = fetched.len;
+        let offset_size = ChunkTable::OFFSET_SIZE as u64;
+        let mut offset = read_offset(fetched.fetch(read, start, offset_size).await?);
+        if offset <= start as i64 {
+            // Like laszip, a missing offset is looked for at the end of the file.
+            let end = len.saturating_sub(offset_size);
+            offset = read_offset(fetched.fetch(read, end, offset_size).await?);
+        }
+        if let Ok(offset) = u64::try_from(offset)
+            && offset < len
+        {
+            let end = if end_of_chunks > offset {
+                end_of_chunks
+            } else {
+                len
+            };
+            let _ = fetched.fetch(read, offset, end - offset).await?;
+        }
+        fetched.position = start;
+        let chunk_table = crate::laz::read_chunk_table(&mut fetched, header)?;
+        let mut first_point = 0;
+        let mut offset = start + offset_size;
+        let chunks = chunk_table
+            .as_ref()
+            .iter()
+            .map(|entry| {
+                let chunk = Chunk {
+                    first_point,
+                    point_count: entry.point_count,
+                    offset,
+                    byte_count: entry.byte_count,
+                };
+                first_point += entry.point_count;
+                offset += entry.byte_count;
+                chunk
+            })
+            .collect();
+        Ok(Compressed {
+            items: header.laz_vlr()?.items().clone(),
+            chunks,
+            decompressed: None,
+            restored: Layout::restore(header)?,
+        })
+    }
+
+    /// Reads points from the chunk that holds `index`, until there are `n` points, returning how
+    /// many were read.
+    async fn read_points<R: AsyncRead + AsyncSeek + Unpin>(
+        &mut self,
+        read: &mut R,
+        header: &Header,
+        index: u64,
+        n: u64,
+        points: &mut Vec<Point>,
+    ) -> Result<u64> {
+        let chunk_index = self
+            .chunks
+            .partition_point(|chunk| chunk.first_point + chunk.point_count <= index);
+        let chunk = *self.chunks.get(chunk_index).ok_or_else(|| {
+            read_failed(
+                index,
+                None,
+                Error::InvalidChunkTable(format!("the chunk table ends before point {index}")),
+            )
+        })?;
+        if self
+            .decompressed
+            .as_ref()
+            .is_none_or(|&(decompressed, _)| decompressed != chunk_index)
+        {
+            let mut chunk_points = fetch(read, chunk.offset, chunk.byte_count)
+                .await
+                .and_then(|bytes| {
+                    CompressedChunk::new(self.items.clone(), bytes, chunk.point_count)
+                        .decompress(header)
+                })
+                .map_err(|err| read_failed(chunk.first_point, None, err))?;
+            if let Some((_, layout)) = &self.restored {
+                for point in &mut chunk_points {
+                    layout.restore_point(point)?;
+                }
+            }
+            self.decompressed = Some((chunk_index, chunk_points));
+        }
+        let (_, chunk_points) = self.decompressed.as_mut().expect("decompressed above");
+        let from = usize::try_from(index - chunk.first_point)?;
+        let to = usize::try_from((n - points.len() as u64).min(chunk.point_count))?
+            .saturating_add(from)
+            .min(chunk_points.len());
+        points.extend(chunk_points[from..to].iter_mut().map(std::mem::take));
+        Ok((to - from) as u64)
+    }
+}
+
+/// Reads the offset to the chunk table from its eight bytes.
+#[cfg(feature = "laz")]
+fn read_offset(bytes: &[u8]) -> i64 {
+    bytes.try_into().map(i64::from_le_bytes).unwrap_or_default()
+}
+
+/// Wraps an error from reading points, starting with the point at `index`.
+fn read_failed(index: u64, offset: Option<u64>, err: Error) -> Error {
+    Error::PointReadFailed {
+        index,
+        offset,
+        source: Box::new(err),
+    }
+}
+
+/// Reads `len` bytes at an offset.
+async fn fetch<R: AsyncRead + AsyncSeek + Unpin>(
+    read: &mut R,
+    offset: u64,
+    len: u64,
+) -> Result<Vec<u8>> {
+    let _ = read.seek(SeekFrom::Start(offset)).await?;
+    let mut bytes = vec![0; usize::try_from(len)?];
+    let _ = read.read_exact(&mut bytes).await?;
+    Ok(bytes)
+}
+
+/// The parts of a file that have been read, for the header and chunk table code, which reads
+/// synchronously.
+///
+/// Reading bytes that haven't been fetched is an error.
+struct Fetched {
+    len: u64,
+    position: u64,
+    ranges: Vec<(u64, Vec<u8>)>,
+}
+
+impl Fetched {
+    fn new(len: u64) -> Fetched {
+        Fetched {
+            len,
+            position: 0,
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Reads up to `len` bytes at an offset, or up to the end of the file, and returns them.
+    async fn fetch<R: AsyncRead + AsyncSeek + Unpin>(
+        &mut self,
+        read: &mut R,
+        offset: u64,
+        len: u64,
+    ) -> Result<&[u8]> {
+        let len = len.min(self.len.saturating_sub(offset));
+        let bytes = fetch(read, offset, len).await?;
+        self.ranges.push((offset, bytes));
+        Ok(self
+            .ranges
+            .last()
+            .map(|(_, bytes)| bytes.as_slice())
+            .unwrap_or_default())
+    }
+}
+
+impl Read for Fetched {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        let position = self.position;
+        let bytes = self
+            .ranges
+            .iter()
+            .rev()
+            .find_map(|(offset, bytes)| {
+                let start = usize::try_from(position.checked_sub(*offset)?).ok()?;
+                bytes.get(start..).filter(|bytes| !bytes.is_empty())
+            })
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("byte {position} hasn't been fetched"),
+                )
+            })?;
+        let n = buf.len().min(bytes.len());
+        buf[..n].copy_from_slice(&bytes[..n]);
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for Fetched {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = seek_target(self.position, self.len, pos)?;
+        Ok(self.position)
+    }
+}
+
+/// Writes las data to an asynchronous sink.
+///
+/// Points are encoded, and compressed, by a synchronous [Writer](crate::Writer) into memory, and
+/// written to the sink once there's enough of them. Closing writes the rest, and then the header
+/// again with the number of points and the bounds, so the sink must be able to seek.
+///
+/// A writer that's dropped without being closed leaves an incomplete file, since closing needs
+/// to write.
+#[allow(missing_debug_implementations)]
+pub struct Writer<W: AsyncWrite + AsyncSeek + Unpin> {
+    write: W,
+    /// Where the las data starts in the sink.
+    start: u64,
+    /// Where the sink is, relative to `start`.
+    position: u64,
+    writer: crate::Writer<Spool>,
+    closed: bool,
+    /// The failure that poisoned this writer, if any.
+    poisoned: Option<String>,
+}
+
+impl<W: AsyncWrite + AsyncSeek + Unpin> Writer<W> {
+    /// Creates a new writer, which writes the las data from the sink's current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Writer;
+    /// use std::io::Cursor;
+    /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default()).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn new(write: W, header: Header) -> Result<Writer<W>> {
+        Writer::with_options(write, header, WriteOptions::default()).await
+    }
+
+    /// Creates a new writer with options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::{tokio::Writer, writer::WriteOptions};
+    /// use std::io::Cursor;
+    /// let writer = Writer::with_options(
+    ///     Cursor::new(Vec::new()),
+    ///     Default::default(),
+    ///     WriteOptions::default(),
+    /// )
+    /// .await
+    /// .unwrap();
+    /// # });
+    /// ```
+    pub async fn with_options(
+        mut write: W,
+        header: Header,
+        options: WriteOptions,
+    ) -> Result<Writer<W>> {
+        let start = write.stream_position().await?;
+        let mut writer = Writer {
+            writer: crate::Writer::with_options(Spool::default(), header, options)?,
+            write,
+            start,
+            position: 0,
+            closed: false,
+            poisoned: None,
+        };
+        writer.drain().await?;
+        Ok(writer)
+    }
+
+    /// Returns the header, as it will be written when the writer closes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Writer;
+    /// use std::io::Cursor;
+    /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default()).await.unwrap();
+    /// assert_eq!(0, writer.header().number_of_points());
+    /// # });
+    /// ```
+    pub fn header(&self) -> &Header {
+        self.writer.header()
+    }
+
+    /// Writes a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Writer;
+    /// use std::io::Cursor;
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), Default::default()).await.unwrap();
+    /// writer.write_point(Default::default()).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn write_point(&mut self, point: Point) -> Result<()> {
+        self.write_point_ref(&point).await
+    }
+
+    /// Writes a point without taking ownership of it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::{tokio::Writer, Point};
+    /// use std::io::Cursor;
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), Default::default()).await.unwrap();
+    /// let point = Point::default();
+    /// writer.write_point_ref(&point).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn write_point_ref(&mut self, point: &Point) -> Result<()> {
+        self.check_poisoned()?;
+        self.writer.write_point_ref(point)?;
+        if self.writer.get_mut().pending_len() >= SPOOL_CAPACITY {
+            self.drain().await?;
+        }
+        Ok(())
+    }
+
+    /// Closes this writer, writing the rest of the points and the header, and returns a summary
+    /// of what it wrote.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Writer;
+    /// use std::io::Cursor;
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), Default::default()).await.unwrap();
+    /// let summary = writer.close().await.unwrap();
+    /// assert_eq!(0, summary.points_written);
+    /// assert!(writer.close().await.is_err());
+    /// # });
+    /// ```
+    pub async fn close(&mut self) -> Result<WriteSummary> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.check_poisoned()?;
+        let summary = self.writer.close()?;
+        self.drain().await?;
+        self.write.flush().await?;
+        self.closed = true;
+        Ok(summary)
+    }
+
+    /// Closes this writer if it isn't closed, and returns the sink, seeked to the start of the las
+    /// data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Writer;
+    /// use std::io::Cursor;
+    /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default()).await.unwrap();
+    /// let cursor = writer.into_inner().await.unwrap();
+    /// # });
+    /// ```
+    pub async fn into_inner(mut self) -> Result<W> {
+        if !self.closed {
+            let _ = self.close().await?;
+        }
+        let _ = self.write.seek(SeekFrom::Start(self.start)).await?;
+        Ok(self.write)
+    }
+
+    fn check_poisoned(&self) -> Result<()> {
+        match &self.poisoned {
+            Some(reason) => Err(Error::WriterPoisoned(reason.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the spooled bytes to the sink.
+    ///
+    /// Bytes that fail to be written are lost, so the writer is poisoned.
+    async fn drain(&mut self) -> Result<()> {
+        let pending = std::mem::take(&mut self.writer.get_mut().pending);
+        for (offset, bytes) in pending {
+            if offset != self.position
+                && let Err(err) = self.write.seek(SeekFrom::Start(self.start + offset)).await
+            {
+                return Err(self.poison(err));
+            }
+            if let Err(err) = self.write.write_all(&bytes).await {
+                return Err(self.poison(err));
+            }
+            self.position = offset + bytes.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn poison(&mut self, err: std::io::Error) -> Error {
+        self.poisoned = Some(err.to_string());
+        err.into()
+    }
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a new writer for a path, wrapping the file in a `BufWriter`.
+    ///
+    /// If the "laz" feature is enabled, the points are compressed if the path has a `.laz`
+    /// extension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use las::tokio::Writer;
+    /// let writer = Writer::from_path("/dev/null", Default::default()).await;
+    /// # });
+    /// ```
+    pub async fn from_path<P: AsRef<Path>>(
+        path: P,
+        mut header: Header,
+    ) -> Result<Writer<BufWriter<File>>> {
+        header.point_format_mut().is_compressed = crate::writer::is_laz_path(path.as_ref());
+        let file = File::create(path).await?;
+        Writer::new(BufWriter::new(file), header).await
+    }
+}
+
+/// The bytes a synchronous writer has written, and where, until they're written to the sink.
+///
+/// Seeking only moves where the next bytes go, so the header can be written again when the
+/// writer closes.
+#[derive(Debug, Default)]
+pub(crate) struct Spool {
+    position: u64,
+    len: u64,
+    /// Runs of bytes, and the offsets they start at, in the order they were written.
+    pending: Vec<(u64, Vec<u8>)>,
+}
+
+impl Spool {
+    fn pending_len(&self) -> usize {
+        self.pending.iter().map(|(_, bytes)| bytes.len()).sum()
+    }
+}
+
+impl Write for Spool {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.pending.last_mut() {
+            Some((offset, bytes)) if *offset + bytes.len() as u64 == self.position => {
+                bytes.extend_from_slice(buf)
+            }
+            _ => self.pending.push((self.position, buf.to_vec())),
+        }
+        self.position += buf.len() as u64;
+        self.len = self.len.max(self.position);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for Spool {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = seek_target(self.position, self.len, pos)?;
+        Ok(self.position)
+    }
+}
+
+/// Returns where a seek from `position` goes, in a stream of `len` bytes.
+fn seek_target(position: u64, len: u64, pos: SeekFrom) -> std::io::Result<u64> {
+    match pos {
+        SeekFrom::Start(target) => Some(target),
+        SeekFrom::Current(offset) => position.checked_add_signed(offset),
+        SeekFrom::End(offset) => len.checked_add_signed(offset),
+    }
+    .ok_or_else(|| {
+        std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "invalid seek to a negative or overflowing position",
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Reader, Writer};
+    use crate::{Builder, Error, Point};
+    use std::io::Cursor;
+
+    async fn check(path: &str) {
+        let expected = crate::Reader::from_path(path)
+            .unwrap()
+            .into_points()
+            .unwrap();
+        let mut reader = Reader::from_path(path).await.unwrap();
+        assert_eq!(
+            crate::Reader::from_path(path).unwrap().header(),
+            reader.header()
+        );
+        assert_eq!(expected[..10], reader.read_points(10).await.unwrap());
+        reader.seek(42).unwrap();
+        assert_eq!(expected[42], reader.read_point().await.unwrap().unwrap());
+        reader.seek(0).unwrap();
+        assert_eq!(expected, reader.into_points().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn read_las() {
+        check("tests/data/autzen.las").await;
+    }
+
+    #[cfg(feature = "laz")]
+    #[tokio::test]
+    async fn read_laz() {
+        check("tests/data/autzen.laz").await;
+    }
+
+    #[tokio::test]
+    async fn read_evlrs() {
+        let expected = crate::Reader::from_path("tests/data/waveform.las").unwrap();
+        let reader = Reader::from_path("tests/data/waveform.las").await.unwrap();
+        assert_eq!(expected.header().evlrs(), reader.header().evlrs());
+    }
+
+    #[tokio::test]
+    async fn read_past_the_end() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").await.unwrap();
+        reader.seek(106).unwrap();
+        assert!(reader.read_point().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn point_count_past_the_end_of_the_file() {
+        let mut bytes = std::fs::read("tests/data/autzen.las").unwrap();
+        bytes[107..111].copy_from_slice(&u32::MAX.to_le_bytes());
+        let reader = Reader::new(Cursor::new(bytes)).await.unwrap();
+        assert!(matches!(
+            reader.into_points().await,
+            Err(Error::PointReadFailed { index: 106, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn not_las() {
+        assert!(Reader::new(Cursor::new(b"not a las file".to_vec()))
+            .await
+            .is_err());
+    }
+
+    async fn roundtrip(builder: Builder) {
+        let points = (0..1000)
+            .map(|i| Point {
+                x: f64::from(i),
+                y: f64::from(i) / 2.,
+                z: 1.,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .await
+            .unwrap();
+        for point in &points {
+            writer.write_point_ref(point).await.unwrap();
+        }
+        let bytes = writer.into_inner().await.unwrap().into_inner();
+        let reader = crate::Reader::new(Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(1000, reader.header().number_of_points());
+        assert_eq!(points, reader.into_points().unwrap());
+        let reader = Reader::new(Cursor::new(bytes)).await.unwrap();
+        assert_eq!(points, reader.into_points().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn write_las() {
+        roundtrip(Builder::from((1, 4))).await;
+    }
+
+    #[cfg(feature = "laz")]
+    #[tokio::test]
+    async fn write_laz() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.is_compressed = true;
+        roundtrip(builder).await;
+    }
+
+    #[tokio::test]
+    async fn write_after_close() {
+        let mut writer = Writer::new(Cursor::new(Vec::new()), Default::default())
+            .await
+            .unwrap();
+        let _ = writer.close().await.unwrap();
+        assert!(writer.write_point(Point::default()).await.is_err());
+    }
+}
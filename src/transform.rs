@@ -1,8 +1,10 @@
 use crate::Result;
-use std::fmt;
+use core::fmt;
+use num_traits::float::FloatCore;
 
 /// A scale and an offset that transforms xyz coordinates.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transform {
     /// The scale.
     pub scale: f64,
@@ -27,7 +29,8 @@ impl Transform {
 
     /// Applies the inverse transform, and rounds the result.
     ///
-    /// Returns an error if the resultant value can't be represented as an i32.
+    /// Returns an error if the resultant value can't be represented as an i32, including if it's
+    /// NaN.
     ///
     /// # Examples
     ///
@@ -43,17 +46,9 @@ impl Transform {
     pub(crate) fn inverse_with_rounding_mode(&self, n: f64, r: RoundingMode) -> Result<i32> {
         use crate::Error;
 
-        fn round(n: f64, r: RoundingMode) -> f64 {
-            match r {
-                RoundingMode::Round => n.round(),
-                RoundingMode::Ceil => n.ceil(),
-                RoundingMode::Floor => n.floor(),
-            }
-        }
-
-        let n = round((n - self.offset) / self.scale, r);
+        let n = self.rounded_inverse(n, r);
 
-        if n > f64::from(i32::MAX) || n < f64::from(i32::MIN) {
+        if !(f64::from(i32::MIN)..=f64::from(i32::MAX)).contains(&n) {
             Err(Error::InvalidInverseTransform {
                 n,
                 transform: *self,
@@ -62,6 +57,25 @@ impl Transform {
             Ok(n as i32)
         }
     }
+
+    /// Applies the inverse transform and rounds the result, saturating at the ends of the i32
+    /// range instead of returning an error.
+    ///
+    /// Also returns whether the value was clamped. NaN is stored as zero, and counts as clamped.
+    pub(crate) fn inverse_saturating(&self, n: f64, r: RoundingMode) -> (i32, bool) {
+        let n = self.rounded_inverse(n, r);
+        let clamped = !(f64::from(i32::MIN)..=f64::from(i32::MAX)).contains(&n);
+        (n as i32, clamped)
+    }
+
+    fn rounded_inverse(&self, n: f64, r: RoundingMode) -> f64 {
+        let n = (n - self.offset) / self.scale;
+        match r {
+            RoundingMode::Round => FloatCore::round(n),
+            RoundingMode::Ceil => FloatCore::ceil(n),
+            RoundingMode::Floor => FloatCore::floor(n),
+        }
+    }
 }
 
 impl Default for Transform {
@@ -73,15 +87,39 @@ impl Default for Transform {
     }
 }
 
+/// Formats the transform as the expression that turns a stored integer `x` into a coordinate.
+///
+/// # Examples
+///
+/// ```
+/// use las::Transform;
+/// let transform = Transform { scale: 0.01, offset: 100. };
+/// assert_eq!("`0.01 * x + 100`", transform.to_string());
+/// ```
 impl fmt::Display for Transform {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "`{} * x + {}`", self.scale, self.offset)
     }
 }
 
-pub(crate) enum RoundingMode {
+/// How a coordinate is rounded to the integer that's stored.
+///
+/// # Examples
+///
+/// ```
+/// use las::{writer::WriteOptions, RoundingMode};
+/// let options = WriteOptions::default().rounding_mode(RoundingMode::Floor);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Rounds to the nearest integer, and half-way cases away from zero.
+    #[default]
     Round,
+
+    /// Rounds up.
     Ceil,
+
+    /// Rounds down.
     Floor,
 }
 
@@ -96,6 +134,19 @@ mod tests {
         assert!(transform.inverse(n).is_err());
     }
 
+    #[test]
+    fn not_finite() {
+        let transform = Transform::default();
+        for n in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(transform.inverse(n).is_err());
+        }
+        let transform = Transform {
+            scale: 0.,
+            offset: 0.,
+        };
+        assert!(transform.inverse(0.).is_err());
+    }
+
     #[test]
     fn too_small() {
         let transform = Transform::default();
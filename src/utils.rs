@@ -1,6 +1,8 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::{Error, Result};
+use core::str;
 use num_traits::Zero;
-use std::str;
 
 pub(crate) trait AsLasStr {
     fn as_las_str(&self) -> Result<&str>;
@@ -12,6 +14,29 @@ pub(crate) trait FromLasStr {
     fn from_las_str(&mut self, s: &str) -> Result<()>;
 }
 
+/// Checks that a string can be written to a las string field of `len` bytes.
+pub(crate) fn check_las_str(s: &str, len: usize) -> Result<()> {
+    if !s.is_ascii() {
+        Err(Error::NotAscii(s.to_string()))
+    } else if s.len() > len {
+        Err(Error::StringTooLong {
+            string: s.to_string(),
+            len,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Makes a string that fits in a las string field of `len` bytes, replacing characters that
+/// aren't ascii with `?` and cutting off the rest.
+pub(crate) fn to_las_string_lossy(s: &str, len: usize) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii() { c } else { '?' })
+        .take(len)
+        .collect()
+}
+
 pub(crate) fn some_or_none_if_zero<T: Zero>(n: T) -> Option<T> {
     if n.is_zero() {
         None
@@ -20,22 +45,27 @@ pub(crate) fn some_or_none_if_zero<T: Zero>(n: T) -> Option<T> {
     }
 }
 
-impl AsLasStr for &'_ [u8] {
-    fn as_las_str(&self) -> Result<&str> {
-        let s = if let Some(position) = self.iter().position(|c| *c == 0) {
-            if self[position..].iter().any(|c| *c != 0) {
-                return Err(Error::NotZeroFilled(self.to_vec()));
-            } else {
-                str::from_utf8(&self[0..position])?
-            }
-        } else {
-            str::from_utf8(self)?
-        };
-        if !s.is_ascii() {
-            Err(Error::NotAscii(s.to_string()))
+/// Returns the string in a nul-filled las string field.
+pub(crate) fn las_str(bytes: &[u8]) -> Result<&str> {
+    let s = if let Some(position) = bytes.iter().position(|c| *c == 0) {
+        if bytes[position..].iter().any(|c| *c != 0) {
+            return Err(Error::NotZeroFilled(bytes.to_vec()));
         } else {
-            Ok(s)
+            str::from_utf8(&bytes[0..position])?
         }
+    } else {
+        str::from_utf8(bytes)?
+    };
+    if !s.is_ascii() {
+        Err(Error::NotAscii(s.to_string()))
+    } else {
+        Ok(s)
+    }
+}
+
+impl AsLasStr for &'_ [u8] {
+    fn as_las_str(&self) -> Result<&str> {
+        las_str(self)
     }
 
     fn as_las_string_lossy(&self) -> String {
@@ -105,6 +135,25 @@ mod tests {
         assert!(bytes.as_mut().from_las_str("Beer!!").is_err());
     }
 
+    #[test]
+    fn check() {
+        assert!(check_las_str("Beer!", 5).is_ok());
+        assert!(matches!(
+            check_las_str("Beer!!", 5).unwrap_err(),
+            Error::StringTooLong { len: 5, .. }
+        ));
+        assert!(matches!(
+            check_las_str("Bière", 10).unwrap_err(),
+            Error::NotAscii(_)
+        ));
+    }
+
+    #[test]
+    fn lossy_to_las_string() {
+        assert_eq!("Beer!", to_las_string_lossy("Beer!!", 5));
+        assert_eq!("Bi?re", to_las_string_lossy("Bière", 5));
+    }
+
     #[test]
     fn lossy_from_not_null_filled() {
         let bytes = [65, 66, 67, 0, 68];
@@ -0,0 +1,501 @@
+//! Check a file against the las specification.
+//!
+//! [validate] reads a file's raw header and every point, and lists what doesn't follow the
+//! specification in a [ValidationReport], much like `lasvalidate`:
+//!
+//! ```
+//! use las::{validate, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let report = validate::validate(&mut reader).unwrap();
+//! assert!(report.is_valid());
+//! ```
+
+use crate::{
+    header::ReturnCountConflict, raw, Bounds, Error, Point, Reader, Result, Transform, Vector,
+};
+use std::{
+    fmt,
+    io::{ErrorKind, Read, Seek},
+};
+
+/// The highest return number that extended point formats and las 1.4 headers count points for.
+const RETURN_NUMBERS: usize = 15;
+
+/// The highest return number that legacy headers count points for.
+const LEGACY_RETURN_NUMBERS: usize = 5;
+
+/// What [validate] found.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    /// The number of points that were read.
+    pub points: u64,
+
+    /// What breaks the specification, and may make other software read the file differently.
+    pub errors: Vec<Issue>,
+
+    /// What the specification discourages, but that can still be read as intended.
+    pub warnings: Vec<Issue>,
+}
+
+impl ValidationReport {
+    /// Returns true if there are no errors, though there may be warnings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::validate::ValidationReport;
+    /// assert!(ValidationReport::default().is_valid());
+    /// ```
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// A way that a file doesn't follow the specification, see [ValidationReport].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Issue {
+    /// The header's number of points isn't the number of points in the file.
+    NumberOfPoints {
+        /// The number of points in the header.
+        header: u64,
+
+        /// The number of points that were read.
+        read: u64,
+    },
+
+    /// The header's number of points for a return number isn't the number of points in the file
+    /// with that return number.
+    NumberOfPointsByReturn {
+        /// The return number.
+        return_number: u8,
+
+        /// The number of points in the header.
+        header: u64,
+
+        /// The number of points that were read.
+        read: u64,
+    },
+
+    /// A las 1.4 header's legacy number of points isn't zero, and isn't its number of points.
+    LegacyNumberOfPoints {
+        /// The legacy number of points.
+        legacy: u64,
+
+        /// The number of points.
+        extended: u64,
+    },
+
+    /// A las 1.4 header's legacy counts of points by return disagree with its counts.
+    ReturnCountConflict(ReturnCountConflict),
+
+    /// An extended point format's header has legacy point counts, which should be zero.
+    LegacyCountsInExtendedFormat,
+
+    /// Some points are outside of the header's bounds.
+    PointsOutsideBounds {
+        /// The number of points outside of the bounds.
+        count: u64,
+
+        /// The bounds of the points.
+        actual: Bounds,
+    },
+
+    /// The header's bounds are wider than the points, by more than a scale step.
+    BoundsNotTight {
+        /// The bounds of the points.
+        actual: Bounds,
+    },
+
+    /// Some points have a return number of zero, or one that's larger than their number of
+    /// returns.
+    InvalidReturnNumbers {
+        /// The number of points.
+        count: u64,
+    },
+
+    /// A header string has bytes that aren't ascii.
+    NotAscii {
+        /// The header field, e.g. `system_identifier`.
+        field: &'static str,
+    },
+
+    /// A header string has bytes after its end that aren't zero.
+    NotZeroFilled {
+        /// The header field, e.g. `system_identifier`.
+        field: &'static str,
+    },
+
+    /// There are bytes between the vlrs and the points, so the offset to the point data is past
+    /// the end of the vlrs.
+    VlrPadding {
+        /// The number of bytes.
+        len: usize,
+    },
+
+    /// There are bytes between the points and the evlrs.
+    PointPadding {
+        /// The number of bytes.
+        len: usize,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::NumberOfPoints { header, read } => {
+                write!(f, "the header has {header} points, but {read} were read")
+            }
+            Issue::NumberOfPointsByReturn {
+                return_number,
+                header,
+                read,
+            } => write!(
+                f,
+                "the header has {header} points with return number {return_number}, but {read} were read"
+            ),
+            Issue::LegacyNumberOfPoints { legacy, extended } => write!(
+                f,
+                "the legacy number of points {legacy} is not the number of points {extended}"
+            ),
+            Issue::ReturnCountConflict(conflict) => write!(
+                f,
+                "the legacy return counts {:?} don't match the return counts {:?}",
+                conflict.legacy, conflict.extended
+            ),
+            Issue::LegacyCountsInExtendedFormat => {
+                write!(f, "the legacy point counts of an extended point format are not zero")
+            }
+            Issue::PointsOutsideBounds { count, .. } => {
+                write!(f, "{count} points are outside of the header's bounds")
+            }
+            Issue::BoundsNotTight { .. } => {
+                write!(f, "the header's bounds are wider than the points")
+            }
+            Issue::InvalidReturnNumbers { count } => {
+                write!(f, "{count} points have an invalid return number")
+            }
+            Issue::NotAscii { field } => write!(f, "the {field} is not ascii"),
+            Issue::NotZeroFilled { field } => {
+                write!(f, "the {field} is not zero filled after its end")
+            }
+            Issue::VlrPadding { len } => {
+                write!(f, "there are {len} bytes between the vlrs and the points")
+            }
+            Issue::PointPadding { len } => {
+                write!(f, "there are {len} bytes between the points and the evlrs")
+            }
+        }
+    }
+}
+
+/// Checks a reader's file against the las specification.
+///
+/// The raw header is checked for:
+///
+/// - Legacy point counts that disagree with the las 1.4 counts, or that aren't zero for an
+///   extended point format.
+/// - System identifiers and generating software that aren't ascii, or aren't zero filled.
+/// - Bytes between the vlrs and the points, or between the points and the evlrs.
+///
+/// Then every point is read, and checked against the header's number of points, its counts by
+/// return, and its bounds, which should hold every point and be no wider than a scale step past
+/// them. Points with impossible return numbers are counted too.
+///
+/// The reader is read from the start, and is left back at the start. If the file ends before the
+/// header's number of points, that's reported, but other errors reading the file are returned as
+/// errors.
+///
+/// # Examples
+///
+/// ```
+/// use las::{validate, Reader};
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let report = validate::validate(&mut reader).unwrap();
+/// assert_eq!(106, report.points);
+/// assert!(report.errors.is_empty());
+/// ```
+pub fn validate<R: Read + Seek + Send>(reader: &mut Reader<R>) -> Result<ValidationReport> {
+    let mut report = ValidationReport::default();
+    let header = reader.header().clone();
+    let mut bytes = vec![0; usize::from(header.version().header_size())];
+    reader.read_at(0, &mut bytes)?;
+    let raw_header = raw::Header::read_from(bytes.as_slice())?;
+    validate_raw_header(&raw_header, header.point_format().is_extended, &mut report);
+    if let Some(conflict) = header.return_count_conflict() {
+        report.errors.push(Issue::ReturnCountConflict(conflict));
+    }
+    if header.vlr_padding_len() > 0 {
+        report.warnings.push(Issue::VlrPadding {
+            len: header.vlr_padding_len(),
+        });
+    }
+    if !header.point_padding().is_empty() {
+        report.warnings.push(Issue::PointPadding {
+            len: header.point_padding().len(),
+        });
+    }
+
+    let transforms = *header.effective_transforms();
+    let bounds = header.bounds();
+    let mut actual = Bounds::default();
+    let mut by_return = [0; RETURN_NUMBERS];
+    let mut outside = 0;
+    let mut invalid_return_numbers = 0;
+    let mut point = Point::default();
+    reader.seek(0)?;
+    loop {
+        match reader.read_point_into(&mut point) {
+            Ok(true) => {}
+            Ok(false) => break,
+            // The header has more points than the file, which is reported below.
+            Err(Error::PointReadFailed { source, .. }) if matches!(&*source, Error::Io(err) if err.kind() == ErrorKind::UnexpectedEof) => {
+                break
+            }
+            Err(err) => return Err(err),
+        }
+        report.points += 1;
+        actual.grow(&point);
+        if !contains(&bounds, &transforms, &point) {
+            outside += 1;
+        }
+        if point.return_number == 0 || point.return_number > point.number_of_returns {
+            invalid_return_numbers += 1;
+        }
+        if let Some(count) = usize::from(point.return_number)
+            .checked_sub(1)
+            .and_then(|i| by_return.get_mut(i))
+        {
+            *count += 1;
+        }
+    }
+    reader.seek(0)?;
+
+    if report.points != header.number_of_points() {
+        report.errors.push(Issue::NumberOfPoints {
+            header: header.number_of_points(),
+            read: report.points,
+        });
+    }
+    let return_numbers = if header.point_format().is_extended || raw_header.large_file.is_some() {
+        RETURN_NUMBERS
+    } else {
+        LEGACY_RETURN_NUMBERS
+    };
+    for (i, &read) in by_return.iter().enumerate().take(return_numbers) {
+        let return_number = i as u8 + 1;
+        let count = header
+            .number_of_points_by_return(return_number)
+            .unwrap_or(0);
+        if count != read {
+            report.errors.push(Issue::NumberOfPointsByReturn {
+                return_number,
+                header: count,
+                read,
+            });
+        }
+    }
+    if outside > 0 {
+        report.errors.push(Issue::PointsOutsideBounds {
+            count: outside,
+            actual,
+        });
+    } else if report.points > 0 && !is_tight(&bounds, &transforms, &actual) {
+        report.warnings.push(Issue::BoundsNotTight { actual });
+    }
+    if invalid_return_numbers > 0 {
+        report.warnings.push(Issue::InvalidReturnNumbers {
+            count: invalid_return_numbers,
+        });
+    }
+    Ok(report)
+}
+
+fn validate_raw_header(raw_header: &raw::Header, is_extended: bool, report: &mut ValidationReport) {
+    let legacy = u64::from(raw_header.number_of_point_records);
+    if let Some(large_file) = raw_header.large_file
+        && legacy > 0
+        && legacy != large_file.number_of_point_records
+    {
+        report.errors.push(Issue::LegacyNumberOfPoints {
+            legacy,
+            extended: large_file.number_of_point_records,
+        });
+    }
+    if is_extended && (legacy > 0 || raw_header.number_of_points_by_return.iter().any(|&n| n > 0)) {
+        report.warnings.push(Issue::LegacyCountsInExtendedFormat);
+    }
+    for (field, bytes) in [
+        ("system_identifier", &raw_header.system_identifier),
+        ("generating_software", &raw_header.generating_software),
+    ] {
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        if !bytes[..end].is_ascii() {
+            report.errors.push(Issue::NotAscii { field });
+        }
+        if bytes[end..].iter().any(|&b| b != 0) {
+            report.warnings.push(Issue::NotZeroFilled { field });
+        }
+    }
+}
+
+/// Returns true if a point is inside the bounds, give or take half a scale step.
+fn contains(bounds: &Bounds, transforms: &Vector<Transform>, point: &Point) -> bool {
+    [
+        (point.x, bounds.min.x, bounds.max.x, transforms.x.scale),
+        (point.y, bounds.min.y, bounds.max.y, transforms.y.scale),
+        (point.z, bounds.min.z, bounds.max.z, transforms.z.scale),
+    ]
+    .into_iter()
+    .all(|(value, min, max, scale)| {
+        let tolerance = scale.abs() / 2.;
+        value >= min - tolerance && value <= max + tolerance
+    })
+}
+
+/// Returns true if the bounds are no more than a scale step wider than the actual bounds.
+fn is_tight(bounds: &Bounds, transforms: &Vector<Transform>, actual: &Bounds) -> bool {
+    [
+        (
+            bounds.min.x,
+            bounds.max.x,
+            actual.min.x,
+            actual.max.x,
+            transforms.x.scale,
+        ),
+        (
+            bounds.min.y,
+            bounds.max.y,
+            actual.min.y,
+            actual.max.y,
+            transforms.y.scale,
+        ),
+        (
+            bounds.min.z,
+            bounds.max.z,
+            actual.min.z,
+            actual.max.z,
+            transforms.z.scale,
+        ),
+    ]
+    .into_iter()
+    .all(|(min, max, actual_min, actual_max, scale)| {
+        actual_min - min <= scale.abs() && max - actual_max <= scale.abs()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{synthetic::fixture, Builder};
+
+    fn points() -> Vec<Point> {
+        (0..4)
+            .map(|i| Point {
+                x: f64::from(i),
+                return_number: 1,
+                number_of_returns: 1,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn write(version: (u8, u8), points: &[Point]) -> Vec<u8> {
+        fixture::write(Builder::from(version), points)
+    }
+
+    fn validate_bytes(bytes: Vec<u8>) -> ValidationReport {
+        validate(&mut fixture::reader(bytes)).unwrap()
+    }
+
+    #[test]
+    fn valid() {
+        let report = validate_bytes(write((1, 4), &points()));
+        assert_eq!(4, report.points);
+        assert!(report.is_valid());
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    }
+
+    #[test]
+    fn bounds() {
+        let mut bytes = write((1, 2), &points());
+        // The max x is the first bound.
+        bytes[179..187].copy_from_slice(&2f64.to_le_bytes());
+        let report = validate_bytes(bytes.clone());
+        assert_eq!(
+            vec![Issue::PointsOutsideBounds {
+                count: 1,
+                actual: Bounds {
+                    min: Vector {
+                        x: 0.,
+                        y: 0.,
+                        z: 0.
+                    },
+                    max: Vector {
+                        x: 3.,
+                        y: 0.,
+                        z: 0.
+                    },
+                },
+            }],
+            report.errors
+        );
+
+        bytes[179..187].copy_from_slice(&10f64.to_le_bytes());
+        let report = validate_bytes(bytes);
+        assert!(report.is_valid());
+        assert!(matches!(
+            report.warnings[..],
+            [Issue::BoundsNotTight { .. }]
+        ));
+    }
+
+    #[test]
+    fn counts() {
+        let mut bytes = write((1, 4), &points());
+        // The legacy number of points, then the first legacy count by return.
+        bytes[107..111].copy_from_slice(&5u32.to_le_bytes());
+        bytes[111..115].copy_from_slice(&5u32.to_le_bytes());
+        let report = validate_bytes(bytes);
+        assert!(report
+            .errors
+            .contains(&Issue::NumberOfPoints { header: 5, read: 4 }));
+        assert!(report.errors.contains(&Issue::LegacyNumberOfPoints {
+            legacy: 5,
+            extended: 4
+        }));
+        assert!(report.errors.contains(&Issue::NumberOfPointsByReturn {
+            return_number: 1,
+            header: 5,
+            read: 4
+        }));
+    }
+
+    #[test]
+    fn strings() {
+        let mut bytes = write((1, 2), &points());
+        // The system identifier.
+        bytes[26..58].copy_from_slice(&[0; 32]);
+        bytes[26] = 0xFF;
+        bytes[40] = b'x';
+        let report = validate_bytes(bytes);
+        assert!(report.errors.contains(&Issue::NotAscii {
+            field: "system_identifier"
+        }));
+        assert!(report.warnings.contains(&Issue::NotZeroFilled {
+            field: "system_identifier"
+        }));
+    }
+
+    #[test]
+    fn return_numbers() {
+        let mut points = points();
+        points[0].return_number = 2;
+        let report = validate_bytes(write((1, 2), &points));
+        assert!(report
+            .warnings
+            .contains(&Issue::InvalidReturnNumbers { count: 1 }));
+    }
+}
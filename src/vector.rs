@@ -1,5 +1,6 @@
 /// An xyz collection.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector<T> {
     /// X
     pub x: T,
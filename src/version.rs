@@ -1,10 +1,11 @@
 use crate::{point::Format, Error, Feature, Result};
-use std::fmt;
+use core::fmt;
 
 /// LAS version.
 ///
 /// Defaults to 1.2.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Version {
     /// The major version.
     ///
@@ -135,6 +136,14 @@ impl Default for Version {
     }
 }
 
+/// Formats the version as `<major>.<minor>`.
+///
+/// # Examples
+///
+/// ```
+/// use las::Version;
+/// assert_eq!("1.4", Version::new(1, 4).to_string());
+/// ```
 impl fmt::Display for Version {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}.{}", self.major, self.minor)
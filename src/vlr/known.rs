@@ -0,0 +1,603 @@
+//! Typed versions of the vlrs that the las specification defines.
+//!
+//! Each type converts from a [Vlr] with [TryFrom], which fails if the vlr is another kind of
+//! record or its data is invalid, and converts back into a [Vlr] with [From]:
+//!
+//! ```
+//! use las::{vlr::known::TextAreaDescription, Vlr};
+//!
+//! let vlr = Vlr::from(TextAreaDescription::new("Flown in the fall of 2010"));
+//! assert_eq!(3, vlr.record_id);
+//! let text = TextAreaDescription::try_from(&vlr).unwrap();
+//! assert_eq!("Flown in the fall of 2010", text.text);
+//! assert!(TextAreaDescription::try_from(&Vlr::default()).is_err());
+//! ```
+//!
+//! The types that can be checked when they're created do so, so converting them into a vlr can't
+//! fail.
+
+use super::{Vlr, VlrRecord};
+use crate::{
+    point::ExtraBytesDescriptor,
+    utils::{check_las_str, AsLasStr},
+    waveform::{self, WaveformPacketDescriptor},
+    Error, Result,
+};
+use std::collections::BTreeMap;
+
+/// The user id of the vlrs that the las specification defines.
+pub const USER_ID: &str = "LASF_Spec";
+
+/// The number of bytes in each entry of a classification lookup.
+const CLASSIFICATION_ENTRY_LEN: usize = 16;
+
+/// The number of bytes in a classification lookup's description.
+const CLASSIFICATION_DESCRIPTION_LEN: usize = CLASSIFICATION_ENTRY_LEN - 1;
+
+/// Descriptions of the classes used by the points, from the classification lookup vlr.
+///
+/// # Examples
+///
+/// ```
+/// use las::{vlr::known::ClassificationLookup, Vlr};
+///
+/// let lookup = ClassificationLookup::new(vec![(64, "Power line".to_string())]).unwrap();
+/// let vlr = Vlr::from(lookup);
+/// assert_eq!(16, vlr.data.len());
+/// let lookup = ClassificationLookup::try_from(&vlr).unwrap();
+/// assert_eq!(Some("Power line"), lookup.description(64));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClassificationLookup {
+    descriptions: BTreeMap<u8, String>,
+}
+
+/// A free-form description of the file's contents.
+///
+/// # Examples
+///
+/// ```
+/// use las::{vlr::known::TextAreaDescription, Builder};
+///
+/// let mut builder = Builder::from((1, 4));
+/// builder.vlrs.push(TextAreaDescription::new("Test flight").into());
+/// let header = builder.into_header().unwrap();
+/// let text = header.get_record::<TextAreaDescription>().unwrap().unwrap();
+/// assert_eq!("Test flight", text.text);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextAreaDescription {
+    /// The description.
+    pub text: String,
+}
+
+/// A vlr that has been replaced by a newer one, and should be ignored.
+///
+/// A vlr is superseded in place by changing its record id, so its description and data are kept.
+///
+/// # Examples
+///
+/// ```
+/// use las::{vlr::known::Superseded, Vlr};
+///
+/// let superseded = Superseded {
+///     description: "Old lookup".to_string(),
+///     data: vec![1, 2, 3],
+/// };
+/// let vlr = Vlr::from(superseded.clone());
+/// assert_eq!(7, vlr.record_id);
+/// assert_eq!(superseded, Superseded::try_from(&vlr).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Superseded {
+    /// The description of the superseded vlr.
+    pub description: String,
+
+    /// The data of the superseded vlr.
+    pub data: Vec<u8>,
+}
+
+/// The descriptions of the points' extra bytes, from the extra bytes vlr.
+///
+/// # Examples
+///
+/// ```
+/// use las::{
+///     point::{ExtraBytesDescriptor, ExtraBytesType},
+///     vlr::known::ExtraBytes,
+///     Vlr,
+/// };
+///
+/// let descriptor = ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32);
+/// let extra_bytes = ExtraBytes::new(vec![descriptor.clone()]).unwrap();
+/// let vlr = Vlr::from(extra_bytes);
+/// let extra_bytes = ExtraBytes::try_from(&vlr).unwrap();
+/// assert_eq!([descriptor], extra_bytes.descriptors());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtraBytes {
+    descriptors: Vec<ExtraBytesDescriptor>,
+}
+
+/// A waveform packet descriptor, with the index that points use to refer to it.
+///
+/// # Examples
+///
+/// ```
+/// use las::{
+///     vlr::known::WaveformDescriptor,
+///     waveform::WaveformPacketDescriptor,
+///     Vlr,
+/// };
+///
+/// let descriptor = WaveformPacketDescriptor {
+///     bits_per_sample: 8,
+///     compression_type: 0,
+///     number_of_samples: 3,
+///     temporal_sample_spacing: 1000,
+///     digitizer_gain: 1.,
+///     digitizer_offset: 0.,
+/// };
+/// let vlr = Vlr::from(WaveformDescriptor::new(3, descriptor).unwrap());
+/// assert_eq!(102, vlr.record_id);
+/// let waveform_descriptor = WaveformDescriptor::try_from(&vlr).unwrap();
+/// assert_eq!(3, waveform_descriptor.index());
+/// assert_eq!(&descriptor, waveform_descriptor.descriptor());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WaveformDescriptor {
+    index: u8,
+    descriptor: WaveformPacketDescriptor,
+}
+
+impl ClassificationLookup {
+    /// Creates a lookup from class descriptions, with later descriptions replacing earlier ones
+    /// for the same class.
+    ///
+    /// Returns an error if a description isn't ascii or is longer than fifteen bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::known::ClassificationLookup;
+    /// assert!(ClassificationLookup::new(vec![(2, "Ground".to_string())]).is_ok());
+    /// assert!(ClassificationLookup::new(vec![(2, "Ground, but really".to_string())]).is_err());
+    /// ```
+    pub fn new(descriptions: Vec<(u8, String)>) -> Result<ClassificationLookup> {
+        for (_, description) in &descriptions {
+            check_las_str(description, CLASSIFICATION_DESCRIPTION_LEN)?;
+        }
+        Ok(ClassificationLookup {
+            descriptions: descriptions.into_iter().collect(),
+        })
+    }
+
+    /// Returns the description of a class.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::known::ClassificationLookup;
+    /// let lookup = ClassificationLookup::new(vec![(2, "Ground".to_string())]).unwrap();
+    /// assert_eq!(Some("Ground"), lookup.description(2));
+    /// assert_eq!(None, lookup.description(3));
+    /// ```
+    pub fn description(&self, class: u8) -> Option<&str> {
+        self.descriptions.get(&class).map(String::as_str)
+    }
+
+    /// Returns the described classes and their descriptions, in class order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::known::ClassificationLookup;
+    /// let lookup =
+    ///     ClassificationLookup::new(vec![(9, "Water".to_string()), (2, "Ground".to_string())])
+    ///         .unwrap();
+    /// let classes: Vec<_> = lookup.iter().map(|(class, _)| class).collect();
+    /// assert_eq!(vec![2, 9], classes);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &str)> {
+        self.descriptions
+            .iter()
+            .map(|(&class, description)| (class, description.as_str()))
+    }
+}
+
+impl VlrRecord for ClassificationLookup {
+    const USER_ID: &'static str = USER_ID;
+    const RECORD_ID: u16 = 0;
+    const DESCRIPTION: &'static str = "Classification lookup";
+
+    /// Decodes the lookup, skipping classes with empty descriptions.
+    fn decode(data: &[u8]) -> Result<ClassificationLookup> {
+        if !data.len().is_multiple_of(CLASSIFICATION_ENTRY_LEN) {
+            return Err(Error::InvalidClassificationLookup(format!(
+                "{} bytes isn't a whole number of entries",
+                data.len()
+            )));
+        }
+        let mut descriptions = BTreeMap::new();
+        for entry in data.chunks_exact(CLASSIFICATION_ENTRY_LEN) {
+            let (class, description) = entry.split_first().expect("a non-empty entry");
+            let description = description.as_las_str()?;
+            if !description.is_empty() {
+                let _ = descriptions.insert(*class, description.to_string());
+            }
+        }
+        Ok(ClassificationLookup { descriptions })
+    }
+
+    /// Encodes an entry for each described class.
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(self.descriptions.len() * CLASSIFICATION_ENTRY_LEN);
+        for (&class, description) in &self.descriptions {
+            data.push(class);
+            data.extend(description.as_bytes());
+            data.resize(
+                data.len() + CLASSIFICATION_DESCRIPTION_LEN - description.len(),
+                0,
+            );
+        }
+        Ok(data)
+    }
+}
+
+impl TextAreaDescription {
+    /// Creates a text area description.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::known::TextAreaDescription;
+    /// assert_eq!("Test flight", TextAreaDescription::new("Test flight").text);
+    /// ```
+    pub fn new(text: impl Into<String>) -> TextAreaDescription {
+        TextAreaDescription { text: text.into() }
+    }
+}
+
+impl VlrRecord for TextAreaDescription {
+    const USER_ID: &'static str = USER_ID;
+    const RECORD_ID: u16 = 3;
+    const DESCRIPTION: &'static str = "Text area description";
+
+    /// Decodes the text, up to its null terminator if it has one.
+    fn decode(data: &[u8]) -> Result<TextAreaDescription> {
+        let len = data
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(data.len());
+        Ok(TextAreaDescription::new(std::str::from_utf8(&data[..len])?))
+    }
+
+    /// Encodes the text with a null terminator.
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(self.text.len() + 1);
+        data.extend(self.text.as_bytes());
+        data.push(0);
+        Ok(data)
+    }
+}
+
+impl Superseded {
+    /// The record id of superseded vlrs.
+    pub const RECORD_ID: u16 = 7;
+}
+
+impl ExtraBytes {
+    /// Creates the extra bytes record from descriptors.
+    ///
+    /// Returns an error if a descriptor can't be written, e.g. because its name is too long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{
+    ///     point::{ExtraBytesDescriptor, ExtraBytesType},
+    ///     vlr::known::ExtraBytes,
+    /// };
+    /// let descriptor = ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32);
+    /// assert!(ExtraBytes::new(vec![descriptor]).is_ok());
+    /// let descriptor = ExtraBytesDescriptor::new(&"a".repeat(33), ExtraBytesType::F32);
+    /// assert!(ExtraBytes::new(vec![descriptor]).is_err());
+    /// ```
+    pub fn new(descriptors: Vec<ExtraBytesDescriptor>) -> Result<ExtraBytes> {
+        let _ = ExtraBytesDescriptor::to_vlr(&descriptors)?;
+        Ok(ExtraBytes { descriptors })
+    }
+
+    /// Returns the descriptors, in the order of the points' extra bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::known::ExtraBytes;
+    /// assert!(ExtraBytes::default().descriptors().is_empty());
+    /// ```
+    pub fn descriptors(&self) -> &[ExtraBytesDescriptor] {
+        &self.descriptors
+    }
+
+    /// Returns the descriptors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::known::ExtraBytes;
+    /// assert!(ExtraBytes::default().into_descriptors().is_empty());
+    /// ```
+    pub fn into_descriptors(self) -> Vec<ExtraBytesDescriptor> {
+        self.descriptors
+    }
+}
+
+impl VlrRecord for ExtraBytes {
+    const USER_ID: &'static str = ExtraBytesDescriptor::USER_ID;
+    const RECORD_ID: u16 = ExtraBytesDescriptor::RECORD_ID;
+    const DESCRIPTION: &'static str = "Extra bytes";
+
+    fn decode(data: &[u8]) -> Result<ExtraBytes> {
+        Ok(ExtraBytes {
+            descriptors: ExtraBytesDescriptor::read_all(data)?,
+        })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        Ok(ExtraBytesDescriptor::to_vlr(&self.descriptors)?.data)
+    }
+}
+
+impl WaveformDescriptor {
+    /// Creates a waveform descriptor for a descriptor index.
+    ///
+    /// Returns an error if the index is zero, which means a point has no waveform.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{vlr::known::WaveformDescriptor, waveform::WaveformPacketDescriptor};
+    /// let descriptor = WaveformPacketDescriptor {
+    ///     bits_per_sample: 8,
+    ///     compression_type: 0,
+    ///     number_of_samples: 3,
+    ///     temporal_sample_spacing: 1000,
+    ///     digitizer_gain: 1.,
+    ///     digitizer_offset: 0.,
+    /// };
+    /// assert!(WaveformDescriptor::new(1, descriptor).is_ok());
+    /// assert!(WaveformDescriptor::new(0, descriptor).is_err());
+    /// ```
+    pub fn new(index: u8, descriptor: WaveformPacketDescriptor) -> Result<WaveformDescriptor> {
+        if index == 0 {
+            return Err(Error::WaveformPacketDescriptorNotFound(index));
+        }
+        Ok(WaveformDescriptor { index, descriptor })
+    }
+
+    /// Returns the descriptor index, as used by
+    /// [Waveform::wave_packet_descriptor_index](crate::raw::point::Waveform::wave_packet_descriptor_index).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{vlr::known::WaveformDescriptor, Reader};
+    /// let reader = Reader::from_path("tests/data/waveform-internal.las").unwrap();
+    /// let vlr = reader.header().vlrs().iter().find(|vlr| vlr.record_id == 101).unwrap();
+    /// assert_eq!(2, WaveformDescriptor::try_from(vlr).unwrap().index());
+    /// ```
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Returns the waveform packet descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{vlr::known::WaveformDescriptor, Reader};
+    /// let reader = Reader::from_path("tests/data/waveform-internal.las").unwrap();
+    /// let vlr = reader.header().vlrs().iter().find(|vlr| vlr.record_id == 101).unwrap();
+    /// let descriptor = WaveformDescriptor::try_from(vlr).unwrap();
+    /// assert_eq!(16, descriptor.descriptor().bits_per_sample);
+    /// ```
+    pub fn descriptor(&self) -> &WaveformPacketDescriptor {
+        &self.descriptor
+    }
+}
+
+/// Returns the error for a vlr that isn't the expected kind of record.
+fn unexpected(vlr: &Vlr, expected: &'static str) -> Error {
+    Error::UnexpectedVlr {
+        user_id: vlr.user_id.clone(),
+        record_id: vlr.record_id,
+        expected,
+    }
+}
+
+/// Decodes a record from a vlr, checking that it's the right kind.
+fn decode<T: VlrRecord>(vlr: &Vlr, expected: &'static str) -> Result<T> {
+    if T::matches(vlr) {
+        T::decode(&vlr.data)
+    } else {
+        Err(unexpected(vlr, expected))
+    }
+}
+
+/// Encodes a record that was checked when it was created.
+fn encode<T: VlrRecord>(record: &T) -> Vlr {
+    record
+        .to_vlr()
+        .expect("the record was checked when it was created")
+}
+
+impl TryFrom<&Vlr> for ClassificationLookup {
+    type Error = Error;
+
+    fn try_from(vlr: &Vlr) -> Result<ClassificationLookup> {
+        decode(vlr, "classification lookup")
+    }
+}
+
+impl From<ClassificationLookup> for Vlr {
+    fn from(lookup: ClassificationLookup) -> Vlr {
+        encode(&lookup)
+    }
+}
+
+impl TryFrom<&Vlr> for TextAreaDescription {
+    type Error = Error;
+
+    fn try_from(vlr: &Vlr) -> Result<TextAreaDescription> {
+        decode(vlr, "text area description")
+    }
+}
+
+impl From<TextAreaDescription> for Vlr {
+    fn from(text: TextAreaDescription) -> Vlr {
+        encode(&text)
+    }
+}
+
+impl TryFrom<&Vlr> for Superseded {
+    type Error = Error;
+
+    fn try_from(vlr: &Vlr) -> Result<Superseded> {
+        if vlr.user_id == USER_ID && vlr.record_id == Superseded::RECORD_ID {
+            Ok(Superseded {
+                description: vlr.description.clone(),
+                data: vlr.data.clone(),
+            })
+        } else {
+            Err(unexpected(vlr, "superseded"))
+        }
+    }
+}
+
+impl From<Superseded> for Vlr {
+    fn from(superseded: Superseded) -> Vlr {
+        Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: Superseded::RECORD_ID,
+            description: superseded.description,
+            data: superseded.data,
+            reserved: None,
+        }
+    }
+}
+
+impl TryFrom<&Vlr> for ExtraBytes {
+    type Error = Error;
+
+    fn try_from(vlr: &Vlr) -> Result<ExtraBytes> {
+        decode(vlr, "extra bytes")
+    }
+}
+
+impl From<ExtraBytes> for Vlr {
+    fn from(extra_bytes: ExtraBytes) -> Vlr {
+        encode(&extra_bytes)
+    }
+}
+
+impl TryFrom<&Vlr> for WaveformDescriptor {
+    type Error = Error;
+
+    fn try_from(vlr: &Vlr) -> Result<WaveformDescriptor> {
+        let descriptor = WaveformPacketDescriptor::from_vlr(vlr)
+            .ok_or_else(|| unexpected(vlr, "waveform packet descriptor"))??;
+        let index = vlr.record_id - waveform::FIRST_DESCRIPTOR_RECORD_ID + 1;
+        WaveformDescriptor::new(u8::try_from(index)?, descriptor)
+    }
+}
+
+impl From<WaveformDescriptor> for Vlr {
+    fn from(waveform_descriptor: WaveformDescriptor) -> Vlr {
+        waveform_descriptor
+            .descriptor
+            .to_vlr(waveform_descriptor.index)
+            .expect("the index isn't zero")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::ExtraBytesType;
+
+    #[test]
+    fn classification_lookup() {
+        let lookup = ClassificationLookup::new(vec![
+            (2, "Ground".to_string()),
+            (9, "Water".to_string()),
+            (2, "Bare earth".to_string()),
+            (10, String::new()),
+        ])
+        .unwrap();
+        let vlr = Vlr::from(lookup.clone());
+        assert_eq!(48, vlr.data.len());
+        assert_eq!(9, vlr.data[16]);
+        assert_eq!(b"Water\0", &vlr.data[17..23]);
+        let decoded = ClassificationLookup::try_from(&vlr).unwrap();
+        assert_eq!(Some("Bare earth"), decoded.description(2));
+        assert_eq!(None, decoded.description(10));
+
+        let mut vlr = vlr;
+        let _ = vlr.data.pop();
+        assert!(matches!(
+            ClassificationLookup::try_from(&vlr),
+            Err(Error::InvalidClassificationLookup(_))
+        ));
+        assert!(ClassificationLookup::new(vec![(1, "é".to_string())]).is_err());
+    }
+
+    #[test]
+    fn text_area_description() {
+        let vlr = Vlr::from(TextAreaDescription::new("hello"));
+        assert_eq!(b"hello\0", vlr.data.as_slice());
+        let vlr = Vlr {
+            data: b"no terminator".to_vec(),
+            ..vlr
+        };
+        assert_eq!(
+            "no terminator",
+            TextAreaDescription::try_from(&vlr).unwrap().text
+        );
+    }
+
+    #[test]
+    fn extra_bytes() {
+        let mut descriptor = ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::U16);
+        descriptor.scale = Some(0.1);
+        let vlr = Vlr::from(ExtraBytes::new(vec![descriptor.clone()]).unwrap());
+        assert_eq!(
+            vec![descriptor],
+            ExtraBytes::try_from(&vlr).unwrap().into_descriptors()
+        );
+        let vlr = Vlr {
+            data: vec![0; 10],
+            ..vlr
+        };
+        assert!(matches!(
+            ExtraBytes::try_from(&vlr),
+            Err(Error::InvalidExtraBytesVlr(_))
+        ));
+    }
+
+    #[test]
+    fn unexpected_vlr() {
+        let vlr = Vlr::from(TextAreaDescription::new("hello"));
+        assert!(matches!(
+            ClassificationLookup::try_from(&vlr),
+            Err(Error::UnexpectedVlr {
+                record_id: 3,
+                expected: "classification lookup",
+                ..
+            })
+        ));
+        assert!(Superseded::try_from(&vlr).is_err());
+        assert!(ExtraBytes::try_from(&vlr).is_err());
+        assert!(WaveformDescriptor::try_from(&vlr).is_err());
+    }
+}
@@ -0,0 +1,1289 @@
+//! Variable length records are used to store additional metadata not defined in the header.
+//!
+//! Variable length records (VLRs) can be "regular" or "extended". "Regular" vlrs are stored right
+//! after the header, before the point records. "Extended" vlrs (EVLRs) are stored at the end of
+//! the file, after the point records.
+//!
+//! Vlrs contain arbitrary data:
+//!
+//! ```
+//! use las::Vlr;
+//! let mut vlr = Vlr::default();
+//! vlr.user_id = "gadomski".to_string();
+//! vlr.record_id = 42;
+//! vlr.description = "Some really important data".to_string();
+//! vlr.data = vec![1, 2, 3];
+//! ```
+//!
+//! ```
+//! use las::{Vlr, Builder};
+//! let mut builder = Builder::from((1, 4));
+//!
+//! builder.evlrs.push(Vlr::default());
+//! let header = builder.clone().into_header().unwrap();
+//! assert_eq!(1, header.evlrs().len());
+//!
+//! builder.version = (1, 2).into(); // las 1.2 doesn't support evlrs
+//! let header = builder.into_header().unwrap();
+//! assert_eq!(0, header.evlrs().len());
+//! assert_eq!(1, header.vlrs().len());
+//! ```
+
+use crate::{raw, Error, Result, Version};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+pub mod known;
+
+const REGULAR_HEADER_SIZE: usize = 54;
+const EXTENDED_HEADER_SIZE: usize = 60;
+const LASZIP_USER_ID: &str = "laszip encoded";
+const LASZIP_RECORD_ID: u16 = 22204;
+const LASZIP_VARIABLE_CHUNK_SIZE: u32 = u32::MAX;
+const DESCRIPTION_LEN: usize = 32;
+/// The reserved value of vlrs in las 1.0 and 1.1.
+const RECORD_SIGNATURE: u16 = 0xAABB;
+
+/// The user id of the vlrs that this crate defines.
+pub const LAS_RS_USER_ID: &str = "las-rs";
+
+/// A variable length record.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vlr {
+    /// The user that created this record.
+    ///
+    /// This value is often an official, "registered" user_id, such as "LASF_Spec" or
+    /// "LASF_Projection".
+    pub user_id: String,
+
+    /// This value specifies the type of record, and depends on the user id.
+    pub record_id: u16,
+
+    /// Textual description of these data.
+    pub description: String,
+
+    /// The data themselves.
+    pub data: Vec<u8>,
+
+    /// The two reserved bytes before the user id, if they aren't what the version expects.
+    ///
+    /// Las 1.0 and 1.1 put `0xAABB` here, and later versions zero. `None` is written as the value
+    /// the version expects, see [Vlr::reserved_for]. Reading a file keeps any other value, so it's
+    /// written back as it was read.
+    pub reserved: Option<u16>,
+}
+
+impl Vlr {
+    /// Creates a vlr from a raw vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Vlr, raw};
+    /// let raw_vlr = raw::Vlr::default();
+    /// let vlr = Vlr::new(raw_vlr);
+    /// ```
+    pub fn new(raw_vlr: raw::Vlr) -> Vlr {
+        use crate::utils::AsLasStr;
+        Vlr {
+            user_id: raw_vlr.user_id.as_ref().as_las_string_lossy(),
+            record_id: raw_vlr.record_id,
+            description: crate::utils::to_las_string_lossy(
+                &raw_vlr.description.as_ref().as_las_string_lossy(),
+                DESCRIPTION_LEN,
+            ),
+            data: raw_vlr.data,
+            reserved: Some(raw_vlr.reserved),
+        }
+    }
+
+    /// Creates a vlr from a raw vlr read from a file of a version, keeping its reserved bytes only
+    /// if they aren't what the version expects.
+    pub(crate) fn read_in(raw_vlr: raw::Vlr, version: Version) -> Vlr {
+        let mut vlr = Vlr::new(raw_vlr);
+        if vlr.reserved == Some(Vlr::default().reserved_for(version)) {
+            vlr.reserved = None;
+        }
+        vlr
+    }
+
+    /// Converts this vlr to a raw vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    /// let raw_vlr =  Vlr::default().into_raw(false).unwrap();
+    /// let raw_evlr =  Vlr::default().into_raw(true).unwrap();
+    /// ```
+    pub fn into_raw(self, is_extended: bool) -> Result<raw::Vlr> {
+        use crate::utils::FromLasStr;
+
+        let mut user_id = [0; 16];
+        user_id.as_mut().from_las_str(&self.user_id)?;
+        self.verify_description()?;
+        let mut description = [0; DESCRIPTION_LEN];
+        description.as_mut().from_las_str(&self.description)?;
+        Ok(raw::Vlr {
+            reserved: self.reserved.unwrap_or(0),
+            user_id,
+            record_id: self.record_id,
+            record_length_after_header: self.record_length_after_header(is_extended)?,
+            description,
+            data: self.data,
+        })
+    }
+
+    /// Returns the reserved bytes to write for this vlr in a file of a version.
+    ///
+    /// A vlr that was read keeps its value, and a new one gets `0xAABB` for las 1.0 and 1.1, and
+    /// zero for later versions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Version, Vlr};
+    /// let mut vlr = Vlr::default();
+    /// assert_eq!(0xAABB, vlr.reserved_for(Version::new(1, 1)));
+    /// assert_eq!(0, vlr.reserved_for(Version::new(1, 2)));
+    /// vlr.reserved = Some(42);
+    /// assert_eq!(42, vlr.reserved_for(Version::new(1, 2)));
+    /// ```
+    pub fn reserved_for(&self, version: Version) -> u16 {
+        self.reserved.unwrap_or(if version <= Version::new(1, 1) {
+            RECORD_SIGNATURE
+        } else {
+            0
+        })
+    }
+
+    /// Sets the description, if it's ascii and fits in 32 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    /// let mut vlr = Vlr::default();
+    /// vlr.set_description("Some really important data").unwrap();
+    /// assert!(vlr.set_description(&"a".repeat(33)).is_err());
+    /// assert!(vlr.set_description("Données").is_err());
+    /// assert_eq!("Some really important data", vlr.description);
+    /// ```
+    pub fn set_description(&mut self, description: &str) -> Result<()> {
+        crate::utils::check_las_str(description, DESCRIPTION_LEN)?;
+        self.description = description.to_string();
+        Ok(())
+    }
+
+    /// Sets the description, replacing characters that aren't ascii with `?` and cutting it off
+    /// at 32 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    /// let mut vlr = Vlr::default();
+    /// vlr.set_description_lossy("Données");
+    /// assert_eq!("Donn?es", vlr.description);
+    /// vlr.set_description_lossy(&"a".repeat(33));
+    /// assert_eq!(32, vlr.description.len());
+    /// ```
+    pub fn set_description_lossy(&mut self, description: &str) {
+        self.description = crate::utils::to_las_string_lossy(description, DESCRIPTION_LEN);
+    }
+
+    /// Checks that the description can be written, naming this vlr in the error if it can't.
+    pub(crate) fn verify_description(&self) -> Result<()> {
+        crate::utils::check_las_str(&self.description, DESCRIPTION_LEN).map_err(|source| {
+            Error::InvalidVlrDescription {
+                user_id: self.user_id.clone(),
+                record_id: self.record_id,
+                source: Box::new(source),
+            }
+        })
+    }
+
+    /// Returns the total length of this vlr, header and data.
+    ///
+    /// Regular vlr headers are 54 bytes, and extended ones are 60.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    ///
+    /// let mut vlr = Vlr::default();
+    /// assert_eq!(54, vlr.total_len(false));
+    /// assert_eq!(60, vlr.total_len(true));
+    /// vlr.data = vec![0];
+    /// assert_eq!(55, vlr.total_len(false));
+    /// ```
+    pub fn total_len(&self, is_extended: bool) -> usize {
+        self.data.len()
+            + if is_extended {
+                EXTENDED_HEADER_SIZE
+            } else {
+                REGULAR_HEADER_SIZE
+            }
+    }
+
+    /// Returns the total length of this vlr, header and data.
+    ///
+    /// This is the same as [Vlr::total_len].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    /// assert_eq!(54, Vlr::default().len(false));
+    /// ```
+    pub fn len(&self, is_extended: bool) -> usize {
+        self.total_len(is_extended)
+    }
+
+    /// Returns true if the data of this vlr is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    /// let mut vlr = Vlr::default();
+    /// assert!(vlr.is_empty());
+    /// vlr.data = vec![42];
+    /// assert!(!vlr.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns true if this vlr *must* be extended due to large data size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    /// use std::u16;
+    /// let mut vlr = Vlr::default();
+    /// assert!(!vlr.has_large_data());
+    /// vlr.data = vec![0; u16::MAX as usize + 1 ];
+    /// assert!(vlr.has_large_data());
+    pub fn has_large_data(&self) -> bool {
+        self.data.len() > u16::MAX as usize
+    }
+
+    /// Makes this record an evlr, which always works.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    /// let vlr = Vlr::default();
+    /// assert_eq!(vlr.clone(), vlr.into_evlr().0);
+    /// ```
+    pub fn into_evlr(self) -> Evlr {
+        Evlr(self)
+    }
+
+    /// Returns true if this record can be stored as a regular vlr.
+    ///
+    /// Its data has to fit in a vlr, and the waveform data packets have no place outside an evlr.
+    pub(crate) fn can_be_vlr(&self) -> bool {
+        let is_waveform_data = self.user_id == crate::waveform::USER_ID
+            && self.record_id == crate::waveform::RECORD_ID;
+        !self.has_large_data() && !is_waveform_data
+    }
+
+    /// Check if the vlr is a projection (coordinate reference system) VLR
+    pub fn is_projection(&self) -> bool {
+        matches!(
+            (self.user_id.to_lowercase().as_str(), self.record_id),
+            ("lasf_projection", 2112 | 34735 | 34736 | 34737)
+        )
+    }
+
+    fn record_length_after_header(&self, is_extended: bool) -> Result<raw::vlr::RecordLength> {
+        if is_extended {
+            Ok(raw::vlr::RecordLength::Evlr(self.data.len() as u64))
+        } else if self.data.len() > u16::MAX as usize {
+            Err(Error::VlrTooLong(self.data.len()))
+        } else {
+            Ok(raw::vlr::RecordLength::Vlr(self.data.len() as u16))
+        }
+    }
+}
+
+/// An extended variable length record, which goes after the points.
+///
+/// An evlr has the same fields as a [Vlr], but its data can be longer. Headers and builders keep
+/// their evlrs as plain [Vlr]s, so this wraps one while it's moved between the two.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Evlr(pub Vlr);
+
+impl Evlr {
+    /// Makes this evlr a regular vlr, or returns it unchanged if it can't be one.
+    ///
+    /// Evlrs with more than `u16::MAX` bytes of data don't fit in a vlr, and neither do the
+    /// waveform data packets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    ///
+    /// let mut vlr = Vlr::default();
+    /// assert!(vlr.clone().into_evlr().try_into_vlr().is_ok());
+    /// vlr.data = vec![0; usize::from(u16::MAX) + 1];
+    /// let evlr = vlr.into_evlr().try_into_vlr().unwrap_err();
+    /// assert_eq!(usize::from(u16::MAX) + 1, evlr.0.data.len());
+    /// ```
+    pub fn try_into_vlr(self) -> std::result::Result<Vlr, Evlr> {
+        if self.0.can_be_vlr() {
+            Ok(self.0)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// A typed record that's stored in a vlr or evlr with a known user id and record id.
+///
+/// Implement this for your own records to look them up with [Header::get_record] and to add them
+/// with [Builder::set_record].
+///
+/// # Examples
+///
+/// ```
+/// use las::{vlr::VlrRecord, Builder, Reader, Result, Writer};
+/// use std::io::Cursor;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Calibration {
+///     gains: Vec<u8>,
+/// }
+///
+/// impl VlrRecord for Calibration {
+///     const USER_ID: &'static str = "my sensor";
+///     const RECORD_ID: u16 = 1;
+///     const DESCRIPTION: &'static str = "Calibration table";
+///
+///     fn decode(data: &[u8]) -> Result<Calibration> {
+///         Ok(Calibration { gains: data.to_vec() })
+///     }
+///
+///     fn encode(&self) -> Result<Vec<u8>> {
+///         Ok(self.gains.clone())
+///     }
+/// }
+///
+/// let calibration = Calibration { gains: vec![1, 2, 3] };
+/// let mut builder = Builder::from((1, 4));
+/// builder.set_record(&calibration).unwrap();
+/// let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+/// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+/// assert_eq!(Some(calibration), reader.header().get_record::<Calibration>().unwrap());
+/// ```
+///
+/// [Header::get_record]: crate::Header::get_record
+/// [Builder::set_record]: crate::Builder::set_record
+pub trait VlrRecord: Sized {
+    /// The user id of the vlrs that hold this record.
+    const USER_ID: &'static str;
+
+    /// The record id of the vlrs that hold this record.
+    const RECORD_ID: u16;
+
+    /// The description that [Builder::set_record](crate::Builder::set_record) gives the vlr.
+    const DESCRIPTION: &'static str = "";
+
+    /// Decodes this record from a vlr's data.
+    fn decode(data: &[u8]) -> Result<Self>;
+
+    /// Encodes this record as a vlr's data.
+    fn encode(&self) -> Result<Vec<u8>>;
+
+    /// Returns true if the vlr holds this kind of record.
+    fn matches(vlr: &Vlr) -> bool {
+        vlr.user_id == Self::USER_ID && vlr.record_id == Self::RECORD_ID
+    }
+
+    /// Encodes this record into a new vlr.
+    fn to_vlr(&self) -> Result<Vlr> {
+        Ok(Vlr {
+            user_id: Self::USER_ID.to_string(),
+            record_id: Self::RECORD_ID,
+            description: Self::DESCRIPTION.to_string(),
+            data: self.encode()?,
+            reserved: None,
+        })
+    }
+}
+
+/// Labels for the values of the points' [user data](crate::Point::user_data).
+///
+/// Scanners often put their own data in the user data byte, e.g. a channel or an amplitude class.
+/// This record, under [LAS_RS_USER_ID], says what each value means, so the file describes itself.
+/// Each label is stored as its value, the label's length as a little-endian `u16`, and the label
+/// as utf-8.
+///
+/// # Examples
+///
+/// ```
+/// use las::{vlr::UserDataDictionary, Builder};
+///
+/// let mut builder = Builder::from((1, 4));
+/// builder
+///     .user_data_dictionary(vec![(1, "left".to_string()), (2, "right".to_string())])
+///     .unwrap();
+/// let header = builder.into_header().unwrap();
+/// let dictionary = header.user_data_dictionary().unwrap().unwrap();
+/// assert_eq!(Some("right"), dictionary.label(2));
+/// assert_eq!(None, dictionary.label(3));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UserDataDictionary {
+    /// The label for each user data value.
+    pub labels: BTreeMap<u8, String>,
+}
+
+impl UserDataDictionary {
+    /// Creates a dictionary from labels, with later labels replacing earlier ones for the same
+    /// value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::UserDataDictionary;
+    /// let dictionary = UserDataDictionary::new(vec![(1, "a".to_string()), (1, "b".to_string())]);
+    /// assert_eq!(Some("b"), dictionary.label(1));
+    /// ```
+    pub fn new(labels: Vec<(u8, String)>) -> UserDataDictionary {
+        UserDataDictionary {
+            labels: labels.into_iter().collect(),
+        }
+    }
+
+    /// Returns the label for a user data value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::UserDataDictionary;
+    /// let dictionary = UserDataDictionary::new(vec![(7, "channel 7".to_string())]);
+    /// assert_eq!(Some("channel 7"), dictionary.label(7));
+    /// ```
+    pub fn label(&self, value: u8) -> Option<&str> {
+        self.labels.get(&value).map(String::as_str)
+    }
+}
+
+impl VlrRecord for UserDataDictionary {
+    const USER_ID: &'static str = LAS_RS_USER_ID;
+    const RECORD_ID: u16 = 1;
+    const DESCRIPTION: &'static str = "User data dictionary";
+
+    fn decode(mut data: &[u8]) -> Result<UserDataDictionary> {
+        let invalid = |message: &str| Error::InvalidUserDataDictionary(message.to_string());
+        let mut labels = BTreeMap::new();
+        while let Some((&value, rest)) = data.split_first() {
+            let (len, rest) = rest
+                .split_first_chunk::<2>()
+                .ok_or_else(|| invalid("the data ends in a label's length"))?;
+            let len = usize::from(u16::from_le_bytes(*len));
+            if rest.len() < len {
+                return Err(invalid("the data ends in a label"));
+            }
+            let (label, rest) = rest.split_at(len);
+            if labels
+                .insert(value, std::str::from_utf8(label)?.to_string())
+                .is_some()
+            {
+                return Err(Error::InvalidUserDataDictionary(format!(
+                    "there's more than one label for {value}"
+                )));
+            }
+            data = rest;
+        }
+        Ok(UserDataDictionary { labels })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        for (&value, label) in &self.labels {
+            let len = u16::try_from(label.len()).map_err(|_| {
+                Error::InvalidUserDataDictionary(format!("the label for {value} is too long"))
+            })?;
+            data.push(value);
+            data.extend(len.to_le_bytes());
+            data.extend(label.as_bytes());
+        }
+        Ok(data)
+    }
+}
+
+/// What the laszip vlr says about how the points are compressed.
+///
+/// This is read straight from the vlr's bytes, so it's available without the `laz` feature. It's
+/// a [VlrRecord], so it encodes back to the same bytes.
+///
+/// # Examples
+///
+/// ```
+/// use std::{fs::File, io::BufReader};
+/// use las::{vlr::LazCompressor, Header};
+///
+/// let file = BufReader::new(File::open("tests/data/autzen.laz").unwrap());
+/// let info = Header::new(file).unwrap().laz_vlr_info().unwrap();
+/// assert_eq!(LazCompressor::PointWiseChunked, info.compressor);
+/// assert_eq!(Some(50000), info.chunk_size);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LazVlrInfo {
+    /// How the points are compressed.
+    pub compressor: LazCompressor,
+
+    /// The entropy coder, which is zero for the arithmetic coder that laszip uses.
+    pub coder: u16,
+
+    /// The version of laszip that compressed the points, as major, minor, and revision.
+    pub version: (u8, u8, u16),
+
+    /// The laszip options bit field.
+    pub options: u32,
+
+    /// The number of points in each chunk, or `None` if the chunks vary in size.
+    pub chunk_size: Option<u32>,
+
+    /// The number of special evlrs, or -1 if there aren't any.
+    pub number_of_special_evlrs: i64,
+
+    /// The offset to the special evlrs, or -1 if there aren't any.
+    pub offset_to_special_evlrs: i64,
+
+    /// The compressed items, in the order that they're stored in each point.
+    pub items: Vec<LazItemInfo>,
+}
+
+/// The way a laz file's points are compressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LazCompressor {
+    /// The points aren't compressed.
+    None,
+
+    /// Every point is compressed with the ones before it, with no chunks.
+    PointWise,
+
+    /// Points are compressed in chunks that can be decompressed independently.
+    PointWiseChunked,
+
+    /// Points are compressed in chunks, with each field in its own layer (laszip 1.4 formats).
+    LayeredChunked,
+
+    /// A compressor that this crate doesn't know about.
+    Unknown(u16),
+}
+
+/// One item of a laz point, e.g. the core point fields or the color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LazItemInfo {
+    /// The laszip item type number.
+    pub item_type: u16,
+
+    /// The size of the item in an uncompressed point, in bytes.
+    pub size: u16,
+
+    /// The version of the item's compression.
+    pub version: u16,
+}
+
+impl LazVlrInfo {
+    /// Reads laszip info from a vlr.
+    ///
+    /// Returns `None` if the vlr isn't the laszip vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{vlr::LazVlrInfo, Vlr};
+    /// assert!(LazVlrInfo::from_vlr(&Vlr::default()).is_none());
+    /// ```
+    pub fn from_vlr(vlr: &Vlr) -> Option<Result<LazVlrInfo>> {
+        LazVlrInfo::matches(vlr).then(|| LazVlrInfo::decode(&vlr.data))
+    }
+}
+
+impl VlrRecord for LazVlrInfo {
+    const USER_ID: &'static str = LASZIP_USER_ID;
+    const RECORD_ID: u16 = LASZIP_RECORD_ID;
+    const DESCRIPTION: &'static str = "https://laszip.org";
+
+    fn decode(mut data: &[u8]) -> Result<LazVlrInfo> {
+        use byteorder::{LittleEndian, ReadBytesExt};
+
+        let len = data.len();
+        let invalid = |_| Error::InvalidLaszipVlr(len);
+        let compressor = match data.read_u16::<LittleEndian>().map_err(invalid)? {
+            0 => LazCompressor::None,
+            1 => LazCompressor::PointWise,
+            2 => LazCompressor::PointWiseChunked,
+            3 => LazCompressor::LayeredChunked,
+            n => LazCompressor::Unknown(n),
+        };
+        let coder = data.read_u16::<LittleEndian>().map_err(invalid)?;
+        let version = (
+            data.read_u8().map_err(invalid)?,
+            data.read_u8().map_err(invalid)?,
+            data.read_u16::<LittleEndian>().map_err(invalid)?,
+        );
+        let options = data.read_u32::<LittleEndian>().map_err(invalid)?;
+        let chunk_size = Some(data.read_u32::<LittleEndian>().map_err(invalid)?)
+            .filter(|&chunk_size| chunk_size != LASZIP_VARIABLE_CHUNK_SIZE);
+        let number_of_special_evlrs = data.read_i64::<LittleEndian>().map_err(invalid)?;
+        let offset_to_special_evlrs = data.read_i64::<LittleEndian>().map_err(invalid)?;
+        let number_of_items = data.read_u16::<LittleEndian>().map_err(invalid)?;
+        let items = (0..number_of_items)
+            .map(|_| {
+                Ok(LazItemInfo {
+                    item_type: data.read_u16::<LittleEndian>().map_err(invalid)?,
+                    size: data.read_u16::<LittleEndian>().map_err(invalid)?,
+                    version: data.read_u16::<LittleEndian>().map_err(invalid)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(LazVlrInfo {
+            compressor,
+            coder,
+            version,
+            options,
+            chunk_size,
+            number_of_special_evlrs,
+            offset_to_special_evlrs,
+            items,
+        })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let number_of_items = u16::try_from(self.items.len())?;
+        let mut data = Vec::with_capacity(34 + 6 * self.items.len());
+        data.extend(
+            match self.compressor {
+                LazCompressor::None => 0,
+                LazCompressor::PointWise => 1,
+                LazCompressor::PointWiseChunked => 2,
+                LazCompressor::LayeredChunked => 3,
+                LazCompressor::Unknown(n) => n,
+            }
+            .to_le_bytes(),
+        );
+        data.extend(self.coder.to_le_bytes());
+        data.push(self.version.0);
+        data.push(self.version.1);
+        data.extend(self.version.2.to_le_bytes());
+        data.extend(self.options.to_le_bytes());
+        data.extend(
+            self.chunk_size
+                .unwrap_or(LASZIP_VARIABLE_CHUNK_SIZE)
+                .to_le_bytes(),
+        );
+        data.extend(self.number_of_special_evlrs.to_le_bytes());
+        data.extend(self.offset_to_special_evlrs.to_le_bytes());
+        data.extend(number_of_items.to_le_bytes());
+        for item in &self.items {
+            data.extend(item.item_type.to_le_bytes());
+            data.extend(item.size.to_le_bytes());
+            data.extend(item.version.to_le_bytes());
+        }
+        Ok(data)
+    }
+}
+
+impl LazItemInfo {
+    /// Returns laszip's name for this item type, if it's one we know.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::LazItemInfo;
+    /// let item = LazItemInfo { item_type: 6, size: 20, version: 2 };
+    /// assert_eq!(Some("point10"), item.name());
+    /// ```
+    pub fn name(&self) -> Option<&'static str> {
+        Some(match self.item_type {
+            0 => "byte",
+            1 => "short",
+            2 => "integer",
+            3 => "long",
+            4 => "float",
+            5 => "double",
+            6 => "point10",
+            7 => "gpstime11",
+            8 => "rgb12",
+            9 => "wavepacket13",
+            10 => "point14",
+            11 => "rgb14",
+            12 => "rgbnir14",
+            13 => "wavepacket14",
+            14 => "byte14",
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for LazCompressor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LazCompressor::None => write!(f, "none"),
+            LazCompressor::PointWise => write!(f, "point-wise"),
+            LazCompressor::PointWiseChunked => write!(f, "point-wise chunked"),
+            LazCompressor::LayeredChunked => write!(f, "layered chunked"),
+            LazCompressor::Unknown(n) => write!(f, "unknown compressor {n}"),
+        }
+    }
+}
+
+impl fmt::Display for LazItemInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{name} v{}", self.version),
+            None => write!(f, "item {} v{}", self.item_type, self.version),
+        }
+    }
+}
+
+impl fmt::Display for LazVlrInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (major, minor, revision) = self.version;
+        write!(f, "laszip {major}.{minor}r{revision}, {}", self.compressor)?;
+        match self.chunk_size {
+            Some(chunk_size) => write!(f, ", {chunk_size} point chunks")?,
+            None => write!(f, ", variable chunks")?,
+        }
+        for (i, item) in self.items.iter().enumerate() {
+            write!(f, "{} {item}", if i == 0 { ":" } else { "," })?;
+        }
+        Ok(())
+    }
+}
+
+/// Inserts a vlr after the existing vlrs without moving the point data.
+///
+/// The vlr is written into the vlr padding, so the padding must be large enough to hold the vlr
+/// and its header. Reserve padding when writing with [Builder::vlr_padding](crate::Builder::vlr_padding).
+/// Any bytes in the padding that the vlr covers are overwritten. The las data must start at the
+/// beginning of the stream.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Builder, Reader, Vlr, Writer};
+/// use std::io::Cursor;
+///
+/// let mut builder = Builder::from((1, 4));
+/// builder.vlr_padding(1024);
+/// let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+/// let mut cursor = writer.into_inner().unwrap();
+///
+/// las::insert_vlr_in_place(&mut cursor, Vlr::default()).unwrap();
+/// cursor.set_position(0);
+/// let reader = Reader::new(cursor).unwrap();
+/// assert_eq!(1, reader.header().vlrs().len());
+/// assert_eq!(1024 - 54, reader.header().vlr_padding_len());
+/// ```
+pub fn insert_vlr_in_place<T: Read + Write + Seek>(mut stream: T, vlr: Vlr) -> Result<()> {
+    use byteorder::{LittleEndian, WriteBytesExt};
+
+    /// The byte offset of the number of variable length records in the header.
+    const NUMBER_OF_VLRS_OFFSET: u64 = 100;
+
+    let _ = stream.seek(SeekFrom::Start(0))?;
+    let raw_header = raw::Header::read_from(&mut stream)?;
+    let mut position = u64::from(raw_header.header_size);
+    for _ in 0..raw_header.number_of_variable_length_records {
+        let _ = stream.seek(SeekFrom::Start(position))?;
+        let raw_vlr = raw::Vlr::read_header_from(&mut stream, false)?;
+        position += REGULAR_HEADER_SIZE as u64 + u64::from(raw_vlr.record_length_after_header);
+    }
+    let mut available = u64::from(raw_header.offset_to_point_data).saturating_sub(position);
+    if raw_header.version.requires_point_data_start_signature() {
+        available = available.saturating_sub(2);
+    }
+    let required = vlr.total_len(false) as u64;
+    let reserved = vlr.reserved_for(raw_header.version);
+    let mut raw_vlr = vlr.into_raw(false)?;
+    raw_vlr.reserved = reserved;
+    if required > available {
+        return Err(Error::InsufficientVlrPadding {
+            required,
+            available,
+        });
+    }
+    let _ = stream.seek(SeekFrom::Start(position))?;
+    raw_vlr.write_to(&mut stream)?;
+    let _ = stream.seek(SeekFrom::Start(NUMBER_OF_VLRS_OFFSET))?;
+    stream.write_u32::<LittleEndian>(raw_header.number_of_variable_length_records + 1)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Builder, Header};
+
+    /// A record that's at least two bytes, and whose data is its first two bytes, repeated.
+    #[derive(Debug, PartialEq)]
+    struct Repeated {
+        bytes: [u8; 2],
+        times: usize,
+    }
+
+    impl VlrRecord for Repeated {
+        const USER_ID: &'static str = "las-rs tests";
+        const RECORD_ID: u16 = 42;
+
+        fn decode(data: &[u8]) -> Result<Repeated> {
+            if data.len() < 2 {
+                return Err(Error::VlrTooLong(data.len()));
+            }
+            Ok(Repeated {
+                bytes: [data[0], data[1]],
+                times: data.len() / 2,
+            })
+        }
+
+        fn encode(&self) -> Result<Vec<u8>> {
+            Ok(self.bytes.repeat(self.times))
+        }
+    }
+
+    #[test]
+    fn set_record_replaces() {
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            user_id: Repeated::USER_ID.to_string(),
+            record_id: Repeated::RECORD_ID,
+            ..Default::default()
+        });
+        let record = Repeated {
+            bytes: [1, 2],
+            times: 3,
+        };
+        builder.set_record(&record).unwrap();
+        assert!(builder.evlrs.is_empty());
+        assert_eq!(1, builder.vlrs.len());
+        assert_eq!("", builder.vlrs[0].description);
+        let header = builder.into_header().unwrap();
+        assert_eq!(Some(record), header.get_record::<Repeated>().unwrap());
+    }
+
+    #[test]
+    fn set_large_record() {
+        let mut builder = Builder::from((1, 4));
+        let record = Repeated {
+            bytes: [1, 2],
+            times: usize::from(u16::MAX),
+        };
+        builder.set_record(&record).unwrap();
+        assert!(builder.vlrs.is_empty());
+        assert_eq!(1, builder.evlrs.len());
+        let header = builder.into_header().unwrap();
+        assert_eq!(Some(record), header.get_record::<Repeated>().unwrap());
+    }
+
+    #[test]
+    fn get_record() {
+        assert_eq!(None, Header::default().get_record::<Repeated>().unwrap());
+        let mut builder = Builder::from((1, 4));
+        builder.vlrs.push(Vlr {
+            user_id: Repeated::USER_ID.to_string(),
+            record_id: Repeated::RECORD_ID,
+            data: vec![1],
+            ..Default::default()
+        });
+        let header = builder.into_header().unwrap();
+        assert!(header.get_record::<Repeated>().is_err());
+    }
+
+    #[test]
+    fn len() {
+        let data = vec![0; 1];
+        let vlr = Vlr {
+            data,
+            ..Default::default()
+        };
+        assert_eq!(55, vlr.len(false));
+        assert_eq!(61, vlr.len(true));
+    }
+
+    #[test]
+    fn too_long() {
+        let data = vec![0; u16::MAX as usize + 1];
+        let vlr = Vlr {
+            data,
+            ..Default::default()
+        };
+        assert!(vlr.into_raw(false).is_err());
+    }
+
+    #[test]
+    fn allow_non_ascii_user_id() {
+        let raw_vlr = raw::Vlr {
+            user_id: [194, 174, 0, 0, 0, 0, 0, 0, 42, 0, 0, 0, 0, 0, 0, 0],
+            ..Default::default()
+        };
+        let vlr = Vlr::new(raw_vlr);
+        assert_eq!("®", vlr.user_id);
+    }
+
+    #[test]
+    fn allow_non_ascii_description() {
+        let raw_vlr = raw::Vlr {
+            description: [
+                194, 174, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 42, 0, 0, 0, 0, 0, 0, 0, 0,
+                0, 0, 0, 0, 0, 0,
+            ],
+            ..Default::default()
+        };
+        let vlr = Vlr::new(raw_vlr);
+        assert_eq!("?", vlr.description);
+        assert!(vlr.into_raw(false).is_ok());
+    }
+
+    #[test]
+    fn description_too_long() {
+        let description = "a".repeat(DESCRIPTION_LEN + 1);
+        let mut vlr = Vlr::default();
+        assert!(matches!(
+            vlr.set_description(&description).unwrap_err(),
+            Error::StringTooLong { len: 32, .. }
+        ));
+        assert_eq!("", vlr.description);
+        vlr.set_description_lossy(&description);
+        assert_eq!(description[..DESCRIPTION_LEN], vlr.description);
+        assert!(vlr.into_raw(false).is_ok());
+    }
+
+    #[test]
+    fn invalid_description_in_builder() {
+        for (description, vlrs) in [
+            ("a".repeat(DESCRIPTION_LEN + 1), true),
+            ("é".to_string(), false),
+        ] {
+            let vlr = Vlr {
+                user_id: "las-rs test".to_string(),
+                record_id: 42,
+                description,
+                ..Default::default()
+            };
+            let mut builder = Builder::from((1, 4));
+            if vlrs {
+                builder.vlrs.push(vlr);
+            } else {
+                builder.evlrs.push(vlr);
+            }
+            match builder.into_header().unwrap_err() {
+                Error::InvalidVlrDescription {
+                    user_id, record_id, ..
+                } => {
+                    assert_eq!("las-rs test", user_id);
+                    assert_eq!(42, record_id);
+                }
+                err => panic!("unexpected error: {err}"),
+            }
+        }
+    }
+
+    /// Returns the reserved bytes of the first vlr of a file.
+    fn first_reserved(bytes: &[u8]) -> u16 {
+        let header_size = usize::from(u16::from_le_bytes([bytes[94], bytes[95]]));
+        u16::from_le_bytes([bytes[header_size], bytes[header_size + 1]])
+    }
+
+    /// Reads a file and writes its header and points to a new one.
+    fn rewrite(bytes: Vec<u8>) -> Vec<u8> {
+        use crate::{Reader, Writer};
+
+        let reader = Reader::new(std::io::Cursor::new(bytes)).unwrap();
+        let mut writer =
+            Writer::new(std::io::Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+        for point in reader.into_points().unwrap() {
+            writer.write_point(point).unwrap();
+        }
+        writer.into_inner().unwrap().into_inner()
+    }
+
+    #[test]
+    fn reserved_for_version() {
+        for (version, expected) in [((1, 0), 0xAABB), ((1, 1), 0xAABB), ((1, 2), 0), ((1, 4), 0)] {
+            let bytes = padded(version, 0).into_inner();
+            assert_eq!(expected, first_reserved(&bytes));
+            let header = crate::Reader::new(std::io::Cursor::new(bytes.clone()))
+                .unwrap()
+                .header()
+                .clone();
+            assert_eq!(None, header.vlrs()[0].reserved);
+            assert_eq!(bytes, rewrite(bytes.clone()));
+        }
+    }
+
+    #[test]
+    fn nonconforming_reserved() {
+        for (version, reserved) in [((1, 0), 0), ((1, 2), 0xAABB), ((1, 4), 0x1234)] {
+            let mut bytes = padded(version, 0).into_inner();
+            let header_size = usize::from(u16::from_le_bytes([bytes[94], bytes[95]]));
+            bytes[header_size..header_size + 2].copy_from_slice(&u16::to_le_bytes(reserved));
+            let reader = crate::Reader::new(std::io::Cursor::new(bytes.clone())).unwrap();
+            assert_eq!(Some(reserved), reader.header().vlrs()[0].reserved);
+            let expected = if version >= (1, 2) { 1 } else { 0 };
+            assert_eq!(expected, reader.summary().nonconforming_vlr_reserved);
+            assert_eq!(bytes, rewrite(bytes.clone()));
+
+            let mut builder = reader.header().clone().into_builder();
+            builder.vlrs[0].reserved = None;
+            let header = builder.into_header().unwrap();
+            let mut reset = Vec::new();
+            header.write_to(&mut reset).unwrap();
+            assert_eq!(
+                Vlr::default().reserved_for(header.version()),
+                first_reserved(&reset)
+            );
+        }
+    }
+
+    fn padded(version: (u8, u8), padding: u32) -> std::io::Cursor<Vec<u8>> {
+        use crate::{synthetic::fixture, Builder, Point};
+
+        let mut builder = Builder::from(version);
+        builder.vlrs.push(Vlr {
+            user_id: "existing".to_string(),
+            data: vec![1, 2, 3],
+            ..Default::default()
+        });
+        builder.vlr_padding(padding);
+        let points = (0..10).map(|i| Point {
+            x: f64::from(i),
+            y: f64::from(i) * 2.,
+            z: f64::from(i) * 3.,
+            intensity: i as u16,
+            ..Default::default()
+        });
+        std::io::Cursor::new(fixture::write(builder, points))
+    }
+
+    #[test]
+    fn insert_in_place() {
+        use crate::Reader;
+
+        for version in [(1, 0), (1, 2), (1, 4)] {
+            let mut cursor = padded(version, 100);
+            let mut before = Reader::new(cursor.clone()).unwrap();
+            assert_eq!(100, before.header().vlr_padding_len());
+            let offset = usize::try_from(before.point_offset(0).unwrap()).unwrap();
+            let points = before.points().collect::<Result<Vec<_>>>().unwrap();
+            let point_bytes = cursor.get_ref()[offset..].to_vec();
+
+            let vlr = Vlr {
+                user_id: "inserted".to_string(),
+                record_id: 42,
+                description: "in place".to_string(),
+                data: vec![4, 5, 6, 7],
+                reserved: None,
+            };
+            insert_vlr_in_place(&mut cursor, vlr.clone()).unwrap();
+            assert_eq!(point_bytes, cursor.get_ref()[offset..]);
+
+            cursor.set_position(0);
+            let mut after = Reader::new(cursor).unwrap();
+            assert_eq!(2, after.header().vlrs().len());
+            assert_eq!(vlr, after.header().vlrs()[1]);
+            assert_eq!(100 - 58, after.header().vlr_padding_len());
+            assert_eq!(points, after.points().collect::<Result<Vec<_>>>().unwrap());
+        }
+    }
+
+    #[test]
+    fn insert_in_place_insufficient_padding() {
+        let mut cursor = padded((1, 0), 57);
+        let bytes = cursor.get_ref().clone();
+        let vlr = Vlr {
+            data: vec![0; 4],
+            ..Default::default()
+        };
+        assert!(matches!(
+            insert_vlr_in_place(&mut cursor, vlr),
+            Err(Error::InsufficientVlrPadding {
+                required: 58,
+                available: 57
+            })
+        ));
+        assert_eq!(bytes, *cursor.get_ref());
+    }
+
+    fn laszip_vlr(compressor: u16, chunk_size: u32, items: &[(u16, u16, u16)]) -> Vlr {
+        let mut data = Vec::new();
+        data.extend(compressor.to_le_bytes());
+        data.extend(0u16.to_le_bytes());
+        data.extend([3, 4]);
+        data.extend(3u16.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(chunk_size.to_le_bytes());
+        data.extend((-1i64).to_le_bytes());
+        data.extend((-1i64).to_le_bytes());
+        data.extend((items.len() as u16).to_le_bytes());
+        for &(item_type, size, version) in items {
+            data.extend(item_type.to_le_bytes());
+            data.extend(size.to_le_bytes());
+            data.extend(version.to_le_bytes());
+        }
+        Vlr {
+            user_id: LASZIP_USER_ID.to_string(),
+            record_id: LASZIP_RECORD_ID,
+            data,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn laz_vlr_info_chunked() {
+        let file = std::fs::File::open("tests/data/autzen.laz").unwrap();
+        let header = Header::new(std::io::BufReader::new(file)).unwrap();
+        let info = header.laz_vlr_info().unwrap();
+        assert_eq!(LazCompressor::PointWiseChunked, info.compressor);
+        assert_eq!((2, 2, 0), info.version);
+        assert_eq!(Some(50000), info.chunk_size);
+        assert_eq!(
+            vec![
+                LazItemInfo {
+                    item_type: 6,
+                    size: 20,
+                    version: 2
+                },
+                LazItemInfo {
+                    item_type: 7,
+                    size: 8,
+                    version: 2
+                },
+                LazItemInfo {
+                    item_type: 8,
+                    size: 6,
+                    version: 2
+                },
+            ],
+            info.items
+        );
+    }
+
+    #[test]
+    fn laz_vlr_info_point_wise() {
+        let vlr = laszip_vlr(1, 0, &[(6, 20, 1), (99, 4, 1)]);
+        let info = LazVlrInfo::from_vlr(&vlr).unwrap().unwrap();
+        assert_eq!(LazCompressor::PointWise, info.compressor);
+        assert_eq!((3, 4, 3), info.version);
+        assert_eq!(Some(0), info.chunk_size);
+        assert_eq!(2, info.items.len());
+        assert_eq!(None, info.items[1].name());
+        assert_eq!(
+            "laszip 3.4r3, point-wise, 0 point chunks: point10 v1, item 99 v1",
+            info.to_string()
+        );
+    }
+
+    #[test]
+    fn laz_vlr_info_variable_chunks() {
+        let vlr = laszip_vlr(3, u32::MAX, &[(10, 30, 3)]);
+        let info = LazVlrInfo::from_vlr(&vlr).unwrap().unwrap();
+        assert_eq!(LazCompressor::LayeredChunked, info.compressor);
+        assert_eq!(None, info.chunk_size);
+    }
+
+    #[test]
+    fn laz_vlr_info_roundtrip() {
+        let vlr = laszip_vlr(2, 50000, &[(6, 20, 2), (7, 8, 2)]);
+        let info = LazVlrInfo::from_vlr(&vlr).unwrap().unwrap();
+        assert_eq!(-1, info.number_of_special_evlrs);
+        assert_eq!(vlr.data, info.encode().unwrap());
+        let vlr = laszip_vlr(3, u32::MAX, &[(10, 30, 3)]);
+        let info = LazVlrInfo::from_vlr(&vlr).unwrap().unwrap();
+        assert_eq!(vlr.data, info.encode().unwrap());
+    }
+
+    #[test]
+    fn laz_vlr_info_truncated() {
+        let mut vlr = laszip_vlr(2, 50000, &[(6, 20, 2)]);
+        let _ = vlr.data.pop();
+        assert!(matches!(
+            LazVlrInfo::from_vlr(&vlr).unwrap().unwrap_err(),
+            Error::InvalidLaszipVlr(39)
+        ));
+    }
+
+    #[test]
+    fn user_data_dictionary_roundtrip() {
+        let dictionary = UserDataDictionary::new(vec![
+            (200, "far".to_string()),
+            (0, String::new()),
+            (3, "ünïcode".to_string()),
+        ]);
+        let data = dictionary.encode().unwrap();
+        assert_eq!([0, 0, 0, 3], data[..4]);
+        assert_eq!(dictionary, UserDataDictionary::decode(&data).unwrap());
+        assert_eq!(
+            UserDataDictionary::default(),
+            UserDataDictionary::decode(&[]).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_user_data_dictionary() {
+        for data in [&[1][..], &[1, 2, 0, b'a'], &[1, 0, 0, 1, 0, 0]] {
+            assert!(matches!(
+                UserDataDictionary::decode(data).unwrap_err(),
+                Error::InvalidUserDataDictionary(_)
+            ));
+        }
+        assert!(matches!(
+            UserDataDictionary::decode(&[1, 1, 0, 0xFF]).unwrap_err(),
+            Error::Utf8(_)
+        ));
+        let dictionary = UserDataDictionary::new(vec![(1, "a".repeat(70_000))]);
+        assert!(matches!(
+            dictionary.encode().unwrap_err(),
+            Error::InvalidUserDataDictionary(_)
+        ));
+    }
+
+    #[test]
+    fn evlr_into_vlr_boundary() {
+        let vlr = Vlr {
+            data: vec![0; usize::from(u16::MAX)],
+            ..Default::default()
+        };
+        assert_eq!(vlr.clone(), vlr.clone().into_evlr().try_into_vlr().unwrap());
+
+        let mut evlr = vlr.into_evlr();
+        evlr.0.data.push(1);
+        assert_eq!(evlr.clone(), evlr.clone().try_into_vlr().unwrap_err());
+    }
+
+    #[test]
+    fn waveform_evlr_is_never_a_vlr() {
+        let evlr = Vlr {
+            user_id: crate::waveform::USER_ID.to_string(),
+            record_id: crate::waveform::RECORD_ID,
+            description: "Waveform data packets".to_string(),
+            data: vec![0; 10],
+            reserved: None,
+        }
+        .into_evlr();
+        assert_eq!(evlr.clone(), evlr.clone().try_into_vlr().unwrap_err());
+
+        let mut builder = Builder::from((1, 2));
+        builder.evlrs.push(evlr.0.clone());
+        assert!(builder.clone().into_header().is_err());
+        let _ = builder.normalize();
+        let header = builder.into_header().unwrap();
+        assert_eq!(vec![evlr.0], *header.evlrs());
+    }
+}
@@ -0,0 +1,740 @@
+//! Read waveform data packets, either from the file itself or from a `.wdp` file.
+//!
+//! Each point with a waveform names a [WaveformPacketDescriptor], which says how to decode the
+//! point's packet into [Samples]:
+//!
+//! ```
+//! use las::{waveform::Samples, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/waveform-internal.las").unwrap();
+//! let points: Vec<_> = reader.points().collect::<Result<_, _>>().unwrap();
+//! assert_eq!(Samples::U16(vec![100, 200, 300]), reader.waveform(&points[1]).unwrap());
+//! ```
+//!
+//! To write waveforms, add the descriptors with
+//! [Builder::waveform_packet_descriptor](crate::Builder::waveform_packet_descriptor), and collect
+//! the packets in [WaveformPackets], which gives each point its [Waveform]. The packets then go in
+//! the file as an evlr, or in a `.wdp` file.
+
+use crate::{raw, raw::point::Waveform, Builder, Error, Header, Point, Result, Vlr, VlrRecord};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+};
+
+/// The user id of waveform packet descriptors and the waveform data packet record.
+pub const USER_ID: &str = "LASF_Spec";
+
+/// The record id of the waveform data packet record.
+pub const RECORD_ID: u16 = 65535;
+
+/// The record id of the first waveform packet descriptor, for descriptor index one.
+pub const FIRST_DESCRIPTOR_RECORD_ID: u16 = 100;
+
+/// The size of an evlr header, which is included in internal byte offsets.
+const EVLR_HEADER_SIZE: u64 = 60;
+
+/// The number of bytes in a waveform packet descriptor.
+const DESCRIPTOR_LEN: usize = 26;
+
+/// Describes how the samples of a waveform packet are stored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WaveformPacketDescriptor {
+    /// The number of bits in each sample.
+    pub bits_per_sample: u8,
+
+    /// The compression algorithm, where zero means uncompressed.
+    pub compression_type: u8,
+
+    /// The number of samples in each packet.
+    pub number_of_samples: u32,
+
+    /// The time between samples, in picoseconds.
+    pub temporal_sample_spacing: u32,
+
+    /// The gain used to convert samples to volts, as `volts = offset + gain * sample`.
+    pub digitizer_gain: f64,
+
+    /// The offset used to convert samples to volts.
+    pub digitizer_offset: f64,
+}
+
+/// Waveform packets for writing, one after another, as they'll be in the waveform data packet
+/// record.
+///
+/// # Examples
+///
+/// ```
+/// use las::{
+///     point::Format,
+///     waveform::{Samples, WaveformPacketDescriptor, WaveformPackets},
+///     Builder, Point, Reader, Writer,
+/// };
+/// use std::io::Cursor;
+///
+/// let descriptor = WaveformPacketDescriptor {
+///     bits_per_sample: 8,
+///     compression_type: 0,
+///     number_of_samples: 3,
+///     temporal_sample_spacing: 1000,
+///     digitizer_gain: 1.,
+///     digitizer_offset: 0.,
+/// };
+/// let mut packets = WaveformPackets::new();
+/// let point = Point {
+///     gps_time: Some(0.),
+///     waveform: Some(packets.push(1, &descriptor, &Samples::U8(vec![1, 2, 3])).unwrap()),
+///     ..Default::default()
+/// };
+///
+/// let mut builder = Builder::from((1, 4));
+/// builder.point_format = Format::new(4).unwrap();
+/// builder.has_internal_waveform_data_packets = true;
+/// builder.waveform_packet_descriptor(1, &descriptor).unwrap();
+/// builder.evlrs.push(packets.into_evlr());
+/// let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+/// writer.write_point(point).unwrap();
+///
+/// let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+/// let point = reader.read_point().unwrap().unwrap();
+/// assert_eq!(Samples::U8(vec![1, 2, 3]), reader.waveform(&point).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WaveformPackets {
+    data: Vec<u8>,
+}
+
+/// The decoded samples of a waveform packet.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Samples {
+    /// Samples of up to eight bits.
+    U8(Vec<u8>),
+
+    /// Samples of nine to sixteen bits.
+    U16(Vec<u16>),
+}
+
+impl WaveformPacketDescriptor {
+    /// Reads a waveform packet descriptor from a vlr.
+    ///
+    /// Returns `None` if the vlr isn't a waveform packet descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{waveform::WaveformPacketDescriptor, Vlr};
+    /// assert!(WaveformPacketDescriptor::from_vlr(&Vlr::default()).is_none());
+    /// ```
+    pub fn from_vlr(vlr: &Vlr) -> Option<Result<WaveformPacketDescriptor>> {
+        if vlr.user_id == USER_ID
+            && (FIRST_DESCRIPTOR_RECORD_ID..FIRST_DESCRIPTOR_RECORD_ID + 255)
+                .contains(&vlr.record_id)
+        {
+            Some(VlrRecord::decode(&vlr.data))
+        } else {
+            None
+        }
+    }
+
+    /// Writes this descriptor to a vlr, for the descriptor index that points use.
+    ///
+    /// Returns an error if the index is zero, which means a point has no waveform.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPacketDescriptor;
+    /// let descriptor = WaveformPacketDescriptor {
+    ///     bits_per_sample: 8,
+    ///     compression_type: 0,
+    ///     number_of_samples: 3,
+    ///     temporal_sample_spacing: 1000,
+    ///     digitizer_gain: 1.,
+    ///     digitizer_offset: 0.,
+    /// };
+    /// let vlr = descriptor.to_vlr(2).unwrap();
+    /// assert_eq!(101, vlr.record_id);
+    /// assert_eq!(descriptor, WaveformPacketDescriptor::from_vlr(&vlr).unwrap().unwrap());
+    /// ```
+    pub fn to_vlr(&self, index: u8) -> Result<Vlr> {
+        if index == 0 {
+            return Err(Error::WaveformPacketDescriptorNotFound(index));
+        }
+        Ok(Vlr {
+            record_id: FIRST_DESCRIPTOR_RECORD_ID + u16::from(index) - 1,
+            ..VlrRecord::to_vlr(self)?
+        })
+    }
+
+    /// Decodes a waveform packet into samples.
+    ///
+    /// Samples of up to eight bits are stored in one byte each, and samples of up to sixteen bits
+    /// in two little-endian bytes. Wider samples and compressed packets aren't supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::{Samples, WaveformPacketDescriptor};
+    /// let descriptor = WaveformPacketDescriptor {
+    ///     bits_per_sample: 16,
+    ///     compression_type: 0,
+    ///     number_of_samples: 2,
+    ///     temporal_sample_spacing: 1000,
+    ///     digitizer_gain: 1.,
+    ///     digitizer_offset: 0.,
+    /// };
+    /// assert_eq!(Samples::U16(vec![1, 256]), descriptor.decode(&[1, 0, 0, 1]).unwrap());
+    /// assert!(descriptor.decode(&[1, 0]).is_err());
+    /// ```
+    pub fn decode(&self, packet: &[u8]) -> Result<Samples> {
+        let bytes_per_sample = self.bytes_per_sample()?;
+        let expected = u64::from(self.number_of_samples) * bytes_per_sample;
+        if packet.len() as u64 != expected {
+            return Err(Error::WaveformPacketSize {
+                expected,
+                actual: packet.len(),
+            });
+        }
+        if bytes_per_sample == 1 {
+            Ok(Samples::U8(packet.to_vec()))
+        } else {
+            Ok(Samples::U16(
+                packet
+                    .chunks_exact(2)
+                    .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+                    .collect(),
+            ))
+        }
+    }
+
+    /// Encodes samples into a waveform packet, the reverse of [decode](Self::decode).
+    ///
+    /// Eight bit samples can be encoded with a wider descriptor. Returns an error if there aren't
+    /// as many samples as the descriptor calls for, or if they're too big for its bits per
+    /// sample.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::{Samples, WaveformPacketDescriptor};
+    /// let descriptor = WaveformPacketDescriptor {
+    ///     bits_per_sample: 12,
+    ///     compression_type: 0,
+    ///     number_of_samples: 2,
+    ///     temporal_sample_spacing: 1000,
+    ///     digitizer_gain: 1.,
+    ///     digitizer_offset: 0.,
+    /// };
+    /// assert_eq!(vec![1, 0, 0, 1], descriptor.encode(&Samples::U16(vec![1, 256])).unwrap());
+    /// assert!(descriptor.encode(&Samples::U16(vec![1, 4096])).is_err());
+    /// ```
+    pub fn encode(&self, samples: &Samples) -> Result<Vec<u8>> {
+        let bytes_per_sample = self.bytes_per_sample()?;
+        let values: Vec<u16> = match samples {
+            Samples::U8(samples) => samples.iter().copied().map(u16::from).collect(),
+            Samples::U16(samples) => samples.clone(),
+        };
+        if values
+            .iter()
+            .any(|&value| u32::from(value) >= 1 << self.bits_per_sample)
+        {
+            return Err(Error::WaveformSamplesDoNotFit(self.bits_per_sample));
+        }
+        let packet: Vec<u8> = if bytes_per_sample == 1 {
+            values.iter().map(|&value| value as u8).collect()
+        } else {
+            values
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect()
+        };
+        let expected = u64::from(self.number_of_samples) * bytes_per_sample;
+        if packet.len() as u64 != expected {
+            return Err(Error::WaveformPacketSize {
+                expected,
+                actual: packet.len(),
+            });
+        }
+        Ok(packet)
+    }
+
+    /// Returns the bytes each sample takes, for uncompressed packets of up to sixteen bits.
+    fn bytes_per_sample(&self) -> Result<u64> {
+        if self.compression_type != 0 {
+            return Err(Error::UnsupportedWaveformCompression(self.compression_type));
+        }
+        match self.bits_per_sample {
+            1..=8 => Ok(1),
+            9..=16 => Ok(2),
+            bits => Err(Error::UnsupportedBitsPerSample(bits)),
+        }
+    }
+}
+
+impl WaveformPackets {
+    /// Creates an empty set of packets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPackets;
+    /// assert!(WaveformPackets::new().is_empty());
+    /// ```
+    pub fn new() -> WaveformPackets {
+        WaveformPackets::default()
+    }
+
+    /// Encodes samples with a descriptor, adds the packet, and returns the waveform for its point.
+    ///
+    /// The waveform has the packet's descriptor index, offset, and size. Its return point location
+    /// and direction are zero, so set them if they're known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::{Samples, WaveformPacketDescriptor, WaveformPackets};
+    /// let descriptor = WaveformPacketDescriptor {
+    ///     bits_per_sample: 16,
+    ///     compression_type: 0,
+    ///     number_of_samples: 2,
+    ///     temporal_sample_spacing: 1000,
+    ///     digitizer_gain: 1.,
+    ///     digitizer_offset: 0.,
+    /// };
+    /// let mut packets = WaveformPackets::new();
+    /// let first = packets.push(1, &descriptor, &Samples::U16(vec![1, 2])).unwrap();
+    /// let second = packets.push(1, &descriptor, &Samples::U16(vec![3, 4])).unwrap();
+    /// assert_eq!(60, first.byte_offset_to_waveform_data);
+    /// assert_eq!(64, second.byte_offset_to_waveform_data);
+    /// assert_eq!(4, second.waveform_packet_size_in_bytes);
+    /// ```
+    pub fn push(
+        &mut self,
+        index: u8,
+        descriptor: &WaveformPacketDescriptor,
+        samples: &Samples,
+    ) -> Result<Waveform> {
+        if index == 0 {
+            return Err(Error::WaveformPacketDescriptorNotFound(index));
+        }
+        let packet = descriptor.encode(samples)?;
+        let waveform = Waveform {
+            wave_packet_descriptor_index: index,
+            byte_offset_to_waveform_data: EVLR_HEADER_SIZE + self.data.len() as u64,
+            waveform_packet_size_in_bytes: u32::try_from(packet.len())?,
+            return_point_waveform_location: 0.,
+            x_t: 0.,
+            y_t: 0.,
+            z_t: 0.,
+        };
+        self.data.extend(packet);
+        Ok(waveform)
+    }
+
+    /// Returns the number of bytes of packets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPackets;
+    /// assert_eq!(0, WaveformPackets::new().len());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if there are no packets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPackets;
+    /// assert!(WaveformPackets::new().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the waveform data packet record, for the evlrs of a file with internal packets.
+    ///
+    /// A [Writer](crate::Writer) points the header's start of the waveform data packet record at
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPackets;
+    /// assert_eq!(65535, WaveformPackets::new().into_evlr().record_id);
+    /// ```
+    pub fn into_evlr(self) -> Vlr {
+        Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: RECORD_ID,
+            description: String::new(),
+            data: self.data,
+            reserved: None,
+        }
+    }
+
+    /// Writes the packets as a `.wdp` file, which starts with the record's evlr header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPackets;
+    /// let mut wdp = Vec::new();
+    /// WaveformPackets::new().write_wdp(&mut wdp).unwrap();
+    /// assert_eq!(60, wdp.len());
+    /// ```
+    pub fn write_wdp<W: Write>(&self, mut write: W) -> Result<()> {
+        let evlr = Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: RECORD_ID,
+            description: String::new(),
+            data: Vec::new(),
+            reserved: None,
+        };
+        let mut raw_evlr = evlr.into_raw(true)?;
+        raw_evlr.record_length_after_header = raw::vlr::RecordLength::Evlr(self.data.len() as u64);
+        raw_evlr.write_to(&mut write)?;
+        write.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+/// As a [VlrRecord], a descriptor is the one with index one. Use
+/// [Header::waveform_packet_descriptor] and [Builder::waveform_packet_descriptor] for the others.
+impl VlrRecord for WaveformPacketDescriptor {
+    const USER_ID: &'static str = USER_ID;
+    const RECORD_ID: u16 = FIRST_DESCRIPTOR_RECORD_ID;
+
+    fn decode(mut data: &[u8]) -> Result<WaveformPacketDescriptor> {
+        if data.len() != DESCRIPTOR_LEN {
+            return Err(Error::InvalidWaveformPacketDescriptor(data.len()));
+        }
+        Ok(WaveformPacketDescriptor {
+            bits_per_sample: data.read_u8()?,
+            compression_type: data.read_u8()?,
+            number_of_samples: data.read_u32::<LittleEndian>()?,
+            temporal_sample_spacing: data.read_u32::<LittleEndian>()?,
+            digitizer_gain: data.read_f64::<LittleEndian>()?,
+            digitizer_offset: data.read_f64::<LittleEndian>()?,
+        })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(DESCRIPTOR_LEN);
+        data.write_u8(self.bits_per_sample)?;
+        data.write_u8(self.compression_type)?;
+        data.write_u32::<LittleEndian>(self.number_of_samples)?;
+        data.write_u32::<LittleEndian>(self.temporal_sample_spacing)?;
+        data.write_f64::<LittleEndian>(self.digitizer_gain)?;
+        data.write_f64::<LittleEndian>(self.digitizer_offset)?;
+        Ok(data)
+    }
+}
+
+impl Builder {
+    /// Sets the waveform packet descriptor for an index, replacing any that's there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{waveform::WaveformPacketDescriptor, Builder};
+    /// let descriptor = WaveformPacketDescriptor {
+    ///     bits_per_sample: 8,
+    ///     compression_type: 0,
+    ///     number_of_samples: 3,
+    ///     temporal_sample_spacing: 1000,
+    ///     digitizer_gain: 1.,
+    ///     digitizer_offset: 0.,
+    /// };
+    /// let mut builder = Builder::from((1, 3));
+    /// builder.waveform_packet_descriptor(1, &descriptor).unwrap();
+    /// builder.waveform_packet_descriptor(1, &descriptor).unwrap();
+    /// assert_eq!(1, builder.vlrs.len());
+    /// ```
+    pub fn waveform_packet_descriptor(
+        &mut self,
+        index: u8,
+        descriptor: &WaveformPacketDescriptor,
+    ) -> Result<()> {
+        let vlr = descriptor.to_vlr(index)?;
+        self.vlrs
+            .retain(|existing| existing.user_id != USER_ID || existing.record_id != vlr.record_id);
+        self.vlrs.push(vlr);
+        Ok(())
+    }
+}
+
+impl Header {
+    /// Returns the waveform packet descriptor with this index, as used by
+    /// [Waveform::wave_packet_descriptor_index].
+    ///
+    /// Returns `None` if there's no such descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/waveform-internal.las").unwrap();
+    /// let descriptor = reader.header().waveform_packet_descriptor(2).unwrap().unwrap();
+    /// assert_eq!(16, descriptor.bits_per_sample);
+    /// assert!(reader.header().waveform_packet_descriptor(3).is_none());
+    /// ```
+    pub fn waveform_packet_descriptor(
+        &self,
+        index: u8,
+    ) -> Option<Result<WaveformPacketDescriptor>> {
+        let record_id = FIRST_DESCRIPTOR_RECORD_ID + u16::from(index) - 1;
+        self.vlrs()
+            .iter()
+            .filter(|vlr| vlr.record_id == record_id)
+            .find_map(WaveformPacketDescriptor::from_vlr)
+    }
+}
+
+/// An auxiliary `.wdp` file, opened the first time a packet is read from it.
+#[derive(Debug)]
+pub(crate) struct Wdp {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl Wdp {
+    pub(crate) fn new(path: PathBuf) -> Wdp {
+        Wdp { path, file: None }
+    }
+
+    /// Reads a waveform packet, whose byte offset is from the start of the file.
+    pub(crate) fn read_packet(&mut self, waveform: &Waveform) -> Result<Vec<u8>> {
+        let file =
+            match &mut self.file {
+                Some(file) => file,
+                None => self.file.insert(File::open(&self.path).map_err(|source| {
+                    Error::WaveformFile {
+                        path: self.path.clone(),
+                        source,
+                    }
+                })?),
+            };
+        verify_in_bounds(waveform, 0, file.metadata()?.len())?;
+        let _ = file.seek(SeekFrom::Start(waveform.byte_offset_to_waveform_data))?;
+        let mut packet = vec![0; waveform.waveform_packet_size_in_bytes as usize];
+        file.read_exact(&mut packet)?;
+        Ok(packet)
+    }
+}
+
+/// Returns the point's waveform, or an error if it doesn't have one.
+pub(crate) fn waveform(point: &Point) -> Result<&Waveform> {
+    point
+        .waveform
+        .as_ref()
+        .filter(|waveform| waveform.wave_packet_descriptor_index != 0)
+        .ok_or(Error::NoWaveform)
+}
+
+/// Where to find the waveform data packet record inside the las file.
+pub(crate) enum Internal<'a> {
+    /// The record was read into the header as an evlr.
+    Evlr(&'a [u8]),
+
+    /// The record starts at this offset in the file.
+    Offset(u64),
+}
+
+/// Locates the waveform data packet record, preferring an evlr that's already been read.
+pub(crate) fn internal(header: &Header) -> Result<Internal<'_>> {
+    if let Some(vlr) = header
+        .evlrs()
+        .iter()
+        .find(|vlr| vlr.user_id == USER_ID && vlr.record_id == RECORD_ID)
+    {
+        Ok(Internal::Evlr(&vlr.data))
+    } else {
+        header
+            .start_of_waveform_data_packet_record()
+            .filter(|&start| start > 0)
+            .map(Internal::Offset)
+            .ok_or(Error::NoWaveformDataPackets)
+    }
+}
+
+/// Reads a waveform packet from a waveform data packet record that was read into the header.
+///
+/// Byte offsets are from the start of the record's header.
+pub(crate) fn read_evlr_packet(data: &[u8], waveform: &Waveform) -> Result<Vec<u8>> {
+    let len = EVLR_HEADER_SIZE + data.len() as u64;
+    verify_in_bounds(waveform, EVLR_HEADER_SIZE, len)?;
+    let start = (waveform.byte_offset_to_waveform_data - EVLR_HEADER_SIZE) as usize;
+    Ok(data[start..start + waveform.waveform_packet_size_in_bytes as usize].to_vec())
+}
+
+/// Reads a waveform packet from a waveform data packet record that starts at `start` in the las
+/// file.
+///
+/// `read_at` fills a buffer from an absolute offset in the las file.
+pub(crate) fn read_packet_at(
+    start: u64,
+    waveform: &Waveform,
+    mut read_at: impl FnMut(u64, &mut [u8]) -> Result<()>,
+) -> Result<Vec<u8>> {
+    let mut header = [0; EVLR_HEADER_SIZE as usize];
+    read_at(start, &mut header)?;
+    let raw_vlr = raw::Vlr::read_header_from(&header[..], true)?;
+    if raw_vlr.record_id != RECORD_ID {
+        return Err(Error::NoWaveformDataPackets);
+    }
+    let len = EVLR_HEADER_SIZE + u64::from(raw_vlr.record_length_after_header);
+    verify_in_bounds(waveform, EVLR_HEADER_SIZE, len)?;
+    let mut packet = vec![0; waveform.waveform_packet_size_in_bytes as usize];
+    read_at(start + waveform.byte_offset_to_waveform_data, &mut packet)?;
+    Ok(packet)
+}
+
+fn verify_in_bounds(waveform: &Waveform, start: u64, end: u64) -> Result<()> {
+    let offset = waveform.byte_offset_to_waveform_data;
+    let size = waveform.waveform_packet_size_in_bytes;
+    if offset < start || offset.saturating_add(u64::from(size)) > end {
+        Err(Error::WaveformPacketOutOfBounds {
+            offset,
+            size,
+            len: end,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descriptor(bits_per_sample: u8, number_of_samples: u32) -> WaveformPacketDescriptor {
+        WaveformPacketDescriptor {
+            bits_per_sample,
+            compression_type: 0,
+            number_of_samples,
+            temporal_sample_spacing: 1000,
+            digitizer_gain: 1.,
+            digitizer_offset: 0.,
+        }
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(
+            Samples::U8(vec![1, 2, 3]),
+            descriptor(8, 3).decode(&[1, 2, 3]).unwrap()
+        );
+        assert_eq!(
+            Samples::U16(vec![0x0201]),
+            descriptor(12, 1).decode(&[1, 2]).unwrap()
+        );
+        assert!(matches!(
+            descriptor(8, 3).decode(&[1, 2]).unwrap_err(),
+            Error::WaveformPacketSize {
+                expected: 3,
+                actual: 2
+            }
+        ));
+        assert!(matches!(
+            descriptor(32, 1).decode(&[0; 4]).unwrap_err(),
+            Error::UnsupportedBitsPerSample(32)
+        ));
+        let compressed = WaveformPacketDescriptor {
+            compression_type: 1,
+            ..descriptor(8, 1)
+        };
+        assert!(matches!(
+            compressed.decode(&[0]).unwrap_err(),
+            Error::UnsupportedWaveformCompression(1)
+        ));
+    }
+
+    #[test]
+    fn encode() {
+        assert_eq!(
+            vec![1, 2, 3],
+            descriptor(8, 3)
+                .encode(&Samples::U8(vec![1, 2, 3]))
+                .unwrap()
+        );
+        assert_eq!(
+            vec![1, 0, 2, 0],
+            descriptor(16, 2).encode(&Samples::U8(vec![1, 2])).unwrap()
+        );
+        assert!(matches!(
+            descriptor(8, 1)
+                .encode(&Samples::U16(vec![256]))
+                .unwrap_err(),
+            Error::WaveformSamplesDoNotFit(8)
+        ));
+        assert!(matches!(
+            descriptor(8, 3).encode(&Samples::U8(vec![1])).unwrap_err(),
+            Error::WaveformPacketSize {
+                expected: 3,
+                actual: 1
+            }
+        ));
+    }
+
+    #[test]
+    fn descriptor_to_vlr() {
+        let vlr = descriptor(12, 7).to_vlr(255).unwrap();
+        assert_eq!(354, vlr.record_id);
+        assert_eq!(DESCRIPTOR_LEN, vlr.data.len());
+        assert_eq!(
+            descriptor(12, 7),
+            WaveformPacketDescriptor::from_vlr(&vlr).unwrap().unwrap()
+        );
+        assert!(matches!(
+            descriptor(8, 1).to_vlr(0).unwrap_err(),
+            Error::WaveformPacketDescriptorNotFound(0)
+        ));
+    }
+
+    #[test]
+    fn descriptor_from_vlr() {
+        let vlr = Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: 101,
+            data: vec![0; 25],
+            ..Default::default()
+        };
+        assert!(matches!(
+            WaveformPacketDescriptor::from_vlr(&vlr)
+                .unwrap()
+                .unwrap_err(),
+            Error::InvalidWaveformPacketDescriptor(25)
+        ));
+        let vlr = Vlr {
+            record_id: 99,
+            ..vlr
+        };
+        assert!(WaveformPacketDescriptor::from_vlr(&vlr).is_none());
+    }
+
+    #[test]
+    fn descriptor_record_is_index_one() {
+        let mut builder = Builder::from((1, 3));
+        builder
+            .waveform_packet_descriptor(2, &descriptor(16, 2))
+            .unwrap();
+        builder.set_record(&descriptor(8, 3)).unwrap();
+        builder.set_record(&descriptor(8, 4)).unwrap();
+        assert_eq!(2, builder.vlrs.len());
+        let header = builder.into_header().unwrap();
+        assert_eq!(
+            Some(descriptor(8, 4)),
+            header.get_record::<WaveformPacketDescriptor>().unwrap()
+        );
+        assert_eq!(
+            descriptor(16, 2),
+            header.waveform_packet_descriptor(2).unwrap().unwrap()
+        );
+    }
+}
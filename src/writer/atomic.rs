@@ -0,0 +1,257 @@
+use crate::{Header, Result, Writer};
+use std::{
+    fs::File,
+    io::{BufWriter, ErrorKind, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Tells apart the temporary files of atomic writers opened at the same time.
+static TEMPORARY_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A temporary file that's renamed over its destination when it's committed, and removed if it's
+/// dropped before then.
+///
+/// Created by [Writer::create_atomic] and [Writer::create_atomic_new]. The temporary file is in the
+/// destination's directory, so the rename never crosses a filesystem.
+#[derive(Debug)]
+pub struct AtomicFile {
+    write: BufWriter<File>,
+    temporary: PathBuf,
+    destination: PathBuf,
+    overwrite: bool,
+    committed: bool,
+}
+
+impl AtomicFile {
+    fn create(destination: &Path, overwrite: bool) -> Result<AtomicFile> {
+        if !overwrite && destination.try_exists()? {
+            return Err(already_exists(destination).into());
+        }
+        let file_name = destination
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let temporary = destination.with_file_name(format!(
+            ".{}.{}-{}.tmp",
+            file_name,
+            std::process::id(),
+            TEMPORARY_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::create_new(&temporary)?;
+        Ok(AtomicFile {
+            write: BufWriter::new(file),
+            temporary,
+            destination: destination.to_path_buf(),
+            overwrite,
+            committed: false,
+        })
+    }
+
+    /// Flushes and syncs the temporary file, and moves it to the destination.
+    pub(super) fn commit(&mut self) -> Result<()> {
+        self.write.flush()?;
+        self.write.get_ref().sync_all()?;
+        if self.overwrite {
+            std::fs::rename(&self.temporary, &self.destination)?;
+        } else {
+            // A hard link fails if the destination has appeared since the file was created.
+            match std::fs::hard_link(&self.temporary, &self.destination) {
+                Ok(()) => std::fs::remove_file(&self.temporary)?,
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => return Err(err.into()),
+                Err(_) => {
+                    // Some filesystems can't link, so check and rename instead.
+                    if self.destination.try_exists()? {
+                        return Err(already_exists(&self.destination).into());
+                    }
+                    std::fs::rename(&self.temporary, &self.destination)?;
+                }
+            }
+        }
+        self.committed = true;
+        Ok(())
+    }
+}
+
+fn already_exists(path: &Path) -> std::io::Error {
+    std::io::Error::new(
+        ErrorKind::AlreadyExists,
+        format!("{} already exists", path.display()),
+    )
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write.flush()
+    }
+}
+
+impl Seek for AtomicFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.write.seek(pos)
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if !self.committed
+            && let Err(err) = std::fs::remove_file(&self.temporary)
+        {
+            log::warn!("could not remove {}: {}", self.temporary.display(), err);
+        }
+    }
+}
+
+impl Writer<AtomicFile> {
+    /// Creates a writer that writes to a temporary file next to a path, and renames it over the
+    /// path when the writer is closed.
+    ///
+    /// Until [Writer::close] succeeds, nothing is at the path, or whatever was there before is
+    /// left alone. If closing fails, or the writer is dropped without being closed, the temporary
+    /// file is removed. An existing file at the path is replaced, use [Writer::create_atomic_new]
+    /// to refuse to. Like [Writer::from_path], a `.laz` extension means the points are compressed,
+    /// if the `laz` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Reader, Writer};
+    /// let path = std::env::temp_dir().join("las-rs-create-atomic.las");
+    /// let mut writer = Writer::create_atomic(&path, Default::default()).unwrap();
+    /// writer.write_point(Default::default()).unwrap();
+    /// assert!(!path.exists());
+    /// writer.close().unwrap();
+    /// assert_eq!(1, Reader::from_path(&path).unwrap().header().number_of_points());
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    pub fn create_atomic<P: AsRef<Path>>(path: P, header: Header) -> Result<Writer<AtomicFile>> {
+        Writer::atomic(path.as_ref(), header, true)
+    }
+
+    /// Creates a writer like [Writer::create_atomic], but returns an error if the path already
+    /// exists, when the writer is created or when it's closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Writer;
+    /// assert!(Writer::create_atomic_new("tests/data/autzen.las", Default::default()).is_err());
+    /// ```
+    pub fn create_atomic_new<P: AsRef<Path>>(
+        path: P,
+        header: Header,
+    ) -> Result<Writer<AtomicFile>> {
+        Writer::atomic(path.as_ref(), header, false)
+    }
+
+    fn atomic(path: &Path, mut header: Header, overwrite: bool) -> Result<Writer<AtomicFile>> {
+        header.point_format_mut().is_compressed = super::is_laz_path(path);
+        let mut writer = Writer::new(AtomicFile::create(path, overwrite)?, header)?;
+        writer.on_close = Some(AtomicFile::commit);
+        Ok(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Builder, Error, Header, Point, Reader, Writer};
+    use std::path::PathBuf;
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("las-rs-atomic-{name}"));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn is_empty(dir: &PathBuf) -> bool {
+        std::fs::read_dir(dir).unwrap().next().is_none()
+    }
+
+    #[test]
+    fn success() {
+        let dir = dir("success");
+        let path = dir.join("points.las");
+        let mut writer = Writer::create_atomic(&path, Header::default()).unwrap();
+        for i in 0..10 {
+            writer
+                .write_point(Point {
+                    x: f64::from(i),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        assert!(!path.exists());
+        let _ = writer.close().unwrap();
+        drop(writer);
+        let points = Reader::from_path(&path).unwrap().into_points().unwrap();
+        assert_eq!(10, points.len());
+        assert_eq!(1, std::fs::read_dir(&dir).unwrap().count());
+    }
+
+    #[test]
+    fn dropped_without_closing() {
+        let dir = dir("dropped");
+        let path = dir.join("points.las");
+        let mut writer = Writer::create_atomic(&path, Header::default()).unwrap();
+        writer.write_point(Point::default()).unwrap();
+        assert_eq!(1, std::fs::read_dir(&dir).unwrap().count());
+        drop(writer);
+        assert!(!path.exists());
+        assert!(is_empty(&dir));
+    }
+
+    #[test]
+    fn failed_close() {
+        let dir = dir("failed-close");
+        let path = dir.join("points.las");
+        let header = Builder::from((1, 4)).into_header().unwrap();
+        let mut writer = Writer::create_atomic(&path, header).unwrap();
+        writer.write_point(Point::default()).unwrap();
+        // An evlr that's never finished makes closing fail.
+        let _ = writer.begin_evlr("las-rs", 1, "").unwrap();
+        assert!(matches!(writer.close().unwrap_err(), Error::UnfinishedEvlr));
+        drop(writer);
+        assert!(!path.exists());
+        assert!(is_empty(&dir));
+    }
+
+    #[test]
+    fn overwrite() {
+        let dir = dir("overwrite");
+        let path = dir.join("points.las");
+        std::fs::write(&path, b"old").unwrap();
+        let mut writer = Writer::create_atomic(&path, Header::default()).unwrap();
+        assert_eq!(b"old", std::fs::read(&path).unwrap().as_slice());
+        let _ = writer.close().unwrap();
+        drop(writer);
+        assert!(Reader::from_path(&path).is_ok());
+    }
+
+    #[test]
+    fn refuse_existing() {
+        let dir = dir("refuse-existing");
+        let path = dir.join("points.las");
+        std::fs::write(&path, b"old").unwrap();
+        assert!(matches!(
+            Writer::create_atomic_new(&path, Header::default())
+                .map(|_| ())
+                .unwrap_err(),
+            Error::Io(err) if err.kind() == std::io::ErrorKind::AlreadyExists
+        ));
+
+        let path = dir.join("late.las");
+        let mut writer = Writer::create_atomic_new(&path, Header::default()).unwrap();
+        std::fs::write(&path, b"late").unwrap();
+        assert!(writer.close().is_err());
+        drop(writer);
+        assert_eq!(b"late", std::fs::read(&path).unwrap().as_slice());
+        assert_eq!(2, std::fs::read_dir(&dir).unwrap().count());
+    }
+}
@@ -1,5 +1,5 @@
 use super::WritePoint;
-use crate::{Header, Point, Result};
+use crate::{Error, Header, Result};
 use std::io::{Seek, Write};
 
 pub(crate) struct PointWriter<W: Write + Seek> {
@@ -14,14 +14,11 @@ impl<W: Write + Seek> PointWriter<W> {
 }
 
 impl<W: Write + Seek + Send> WritePoint<W> for PointWriter<W> {
-    fn write_point(&mut self, point: Point) -> Result<()> {
-        self.header.add_point(&point);
-        point
-            .into_raw(self.header.transforms())
-            .and_then(|raw_point| raw_point.write_to(&mut self.write, self.header.point_format()))
+    fn write_raw_points(&mut self, bytes: &[u8]) -> Result<()> {
+        self.write.write_all(bytes).map_err(Error::from)
     }
 
-    fn into_inner(self: Box<Self>) -> W {
+    fn into_inner(self) -> W {
         self.write
     }
 
@@ -1,48 +1,149 @@
 use super::WritePoint;
-use crate::{Error, Header, Point, Result};
-use ::laz::LasZipCompressor;
-use std::io::{Cursor, Seek, Write};
+use crate::{laz::is_laszip_vlr, Error, Header, Result};
+use ::laz::{
+    laszip::{ChunkTable, ChunkTableEntry},
+    record::{LayeredPointRecordCompressor, RecordCompressor, SequentialPointRecordCompressor},
+    LasZipError, LazItem, LazVlr,
+};
+use std::io::{Seek, SeekFrom, Write};
 
-pub(crate) struct PointWriter<'a, W: Write + Seek + Send> {
-    compressor: LasZipCompressor<'a, W>,
-    buffer: Cursor<Vec<u8>>,
+pub(crate) struct PointWriter<W: Write + Seek + Send> {
+    compressor: ChunkWriter<W>,
     header: Header,
+    has_points: bool,
+    /// Chunks end just before the next point, so that the last chunk isn't an empty one.
+    chunk_ends: bool,
 }
 
-impl<'a, W: Write + Seek + Send> PointWriter<'a, W> {
-    pub(crate) fn new(write: W, header: Header) -> Result<PointWriter<'a, W>> {
-        let buffer = Cursor::new(vec![0u8; header.point_format().len() as usize]);
+impl<W: Write + Seek + Send> PointWriter<W> {
+    /// Compresses points into a sink that's at the start of the point data.
+    ///
+    /// The offset to the chunk table is written as -1 for now, and filled in when the points are
+    /// done.
+    pub(crate) fn new(mut write: W, header: Header) -> Result<PointWriter<W>> {
         let vlr = header.laz_vlr()?;
-        let compressor = LasZipCompressor::new(write, vlr)?;
+        let offset_position = write.stream_position()?;
+        write.write_all(&(-1i64).to_le_bytes())?;
+        let chunk_size = u64::from(vlr.chunk_size());
+        let compressor = ChunkWriter::new(
+            write,
+            vlr,
+            chunk_size,
+            ChunkTable::default(),
+            Some(offset_position),
+        )?;
+        Ok(Self {
+            header,
+            compressor,
+            has_points: false,
+            chunk_ends: false,
+        })
+    }
 
+    /// Adds chunks after the existing ones of a file whose point data starts at an offset.
+    ///
+    /// The sink must hold the whole file. New chunks have as many points as the old ones, but
+    /// if the last old chunk of a fixed size chunk table is short, the table is switched to
+    /// variable size chunks, in the header and in the file's laszip vlr.
+    pub(crate) fn appending(
+        mut write: W,
+        mut header: Header,
+        offset_to_point_data: u64,
+        mut chunk_table: ChunkTable,
+    ) -> Result<PointWriter<W>> {
+        // Point data without points still has a chunk, which the new points can go over.
+        while chunk_table
+            .as_ref()
+            .last()
+            .is_some_and(|entry| entry.point_count == 0)
+        {
+            let _ = chunk_table.pop();
+        }
+        let vlr = header.laz_vlr()?;
+        let chunk_size = u64::from(vlr.chunk_size());
+        if !vlr.uses_variable_size_chunks() && !header.number_of_points().is_multiple_of(chunk_size)
+        {
+            let index = header
+                .vlrs
+                .iter()
+                .position(is_laszip_vlr)
+                .ok_or(Error::LasZipVlrNotFound)?;
+            // The chunk size sits after the compressor, coder, version, and options.
+            let chunk_size_bytes = u32::MAX.to_le_bytes();
+            header.vlrs[index].data[12..16].copy_from_slice(&chunk_size_bytes);
+            let _ = write.seek(SeekFrom::Start(header.vlr_data_offset(index) + 12))?;
+            write.write_all(&chunk_size_bytes)?;
+        }
+        let end_of_chunks = offset_to_point_data
+            + ChunkTable::OFFSET_SIZE as u64
+            + chunk_table
+                .as_ref()
+                .iter()
+                .map(|entry| entry.byte_count)
+                .sum::<u64>();
+        let _ = write.seek(SeekFrom::Start(end_of_chunks))?;
+        let compressor = ChunkWriter::new(
+            write,
+            header.laz_vlr()?,
+            chunk_size,
+            chunk_table,
+            Some(offset_to_point_data),
+        )?;
         Ok(Self {
             header,
-            buffer,
             compressor,
+            has_points: false,
+            chunk_ends: false,
         })
     }
+
+    /// Compresses points into a sink that can't seek back to the offset to the chunk table.
+    ///
+    /// The offset is left as -1, which tells readers to find it in the last eight bytes of the
+    /// point data, after the chunk table.
+    pub(crate) fn streaming(mut write: W, header: Header) -> Result<PointWriter<W>> {
+        let vlr = header.laz_vlr()?;
+        write.write_all(&(-1i64).to_le_bytes())?;
+        let chunk_size = u64::from(vlr.chunk_size());
+        let compressor = ChunkWriter::new(write, vlr, chunk_size, ChunkTable::default(), None)?;
+        Ok(Self {
+            header,
+            compressor,
+            has_points: false,
+            chunk_ends: false,
+        })
+    }
+
+    pub(crate) fn has_variable_chunks(&self) -> bool {
+        self.compressor.vlr.uses_variable_size_chunks()
+    }
+
+    /// Ends the current variable size chunk, unless no points have been written yet.
+    pub(crate) fn finish_chunk(&mut self) {
+        self.chunk_ends = self.has_points;
+    }
 }
 
-impl<W: Write + Seek + Send> WritePoint<W> for PointWriter<'_, W> {
-    fn write_point(&mut self, point: Point) -> Result<()> {
-        self.header.add_point(&point);
-        self.buffer.set_position(0);
-        point
-            .into_raw(self.header.transforms())
-            .and_then(|raw_point| {
-                raw_point.write_to(&mut self.buffer, self.header.point_format())
-            })?;
-        self.compressor
-            .compress_one(self.buffer.get_ref())
-            .map_err(Error::from)
+impl<W: Write + Seek + Send> WritePoint<W> for PointWriter<W> {
+    fn write_raw_points(&mut self, bytes: &[u8]) -> Result<()> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        if self.chunk_ends {
+            self.compressor.finish_chunk()?;
+            self.chunk_ends = false;
+        }
+        self.compressor.compress_many(bytes)?;
+        self.has_points = true;
+        Ok(())
     }
 
-    fn into_inner(self: Box<Self>) -> W {
-        self.compressor.into_inner()
+    fn into_inner(self) -> W {
+        self.compressor.records.into_inner()
     }
 
     fn get_mut(&mut self) -> &mut W {
-        self.compressor.get_mut()
+        self.compressor.records.get_mut()
     }
 
     fn header(&self) -> &Header {
@@ -59,6 +160,172 @@ impl<W: Write + Seek + Send> WritePoint<W> for PointWriter<'_, W> {
     }
 }
 
+/// Compresses points into chunks that go after a chunk table's existing chunks, if there are any,
+/// and writes the whole table once it's done.
+///
+/// This is what laz's `LasZipCompressor` does for new point data, but that can only be used with
+/// a `'static` sink.
+struct ChunkWriter<W: Write + Seek + Send> {
+    records: Records<W>,
+    vlr: LazVlr,
+    /// The number of points in a chunk, unless one is finished early.
+    chunk_size: u64,
+    chunk_table: ChunkTable,
+    /// Where the offset to the chunk table goes, if the sink can seek back to it. Otherwise, the
+    /// offset goes after the table.
+    offset_position: Option<u64>,
+    chunk_start: u64,
+    chunk_points: u64,
+}
+
+impl<W: Write + Seek + Send> ChunkWriter<W> {
+    /// Starts a new chunk at the sink's position.
+    fn new(
+        mut write: W,
+        vlr: LazVlr,
+        chunk_size: u64,
+        chunk_table: ChunkTable,
+        offset_position: Option<u64>,
+    ) -> Result<ChunkWriter<W>> {
+        let chunk_start = write.stream_position()?;
+        Ok(ChunkWriter {
+            records: Records::new(vlr.items(), write)?,
+            vlr,
+            chunk_size,
+            chunk_table,
+            offset_position,
+            chunk_start,
+            chunk_points: 0,
+        })
+    }
+
+    fn compress_many(&mut self, points: &[u8]) -> Result<()> {
+        let record_size = self.records.record_size();
+        for point in points.chunks_exact(record_size) {
+            if self.chunk_points == self.chunk_size {
+                self.finish_chunk()?;
+            }
+            self.records.compress_next(point)?;
+            self.chunk_points += 1;
+        }
+        Ok(())
+    }
+
+    /// Ends the current chunk, unless it's empty.
+    fn finish_chunk(&mut self) -> Result<()> {
+        if self.chunk_points == 0 {
+            return Ok(());
+        }
+        self.records.finish(self.vlr.items())?;
+        let position = self.records.get_mut().stream_position()?;
+        self.chunk_table.push(ChunkTableEntry {
+            point_count: self.chunk_points,
+            byte_count: position - self.chunk_start,
+        });
+        self.chunk_start = position;
+        self.chunk_points = 0;
+        Ok(())
+    }
+
+    /// Ends the last chunk and writes the chunk table after it.
+    fn done(&mut self) -> Result<()> {
+        self.finish_chunk()?;
+        let write = self.records.get_mut();
+        let offset_to_chunk_table = write.stream_position()?;
+        self.chunk_table.write_to(&mut *write, &self.vlr)?;
+        if let Some(offset_position) = self.offset_position {
+            let end = write.stream_position()?;
+            let _ = write.seek(SeekFrom::Start(offset_position))?;
+            write.write_all(&offset_to_chunk_table.to_le_bytes())?;
+            let _ = write.seek(SeekFrom::Start(end))?;
+        } else {
+            write.write_all(&offset_to_chunk_table.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// The compressor for the records of a chunk, which depends on the version of the laz items.
+///
+/// The compressors are kept by value rather than boxed as trait objects, since those would need a
+/// `'static` sink.
+enum Records<W: Write> {
+    Sequential(SequentialPointRecordCompressor<'static, W>),
+    Layered(LayeredPointRecordCompressor<'static, W>),
+}
+
+impl<W: Write + Seek> Records<W> {
+    /// Makes a compressor for the records of one chunk, starting at the sink's position.
+    fn new(items: &Vec<LazItem>, output: W) -> Result<Records<W>> {
+        let mut records = match items.first().map(|item| item.version()) {
+            Some(1 | 2) => Records::Sequential(SequentialPointRecordCompressor::new(output)),
+            Some(3 | 4) => Records::Layered(LayeredPointRecordCompressor::new(output)),
+            _ => {
+                let item = items.first().ok_or(LasZipError::MissingChunkTable)?;
+                return Err(LasZipError::UnsupportedLazItemVersion(
+                    item.item_type(),
+                    item.version(),
+                )
+                .into());
+            }
+        };
+        records.set_fields_from(items)?;
+        Ok(records)
+    }
+
+    fn set_fields_from(&mut self, items: &Vec<LazItem>) -> Result<()> {
+        match self {
+            Records::Sequential(compressor) => compressor.set_fields_from(items)?,
+            Records::Layered(compressor) => compressor.set_fields_from(items)?,
+        }
+        Ok(())
+    }
+
+    fn record_size(&self) -> usize {
+        match self {
+            Records::Sequential(compressor) => compressor.record_size(),
+            Records::Layered(compressor) => compressor.record_size(),
+        }
+    }
+
+    fn compress_next(&mut self, point: &[u8]) -> Result<()> {
+        match self {
+            Records::Sequential(compressor) => compressor.compress_next(point)?,
+            Records::Layered(compressor) => compressor.compress_next(point)?,
+        }
+        Ok(())
+    }
+
+    /// Ends the chunk's records and gets ready for the next chunk.
+    fn finish(&mut self, items: &Vec<LazItem>) -> Result<()> {
+        match self {
+            Records::Sequential(compressor) => {
+                compressor.done()?;
+                compressor.reset();
+            }
+            Records::Layered(compressor) => {
+                compressor.done()?;
+                compressor.reset();
+            }
+        }
+        self.set_fields_from(items)
+    }
+
+    fn get_mut(&mut self) -> &mut W {
+        match self {
+            Records::Sequential(compressor) => compressor.get_mut(),
+            Records::Layered(compressor) => compressor.get_mut(),
+        }
+    }
+
+    fn into_inner(self) -> W {
+        match self {
+            Records::Sequential(compressor) => compressor.into_inner(),
+            Records::Layered(compressor) => compressor.into_inner(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{Builder, Point, Reader, Vlr, Writer};
@@ -71,6 +338,7 @@ mod tests {
             record_id: 42,
             description: "A great vlr".to_string(),
             data: b"some data".to_vec(),
+            reserved: None,
         };
         let mut builder = Builder::default();
         builder.version.minor = 4;
@@ -0,0 +1,270 @@
+use crate::{writer::WriteSummary, Header, Point, Result, Writer};
+use std::io::{Seek, Write};
+
+/// A change to one field of every point written by a [MapWriter].
+///
+/// Maps of optional fields, like [FieldMap::GpsTime], leave points without the field alone.
+#[derive(Clone, Copy, Debug)]
+pub enum FieldMap {
+    /// Maps the x coordinate.
+    X(fn(f64) -> f64),
+
+    /// Maps the y coordinate.
+    Y(fn(f64) -> f64),
+
+    /// Maps the z coordinate.
+    Z(fn(f64) -> f64),
+
+    /// Maps the intensity.
+    Intensity(fn(u16) -> u16),
+
+    /// Maps the gps time.
+    GpsTime(fn(f64) -> f64),
+
+    /// Maps the user data.
+    UserData(fn(u8) -> u8),
+
+    /// Maps the point source id.
+    PointSourceId(fn(u16) -> u16),
+
+    /// Changes anything on the point, e.g. to compute one field from others.
+    Point(fn(&mut Point)),
+}
+
+/// Wraps a [Writer], mapping the fields of each point before it's written.
+///
+/// The header is finalized from the mapped points, so its bounds hold the mapped coordinates.
+///
+/// # Examples
+///
+/// ```
+/// use las::{writer::{FieldMap, MapWriter}, Point, Reader, Writer};
+///
+/// let writer = Writer::default();
+/// let mut writer = MapWriter::new(writer, [FieldMap::Z(|z| z - 100.)]);
+/// writer.write_point(Point { z: 142., ..Default::default() }).unwrap();
+/// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+/// assert_eq!(42., reader.header().bounds().max.z);
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct MapWriter<W: Write + Seek + Send> {
+    writer: Writer<W>,
+    maps: Vec<FieldMap>,
+}
+
+impl FieldMap {
+    /// Applies this map to a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::FieldMap, Point};
+    /// let mut point = Point { intensity: 100, ..Default::default() };
+    /// FieldMap::Intensity(|intensity| intensity / 2).apply(&mut point);
+    /// assert_eq!(50, point.intensity);
+    /// ```
+    pub fn apply(&self, point: &mut Point) {
+        match *self {
+            FieldMap::X(f) => point.x = f(point.x),
+            FieldMap::Y(f) => point.y = f(point.y),
+            FieldMap::Z(f) => point.z = f(point.z),
+            FieldMap::Intensity(f) => point.intensity = f(point.intensity),
+            FieldMap::GpsTime(f) => point.gps_time = point.gps_time.map(f),
+            FieldMap::UserData(f) => point.user_data = f(point.user_data),
+            FieldMap::PointSourceId(f) => point.point_source_id = f(point.point_source_id),
+            FieldMap::Point(f) => f(point),
+        }
+    }
+}
+
+impl<W: Write + Seek + Send> MapWriter<W> {
+    /// Wraps a writer with maps, which are applied in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::{FieldMap, MapWriter}, Writer};
+    /// let writer = MapWriter::new(
+    ///     Writer::default(),
+    ///     [FieldMap::Intensity(|i| i.saturating_mul(2)), FieldMap::Z(|z| z * 0.3048)],
+    /// );
+    /// ```
+    pub fn new(writer: Writer<W>, maps: impl IntoIterator<Item = FieldMap>) -> MapWriter<W> {
+        MapWriter {
+            writer,
+            maps: maps.into_iter().collect(),
+        }
+    }
+
+    /// Maps a point and writes it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::{FieldMap, MapWriter}, Point, Writer};
+    /// let mut writer = MapWriter::new(Writer::default(), [FieldMap::X(|x| x + 1.)]);
+    /// writer.write_point(Point::default()).unwrap();
+    /// assert_eq!(1., writer.header().bounds().max.x);
+    /// ```
+    pub fn write_point(&mut self, mut point: Point) -> Result<()> {
+        for map in &self.maps {
+            map.apply(&mut point);
+        }
+        self.writer.write_point(point)
+    }
+
+    /// Returns the wrapped writer's header, which so far describes the mapped points written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::MapWriter, Writer};
+    /// let writer = MapWriter::new(Writer::default(), []);
+    /// assert_eq!(0, writer.header().number_of_points());
+    /// ```
+    pub fn header(&self) -> &Header {
+        self.writer.header()
+    }
+
+    /// Returns the maps, in the order they're applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::{FieldMap, MapWriter}, Writer};
+    /// let writer = MapWriter::new(Writer::default(), [FieldMap::Z(|z| z + 1.)]);
+    /// assert_eq!(1, writer.maps().len());
+    /// ```
+    pub fn maps(&self) -> &[FieldMap] {
+        &self.maps
+    }
+
+    /// Closes the wrapped writer, and returns its summary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::MapWriter, Writer};
+    /// let mut writer = MapWriter::new(Writer::default(), []);
+    /// writer.close().unwrap();
+    /// ```
+    pub fn close(&mut self) -> Result<WriteSummary> {
+        self.writer.close()
+    }
+
+    /// Returns the wrapped writer, without closing it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::MapWriter, Writer};
+    /// let writer = MapWriter::new(Writer::default(), []).into_writer();
+    /// ```
+    pub fn into_writer(self) -> Writer<W> {
+        self.writer
+    }
+
+    /// Closes the wrapped writer and returns its inner `Write`, seeked to the beginning of the las
+    /// data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::MapWriter, Writer};
+    /// let cursor = MapWriter::new(Writer::default(), []).into_inner().unwrap();
+    /// ```
+    pub fn into_inner(self) -> Result<W> {
+        self.writer.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, Builder, Reader};
+    use std::io::Cursor;
+
+    fn points() -> Vec<Point> {
+        (0..10)
+            .map(|i| Point {
+                x: f64::from(i),
+                y: 2. * f64::from(i),
+                z: 100. + f64::from(i),
+                intensity: 1000 * i as u16,
+                gps_time: Some(f64::from(i)),
+                user_data: i as u8,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn roundtrip(maps: Vec<FieldMap>) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(1).unwrap();
+        let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        let mut writer = MapWriter::new(writer, maps);
+        for point in points() {
+            writer.write_point(point).unwrap();
+        }
+        Reader::new(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn bounds_hold_mapped_values() {
+        let reader = roundtrip(vec![FieldMap::Z(|z| z - 100.)]);
+        let bounds = reader.header().bounds();
+        assert_eq!(0., bounds.min.z);
+        assert_eq!(9., bounds.max.z);
+        assert_eq!(0., bounds.min.x);
+        assert_eq!(18., bounds.max.y);
+    }
+
+    #[test]
+    fn unmapped_fields_untouched() {
+        let reader = roundtrip(vec![FieldMap::Intensity(|intensity| intensity / 1000)]);
+        for (expected, point) in points().into_iter().zip(reader.into_points().unwrap()) {
+            assert_eq!(expected.intensity / 1000, point.intensity);
+            assert_eq!(
+                Point {
+                    intensity: point.intensity,
+                    ..expected
+                },
+                point
+            );
+        }
+    }
+
+    #[test]
+    fn maps_compose_in_order() {
+        let reader = roundtrip(vec![
+            FieldMap::Z(|z| z - 100.),
+            FieldMap::Z(|z| z * 2.),
+            FieldMap::GpsTime(|t| t + 0.5),
+            FieldMap::Point(|point| point.user_data = point.z as u8),
+        ]);
+        let header = reader.header().clone();
+        assert_eq!(18., header.bounds().max.z);
+        for (i, point) in reader.into_points().unwrap().into_iter().enumerate() {
+            let i = i as f64;
+            assert_eq!(2. * i, point.z);
+            assert_eq!(Some(i + 0.5), point.gps_time);
+            assert_eq!(2 * i as u8, point.user_data);
+        }
+    }
+
+    #[test]
+    fn header_counts_mapped_points() {
+        let mut writer = MapWriter::new(Writer::default(), [FieldMap::X(|x| x * 10.)]);
+        writer.write_point(Point::default()).unwrap();
+        writer
+            .write_point(Point {
+                x: 1.,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(2, writer.header().number_of_points());
+        assert_eq!(10., writer.header().bounds().max.x);
+        let _ = writer.close().unwrap();
+        assert!(writer.write_point(Point::default()).is_err());
+    }
+}
@@ -31,49 +31,469 @@
 //! assert!(writer.write(point).is_err()); // the point's color would be lost
 //! ```
 
+mod atomic;
 mod las;
 #[cfg(feature = "laz")]
 mod laz;
+mod map;
+mod non_seekable;
+mod reproject;
+mod two_pass;
 
-use crate::{Error, Header, Point, Result};
+pub use self::{
+    atomic::AtomicFile,
+    map::{FieldMap, MapWriter},
+    non_seekable::NonSeekable,
+    reproject::{Reproject, Reprojection},
+    two_pass::TwoPass,
+};
+use crate::{
+    compatibility::Extended,
+    index::{IndexTarget, Indexer},
+    point::{ExtraBytesSchema, Format},
+    progress::Progress,
+    raw, waveform, Bounds, Embedded, Error, GpsTime, Header, LasPointRecord, Point,
+    PointDataDigest, ProgressEvent, Result, RoundingMode, Transform, Vector, Vlr, VlrRecord,
+};
+#[cfg(feature = "digest")]
+use crate::{digest::Hasher, Digest};
 use std::{
+    collections::HashMap,
     fmt::Debug,
-    fs::File,
+    fs::{File, OpenOptions},
     io::{BufWriter, Cursor, Seek, SeekFrom},
     path::Path,
+    time::{Duration, Instant},
 };
 
 trait WritePoint<W: std::io::Write>: Send {
-    fn write_point(&mut self, point: Point) -> Result<()>;
-    //https://users.rust-lang.org/t/is-there-a-way-to-move-a-trait-object/707
-    fn into_inner(self: Box<Self>) -> W;
+    fn write_raw_points(&mut self, bytes: &[u8]) -> Result<()>;
+    fn into_inner(self) -> W;
     fn get_mut(&mut self) -> &mut W;
     fn header(&self) -> &Header;
     fn header_mut(&mut self) -> &mut Header;
     fn done(&mut self) -> Result<()>;
 }
 
-struct ClosedPointWriter;
+/// Uncompressed and compressed point writers, statically dispatched.
+///
+/// `Closed` is left behind once the sink has been moved out by [Writer::into_inner].
+#[allow(clippy::large_enum_variant)]
+enum PointWriter<W: std::io::Write + Seek + Send> {
+    Las(las::PointWriter<W>),
+    #[cfg(feature = "laz")]
+    Laz(laz::PointWriter<W>),
+    Closed,
+}
 
-impl<W: std::io::Write> WritePoint<W> for ClosedPointWriter {
-    fn write_point(&mut self, _point: Point) -> Result<()> {
-        unreachable!()
+impl<W: std::io::Write + Seek + Send> WritePoint<W> for PointWriter<W> {
+    fn write_raw_points(&mut self, bytes: &[u8]) -> Result<()> {
+        match self {
+            PointWriter::Las(point_writer) => point_writer.write_raw_points(bytes),
+            #[cfg(feature = "laz")]
+            PointWriter::Laz(point_writer) => point_writer.write_raw_points(bytes),
+            PointWriter::Closed => Err(Error::ClosedWriter),
+        }
     }
-    fn into_inner(self: Box<Self>) -> W {
-        unreachable!()
+
+    fn into_inner(self) -> W {
+        match self {
+            PointWriter::Las(point_writer) => point_writer.into_inner(),
+            #[cfg(feature = "laz")]
+            PointWriter::Laz(point_writer) => point_writer.into_inner(),
+            PointWriter::Closed => unreachable!(),
+        }
     }
+
     fn get_mut(&mut self) -> &mut W {
-        unreachable!()
+        match self {
+            PointWriter::Las(point_writer) => point_writer.get_mut(),
+            #[cfg(feature = "laz")]
+            PointWriter::Laz(point_writer) => point_writer.get_mut(),
+            PointWriter::Closed => unreachable!(),
+        }
     }
+
     fn header(&self) -> &Header {
-        unreachable!()
+        match self {
+            PointWriter::Las(point_writer) => point_writer.header(),
+            #[cfg(feature = "laz")]
+            PointWriter::Laz(point_writer) => point_writer.header(),
+            PointWriter::Closed => unreachable!(),
+        }
     }
+
     fn header_mut(&mut self) -> &mut Header {
-        unreachable!()
+        match self {
+            PointWriter::Las(point_writer) => point_writer.header_mut(),
+            #[cfg(feature = "laz")]
+            PointWriter::Laz(point_writer) => point_writer.header_mut(),
+            PointWriter::Closed => unreachable!(),
+        }
     }
+
     fn done(&mut self) -> Result<()> {
-        unreachable!()
+        match self {
+            PointWriter::Las(point_writer) => point_writer.done(),
+            #[cfg(feature = "laz")]
+            PointWriter::Laz(point_writer) => point_writer.done(),
+            PointWriter::Closed => Err(Error::ClosedWriter),
+        }
+    }
+}
+
+/// The number of points serialized together by [Writer::write_par_iter].
+#[cfg(feature = "parallel")]
+const BATCH_SIZE: usize = 4096;
+
+/// The laszip chunk size that marks chunks as variable sized.
+const VARIABLE_LAZ_CHUNK_SIZE: u32 = u32::MAX;
+
+/// A run of serialized points, along with the counts and bounds they add to the header.
+#[cfg(feature = "parallel")]
+struct Batch {
+    bytes: Vec<u8>,
+    xy: Vec<(f64, f64)>,
+    /// The indices, point source ids and gps times, if the gps time order is checked.
+    times: Vec<(u64, u16, Option<f64>)>,
+    number_of_points: u64,
+    number_of_points_by_return: HashMap<u8, u64>,
+    /// The extended return counts, if the points are stored in compatibility mode.
+    extended_number_of_points_by_return: [u64; 15],
+    bounds: Bounds,
+    summary: WriteSummary,
+}
+
+#[cfg(feature = "parallel")]
+impl Batch {
+    fn new(
+        points: Vec<Point>,
+        start: u64,
+        format: &Format,
+        transforms: &Vector<Transform>,
+        extra_attributes: &ExtraBytesSchema,
+        compatibility: Option<&Extended>,
+        options: &WriteOptions,
+    ) -> Result<Batch> {
+        let mut batch = Batch {
+            bytes: Vec::with_capacity(points.len() * usize::from(format.len())),
+            xy: Vec::with_capacity(points.len()),
+            times: Vec::new(),
+            number_of_points: 0,
+            number_of_points_by_return: Default::default(),
+            extended_number_of_points_by_return: [0; 15],
+            bounds: Default::default(),
+            summary: Default::default(),
+        };
+        for (index, mut point) in (start..).zip(points) {
+            match check_finite(&point, options.non_finite, &mut batch.summary).map_err(
+                |source| Error::PointWriteFailed {
+                    index,
+                    source: Box::new(source),
+                },
+            )? {
+                Finite::Yes => {}
+                Finite::Skip => continue,
+                Finite::Replace(value) => replace_non_finite(&mut point, value),
+            }
+            if skip_out_of_range(&point, transforms, options, &mut batch.summary) {
+                continue;
+            }
+            verify_extra_attributes(&point, extra_attributes).map_err(|source| {
+                Error::PointWriteFailed {
+                    index,
+                    source: Box::new(source),
+                }
+            })?;
+            if options.gps_time_order.is_some() && point.gps_time.is_none() {
+                return Err(Error::PointWriteFailed {
+                    index,
+                    source: Box::new(Error::MissingGpsTime),
+                });
+            }
+            let extended_return_number = point.return_number;
+            if let Some(extended) = compatibility {
+                point = extended
+                    .distill(&point)
+                    .map_err(|source| Error::PointWriteFailed {
+                        index,
+                        source: Box::new(source),
+                    })?;
+            }
+            let stored = serialize_point(
+                &point,
+                index,
+                format,
+                transforms,
+                options,
+                &mut batch.summary,
+                &mut batch.bytes,
+            )?;
+            let point = match stored {
+                Some(xyz) => Point {
+                    x: xyz.x,
+                    y: xyz.y,
+                    z: xyz.z,
+                    ..point
+                },
+                None => point,
+            };
+            batch.number_of_points += 1;
+            if let Some(count) = batch
+                .extended_number_of_points_by_return
+                .get_mut(usize::from(extended_return_number).wrapping_sub(1))
+            {
+                *count += 1;
+            }
+            if point.return_number > 0 {
+                *batch
+                    .number_of_points_by_return
+                    .entry(point.return_number)
+                    .or_insert(0) += 1;
+            }
+            batch.bounds.grow(&point);
+            batch.xy.push((point.x, point.y));
+            if options.gps_time_order.is_some() {
+                batch
+                    .times
+                    .push((index, point.point_source_id, point.gps_time));
+            }
+        }
+        Ok(batch)
+    }
+}
+
+/// What to do with a point, once it's been checked against the [NonFinitePolicy].
+enum Finite {
+    Yes,
+    Skip,
+    Replace(f64),
+}
+
+/// Checks a point's coordinates and gps time, and counts what the policy does to it.
+///
+/// With [NonFinitePolicy::Error], a non-finite gps time is left for [verify_point] to refuse.
+fn check_finite(
+    point: &Point,
+    policy: NonFinitePolicy,
+    summary: &mut WriteSummary,
+) -> Result<Finite> {
+    let coordinates = [('x', point.x), ('y', point.y), ('z', point.z)];
+    if coordinates.iter().all(|(_, value)| value.is_finite())
+        && point.gps_time.is_none_or(f64::is_finite)
+    {
+        return Ok(Finite::Yes);
+    }
+    match policy {
+        NonFinitePolicy::Error => match coordinates.iter().find(|(_, value)| !value.is_finite()) {
+            Some(&(axis, value)) => Err(Error::NonFiniteCoordinate { axis, value }),
+            None => Ok(Finite::Yes),
+        },
+        NonFinitePolicy::Skip => {
+            summary.non_finite_skipped += 1;
+            Ok(Finite::Skip)
+        }
+        NonFinitePolicy::Replace(value) => {
+            summary.non_finite_replaced += 1;
+            Ok(Finite::Replace(value))
+        }
+    }
+}
+
+/// Returns true if [OutOfRangePolicy::Skip] leaves the point out, and counts it.
+fn skip_out_of_range(
+    point: &Point,
+    transforms: &Vector<Transform>,
+    options: &WriteOptions,
+    summary: &mut WriteSummary,
+) -> bool {
+    if options.out_of_range != OutOfRangePolicy::Skip {
+        return false;
+    }
+    let coordinates = [
+        (&transforms.x, point.x),
+        (&transforms.y, point.y),
+        (&transforms.z, point.z),
+    ];
+    let skip = coordinates
+        .into_iter()
+        .any(|(transform, n)| transform.inverse_saturating(n, options.rounding_mode).1);
+    summary.out_of_range_skipped += u64::from(skip);
+    skip
+}
+
+fn replace_non_finite(point: &mut Point, value: f64) {
+    for n in [&mut point.x, &mut point.y, &mut point.z]
+        .into_iter()
+        .chain(point.gps_time.as_mut())
+    {
+        if !n.is_finite() {
+            *n = value;
+        }
+    }
+}
+
+/// Checks a point against the format and appends its record to `bytes`.
+///
+/// Nothing is cloned from the point. Failures are wrapped with the index of the point in the
+/// input. If the point's coordinates had to be clamped, returns the coordinates that were
+/// stored instead.
+fn serialize_point(
+    point: &Point,
+    index: u64,
+    format: &Format,
+    transforms: &Vector<Transform>,
+    options: &WriteOptions,
+    summary: &mut WriteSummary,
+    bytes: &mut Vec<u8>,
+) -> Result<Option<Vector<f64>>> {
+    let wrap = |source| Error::PointWriteFailed {
+        index,
+        source: Box::new(source),
+    };
+    let (mut raw_point, clamped) = verify_point(point, format)
+        .and_then(|()| to_raw_point(point, transforms, options))
+        .map_err(wrap)?;
+    if let Some(policy) = options.strict_precision {
+        check_precision(point, &raw_point, transforms, policy, summary).map_err(wrap)?;
+    }
+    let mut color_normalized = false;
+    if options.normalize_color_to_16bit
+        && let Some(color) = raw_point.color
+    {
+        let scaled = color.scaled_to_16bit();
+        color_normalized = scaled != color;
+        raw_point.color = Some(scaled);
+    }
+    raw_point
+        .write_without_extra_bytes_to(&mut *bytes, format)
+        .map_err(wrap)?;
+    bytes.extend_from_slice(&point.extra_bytes);
+    summary.colors_normalized += u64::from(color_normalized);
+    summary.withheld_points += u64::from(point.is_withheld);
+    if clamped {
+        summary.points_clamped += 1;
+        Ok(Some(Vector {
+            x: transforms.x.direct(raw_point.x),
+            y: transforms.y.direct(raw_point.y),
+            z: transforms.z.direct(raw_point.z),
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Transforms a point's coordinates with the writer's rounding mode, and returns the raw point
+/// along with whether any coordinate was clamped.
+fn to_raw_point(
+    point: &Point,
+    transforms: &Vector<Transform>,
+    options: &WriteOptions,
+) -> Result<(raw::Point, bool)> {
+    let mut clamped = false;
+    let mut inverse = |transform: &Transform, n: f64| {
+        if options.out_of_range == OutOfRangePolicy::Clamp {
+            let (n, was_clamped) = transform.inverse_saturating(n, options.rounding_mode);
+            clamped |= was_clamped;
+            Ok(n)
+        } else {
+            transform.inverse_with_rounding_mode(n, options.rounding_mode)
+        }
+    };
+    let xyz = Vector {
+        x: inverse(&transforms.x, point.x)?,
+        y: inverse(&transforms.y, point.y)?,
+        z: inverse(&transforms.z, point.z)?,
+    };
+    Ok((point.to_raw_at(xyz)?, clamped))
+}
+
+/// Refuses or counts a point if any of its coordinates is stored more than half the scale away.
+fn check_precision(
+    point: &Point,
+    raw_point: &raw::Point,
+    transforms: &Vector<Transform>,
+    policy: PrecisionLoss,
+    summary: &mut WriteSummary,
+) -> Result<()> {
+    let coordinates = [
+        ('x', point.x, raw_point.x, &transforms.x),
+        ('y', point.y, raw_point.y, &transforms.y),
+        ('z', point.z, raw_point.z, &transforms.z),
+    ];
+    for (axis, value, n, transform) in coordinates {
+        let stored = transform.direct(n);
+        // Rounding to the nearest integer is off by up to half the scale, give or take the
+        // floating point error of the transform itself.
+        if (stored - value).abs() > transform.scale / 2. * (1. + 1e-9) {
+            match policy {
+                PrecisionLoss::Error => {
+                    return Err(Error::PrecisionLoss {
+                        axis,
+                        value,
+                        stored,
+                    });
+                }
+                PrecisionLoss::Count => {
+                    summary.precision_loss += 1;
+                    return Ok(());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Refuses points that don't match the format, or that have a gps time that couldn't be a
+/// [GpsTime].
+pub(crate) fn verify_point(point: &Point, format: &Format) -> Result<()> {
+    let mismatches = point.mismatches(format);
+    if !mismatches.is_empty() {
+        return Err(Error::PointAttributesDoNotMatch {
+            format: *format,
+            mismatches,
+        });
+    }
+    if let Some(gps_time) = point.gps_time {
+        let _ = GpsTime::new(gps_time)?;
+    }
+    Ok(())
+}
+
+/// Refuses points whose extra bytes don't hold every attribute that the header declares.
+fn verify_extra_attributes(point: &Point, schema: &ExtraBytesSchema) -> Result<()> {
+    match schema.missing(&point.extra_bytes) {
+        Some(descriptor) => Err(Error::ExtraAttributeNotFound(descriptor.name.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the points written match the header that was written before them.
+fn verify_committed_header(committed: &Header, actual: &Header) -> Result<()> {
+    if committed.number_of_points() != actual.number_of_points() {
+        return Err(Error::CannotStream(
+            "the number of points doesn't match the committed header",
+        ));
+    }
+    let (committed, actual) = (committed.bounds(), actual.bounds());
+    if actual.min.x <= actual.max.x
+        && (actual.min.x < committed.min.x
+            || actual.min.y < committed.min.y
+            || actual.min.z < committed.min.z
+            || actual.max.x > committed.max.x
+            || actual.max.y > committed.max.y
+            || actual.max.z > committed.max.z)
+    {
+        return Err(Error::CannotStream(
+            "the points are outside the committed header's bounds",
+        ));
     }
+    Ok(())
+}
+
+/// Returns true if this is the user and record id of the waveform data packet record.
+fn is_waveform_data(user_id: &str, record_id: u16) -> bool {
+    user_id == waveform::USER_ID && record_id == waveform::RECORD_ID
 }
 
 /// Writes LAS data.
@@ -109,328 +529,3529 @@ pub trait Write {
     fn write(&mut self, point: Point) -> Result<()>;
 }
 
-/// Writes LAS data.
+/// Options for a [Writer], beyond what its [Header] configures.
 ///
-/// The LAS header needs to be re-written when the writer closes. For convenience, this is done via
-/// the `Drop` implementation of the writer. One consequence is that if the header re-write fails
-/// during the drop, a panic will result. If you want to check for errors instead of panicing, use
-/// `close` explicitly.
+/// # Examples
 ///
 /// ```
 /// use std::io::Cursor;
-/// use las::Writer;
-/// {
-///     let mut writer = Writer::default();
-///     writer.close().unwrap();
-/// } // <- `close` is not called
+/// use las::{index::IndexTarget, writer::WriteOptions, Builder, Writer};
+///
+/// let options = WriteOptions::default().spatial_index(IndexTarget::Evlr);
+/// let header = Builder::from((1, 4)).into_header().unwrap();
+/// let writer = Writer::with_options(Cursor::new(Vec::new()), header, options);
 /// ```
-#[allow(missing_debug_implementations)]
-pub struct Writer<W: 'static + std::io::Write + Seek + Send> {
-    closed: bool,
-    start: u64,
-    point_writer: Box<dyn WritePoint<W> + Send>,
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WriteOptions {
+    spatial_index: Option<IndexTarget>,
+    index_cell_size: Option<f64>,
+    index_bounds: Option<Bounds>,
+    normalize_color_to_16bit: bool,
+    rounding_mode: RoundingMode,
+    out_of_range: OutOfRangePolicy,
+    laz_chunk_size: Option<u32>,
+    non_finite: NonFinitePolicy,
+    gps_time_order: Option<GpsTimeOrder>,
+    gps_time_order_per_source_id: bool,
+    #[cfg(feature = "digest")]
+    point_data_digest: Option<Digest>,
+    strict_precision: Option<PrecisionLoss>,
+    compatibility_mode: bool,
 }
 
-impl<W: 'static + std::io::Write + Seek + Send> Writer<W> {
-    /// Creates a new writer.
+/// What a [Writer] does with points whose coordinates or gps time are NaN or infinite.
+///
+/// # Examples
+///
+/// ```
+/// use las::writer::{NonFinitePolicy, WriteOptions};
+/// let options = WriteOptions::default().non_finite(NonFinitePolicy::Replace(-9999.));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NonFinitePolicy {
+    /// Refuses the point, with an error that names the point and the coordinate.
+    #[default]
+    Error,
+
+    /// Leaves the point out, and counts it in [WriteSummary::non_finite_skipped].
+    Skip,
+
+    /// Replaces each non-finite value with this one, and counts the point in
+    /// [WriteSummary::non_finite_replaced].
+    Replace(f64),
+}
+
+/// What a [Writer] does with points whose coordinates are too big or too small to store with the
+/// header's transforms.
+///
+/// # Examples
+///
+/// ```
+/// use las::writer::{OutOfRangePolicy, WriteOptions};
+/// let options = WriteOptions::default().out_of_range(OutOfRangePolicy::Skip);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutOfRangePolicy {
+    /// Refuses the point, with an error that names the point and the transform.
+    #[default]
+    Error,
+
+    /// Leaves the point out, and counts it in [WriteSummary::out_of_range_skipped].
+    Skip,
+
+    /// Clamps the coordinates to the ends of the range that can be stored, and counts the point
+    /// in [WriteSummary::points_clamped].
+    Clamp,
+}
+
+/// What a [Writer] does with points that are out of gps time order, see
+/// [WriteOptions::require_gps_time_order].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpsTimeOrder {
+    /// Refuses the first point whose gps time is before the previous point's, with an error that
+    /// names both points and their times.
+    Error,
+
+    /// Writes every point, and counts those whose gps time is before the previous point's in
+    /// [WriteSummary::gps_time_order_violations].
+    Count,
+}
+
+/// What a [Writer] does with points whose coordinates are stored more than half the scale away
+/// from their value, see [WriteOptions::strict_precision].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrecisionLoss {
+    /// Refuses the point, with an error that names the coordinate and how it would be stored.
+    Error,
+
+    /// Writes every point, and counts those with a coordinate that lost precision in
+    /// [WriteSummary::precision_loss].
+    Count,
+}
+
+/// The last gps time written, per point source id if the order is checked per source id.
+#[derive(Clone, Debug)]
+struct GpsTimeOrderTracker {
+    policy: GpsTimeOrder,
+    per_source_id: bool,
+    previous: HashMap<u16, (u64, f64)>,
+}
+
+impl WriteOptions {
+    /// Builds a LAStools spatial index as points are written, and writes it out when the writer
+    /// closes.
     ///
-    /// The header that is passed in will have various fields zero'd, e.g. bounds, number of
-    /// points, etc.
+    /// The quadtree covers the bounds of the header passed to the writer, or
+    /// [WriteOptions::index_bounds] if they are set. Points outside of those bounds are still
+    /// indexed, in the cells on the edge of the quadtree.
+    pub fn spatial_index(mut self, target: IndexTarget) -> WriteOptions {
+        self.spatial_index = Some(target);
+        self
+    }
+
+    /// Sets the size of the smallest cells in the spatial index.
+    ///
+    /// Defaults to the size that gives the quadtree eight levels.
+    pub fn index_cell_size(mut self, cell_size: f64) -> WriteOptions {
+        self.index_cell_size = Some(cell_size);
+        self
+    }
+
+    /// Sets the bounds that the spatial index covers.
+    pub fn index_bounds(mut self, bounds: Bounds) -> WriteOptions {
+        self.index_bounds = Some(bounds);
+        self
+    }
+
+    /// Treats every point's color as eight bit, and stretches it to sixteen bits when writing.
+    ///
+    /// See [Color::scaled_to_16bit](crate::Color::scaled_to_16bit). The points themselves aren't
+    /// changed, only what's written.
     ///
     /// # Examples
     ///
     /// ```
     /// use std::io::Cursor;
-    /// use las::Writer;
-    /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default());
+    /// use las::{point::Format, writer::WriteOptions, Builder, Color, Point, Reader, Writer};
+    ///
+    /// let mut builder = Builder::from((1, 2));
+    /// builder.point_format = Format::new(2).unwrap();
+    /// let options = WriteOptions::default().normalize_color_to_16bit();
+    /// let mut writer =
+    ///     Writer::with_options(Cursor::new(Vec::new()), builder.into_header().unwrap(), options)
+    ///         .unwrap();
+    /// writer
+    ///     .write_point(Point { color: Some(Color::new(255, 0, 0)), ..Default::default() })
+    ///     .unwrap();
+    /// let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert_eq!(Some(Color::new(65535, 0, 0)), point.color);
     /// ```
-    pub fn new(mut write: W, mut header: Header) -> Result<Writer<W>> {
-        let start = write.stream_position()?;
-        header.clear();
-        if header.point_format().is_compressed {
-            #[cfg(feature = "laz")]
-            {
-                header.add_laz_vlr()?;
-                header.write_to(&mut write)?;
-                Ok(Writer {
-                    closed: false,
-                    start,
-                    point_writer: Box::new(laz::PointWriter::new(write, header)?),
-                })
-            }
-            #[cfg(not(feature = "laz"))]
-            {
-                Err(Error::LaszipNotEnabled)
-            }
-        } else {
-            header.write_to(&mut write)?;
-            Ok(Writer {
-                closed: false,
-                start,
-                point_writer: Box::new(las::PointWriter::new(write, header)),
-            })
-        }
+    pub fn normalize_color_to_16bit(mut self) -> WriteOptions {
+        self.normalize_color_to_16bit = true;
+        self
     }
 
-    /// Close this writer.
+    /// Sets how coordinates are rounded when they're scaled and offset to integers.
+    ///
+    /// Defaults to [RoundingMode::Round].
     ///
     /// # Examples
     ///
     /// ```
     /// use std::io::Cursor;
-    /// use las::Writer;
-    /// let mut writer = Writer::default();
-    /// writer.close().unwrap();
-    /// assert!(writer.close().is_err());
+    /// use las::{writer::WriteOptions, Point, Reader, RoundingMode, Writer};
+    ///
+    /// let options = WriteOptions::default().rounding_mode(RoundingMode::Floor);
+    /// let mut writer =
+    ///     Writer::with_options(Cursor::new(Vec::new()), Default::default(), options).unwrap();
+    /// writer.write_point(Point { x: 0.0019, ..Default::default() }).unwrap();
+    /// let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// assert_eq!(0.001, reader.read_point().unwrap().unwrap().x);
     /// ```
-    pub fn close(&mut self) -> Result<()> {
-        if self.closed {
-            return Err(Error::ClosedWriter);
-        }
-
-        self.point_writer.done()?;
-
-        let point_padding = self.header().point_padding().clone();
-        self.point_writer.get_mut().write_all(&point_padding)?;
-
-        let start_of_first_evlr = self.point_writer.get_mut().stream_position()?;
-        self.point_writer
-            .header_mut()
-            .set_start_of_first_evlr(start_of_first_evlr);
-        let raw_evlrs: Vec<Result<crate::raw::Vlr>> = {
-            self.point_writer
-                .header()
-                .evlrs()
-                .iter()
-                .map(|evlr| evlr.clone().into_raw(true))
-                .collect()
-        };
-
-        for raw_evlr in raw_evlrs {
-            raw_evlr?.write_to(self.point_writer.get_mut())?;
-        }
+    pub fn rounding_mode(mut self, rounding_mode: RoundingMode) -> WriteOptions {
+        self.rounding_mode = rounding_mode;
+        self
+    }
 
-        let _ = self
-            .point_writer
-            .get_mut()
-            .seek(SeekFrom::Start(self.start))?;
-        self.header()
-            .clone()
-            .into_raw()
-            .and_then(|raw_header| raw_header.write_to(self.point_writer.get_mut()))?;
-        let _ = self
-            .point_writer
-            .get_mut()
-            .seek(SeekFrom::Start(self.start))?;
-        self.closed = true;
-        Ok(())
+    /// Clamps coordinates that are too big or too small to store, instead of refusing the point.
+    ///
+    /// The header's bounds cover the clamped coordinates, and the number of clamped points is
+    /// counted in [Writer::summary]. This is the same as
+    /// [WriteOptions::out_of_range] with [OutOfRangePolicy::Clamp].
+    pub fn clamp_out_of_range(mut self) -> WriteOptions {
+        self.out_of_range = OutOfRangePolicy::Clamp;
+        self
     }
 
-    /// Returns a reference to this writer's header.
+    /// Sets what happens to points whose coordinates are too big or too small to store.
+    ///
+    /// Defaults to [OutOfRangePolicy::Error]. Skipped points don't count towards the header, and
+    /// point indices in errors still count them, like [NonFinitePolicy::Skip].
     ///
     /// # Examples
     ///
     /// ```
-    /// use las::Writer;
+    /// use las::{writer::{OutOfRangePolicy, WriteOptions}, Point, Writer};
     ///
-    /// let writer = Writer::default();
-    /// let header = writer.header();
+    /// let options = WriteOptions::default().out_of_range(OutOfRangePolicy::Skip);
+    /// let mut writer =
+    ///     Writer::with_options(std::io::Cursor::new(Vec::new()), Default::default(), options).unwrap();
+    /// writer.write_point(Point { x: 1e100, ..Default::default() }).unwrap();
+    /// writer.write_point(Point::default()).unwrap();
+    /// let summary = writer.close().unwrap();
+    /// assert_eq!(1, summary.out_of_range_skipped);
+    /// assert_eq!(1, summary.points_written);
     /// ```
-    pub fn header(&self) -> &Header {
-        self.point_writer.header()
+    pub fn out_of_range(mut self, policy: OutOfRangePolicy) -> WriteOptions {
+        self.out_of_range = policy;
+        self
     }
 
-    /// Writes a point.
+    /// Compresses points in chunks of this many points, instead of the laszip default.
+    ///
+    /// The header's point format must be compressed, and the size can't be zero.
+    pub fn laz_chunk_size(mut self, chunk_size: u32) -> WriteOptions {
+        self.laz_chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Compresses points in chunks that end when `Writer::finish_laz_chunk` is called, instead of
+    /// every so many points.
+    ///
+    /// The header's point format must be compressed. This replaces [WriteOptions::laz_chunk_size].
     ///
     /// # Examples
     ///
     /// ```
-    /// use las::Writer;
-    ///
-    /// let mut writer = Writer::default();
-    /// writer.write_point(Default::default()).unwrap();
+    /// use las::writer::WriteOptions;
+    /// let options = WriteOptions::default().variable_laz_chunks();
     /// ```
-    pub fn write_point(&mut self, point: Point) -> Result<()> {
-        if self.closed {
-            return Err(Error::ClosedWriter);
-        }
-        if !point.matches(self.header().point_format()) {
-            return Err(Error::PointAttributesDoNotMatch(
-                *self.header().point_format(),
-            ));
-        }
-        self.point_writer.write_point(point)
-    }
-
-    /// Writes a point.
-    #[deprecated(since = "0.9.0", note = "Use write_point() instead")]
-    pub fn write(&mut self, point: Point) -> Result<()> {
-        self.write_point(point)
+    pub fn variable_laz_chunks(mut self) -> WriteOptions {
+        self.laz_chunk_size = Some(VARIABLE_LAZ_CHUNK_SIZE);
+        self
     }
 
-    /// Closes this writer and returns its inner `Write`, seeked to the beginning of the las data.
+    /// Sets what happens to points whose coordinates or gps time are NaN or infinite.
+    ///
+    /// Defaults to [NonFinitePolicy::Error]. A replacement value must itself be finite.
     ///
     /// # Examples
     ///
     /// ```
-    /// use las::Writer;
-    /// let writer = Writer::default();
-    /// let cursor = writer.into_inner().unwrap();
+    /// use std::io::Cursor;
+    /// use las::{writer::{NonFinitePolicy, WriteOptions}, Point, Writer};
+    ///
+    /// let options = WriteOptions::default().non_finite(NonFinitePolicy::Skip);
+    /// let mut writer =
+    ///     Writer::with_options(Cursor::new(Vec::new()), Default::default(), options).unwrap();
+    /// writer.write_point(Point { x: f64::NAN, ..Default::default() }).unwrap();
+    /// assert_eq!(0, writer.header().number_of_points());
+    /// assert_eq!(1, writer.summary().non_finite_skipped);
     /// ```
-    pub fn into_inner(mut self) -> Result<W> {
-        if !self.closed {
-            self.close()?;
-        }
-
-        // since Writer implements Drop, the stream cannot be moved
-        // to work around this, we replace the current point writer with a point writer
-        // that will panic on any of the PintWriter fn call, basically meaning
-        // that any call to a Writer function after close can potentially cause a panic
-        // if the method does no checks for self.closed before, which should not be
-        // a problem as this function moves the writer, meaning the user won't have
-        // access to it anymore
-        let point_writer = std::mem::replace(&mut self.point_writer, Box::new(ClosedPointWriter));
-        let mut inner = point_writer.into_inner();
-        let _ = inner.seek(SeekFrom::Start(self.start))?;
-        Ok(inner)
+    pub fn non_finite(mut self, policy: NonFinitePolicy) -> WriteOptions {
+        self.non_finite = policy;
+        self
     }
-}
 
-#[allow(deprecated)]
-impl<W: 'static + std::io::Write + Seek + Debug + Send> Write for Writer<W> {
-    fn header(&self) -> &Header {
-        self.header()
+    /// Requires points to be written in gps time order, or counts the points that aren't.
+    ///
+    /// Points with the same gps time are in order. Every point needs a gps time, so the header's
+    /// point format must have one, and a point without one is an error with either policy. Use
+    /// [WriteOptions::gps_time_order_per_source_id] to only compare points with the same point
+    /// source id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{point::Format, writer::{GpsTimeOrder, WriteOptions}, Builder, Point, Writer};
+    ///
+    /// let mut builder = Builder::from((1, 2));
+    /// builder.point_format = Format::new(1).unwrap();
+    /// let options = WriteOptions::default().require_gps_time_order(GpsTimeOrder::Error);
+    /// let mut writer =
+    ///     Writer::with_options(Cursor::new(Vec::new()), builder.into_header().unwrap(), options)
+    ///         .unwrap();
+    /// writer.write_point(Point { gps_time: Some(2.), ..Default::default() }).unwrap();
+    /// assert!(writer.write_point(Point { gps_time: Some(1.), ..Default::default() }).is_err());
+    /// ```
+    pub fn require_gps_time_order(mut self, policy: GpsTimeOrder) -> WriteOptions {
+        self.gps_time_order = Some(policy);
+        self
     }
 
-    fn write(&mut self, point: Point) -> Result<()> {
-        self.write(point)
+    /// Checks the gps time order of each point source id on its own.
+    ///
+    /// Without [WriteOptions::require_gps_time_order], this does nothing.
+    pub fn gps_time_order_per_source_id(mut self) -> WriteOptions {
+        self.gps_time_order_per_source_id = true;
+        self
     }
-}
 
-impl Writer<BufWriter<File>> {
-    /// Creates a new writer for a path.
+    /// Digests the point records as they're written, and stores the digest in a
+    /// [PointDataDigest] vlr when the writer closes, for [verify_digest](crate::verify_digest).
     ///
-    /// If the "laz" feature is enabled, guesses from the extension if the
-    /// data will be written compressed
+    /// The vlr is written with the header, and its digest is filled in on close. A digest vlr in
+    /// the header passed to the writer is always dropped, since it wouldn't describe the new points.
     ///
     /// # Examples
     ///
     /// ```
-    /// use las::Writer;
-    /// let writer = Writer::from_path("/dev/null", Default::default());
+    /// use std::io::Cursor;
+    /// use las::{writer::WriteOptions, Digest, PointDataDigest, Reader, Writer};
+    ///
+    /// let options = WriteOptions::default().point_data_digest(Digest::Sha256);
+    /// let mut writer =
+    ///     Writer::with_options(Cursor::new(Vec::new()), Default::default(), options).unwrap();
+    /// writer.write_point(Default::default()).unwrap();
+    /// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// let record = reader.header().get_record::<PointDataDigest>().unwrap().unwrap();
+    /// assert_eq!(1, record.number_of_points);
+    /// ```
+    #[cfg(feature = "digest")]
+    pub fn point_data_digest(mut self, digest: Digest) -> WriteOptions {
+        self.point_data_digest = Some(digest);
+        self
+    }
+
+    /// Checks that each coordinate is stored within half the scale of its value, and refuses or
+    /// counts the points that aren't.
+    ///
+    /// Rounding to the nearest integer never loses more than half the scale, so this catches
+    /// coordinates that are moved further by a [RoundingMode] that rounds up or down, or by
+    /// [WriteOptions::clamp_out_of_range].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{writer::{PrecisionLoss, WriteOptions}, Point, RoundingMode, Writer};
+    ///
+    /// let options = WriteOptions::default()
+    ///     .rounding_mode(RoundingMode::Floor)
+    ///     .strict_precision(PrecisionLoss::Error);
+    /// let mut writer =
+    ///     Writer::with_options(Cursor::new(Vec::new()), Default::default(), options).unwrap();
+    /// writer.write_point(Point { x: 1.0004, ..Default::default() }).unwrap();
+    /// assert!(writer.write_point(Point { x: 1.0009, ..Default::default() }).is_err());
+    /// ```
+    pub fn strict_precision(mut self, policy: PrecisionLoss) -> WriteOptions {
+        self.strict_precision = Some(policy);
+        self
+    }
+
+    /// Stores points of the extended formats in laszip's las 1.4 compatibility mode, so that laz
+    /// readers that only know the legacy point formats can read them.
+    ///
+    /// The header's point format must be extended and compressed. Points are written as the legacy
+    /// format with the same fields, i.e. six as one, seven and eight as three, nine as four, and
+    /// ten as five, and whatever the legacy format can't hold, e.g. return numbers above five,
+    /// classes above 31, the overlap flag, the scanner channel, the fine scan angle, and the near
+    /// infrared, goes into extra bytes attributes after the points' own. The header is stored as
+    /// las 1.2, or 1.3 for waveforms, with a "lascompatible" vlr that keeps the extended return
+    /// counts, and that's what [Writer::header] returns. A [Reader](crate::Reader) turns the points
+    /// back into the extended format.
+    ///
+    /// Laszip gives legacy points up to seven returns, but this writer gives them at most five,
+    /// since a las 1.2 header can't count more. Points written with [Writer::write_raw_point] and
+    /// [Writer::write_record] are stored as they are, so they must already be legacy records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "laz")]
+    /// # {
+    /// use las::{point::Format, writer::WriteOptions, Builder, GpsTimeType, Point, Reader, Writer};
+    /// use std::io::Cursor;
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format = Format::new(6).unwrap();
+    /// builder.point_format.is_compressed = true;
+    /// builder.gps_time_type = GpsTimeType::Standard;
+    /// let options = WriteOptions::default().compatibility_mode();
+    /// let mut writer =
+    ///     Writer::with_options(Cursor::new(Vec::new()), builder.into_header().unwrap(), options)
+    ///         .unwrap();
+    /// assert_eq!(1, writer.header().point_format().to_u8().unwrap());
+    /// let point = Point { return_number: 7, number_of_returns: 9, gps_time: Some(0.), ..Default::default() };
+    /// writer.write_point(point.clone()).unwrap();
+    ///
+    /// let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// assert_eq!(6, reader.header().point_format().to_u8().unwrap());
+    /// assert_eq!(point, reader.read_point().unwrap().unwrap());
+    /// # }
+    /// ```
+    pub fn compatibility_mode(mut self) -> WriteOptions {
+        self.compatibility_mode = true;
+        self
+    }
+
+    /// Returns the laz chunk size for this header, which is [VARIABLE_LAZ_CHUNK_SIZE] for variable
+    /// size chunks, or `None` for the laszip default.
+    #[cfg(feature = "laz")]
+    fn effective_laz_chunk_size(&self, header: &Header) -> Option<u32> {
+        use crate::header::LazChunks;
+
+        self.laz_chunk_size.or(match header.laz_chunks() {
+            Some(LazChunks::Fixed(chunk_size)) => Some(chunk_size),
+            Some(LazChunks::Variable) => Some(VARIABLE_LAZ_CHUNK_SIZE),
+            None => None,
+        })
+    }
+
+    /// Returns a tracker for the gps time order, if it's checked.
+    fn gps_time_order_tracker(&self) -> Option<GpsTimeOrderTracker> {
+        self.gps_time_order.map(|policy| GpsTimeOrderTracker {
+            policy,
+            per_source_id: self.gps_time_order_per_source_id,
+            previous: HashMap::new(),
+        })
+    }
+
+    /// Returns an error if an option can't be used with this header.
+    fn verify(&self, header: &Header) -> Result<()> {
+        if self.gps_time_order.is_some() && !header.point_format().has_gps_time {
+            return Err(Error::InvalidWriteOption {
+                option: "require_gps_time_order",
+                reason: format!(
+                    "the header's point format {} has no gps time",
+                    header.point_format()
+                ),
+            });
+        }
+        if let NonFinitePolicy::Replace(value) = self.non_finite
+            && !value.is_finite()
+        {
+            return Err(Error::InvalidWriteOption {
+                option: "non_finite",
+                reason: format!("the replacement {value} isn't finite"),
+            });
+        }
+        if self.compatibility_mode {
+            let format = header.point_format();
+            if !(format.is_extended && format.is_compressed) {
+                return Err(Error::InvalidWriteOption {
+                    option: "compatibility_mode",
+                    reason: format!(
+                        "the header's point format {format} isn't extended and compressed"
+                    ),
+                });
+            }
+        }
+        if let Some(chunk_size) = self.laz_chunk_size {
+            if !header.point_format().is_compressed {
+                return Err(Error::InvalidWriteOption {
+                    option: if chunk_size == VARIABLE_LAZ_CHUNK_SIZE {
+                        "variable_laz_chunks"
+                    } else {
+                        "laz_chunk_size"
+                    },
+                    reason: format!(
+                        "the header's point format {} is uncompressed",
+                        header.point_format()
+                    ),
+                });
+            }
+            if chunk_size == 0 {
+                return Err(Error::InvalidWriteOption {
+                    option: "laz_chunk_size",
+                    reason: "chunks must have at least one point".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What a [Writer] has written, and what its lenient options have done to the points.
+///
+/// Returned by [Writer::close], and by [Writer::summary] for the points written so far. With the
+/// `serde` feature, it can be serialized, e.g. for job logs.
+///
+/// # Examples
+///
+/// ```
+/// use las::Writer;
+/// let mut writer = Writer::default();
+/// writer.write_point(Default::default()).unwrap();
+/// let summary = writer.close().unwrap();
+/// assert_eq!(1, summary.points_written);
+/// assert_eq!(0, summary.points_clamped);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct WriteSummary {
+    /// The number of points written.
+    pub points_written: u64,
+
+    /// The number of points written with each return number, return number one first.
+    pub number_of_points_by_return: [u64; 15],
+
+    /// The bounds of the points written.
+    pub bounds: Bounds,
+
+    /// The time since the writer was created, or until it was closed.
+    pub elapsed: Duration,
+
+    /// The size of the output, from the start of the header to the end of the last evlr, or zero
+    /// if the writer isn't closed yet.
+    pub bytes_written: u64,
+
+    /// The number of points whose coordinates were clamped by
+    /// [WriteOptions::clamp_out_of_range].
+    pub points_clamped: u64,
+
+    /// The number of points whose colors were changed by
+    /// [WriteOptions::normalize_color_to_16bit].
+    pub colors_normalized: u64,
+
+    /// The number of withheld points written.
+    ///
+    /// Withheld points count towards the header's bounds and point counts like any other point,
+    /// so if this isn't zero, the bounds may be wider than the points that aren't withheld.
+    pub withheld_points: u64,
+
+    /// The number of points left out by [NonFinitePolicy::Skip].
+    pub non_finite_skipped: u64,
+
+    /// The number of points left out by [OutOfRangePolicy::Skip].
+    pub out_of_range_skipped: u64,
+
+    /// The number of points with values replaced by [NonFinitePolicy::Replace].
+    pub non_finite_replaced: u64,
+
+    /// The number of points whose gps time is before the previous point's, counted by
+    /// [GpsTimeOrder::Count].
+    pub gps_time_order_violations: u64,
+
+    /// The number of points with a coordinate stored more than half the scale away from its
+    /// value, counted by [PrecisionLoss::Count].
+    pub precision_loss: u64,
+}
+
+impl WriteSummary {
+    fn add(&mut self, other: &WriteSummary) {
+        self.points_clamped += other.points_clamped;
+        self.colors_normalized += other.colors_normalized;
+        self.withheld_points += other.withheld_points;
+        self.non_finite_skipped += other.non_finite_skipped;
+        self.out_of_range_skipped += other.out_of_range_skipped;
+        self.non_finite_replaced += other.non_finite_replaced;
+        self.gps_time_order_violations += other.gps_time_order_violations;
+        self.precision_loss += other.precision_loss;
+    }
+}
+
+impl GpsTimeOrderTracker {
+    /// Checks a point's gps time against the previous one, and returns what to remember once
+    /// the point is written.
+    fn check(&self, point: &Point, summary: &mut WriteSummary) -> Result<(u16, f64)> {
+        let time = point.gps_time.ok_or(Error::MissingGpsTime)?;
+        let key = if self.per_source_id {
+            point.point_source_id
+        } else {
+            0
+        };
+        if let Some(&(previous_index, previous_time)) = self.previous.get(&key)
+            && time < previous_time
+        {
+            match self.policy {
+                GpsTimeOrder::Error => {
+                    return Err(Error::GpsTimeOutOfOrder {
+                        time,
+                        previous_index,
+                        previous_time,
+                    });
+                }
+                GpsTimeOrder::Count => summary.gps_time_order_violations += 1,
+            }
+        }
+        Ok((key, time))
+    }
+
+    /// Remembers the gps time of a written point.
+    fn remember(&mut self, (key, time): (u16, f64), index: u64) {
+        let _ = self.previous.insert(key, (index, time));
+    }
+}
+
+/// Writes LAS data.
+///
+/// A writer is generic over its sink, e.g. a `BufWriter<File>` or a `Cursor<Vec<u8>>`, and the
+/// sink can be recovered with [Writer::into_inner].
+///
+/// The LAS header needs to be re-written when the writer closes. For convenience, the `Drop`
+/// implementation of the writer tries to, but it never panics, so any error is lost. Call
+/// [Writer::close] to find out whether the file was finished.
+///
+/// If writing to the sink fails, the file is left in an unknown state, so the writer is
+/// [poisoned](Writer::is_poisoned): later writes, and closing, return [Error::WriterPoisoned], and
+/// dropping it doesn't try to finish the file.
+///
+/// ```
+/// use std::io::Cursor;
+/// use las::Writer;
+/// {
+///     let mut writer = Writer::default();
+///     writer.close().unwrap();
+/// } // <- `close` is not called
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct Writer<W: std::io::Write + Seek + Send> {
+    closed: bool,
+    /// The failure that poisoned this writer, if any.
+    poisoned: Option<String>,
+    start: u64,
+    point_writer: PointWriter<W>,
+    index: Option<(IndexTarget, Indexer)>,
+    buffer: Vec<u8>,
+    options: WriteOptions,
+    summary: WriteSummary,
+    /// Where the evlrs start, once the point data is finished.
+    evlr_start: Option<u64>,
+    streamed_evlrs: u32,
+    evlr_open: bool,
+    /// Where the waveform data packet record starts, if it has been written as an evlr.
+    waveform_data_start: Option<u64>,
+    /// The header that was written before the points, for a sink that can't seek back to write
+    /// it again.
+    committed_header: Option<Header>,
+    gps_time_order: Option<GpsTimeOrderTracker>,
+    #[cfg(feature = "digest")]
+    digest: Option<Hasher>,
+    extra_attributes: ExtraBytesSchema,
+    /// The extended format of the points, if they're stored in compatibility mode.
+    compatibility: Option<Extended>,
+    /// Called with the sink once the file is complete, e.g. to move an atomic file into place.
+    on_close: Option<fn(&mut W) -> Result<()>>,
+    created: Instant,
+    progress: Option<Progress>,
+}
+
+/// Streams the data of an evlr straight to a [Writer]'s sink.
+///
+/// This is created by [Writer::begin_evlr], and the evlr is only complete once
+/// [EvlrWriter::finish] is called.
+#[allow(missing_debug_implementations)]
+pub struct EvlrWriter<'a, W: std::io::Write + Seek + Send> {
+    writer: &'a mut Writer<W>,
+    start: u64,
+    len: u64,
+}
+
+impl<W: std::io::Write + Seek + Send> Writer<W> {
+    /// Creates a new writer.
+    ///
+    /// The header that is passed in will have various fields zero'd, e.g. bounds, number of
+    /// points, etc.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::Writer;
+    /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default());
+    /// ```
+    pub fn new(write: W, header: Header) -> Result<Writer<W>> {
+        Writer::with_options(write, header, WriteOptions::default())
+    }
+
+    /// Creates a writer for a las file that starts at the sink's current position, inside a larger
+    /// stream.
+    ///
+    /// The file's offsets count from that position, so the bytes from there on are a valid las
+    /// file, and the writer never seeks before it. See [Embedded].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Reader, Writer};
+    /// use std::io::{Cursor, Write};
+    ///
+    /// let mut cursor = Cursor::new(Vec::new());
+    /// cursor.write_all(b"preamble").unwrap();
+    /// let mut writer = Writer::new_at(cursor, Default::default()).unwrap();
+    /// writer.write_point(Default::default()).unwrap();
+    /// let bytes = writer.into_inner().unwrap().into_inner().into_inner();
+    /// assert_eq!(1, Reader::new_at(Cursor::new(bytes), 8).unwrap().header().number_of_points());
+    /// ```
+    pub fn new_at(write: W, header: Header) -> Result<Writer<Embedded<W>>> {
+        Writer::new(Embedded::here(write)?, header)
+    }
+
+    /// Positions a sink after the points of an existing file with this header, for appending.
+    ///
+    /// Compressed points also need their chunk table, see [Writer::appending_compressed].
+    fn appending(mut write: W, mut header: Header) -> Result<Writer<W>> {
+        let offset_to_point_data = appendable_offset(&mut header)?;
+        if header.point_format().is_compressed {
+            return Err(Error::LaszipNotEnabled);
+        }
+        let end_of_points = offset_to_point_data
+            + header.number_of_points() * u64::from(header.point_data_record_length());
+        let _ = write.seek(SeekFrom::Start(end_of_points))?;
+        Ok(Writer::resumed(PointWriter::Las(las::PointWriter::new(
+            write, header,
+        ))))
+    }
+
+    /// Positions a sink after the compressed points of an existing file with this header, for
+    /// appending chunks to their chunk table.
+    #[cfg(feature = "laz")]
+    fn appending_compressed(
+        write: W,
+        mut header: Header,
+        chunk_table: ::laz::laszip::ChunkTable,
+    ) -> Result<Writer<W>> {
+        let offset_to_point_data = appendable_offset(&mut header)?;
+        let point_writer =
+            laz::PointWriter::appending(write, header, offset_to_point_data, chunk_table)?;
+        Ok(Writer::resumed(PointWriter::Laz(point_writer)))
+    }
+
+    /// Wraps a point writer that carries on after the points already in its sink.
+    fn resumed(point_writer: PointWriter<W>) -> Writer<W> {
+        // A schema that doesn't parse is copied through as it is, so there's nothing to enforce.
+        let extra_attributes = point_writer
+            .header()
+            .extra_bytes_schema()
+            .unwrap_or_default();
+        let options = WriteOptions::default();
+        Writer {
+            closed: false,
+            poisoned: None,
+            start: 0,
+            point_writer,
+            index: None,
+            buffer: Vec::new(),
+            gps_time_order: options.gps_time_order_tracker(),
+            #[cfg(feature = "digest")]
+            digest: None,
+            extra_attributes,
+            compatibility: None,
+            options,
+            summary: Default::default(),
+            evlr_start: None,
+            streamed_evlrs: 0,
+            evlr_open: false,
+            waveform_data_start: None,
+            committed_header: None,
+            on_close: None,
+            progress: None,
+            created: Instant::now(),
+        }
+    }
+
+    /// Creates a new writer with options.
+    ///
+    /// Returns an error if an option conflicts with the header, e.g.
+    /// [WriteOptions::laz_chunk_size] with an uncompressed point format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{writer::WriteOptions, Writer};
+    /// let writer = Writer::with_options(
+    ///     Cursor::new(Vec::new()),
+    ///     Default::default(),
+    ///     WriteOptions::default(),
+    /// );
+    /// ```
+    pub fn with_options(write: W, header: Header, options: WriteOptions) -> Result<Writer<W>> {
+        Writer::create(write, header, options, false)
+    }
+
+    /// Creates a new writer, writing the header as it is if `commit` is true, and empty otherwise.
+    fn create(
+        mut write: W,
+        mut header: Header,
+        options: WriteOptions,
+        commit: bool,
+    ) -> Result<Writer<W>> {
+        if let Some(n) = header.point_format().unknown {
+            return Err(Error::UnknownPointFormat(n));
+        }
+        header.apply_effective_transforms();
+        options.verify(&header)?;
+        let extra_attributes = header.extra_bytes_schema().unwrap_or_default();
+        let (compatibility, mut header) = if options.compatibility_mode {
+            let (extended, header) = Extended::new(header)?;
+            (Some(extended), header)
+        } else {
+            (None, header)
+        };
+        let index = match &options.spatial_index {
+            Some(target) => {
+                if *target == IndexTarget::Evlr {
+                    header
+                        .version()
+                        .verify_support_for::<crate::feature::Evlrs>()?;
+                }
+                let bounds = options.index_bounds.unwrap_or(header.bounds());
+                if !(bounds.min.x.is_finite()
+                    && bounds.min.y.is_finite()
+                    && bounds.max.x.is_finite()
+                    && bounds.max.y.is_finite())
+                {
+                    return Err(Error::SpatialIndexBoundsRequired);
+                }
+                Some((
+                    target.clone(),
+                    Indexer::new(&bounds, options.index_cell_size),
+                ))
+            }
+            None => None,
+        };
+        let start = write.stream_position()?;
+        let mut committed_header = commit.then(|| header.clone());
+        header.clear();
+        let _ = header.remove_vlrs(|vlr| PointDataDigest::matches(&vlr));
+        #[cfg(feature = "digest")]
+        if let Some(digest) = options.point_data_digest {
+            let placeholder = PointDataDigest {
+                digest,
+                number_of_points: 0,
+                value: Default::default(),
+            };
+            header.vlrs.push(placeholder.to_vlr()?);
+        }
+        if header.point_format().is_compressed {
+            #[cfg(feature = "laz")]
+            {
+                let chunk_size = options.effective_laz_chunk_size(&header);
+                header.add_laz_vlr_with_chunk_size(chunk_size)?;
+                let _ = header.recompute_offsets();
+                let point_writer = if let Some(committed_header) = &mut committed_header {
+                    committed_header.add_laz_vlr_with_chunk_size(chunk_size)?;
+                    let _ = committed_header.recompute_offsets();
+                    committed_header.write_to(&mut write)?;
+                    laz::PointWriter::streaming(write, header)?
+                } else {
+                    header.write_to(&mut write)?;
+                    laz::PointWriter::new(write, header)?
+                };
+                Ok(Writer {
+                    closed: false,
+                    poisoned: None,
+                    start,
+                    point_writer: PointWriter::Laz(point_writer),
+                    index,
+                    buffer: Vec::new(),
+                    gps_time_order: options.gps_time_order_tracker(),
+                    #[cfg(feature = "digest")]
+                    digest: options.point_data_digest.map(Digest::hasher),
+                    extra_attributes,
+                    compatibility,
+                    options,
+                    summary: Default::default(),
+                    evlr_start: None,
+                    streamed_evlrs: 0,
+                    evlr_open: false,
+                    waveform_data_start: None,
+                    committed_header,
+                    on_close: None,
+                    progress: None,
+                    created: Instant::now(),
+                })
+            }
+            #[cfg(not(feature = "laz"))]
+            {
+                Err(Error::LaszipNotEnabled)
+            }
+        } else {
+            let _ = header.recompute_offsets();
+            if let Some(committed_header) = &mut committed_header {
+                let _ = committed_header.recompute_offsets();
+                committed_header.write_to(&mut write)?;
+            } else {
+                header.write_to(&mut write)?;
+            }
+            Ok(Writer {
+                closed: false,
+                poisoned: None,
+                start,
+                point_writer: PointWriter::Las(las::PointWriter::new(write, header)),
+                index,
+                buffer: Vec::new(),
+                gps_time_order: options.gps_time_order_tracker(),
+                #[cfg(feature = "digest")]
+                digest: options.point_data_digest.map(Digest::hasher),
+                extra_attributes,
+                compatibility,
+                options,
+                summary: Default::default(),
+                evlr_start: None,
+                streamed_evlrs: 0,
+                evlr_open: false,
+                waveform_data_start: None,
+                committed_header,
+                on_close: None,
+                progress: None,
+                created: Instant::now(),
+            })
+        }
+    }
+
+    /// Close this writer, and returns a summary of what it wrote.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::Writer;
+    /// let mut writer = Writer::default();
+    /// let summary = writer.close().unwrap();
+    /// assert_eq!(0, summary.points_written);
+    /// assert!(writer.close().is_err());
+    /// ```
+    pub fn close(&mut self) -> Result<WriteSummary> {
+        let summary = self.finish_file()?;
+        if summary.withheld_points > 0 {
+            log::warn!(
+                "{} withheld points count towards the header's bounds",
+                summary.withheld_points
+            );
+        }
+        Ok(summary)
+    }
+
+    /// Returns true if writing to the sink failed, so this writer can't write or close any more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Writer;
+    /// let writer = Writer::default();
+    /// assert!(!writer.is_poisoned());
+    /// ```
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.is_some()
+    }
+
+    /// Returns the sink, e.g. to move what's been written so far somewhere else.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        self.point_writer.get_mut()
+    }
+
+    /// Closes without logging, for [Writer::close] and drop.
+    fn finish_file(&mut self) -> Result<WriteSummary> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.check_poisoned()?;
+        if self.evlr_open {
+            return Err(Error::UnfinishedEvlr);
+        }
+        let result = self.write_end();
+        self.poison(result)
+    }
+
+    /// Returns [Error::WriterPoisoned] if this writer is poisoned.
+    fn check_poisoned(&self) -> Result<()> {
+        match &self.poisoned {
+            Some(reason) => Err(Error::WriterPoisoned(reason.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the index of the next point in the input, counting the points left out by a skip
+    /// policy.
+    fn next_index(&self) -> u64 {
+        self.header().number_of_points()
+            + self.summary.non_finite_skipped
+            + self.summary.out_of_range_skipped
+    }
+
+    /// Poisons this writer if the result is an error.
+    fn poison<T, E: std::fmt::Display>(
+        &mut self,
+        result: std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        if let Err(err) = &result {
+            let _ = self.poisoned.get_or_insert_with(|| err.to_string());
+        }
+        result
+    }
+
+    /// Writes everything after the points, and the header again.
+    fn write_end(&mut self) -> Result<WriteSummary> {
+        let start_of_first_evlr = self.finish_points()?;
+        #[cfg(feature = "digest")]
+        self.write_digest()?;
+
+        if let Some((target, indexer)) = self.index.take() {
+            let lax = indexer.into_lax()?;
+            match target {
+                IndexTarget::Sidecar(path) => {
+                    let mut write = BufWriter::new(File::create(path)?);
+                    lax.write_to(&mut write)?;
+                    std::io::Write::flush(&mut write)?;
+                }
+                IndexTarget::Evlr => self.point_writer.header_mut().evlrs.push(lax.into_vlr()?),
+            }
+        }
+
+        let end_of_streamed_evlrs = self.point_writer.get_mut().stream_position()?;
+        self.point_writer
+            .header_mut()
+            .set_start_of_first_evlr(end_of_streamed_evlrs);
+        let raw_evlrs: Vec<Result<raw::Vlr>> = {
+            self.point_writer
+                .header()
+                .evlrs()
+                .iter()
+                .map(|evlr| evlr.clone().into_raw(true))
+                .collect()
+        };
+
+        let waveform_data = self
+            .point_writer
+            .header()
+            .evlrs()
+            .iter()
+            .position(|evlr| is_waveform_data(&evlr.user_id, evlr.record_id));
+        for (i, raw_evlr) in raw_evlrs.into_iter().enumerate() {
+            if waveform_data == Some(i) {
+                self.waveform_data_start =
+                    Some(self.point_writer.get_mut().stream_position()? - self.start);
+            }
+            raw_evlr?.write_to(self.point_writer.get_mut())?;
+        }
+        let end = self.point_writer.get_mut().stream_position()?;
+        self.write_compatibility_vlr()?;
+
+        if let Some(committed_header) = &self.committed_header {
+            verify_committed_header(committed_header, self.point_writer.header())?;
+            std::io::Write::flush(self.point_writer.get_mut())?;
+            self.closed = true;
+            self.summary.bytes_written = end - self.start;
+            self.summary.elapsed = self.created.elapsed();
+            return Ok(self.summary());
+        }
+        let _ = self
+            .point_writer
+            .get_mut()
+            .seek(SeekFrom::Start(self.start))?;
+        let mut raw_header = self.header().clone().into_raw()?;
+        if self.streamed_evlrs > 0 {
+            let number_of_evlrs = raw_header
+                .evlr
+                .map_or(0, |evlr| evlr.number_of_evlrs)
+                .checked_add(self.streamed_evlrs)
+                .ok_or(Error::TooManyEvlrs(usize::MAX))?;
+            raw_header.evlr = Some(raw::header::Evlr {
+                start_of_first_evlr,
+                number_of_evlrs,
+            });
+        }
+        if self.waveform_data_start.is_some() {
+            raw_header.start_of_waveform_data_packet_record = self.waveform_data_start;
+        }
+        raw_header.write_to(self.point_writer.get_mut())?;
+        let _ = self
+            .point_writer
+            .get_mut()
+            .seek(SeekFrom::Start(self.start))?;
+        if let Some(on_close) = self.on_close {
+            on_close(self.point_writer.get_mut())?;
+        }
+        self.closed = true;
+        self.summary.bytes_written = end - self.start;
+        self.summary.elapsed = self.created.elapsed();
+        Ok(self.summary())
+    }
+
+    /// Finishes the point data, if it isn't already, and returns where the evlrs start.
+    fn finish_points(&mut self) -> Result<u64> {
+        if let Some(evlr_start) = self.evlr_start {
+            return Ok(evlr_start);
+        }
+        self.point_writer.done()?;
+        let point_padding = self.header().point_padding().clone();
+        self.point_writer.get_mut().write_all(&point_padding)?;
+        let evlr_start = self.point_writer.get_mut().stream_position()?;
+        self.evlr_start = Some(evlr_start);
+        Ok(evlr_start)
+    }
+
+    /// Fills in the digest vlr, which was written with the header, with the digest of the points.
+    #[cfg(feature = "digest")]
+    fn write_digest(&mut self) -> Result<()> {
+        let (Some(hasher), Some(digest)) = (self.digest.take(), self.options.point_data_digest)
+        else {
+            return Ok(());
+        };
+        let header = self.point_writer.header_mut();
+        let Some(index) = header.vlrs.iter().position(PointDataDigest::matches) else {
+            return Ok(());
+        };
+        let data = PointDataDigest {
+            digest,
+            number_of_points: header.number_of_points(),
+            value: hasher.finish(),
+        }
+        .encode()?;
+        let offset = self.start + header.vlr_data_offset(index);
+        header.vlrs[index].data.clone_from(&data);
+        let write = self.point_writer.get_mut();
+        let position = write.stream_position()?;
+        let _ = write.seek(SeekFrom::Start(offset))?;
+        write.write_all(&data)?;
+        let _ = write.seek(SeekFrom::Start(position))?;
+        Ok(())
+    }
+
+    /// Fills in the compatibility vlr, which was written with the header, with the extended
+    /// counts.
+    fn write_compatibility_vlr(&mut self) -> Result<()> {
+        let Some(extended) = &self.compatibility else {
+            return Ok(());
+        };
+        let header = self.point_writer.header_mut();
+        let Some((index, data)) = extended.finish(header, self.waveform_data_start) else {
+            return Ok(());
+        };
+        let offset = self.start + header.vlr_data_offset(index);
+        header.vlrs[index].data.clone_from(&data);
+        let write = self.point_writer.get_mut();
+        let position = write.stream_position()?;
+        let _ = write.seek(SeekFrom::Start(offset))?;
+        write.write_all(&data)?;
+        let _ = write.seek(SeekFrom::Start(position))?;
+        Ok(())
+    }
+
+    /// Starts an evlr whose data is streamed straight to the sink, for records too big to hold in
+    /// memory.
+    ///
+    /// The header's version must support evlrs. This finishes the point data, so no more points
+    /// can be written afterwards. Streamed evlrs come before the header's own evlrs in the file,
+    /// and aren't in [Writer::header]. If the [EvlrWriter] is dropped without being finished,
+    /// closing the writer returns an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Cursor, Write};
+    /// use las::{Builder, Reader, Writer};
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    /// writer.write_point(Default::default()).unwrap();
+    /// let mut evlr = writer.begin_evlr("my software", 1, "big record").unwrap();
+    /// evlr.write_all(&[42; 100]).unwrap();
+    /// evlr.finish().unwrap();
+    /// assert!(writer.write_point(Default::default()).is_err());
+    ///
+    /// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// assert_eq!(vec![42; 100], reader.header().evlrs()[0].data);
+    /// ```
+    pub fn begin_evlr(
+        &mut self,
+        user_id: &str,
+        record_id: u16,
+        description: &str,
+    ) -> Result<EvlrWriter<'_, W>> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.check_poisoned()?;
+        if self.evlr_open {
+            return Err(Error::UnfinishedEvlr);
+        }
+        if self.committed_header.is_some() {
+            return Err(Error::CannotStream(
+                "the committed header doesn't count streamed evlrs",
+            ));
+        }
+        self.header()
+            .version()
+            .verify_support_for::<crate::feature::Evlrs>()?;
+        let raw_evlr = Vlr {
+            user_id: user_id.to_string(),
+            record_id,
+            description: description.to_string(),
+            data: Vec::new(),
+            reserved: None,
+        }
+        .into_raw(true)?;
+        let result = self.write_evlr_header(raw_evlr);
+        let start = self.poison(result)?;
+        if is_waveform_data(user_id, record_id) && self.waveform_data_start.is_none() {
+            self.waveform_data_start = Some(start - self.start);
+        }
+        self.evlr_open = true;
+        Ok(EvlrWriter {
+            writer: self,
+            start,
+            len: 0,
+        })
+    }
+
+    /// Finishes the points and writes the start of a streamed evlr, returning where it starts.
+    fn write_evlr_header(&mut self, raw_evlr: raw::Vlr) -> Result<u64> {
+        let _ = self.finish_points()?;
+        let start = self.point_writer.get_mut().stream_position()?;
+        raw_evlr.write_to(self.point_writer.get_mut())?;
+        Ok(start)
+    }
+
+    /// Returns a reference to this writer's header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Writer;
+    ///
+    /// let writer = Writer::default();
+    /// let header = writer.header();
+    /// ```
+    pub fn header(&self) -> &Header {
+        self.point_writer.header()
+    }
+
+    /// Returns the options this writer was created with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::WriteOptions, Writer};
+    ///
+    /// let writer = Writer::default();
+    /// assert_eq!(&WriteOptions::default(), writer.options());
+    /// ```
+    pub fn options(&self) -> &WriteOptions {
+        &self.options
+    }
+
+    /// Returns what this writer's lenient options have done to the points written so far.
+    ///
+    /// The summary is final once the writer is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Format, writer::WriteOptions, Builder, Color, Point, Writer};
+    /// use std::io::Cursor;
+    ///
+    /// let mut builder = Builder::from((1, 2));
+    /// builder.point_format = Format::new(2).unwrap();
+    /// let options = WriteOptions::default().normalize_color_to_16bit();
+    /// let mut writer =
+    ///     Writer::with_options(Cursor::new(Vec::new()), builder.into_header().unwrap(), options)
+    ///         .unwrap();
+    /// writer
+    ///     .write_point(Point { color: Some(Color::new(255, 0, 0)), ..Default::default() })
+    ///     .unwrap();
+    /// writer.close().unwrap();
+    /// assert_eq!(1, writer.summary().colors_normalized);
+    /// ```
+    pub fn summary(&self) -> WriteSummary {
+        let header = self.header();
+        let mut number_of_points_by_return = [0; 15];
+        for (n, count) in (1..).zip(&mut number_of_points_by_return) {
+            *count = header.number_of_points_by_return(n).unwrap_or(0);
+        }
+        WriteSummary {
+            points_written: header.number_of_points(),
+            number_of_points_by_return,
+            bounds: header.bounds(),
+            elapsed: if self.closed {
+                self.summary.elapsed
+            } else {
+                self.created.elapsed()
+            },
+            ..self.summary
+        }
+    }
+
+    /// Writes a point.
+    ///
+    /// Returns an error if the point doesn't match the point format, or if its coordinates or gps
+    /// time are NaN or infinite, unless [WriteOptions::non_finite] says otherwise. Also returns an
+    /// error, without writing anything, if the header's version can't count this point, e.g. the
+    /// `u32::MAX + 1`th point of a las 1.2 file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Writer;
+    ///
+    /// let mut writer = Writer::default();
+    /// writer.write_point(Default::default()).unwrap();
+    /// ```
+    pub fn write_point(&mut self, point: Point) -> Result<()> {
+        self.write_point_ref(&point)
+    }
+
+    /// Writes a point from a reference, without cloning it.
+    ///
+    /// This is the same as [Writer::write_point], but the points can be kept afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Writer};
+    ///
+    /// let points = vec![Point::default(); 10];
+    /// let mut writer = Writer::default();
+    /// for point in &points {
+    ///     writer.write_point_ref(point).unwrap();
+    /// }
+    /// assert_eq!(10, writer.header().number_of_points());
+    /// ```
+    pub fn write_point_ref(&mut self, point: &Point) -> Result<()> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.check_poisoned()?;
+        if self.evlr_start.is_some() {
+            return Err(Error::PointsAfterEvlr);
+        }
+        let index = self.next_index();
+        let mut summary = WriteSummary::default();
+        let replaced;
+        let point =
+            match check_finite(point, self.options.non_finite, &mut summary).map_err(|source| {
+                Error::PointWriteFailed {
+                    index,
+                    source: Box::new(source),
+                }
+            })? {
+                Finite::Yes => point,
+                Finite::Skip => {
+                    self.summary.add(&summary);
+                    return Ok(());
+                }
+                Finite::Replace(value) => {
+                    let mut point = point.clone();
+                    replace_non_finite(&mut point, value);
+                    replaced = point;
+                    &replaced
+                }
+            };
+        let transforms = self.point_writer.header().transforms();
+        if skip_out_of_range(point, transforms, &self.options, &mut summary) {
+            self.summary.add(&summary);
+            return Ok(());
+        }
+        let wrap = |source| Error::PointWriteFailed {
+            index,
+            source: Box::new(source),
+        };
+        verify_extra_attributes(point, &self.extra_attributes).map_err(wrap)?;
+        let gps_time = match &self.gps_time_order {
+            Some(tracker) => Some(tracker.check(point, &mut summary).map_err(wrap)?),
+            None => None,
+        };
+        let extended_return_number = point.return_number;
+        let distilled;
+        let point = match &self.compatibility {
+            Some(extended) => {
+                distilled = extended.distill(point).map_err(wrap)?;
+                &distilled
+            }
+            None => point,
+        };
+        self.header()
+            .verify_room_for(1, [(point.return_number, 1)])
+            .map_err(wrap)?;
+        self.buffer.clear();
+        let stored = serialize_point(
+            point,
+            index,
+            self.point_writer.header().point_format(),
+            self.point_writer.header().transforms(),
+            &self.options,
+            &mut summary,
+            &mut self.buffer,
+        )?;
+        let result = self.point_writer.write_raw_points(&self.buffer);
+        self.poison(result)?;
+        #[cfg(feature = "digest")]
+        if let Some(hasher) = &mut self.digest {
+            hasher.update(&self.buffer);
+        }
+        if let (Some(tracker), Some(gps_time)) = (&mut self.gps_time_order, gps_time) {
+            tracker.remember(gps_time, index);
+        }
+        if let Some(extended) = &mut self.compatibility {
+            extended.add(extended_return_number);
+        }
+        self.summary.add(&summary);
+        let clamped = stored.map(|xyz| Point {
+            x: xyz.x,
+            y: xyz.y,
+            z: xyz.z,
+            ..point.clone()
+        });
+        let point = clamped.as_ref().unwrap_or(point);
+        let position = self.header().number_of_points();
+        self.point_writer.header_mut().add_point(point);
+        if let Some((_, indexer)) = &mut self.index {
+            indexer.add(position, point.x, point.y);
+        }
+        self.report_progress();
+        Ok(())
+    }
+
+    /// Writes a point of your own type, encoded straight into a record.
+    ///
+    /// No [Point] is made along the way, see [LasPointRecord]. The record
+    /// is written like [Writer::write_raw_point], so it isn't checked against the [WriteOptions].
+    /// Returns an error if the raw point doesn't have as many extra bytes as the point format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raw, reader::RawPointView, Header, LasPointRecord, Result, Writer};
+    ///
+    /// struct Height(f64);
+    ///
+    /// impl LasPointRecord for Height {
+    ///     fn decode(view: RawPointView<'_>, header: &Header) -> Result<Height> {
+    ///         Ok(Height(header.effective_transforms().z.direct(view.z())))
+    ///     }
+    ///
+    ///     fn encode(&self, header: &Header) -> Result<raw::Point> {
+    ///         let z = header.transforms().z.inverse(self.0)?;
+    ///         Ok(raw::Point { z, ..Default::default() })
+    ///     }
+    /// }
+    ///
+    /// let mut writer = Writer::default();
+    /// writer.write_record(&Height(42.)).unwrap();
+    /// assert_eq!(42., writer.header().bounds().max.z);
+    /// ```
+    pub fn write_record<T: LasPointRecord>(&mut self, point: &T) -> Result<()> {
+        let raw_point = point.encode(self.header())?;
+        let format = self.header().point_format();
+        let extra_bytes = usize::from(format.extra_bytes);
+        if raw_point.extra_bytes.len() != extra_bytes {
+            let expected = usize::from(format.len());
+            return Err(Error::InvalidRecordLength {
+                expected,
+                len: expected - extra_bytes + raw_point.extra_bytes.len(),
+            });
+        }
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        let result = raw_point
+            .write_to(&mut buffer, self.header().point_format())
+            .and_then(|()| self.write_raw_point(&buffer));
+        self.buffer = buffer;
+        result
+    }
+
+    /// Calls `callback` each time another `every` points have been written.
+    ///
+    /// Points that are written in batches are reported once per batch, so the callback can be
+    /// called less often than asked. The count is the number of points in the file, so it includes
+    /// the points that were already there when appending. Zero is the same as one. This replaces
+    /// any earlier callback.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Writer};
+    /// use std::sync::{
+    ///     atomic::{AtomicU64, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// let written = Arc::new(AtomicU64::new(0));
+    /// let counter = Arc::clone(&written);
+    /// let mut writer = Writer::default();
+    /// writer.on_progress(10, move |event| counter.store(event.points, Ordering::Relaxed));
+    /// for _ in 0..25 {
+    ///     writer.write_point(Point::default()).unwrap();
+    /// }
+    /// assert_eq!(20, written.load(Ordering::Relaxed));
+    /// ```
+    pub fn on_progress(
+        &mut self,
+        every: u64,
+        callback: impl FnMut(ProgressEvent) + Send + 'static,
+    ) {
+        self.progress = Some(Progress::new(every, callback));
+    }
+
+    /// Calls the progress callback, if there is one and it's due.
+    fn report_progress(&mut self) {
+        if let Some(progress) = &mut self.progress {
+            let header = self.point_writer.header();
+            let points = header.number_of_points();
+            let format = header.point_format();
+            let bytes = (!format.is_compressed).then(|| points * u64::from(format.len()));
+            progress.update(points, None, bytes);
+        }
+    }
+
+    /// Writes a raw point record as it is, without decoding and encoding it.
+    ///
+    /// This copies points between files with the same point format and transforms, e.g. records
+    /// from [Reader::raw_records](crate::Reader::raw_records). Only the coordinates and the return
+    /// number are read from the record, to keep the header's counts and bounds. The record isn't
+    /// checked against the [WriteOptions], and it's compressed if the writer is.
+    ///
+    /// Returns an error if the record isn't as long as the point format's records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Reader, Writer};
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let header = reader.header().clone().into_builder().into_header().unwrap();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    /// for record in reader.raw_records() {
+    ///     writer.write_raw_point(&record.unwrap()).unwrap();
+    /// }
+    /// assert_eq!(106, writer.header().number_of_points());
+    /// assert!(writer.write_raw_point(&[0; 3]).is_err());
+    /// ```
+    pub fn write_raw_point(&mut self, record: &[u8]) -> Result<()> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.check_poisoned()?;
+        if self.evlr_start.is_some() {
+            return Err(Error::PointsAfterEvlr);
+        }
+        let format = *self.header().point_format();
+        if let Some(n) = format.unknown {
+            return Err(Error::UnknownPointFormat(n));
+        }
+        let expected = usize::from(format.len());
+        if record.len() != expected {
+            return Err(Error::InvalidRecordLength {
+                expected,
+                len: record.len(),
+            });
+        }
+        let coordinate =
+            |i: usize| i32::from_le_bytes([record[i], record[i + 1], record[i + 2], record[i + 3]]);
+        let transforms = self.header().transforms();
+        let point = Point {
+            x: transforms.x.direct(coordinate(0)),
+            y: transforms.y.direct(coordinate(4)),
+            z: transforms.z.direct(coordinate(8)),
+            return_number: if format.is_extended {
+                record[14] & 0b1111
+            } else {
+                record[14] & 0b111
+            },
+            ..Default::default()
+        };
+        let index = self.next_index();
+        let position = self.header().number_of_points();
+        self.header()
+            .verify_room_for(1, [(point.return_number, 1)])
+            .map_err(|source| Error::PointWriteFailed {
+                index,
+                source: Box::new(source),
+            })?;
+        let result = self.point_writer.write_raw_points(record);
+        self.poison(result)?;
+        #[cfg(feature = "digest")]
+        if let Some(hasher) = &mut self.digest {
+            hasher.update(record);
+        }
+        self.point_writer.header_mut().add_point(&point);
+        if let Some((_, indexer)) = &mut self.index {
+            indexer.add(position, point.x, point.y);
+        }
+        self.report_progress();
+        Ok(())
+    }
+
+    /// Ends the current laz chunk, so the next point starts a new one.
+    ///
+    /// The writer must have been created with [WriteOptions::variable_laz_chunks], or with a header
+    /// whose [laz chunks](Header::laz_chunks) are
+    /// [LazChunks::Variable](crate::header::LazChunks::Variable). Nothing
+    /// happens if no points have been written since the last chunk ended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::WriteOptions, Builder, Reader, Writer};
+    /// use std::io::Cursor;
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format.is_compressed = true;
+    /// let options = WriteOptions::default().variable_laz_chunks();
+    /// let mut writer =
+    ///     Writer::with_options(Cursor::new(Vec::new()), builder.into_header().unwrap(), options)
+    ///         .unwrap();
+    /// writer.write_point(Default::default()).unwrap();
+    /// writer.finish_laz_chunk().unwrap();
+    /// writer.write_point(Default::default()).unwrap();
+    /// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// assert_eq!(2, reader.header().number_of_points());
+    /// ```
+    #[cfg(feature = "laz")]
+    pub fn finish_laz_chunk(&mut self) -> Result<()> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.check_poisoned()?;
+        match &mut self.point_writer {
+            PointWriter::Laz(point_writer) if point_writer.has_variable_chunks() => {
+                point_writer.finish_chunk();
+                Ok(())
+            }
+            _ => Err(Error::InvalidWriteOption {
+                option: "variable_laz_chunks",
+                reason: "the writer doesn't write variable size laz chunks".to_string(),
+            }),
+        }
+    }
+
+    /// Writes a point.
+    #[deprecated(since = "0.9.0", note = "Use write_point() instead")]
+    pub fn write(&mut self, point: Point) -> Result<()> {
+        self.write_point(point)
+    }
+
+    /// Writes points from a parallel iterator.
+    ///
+    /// Points are transformed and serialized in parallel, then written to the sink in their
+    /// original order. All serialized records are buffered in memory before any are written, so if
+    /// any point fails to serialize, or if the header's version can't count all of them, nothing is
+    /// written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Writer};
+    /// use rayon::prelude::*;
+    ///
+    /// let mut writer = Writer::default();
+    /// let points: Vec<Point> = (0..10).map(|i| Point { x: i as f64, ..Default::default() }).collect();
+    /// writer.write_par_iter(points.into_par_iter()).unwrap();
+    /// assert_eq!(10, writer.header().number_of_points());
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn write_par_iter(
+        &mut self,
+        points: impl rayon::iter::IndexedParallelIterator<Item = Point>,
+    ) -> Result<()> {
+        use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        self.check_poisoned()?;
+        if self.evlr_start.is_some() {
+            return Err(Error::PointsAfterEvlr);
+        }
+        let format = *self.header().point_format();
+        let transforms = *self.header().transforms();
+        let options = &self.options;
+        let extra_attributes = &self.extra_attributes;
+        let compatibility = self.compatibility.as_ref();
+        let start = self.next_index();
+        let batches = points
+            .chunks(BATCH_SIZE)
+            .enumerate()
+            .map(|(i, points)| {
+                Batch::new(
+                    points,
+                    start + (i * BATCH_SIZE) as u64,
+                    &format,
+                    &transforms,
+                    extra_attributes,
+                    compatibility,
+                    options,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut number_of_points_by_return = HashMap::new();
+        for batch in &batches {
+            for (&return_number, &count) in &batch.number_of_points_by_return {
+                *number_of_points_by_return.entry(return_number).or_insert(0) += count;
+            }
+        }
+        self.header().verify_room_for(
+            batches.iter().map(|batch| batch.number_of_points).sum(),
+            number_of_points_by_return,
+        )?;
+        if let Some(tracker) = &self.gps_time_order {
+            let mut tracker = tracker.clone();
+            let mut summary = WriteSummary::default();
+            let times = batches.iter().flat_map(|batch| &batch.times);
+            for &(index, point_source_id, gps_time) in times {
+                let point = Point {
+                    point_source_id,
+                    gps_time,
+                    ..Default::default()
+                };
+                let gps_time = tracker.check(&point, &mut summary).map_err(|source| {
+                    Error::PointWriteFailed {
+                        index,
+                        source: Box::new(source),
+                    }
+                })?;
+                tracker.remember(gps_time, index);
+            }
+            self.gps_time_order = Some(tracker);
+            self.summary.add(&summary);
+        }
+        for batch in batches {
+            let result = self.point_writer.write_raw_points(&batch.bytes);
+            self.poison(result)?;
+            #[cfg(feature = "digest")]
+            if let Some(hasher) = &mut self.digest {
+                hasher.update(&batch.bytes);
+            }
+            if let Some((_, indexer)) = &mut self.index {
+                let start = self.point_writer.header().number_of_points();
+                for (index, (x, y)) in (start..).zip(batch.xy) {
+                    indexer.add(index, x, y);
+                }
+            }
+            self.point_writer.header_mut().add_points(
+                batch.number_of_points,
+                &batch.number_of_points_by_return,
+                &batch.bounds,
+            );
+            if let Some(extended) = &mut self.compatibility {
+                extended.add_all(&batch.extended_number_of_points_by_return);
+            }
+            self.summary.add(&batch.summary);
+            self.report_progress();
+        }
+        Ok(())
+    }
+
+    /// Closes this writer and returns its inner `Write`, seeked to the beginning of the las data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Writer;
+    /// let writer = Writer::default();
+    /// let cursor = writer.into_inner().unwrap();
+    /// ```
+    pub fn into_inner(mut self) -> Result<W> {
+        if !self.closed {
+            let _ = self.close()?;
+        }
+
+        // Since Writer implements Drop, the sink can't be moved out directly, so we swap in the
+        // closed point writer. The writer is consumed by this method, so nothing can touch the
+        // closed point writer afterwards.
+        let point_writer = std::mem::replace(&mut self.point_writer, PointWriter::Closed);
+        let mut inner = point_writer.into_inner();
+        if self.committed_header.is_none() {
+            let _ = inner.seek(SeekFrom::Start(self.start))?;
+        }
+        Ok(inner)
+    }
+}
+
+impl<W: std::io::Write + Seek + Send> EvlrWriter<'_, W> {
+    /// Finishes the evlr, filling in its length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{Builder, Writer};
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    /// writer.begin_evlr("my software", 1, "").unwrap().finish().unwrap();
+    /// writer.close().unwrap();
+    /// ```
+    pub fn finish(self) -> Result<()> {
+        let streamed_evlrs = self
+            .writer
+            .streamed_evlrs
+            .checked_add(1)
+            .ok_or(Error::TooManyEvlrs(usize::MAX))?;
+        self.writer.check_poisoned()?;
+        let result = write_evlr_len(self.writer.point_writer.get_mut(), self.start, self.len);
+        self.writer.poison(result)?;
+        self.writer.streamed_evlrs = streamed_evlrs;
+        self.writer.evlr_open = false;
+        Ok(())
+    }
+}
+
+/// Fills in the record length of the streamed evlr that starts at `start`.
+fn write_evlr_len<W: std::io::Write + Seek>(sink: &mut W, start: u64, len: u64) -> Result<()> {
+    use byteorder::{LittleEndian, WriteBytesExt};
+
+    let end = sink.stream_position()?;
+    // The record length comes after the reserved bytes, the user id, and the record id.
+    let _ = sink.seek(SeekFrom::Start(start + 20))?;
+    sink.write_u64::<LittleEndian>(len)?;
+    let _ = sink.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
+impl<W: std::io::Write + Seek + Send> std::io::Write for EvlrWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer
+            .check_poisoned()
+            .map_err(std::io::Error::other)?;
+        let result = self.writer.point_writer.get_mut().write(buf);
+        let n = self.writer.poison(result)?;
+        self.len += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let result = self.writer.point_writer.get_mut().flush();
+        self.writer.poison(result)
+    }
+}
+
+#[allow(deprecated)]
+impl<W: std::io::Write + Seek + Debug + Send> Write for Writer<W> {
+    fn header(&self) -> &Header {
+        self.header()
+    }
+
+    fn write(&mut self, point: Point) -> Result<()> {
+        self.write(point)
+    }
+}
+
+impl<W: std::io::Read + std::io::Write + Seek + Send> Writer<W> {
+    /// Opens existing las data for appending points.
+    ///
+    /// The las data must start at the beginning of the stream. New points go after the last
+    /// point, and any evlrs are written again after them. Closing the writer rewrites the header
+    /// with the new point counts, bounds, and counts by return. New compressed points go into new
+    /// chunks, which are added to the chunk table. A point data digest or spatial index in the
+    /// file isn't updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Reader, Writer};
+    /// use std::io::Cursor;
+    ///
+    /// let mut writer = Writer::default();
+    /// writer.write_point(Default::default()).unwrap();
+    /// let mut writer = Writer::append_from(writer.into_inner().unwrap()).unwrap();
+    /// writer.write_point(Default::default()).unwrap();
+    /// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// assert_eq!(2, reader.header().number_of_points());
+    /// ```
+    pub fn append_from(mut read_write: W) -> Result<Writer<W>> {
+        let _ = read_write.seek(SeekFrom::Start(0))?;
+        let header = Header::new(&mut read_write)?;
+        #[cfg(feature = "laz")]
+        if header.point_format().is_compressed {
+            let _ = read_write.seek(SeekFrom::Start(header.offsets().offset_to_point_data))?;
+            let chunk_table = crate::laz::read_chunk_table(&mut read_write, &header)?;
+            return Writer::appending_compressed(read_write, header, chunk_table);
+        }
+        Writer::appending(read_write, header)
+    }
+}
+
+impl Writer<BufWriter<File>> {
+    /// Opens an existing las file for appending points.
+    ///
+    /// See [Writer::append_from] for what can be appended to.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use las::Writer;
+    /// let mut writer = Writer::append("points.las").unwrap();
+    /// writer.write_point(Default::default()).unwrap();
+    /// writer.close().unwrap();
+    /// ```
+    pub fn append<P: AsRef<Path>>(path: P) -> Result<Writer<BufWriter<File>>> {
+        let header = crate::read_header(path.as_ref())?;
+        #[cfg(feature = "laz")]
+        if header.point_format().is_compressed {
+            let mut file = std::io::BufReader::new(File::open(path.as_ref())?);
+            let _ = file.seek(SeekFrom::Start(header.offsets().offset_to_point_data))?;
+            let chunk_table = crate::laz::read_chunk_table(file, &header)?;
+            let file = OpenOptions::new().write(true).open(path)?;
+            return Writer::appending_compressed(BufWriter::new(file), header, chunk_table);
+        }
+        let file = OpenOptions::new().write(true).open(path)?;
+        Writer::appending(BufWriter::new(file), header)
+    }
+
+    /// Creates a new writer for a path.
+    ///
+    /// If the "laz" feature is enabled, guesses from the extension if the
+    /// data will be written compressed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Writer;
+    /// let writer = Writer::from_path("/dev/null", Default::default());
     /// ```
     pub fn from_path<P: AsRef<Path>>(
         path: P,
         mut header: Header,
     ) -> Result<Writer<BufWriter<File>>> {
-        let compress = if cfg!(feature = "laz") {
-            match path.as_ref().extension() {
-                Some(ext) => match &ext.to_str() {
-                    Some(ext_str) => &ext_str.to_lowercase() == "laz",
-                    None => false,
-                },
-                None => false,
+        header.point_format_mut().is_compressed = is_laz_path(path.as_ref());
+        File::create(path)
+            .map_err(Error::from)
+            .and_then(|file| Writer::new(BufWriter::new(file), header))
+    }
+}
+
+/// Checks that a file with this header can be appended to, and returns where its points start.
+fn appendable_offset(header: &mut Header) -> Result<u64> {
+    if let Some(n) = header.point_format().unknown {
+        return Err(Error::UnknownPointFormat(n));
+    }
+    let offsets = header.recompute_offsets();
+    if offsets.before.offset_to_point_data != offsets.after.offset_to_point_data {
+        return Err(Error::CannotAppend(
+            "its header can't be rewritten in the same space",
+        ));
+    }
+    Ok(offsets.after.offset_to_point_data)
+}
+
+/// Returns true if the "laz" feature is enabled and a path has a `.laz` extension.
+pub(crate) fn is_laz_path(path: &Path) -> bool {
+    cfg!(feature = "laz")
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.to_lowercase() == "laz")
+}
+
+impl Default for Writer<Cursor<Vec<u8>>> {
+    fn default() -> Writer<Cursor<Vec<u8>>> {
+        Writer::new(Cursor::new(Vec::new()), Header::default()).unwrap()
+    }
+}
+
+impl<W: Seek + std::io::Write + Send> Drop for Writer<W> {
+    fn drop(&mut self) {
+        // Finishing here is best effort, since there's no one to tell about an error. A poisoned
+        // file can't be finished, and an atomic file that wasn't closed is thrown away.
+        if !self.closed && self.poisoned.is_none() && self.on_close.is_none() {
+            let _ = self.finish_file();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        header::{Builder, LazChunks},
+        point::Format,
+        Color, Version,
+    };
+    use std::io::Cursor;
+
+    /// Unwraps a point write failure into the index of the point and the reason.
+    fn failure(error: Error) -> (u64, Error) {
+        match error {
+            Error::PointWriteFailed { index, source } => (index, *source),
+            error => panic!("unexpected error: {error}"),
+        }
+    }
+
+    /// A thousand good points, then one that can't be transformed.
+    fn points_with_bad_one() -> Vec<Point> {
+        let mut points: Vec<Point> = (0..1000)
+            .map(|i| Point {
+                x: f64::from(i),
+                ..Default::default()
+            })
+            .collect();
+        points.push(Point {
+            x: 1e100,
+            ..Default::default()
+        });
+        points.push(Point::default());
+        points
+    }
+
+    fn writer(format: Format, version: Version) -> Writer<Cursor<Vec<u8>>> {
+        let mut builder = Builder::default();
+        builder.point_format = format;
+        builder.version = version;
+        if format.is_extended {
+            builder.gps_time_type = crate::GpsTimeType::Standard;
+        }
+        Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn already_closed() {
+        let mut writer = Writer::default();
+        let _ = writer.close().unwrap();
+        assert!(writer.close().is_err());
+        assert!(writer.write_point(Default::default()).is_err());
+    }
+
+    #[test]
+    fn return_number_too_large_for_version() {
+        let mut writer = writer(Format::default(), Version::new(1, 2));
+        writer.write_point(Default::default()).unwrap();
+        let point = Point {
+            return_number: 6,
+            number_of_returns: 6,
+            ..Default::default()
+        };
+        let (index, source) = failure(writer.write_point(point).unwrap_err());
+        assert_eq!(1, index);
+        assert!(matches!(
+            source,
+            Error::ReturnNumber {
+                return_number: 6,
+                ..
+            }
+        ));
+        let mut record = vec![0; 20];
+        record[14] = 6;
+        let (index, source) = failure(writer.write_raw_point(&record).unwrap_err());
+        assert_eq!(1, index);
+        assert!(matches!(source, Error::ReturnNumber { .. }));
+        assert_eq!(1, writer.header().number_of_points());
+        let _ = writer.close().unwrap();
+    }
+
+    #[test]
+    fn missing_extra_bytes() {
+        let format = Format {
+            extra_bytes: 1,
+            ..Default::default()
+        };
+        let mut writer = writer(format, Version::new(1, 4));
+        assert!(writer.write_point(Default::default()).is_err());
+    }
+
+    #[test]
+    fn missing_extra_attribute() {
+        use crate::point::{ExtraBytesDescriptor, ExtraBytesType};
+
+        let mut builder = Builder::from((1, 4));
+        builder.extra_bytes(2);
+        builder
+            .add_extra_attribute(ExtraBytesDescriptor::new("Amplitude", ExtraBytesType::F32))
+            .unwrap();
+        let header = builder.into_header().unwrap();
+        let schema = header.extra_bytes_schema().unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        let point = Point {
+            extra_bytes: vec![0; 2].into(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            failure(writer.write_point(point.clone()).unwrap_err()),
+            (0, Error::ExtraAttributeNotFound(name)) if name == "Amplitude"
+        ));
+        let mut point = point;
+        point
+            .set_extra_attribute(&schema, "Amplitude", 2.5)
+            .unwrap();
+        writer.write_point(point).unwrap();
+        let mut reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        let point = reader.read_point().unwrap().unwrap();
+        assert_eq!(
+            Some(2.5),
+            point.extra_attribute::<f32>(&schema, "Amplitude").unwrap()
+        );
+    }
+
+    #[test]
+    fn missing_gps_time() {
+        let format = Format::new(1).unwrap();
+        let mut writer = writer(format, Version::new(1, 2));
+        let error = writer.write_point(Default::default()).unwrap_err();
+        assert_eq!(
+            "could not write point 0: the attributes of the point format (point format 1) do not match the point: missing gps_time",
+            error.to_string()
+        );
+        assert!(matches!(
+            failure(error),
+            (0, Error::PointAttributesDoNotMatch { mismatches, .. }) if mismatches == [crate::point::FieldMismatch::Missing("gps_time")]
+        ));
+    }
+
+    #[test]
+    fn missing_color() {
+        let format = Format::new(2).unwrap();
+        let mut writer = writer(format, Version::new(1, 2));
+        assert!(writer.write_point(Default::default()).is_err());
+    }
+
+    #[test]
+    fn missing_nir() {
+        let format = Format::new(8).unwrap();
+        let mut writer = writer(format, Version::new(1, 4));
+        let point = Point {
+            gps_time: Some(0.),
+            color: Some(Default::default()),
+            ..Default::default()
+        };
+        assert!(writer.write_point(point).is_err());
+    }
+
+    #[test]
+    fn missing_waveform() {
+        let format = Format::new(4).unwrap();
+        let mut writer = writer(format, Version::new(1, 4));
+        assert!(writer.write_point(Default::default()).is_err());
+    }
+
+    #[test]
+    fn write_not_at_start() {
+        use crate::Reader;
+        use byteorder::WriteBytesExt;
+
+        let mut cursor = Cursor::new(Vec::new());
+        cursor.write_u8(42).unwrap();
+        let mut writer = Writer::new(cursor, Default::default()).unwrap();
+        let point = Point::default();
+        writer.write_point(point.clone()).unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(point, reader.read_point().unwrap().unwrap());
+    }
+
+    #[test]
+    fn write_to_vec() {
+        let mut writer = Writer::new(Cursor::new(Vec::new()), Default::default()).unwrap();
+        let point = Point {
+            x: 1.,
+            y: 2.,
+            z: 3.,
+            ..Default::default()
+        };
+        writer.write_point(point.clone()).unwrap();
+        let bytes = writer.into_inner().unwrap().into_inner();
+        assert_eq!(bytes.len(), 227 + 20);
+
+        let mut reader = crate::Reader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(point, reader.read_point().unwrap().unwrap());
+        assert!(reader.read_point().unwrap().is_none());
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn write_compressed_to_vec() {
+        let mut builder = Builder::default();
+        builder.point_format.is_compressed = true;
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        let point = Point {
+            x: 1.,
+            y: 2.,
+            z: 3.,
+            ..Default::default()
+        };
+        writer.write_point(point.clone()).unwrap();
+        let bytes = writer.into_inner().unwrap().into_inner();
+
+        let mut reader = crate::Reader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(point, reader.read_point().unwrap().unwrap());
+        assert!(reader.read_point().unwrap().is_none());
+    }
+
+    /// A sink that runs out of space after `limit` bytes.
+    struct Full {
+        cursor: Cursor<Vec<u8>>,
+        limit: u64,
+    }
+
+    impl std::io::Write for Full {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let room = self.limit.saturating_sub(self.cursor.position());
+            if room == 0 {
+                return Err(std::io::ErrorKind::StorageFull.into());
+            }
+            let n = buf.len().min(room as usize);
+            self.cursor.write(&buf[..n])
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Seek for Full {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.cursor.seek(pos)
+        }
+    }
+
+    /// A writer that has room for `points` more default points, and part of another.
+    fn writer_with_room_for(header: Header, points: u64) -> Writer<Full> {
+        let full = Full {
+            cursor: Cursor::new(Vec::new()),
+            limit: u64::MAX,
+        };
+        let mut writer = Writer::new(full, header).unwrap();
+        let sink = writer.point_writer.get_mut();
+        sink.limit = sink.cursor.position() + points * 20 + 5;
+        writer
+    }
+
+    fn with_evlr() -> Header {
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            data: vec![42; 100],
+            ..Default::default()
+        });
+        builder.into_header().unwrap()
+    }
+
+    #[test]
+    fn poisoned_by_failed_write() {
+        let mut writer = writer_with_room_for(Header::default(), 3);
+        for _ in 0..3 {
+            writer.write_point(Default::default()).unwrap();
+        }
+        assert!(!writer.is_poisoned());
+        assert!(matches!(
+            writer.write_point(Default::default()).unwrap_err(),
+            Error::Io(err) if err.kind() == std::io::ErrorKind::StorageFull
+        ));
+        assert!(writer.is_poisoned());
+        let original = Error::from(std::io::Error::from(std::io::ErrorKind::StorageFull));
+        for result in [
+            writer.write_point(Default::default()).map(|_| ()),
+            writer.close().map(|_| ()),
+            writer.begin_evlr("las-rs", 1, "").map(|_| ()),
+        ] {
+            assert!(matches!(
+                result.unwrap_err(),
+                Error::WriterPoisoned(reason) if reason == original.to_string()
+            ));
+        }
+        assert_eq!(3, writer.header().number_of_points());
+        drop(writer);
+    }
+
+    #[test]
+    fn poisoned_by_failed_close() {
+        // There's no room for the evlr.
+        let mut writer = writer_with_room_for(with_evlr(), 1);
+        writer.write_point(Default::default()).unwrap();
+        assert!(matches!(writer.close().unwrap_err(), Error::Io(_)));
+        assert!(writer.is_poisoned());
+        assert!(matches!(
+            writer.close().unwrap_err(),
+            Error::WriterPoisoned(_)
+        ));
+        drop(writer);
+    }
+
+    #[test]
+    fn drop_does_not_panic() {
+        let mut writer = writer_with_room_for(with_evlr(), 1);
+        writer.write_point(Default::default()).unwrap();
+        drop(writer);
+    }
+
+    #[test]
+    fn drop_finalizes() {
+        let path = std::env::temp_dir().join("las-rs-drop-finalizes.las");
+        {
+            let mut writer = Writer::from_path(&path, Default::default()).unwrap();
+            writer.write_point(Default::default()).unwrap();
+        }
+        let reader = crate::Reader::from_path(&path).unwrap();
+        assert_eq!(1, reader.header().number_of_points());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "parallel")]
+    fn par_iter_bytes(format: Format) -> (Vec<u8>, Vec<u8>) {
+        use rayon::prelude::*;
+
+        let points: Vec<Point> = (0..10_000)
+            .map(|i| {
+                let mut point = Point {
+                    x: f64::from(i) * 0.5,
+                    y: -f64::from(i),
+                    z: f64::from(i % 97),
+                    return_number: (i % 5) as u8,
+                    number_of_returns: 5,
+                    ..Default::default()
+                };
+                if format.has_gps_time {
+                    point.gps_time = Some(f64::from(i));
+                }
+                point
+            })
+            .collect();
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = format;
+        let header = builder.into_header().unwrap();
+
+        let mut sequential = Writer::new(Cursor::new(Vec::new()), header.clone()).unwrap();
+        for point in points.clone() {
+            sequential.write_point(point).unwrap();
+        }
+        let mut parallel = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        parallel.write_par_iter(points.into_par_iter()).unwrap();
+        assert_eq!(sequential.header(), parallel.header());
+        (
+            sequential.into_inner().unwrap().into_inner(),
+            parallel.into_inner().unwrap().into_inner(),
+        )
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn write_par_iter_matches_write_point() {
+        let (sequential, parallel) = par_iter_bytes(Format::new(1).unwrap());
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    #[cfg(all(feature = "parallel", feature = "laz"))]
+    fn write_par_iter_compressed_matches_write_point() {
+        let mut format = Format::new(1).unwrap();
+        format.is_compressed = true;
+        let (sequential, parallel) = par_iter_bytes(format);
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn write_par_iter_mismatched_point() {
+        use rayon::prelude::*;
+
+        let mut writer = writer(Format::new(1).unwrap(), Version::new(1, 2));
+        let points = vec![Point::default(); 10];
+        assert!(writer.write_par_iter(points.into_par_iter()).is_err());
+        assert_eq!(0, writer.header().number_of_points());
+    }
+
+    #[test]
+    fn nan_gps_time() {
+        let mut writer = writer(Format::new(1).unwrap(), Version::new(1, 2));
+        let point = Point {
+            gps_time: Some(1.),
+            ..Default::default()
+        };
+        writer.write_point(point.clone()).unwrap();
+        let result = writer.write_point(Point {
+            gps_time: Some(f64::NAN),
+            ..point
+        });
+        assert!(matches!(
+            failure(result.unwrap_err()),
+            (1, Error::InvalidGpsTime(gps_time)) if gps_time.is_nan()
+        ));
+        assert_eq!(1, writer.header().number_of_points());
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn write_par_iter_nan_gps_time() {
+        use rayon::prelude::*;
+
+        let mut writer = writer(Format::new(1).unwrap(), Version::new(1, 2));
+        writer
+            .write_point(Point {
+                gps_time: Some(0.),
+                ..Default::default()
+            })
+            .unwrap();
+        let points: Vec<Point> = (0..10_000)
+            .map(|i| Point {
+                gps_time: Some(if i == 5000 { f64::INFINITY } else { 0. }),
+                ..Default::default()
+            })
+            .collect();
+        assert!(matches!(
+            failure(writer.write_par_iter(points.into_par_iter()).unwrap_err()),
+            (5001, Error::InvalidGpsTime(f64::INFINITY))
+        ));
+        assert_eq!(1, writer.header().number_of_points());
+    }
+
+    #[test]
+    fn write_point_ref_matches_write_point() {
+        let mut format = Format::new(3).unwrap();
+        format.extra_bytes = 3;
+        let points: Vec<Point> = (0..10u8)
+            .map(|i| Point {
+                x: f64::from(i),
+                return_number: i % 3,
+                gps_time: Some(f64::from(i)),
+                color: Some(Default::default()),
+                extra_bytes: vec![i, i + 1, i + 2].into(),
+                ..Default::default()
+            })
+            .collect();
+        let mut owned = writer(format, Version::new(1, 4));
+        let mut borrowed = writer(format, Version::new(1, 4));
+        for point in &points {
+            owned.write_point(point.clone()).unwrap();
+            borrowed.write_point_ref(point).unwrap();
+        }
+        assert_eq!(owned.header(), borrowed.header());
+        assert_eq!(
+            owned.into_inner().unwrap().into_inner(),
+            borrowed.into_inner().unwrap().into_inner()
+        );
+    }
+
+    #[test]
+    fn failing_point_index() {
+        let mut writer = Writer::default();
+        let mut points = points_with_bad_one().into_iter();
+        for point in points.by_ref().take(1000) {
+            writer.write_point(point).unwrap();
+        }
+        let error = writer.write_point(points.next().unwrap()).unwrap_err();
+        assert!(matches!(
+            failure(error),
+            (1000, Error::InvalidInverseTransform { .. })
+        ));
+        assert_eq!(1000, writer.header().number_of_points());
+        writer.write_point(points.next().unwrap()).unwrap();
+        assert_eq!(1001, writer.header().number_of_points());
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn write_par_iter_failing_point_index() {
+        use rayon::prelude::*;
+
+        let mut writer = Writer::default();
+        for _ in 0..10 {
+            writer.write_point(Point::default()).unwrap();
+        }
+        let error = writer
+            .write_par_iter(points_with_bad_one().into_par_iter())
+            .unwrap_err();
+        assert!(matches!(
+            failure(error),
+            (1010, Error::InvalidInverseTransform { .. })
+        ));
+    }
+
+    fn write_with_options(options: WriteOptions, points: &[Point]) -> Writer<Cursor<Vec<u8>>> {
+        let mut writer =
+            Writer::with_options(Cursor::new(Vec::new()), Header::default(), options).unwrap();
+        for point in points {
+            writer.write_point_ref(point).unwrap();
+        }
+        let _ = writer.close().unwrap();
+        writer
+    }
+
+    /// Keeps only the counters of the lenient write options.
+    fn counters(summary: WriteSummary) -> WriteSummary {
+        WriteSummary {
+            points_clamped: summary.points_clamped,
+            colors_normalized: summary.colors_normalized,
+            withheld_points: summary.withheld_points,
+            non_finite_skipped: summary.non_finite_skipped,
+            out_of_range_skipped: summary.out_of_range_skipped,
+            non_finite_replaced: summary.non_finite_replaced,
+            gps_time_order_violations: summary.gps_time_order_violations,
+            precision_loss: summary.precision_loss,
+            ..Default::default()
+        }
+    }
+
+    fn first_point(writer: Writer<Cursor<Vec<u8>>>) -> Point {
+        let mut reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        reader.read_point().unwrap().unwrap()
+    }
+
+    #[test]
+    fn rounding_mode() {
+        let point = Point {
+            x: 1.0016,
+            y: -1.0016,
+            ..Default::default()
+        };
+        let stored = |rounding_mode| {
+            let options = WriteOptions::default().rounding_mode(rounding_mode);
+            let point = first_point(write_with_options(options, std::slice::from_ref(&point)));
+            (point.x, point.y)
+        };
+        let transform = Transform::default();
+        assert_eq!(
+            (transform.direct(1002), transform.direct(-1002)),
+            stored(RoundingMode::Round)
+        );
+        assert_eq!(
+            (transform.direct(1001), transform.direct(-1002)),
+            stored(RoundingMode::Floor)
+        );
+        assert_eq!(
+            (transform.direct(1002), transform.direct(-1001)),
+            stored(RoundingMode::Ceil)
+        );
+    }
+
+    #[test]
+    fn clamp_out_of_range() {
+        let points = [
+            Point::default(),
+            Point {
+                x: 1e100,
+                z: -1e100,
+                ..Default::default()
+            },
+        ];
+        let mut writer = Writer::default();
+        writer.write_point_ref(&points[0]).unwrap();
+        assert!(matches!(
+            failure(writer.write_point_ref(&points[1]).unwrap_err()),
+            (1, Error::InvalidInverseTransform { .. })
+        ));
+
+        let writer = write_with_options(WriteOptions::default().clamp_out_of_range(), &points);
+        assert_eq!(1, writer.summary().points_clamped);
+        let max = Transform::default().direct(i32::MAX);
+        let min = Transform::default().direct(i32::MIN);
+        let bounds = writer.header().bounds();
+        assert_eq!((max, min), (bounds.max.x, bounds.min.z));
+        let mut reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        let point = reader.points().nth(1).unwrap().unwrap();
+        assert_eq!((max, min), (point.x, point.z));
+    }
+
+    #[test]
+    fn skip_out_of_range() {
+        let points = [
+            Point::default(),
+            Point {
+                y: -1e100,
+                ..Default::default()
+            },
+            Point {
+                x: 1.,
+                ..Default::default()
+            },
+        ];
+        let writer = write_with_options(
+            WriteOptions::default().out_of_range(OutOfRangePolicy::Skip),
+            &points,
+        );
+        assert_eq!(1, writer.summary().out_of_range_skipped);
+        assert_eq!(0, writer.summary().points_clamped);
+        assert_eq!(1., writer.header().bounds().max.x);
+        assert_eq!(0., writer.header().bounds().min.y);
+        let reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            vec![points[0].clone(), points[2].clone()],
+            reader.into_points().unwrap()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn write_par_iter_skip_out_of_range() {
+        use rayon::prelude::*;
+
+        let points = points_with_bad_one();
+        let number_of_points = points.len() as u64;
+        let mut writer = Writer::with_options(
+            Cursor::new(Vec::new()),
+            Header::default(),
+            WriteOptions::default().out_of_range(OutOfRangePolicy::Skip),
+        )
+        .unwrap();
+        writer.write_par_iter(points.into_par_iter()).unwrap();
+        assert_eq!(1, writer.summary().out_of_range_skipped);
+        assert_eq!(number_of_points - 1, writer.header().number_of_points());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn write_par_iter_clamp_out_of_range() {
+        use rayon::prelude::*;
+
+        let points = points_with_bad_one();
+        let mut writer = Writer::with_options(
+            Cursor::new(Vec::new()),
+            Header::default(),
+            WriteOptions::default().clamp_out_of_range(),
+        )
+        .unwrap();
+        writer.write_par_iter(points.into_par_iter()).unwrap();
+        assert_eq!(1, writer.summary().points_clamped);
+        assert_eq!(
+            Transform::default().direct(i32::MAX),
+            writer.header().bounds().max.x
+        );
+    }
+
+    #[test]
+    fn colors_normalized() {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(2).unwrap();
+        let options = WriteOptions::default().normalize_color_to_16bit();
+        let mut writer = Writer::with_options(
+            Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+            options.clone(),
+        )
+        .unwrap();
+        assert_eq!(&options, writer.options());
+        for color in [
+            Color::new(255, 0, 0),
+            Color::new(0, 0, 0),
+            Color::new(1, 2, 3),
+        ] {
+            writer
+                .write_point(Point {
+                    color: Some(color),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            WriteSummary {
+                points_clamped: 0,
+                colors_normalized: 2,
+                withheld_points: 0,
+                non_finite_skipped: 0,
+                non_finite_replaced: 0,
+                gps_time_order_violations: 0,
+                ..Default::default()
+            },
+            counters(writer.close().unwrap())
+        );
+    }
+
+    #[test]
+    fn close_summary() {
+        let points: Vec<Point> = (1..=3)
+            .map(|i| Point {
+                x: f64::from(i),
+                return_number: i as u8,
+                number_of_returns: 3,
+                ..Default::default()
+            })
+            .collect();
+        let path = std::env::temp_dir().join("las-rs-writer-close-summary.las");
+        let mut writer = Writer::from_path(&path, Header::default()).unwrap();
+        for point in &points {
+            writer.write_point_ref(point).unwrap();
+        }
+        let summary = writer.close().unwrap();
+        drop(writer);
+        let len = std::fs::metadata(&path).unwrap().len();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(WriteSummary::default(), counters(summary));
+        assert_eq!(3, summary.points_written);
+        assert_eq!([1, 1, 1], summary.number_of_points_by_return[..3]);
+        assert!(summary.number_of_points_by_return[3..]
+            .iter()
+            .all(|&n| n == 0));
+        assert_eq!((1., 3.), (summary.bounds.min.x, summary.bounds.max.x));
+        assert_eq!(len, summary.bytes_written);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn summary_serde() {
+        let mut writer = Writer::default();
+        writer.write_point(Point::default()).unwrap();
+        let summary = writer.close().unwrap();
+        let json = serde_json::to_string(&summary).unwrap();
+        assert_eq!(summary, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn laz_chunk_size_needs_compression() {
+        let options = WriteOptions::default().laz_chunk_size(1000);
+        let error = Writer::with_options(Cursor::new(Vec::new()), Header::default(), options)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error,
+            Error::InvalidWriteOption {
+                option: "laz_chunk_size",
+                ..
             }
-        } else {
-            false
+        ));
+        assert!(error.to_string().contains("laz_chunk_size"));
+        assert!(error.to_string().contains("uncompressed"));
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn finish_laz_chunk() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.is_compressed = true;
+        let header = builder.into_header().unwrap();
+        let options = WriteOptions::default().variable_laz_chunks();
+        let mut writer =
+            Writer::with_options(Cursor::new(Vec::new()), header.clone(), options).unwrap();
+        writer.finish_laz_chunk().unwrap();
+        for n in [1, 3, 2] {
+            for _ in 0..n {
+                writer.write_point(Default::default()).unwrap();
+            }
+            writer.finish_laz_chunk().unwrap();
+            writer.finish_laz_chunk().unwrap();
+        }
+        let mut reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(3, reader.number_of_chunks().unwrap());
+        assert_eq!(3, reader.read_chunk_at(1).unwrap().len());
+        assert_eq!(6, reader.points().count());
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        assert!(matches!(
+            writer.finish_laz_chunk().unwrap_err(),
+            Error::InvalidWriteOption {
+                option: "variable_laz_chunks",
+                ..
+            }
+        ));
+    }
+
+    /// A good point, then non-finite values in each coordinate and the gps time.
+    fn non_finite_points() -> Vec<Point> {
+        let good = Point {
+            x: 1.,
+            y: 2.,
+            z: 3.,
+            gps_time: Some(4.),
+            ..Default::default()
         };
+        let mut points = vec![good.clone()];
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            points.push(Point {
+                x: value,
+                ..good.clone()
+            });
+            points.push(Point {
+                z: value,
+                ..good.clone()
+            });
+            points.push(Point {
+                gps_time: Some(value),
+                ..good.clone()
+            });
+        }
+        points
+    }
 
-        header.point_format_mut().is_compressed = compress;
-        File::create(path)
-            .map_err(Error::from)
-            .and_then(|file| Writer::new(BufWriter::new(file), header))
+    fn non_finite_writer(policy: NonFinitePolicy) -> Writer<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(1).unwrap();
+        let options = WriteOptions::default().non_finite(policy);
+        Writer::with_options(
+            Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+            options,
+        )
+        .unwrap()
     }
-}
 
-impl Default for Writer<Cursor<Vec<u8>>> {
-    fn default() -> Writer<Cursor<Vec<u8>>> {
-        Writer::new(Cursor::new(Vec::new()), Header::default()).unwrap()
+    #[test]
+    fn non_finite_error() {
+        let points = non_finite_points();
+        for (i, point) in points.iter().enumerate().skip(1) {
+            let mut writer = non_finite_writer(NonFinitePolicy::Error);
+            writer.write_point_ref(&points[0]).unwrap();
+            let (index, err) = failure(writer.write_point_ref(point).unwrap_err());
+            assert_eq!(1, index);
+            match (i - 1) % 3 {
+                0 => assert!(matches!(
+                    err,
+                    Error::NonFiniteCoordinate { axis: 'x', value } if value.to_bits() == point.x.to_bits()
+                )),
+                1 => assert!(matches!(err, Error::NonFiniteCoordinate { axis: 'z', .. })),
+                _ => assert!(matches!(err, Error::InvalidGpsTime(_))),
+            }
+            assert_eq!(1, writer.header().number_of_points());
+        }
     }
-}
 
-impl<W: 'static + Seek + std::io::Write + Send> Drop for Writer<W> {
-    fn drop(&mut self) {
-        if !self.closed {
-            self.close().expect("Error when dropping the writer");
+    #[test]
+    fn non_finite_skip() {
+        let mut writer = non_finite_writer(NonFinitePolicy::Skip);
+        for point in non_finite_points() {
+            writer.write_point(point).unwrap();
         }
+        let summary = writer.close().unwrap();
+        assert_eq!(9, summary.non_finite_skipped);
+        assert_eq!(0, summary.non_finite_replaced);
+        assert_eq!(1, summary.points_written);
+        let reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(1, reader.header().number_of_points());
+        assert_eq!(3., reader.header().bounds().max.z);
+        assert_eq!(non_finite_points()[..1], reader.into_points().unwrap()[..]);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{header::Builder, point::Format, Version};
-    use std::io::Cursor;
+    #[test]
+    fn non_finite_replace() {
+        let mut writer = non_finite_writer(NonFinitePolicy::Replace(-9.));
+        for point in non_finite_points() {
+            writer.write_point(point).unwrap();
+        }
+        assert_eq!(0, writer.summary().non_finite_skipped);
+        assert_eq!(9, writer.summary().non_finite_replaced);
+        let reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(-9., reader.header().bounds().min.x);
+        assert_eq!(-9., reader.header().bounds().min.z);
+        let points = reader.into_points().unwrap();
+        assert_eq!(10, points.len());
+        for point in &points[1..] {
+            assert!([point.x, point.z, point.gps_time.unwrap()].contains(&-9.));
+            assert_eq!(2., point.y);
+        }
+    }
 
-    fn writer(format: Format, version: Version) -> Writer<Cursor<Vec<u8>>> {
-        let mut builder = Builder::default();
-        builder.point_format = format;
-        builder.version = version;
-        Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap()
+    #[test]
+    fn non_finite_replacement_must_be_finite() {
+        let options = WriteOptions::default().non_finite(NonFinitePolicy::Replace(f64::NAN));
+        assert!(matches!(
+            Writer::with_options(Cursor::new(Vec::new()), Header::default(), options)
+                .map(|_| ())
+                .unwrap_err(),
+            Error::InvalidWriteOption {
+                option: "non_finite",
+                ..
+            }
+        ));
     }
 
     #[test]
-    fn already_closed() {
-        let mut writer = Writer::default();
-        writer.close().unwrap();
-        assert!(writer.close().is_err());
-        assert!(writer.write_point(Default::default()).is_err());
+    #[cfg(feature = "parallel")]
+    fn write_par_iter_non_finite() {
+        use rayon::prelude::*;
+
+        let mut writer = non_finite_writer(NonFinitePolicy::Skip);
+        writer
+            .write_par_iter(non_finite_points().into_par_iter())
+            .unwrap();
+        assert_eq!(1, writer.header().number_of_points());
+        assert_eq!(9, writer.summary().non_finite_skipped);
+
+        let mut writer = non_finite_writer(NonFinitePolicy::Replace(0.));
+        writer
+            .write_par_iter(non_finite_points().into_par_iter())
+            .unwrap();
+        assert_eq!(10, writer.header().number_of_points());
+        assert_eq!(9, writer.summary().non_finite_replaced);
+
+        let mut writer = non_finite_writer(NonFinitePolicy::Error);
+        assert!(matches!(
+            failure(
+                writer
+                    .write_par_iter(non_finite_points().into_par_iter())
+                    .unwrap_err()
+            ),
+            (1, Error::NonFiniteCoordinate { axis: 'x', .. })
+        ));
+    }
+
+    /// Three non-finite points, then one that can't be transformed at index five.
+    fn points_with_bad_one_after_skipped() -> Vec<Point> {
+        let mut points = vec![Point::default(); 10];
+        for point in &mut points[..3] {
+            point.x = f64::NAN;
+        }
+        points[5].x = 1e100;
+        points
     }
 
     #[test]
-    fn missing_extra_bytes() {
-        let format = Format {
-            extra_bytes: 1,
+    fn non_finite_skip_failing_point_index() {
+        let options = WriteOptions::default().non_finite(NonFinitePolicy::Skip);
+        let mut writer =
+            Writer::with_options(Cursor::new(Vec::new()), Header::default(), options).unwrap();
+        let error = points_with_bad_one_after_skipped()
+            .iter()
+            .try_for_each(|point| writer.write_point_ref(point))
+            .unwrap_err();
+        assert!(matches!(
+            failure(error),
+            (5, Error::InvalidInverseTransform { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn write_par_iter_non_finite_skip_failing_point_index() {
+        use rayon::prelude::*;
+
+        let options = WriteOptions::default().non_finite(NonFinitePolicy::Skip);
+        let mut writer =
+            Writer::with_options(Cursor::new(Vec::new()), Header::default(), options).unwrap();
+        let error = writer
+            .write_par_iter(points_with_bad_one_after_skipped().into_par_iter())
+            .unwrap_err();
+        assert!(matches!(
+            failure(error),
+            (5, Error::InvalidInverseTransform { .. })
+        ));
+    }
+
+    fn gps_time_order_writer(policy: GpsTimeOrder, per_source_id: bool) -> Writer<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(1).unwrap();
+        let mut options = WriteOptions::default().require_gps_time_order(policy);
+        if per_source_id {
+            options = options.gps_time_order_per_source_id();
+        }
+        Writer::with_options(
+            Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+            options,
+        )
+        .unwrap()
+    }
+
+    fn timed_points(times: &[(u16, f64)]) -> Vec<Point> {
+        times
+            .iter()
+            .map(|&(point_source_id, gps_time)| Point {
+                point_source_id,
+                gps_time: Some(gps_time),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn write_all(writer: &mut Writer<Cursor<Vec<u8>>>, points: Vec<Point>) -> Result<()> {
+        for point in points {
+            writer.write_point(point)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gps_time_order_in_order() {
+        for policy in [GpsTimeOrder::Error, GpsTimeOrder::Count] {
+            let mut writer = gps_time_order_writer(policy, false);
+            write_all(&mut writer, timed_points(&[(1, 1.), (2, 1.), (1, 2.)])).unwrap();
+            let _ = writer.close().unwrap();
+            assert_eq!(0, writer.summary().gps_time_order_violations);
+            assert_eq!(3, writer.header().number_of_points());
+        }
+    }
+
+    #[test]
+    fn gps_time_order_error() {
+        let mut writer = gps_time_order_writer(GpsTimeOrder::Error, false);
+        let error = write_all(&mut writer, timed_points(&[(1, 1.), (1, 3.), (1, 2.)])).unwrap_err();
+        assert!(error.to_string().contains("point 2"));
+        assert!(matches!(
+            failure(error),
+            (
+                2,
+                Error::GpsTimeOutOfOrder {
+                    previous_index: 1,
+                    ..
+                }
+            )
+        ));
+        assert_eq!(2, writer.header().number_of_points());
+    }
+
+    #[test]
+    fn gps_time_order_count() {
+        let mut writer = gps_time_order_writer(GpsTimeOrder::Count, false);
+        write_all(
+            &mut writer,
+            timed_points(&[(1, 1.), (1, 3.), (1, 2.), (1, 2.5)]),
+        )
+        .unwrap();
+        let summary = writer.close().unwrap();
+        assert_eq!(1, summary.gps_time_order_violations);
+        assert_eq!(summary, writer.summary());
+        assert_eq!(4, writer.header().number_of_points());
+    }
+
+    #[test]
+    fn gps_time_order_per_source_id() {
+        let points = timed_points(&[(1, 5.), (2, 1.), (1, 6.), (2, 2.)]);
+        let mut writer = gps_time_order_writer(GpsTimeOrder::Error, true);
+        write_all(&mut writer, points.clone()).unwrap();
+        let mut writer = gps_time_order_writer(GpsTimeOrder::Count, false);
+        write_all(&mut writer, points).unwrap();
+        assert_eq!(2, writer.summary().gps_time_order_violations);
+    }
+
+    #[test]
+    fn gps_time_order_missing_time() {
+        for policy in [GpsTimeOrder::Error, GpsTimeOrder::Count] {
+            let mut writer = gps_time_order_writer(policy, false);
+            let mut points = timed_points(&[(1, 1.), (1, 2.)]);
+            points[1].gps_time = None;
+            let error = write_all(&mut writer, points).unwrap_err();
+            assert!(matches!(failure(error), (1, Error::MissingGpsTime)));
+        }
+    }
+
+    #[test]
+    fn gps_time_order_needs_gps_time() {
+        let options = WriteOptions::default().require_gps_time_order(GpsTimeOrder::Count);
+        let error = Writer::with_options(Cursor::new(Vec::new()), Header::default(), options)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error,
+            Error::InvalidWriteOption {
+                option: "require_gps_time_order",
+                ..
+            }
+        ));
+    }
+
+    fn strict_precision_writer(
+        rounding_mode: RoundingMode,
+        policy: PrecisionLoss,
+    ) -> Writer<Cursor<Vec<u8>>> {
+        let options = WriteOptions::default()
+            .rounding_mode(rounding_mode)
+            .strict_precision(policy);
+        Writer::with_options(Cursor::new(Vec::new()), Header::default(), options).unwrap()
+    }
+
+    #[test]
+    fn strict_precision_round() {
+        let mut writer = strict_precision_writer(RoundingMode::Round, PrecisionLoss::Error);
+        for value in [0.0005, -0.0005, 1.23456789, 1e6 + 0.0004999] {
+            writer
+                .write_point(Point {
+                    x: value,
+                    y: value,
+                    z: value,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn strict_precision_error() {
+        let mut writer = strict_precision_writer(RoundingMode::Ceil, PrecisionLoss::Error);
+        let point = Point {
+            z: 2.0001,
             ..Default::default()
         };
-        let mut writer = writer(format, Version::new(1, 4));
-        assert!(writer.write_point(Default::default()).is_err());
+        let error = writer.write_point(point).unwrap_err();
+        let (index, error) = failure(error);
+        assert_eq!(0, index);
+        assert!(matches!(
+            error,
+            Error::PrecisionLoss {
+                axis: 'z',
+                value: 2.0001,
+                ..
+            }
+        ));
+        assert_eq!(0, writer.header().number_of_points());
     }
 
     #[test]
-    fn missing_gps_time() {
-        let format = Format::new(1).unwrap();
-        let mut writer = writer(format, Version::new(1, 2));
-        assert!(writer.write_point(Default::default()).is_err());
+    fn strict_precision_count() {
+        let mut writer = strict_precision_writer(RoundingMode::Floor, PrecisionLoss::Count);
+        let points = [0.0001, 0.0009, 0.0008, 0.002]
+            .into_iter()
+            .map(|x| Point {
+                x,
+                y: x,
+                ..Default::default()
+            })
+            .collect();
+        write_all(&mut writer, points).unwrap();
+        assert_eq!(2, writer.close().unwrap().precision_loss);
+        assert_eq!(4, writer.header().number_of_points());
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn missing_color() {
-        let format = Format::new(2).unwrap();
-        let mut writer = writer(format, Version::new(1, 2));
-        assert!(writer.write_point(Default::default()).is_err());
+    fn write_par_iter_gps_time_order() {
+        use rayon::prelude::*;
+
+        let mut points = timed_points(&vec![(1, 0.); 10_000]);
+        for (i, point) in points.iter_mut().enumerate() {
+            point.gps_time = Some(i as f64);
+        }
+        points[9000].gps_time = Some(0.);
+        let mut writer = gps_time_order_writer(GpsTimeOrder::Count, false);
+        writer
+            .write_par_iter(points.clone().into_par_iter())
+            .unwrap();
+        assert_eq!(1, writer.summary().gps_time_order_violations);
+        let mut writer = gps_time_order_writer(GpsTimeOrder::Error, false);
+        assert!(matches!(
+            failure(writer.write_par_iter(points.into_par_iter()).unwrap_err()),
+            (
+                9000,
+                Error::GpsTimeOutOfOrder {
+                    previous_index: 8999,
+                    ..
+                }
+            )
+        ));
+        assert_eq!(0, writer.header().number_of_points());
     }
 
+    #[cfg(feature = "laz")]
     #[test]
-    fn missing_nir() {
-        let format = Format::new(8).unwrap();
-        let mut writer = writer(format, Version::new(1, 4));
-        let point = Point {
-            gps_time: Some(0.),
-            color: Some(Default::default()),
+    fn laz_chunk_size() {
+        let mut builder = Builder::default();
+        builder.point_format.is_compressed = true;
+        let header = builder.into_header().unwrap();
+        let options = WriteOptions::default().laz_chunk_size(0);
+        assert!(Writer::with_options(Cursor::new(Vec::new()), header.clone(), options).is_err());
+
+        let options = WriteOptions::default().laz_chunk_size(10);
+        let mut writer = Writer::with_options(Cursor::new(Vec::new()), header, options).unwrap();
+        let points: Vec<Point> = (0..25)
+            .map(|i| Point {
+                x: f64::from(i),
+                ..Default::default()
+            })
+            .collect();
+        for point in &points {
+            writer.write_point_ref(point).unwrap();
+        }
+        let reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(10, reader.header().laz_vlr().unwrap().chunk_size());
+        assert_eq!(points, reader.into_points().unwrap());
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn laz_chunks_from_header() {
+        let mut builder = Builder::default();
+        builder.point_format.is_compressed = true;
+        builder.laz_chunks = Some(LazChunks::Fixed(0));
+        assert!(matches!(
+            builder.clone().into_header().unwrap_err(),
+            Error::InvalidLazChunkSize(0)
+        ));
+
+        builder.laz_chunks = Some(LazChunks::Fixed(7));
+        let header = builder.clone().into_header().unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header.clone()).unwrap();
+        for _ in 0..10 {
+            writer.write_point(Default::default()).unwrap();
+        }
+        let reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(7, reader.header().laz_vlr().unwrap().chunk_size());
+
+        let options = WriteOptions::default().laz_chunk_size(3);
+        let writer = Writer::with_options(Cursor::new(Vec::new()), header, options).unwrap();
+        let reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(3, reader.header().laz_vlr().unwrap().chunk_size());
+
+        builder.laz_chunks = Some(LazChunks::Variable);
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        for n in [2, 5] {
+            for _ in 0..n {
+                writer.write_point(Default::default()).unwrap();
+            }
+            writer.finish_laz_chunk().unwrap();
+        }
+        let mut reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert!(reader
+            .header()
+            .laz_vlr()
+            .unwrap()
+            .uses_variable_size_chunks());
+        assert_eq!(5, reader.read_chunk_at(1).unwrap().len());
+    }
+
+    #[test]
+    fn laz_chunks_ignored_without_compression() {
+        let mut builder = Builder::default();
+        builder.laz_chunks = Some(LazChunks::Fixed(10));
+        let header = builder.into_header().unwrap();
+        assert!(Writer::new(Cursor::new(Vec::new()), header).is_ok());
+    }
+
+    fn evlr_writer(version: Version, compressed: bool) -> Writer<Cursor<Vec<u8>>> {
+        let mut builder = Builder::from(version);
+        builder.point_format.is_compressed = compressed;
+        builder.evlrs.push(Vlr {
+            user_id: "header".to_string(),
+            data: vec![1, 2, 3],
+            ..Default::default()
+        });
+        Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap()
+    }
+
+    fn streamed_evlrs(compressed: bool) {
+        use std::io::Write;
+
+        let mut writer = evlr_writer(Version::new(1, 4), compressed);
+        let points: Vec<Point> = (0..10)
+            .map(|i| Point {
+                x: f64::from(i),
+                ..Default::default()
+            })
+            .collect();
+        for point in &points {
+            writer.write_point_ref(point).unwrap();
+        }
+        let mut evlr = writer.begin_evlr("first", 1, "streamed").unwrap();
+        evlr.write_all(&[4; 1000]).unwrap();
+        evlr.write_all(&[5; 10]).unwrap();
+        evlr.finish().unwrap();
+        writer
+            .begin_evlr("second", 2, "")
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert!(matches!(
+            writer.write_point(Point::default()).unwrap_err(),
+            Error::PointsAfterEvlr
+        ));
+
+        let reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        let evlrs = reader.header().evlrs();
+        assert_eq!(3, evlrs.len());
+        assert_eq!("first", evlrs[0].user_id);
+        assert_eq!(1, evlrs[0].record_id);
+        assert_eq!("streamed", evlrs[0].description);
+        assert_eq!(1010, evlrs[0].data.len());
+        assert_eq!([5; 10], evlrs[0].data[1000..]);
+        assert_eq!("second", evlrs[1].user_id);
+        assert!(evlrs[1].data.is_empty());
+        assert_eq!(vec![1, 2, 3], evlrs[2].data);
+        assert_eq!(points, reader.into_points().unwrap());
+    }
+
+    #[test]
+    fn stream_evlrs() {
+        streamed_evlrs(false);
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn stream_evlrs_compressed() {
+        streamed_evlrs(true);
+    }
+
+    #[test]
+    fn stream_evlr_needs_evlrs() {
+        let mut writer = evlr_writer(Version::new(1, 2), false);
+        assert!(writer.begin_evlr("user", 1, "").is_err());
+        writer.write_point(Point::default()).unwrap();
+    }
+
+    #[test]
+    fn unfinished_evlr() {
+        let mut writer = evlr_writer(Version::new(1, 4), false);
+        let _ = writer.begin_evlr("user", 1, "").unwrap();
+        assert!(matches!(
+            writer.begin_evlr("user", 2, "").map(|_| ()).unwrap_err(),
+            Error::UnfinishedEvlr
+        ));
+        assert!(matches!(writer.close().unwrap_err(), Error::UnfinishedEvlr));
+        // Dropping an unclosed writer panics.
+        std::mem::forget(writer);
+    }
+
+    #[test]
+    fn append_from() {
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            user_id: "user".to_string(),
+            record_id: 1,
+            data: vec![42; 10],
+            ..Default::default()
+        });
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        writer
+            .write_point(Point {
+                return_number: 1,
+                number_of_returns: 1,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut writer = Writer::append_from(writer.into_inner().unwrap()).unwrap();
+        writer
+            .write_point(Point {
+                x: 10.,
+                return_number: 2,
+                number_of_returns: 2,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        let header = reader.header();
+        assert_eq!(2, header.number_of_points());
+        assert_eq!(Some(1), header.number_of_points_by_return(2));
+        assert_eq!(10., header.bounds().max.x);
+        assert_eq!(vec![42; 10], header.evlrs()[0].data);
+        let points = reader.points().map(|p| p.unwrap()).collect::<Vec<_>>();
+        assert_eq!(0., points[0].x);
+        assert_eq!(10., points[1].x);
+    }
+
+    #[test]
+    fn append_to_path() {
+        let path = std::env::temp_dir().join("las-rs-append-to-path.las");
+        let mut writer = Writer::from_path(&path, Header::default()).unwrap();
+        writer.write_point(Point::default()).unwrap();
+        let _ = writer.close().unwrap();
+        let mut writer = Writer::append(&path).unwrap();
+        writer.write_point(Point::default()).unwrap();
+        let _ = writer.close().unwrap();
+        assert_eq!(2, crate::read_header(&path).unwrap().number_of_points());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Writes compressed points in chunks of three, appends more, and reads them all back.
+    #[cfg(feature = "laz")]
+    fn append_to_compressed(format: u8, before: u32, after: u32, variable: bool) {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.point_format.is_compressed = true;
+        builder.gps_time_type = crate::GpsTimeType::Standard;
+        builder.evlrs.push(Vlr {
+            user_id: "user".to_string(),
+            record_id: 1,
+            data: vec![42; 10],
             ..Default::default()
+        });
+        let options = if variable {
+            WriteOptions::default().variable_laz_chunks()
+        } else {
+            WriteOptions::default().laz_chunk_size(3)
         };
-        assert!(writer.write_point(point).is_err());
+        let point = |i: u32| Point {
+            x: f64::from(i),
+            gps_time: builder.point_format.has_gps_time.then_some(f64::from(i)),
+            ..Default::default()
+        };
+        let mut writer = Writer::with_options(
+            Cursor::new(Vec::new()),
+            builder.clone().into_header().unwrap(),
+            options,
+        )
+        .unwrap();
+        for i in 0..before {
+            writer.write_point(point(i)).unwrap();
+        }
+        let mut writer = Writer::append_from(writer.into_inner().unwrap()).unwrap();
+        for i in before..before + after {
+            if variable && i.is_multiple_of(2) {
+                writer.finish_laz_chunk().unwrap();
+            }
+            writer.write_point(point(i)).unwrap();
+        }
+        let mut reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            u64::from(before + after),
+            reader.header().number_of_points()
+        );
+        assert_eq!(vec![42; 10], reader.header().evlrs()[0].data);
+        let xs = reader
+            .points()
+            .map(|point| point.unwrap().x)
+            .collect::<Vec<_>>();
+        assert_eq!((0..before + after).map(f64::from).collect::<Vec<_>>(), xs);
+        let (_, chunk_table) = reader.chunk_table().unwrap();
+        let point_count: u64 = chunk_table
+            .as_ref()
+            .iter()
+            .map(|entry| entry.point_count)
+            .sum();
+        assert_eq!(u64::from(before + after), point_count);
+        assert_eq!(
+            variable || !before.is_multiple_of(3),
+            reader
+                .header()
+                .laz_vlr()
+                .unwrap()
+                .uses_variable_size_chunks()
+        );
+        for index in (0..before + after)
+            .rev()
+            .chain([before, 0, before + after - 1])
+        {
+            assert_eq!(
+                f64::from(index),
+                reader.read_nth(u64::from(index)).unwrap().unwrap().x,
+                "point {index}"
+            );
+        }
     }
 
+    #[cfg(feature = "laz")]
     #[test]
-    fn missing_waveform() {
-        let format = Format::new(4).unwrap();
-        let mut writer = writer(format, Version::new(1, 4));
-        assert!(writer.write_point(Default::default()).is_err());
+    fn append_to_full_chunks() {
+        append_to_compressed(1, 6, 4, false);
     }
 
+    #[cfg(feature = "laz")]
     #[test]
-    fn write_not_at_start() {
-        use crate::Reader;
-        use byteorder::WriteBytesExt;
+    fn append_to_partial_chunk() {
+        append_to_compressed(1, 4, 5, false);
+    }
 
-        let mut cursor = Cursor::new(Vec::new());
-        cursor.write_u8(42).unwrap();
-        let mut writer = Writer::new(cursor, Default::default()).unwrap();
-        let point = Point::default();
-        writer.write_point(point.clone()).unwrap();
-        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
-        assert_eq!(point, reader.read_point().unwrap().unwrap());
+    #[cfg(feature = "laz")]
+    #[test]
+    fn append_to_layered_chunks() {
+        append_to_compressed(6, 4, 5, false);
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn append_to_variable_chunks() {
+        append_to_compressed(6, 3, 5, true);
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn append_to_empty_compressed() {
+        append_to_compressed(0, 0, 4, false);
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn append_to_compressed_path() {
+        let path = std::env::temp_dir().join("las-rs-append-to-compressed-path.laz");
+        let mut writer = Writer::from_path(&path, Header::default()).unwrap();
+        writer.write_point(Point::default()).unwrap();
+        let _ = writer.close().unwrap();
+        let mut writer = Writer::append(&path).unwrap();
+        writer.write_point(Point::default()).unwrap();
+        let _ = writer.close().unwrap();
+        let mut reader = crate::Reader::from_path(&path).unwrap();
+        assert_eq!(2, reader.points().filter(|point| point.is_ok()).count());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    fn copy_raw_points(compressed: bool) {
+        let mut reader = crate::Reader::from_path("tests/data/autzen.las").unwrap();
+        let points: Vec<Point> = reader.points().map(|point| point.unwrap()).collect();
+        reader.seek(0).unwrap();
+        let mut builder = reader.header().clone().into_builder();
+        builder.point_format.is_compressed = compressed;
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        for record in reader.raw_records() {
+            writer.write_raw_point(&record.unwrap()).unwrap();
+        }
+        assert_eq!(reader.header().bounds(), writer.header().bounds());
+        assert_eq!(
+            reader.header().number_of_points_by_return(1),
+            writer.header().number_of_points_by_return(1)
+        );
+        let mut reader = crate::Reader::new(writer.into_inner().unwrap()).unwrap();
+        let copied: Vec<Point> = reader.points().map(|point| point.unwrap()).collect();
+        assert_eq!(points, copied);
+    }
+
+    #[test]
+    fn write_raw_point() {
+        copy_raw_points(false);
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn write_raw_point_compressed() {
+        copy_raw_points(true);
+    }
+
+    #[test]
+    fn write_raw_point_length() {
+        let mut writer = Writer::default();
+        assert!(matches!(
+            writer.write_raw_point(&[0; 19]).unwrap_err(),
+            Error::InvalidRecordLength {
+                expected: 20,
+                len: 19
+            }
+        ));
+        assert_eq!(0, writer.header().number_of_points());
+    }
+
+    fn write_to_borrowed_sink(compressed: bool) {
+        let mut reader = crate::Reader::from_path("tests/data/autzen.las").unwrap();
+        let points: Vec<Point> = reader.points().map(|point| point.unwrap()).collect();
+        let mut builder = reader.header().clone().into_builder();
+        builder.point_format.is_compressed = compressed;
+        let mut bytes = Vec::new();
+        let mut writer =
+            Writer::new(Cursor::new(&mut bytes), builder.into_header().unwrap()).unwrap();
+        for point in &points {
+            writer.write_point_ref(point).unwrap();
+        }
+        let _ = writer.close().unwrap();
+        drop(writer);
+        let mut reader = crate::Reader::new(Cursor::new(bytes)).unwrap();
+        let written: Vec<Point> = reader.points().map(|point| point.unwrap()).collect();
+        assert_eq!(points, written);
+    }
+
+    #[test]
+    fn borrowed_sink() {
+        write_to_borrowed_sink(false);
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn borrowed_sink_compressed() {
+        write_to_borrowed_sink(true);
+    }
+
+    #[test]
+    fn writer_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Writer<Cursor<Vec<u8>>>>();
+        assert_send::<Writer<BufWriter<File>>>();
     }
 }
@@ -0,0 +1,257 @@
+use crate::{writer::WriteOptions, Error, Header, Result, Writer};
+use std::io::{ErrorKind, Seek, SeekFrom, Write};
+
+/// A sink that can't seek, e.g. stdout, a pipe, or a socket, for a [Writer::non_seekable].
+///
+/// This only tracks how many bytes have been written, so it can say where it is, and fails any
+/// seek that would move it.
+#[derive(Debug)]
+pub struct NonSeekable<W: Write> {
+    write: W,
+    position: u64,
+}
+
+impl<W: Write> NonSeekable<W> {
+    /// Wraps a sink that can't seek.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::writer::NonSeekable;
+    /// let sink = NonSeekable::new(std::io::stdout());
+    /// ```
+    pub fn new(write: W) -> NonSeekable<W> {
+        NonSeekable { write, position: 0 }
+    }
+
+    /// Returns the wrapped sink.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::writer::NonSeekable;
+    /// let sink = NonSeekable::new(Vec::<u8>::new()).into_inner();
+    /// ```
+    pub fn into_inner(self) -> W {
+        self.write
+    }
+}
+
+impl<W: Write> Write for NonSeekable<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.write.write(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write.flush()
+    }
+}
+
+impl<W: Write> Seek for NonSeekable<W> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(_) => None,
+        };
+        if target == Some(self.position) {
+            Ok(self.position)
+        } else {
+            Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                "the sink can't seek",
+            ))
+        }
+    }
+}
+
+impl<W: Write + Send> Writer<NonSeekable<W>> {
+    /// Creates a writer for a sink that can't seek, which writes the header before the points.
+    ///
+    /// Since the header can't be written again when the writer closes, it must already have the
+    /// number of points and their bounds, see [Builder::precommit](crate::Builder::precommit).
+    /// Closing returns an error if the points don't match, but by then the header has been
+    /// written. Evlrs can't be streamed. Compressed points are followed by the laz chunk table and
+    /// then the offset to it, since the offset before the points can't be filled in, so they
+    /// can't have evlrs or padding after them either.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Builder, Point, Reader, Vector, Writer};
+    /// use std::io::Cursor;
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 0., y: 0., z: 0. },
+    ///     max: Vector { x: 1., y: 1., z: 1. },
+    /// };
+    /// builder.precommit(2, bounds);
+    /// let mut writer = Writer::non_seekable(Vec::new(), builder.into_header().unwrap()).unwrap();
+    /// writer.write_point(Point::default()).unwrap();
+    /// writer.write_point(Point { x: 1., ..Default::default() }).unwrap();
+    /// let bytes = writer.into_inner().unwrap().into_inner();
+    ///
+    /// let reader = Reader::new(Cursor::new(bytes)).unwrap();
+    /// assert_eq!(2, reader.header().number_of_points());
+    /// ```
+    pub fn non_seekable(write: W, header: Header) -> Result<Writer<NonSeekable<W>>> {
+        if header.point_format().is_compressed
+            && (!header.evlrs().is_empty() || !header.point_padding().is_empty())
+        {
+            return Err(Error::CannotStream(
+                "compressed points have to be at the end of the file",
+            ));
+        }
+        Writer::create(
+            NonSeekable::new(write),
+            header,
+            WriteOptions::default(),
+            true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bounds, Builder, Point, Reader, Vector, Vlr};
+    use std::io::Cursor;
+
+    fn header(number_of_points: u64) -> Header {
+        let mut builder = Builder::from((1, 4));
+        builder.precommit(
+            number_of_points,
+            Bounds {
+                min: Vector {
+                    x: 0.,
+                    y: 0.,
+                    z: 0.,
+                },
+                max: Vector {
+                    x: 10.,
+                    y: 10.,
+                    z: 10.,
+                },
+            },
+        );
+        builder.evlrs.push(Vlr {
+            data: vec![42; 10],
+            ..Default::default()
+        });
+        builder.into_header().unwrap()
+    }
+
+    #[test]
+    fn evlrs_after_points() {
+        let mut writer = Writer::non_seekable(Vec::new(), header(3)).unwrap();
+        for _ in 0..3 {
+            writer.write_point(Point::default()).unwrap();
+        }
+        let bytes = writer.into_inner().unwrap().into_inner();
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(vec![42; 10], reader.header().evlrs()[0].data);
+        assert_eq!(3, reader.points().count());
+    }
+
+    #[test]
+    fn wrong_number_of_points() {
+        let mut writer = Writer::non_seekable(Vec::new(), header(3)).unwrap();
+        writer.write_point(Point::default()).unwrap();
+        assert!(matches!(
+            writer.close().unwrap_err(),
+            Error::CannotStream(_)
+        ));
+    }
+
+    #[test]
+    fn outside_bounds() {
+        let mut writer = Writer::non_seekable(Vec::new(), header(1)).unwrap();
+        writer
+            .write_point(Point {
+                x: 11.,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(matches!(
+            writer.close().unwrap_err(),
+            Error::CannotStream(_)
+        ));
+    }
+
+    #[test]
+    fn no_streamed_evlrs() {
+        let mut writer = Writer::non_seekable(Vec::new(), header(0)).unwrap();
+        assert!(matches!(
+            writer.begin_evlr("user", 1, "").map(|_| ()).unwrap_err(),
+            Error::CannotStream(_)
+        ));
+    }
+
+    #[cfg(feature = "laz")]
+    fn compressed_header(number_of_points: u64) -> Header {
+        let mut builder = header(number_of_points).into_builder();
+        builder.point_format.is_compressed = true;
+        builder.evlrs.clear();
+        builder.into_header().unwrap()
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn compressed() {
+        let mut writer = Writer::non_seekable(Vec::new(), compressed_header(3)).unwrap();
+        for i in 0..3 {
+            writer
+                .write_point(Point {
+                    x: f64::from(i),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let bytes = writer.into_inner().unwrap().into_inner();
+        let mut reader = Reader::new(Cursor::new(bytes.clone())).unwrap();
+        let start = usize::try_from(reader.header().offsets().offset_to_point_data).unwrap();
+        assert_eq!((-1i64).to_le_bytes(), bytes[start..start + 8]);
+        assert_eq!(3, reader.header().number_of_points());
+        let xs = reader
+            .points()
+            .map(|point| point.unwrap().x)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0., 1., 2.], xs);
+        assert_eq!(1, reader.chunk_table().unwrap().1.len());
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn compressed_without_points() {
+        let mut writer = Writer::non_seekable(Vec::new(), compressed_header(0)).unwrap();
+        let _ = writer.close().unwrap();
+        let bytes = writer.into_inner().unwrap().into_inner();
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(0, reader.points().count());
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn compressed_with_evlrs() {
+        let mut builder = compressed_header(0).into_builder();
+        builder.evlrs.push(Vlr::default());
+        assert!(matches!(
+            Writer::non_seekable(Vec::new(), builder.into_header().unwrap())
+                .map(|_| ())
+                .unwrap_err(),
+            Error::CannotStream(_)
+        ));
+    }
+
+    #[test]
+    fn seek() {
+        let mut sink = NonSeekable::new(Vec::new());
+        sink.write_all(&[0; 4]).unwrap();
+        assert_eq!(4, sink.stream_position().unwrap());
+        assert_eq!(4, sink.seek(SeekFrom::Start(4)).unwrap());
+        assert!(sink.seek(SeekFrom::Start(0)).is_err());
+    }
+}
@@ -0,0 +1,348 @@
+#[cfg(feature = "proj")]
+use crate::Error;
+use crate::{
+    crs::{Crs, CrsKind},
+    header::fit_transform,
+    writer::WriteSummary,
+    Bounds, Header, Point, Result, Vector, Writer,
+};
+use std::io::{Seek, Write};
+
+/// A change of coordinates from one crs to another, for a [Reproject] writer.
+///
+/// With the `proj` feature, a `proj::Proj` is a reprojection. Otherwise, implement this with a
+/// projection library of your own. Closures from and to xyz vectors are reprojections too. Return
+/// [Error::ReprojectionFailed](crate::Error::ReprojectionFailed) for coordinates that can't be
+/// reprojected.
+///
+/// # Examples
+///
+/// ```
+/// use las::{writer::Reprojection, Point, Result, Vector};
+///
+/// let feet_to_metres = |xyz: Vector<f64>| -> Result<Vector<f64>> {
+///     Ok(Vector { x: xyz.x * 0.3048, y: xyz.y * 0.3048, z: xyz.z * 0.3048 })
+/// };
+/// let point = feet_to_metres.reproject_point(Point { x: 10., ..Default::default() }).unwrap();
+/// assert_eq!(3.048, point.x);
+/// ```
+pub trait Reprojection {
+    /// Reprojects coordinates.
+    fn reproject(&self, xyz: Vector<f64>) -> Result<Vector<f64>>;
+
+    /// Reprojects a point's coordinates, e.g. for an iterator of points.
+    fn reproject_point(&self, mut point: Point) -> Result<Point> {
+        let xyz = self.reproject(Vector {
+            x: point.x,
+            y: point.y,
+            z: point.z,
+        })?;
+        point.x = xyz.x;
+        point.y = xyz.y;
+        point.z = xyz.z;
+        Ok(point)
+    }
+}
+
+impl<F: Fn(Vector<f64>) -> Result<Vector<f64>>> Reprojection for F {
+    fn reproject(&self, xyz: Vector<f64>) -> Result<Vector<f64>> {
+        self(xyz)
+    }
+}
+
+/// Reprojects x and y with [proj::Proj::convert], and keeps z as it is.
+#[cfg(feature = "proj")]
+impl Reprojection for proj::Proj {
+    fn reproject(&self, xyz: Vector<f64>) -> Result<Vector<f64>> {
+        let (x, y) = self
+            .convert((xyz.x, xyz.y))
+            .map_err(|err| Error::ReprojectionFailed(err.to_string()))?;
+        Ok(Vector { x, y, z: xyz.z })
+    }
+}
+
+/// Wraps a [Writer], reprojecting each point from the source header's crs to a target crs.
+///
+/// The header's scales and offsets are chosen for the target crs before anything is written:
+/// offsets come from the reprojected corners of the source bounds, and if the crs goes from
+/// projected to geographic or back, the x and y scales become 1e-7 degrees or a millimetre. The
+/// crs (E)VLRs are replaced with the target's, as [Builder::crs](crate::Builder::crs) does, and
+/// the bounds are worked out from the reprojected points.
+///
+/// # Examples
+///
+/// ```
+/// use las::{
+///     crs::{Crs, CrsKind, EpsgCrs},
+///     writer::Reproject,
+///     Reader, Result, Vector,
+/// };
+/// use std::io::Cursor;
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let target = Crs::from_epsg(CrsKind::Projected, EpsgCrs { horizontal: 2992, vertical: None });
+/// let shift = |xyz: Vector<f64>| -> Result<Vector<f64>> { Ok(Vector { x: xyz.x + 1000., ..xyz }) };
+/// let mut writer =
+///     Reproject::new(Cursor::new(Vec::new()), reader.header().clone(), &target, shift).unwrap();
+/// for point in reader.into_points().unwrap() {
+///     writer.write_point(point).unwrap();
+/// }
+/// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+/// assert_eq!(2992, reader.header().crs().unwrap().epsg().unwrap().horizontal);
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct Reproject<W: Write + Seek + Send, P: Reprojection> {
+    writer: Writer<W>,
+    reprojection: P,
+}
+
+impl<W: Write + Seek + Send, P: Reprojection> Reproject<W, P> {
+    /// Creates a writer of reprojected points, from the header of the source points.
+    ///
+    /// Returns an error if the corners of the source bounds can't be reprojected, or the target
+    /// crs can't be stored with the header's version and point format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{crs::Crs, writer::Reproject, Builder, Result, Vector};
+    /// use std::io::Cursor;
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let identity = |xyz: Vector<f64>| -> Result<Vector<f64>> { Ok(xyz) };
+    /// let target = Crs::from_wkt(r#"GEOGCS["WGS 84"]"#);
+    /// let writer = Reproject::new(Cursor::new(Vec::new()), header, &target, identity).unwrap();
+    /// ```
+    pub fn new(write: W, header: Header, target: &Crs, reprojection: P) -> Result<Reproject<W, P>> {
+        let source_kind = header.crs().and_then(|crs| crs.kind());
+        let bounds = header.bounds();
+        let mut builder = header.into_builder();
+        if bounds.min.x <= bounds.max.x {
+            let mut reprojected = Bounds::default();
+            for x in [bounds.min.x, bounds.max.x] {
+                for y in [bounds.min.y, bounds.max.y] {
+                    for z in [bounds.min.z, bounds.max.z] {
+                        let xyz = reprojection.reproject(Vector { x, y, z })?;
+                        reprojected.grow(&Point {
+                            x: xyz.x,
+                            y: xyz.y,
+                            z: xyz.z,
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+            let mut scale = Vector {
+                x: builder.transforms.x.scale,
+                y: builder.transforms.y.scale,
+                z: builder.transforms.z.scale,
+            };
+            match (source_kind, target.kind()) {
+                (Some(CrsKind::Projected), Some(CrsKind::Geographic)) => {
+                    scale.x = 1e-7;
+                    scale.y = 1e-7;
+                }
+                (Some(CrsKind::Geographic), Some(CrsKind::Projected)) => {
+                    scale.x = 0.001;
+                    scale.y = 0.001;
+                }
+                _ => {}
+            }
+            let (min, max) = (reprojected.min, reprojected.max);
+            builder.transforms = Vector {
+                x: fit_transform(scale.x, min.x, max.x),
+                y: fit_transform(scale.y, min.y, max.y),
+                z: fit_transform(scale.z, min.z, max.z),
+            };
+        }
+        builder.crs(target)?;
+        Ok(Reproject {
+            writer: Writer::new(write, builder.into_header()?)?,
+            reprojection,
+        })
+    }
+
+    /// Reprojects a point and writes it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{crs::Crs, writer::Reproject, Builder, Point, Result, Vector};
+    /// use std::io::Cursor;
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let up = |xyz: Vector<f64>| -> Result<Vector<f64>> { Ok(Vector { z: xyz.z + 1., ..xyz }) };
+    /// let target = Crs::from_wkt(r#"GEOGCS["WGS 84"]"#);
+    /// let mut writer = Reproject::new(Cursor::new(Vec::new()), header, &target, up).unwrap();
+    /// writer.write_point(Point::default()).unwrap();
+    /// assert_eq!(1., writer.header().bounds().max.z);
+    /// ```
+    pub fn write_point(&mut self, point: Point) -> Result<()> {
+        let point = self.reprojection.reproject_point(point)?;
+        self.writer.write_point(point)
+    }
+
+    /// Returns the wrapped writer's header, which so far describes the reprojected points written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{crs::Crs, writer::Reproject, Builder, Result, Vector};
+    /// use std::io::Cursor;
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let identity = |xyz: Vector<f64>| -> Result<Vector<f64>> { Ok(xyz) };
+    /// let target = Crs::from_wkt(r#"GEOGCS["WGS 84"]"#);
+    /// let writer = Reproject::new(Cursor::new(Vec::new()), header, &target, identity).unwrap();
+    /// assert!(writer.header().has_wkt_crs());
+    /// ```
+    pub fn header(&self) -> &Header {
+        self.writer.header()
+    }
+
+    /// Closes the wrapped writer, and returns its summary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{crs::Crs, writer::Reproject, Builder, Result, Vector};
+    /// use std::io::Cursor;
+    ///
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let identity = |xyz: Vector<f64>| -> Result<Vector<f64>> { Ok(xyz) };
+    /// let target = Crs::from_wkt(r#"GEOGCS["WGS 84"]"#);
+    /// let mut writer = Reproject::new(Cursor::new(Vec::new()), header, &target, identity).unwrap();
+    /// writer.close().unwrap();
+    /// ```
+    pub fn close(&mut self) -> Result<WriteSummary> {
+        self.writer.close()
+    }
+
+    /// Returns the wrapped writer, without closing it.
+    pub fn into_writer(self) -> Writer<W> {
+        self.writer
+    }
+
+    /// Closes the wrapped writer and returns its inner `Write`, seeked to the beginning of the las
+    /// data.
+    pub fn into_inner(self) -> Result<W> {
+        self.writer.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{crs::EpsgCrs, Builder, Error, Reader};
+    use std::io::Cursor;
+
+    fn source(points: &[(f64, f64)]) -> (Header, Vec<Point>) {
+        let mut builder = Builder::from((1, 2));
+        builder
+            .crs(&Crs::from_epsg(
+                CrsKind::Geographic,
+                EpsgCrs {
+                    horizontal: 4326,
+                    vertical: None,
+                },
+            ))
+            .unwrap();
+        builder.transforms.x.scale = 1e-7;
+        builder.transforms.y.scale = 1e-7;
+        let mut header = builder.into_header().unwrap();
+        let points = points
+            .iter()
+            .map(|&(x, y)| Point {
+                x,
+                y,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        for point in &points {
+            header.add_point(point);
+        }
+        (header, points)
+    }
+
+    /// Degrees to metres on a sphere, near the equator.
+    fn equirectangular(xyz: Vector<f64>) -> Result<Vector<f64>> {
+        if xyz.y.abs() > 90. {
+            return Err(Error::ReprojectionFailed(format!("latitude {}", xyz.y)));
+        }
+        Ok(Vector {
+            x: xyz.x * 111_319.49,
+            y: xyz.y * 111_319.49,
+            z: xyz.z,
+        })
+    }
+
+    #[test]
+    fn geographic_to_projected() {
+        let (header, points) = source(&[(10., 1.), (10.5, 1.5)]);
+        let target = Crs::from_epsg(
+            CrsKind::Projected,
+            EpsgCrs {
+                horizontal: 4087,
+                vertical: None,
+            },
+        );
+        let mut writer =
+            Reproject::new(Cursor::new(Vec::new()), header, &target, equirectangular).unwrap();
+        for point in points {
+            writer.write_point(point).unwrap();
+        }
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let header = reader.header();
+        assert_eq!(Some(target), header.crs());
+        assert_eq!(0.001, header.transforms().x.scale);
+        assert_eq!(0.001, header.transforms().y.scale);
+        assert_eq!(1_141_025., header.transforms().x.offset);
+        assert!((header.bounds().max.x - 1_168_854.645).abs() < 0.001);
+        assert!((header.bounds().min.y - 111_319.49).abs() < 0.001);
+    }
+
+    #[test]
+    fn unreprojectable() {
+        let (header, _) = source(&[(10., 1.)]);
+        let target = Crs::from_epsg(
+            CrsKind::Projected,
+            EpsgCrs {
+                horizontal: 4087,
+                vertical: None,
+            },
+        );
+        let mut writer =
+            Reproject::new(Cursor::new(Vec::new()), header, &target, equirectangular).unwrap();
+        assert!(matches!(
+            writer
+                .write_point(Point {
+                    y: 91.,
+                    ..Default::default()
+                })
+                .unwrap_err(),
+            Error::ReprojectionFailed(_)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "proj")]
+    fn proj_reprojection() {
+        let (header, points) = source(&[(10., 1.), (10.5, 1.5)]);
+        let target = Crs::from_epsg(
+            CrsKind::Projected,
+            EpsgCrs {
+                horizontal: 4087,
+                vertical: None,
+            },
+        );
+        let proj = proj::Proj::new_known_crs("EPSG:4326", "EPSG:4087", None).unwrap();
+        let mut writer = Reproject::new(Cursor::new(Vec::new()), header, &target, proj).unwrap();
+        for point in points {
+            writer.write_point(Point { z: 42., ..point }).unwrap();
+        }
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let bounds = reader.header().bounds();
+        assert!((bounds.min.x - 1_113_194.908).abs() < 0.01);
+        assert_eq!(42., bounds.max.z);
+    }
+}
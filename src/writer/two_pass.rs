@@ -0,0 +1,203 @@
+use crate::{Bounds, Builder, Error, Header, Point, Result, Writer};
+use std::io::{Seek, Write};
+
+/// Buffers points, and writes them once their transforms can be picked from all of them.
+///
+/// Nothing is written until [TwoPass::into_writer] or [TwoPass::into_inner]. Then the header's
+/// transforms come from [Builder::auto_transforms] with the bounds of the buffered points, so the
+/// offsets are centered on the points and each scale is the finest, starting at the resolution,
+/// that keeps every coordinate in i32. The rest of the header is used as-is. The points are kept in
+/// memory until they're written, so errors for any one of them come from that last step.
+///
+/// # Examples
+///
+/// ```
+/// use las::{writer::TwoPass, Header, Point, Reader};
+/// use std::io::Cursor;
+///
+/// let mut writer = TwoPass::new(Cursor::new(Vec::new()), Header::default(), 0.01).unwrap();
+/// writer.write_point(Point { x: 1e9, ..Default::default() });
+/// writer.write_point(Point { x: 1e9 + 100., ..Default::default() });
+/// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+/// assert_eq!(1e9 + 50., reader.header().transforms().x.offset);
+/// assert_eq!(0.01, reader.header().transforms().x.scale);
+/// ```
+#[derive(Debug)]
+pub struct TwoPass<W: Write + Seek + Send> {
+    write: W,
+    builder: Builder,
+    resolution: f64,
+    bounds: Bounds,
+    points: Vec<Point>,
+}
+
+impl<W: Write + Seek + Send> TwoPass<W> {
+    /// Creates a two-pass writer, which picks transforms at this resolution or coarser.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::TwoPass, Header};
+    /// use std::io::Cursor;
+    ///
+    /// let writer = TwoPass::new(Cursor::new(Vec::new()), Header::default(), 0.001).unwrap();
+    /// assert!(TwoPass::new(Cursor::new(Vec::new()), Header::default(), -1.).is_err());
+    /// ```
+    pub fn new(write: W, header: Header, resolution: f64) -> Result<TwoPass<W>> {
+        if !(resolution.is_finite() && resolution > 0.) {
+            return Err(Error::InvalidResolution(resolution));
+        }
+        Ok(TwoPass {
+            write,
+            builder: header.into_builder(),
+            resolution,
+            bounds: Bounds::default(),
+            points: Vec::new(),
+        })
+    }
+
+    /// Buffers a point, to be written later.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::TwoPass, Header, Point};
+    /// use std::io::Cursor;
+    ///
+    /// let mut writer = TwoPass::new(Cursor::new(Vec::new()), Header::default(), 0.001).unwrap();
+    /// writer.write_point(Point::default());
+    /// assert_eq!(1, writer.points().len());
+    /// ```
+    pub fn write_point(&mut self, point: Point) {
+        self.bounds.grow(&point);
+        self.points.push(point);
+    }
+
+    /// Returns the buffered points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::TwoPass, Header};
+    /// use std::io::Cursor;
+    ///
+    /// let writer = TwoPass::new(Cursor::new(Vec::new()), Header::default(), 0.001).unwrap();
+    /// assert!(writer.points().is_empty());
+    /// ```
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Returns the bounds of the buffered points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::TwoPass, Header, Point};
+    /// use std::io::Cursor;
+    ///
+    /// let mut writer = TwoPass::new(Cursor::new(Vec::new()), Header::default(), 0.001).unwrap();
+    /// writer.write_point(Point { z: 42., ..Default::default() });
+    /// assert_eq!(42., writer.bounds().max.z);
+    /// ```
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// Picks the transforms, and writes the buffered points to a new writer, which isn't closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::TwoPass, Header, Point};
+    /// use std::io::Cursor;
+    ///
+    /// let mut writer = TwoPass::new(Cursor::new(Vec::new()), Header::default(), 0.001).unwrap();
+    /// writer.write_point(Point { y: 1234.5, ..Default::default() });
+    /// let mut writer = writer.into_writer().unwrap();
+    /// writer.write_point(Point { y: 1235., ..Default::default() }).unwrap();
+    /// assert_eq!(1235., writer.header().transforms().y.offset);
+    /// ```
+    pub fn into_writer(self) -> Result<Writer<W>> {
+        let TwoPass {
+            write,
+            mut builder,
+            resolution,
+            bounds,
+            points,
+        } = self;
+        builder.auto_transforms(&bounds, resolution)?;
+        let mut writer = Writer::new(write, builder.into_header()?)?;
+        for point in &points {
+            writer.write_point_ref(point)?;
+        }
+        Ok(writer)
+    }
+
+    /// Writes the buffered points, closes the writer, and returns its inner `Write`, seeked to the
+    /// beginning of the las data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{writer::TwoPass, Header};
+    /// use std::io::Cursor;
+    ///
+    /// let writer = TwoPass::new(Cursor::new(Vec::new()), Header::default(), 0.001).unwrap();
+    /// let cursor = writer.into_inner().unwrap();
+    /// ```
+    pub fn into_inner(self) -> Result<W> {
+        self.into_writer()?.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Reader, Vector};
+    use std::io::Cursor;
+
+    #[test]
+    fn large_coordinates() {
+        let mut writer = TwoPass::new(Cursor::new(Vec::new()), Header::default(), 0.001).unwrap();
+        let points = [
+            Vector {
+                x: -1e7,
+                y: 4e6,
+                z: 0.125,
+            },
+            Vector {
+                x: 1e7,
+                y: 4e6 + 0.5,
+                z: 0.25,
+            },
+        ];
+        for xyz in points {
+            writer.write_point(Point {
+                x: xyz.x,
+                y: xyz.y,
+                z: xyz.z,
+                ..Default::default()
+            });
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let transforms = *reader.header().transforms();
+        assert_eq!(0.01, transforms.x.scale);
+        assert_eq!(0., transforms.x.offset);
+        assert_eq!(0.001, transforms.y.scale);
+        assert_eq!(4e6, transforms.y.offset);
+        let points = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(-1e7, points[0].x);
+        assert_eq!(4e6 + 0.5, points[1].y);
+    }
+
+    #[test]
+    fn empty() {
+        let header = Header::default();
+        let transforms = *header.transforms();
+        let writer = TwoPass::new(Cursor::new(Vec::new()), header, 0.01).unwrap();
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(transforms, *reader.header().transforms());
+        assert_eq!(0, reader.header().number_of_points());
+    }
+}
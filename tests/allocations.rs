@@ -0,0 +1,91 @@
+//! Counts heap allocations while reading points.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+use las::{point::Format, Builder, Point, Reader, Writer};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    io::Cursor,
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    // Per thread, so allocations by the test harness's other threads aren't counted.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn reader(extra_bytes: u16) -> Reader<Cursor<Vec<u8>>> {
+    let mut builder = Builder::from((1, 4));
+    builder.point_format = Format {
+        extra_bytes,
+        ..Format::new(3).unwrap()
+    };
+    let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    for i in 0..1000u16 {
+        let point = Point {
+            gps_time: Some(f64::from(i)),
+            color: Some(Default::default()),
+            extra_bytes: vec![i as u8; usize::from(extra_bytes)].into(),
+            ..Default::default()
+        };
+        writer.write_point(point).unwrap();
+    }
+    Reader::new(writer.into_inner().unwrap()).unwrap()
+}
+
+#[test]
+fn read_extra_bytes_without_allocating() {
+    let mut reader = reader(6);
+
+    let before = ALLOCATIONS.with(Cell::get);
+    let mut count = 0;
+    for point in reader.points() {
+        let point = point.unwrap();
+        assert_eq!(6, point.extra_bytes.len());
+        count += 1;
+    }
+    let allocations = ALLOCATIONS.with(Cell::get) - before;
+    assert_eq!(1000, count);
+    assert_eq!(0, allocations);
+}
+
+#[test]
+fn read_point_into_reuses_extra_bytes() {
+    let mut reader = reader(32);
+    let before = ALLOCATIONS.with(Cell::get);
+    while reader.read_point().unwrap().is_some() {}
+    assert!(ALLOCATIONS.with(Cell::get) - before >= 1000);
+
+    reader.seek(0).unwrap();
+    let mut point = Point::default();
+    // The first point allocates the record buffer and the extra bytes.
+    assert!(reader.read_point_into(&mut point).unwrap());
+    let before = ALLOCATIONS.with(Cell::get);
+    let mut count = 1;
+    while reader.read_point_into(&mut point).unwrap() {
+        assert_eq!(32, point.extra_bytes.len());
+        count += 1;
+    }
+    let allocations = ALLOCATIONS.with(Cell::get) - before;
+    assert_eq!(1000, count);
+    assert_eq!(0, allocations);
+    assert_eq!([231; 32], point.extra_bytes[..]);
+}
@@ -1,5 +1,7 @@
 //! Massage and work the auzten file to make sure we can deal with real data.
 
+#![cfg(feature = "std")]
+
 extern crate las;
 
 macro_rules! autzen {
@@ -224,3 +226,30 @@ fn test_laz_read_all_points() {
 fn test_copc_read_all_points() {
     test_read_all_points_into_on("tests/data/autzen.copc.laz");
 }
+
+#[cfg(feature = "laz")]
+#[test]
+fn test_edit_and_copy_copc_with_week_time() {
+    use las::{edit::HeaderEditor, GpsTimeType, Reader, Transform, Vector};
+    use std::io::Cursor;
+
+    let bytes = std::fs::read("tests/data/autzen.copc.laz").unwrap();
+    let mut editor = HeaderEditor::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(GpsTimeType::Week, editor.builder().gps_time_type);
+    editor.builder().file_source_id = 42;
+    let reader = Reader::new(editor.save().unwrap()).unwrap();
+    assert_eq!(42, reader.header().file_source_id());
+    assert!(reader.header().clone().into_builder().into_header().is_ok());
+
+    let transform = Transform {
+        scale: 0.01,
+        offset: 0.,
+    };
+    let transforms = Vector {
+        x: transform,
+        y: transform,
+        z: transform,
+    };
+    let report = las::requantize(reader, Cursor::new(Vec::new()), transforms).unwrap();
+    assert!(report.points > 0);
+}
@@ -0,0 +1,149 @@
+//! Budgeted reads that are resumed on fresh readers.
+
+#![cfg(feature = "std")]
+
+mod common;
+
+#[cfg(feature = "digest")]
+use las::Error;
+use las::{
+    reader::{Budget, ResumeToken},
+    Point, Reader,
+};
+use std::io::Cursor;
+
+fn reader(bytes: &[u8]) -> Reader<Cursor<Vec<u8>>> {
+    Reader::new(Cursor::new(bytes.to_vec())).unwrap()
+}
+
+/// Reads in three budgeted parts, each on a new reader, and returns the points and tokens.
+fn read_in_parts(bytes: &[u8], budgets: [Budget; 3]) -> (Vec<Point>, Vec<ResumeToken>) {
+    let mut points = Vec::new();
+    let mut tokens: Vec<ResumeToken> = Vec::new();
+    for budget in budgets {
+        let mut reader = reader(bytes);
+        if let Some(&token) = tokens.last() {
+            reader.resume(token).unwrap();
+        }
+        let (part, token) = reader.read_budgeted(budget).unwrap();
+        points.extend(part);
+        tokens.push(token);
+    }
+    (points, tokens)
+}
+
+#[test]
+fn three_parts() {
+    let bytes = std::fs::read("tests/data/autzen.las").unwrap();
+    let record_length = u64::from(reader(&bytes).header().point_format().len());
+    let (points, tokens) = read_in_parts(
+        &bytes,
+        [
+            Budget::default().points(40),
+            Budget::default().points(1000).bytes(50 * record_length + 1),
+            Budget::default(),
+        ],
+    );
+    assert_eq!(reader(&bytes).into_points().unwrap(), points);
+    assert_eq!(
+        vec![40, 90, 106],
+        tokens.iter().map(ResumeToken::index).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![false, false, true],
+        tokens
+            .iter()
+            .map(ResumeToken::is_finished)
+            .collect::<Vec<_>>()
+    );
+    assert!(tokens.iter().all(|token| token.chunk().is_none()));
+}
+
+#[test]
+#[cfg(feature = "digest")]
+fn other_file() {
+    let autzen = std::fs::read("tests/data/autzen.las").unwrap();
+    let (_, token) = reader(&autzen)
+        .read_budgeted(Budget::default().points(10))
+        .unwrap();
+    let mut other = Reader::from_path("tests/data/waveform.las").unwrap();
+    assert!(matches!(
+        other.resume(token).unwrap_err(),
+        Error::ResumeTokenMismatch
+    ));
+}
+
+#[cfg(feature = "laz")]
+mod laz {
+    use super::*;
+
+    /// Writes autzen's points over and over, in chunks of 1000.
+    fn chunked() -> Vec<u8> {
+        let (builder, points) = common::autzen();
+        common::write_chunked(builder, 1000, points.into_iter().cycle().take(2500))
+    }
+
+    #[test]
+    fn three_parts() {
+        let bytes = chunked();
+        let (points, tokens) = read_in_parts(
+            &bytes,
+            [
+                Budget::default().points(700),
+                Budget::default().points(1500),
+                Budget::default(),
+            ],
+        );
+        assert_eq!(reader(&bytes).into_points().unwrap(), points);
+        assert_eq!(
+            vec![(Some(0), Some(700)), (Some(2), Some(200)), (None, None)],
+            tokens
+                .iter()
+                .map(|token| (token.chunk(), token.offset_in_chunk()))
+                .collect::<Vec<_>>()
+        );
+        assert!(tokens[2].is_finished());
+    }
+
+    /// Writes autzen's points over and over, in chunks of 100, 300, 50, and 200 points.
+    fn variable() -> Vec<u8> {
+        let (builder, points) = common::autzen();
+        common::write_variable_chunks(builder, &[100, 300, 50, 200], points.into_iter().cycle())
+    }
+
+    #[test]
+    fn variable_chunks() {
+        let bytes = variable();
+        let (points, tokens) = read_in_parts(
+            &bytes,
+            [
+                Budget::default().points(250),
+                Budget::default().points(200),
+                Budget::default(),
+            ],
+        );
+        assert_eq!(reader(&bytes).into_points().unwrap(), points);
+        assert_eq!(
+            vec![(Some(1), Some(150)), (Some(3), Some(0)), (None, None)],
+            tokens
+                .iter()
+                .map(|token| (token.chunk(), token.offset_in_chunk()))
+                .collect::<Vec<_>>()
+        );
+        assert!(tokens[2].is_finished());
+    }
+
+    #[test]
+    #[cfg(feature = "digest")]
+    fn las_token_on_laz() {
+        let las = std::fs::read("tests/data/autzen.las").unwrap();
+        let (_, token) = reader(&las)
+            .read_budgeted(Budget::default().points(10))
+            .unwrap();
+        let mut laz = Reader::from_path("tests/data/autzen.laz").unwrap();
+        assert!(matches!(
+            laz.resume(token).unwrap_err(),
+            Error::ResumeTokenMismatch
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+//! Fixtures shared by the integration tests.
+
+// Each test crate builds this module on its own, and not every one uses every fixture.
+#![allow(dead_code)]
+
+use las::{writer::WriteOptions, Builder, Point, Writer};
+use std::io::Cursor;
+
+/// Writes points into memory with the header from a builder, and leaves the writer open.
+pub fn writer<I: IntoIterator<Item = Point>>(
+    builder: Builder,
+    points: I,
+) -> Writer<Cursor<Vec<u8>>> {
+    writer_with_options(builder, WriteOptions::default(), points)
+}
+
+/// Writes points into memory with the header from a builder and some options, and leaves the
+/// writer open.
+pub fn writer_with_options<I: IntoIterator<Item = Point>>(
+    builder: Builder,
+    options: WriteOptions,
+    points: I,
+) -> Writer<Cursor<Vec<u8>>> {
+    let mut writer = Writer::with_options(
+        Cursor::new(Vec::new()),
+        builder.into_header().unwrap(),
+        options,
+    )
+    .unwrap();
+    for point in points {
+        writer.write_point(point).unwrap();
+    }
+    writer
+}
+
+/// Writes points into memory with the header from a builder, and returns the file's bytes.
+pub fn write<I: IntoIterator<Item = Point>>(builder: Builder, points: I) -> Vec<u8> {
+    write_with_options(builder, WriteOptions::default(), points)
+}
+
+/// Writes points into memory with the header from a builder and some options, and returns the
+/// file's bytes.
+pub fn write_with_options<I: IntoIterator<Item = Point>>(
+    builder: Builder,
+    options: WriteOptions,
+    points: I,
+) -> Vec<u8> {
+    writer_with_options(builder, options, points)
+        .into_inner()
+        .unwrap()
+        .into_inner()
+}
+
+/// Writes compressed points into memory in chunks of a fixed size, and returns the file's bytes.
+pub fn write_chunked<I: IntoIterator<Item = Point>>(
+    builder: Builder,
+    chunk_size: u32,
+    points: I,
+) -> Vec<u8> {
+    let options = WriteOptions::default().laz_chunk_size(chunk_size);
+    write_with_options(builder, options, points)
+}
+
+/// Writes compressed points into memory in variable size chunks of the given lengths, and returns
+/// the file's bytes.
+#[cfg(feature = "laz")]
+pub fn write_variable_chunks<I: IntoIterator<Item = Point>>(
+    builder: Builder,
+    lens: &[usize],
+    points: I,
+) -> Vec<u8> {
+    let options = WriteOptions::default().variable_laz_chunks();
+    let mut writer = writer_with_options(builder, options, []);
+    let mut points = points.into_iter();
+    for &len in lens {
+        for point in points.by_ref().take(len) {
+            writer.write_point(point).unwrap();
+        }
+        writer.finish_laz_chunk().unwrap();
+    }
+    writer.into_inner().unwrap().into_inner()
+}
+
+/// Returns a builder with the version, point format, and transforms of autzen.laz, and its points.
+#[cfg(feature = "laz")]
+pub fn autzen() -> (Builder, Vec<Point>) {
+    let reader = las::Reader::from_path("tests/data/autzen.laz").unwrap();
+    let mut builder = Builder::from(reader.header().version());
+    builder.point_format = *reader.header().point_format();
+    builder.transforms = *reader.header().transforms();
+    (builder, reader.into_points().unwrap())
+}
@@ -0,0 +1,169 @@
+//! Reading only some of the points' dimensions.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+mod common;
+
+use las::{
+    point::{Dimension, Format},
+    raw::point::Waveform,
+    reader::ReadOptions,
+    Builder, Color, GpsTimeType, Point, Reader,
+};
+use std::io::Cursor;
+
+fn points() -> Vec<Point> {
+    (0..10)
+        .map(|i| Point {
+            x: f64::from(i),
+            y: f64::from(i) + 1.,
+            z: f64::from(i) + 2.,
+            intensity: 100 + i,
+            return_number: 1,
+            number_of_returns: 2,
+            scan_angle: 3.,
+            user_data: 4,
+            point_source_id: 5,
+            gps_time: Some(f64::from(i)),
+            color: Some(Color::new(6, 7, 8)),
+            nir: Some(9),
+            extra_bytes: vec![10, 11].into(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn fixture(compressed: bool) -> Vec<u8> {
+    let mut builder = Builder::from((1, 4));
+    builder.point_format = Format::new(8).unwrap();
+    builder.point_format.is_compressed = compressed;
+    builder.point_format.extra_bytes = 2;
+    builder.gps_time_type = GpsTimeType::Standard;
+    common::write(builder, points())
+}
+
+/// The points with only the coordinates, flags, intensity, and gps time.
+fn expected() -> Vec<Point> {
+    points()
+        .into_iter()
+        .map(|point| Point {
+            x: point.x,
+            y: point.y,
+            z: point.z,
+            intensity: point.intensity,
+            return_number: point.return_number,
+            number_of_returns: point.number_of_returns,
+            gps_time: point.gps_time,
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn check(compressed: bool) {
+    let bytes = fixture(compressed);
+    let options = ReadOptions::default().dimensions(&[Dimension::Intensity, Dimension::GpsTime]);
+    let reader = || Reader::with_options(Cursor::new(bytes.clone()), options).unwrap();
+
+    assert_eq!(expected(), reader().read_points(20).unwrap());
+    assert_eq!(
+        expected(),
+        reader().points().collect::<las::Result<Vec<_>>>().unwrap()
+    );
+    assert_eq!(expected(), reader().into_points().unwrap());
+
+    let mut reader = reader();
+    let mut point = points()[0].clone();
+    assert!(reader.read_point_into(&mut point).unwrap());
+    assert_eq!(expected()[0], point);
+    reader.seek(5).unwrap();
+    assert_eq!(expected()[5], reader.read_point().unwrap().unwrap());
+
+    let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(points(), reader.read_points(20).unwrap());
+}
+
+#[test]
+fn uncompressed() {
+    check(false);
+}
+
+#[test]
+#[cfg(feature = "laz")]
+fn compressed() {
+    check(true);
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn parallel() {
+    let options = ReadOptions::default().dimensions(&[Dimension::Intensity, Dimension::GpsTime]);
+    let mut reader = Reader::with_options(Cursor::new(fixture(false)), options).unwrap();
+    assert_eq!(
+        expected(),
+        reader
+            .points_par()
+            .collect::<las::Result<Vec<_>>>()
+            .unwrap()
+    );
+}
+
+#[test]
+fn no_dimensions() {
+    let options = ReadOptions::default().dimensions(&[]);
+    let mut reader = Reader::with_options(Cursor::new(fixture(false)), options).unwrap();
+    let point = reader.read_point().unwrap().unwrap();
+    assert_eq!(0., point.x);
+    assert_eq!(0, point.intensity);
+    assert_eq!(None, point.color);
+    assert!(point.extra_bytes.is_empty());
+}
+
+#[test]
+fn skip_waveforms() {
+    let waveform = Waveform {
+        wave_packet_descriptor_index: 1,
+        byte_offset_to_waveform_data: 2,
+        waveform_packet_size_in_bytes: 3,
+        return_point_waveform_location: 4.,
+        x_t: 5.,
+        y_t: 6.,
+        z_t: 7.,
+    };
+    let mut builder = Builder::from((1, 4));
+    builder.point_format = Format::new(4).unwrap();
+    let bytes = common::write(
+        builder,
+        [Point {
+            intensity: 1,
+            gps_time: Some(2.),
+            waveform: Some(waveform),
+            ..Default::default()
+        }],
+    );
+    let read = |options: ReadOptions| {
+        Reader::with_options(Cursor::new(bytes.clone()), options)
+            .unwrap()
+            .read_point()
+            .unwrap()
+            .unwrap()
+    };
+
+    let point = read(ReadOptions::default().dimensions(&[Dimension::Intensity]));
+    assert_eq!(Some(waveform), point.waveform);
+    assert_eq!(None, point.gps_time);
+
+    let point = read(ReadOptions::default().skip_waveforms(true));
+    assert_eq!(None, point.waveform);
+    assert_eq!(1, point.intensity);
+    assert_eq!(Some(2.), point.gps_time);
+
+    let point = read(
+        ReadOptions::default()
+            .dimensions(&[Dimension::Intensity])
+            .skip_waveforms(true),
+    );
+    assert_eq!(None, point.waveform);
+    assert_eq!(1, point.intensity);
+}
@@ -0,0 +1,79 @@
+//! Las files that start at an offset inside a larger stream.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+use las::{Builder, Point, Reader, Vlr, Writer};
+use std::io::{Cursor, Seek, SeekFrom};
+
+const JUNK: usize = 1024;
+
+fn embed(path: &str) -> Vec<u8> {
+    let mut bytes: Vec<u8> = (0..JUNK).map(|i| (i % 256) as u8).collect();
+    bytes.extend(std::fs::read(path).unwrap());
+    bytes
+}
+
+fn check(path: &str) {
+    let expected = Reader::from_path(path).unwrap().into_points().unwrap();
+    let mut reader = Reader::new_at(Cursor::new(embed(path)), JUNK as u64).unwrap();
+    assert_eq!(
+        expected,
+        reader.points().map(Result::unwrap).collect::<Vec<_>>()
+    );
+    reader.seek(50).unwrap();
+    assert_eq!(expected[50], reader.read_point().unwrap().unwrap());
+}
+
+#[test]
+fn read_las() {
+    check("tests/data/autzen.las");
+}
+
+#[test]
+#[cfg(feature = "laz")]
+fn read_laz() {
+    check("tests/data/autzen.laz");
+}
+
+#[test]
+fn not_at_the_offset() {
+    assert!(Reader::new(Cursor::new(embed("tests/data/autzen.las"))).is_err());
+}
+
+#[test]
+fn write_in_the_middle() {
+    let points: Vec<Point> = (0..10)
+        .map(|i| Point {
+            x: f64::from(i),
+            ..Default::default()
+        })
+        .collect();
+    let mut cursor = Cursor::new(vec![0xAB; 2 * JUNK]);
+    let _ = cursor.seek(SeekFrom::Start(JUNK as u64)).unwrap();
+    let mut builder = Builder::from((1, 4));
+    builder.evlrs.push(Vlr {
+        user_id: "las-rs".to_string(),
+        record_id: 42,
+        data: vec![1, 2, 3],
+        ..Default::default()
+    });
+    let mut writer = Writer::new_at(cursor, builder.into_header().unwrap()).unwrap();
+    for point in &points {
+        writer.write_point_ref(point).unwrap();
+    }
+    let embedded = writer.into_inner().unwrap();
+    assert_eq!(JUNK as u64, embedded.base());
+    let bytes = embedded.into_inner().into_inner();
+    assert_eq!(2 * JUNK, bytes.len());
+    assert!(bytes[..JUNK].iter().all(|&byte| byte == 0xAB));
+    assert_eq!(0xAB, bytes[2 * JUNK - 1]);
+
+    let reader = Reader::new_at(Cursor::new(bytes.clone()), JUNK as u64).unwrap();
+    assert_eq!(vec![1, 2, 3], reader.header().evlrs()[0].data);
+    assert_eq!(points, reader.into_points().unwrap());
+    let reader = Reader::new(Cursor::new(bytes[JUNK..].to_vec())).unwrap();
+    assert_eq!(vec![1, 2, 3], reader.header().evlrs()[0].data);
+    assert_eq!(points, reader.into_points().unwrap());
+}
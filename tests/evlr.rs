@@ -0,0 +1,38 @@
+//! Streaming evlrs that are too big to hold in memory.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+use las::{Builder, Point, Reader, Writer};
+use std::io::{Cursor, Write};
+
+/// A hundred megabytes, written a megabyte at a time.
+const LEN: usize = 100 * CHUNK_LEN;
+const CHUNK_LEN: usize = 1024 * 1024;
+
+/// The bytes of each chunk aren't all the same.
+fn byte(i: usize) -> u8 {
+    (i % CHUNK_LEN % 251) as u8
+}
+
+#[test]
+fn stream_large_evlr() {
+    let header = Builder::from((1, 4)).into_header().unwrap();
+    let mut writer = Writer::new(Cursor::new(Vec::with_capacity(LEN + 1024)), header).unwrap();
+    writer.write_point(Point::default()).unwrap();
+    let mut evlr = writer.begin_evlr("LASF_Spec", 65535, "waveforms").unwrap();
+    let chunk: Vec<u8> = (0..CHUNK_LEN).map(byte).collect();
+    for _ in 0..LEN / chunk.len() {
+        evlr.write_all(&chunk).unwrap();
+    }
+    evlr.finish().unwrap();
+
+    let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(1, reader.header().number_of_points());
+    let evlrs = reader.header().evlrs();
+    assert_eq!(1, evlrs.len());
+    assert_eq!(65535, evlrs[0].record_id);
+    assert_eq!(LEN, evlrs[0].data.len());
+    assert!(evlrs[0].data.iter().enumerate().all(|(i, &b)| b == byte(i)));
+}
@@ -0,0 +1,257 @@
+//! Header -> Builder -> Header round trips.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+extern crate uuid;
+
+use las::{Builder, Error, Header, Reader, Vlr, Writer};
+use std::{
+    fs::File,
+    io::{BufReader, Cursor},
+};
+
+/// Compares every field that a header exposes.
+///
+/// The start of the first evlr is left out of the comparison, since the writer recomputes it.
+fn assert_same(expected: &Header, actual: &Header) {
+    assert_eq!(expected.bounds(), actual.bounds());
+    assert_eq!(expected.date(), actual.date());
+    assert_eq!(expected.evlrs(), actual.evlrs());
+    assert_eq!(expected.file_source_id(), actual.file_source_id());
+    assert_eq!(expected.generating_software(), actual.generating_software());
+    assert_eq!(expected.gps_time_type(), actual.gps_time_type());
+    assert_eq!(expected.guid(), actual.guid());
+    assert_eq!(
+        expected.has_external_waveform_data_packets(),
+        actual.has_external_waveform_data_packets()
+    );
+    assert_eq!(
+        expected.has_internal_waveform_data_packets(),
+        actual.has_internal_waveform_data_packets()
+    );
+    assert_eq!(
+        expected.has_synthetic_return_numbers(),
+        actual.has_synthetic_return_numbers()
+    );
+    assert_eq!(expected.has_wkt_crs(), actual.has_wkt_crs());
+    assert_eq!(expected.number_of_points(), actual.number_of_points());
+    for n in 0..=u8::MAX {
+        assert_eq!(
+            expected.number_of_points_by_return(n),
+            actual.number_of_points_by_return(n)
+        );
+    }
+    assert_eq!(expected.padding(), actual.padding());
+    assert_eq!(expected.point_format(), actual.point_format());
+    assert_eq!(expected.point_padding(), actual.point_padding());
+    assert_eq!(
+        expected.start_of_waveform_data_packet_record(),
+        actual.start_of_waveform_data_packet_record()
+    );
+    assert_eq!(expected.system_identifier(), actual.system_identifier());
+    assert_eq!(expected.transforms(), actual.transforms());
+    assert_eq!(expected.version(), actual.version());
+    assert_eq!(expected.vlr_padding(), actual.vlr_padding());
+    assert_eq!(expected.vlrs(), actual.vlrs());
+}
+
+fn roundtrip(header: Header) {
+    let builder = header.clone().into_builder();
+    assert_same(&header, &builder.into_header().unwrap());
+}
+
+#[test]
+fn fixtures() {
+    for path in [
+        "tests/data/32-1-472-150-76.laz",
+        "tests/data/autzen.copc.laz",
+        "tests/data/autzen.las",
+        "tests/data/autzen.laz",
+        "tests/data/extrabytes.laz",
+    ] {
+        let header = Header::new(BufReader::new(File::open(path).unwrap())).unwrap();
+        roundtrip(header);
+    }
+}
+
+#[test]
+fn everything() {
+    let mut builder = Builder::from((1, 4));
+    builder.file_source_id = 42;
+    builder.guid = uuid::Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+    builder.has_external_waveform_data_packets = true;
+    builder.has_synthetic_return_numbers = true;
+    builder.has_wkt_crs = true;
+    builder.padding = vec![1, 2, 3];
+    builder.vlr_padding = vec![4, 5];
+    builder.point_padding = vec![6];
+    builder.start_of_waveform_data_packet_record = Some(1234);
+    builder.vlrs.push(Vlr {
+        user_id: "vlr".to_string(),
+        ..Default::default()
+    });
+    for record_id in 0..3 {
+        builder.evlrs.push(Vlr {
+            user_id: "evlr".to_string(),
+            record_id,
+            data: vec![record_id as u8; 10],
+            ..Default::default()
+        });
+    }
+    let header = builder.into_header().unwrap();
+    roundtrip(header.clone());
+
+    let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    for return_number in [1, 1, 2, 3] {
+        writer
+            .write_point(las::Point {
+                return_number,
+                number_of_returns: 3,
+                ..Default::default()
+            })
+            .unwrap();
+    }
+    let expected = writer.header().clone();
+    let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    assert_same(&expected, reader.header());
+    roundtrip(reader.header().clone());
+}
+
+fn with_extra_header_bytes() -> Vec<u8> {
+    let mut builder = Builder::from((1, 4));
+    builder.extra_header_bytes((0..64).collect());
+    builder.vlrs.push(Vlr {
+        user_id: "vlr".to_string(),
+        data: vec![7; 10],
+        ..Default::default()
+    });
+    let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    writer.write_point(Default::default()).unwrap();
+    writer.into_inner().unwrap().into_inner()
+}
+
+#[test]
+fn extra_header_bytes_roundtrip() {
+    let bytes = with_extra_header_bytes();
+    assert_eq!(375 + 64, u16::from_le_bytes([bytes[94], bytes[95]]));
+    assert_eq!((0..64).collect::<Vec<u8>>(), bytes[375..439]);
+
+    let mut reader = Reader::new(Cursor::new(bytes.clone())).unwrap();
+    assert_eq!(
+        (0..64).collect::<Vec<u8>>(),
+        reader.header().extra_header_bytes()
+    );
+    let points = reader.read_points(10).unwrap();
+    let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+    for point in points {
+        writer.write_point(point).unwrap();
+    }
+    assert_eq!(bytes, writer.into_inner().unwrap().into_inner());
+}
+
+#[test]
+fn header_size_too_small() {
+    let mut bytes = with_extra_header_bytes();
+    bytes[94..96].copy_from_slice(&300u16.to_le_bytes());
+    match Reader::new(Cursor::new(bytes)) {
+        Err(Error::ReadFailed { offset: 94, source }) => assert!(matches!(
+            *source,
+            Error::HeaderSizeTooSmall {
+                header_size: 300,
+                minimum: 375,
+                ..
+            }
+        )),
+        Err(err) => panic!("unexpected error: {err}"),
+        Ok(_) => panic!("the header size should be refused"),
+    }
+}
+
+#[test]
+fn adding_vlr_moves_point_data() {
+    let mut header = Builder::from((1, 2)).into_header().unwrap();
+    assert!(header.recompute_offsets().is_empty());
+    header.vlrs_mut().push(Vlr {
+        data: vec![0; 10],
+        ..Default::default()
+    });
+    let changes = header.recompute_offsets();
+    assert_eq!(64, changes.point_data_shift());
+    assert_eq!(227 + 64, changes.after.offset_to_point_data);
+    assert_eq!(1, changes.after.number_of_vlrs);
+    assert_eq!(None, changes.after.start_of_first_evlr);
+    let raw_header = header.into_raw().unwrap();
+    assert_eq!(227 + 64, raw_header.offset_to_point_data);
+    assert_eq!(1, raw_header.number_of_variable_length_records);
+}
+
+#[test]
+fn adding_vlr_moves_evlrs() {
+    let mut builder = Builder::from((1, 4));
+    builder.evlrs.push(Vlr {
+        user_id: "evlr".to_string(),
+        data: vec![42; 3],
+        ..Default::default()
+    });
+    let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    for i in 0..3 {
+        writer
+            .write_point(las::Point {
+                x: f64::from(i),
+                ..Default::default()
+            })
+            .unwrap();
+    }
+    writer.close().unwrap();
+    let mut header = writer.header().clone();
+    let bytes = writer.into_inner().unwrap().into_inner();
+    let before = header.offsets();
+    let points_len = 3 * u64::from(header.point_format().len());
+    assert_eq!(
+        Some(before.offset_to_point_data + points_len),
+        before.start_of_first_evlr
+    );
+
+    header.vlrs_mut().push(Vlr {
+        data: vec![0; 6],
+        ..Default::default()
+    });
+    let changes = header.recompute_offsets();
+    assert_eq!(60, changes.point_data_shift());
+    assert_eq!(
+        Some(before.start_of_first_evlr.unwrap() + 60),
+        changes.after.start_of_first_evlr
+    );
+    assert_eq!(1, changes.after.number_of_evlrs);
+
+    // Patch the file by writing the new header in front of the old points and evlrs.
+    let mut patched = Vec::new();
+    header.write_to(&mut patched).unwrap();
+    assert_eq!(changes.after.offset_to_point_data, patched.len() as u64);
+    patched.extend_from_slice(&bytes[before.offset_to_point_data as usize..]);
+    let mut reader = Reader::new(Cursor::new(patched)).unwrap();
+    assert_eq!(1, reader.header().vlrs().len());
+    assert_eq!(header.evlrs(), reader.header().evlrs());
+    let xs: Vec<f64> = reader.points().map(|point| point.unwrap().x).collect();
+    assert_eq!(vec![0., 1., 2.], xs);
+}
+
+#[test]
+fn sensor_system_identifier_roundtrip() {
+    use las::header::SystemIdentifier;
+
+    let mut builder = Builder::default();
+    builder.system_identifier = "Riegl VQ-1560i-DW sn 4711".to_string();
+    let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    writer.write_point(Default::default()).unwrap();
+    let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(
+        "Riegl VQ-1560i-DW sn 4711",
+        reader.header().system_identifier()
+    );
+    assert_eq!(
+        SystemIdentifier::Sensor("Riegl VQ-1560i-DW sn 4711".to_string()),
+        reader.header().system_identifier_kind()
+    );
+}
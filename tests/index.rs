@@ -0,0 +1,414 @@
+//! Bounds queries with and without a lax spatial index.
+//!
+//! `lasindex` isn't available to the test suite, so the index is built here the same way
+//! `lasindex` does it: a quadtree over the xy extent, with points assigned to finest-level cells
+//! by comparing against the `f32` cell midpoints.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+use las::{Bounds, Point, Reader, Vector};
+use std::{collections::BTreeMap, path::PathBuf};
+
+const LEVELS: u32 = 4;
+
+fn level_index(point: &Point, min_x: f32, max_x: f32, min_y: f32, max_y: f32) -> u32 {
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (min_x, max_x, min_y, max_y);
+    let mut level_index = 0;
+    for _ in 0..LEVELS {
+        level_index <<= 2;
+        let mid_x = (min_x + max_x) / 2.;
+        let mid_y = (min_y + max_y) / 2.;
+        if point.x < f64::from(mid_x) {
+            max_x = mid_x;
+        } else {
+            min_x = mid_x;
+            level_index |= 1;
+        }
+        if point.y < f64::from(mid_y) {
+            max_y = mid_y;
+        } else {
+            min_y = mid_y;
+            level_index |= 2;
+        }
+    }
+    level_index
+}
+
+fn lax(points: &[Point]) -> Vec<u8> {
+    let min_x = points
+        .iter()
+        .map(|p| p.x)
+        .fold(f64::INFINITY, f64::min)
+        .floor() as f32;
+    let min_y = points
+        .iter()
+        .map(|p| p.y)
+        .fold(f64::INFINITY, f64::min)
+        .floor() as f32;
+    let max_x = points
+        .iter()
+        .map(|p| p.x)
+        .fold(f64::NEG_INFINITY, f64::max)
+        .ceil() as f32;
+    let max_y = points
+        .iter()
+        .map(|p| p.y)
+        .fold(f64::NEG_INFINITY, f64::max)
+        .ceil() as f32;
+    let size = (max_x - min_x).max(max_y - min_y);
+    let (max_x, max_y) = (min_x + size, min_y + size);
+
+    let level_offset: u32 = (0..LEVELS).map(|l| 1 << (2 * l)).sum();
+    let mut cells: BTreeMap<u32, Vec<(u32, u32)>> = BTreeMap::new();
+    for (i, point) in points.iter().enumerate() {
+        let i = i as u32;
+        let cell_index = level_offset + level_index(point, min_x, max_x, min_y, max_y);
+        let intervals = cells.entry(cell_index).or_default();
+        match intervals.last_mut() {
+            Some((_, end)) if *end + 1 == i => *end = i,
+            _ => intervals.push((i, i)),
+        }
+    }
+
+    let mut bytes = Vec::new();
+    bytes.extend(b"LASX");
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend(b"LASS");
+    for n in [0, LEVELS, 0, 0] {
+        bytes.extend(n.to_le_bytes());
+    }
+    for n in [min_x, max_x, min_y, max_y] {
+        bytes.extend(n.to_le_bytes());
+    }
+    bytes.extend(b"LASV");
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend((cells.len() as u32).to_le_bytes());
+    for (cell_index, intervals) in cells {
+        bytes.extend((cell_index as i32).to_le_bytes());
+        bytes.extend((intervals.len() as u32).to_le_bytes());
+        let number_of_points: u32 = intervals.iter().map(|(start, end)| end - start + 1).sum();
+        bytes.extend(number_of_points.to_le_bytes());
+        for (start, end) in intervals {
+            bytes.extend(start.to_le_bytes());
+            bytes.extend(end.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+/// Copies a fixture into its own directory, optionally with a sidecar index.
+fn fixture(name: &str, sidecar: Option<&[u8]>) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("las-rs-index-{name}"));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("autzen.las");
+    let _ = std::fs::copy("tests/data/autzen.las", &path).unwrap();
+    let lax_path = path.with_extension("lax");
+    match sidecar {
+        Some(bytes) => std::fs::write(lax_path, bytes).unwrap(),
+        None => {
+            let _ = std::fs::remove_file(lax_path);
+        }
+    }
+    path
+}
+
+fn queries(header_bounds: Bounds) -> Vec<Bounds> {
+    let (min, max) = (header_bounds.min, header_bounds.max);
+    let at = |fx: f64, fy: f64| Vector {
+        x: min.x + (max.x - min.x) * fx,
+        y: min.y + (max.y - min.y) * fy,
+        z: 0.,
+    };
+    [
+        (0., 0., 1., 1.),
+        (0.1, 0.2, 0.3, 0.4),
+        (0.5, 0.5, 0.55, 0.6),
+        (0.9, 0.9, 1.2, 1.2),
+        (-1., -1., -0.5, -0.5),
+    ]
+    .into_iter()
+    .map(|(x0, y0, x1, y1)| Bounds {
+        min: Vector {
+            z: f64::NEG_INFINITY,
+            ..at(x0, y0)
+        },
+        max: Vector {
+            z: f64::INFINITY,
+            ..at(x1, y1)
+        },
+    })
+    .collect()
+}
+
+#[test]
+fn same_points_with_and_without_index() {
+    let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    assert!(!reader.has_spatial_index());
+    let points = reader.points().collect::<las::Result<Vec<_>>>().unwrap();
+    let path = fixture("sidecar", Some(&lax(&points)));
+
+    let mut indexed = Reader::from_path(&path).unwrap();
+    assert!(indexed.has_spatial_index());
+    let mut partial = false;
+    for bounds in queries(reader.header().bounds()) {
+        let expected = reader.points_in_bounds(bounds).unwrap();
+        assert_eq!(expected, indexed.points_in_bounds(bounds).unwrap());
+        partial |= !expected.is_empty() && expected.len() < points.len();
+    }
+    assert!(partial);
+}
+
+#[test]
+fn with_lax() {
+    let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    let points = reader.points().collect::<las::Result<Vec<_>>>().unwrap();
+    let path = fixture("with-lax", None);
+    let lax_path = path.with_file_name("elsewhere.lax");
+    std::fs::write(&lax_path, lax(&points)).unwrap();
+
+    let reader = Reader::from_path(&path).unwrap();
+    assert!(!reader.has_spatial_index());
+    let mut reader = reader.with_lax(&lax_path);
+    assert!(reader.has_spatial_index());
+    let bounds = queries(reader.header().bounds())[1];
+    let expected: Vec<Point> = points
+        .into_iter()
+        .filter(|p| {
+            bounds.min.x <= p.x && p.x <= bounds.max.x && bounds.min.y <= p.y && p.y <= bounds.max.y
+        })
+        .collect();
+    assert_eq!(expected, reader.points_in_bounds(bounds).unwrap());
+}
+
+#[test]
+fn corrupt_sidecar_falls_back() {
+    let path = fixture("corrupt", Some(b"LASX not really an index"));
+    let mut reader = Reader::from_path(&path).unwrap();
+    assert!(!reader.has_spatial_index());
+    let mut expected = Reader::from_path("tests/data/autzen.las").unwrap();
+    let bounds = queries(reader.header().bounds())[1];
+    assert_eq!(
+        expected.points_in_bounds(bounds).unwrap(),
+        reader.points_in_bounds(bounds).unwrap()
+    );
+}
+
+fn synthetic_points() -> Vec<Point> {
+    (0..10_000)
+        .map(|i| Point {
+            x: f64::from(i % 100),
+            y: f64::from(i / 100),
+            z: f64::from(i % 7),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn synthetic_bounds() -> Bounds {
+    Bounds {
+        min: Vector {
+            x: 0.,
+            y: 0.,
+            z: 0.,
+        },
+        max: Vector {
+            x: 99.,
+            y: 99.,
+            z: 6.,
+        },
+    }
+}
+
+fn sub_bounds() -> Bounds {
+    Bounds {
+        min: Vector {
+            x: 10.,
+            y: 20.,
+            z: f64::NEG_INFINITY,
+        },
+        max: Vector {
+            x: 30.5,
+            y: 25.,
+            z: f64::INFINITY,
+        },
+    }
+}
+
+fn brute_force(points: &[Point], bounds: &Bounds) -> Vec<Point> {
+    points
+        .iter()
+        .filter(|p| {
+            bounds.min.x <= p.x && p.x <= bounds.max.x && bounds.min.y <= p.y && p.y <= bounds.max.y
+        })
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn write_evlr_index() {
+    use las::{index::IndexTarget, writer::WriteOptions, Builder, Writer};
+
+    let points = synthetic_points();
+    let options = WriteOptions::default()
+        .spatial_index(IndexTarget::Evlr)
+        .index_bounds(synthetic_bounds());
+    let header = Builder::from((1, 4)).into_header().unwrap();
+    let mut writer =
+        Writer::with_options(std::io::Cursor::new(Vec::new()), header, options).unwrap();
+    for point in points.iter().cloned() {
+        writer.write_point(point).unwrap();
+    }
+    let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    assert!(reader.has_spatial_index());
+    let expected = brute_force(&points, &sub_bounds());
+    assert!(!expected.is_empty());
+    assert_eq!(expected, reader.points_in_bounds(sub_bounds()).unwrap());
+}
+
+#[test]
+fn write_sidecar_index() {
+    use las::{
+        index::{IndexTarget, Lax},
+        writer::WriteOptions,
+        Writer,
+    };
+
+    let points = synthetic_points();
+    let directory = std::env::temp_dir().join("las-rs-index-write-sidecar");
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("synthetic.las");
+    let lax_path = path.with_extension("lax");
+    let options = WriteOptions::default()
+        .spatial_index(IndexTarget::Sidecar(lax_path.clone()))
+        .index_bounds(synthetic_bounds())
+        .index_cell_size(10.);
+    let file = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+    let mut writer = Writer::with_options(file, Default::default(), options).unwrap();
+    for point in points.iter().cloned() {
+        writer.write_point(point).unwrap();
+    }
+    writer.close().unwrap();
+
+    let lax = Lax::from_path(&lax_path).unwrap();
+    let covered: u64 = lax
+        .intervals(&sub_bounds())
+        .iter()
+        .map(|interval| interval.end - interval.start)
+        .sum();
+    assert!(covered < points.len() as u64);
+
+    let mut reader = Reader::from_path(&path).unwrap();
+    assert!(reader.has_spatial_index());
+    assert_eq!(
+        brute_force(&points, &sub_bounds()),
+        reader.points_in_bounds(sub_bounds()).unwrap()
+    );
+}
+
+#[test]
+fn skip_withheld_in_bounds() {
+    use las::{reader::ReadOptions, Writer};
+
+    let mut points = synthetic_points();
+    for (i, point) in points.iter_mut().enumerate() {
+        point.is_withheld = i % 3 == 0;
+    }
+    let mut writer = Writer::default();
+    for point in points.iter().cloned() {
+        writer.write_point(point).unwrap();
+    }
+    let options = ReadOptions::default().skip_withheld(true);
+    let mut reader = Reader::with_options(writer.into_inner().unwrap(), options).unwrap();
+    let expected: Vec<Point> = brute_force(&points, &sub_bounds())
+        .into_iter()
+        .filter(|point| !point.is_withheld)
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(expected, reader.points_in_bounds(sub_bounds()).unwrap());
+}
+
+#[test]
+fn index_existing_file() {
+    use las::{
+        index::{Indexer, Lax},
+        Writer,
+    };
+
+    let points = synthetic_points();
+    let mut writer = Writer::default();
+    for point in points.iter().cloned() {
+        writer.write_point(point).unwrap();
+    }
+    let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    let _ = reader.read_point().unwrap();
+    let lax = Indexer::index(&mut reader).unwrap();
+    let covered: u64 = lax
+        .intervals(&sub_bounds())
+        .iter()
+        .map(|interval| interval.end - interval.start)
+        .sum();
+    assert!(covered < points.len() as u64);
+
+    let mut bytes = Vec::new();
+    lax.write_to(&mut bytes).unwrap();
+    assert_eq!(lax, Lax::read_from(bytes.as_slice()).unwrap());
+
+    let mut reader = reader.with_spatial_index(lax);
+    assert_eq!(
+        brute_force(&points, &sub_bounds()),
+        reader.points_in_bounds(sub_bounds()).unwrap()
+    );
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn write_par_iter_index() {
+    use las::{index::IndexTarget, writer::WriteOptions, Builder, Writer};
+    use rayon::prelude::*;
+
+    let points = synthetic_points();
+    let options = WriteOptions::default()
+        .spatial_index(IndexTarget::Evlr)
+        .index_bounds(synthetic_bounds());
+    let header = Builder::from((1, 4)).into_header().unwrap();
+    let mut writer =
+        Writer::with_options(std::io::Cursor::new(Vec::new()), header, options).unwrap();
+    writer
+        .write_par_iter(points.clone().into_par_iter())
+        .unwrap();
+    let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    assert!(reader.has_spatial_index());
+    assert_eq!(
+        brute_force(&points, &sub_bounds()),
+        reader.points_in_bounds(sub_bounds()).unwrap()
+    );
+}
+
+#[test]
+fn write_index_errors() {
+    use las::{index::IndexTarget, writer::WriteOptions, Error, Writer};
+
+    let options = WriteOptions::default()
+        .spatial_index(IndexTarget::Evlr)
+        .index_bounds(synthetic_bounds());
+    assert!(matches!(
+        Writer::with_options(
+            std::io::Cursor::new(Vec::new()),
+            Default::default(),
+            options
+        ),
+        Err(Error::UnsupportedFeature { .. })
+    ));
+
+    let options = WriteOptions::default().spatial_index(IndexTarget::Sidecar("unused.lax".into()));
+    assert!(matches!(
+        Writer::with_options(
+            std::io::Cursor::new(Vec::new()),
+            Default::default(),
+            options
+        ),
+        Err(Error::SpatialIndexBoundsRequired)
+    ));
+}
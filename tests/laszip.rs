@@ -1,5 +1,9 @@
+#![cfg(feature = "std")]
+
 extern crate las;
 
+mod common;
+
 use las::Reader;
 
 #[test]
@@ -63,4 +67,744 @@ mod laz_compression_test {
     fn test_extra_bytes_laz() {
         test_compression_does_not_corrupt("tests/data/extrabytes.laz");
     }
+
+    #[test]
+    fn copy_extra_bytes_laz() {
+        let mut reader = las::Reader::from_path("tests/data/extrabytes.laz").unwrap();
+        let points: Vec<las::Point> = reader.points().map(|r| r.unwrap()).collect();
+        let mut writer =
+            las::Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+        for point in &points {
+            writer.write_point(point.clone()).unwrap();
+        }
+        let cursor = writer.into_inner().unwrap();
+        let mut reader = las::Reader::new(cursor).unwrap();
+        let copied: Vec<las::Point> = reader.points().map(|r| r.unwrap()).collect();
+        assert_eq!(points, copied);
+    }
+}
+
+#[cfg(feature = "laz")]
+mod concat {
+    use las::{header::SystemIdentifier, laz::ConcatOptions, Error, Header, Point, Reader};
+    use std::io::Cursor;
+
+    fn reader(bytes: &[u8]) -> Reader<Cursor<Vec<u8>>> {
+        Reader::new(Cursor::new(bytes.to_vec())).unwrap()
+    }
+
+    fn autzen() -> Vec<u8> {
+        std::fs::read("tests/data/autzen.laz").unwrap()
+    }
+
+    /// Writes points with autzen's layout, optionally compressed.
+    fn write(points: &[Point], compressed: bool) -> Vec<u8> {
+        let (mut builder, _) = super::common::autzen();
+        builder.point_format.is_compressed = compressed;
+        super::common::write(builder, points.iter().cloned())
+    }
+
+    fn points(bytes: &[u8]) -> Vec<Point> {
+        reader(bytes).into_points().unwrap()
+    }
+
+    /// Concatenates the inputs and returns the output's bytes and header.
+    fn concat(
+        name: &str,
+        inputs: &[&[u8]],
+        options: ConcatOptions,
+    ) -> las::Result<(Vec<u8>, Header)> {
+        let path = std::env::temp_dir().join(format!("las-rs-concat-{name}.laz"));
+        let result = las::laz::concat_laz_with_options(
+            inputs.iter().map(|bytes| reader(bytes)).collect(),
+            std::fs::File::create(&path).unwrap(),
+            options,
+        );
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        result.map(|header| (bytes, header))
+    }
+
+    fn assert_concatenated(output: &[u8], header: &Header, inputs: &[&[u8]]) {
+        let expected: Vec<Point> = inputs.iter().flat_map(|bytes| points(bytes)).collect();
+        assert_eq!(expected, points(output));
+        let read = reader(output);
+        assert_eq!(header.number_of_points(), read.header().number_of_points());
+        assert_eq!(expected.len() as u64, read.header().number_of_points());
+        assert_eq!(
+            SystemIdentifier::Merge,
+            read.header().system_identifier_kind()
+        );
+        for n in 0..=5 {
+            let expected_count = inputs
+                .iter()
+                .map(|bytes| {
+                    reader(bytes)
+                        .header()
+                        .number_of_points_by_return(n)
+                        .unwrap_or(0)
+                })
+                .sum::<u64>();
+            assert_eq!(
+                expected_count,
+                read.header().number_of_points_by_return(n).unwrap_or(0)
+            );
+        }
+    }
+
+    #[test]
+    fn copies_chunks() {
+        let autzen = autzen();
+        let inputs: &[&[u8]] = &[&autzen, &autzen];
+        let (output, header) = concat("copies-chunks", inputs, ConcatOptions::default()).unwrap();
+        assert_concatenated(&output, &header, inputs);
+        assert_eq!(reader(&autzen).header().bounds(), header.bounds());
+        // The first input's partial chunk means the chunks can't all be the same size.
+        assert!(header.laz_vlr().unwrap().uses_variable_size_chunks());
+        assert!(output.len() < 2 * autzen.len());
+    }
+
+    #[test]
+    fn variable_size_chunks() {
+        let autzen = autzen();
+        let (twice, _) = concat(
+            "variable-twice",
+            &[&autzen, &autzen],
+            ConcatOptions::default(),
+        )
+        .unwrap();
+        let inputs: &[&[u8]] = &[&twice, &autzen, &twice];
+        let (output, header) = concat("variable", inputs, ConcatOptions::default()).unwrap();
+        assert_concatenated(&output, &header, inputs);
+    }
+
+    #[test]
+    fn fixed_size_chunks() {
+        let autzen_points = points(&autzen());
+        let chunk_size = reader(&autzen()).header().laz_vlr().unwrap().chunk_size() as usize;
+        let full: Vec<Point> = autzen_points
+            .iter()
+            .cycle()
+            .take(chunk_size)
+            .cloned()
+            .collect();
+        let full = write(&full, true);
+        let autzen = autzen();
+        let inputs: &[&[u8]] = &[&full, &autzen];
+        let (output, header) = concat("fixed", inputs, ConcatOptions::default()).unwrap();
+        assert_concatenated(&output, &header, inputs);
+        assert!(!header.laz_vlr().unwrap().uses_variable_size_chunks());
+    }
+
+    #[test]
+    fn incompatible() {
+        let autzen = autzen();
+        let uncompressed = write(&points(&autzen), false);
+        let inputs: &[&[u8]] = &[&autzen, &uncompressed];
+        assert!(matches!(
+            concat("incompatible-error", inputs, ConcatOptions::default()).unwrap_err(),
+            Error::LazItemsDoNotMatch(1)
+        ));
+        let (output, header) = concat(
+            "incompatible-recompress",
+            inputs,
+            ConcatOptions::default().recompress_incompatible(),
+        )
+        .unwrap();
+        assert_concatenated(&output, &header, inputs);
+
+        let las = std::fs::read("tests/data/autzen.las").unwrap();
+        assert!(matches!(
+            concat(
+                "incompatible-layout",
+                &[&autzen, &las],
+                ConcatOptions::default()
+            )
+            .unwrap_err(),
+            Error::IncompatibleLayout { index: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn no_inputs() {
+        assert!(matches!(
+            concat("no-inputs", &[], ConcatOptions::default()).unwrap_err(),
+            Error::NoInputs
+        ));
+    }
+}
+
+#[cfg(feature = "laz")]
+mod skip_corrupt_chunks {
+    use las::{reader::ReadOptions, Point, Reader};
+    use std::io::Cursor;
+
+    /// Returns three copies of autzen in three chunks, with a byte flipped in the middle chunk.
+    fn corrupt() -> Vec<u8> {
+        let autzen = std::fs::read("tests/data/autzen.laz").unwrap();
+        let raw_header = las::raw::Header::read_from(&mut Cursor::new(&autzen)).unwrap();
+        let start = u64::from(raw_header.offset_to_point_data) as usize + 8;
+        let offset_to_chunk_table =
+            i64::from_le_bytes(autzen[start - 8..start].try_into().unwrap()) as usize;
+        let chunk_len = offset_to_chunk_table - start;
+
+        let inputs = (0..3)
+            .map(|_| Reader::new(Cursor::new(autzen.clone())).unwrap())
+            .collect();
+        let path = std::env::temp_dir().join("las-rs-skip-corrupt-chunks.laz");
+        let _ = las::concat_laz(inputs, std::fs::File::create(&path).unwrap()).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        bytes[start + chunk_len + chunk_len / 2] ^= 0xFF;
+        bytes
+    }
+
+    fn autzen_points() -> Vec<Point> {
+        Reader::from_path("tests/data/autzen.laz")
+            .unwrap()
+            .into_points()
+            .unwrap()
+    }
+
+    #[test]
+    fn fail_fast_by_default() {
+        assert!(Reader::new(Cursor::new(corrupt()))
+            .unwrap()
+            .into_points()
+            .is_err());
+    }
+
+    #[test]
+    fn skip() {
+        let options = ReadOptions::default().skip_corrupt_chunks(true);
+        let mut reader = Reader::with_options(Cursor::new(corrupt()), options).unwrap();
+        let points: Vec<Point> = reader.points().map(|point| point.unwrap()).collect();
+        let autzen = autzen_points();
+        assert_eq!(2 * autzen.len(), points.len());
+        assert_eq!(autzen[..], points[..autzen.len()]);
+        assert_eq!(autzen[..], points[autzen.len()..]);
+
+        assert!(reader.options().skips_corrupt_chunks());
+        assert!(!reader.summary().is_clean());
+        let skipped = reader.summary().skipped_chunks;
+        assert_eq!(1, skipped.len());
+        assert_eq!(1, skipped[0].index);
+        assert_eq!(autzen.len() as u64, skipped[0].points.start);
+        assert_eq!(2 * autzen.len() as u64, skipped[0].points.end);
+    }
+
+    #[test]
+    fn skip_many() {
+        let options = ReadOptions::default().skip_corrupt_chunks(true);
+        let reader = Reader::with_options(Cursor::new(corrupt()), options).unwrap();
+        let points = reader.into_points().unwrap();
+        let autzen = autzen_points();
+        assert_eq!(autzen[..], points[..autzen.len()]);
+        assert_eq!(autzen[..], points[autzen.len()..]);
+    }
+}
+
+#[cfg(feature = "laz")]
+mod decimation {
+    use las::{
+        reader::{Decimation, ReadOptions},
+        Builder, Point, Reader,
+    };
+    use std::io::Cursor;
+
+    /// Writes compressed points whose gps times are their indices.
+    fn write(n: u64) -> Vec<u8> {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = las::point::Format::new(1).unwrap();
+        builder.point_format.is_compressed = true;
+        let points = (0..n).map(|i| Point {
+            gps_time: Some(i as f64),
+            ..Default::default()
+        });
+        super::common::write(builder, points)
+    }
+
+    fn gps_times(reader: &mut Reader<Cursor<Vec<u8>>>, decimation: Decimation) -> Vec<u64> {
+        reader
+            .points_decimated(decimation)
+            .map(|point| point.unwrap().gps_time.unwrap() as u64)
+            .collect()
+    }
+
+    #[test]
+    fn skips_chunks() {
+        let bytes = write(120_001);
+        let mut reader = Reader::new(Cursor::new(bytes.clone())).unwrap();
+        let chunk_size = u64::from(reader.header().laz_vlr().unwrap().chunk_size());
+        assert!(chunk_size < 60_000);
+        assert_eq!(
+            vec![0, 60_000, 120_000],
+            gps_times(&mut reader, Decimation::EveryNth(60_000))
+        );
+        reader.seek(0).unwrap();
+        let expected: Vec<u64> = (0..120_001).step_by(7).collect();
+        assert_eq!(expected, gps_times(&mut reader, Decimation::EveryNth(7)));
+
+        let options = ReadOptions::default().skip_corrupt_chunks(true);
+        let mut reader = Reader::with_options(Cursor::new(bytes), options).unwrap();
+        assert_eq!(
+            vec![0, 60_000, 120_000],
+            gps_times(&mut reader, Decimation::EveryNth(60_000))
+        );
+    }
+
+    #[test]
+    fn matches_uncompressed() {
+        for decimation in [
+            Decimation::EveryNth(9),
+            Decimation::Fraction {
+                fraction: 0.3,
+                seed: 7,
+            },
+            Decimation::MaxPoints(17),
+        ] {
+            // The fixtures have different formats, so just compare the gps times.
+            let gps_times = |path| {
+                Reader::from_path(path)
+                    .unwrap()
+                    .points_decimated(decimation)
+                    .map(|point| point.unwrap().gps_time)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                gps_times("tests/data/autzen.las"),
+                gps_times("tests/data/autzen.laz")
+            );
+        }
+    }
+
+    #[test]
+    fn read_point_after_read_points() {
+        let mut reader = Reader::new(Cursor::new(write(20))).unwrap();
+        let _ = reader.read_points(10).unwrap();
+        assert_eq!(Some(10.), reader.read_point().unwrap().unwrap().gps_time);
+        assert_eq!(Some(11.), reader.read_point().unwrap().unwrap().gps_time);
+    }
+}
+
+#[cfg(feature = "laz")]
+#[test]
+fn source_id_hint_scans_compressed() {
+    use las::reader::ReadOptions;
+
+    // autzen isn't sorted by source id, but compressed files ignore the hint.
+    let expected: Vec<_> = Reader::from_path("tests/data/autzen.laz")
+        .unwrap()
+        .into_points()
+        .unwrap()
+        .into_iter()
+        .filter(|point| point.point_source_id == 7328)
+        .collect();
+    let options = ReadOptions::default().assume_sorted_by_source_id(true);
+    let file = std::io::BufReader::new(std::fs::File::open("tests/data/autzen.laz").unwrap());
+    let mut reader = Reader::with_options(file, options).unwrap();
+    let points: Vec<_> = reader
+        .points_with_source_id(7328)
+        .map(|point| point.unwrap())
+        .collect();
+    assert!(!points.is_empty());
+    assert_eq!(expected, points);
+}
+
+#[cfg(feature = "laz")]
+mod missing_chunk_table {
+    use las::{point::Format, Builder, GpsTimeType, Point, Reader};
+    use std::io::Cursor;
+
+    /// Writes compressed points in several chunks, whose gps times are their indices.
+    fn write(format: u8, n: u64) -> Vec<u8> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.point_format.is_compressed = true;
+        builder.gps_time_type = GpsTimeType::Standard;
+        let points = (0..n).map(|i| Point {
+            x: i as f64,
+            gps_time: Some(i as f64),
+            ..Default::default()
+        });
+        super::common::write(builder, points)
+    }
+
+    /// Sets the offset to the chunk table to -1, and optionally cuts the table off the end.
+    fn without_chunk_table(mut bytes: Vec<u8>, truncate: bool) -> Vec<u8> {
+        let raw_header = las::raw::Header::read_from(&mut Cursor::new(&bytes)).unwrap();
+        let start = u64::from(raw_header.offset_to_point_data) as usize;
+        let offset_to_chunk_table =
+            i64::from_le_bytes(bytes[start..start + 8].try_into().unwrap()) as usize;
+        if truncate {
+            bytes.truncate(offset_to_chunk_table);
+        }
+        bytes[start..start + 8].copy_from_slice(&(-1i64).to_le_bytes());
+        bytes
+    }
+
+    fn points(bytes: &[u8]) -> Vec<Point> {
+        Reader::new(Cursor::new(bytes.to_vec()))
+            .unwrap()
+            .into_points()
+            .unwrap()
+    }
+
+    fn check(format: u8) {
+        let bytes = write(format, 120_001);
+        assert!(
+            Reader::new(Cursor::new(bytes.clone()))
+                .unwrap()
+                .header()
+                .laz_vlr()
+                .unwrap()
+                .chunk_size()
+                < 60_000
+        );
+        let expected = points(&bytes);
+        for truncate in [false, true] {
+            let patched = without_chunk_table(bytes.clone(), truncate);
+            assert_eq!(expected, points(&patched));
+
+            let mut reader = Reader::new(Cursor::new(patched)).unwrap();
+            assert_eq!(expected[..3], reader.read_points(3).unwrap()[..]);
+            assert!(reader.summary().missing_chunk_table);
+            assert!(!reader.summary().is_clean());
+            for index in [100_000, 7, 60_000, 120_000, 0] {
+                reader.seek(index).unwrap();
+                assert_eq!(
+                    expected[index as usize],
+                    reader.read_point().unwrap().unwrap()
+                );
+            }
+        }
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        let _ = reader.read_point().unwrap();
+        assert!(!reader.summary().missing_chunk_table);
+    }
+
+    #[test]
+    fn pointwise() {
+        check(1);
+    }
+
+    #[test]
+    fn layered() {
+        check(6);
+    }
+
+    #[test]
+    fn variable_size_pointwise_chunks() {
+        let autzen = std::fs::read("tests/data/autzen.laz").unwrap();
+        let inputs = (0..2)
+            .map(|_| Reader::new(Cursor::new(autzen.clone())).unwrap())
+            .collect();
+        let path = std::env::temp_dir().join("las-rs-missing-chunk-table.laz");
+        let _ = las::concat_laz(inputs, std::fs::File::create(&path).unwrap()).unwrap();
+        let bytes = without_chunk_table(std::fs::read(&path).unwrap(), false);
+        std::fs::remove_file(&path).unwrap();
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        assert!(reader.read_point().is_err());
+    }
+}
+
+#[cfg(feature = "laz")]
+mod invalid_chunk_table {
+    use las::{Error, Reader, Writer};
+    use std::io::Cursor;
+
+    fn autzen() -> (Vec<u8>, usize, usize) {
+        let bytes = std::fs::read("tests/data/autzen.laz").unwrap();
+        let raw_header = las::raw::Header::read_from(&mut Cursor::new(&bytes)).unwrap();
+        let start = u64::from(raw_header.offset_to_point_data) as usize;
+        let offset_to_chunk_table =
+            i64::from_le_bytes(bytes[start..start + 8].try_into().unwrap()) as usize;
+        (bytes, start, offset_to_chunk_table)
+    }
+
+    fn check(bytes: &[u8]) {
+        let mut reader = Reader::new(Cursor::new(bytes.to_vec())).unwrap();
+        assert!(reader.read_chunk_at(0).is_err());
+        assert!(matches!(
+            Writer::append_from(Cursor::new(bytes.to_vec())).err(),
+            Some(Error::InvalidChunkTable(_))
+        ));
+    }
+
+    /// The points are still read in order, without the chunk table.
+    fn check_points(bytes: &[u8]) {
+        let expected = Reader::from_path("tests/data/autzen.laz")
+            .unwrap()
+            .into_points()
+            .unwrap();
+        let reader = Reader::new(Cursor::new(bytes.to_vec())).unwrap();
+        assert_eq!(expected, reader.into_points().unwrap());
+    }
+
+    #[test]
+    fn too_many_chunks() {
+        let (mut bytes, _, offset_to_chunk_table) = autzen();
+        let number_of_chunks = offset_to_chunk_table + 4;
+        bytes[number_of_chunks..number_of_chunks + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        check(&bytes);
+        check_points(&bytes);
+    }
+
+    #[test]
+    fn chunks_past_the_chunk_table() {
+        let (bytes, start, offset_to_chunk_table) = autzen();
+        let cut = start + 108;
+        let mut patched = bytes[..cut].to_vec();
+        patched.extend_from_slice(&bytes[offset_to_chunk_table..]);
+        patched[start..start + 8].copy_from_slice(&(cut as i64).to_le_bytes());
+        check(&patched);
+    }
+
+    #[test]
+    fn offset_past_the_end() {
+        let (mut bytes, start, _) = autzen();
+        bytes[start..start + 8].copy_from_slice(&i64::MAX.to_le_bytes());
+        check(&bytes);
+        check_points(&bytes);
+    }
+}
+
+#[cfg(feature = "laz")]
+mod read_chunk_at {
+    use las::{Error, Reader};
+    use std::io::Cursor;
+
+    fn reader(bytes: &[u8]) -> Reader<Cursor<Vec<u8>>> {
+        Reader::new(Cursor::new(bytes.to_vec())).unwrap()
+    }
+
+    /// Writes autzen's points over and over, in chunks of 1000.
+    fn fixed() -> Vec<u8> {
+        let (builder, points) = super::common::autzen();
+        super::common::write_chunked(builder, 1000, points.into_iter().cycle().take(2500))
+    }
+
+    fn check(bytes: &[u8], sizes: &[usize]) {
+        let expected = reader(bytes).into_points().unwrap();
+        let mut reader = reader(bytes);
+        assert_eq!(sizes.len(), reader.number_of_chunks().unwrap());
+        assert_eq!(expected[..10], reader.read_points(10).unwrap()[..]);
+        // Backwards, to show that the chunks don't depend on each other or on the cursor.
+        for i in (0..sizes.len()).rev() {
+            let start: usize = sizes[..i].iter().sum();
+            let chunk = reader.read_chunk_at(i).unwrap();
+            assert_eq!(expected[start..start + sizes[i]], chunk[..]);
+        }
+        assert_eq!(expected[10..20], reader.read_points(10).unwrap()[..]);
+        assert!(matches!(
+            reader.read_chunk_at(sizes.len()).unwrap_err(),
+            Error::ChunkOutOfRange { index, chunks } if index == sizes.len() && chunks == sizes.len()
+        ));
+    }
+
+    #[test]
+    fn fixed_size_chunks() {
+        let bytes = fixed();
+        assert!(!reader(&bytes)
+            .header()
+            .laz_vlr()
+            .unwrap()
+            .uses_variable_size_chunks());
+        check(&bytes, &[1000, 1000, 500]);
+    }
+
+    #[test]
+    fn variable_size_chunks() {
+        let path = std::env::temp_dir().join("las-rs-read-chunk-at.laz");
+        let autzen = std::fs::read("tests/data/autzen.laz").unwrap();
+        let inputs = vec![reader(&autzen), reader(&fixed())];
+        let header = las::laz::concat_laz(inputs, std::fs::File::create(&path).unwrap()).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(header.laz_vlr().unwrap().uses_variable_size_chunks());
+        check(&bytes, &[106, 1000, 1000, 500]);
+    }
+}
+
+#[cfg(feature = "laz")]
+mod borrowed {
+    use las::{point::Format, GpsTimeType, Reader};
+    use std::io::Cursor;
+
+    /// Reads and seeks through a file in memory without copying it, from a borrowed slice.
+    fn check(bytes: &[u8]) {
+        let expected = Reader::new(Cursor::new(bytes.to_vec()))
+            .unwrap()
+            .into_points()
+            .unwrap();
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(expected, reader.read_points(2500).unwrap());
+        for index in [1500, 5, 2499, 1000] {
+            reader.seek(index).unwrap();
+            assert_eq!(
+                expected[usize::try_from(index).unwrap()],
+                reader.read_point().unwrap().unwrap()
+            );
+        }
+        reader.seek(2500).unwrap();
+        assert!(reader.read_point().unwrap().is_none());
+    }
+
+    fn points() -> (las::Builder, impl Iterator<Item = las::Point>) {
+        let (builder, points) = super::common::autzen();
+        (builder, points.into_iter().cycle().take(2500))
+    }
+
+    #[test]
+    fn fixed_size_chunks() {
+        let (builder, points) = points();
+        check(&super::common::write_chunked(builder, 1000, points));
+    }
+
+    #[test]
+    fn variable_size_chunks() {
+        let (builder, points) = points();
+        check(&super::common::write_variable_chunks(
+            builder,
+            &[300, 0, 1200, 1000],
+            points,
+        ));
+    }
+
+    #[test]
+    fn layered_chunks() {
+        let (mut builder, points) = points();
+        builder.version = (1, 4).into();
+        builder.point_format = Format::new(7).unwrap();
+        builder.point_format.is_compressed = true;
+        builder.gps_time_type = GpsTimeType::Standard;
+        check(&super::common::write_chunked(builder, 1000, points));
+    }
+}
+
+#[cfg(feature = "laz")]
+mod compatibility_mode {
+    use las::{
+        point::{Classification, Format},
+        raw::{self, point::ScanAngle},
+        writer::WriteOptions,
+        Builder, Color, Error, GpsTimeType, Point, Reader, Result, Version, Writer,
+    };
+    use std::io::Cursor;
+
+    fn builder(format: u8) -> Builder {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.point_format.is_compressed = true;
+        builder.point_format.extra_bytes = 1;
+        builder.gps_time_type = GpsTimeType::Standard;
+        builder
+    }
+
+    fn points(format: u8) -> Vec<Point> {
+        let format = Format::new(format).unwrap();
+        (0..15)
+            .map(|i| Point {
+                x: f64::from(i),
+                return_number: i + 1,
+                number_of_returns: 15,
+                classification: Classification::new(if i % 2 == 0 { 40 } else { 6 }).unwrap(),
+                is_overlap: i % 3 == 0,
+                scanner_channel: i % 4,
+                scan_angle: f32::from(ScanAngle::from(-120. + 17.3 * f32::from(i))),
+                gps_time: Some(f64::from(i)),
+                color: format.has_color.then(|| Color::new(1, 2, 3)),
+                nir: format.has_nir.then_some(u16::from(i) * 1000),
+                extra_bytes: vec![i].into(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn write(format: u8) -> Vec<u8> {
+        let options = WriteOptions::default().compatibility_mode();
+        super::common::write_with_options(builder(format), options, points(format))
+    }
+
+    fn check(format: u8, legacy_format: u8) {
+        let bytes = write(format);
+        let raw_header = raw::Header::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(Version::new(1, 2), raw_header.version);
+        assert_eq!(
+            legacy_format,
+            raw_header.point_data_record_format & 0b0011_1111
+        );
+
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        let header = reader.header().clone();
+        assert_eq!(Version::new(1, 4), header.version());
+        assert_eq!(
+            Format::new(format).unwrap().len() + 1,
+            header.point_format().len()
+        );
+        assert!(header.point_format().is_compressed);
+        assert_eq!(Some(1), header.number_of_points_by_return(15));
+        assert_eq!(1, header.extra_bytes_schema().unwrap().len());
+        assert!(!header
+            .vlrs()
+            .iter()
+            .any(|vlr| vlr.user_id == "lascompatible"));
+        assert_eq!(
+            points(format),
+            reader.points().collect::<Result<Vec<_>>>().unwrap()
+        );
+
+        reader.seek(3).unwrap();
+        let record = reader.raw_records().next().unwrap().unwrap();
+        let point = Point::new(
+            raw::Point::read_from(record.as_slice(), header.point_format()).unwrap(),
+            header.transforms(),
+        );
+        assert_eq!(points(format)[3], point);
+    }
+
+    #[test]
+    fn format_6() {
+        check(6, 1);
+    }
+
+    #[test]
+    fn format_8() {
+        check(8, 3);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn write_par_iter() {
+        use rayon::prelude::*;
+
+        let options = WriteOptions::default().compatibility_mode();
+        let mut writer = Writer::with_options(
+            Cursor::new(Vec::new()),
+            builder(8).into_header().unwrap(),
+            options,
+        )
+        .unwrap();
+        writer.write_par_iter(points(8).into_par_iter()).unwrap();
+        let bytes = writer.into_inner().unwrap().into_inner();
+        assert_eq!(write(8), bytes);
+    }
+
+    #[test]
+    fn needs_extended_compressed_format() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        builder.gps_time_type = GpsTimeType::Standard;
+        assert!(matches!(
+            Writer::with_options(
+                Cursor::new(Vec::new()),
+                builder.into_header().unwrap(),
+                WriteOptions::default().compatibility_mode(),
+            ),
+            Err(Error::InvalidWriteOption {
+                option: "compatibility_mode",
+                ..
+            })
+        ));
+    }
 }
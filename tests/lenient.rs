@@ -0,0 +1,122 @@
+//! Lenient reading of files with malformed headers.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+mod common;
+
+use las::{
+    reader::{ReadOptions, Warning},
+    Builder, Point, Reader, Writer,
+};
+use std::io::Cursor;
+
+/// Where the system identifier starts in the raw header.
+const SYSTEM_IDENTIFIER: usize = 26;
+
+/// Where the creation day of year starts, with the year after it.
+const CREATION_DATE: usize = 90;
+
+/// Where the x scale factor starts.
+const X_SCALE_FACTOR: usize = 131;
+
+/// Writes three las 1.2 points.
+fn fixture() -> Vec<u8> {
+    let points = (0..3).map(|i| Point {
+        x: f64::from(i),
+        ..Default::default()
+    });
+    common::write(Builder::from((1, 2)), points)
+}
+
+fn read(bytes: Vec<u8>, lenient: bool) -> las::Result<Reader<Cursor<Vec<u8>>>> {
+    let options = ReadOptions::default().lenient(lenient);
+    Reader::with_options(Cursor::new(bytes), options)
+}
+
+#[test]
+fn clean() {
+    let reader = read(fixture(), true).unwrap();
+    assert!(reader.warnings().is_empty());
+    assert!(reader.summary().is_clean());
+}
+
+#[test]
+fn not_ascii() {
+    let mut bytes = fixture();
+    bytes[SYSTEM_IDENTIFIER..SYSTEM_IDENTIFIER + 3].copy_from_slice("Zü".as_bytes());
+    bytes[SYSTEM_IDENTIFIER + 3] = 0;
+    let reader = read(bytes.clone(), false).unwrap();
+    assert_eq!("Zü", reader.header().system_identifier());
+    assert!(reader.warnings().is_empty());
+
+    let reader = read(bytes, true).unwrap();
+    assert_eq!("Z??", reader.header().system_identifier());
+    assert_eq!(
+        [Warning::NotAscii {
+            field: "system_identifier",
+            original: "Zü".to_string(),
+        }],
+        reader.warnings()
+    );
+    assert!(!reader.summary().is_clean());
+    let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+    let _ = writer.close().unwrap();
+}
+
+#[test]
+fn zero_scale() {
+    let mut bytes = fixture();
+    bytes[X_SCALE_FACTOR..X_SCALE_FACTOR + 8].copy_from_slice(&0f64.to_le_bytes());
+    let mut reader = read(bytes.clone(), false).unwrap();
+    assert_eq!(0., reader.header().transforms().x.scale);
+    assert_eq!(0., reader.read_points(3).unwrap()[2].x);
+
+    let mut reader = read(bytes, true).unwrap();
+    assert_eq!(0.001, reader.header().transforms().x.scale);
+    assert_eq!(2., reader.read_points(3).unwrap()[2].x);
+    assert_eq!(
+        [Warning::ZeroScale {
+            axis: 'x',
+            scale: 0.001
+        }],
+        reader.warnings()
+    );
+}
+
+#[test]
+fn too_many_points() {
+    let mut bytes = fixture();
+    let _ = bytes.split_off(bytes.len() - 10);
+    let mut reader = read(bytes.clone(), false).unwrap();
+    assert_eq!(3, reader.header().number_of_points());
+    assert!(reader.read_points(3).is_err());
+
+    let mut reader = read(bytes, true).unwrap();
+    assert_eq!(2, reader.header().number_of_points());
+    assert_eq!(2, reader.read_points(3).unwrap().len());
+    assert_eq!(
+        [Warning::TooManyPoints {
+            declared: 3,
+            fit: 2
+        }],
+        reader.warnings()
+    );
+}
+
+#[test]
+fn invalid_date() {
+    let mut bytes = fixture();
+    bytes[CREATION_DATE..CREATION_DATE + 2].copy_from_slice(&400u16.to_le_bytes());
+    bytes[CREATION_DATE + 2..CREATION_DATE + 4].copy_from_slice(&2020u16.to_le_bytes());
+    let reader = read(bytes, true).unwrap();
+    assert_eq!(None, reader.header().date());
+    assert_eq!(
+        [Warning::InvalidDate {
+            year: 2020,
+            day_of_year: 400
+        }],
+        reader.warnings()
+    );
+}
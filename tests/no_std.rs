@@ -0,0 +1,136 @@
+//! Slice round trips through the byte level layer, which also builds without std:
+//!
+//! ```text
+//! cargo test --no-default-features --test no_std
+//! ```
+
+extern crate las;
+
+use las::{
+    point::Format,
+    raw::{self, point::Waveform, Header, Point, Vlr},
+    Bounds, Transform, Vector,
+};
+
+fn header() -> Header {
+    let mut header = Header {
+        version: las::Version::new(1, 4),
+        header_size: 380,
+        x_scale_factor: 0.01,
+        y_scale_factor: 0.02,
+        z_scale_factor: 0.03,
+        x_offset: 1.,
+        y_offset: 2.,
+        z_offset: 3.,
+        min_x: -1.,
+        max_x: 1.,
+        min_y: -2.,
+        max_y: 2.,
+        min_z: -3.,
+        max_z: 3.,
+        large_file: Some(Default::default()),
+        padding: vec![42; 5],
+        ..Default::default()
+    };
+    raw::write_las_str(&mut header.system_identifier, "no_std").unwrap();
+    header
+}
+
+#[test]
+fn header_roundtrip() {
+    let header = header();
+    let bytes = header.to_bytes().unwrap();
+    assert_eq!(380, bytes.len());
+    let other = Header::from_bytes(&bytes).unwrap();
+    assert_eq!(header, other);
+    assert!(Header::from_bytes(&bytes[..379]).is_err());
+}
+
+#[test]
+fn header_transforms_and_bounds() {
+    let header = Header::from_bytes(&header().to_bytes().unwrap()).unwrap();
+    assert_eq!(
+        Vector {
+            x: Transform {
+                scale: 0.01,
+                offset: 1.
+            },
+            y: Transform {
+                scale: 0.02,
+                offset: 2.
+            },
+            z: Transform {
+                scale: 0.03,
+                offset: 3.
+            },
+        },
+        header.transforms()
+    );
+    assert_eq!(
+        Bounds {
+            min: Vector {
+                x: -1.,
+                y: -2.,
+                z: -3.
+            },
+            max: Vector {
+                x: 1.,
+                y: 2.,
+                z: 3.
+            },
+        },
+        header.bounds()
+    );
+    assert_eq!(250, header.transforms().x.inverse(3.5).unwrap());
+}
+
+#[test]
+fn header_strings() {
+    let header = Header::from_bytes(&header().to_bytes().unwrap()).unwrap();
+    assert_eq!("no_std", raw::las_str(&header.system_identifier).unwrap());
+    assert_eq!("", raw::las_str(&header.generating_software).unwrap());
+    let mut bytes = [0; 4];
+    assert!(raw::write_las_str(&mut bytes, "Bière").is_err());
+}
+
+#[test]
+fn point_roundtrip() {
+    let mut format = Format::new(10).unwrap();
+    format.extra_bytes = 3;
+    let point = Point {
+        x: 1,
+        y: -2,
+        z: 3,
+        intensity: 4,
+        gps_time: Some(5.),
+        color: Some(las::Color::new(6, 7, 8)),
+        nir: Some(9),
+        waveform: Some(Waveform {
+            byte_offset_to_waveform_data: 10,
+            ..Default::default()
+        }),
+        extra_bytes: vec![11, 12, 13].into(),
+        flags: raw::point::Flags::ThreeByte(0, 0, 2),
+        scan_angle: raw::point::ScanAngle::Scaled(14),
+        ..Default::default()
+    };
+    let bytes = point.to_bytes(&format).unwrap();
+    assert_eq!(usize::from(format.len()), bytes.len());
+    assert_eq!(point, Point::from_bytes(&bytes, &format).unwrap());
+    assert!(Point::from_bytes(&bytes[..bytes.len() - 1], &format).is_err());
+}
+
+#[test]
+fn vlr_roundtrip() {
+    let mut vlr = Vlr {
+        record_id: 1,
+        record_length_after_header: raw::vlr::RecordLength::Evlr(3),
+        data: vec![1, 2, 3],
+        ..Default::default()
+    };
+    raw::write_las_str(&mut vlr.user_id, "no_std").unwrap();
+    let bytes = vlr.to_bytes().unwrap();
+    assert_eq!(63, bytes.len());
+    assert_eq!(vlr, Vlr::from_bytes(&bytes, true).unwrap());
+    assert!(Vlr::from_bytes(&bytes[..62], true).is_err());
+}
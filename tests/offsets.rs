@@ -0,0 +1,121 @@
+//! Read errors report where in the source they happened.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+use las::{Error, Reader};
+use std::io::Cursor;
+
+/// autzen.las is las 1.2 with format 1, vlrs from byte 227, and points from byte 1994.
+const START_OF_POINTS: u64 = 1994;
+const POINT_LEN: u64 = 28;
+
+fn autzen() -> Vec<u8> {
+    std::fs::read("tests/data/autzen.las").unwrap()
+}
+
+fn open(bytes: Vec<u8>) -> Error {
+    match Reader::new(Cursor::new(bytes)) {
+        Ok(_) => panic!("the reader should fail to open"),
+        Err(err) => err,
+    }
+}
+
+/// Asserts that the error happened within a few bytes after `offset`.
+fn assert_near(offset: u64, err: &Error) {
+    let reported = err.offset().unwrap_or_else(|| panic!("no offset: {err}"));
+    assert!(
+        (offset..=offset + 4).contains(&reported),
+        "expected about {offset}, got {reported}: {err}"
+    );
+}
+
+#[test]
+fn file_signature() {
+    let mut bytes = autzen();
+    bytes[1] = b'X';
+    let err = open(bytes);
+    assert_near(0, &err);
+    assert!(matches!(
+        err,
+        Error::ReadFailed { source, .. } if matches!(*source, Error::InvalidFileSignature(_))
+    ));
+}
+
+#[test]
+fn truncated_header() {
+    let mut bytes = autzen();
+    bytes.truncate(150);
+    assert_near(150, &open(bytes));
+}
+
+#[test]
+fn point_format() {
+    let mut bytes = autzen();
+    bytes[104] = 42;
+    assert_near(104, &open(bytes));
+}
+
+#[test]
+fn truncated_vlr() {
+    let mut bytes = autzen();
+    let offset = 227 + 54 + 10;
+    bytes.truncate(offset);
+    assert_near(offset as u64, &open(bytes));
+}
+
+#[test]
+fn truncated_vlr_header() {
+    let mut bytes = autzen();
+    bytes.truncate(240);
+    assert_near(240, &open(bytes));
+}
+
+#[test]
+fn truncated_point() {
+    let mut bytes = autzen();
+    let offset = START_OF_POINTS + 50 * POINT_LEN + 10;
+    bytes.truncate(offset as usize);
+    let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+    let err = reader
+        .points()
+        .find_map(|point| point.err())
+        .expect("reading should fail");
+    match &err {
+        Error::PointReadFailed { index, offset, .. } => {
+            assert_eq!(50, *index);
+            assert_eq!(Some(START_OF_POINTS + 50 * POINT_LEN), *offset);
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+    let reported = err.offset().unwrap();
+    assert!(offset - reported < POINT_LEN);
+}
+
+#[test]
+fn truncated_points_read_in_bulk() {
+    let mut bytes = autzen();
+    bytes.truncate((START_OF_POINTS + 70 * POINT_LEN + 1) as usize);
+    let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+    assert!(matches!(
+        reader.read_points(100).unwrap_err(),
+        Error::PointReadFailed { index: 70, .. }
+    ));
+}
+
+#[cfg(feature = "laz")]
+#[test]
+fn truncated_compressed_points() {
+    let mut bytes = std::fs::read("tests/data/autzen.laz").unwrap();
+    let start = u32::from_le_bytes(bytes[96..100].try_into().unwrap()) as usize;
+    // Drop the chunk table offset and some compressed bytes, so decompression runs dry.
+    bytes.truncate(start + 200);
+    bytes[start..start + 8].copy_from_slice(&(-1i64).to_le_bytes());
+    let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+    match reader.points().find_map(|point| point.err()) {
+        Some(Error::PointReadFailed { offset, .. }) => assert_eq!(None, offset),
+        Some(err) => panic!("unexpected error: {err}"),
+        None => panic!("reading should fail"),
+    }
+}
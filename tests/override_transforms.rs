@@ -0,0 +1,124 @@
+//! Reading files with transforms that override the header's.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+use las::{reader::ReadOptions, Bounds, Reader, Statistics, Transform, Vector, Writer};
+use std::{fs::File, io::BufReader};
+
+fn reader(path: &str, transforms: Vector<Option<Transform>>) -> Reader<BufReader<File>> {
+    let options = ReadOptions::default().override_transforms(transforms);
+    Reader::with_options(BufReader::new(File::open(path).unwrap()), options).unwrap()
+}
+
+fn assert_bounds_near(a: Bounds, b: Bounds) {
+    for (a, b) in [
+        (a.min.x, b.min.x),
+        (a.min.y, b.min.y),
+        (a.min.z, b.min.z),
+        (a.max.x, b.max.x),
+        (a.max.y, b.max.y),
+        (a.max.z, b.max.z),
+    ] {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+}
+
+fn check_offset(path: &str) {
+    let original = Reader::from_path(path).unwrap();
+    let transforms = *original.header().transforms();
+    let z = Transform {
+        offset: transforms.z.offset + 30.,
+        ..transforms.z
+    };
+    let mut shifted = reader(
+        path,
+        Vector {
+            x: None,
+            y: None,
+            z: Some(z),
+        },
+    );
+    assert_eq!(transforms, *shifted.header().transforms());
+    assert_eq!(
+        Vector { z, ..transforms },
+        *shifted.header().effective_transforms()
+    );
+
+    let mut statistics = Statistics::new();
+    let points = original.into_points().unwrap();
+    for (a, b) in points.iter().zip(shifted.points()) {
+        let b = b.unwrap();
+        assert_eq!((a.x, a.y), (b.x, b.y));
+        assert!((a.z + 30. - b.z).abs() < 1e-6);
+        statistics.add_point(&b);
+    }
+    assert_eq!(points.len() as u64, statistics.number_of_points());
+    assert_bounds_near(statistics.bounds(), shifted.header().bounds());
+}
+
+#[test]
+fn las_offset() {
+    check_offset("tests/data/autzen.las");
+}
+
+#[cfg(feature = "laz")]
+#[test]
+fn laz_offset() {
+    check_offset("tests/data/autzen.laz");
+}
+
+#[test]
+fn scale() {
+    let path = "tests/data/autzen.las";
+    let original = Reader::from_path(path).unwrap();
+    let x = original.header().transforms().x;
+    let doubled = Transform {
+        scale: x.scale * 2.,
+        ..x
+    };
+    let mut reader = reader(
+        path,
+        Vector {
+            x: Some(doubled),
+            y: None,
+            z: None,
+        },
+    );
+    let mut statistics = Statistics::new();
+    let points = original.into_points().unwrap();
+    for (a, b) in points.iter().zip(reader.points()) {
+        let b = b.unwrap();
+        assert!(((a.x - x.offset) * 2. + x.offset - b.x).abs() < 1e-6);
+        statistics.add_point(&b);
+    }
+    assert_bounds_near(statistics.bounds(), reader.header().bounds());
+}
+
+#[test]
+fn written_with_effective_transforms() {
+    let z = Transform {
+        scale: 0.01,
+        offset: 100.,
+    };
+    let mut reader = reader(
+        "tests/data/autzen.las",
+        Vector {
+            x: None,
+            y: None,
+            z: Some(z),
+        },
+    );
+    let points = reader.points().collect::<las::Result<Vec<_>>>().unwrap();
+    let mut writer =
+        Writer::new(std::io::Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+    for point in &points {
+        writer.write_point(point.clone()).unwrap();
+    }
+    let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(z, reader.header().transforms().z);
+    for (a, b) in points.iter().zip(reader.into_points().unwrap()) {
+        assert!((a.z - b.z).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,78 @@
+//! Reading points with laz chunks decompressed on the rayon thread pool.
+
+#![cfg(feature = "parallel")]
+
+extern crate las;
+
+mod common;
+
+use las::{reader::ReadOptions, Reader};
+use std::io::Cursor;
+
+fn check(bytes: Vec<u8>, options: ReadOptions) {
+    let expected = Reader::with_options(Cursor::new(bytes.clone()), options)
+        .unwrap()
+        .into_points()
+        .unwrap();
+    let mut reader = Reader::with_options(Cursor::new(bytes), options).unwrap();
+    let _ = reader.read_points(3).unwrap();
+    let points = reader
+        .points_par()
+        .collect::<las::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(expected, points);
+    assert_eq!(expected[3], reader.read_point().unwrap().unwrap());
+}
+
+#[test]
+fn las() {
+    check(
+        std::fs::read("tests/data/autzen.las").unwrap(),
+        ReadOptions::default(),
+    );
+}
+
+#[cfg(feature = "laz")]
+mod laz {
+    use super::*;
+    use las::Point;
+
+    /// Writes autzen's points over and over, in chunks of 100, with every seventh one withheld.
+    fn chunked() -> Vec<u8> {
+        let (builder, points) = common::autzen();
+        let points = points
+            .into_iter()
+            .cycle()
+            .take(2550)
+            .enumerate()
+            .map(|(i, point)| Point {
+                is_withheld: i % 7 == 0,
+                ..point
+            });
+        common::write_chunked(builder, 100, points)
+    }
+
+    #[test]
+    fn chunks() {
+        check(chunked(), ReadOptions::default());
+    }
+
+    #[test]
+    fn skip_withheld() {
+        let options = ReadOptions::default().skip_withheld(true);
+        check(chunked(), options);
+        let mut reader = Reader::with_options(Cursor::new(chunked()), options).unwrap();
+        assert_eq!(2550 - 365, reader.points_par().count());
+    }
+
+    #[test]
+    fn corrupt_chunk() {
+        let mut bytes = chunked();
+        let len = bytes.len();
+        bytes[len / 2..len / 2 + 64].fill(0xff);
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        let mut points = reader.points_par();
+        assert!(points.any(|point| point.is_err()));
+        assert!(points.next().is_none());
+    }
+}
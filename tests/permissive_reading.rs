@@ -1,3 +1,5 @@
+#![cfg(feature = "std")]
+
 #[test]
 #[cfg(feature = "laz")]
 fn read_invalid_file() {
@@ -5,3 +7,209 @@ fn read_invalid_file() {
     let mut reader = las::Reader::from_path("tests/data/32-1-472-150-76.laz").unwrap();
     let _ = reader.points().next().unwrap().unwrap();
 }
+
+mod las_1_0 {
+    use las::{Builder, GpsTimeType, Point, Reader, Version, Writer};
+    use std::io::Cursor;
+
+    const OFFSET_TO_POINT_DATA: usize = 96;
+
+    fn points() -> Vec<Point> {
+        (0..10)
+            .map(|i| Point {
+                x: f64::from(i),
+                y: f64::from(i * 2),
+                z: f64::from(i * 3),
+                intensity: i as u16,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// A las 1.0 file with the point data start signature before the offset to point data.
+    fn with_signature() -> Vec<u8> {
+        let header = Builder::from((1, 0)).into_header().unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        for point in points() {
+            writer.write_point(point).unwrap();
+        }
+        writer.into_inner().unwrap().into_inner()
+    }
+
+    fn offset_to_point_data(bytes: &[u8]) -> u32 {
+        u32::from_le_bytes(
+            bytes[OFFSET_TO_POINT_DATA..OFFSET_TO_POINT_DATA + 4]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    fn set_offset_to_point_data(bytes: &mut [u8], offset: u32) {
+        bytes[OFFSET_TO_POINT_DATA..OFFSET_TO_POINT_DATA + 4]
+            .copy_from_slice(&offset.to_le_bytes());
+    }
+
+    fn read(bytes: Vec<u8>) -> Reader<Cursor<Vec<u8>>> {
+        Reader::new(Cursor::new(bytes)).unwrap()
+    }
+
+    #[test]
+    fn point_data_start_signature() {
+        let bytes = with_signature();
+        let offset = offset_to_point_data(&bytes);
+        let start = offset as usize;
+        assert_eq!([0xCC, 0xDD], bytes[start - 2..start]);
+
+        let mut without_signature = bytes.clone();
+        let _ = without_signature.drain(start - 2..start);
+        set_offset_to_point_data(&mut without_signature, offset - 2);
+
+        let mut counted_as_points = bytes.clone();
+        set_offset_to_point_data(&mut counted_as_points, offset - 2);
+
+        for bytes in [bytes, without_signature, counted_as_points] {
+            let reader = read(bytes);
+            assert_eq!(10, reader.header().number_of_points());
+            assert_eq!(points(), reader.into_points().unwrap());
+        }
+    }
+
+    #[test]
+    fn seek_after_signature_counted_as_points() {
+        let mut bytes = with_signature();
+        let offset = offset_to_point_data(&bytes);
+        set_offset_to_point_data(&mut bytes, offset - 2);
+        let mut reader = read(bytes);
+        reader.seek(7).unwrap();
+        assert_eq!(points()[7], reader.read_point().unwrap().unwrap());
+    }
+
+    #[test]
+    fn reserved() {
+        let mut bytes = with_signature();
+        bytes[4..6].copy_from_slice(&4242u16.to_le_bytes());
+        let reader = read(bytes);
+        assert_eq!(4242, reader.header().reserved());
+        assert_eq!(0, reader.header().file_source_id());
+
+        let header = reader.header().clone();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        for point in reader.into_points().unwrap() {
+            writer.write_point(point).unwrap();
+        }
+        let bytes = writer.into_inner().unwrap().into_inner();
+        assert_eq!(4242u16.to_le_bytes(), bytes[4..6]);
+        assert_eq!(4242, read(bytes).header().reserved());
+    }
+
+    #[test]
+    fn reserved_global_encoding() {
+        for minor in [0, 1] {
+            let header = Builder::from((1, minor)).into_header().unwrap();
+            let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+            for point in points() {
+                writer.write_point(point).unwrap();
+            }
+            let mut bytes = writer.into_inner().unwrap().into_inner();
+            bytes[6..8].copy_from_slice(&0b1_1111u16.to_le_bytes());
+            let reader = read(bytes);
+            let header = reader.header();
+            assert_eq!(Version::new(1, minor), header.version());
+            assert_eq!(GpsTimeType::Week, header.gps_time_type());
+            assert!(!header.has_wkt_crs());
+            assert!(!header.has_synthetic_return_numbers());
+            assert_eq!(0, header.clone().into_raw().unwrap().global_encoding);
+            assert_eq!(points(), reader.into_points().unwrap());
+        }
+    }
+}
+
+mod return_counts {
+    use las::{point::Format, raw, Builder, Error, GpsTimeType, Header, Point, Reader, Writer};
+    use std::io::Cursor;
+
+    const LEGACY: usize = 111;
+    const EXTENDED: usize = 255;
+
+    /// A las 1.4 file with six first returns and four second returns.
+    fn fixture(format: u8) -> Vec<u8> {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(format).unwrap();
+        builder.gps_time_type = GpsTimeType::Standard;
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        for i in 0..10 {
+            writer
+                .write_point(Point {
+                    return_number: if i < 6 { 1 } else { 2 },
+                    number_of_returns: 2,
+                    gps_time: Some(f64::from(i)),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        writer.into_inner().unwrap().into_inner()
+    }
+
+    fn raw_header(bytes: &[u8]) -> raw::Header {
+        raw::Header::read_from(Cursor::new(bytes)).unwrap()
+    }
+
+    #[test]
+    fn conflict() {
+        for (format, patch_extended, second_returns, used_extended) in [
+            (1, true, 4, false),
+            (1, false, 3, false),
+            (6, false, 4, true),
+            (6, true, 5, true),
+        ] {
+            let mut bytes = fixture(format);
+            if patch_extended {
+                bytes[EXTENDED + 8..EXTENDED + 16].copy_from_slice(&5u64.to_le_bytes());
+            } else {
+                bytes[LEGACY + 4..LEGACY + 8].copy_from_slice(&3u32.to_le_bytes());
+            }
+            let reader = Reader::new(Cursor::new(bytes.clone())).unwrap();
+            let header = reader.header();
+            assert_eq!(Some(6), header.number_of_points_by_return(1));
+            assert_eq!(Some(second_returns), header.number_of_points_by_return(2));
+
+            let conflict = reader.summary().return_count_conflict.unwrap();
+            let raw_header = raw_header(&bytes);
+            assert_eq!(raw_header.number_of_points_by_return, conflict.legacy);
+            assert_eq!(
+                raw_header.large_file.unwrap().number_of_points_by_return,
+                conflict.extended
+            );
+            assert_eq!(used_extended, conflict.used_extended);
+            assert_eq!(Some(conflict), header.return_count_conflict());
+            assert!(reader.summary().is_clean());
+
+            let written = header.clone().into_raw().unwrap();
+            let extended = written.large_file.unwrap().number_of_points_by_return;
+            assert_eq!([6, second_returns], extended[..2]);
+            for (legacy, extended) in written.number_of_points_by_return.iter().zip(extended) {
+                assert_eq!(u64::from(*legacy), extended);
+            }
+            assert!(Header::from_raw(written)
+                .unwrap()
+                .return_count_conflict()
+                .is_none());
+
+            assert!(matches!(
+                Builder::new(raw_header).unwrap().into_header().unwrap_err(),
+                Error::ReturnCountConflict(c) if *c == conflict
+            ));
+            assert!(header.clone().into_builder().into_header().is_ok());
+        }
+    }
+
+    #[test]
+    fn zero_legacy_counts_dont_conflict() {
+        let mut bytes = fixture(6);
+        bytes[LEGACY..LEGACY + 20].fill(0);
+        let reader = Reader::new(Cursor::new(bytes)).unwrap();
+        assert!(reader.summary().return_count_conflict.is_none());
+        assert_eq!(Some(4), reader.header().number_of_points_by_return(2));
+    }
+}
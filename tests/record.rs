@@ -0,0 +1,118 @@
+//! Reading and writing points of your own type.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+use las::{
+    point::Format,
+    raw::{self, point::Flags},
+    reader::RawPointView,
+    Builder, Error, GpsTimeType, Header, LasPointRecord, Reader, Result, Writer,
+};
+use std::io::Cursor;
+
+#[derive(Clone, Debug, PartialEq)]
+struct MyPoint {
+    x: f64,
+    y: f64,
+    z: f64,
+    class: u8,
+    gps_time: Option<f64>,
+    extra: Vec<u8>,
+}
+
+impl LasPointRecord for MyPoint {
+    fn decode(view: RawPointView<'_>, header: &Header) -> Result<MyPoint> {
+        let transforms = header.effective_transforms();
+        Ok(MyPoint {
+            x: transforms.x.direct(view.x()),
+            y: transforms.y.direct(view.y()),
+            z: transforms.z.direct(view.z()),
+            class: view.classification(),
+            gps_time: view.gps_time(),
+            extra: view.extra_bytes().to_vec(),
+        })
+    }
+
+    fn encode(&self, header: &Header) -> Result<raw::Point> {
+        let transforms = header.transforms();
+        Ok(raw::Point {
+            x: transforms.x.inverse(self.x)?,
+            y: transforms.y.inverse(self.y)?,
+            z: transforms.z.inverse(self.z)?,
+            flags: Flags::ThreeByte(0b0001_0001, 0, self.class),
+            gps_time: self.gps_time,
+            extra_bytes: self.extra.as_slice().into(),
+            ..Default::default()
+        })
+    }
+}
+
+fn points() -> Vec<MyPoint> {
+    (0..10)
+        .map(|i| MyPoint {
+            x: f64::from(i),
+            y: f64::from(i) + 0.5,
+            z: -f64::from(i),
+            class: 2 + (i % 3) as u8,
+            gps_time: Some(f64::from(i) * 10.),
+            extra: vec![i as u8, 42],
+        })
+        .collect()
+}
+
+fn check(format: u8, compressed: bool) {
+    let mut builder = Builder::from((1, 4));
+    builder.point_format = Format::new(format).unwrap();
+    builder.point_format.is_compressed = compressed;
+    builder.point_format.extra_bytes = 2;
+    builder.gps_time_type = GpsTimeType::Standard;
+    let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    for point in points() {
+        writer.write_record(&point).unwrap();
+    }
+    let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(10, reader.header().number_of_points());
+    assert_eq!(
+        points(),
+        reader
+            .points_as::<MyPoint>()
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+    );
+
+    reader.seek(0).unwrap();
+    let point = reader.read_point().unwrap().unwrap();
+    assert_eq!(2, u8::from(point.classification));
+    assert_eq!(Some(0.), point.gps_time);
+}
+
+#[test]
+fn legacy() {
+    check(1, false);
+}
+
+#[test]
+fn extended() {
+    check(6, false);
+}
+
+#[test]
+#[cfg(feature = "laz")]
+fn compressed() {
+    check(6, true);
+}
+
+#[test]
+fn wrong_extra_bytes() {
+    let mut writer = Writer::default();
+    assert!(matches!(
+        writer.write_record(&points()[0]),
+        Err(Error::InvalidRecordLength {
+            expected: 20,
+            len: 22
+        })
+    ));
+    assert_eq!(0, writer.header().number_of_points());
+}
@@ -0,0 +1,147 @@
+//! Point data record lengths that match, exceed, or fall short of the point format.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+mod common;
+
+use las::{
+    header::RecordLengthStatus, point::Format, reader::ReadOptions, Builder, Error, Point, Reader,
+};
+use std::io::Cursor;
+
+/// Format 1 records are 28 bytes, and the gps time is the last eight.
+const FORMAT_LEN: usize = 28;
+
+fn points() -> Vec<Point> {
+    (0..5)
+        .map(|i| Point {
+            x: f64::from(i),
+            y: 2. * f64::from(i),
+            z: 3. * f64::from(i),
+            intensity: 100 + i as u16,
+            gps_time: Some(1000. + f64::from(i)),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Writes the points as format 1, then rewrites each record to `len` bytes, cutting the end off
+/// or padding it with `0xAB`.
+fn fixture(len: u16) -> Vec<u8> {
+    let mut builder = Builder::from((1, 2));
+    builder.point_format = Format::new(1).unwrap();
+    let bytes = common::write(builder, points());
+    let start = u32::from_le_bytes(bytes[96..100].try_into().unwrap()) as usize;
+    let mut out = bytes[..start].to_vec();
+    out[105..107].copy_from_slice(&len.to_le_bytes());
+    for record in bytes[start..].chunks(FORMAT_LEN) {
+        let len = usize::from(len);
+        if len <= FORMAT_LEN {
+            out.extend_from_slice(&record[..len]);
+        } else {
+            out.extend_from_slice(record);
+            out.resize(out.len() + len - FORMAT_LEN, 0xAB);
+        }
+    }
+    out
+}
+
+fn read(bytes: Vec<u8>, lenient: bool) -> las::Result<Reader<Cursor<Vec<u8>>>> {
+    let options = ReadOptions::default().read_short_records(lenient);
+    Reader::with_options(Cursor::new(bytes), options)
+}
+
+#[test]
+fn exact() {
+    for lenient in [false, true] {
+        let mut reader = read(fixture(28), lenient).unwrap();
+        assert_eq!(
+            RecordLengthStatus::Exact,
+            reader.header().record_length_consistency()
+        );
+        assert_eq!(28, reader.header().point_data_record_length());
+        assert!(reader.summary().is_clean());
+        assert_eq!(points(), reader.read_points(10).unwrap());
+    }
+}
+
+#[test]
+fn surplus() {
+    for lenient in [false, true] {
+        let mut reader = read(fixture(32), lenient).unwrap();
+        assert_eq!(
+            RecordLengthStatus::Surplus(4),
+            reader.header().record_length_consistency()
+        );
+        assert_eq!(32, reader.header().point_data_record_length());
+        assert!(reader.summary().is_clean());
+        for (expected, point) in points().into_iter().zip(reader.read_points(10).unwrap()) {
+            assert_eq!(point.extra_bytes, vec![0xAB; 4]);
+            assert_eq!(
+                Point {
+                    extra_bytes: Default::default(),
+                    ..point
+                },
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn deficit_refused_by_default() {
+    match read(fixture(20), false) {
+        Err(Error::ReadFailed {
+            offset: 105,
+            source,
+        }) => assert!(matches!(
+            *source,
+            Error::PointDataRecordLengthTooLarge {
+                len: 20,
+                minimum: 28,
+                ..
+            }
+        )),
+        Err(err) => panic!("unexpected error: {err}"),
+        Ok(_) => panic!("short records should be refused"),
+    }
+}
+
+#[test]
+fn deficit_read_leniently() {
+    let mut reader = read(fixture(20), true).unwrap();
+    let header = reader.header().clone();
+    assert_eq!(
+        RecordLengthStatus::Deficit(8),
+        header.record_length_consistency()
+    );
+    assert_eq!(20, header.point_data_record_length());
+    assert_eq!(28, header.point_format().len());
+    assert!(header
+        .to_string()
+        .contains("record length: 8 bytes short, read as zeros"));
+    let summary = reader.summary();
+    assert_eq!(8, summary.record_length_deficit);
+    assert!(!summary.is_clean());
+
+    let expected: Vec<Point> = points()
+        .into_iter()
+        .map(|point| Point {
+            gps_time: Some(0.),
+            ..point
+        })
+        .collect();
+    assert_eq!(expected, reader.read_points(10).unwrap());
+    assert_eq!(
+        Some(reader.point_offset(0).unwrap() + 20),
+        reader.point_offset(1)
+    );
+    reader.seek(3).unwrap();
+    assert_eq!(
+        expected[3],
+        reader.read_point().unwrap().unwrap(),
+        "seeking steps by the stored record length"
+    );
+}
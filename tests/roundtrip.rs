@@ -1,5 +1,7 @@
 //! Roundtrip (write-read) tests for supported LAS versions and attributes.
 
+#![cfg(feature = "std")]
+
 extern crate chrono;
 extern crate las;
 extern crate uuid;
@@ -43,6 +45,9 @@ macro_rules! roundtrip_point {
             $modify_point(&mut point);
             let mut builder = Builder::from(version);
             builder.point_format = point_format;
+            if point_format.is_extended {
+                builder.gps_time_type = las::GpsTimeType::Standard;
+            }
             crate::roundtrip(builder, &point, should_succeed);
         }
     };
@@ -152,7 +157,7 @@ macro_rules! version {
                 );
                 roundtrip_point!(
                     extra_bytes,
-                    |p: &mut Point| p.extra_bytes = vec![42],
+                    |p: &mut Point| p.extra_bytes = vec![42].into(),
                     0,
                     |f: &mut Format| f.extra_bytes = 1
                 );
@@ -227,3 +232,85 @@ version!(las_1_1, 1, 1);
 version!(las_1_2, 1, 2);
 version!(las_1_3, 1, 3);
 version!(las_1_4, 1, 4);
+
+mod extra_bytes {
+    use las::{point::Format, Builder, Error, Point, Reader, Writer};
+    use std::io::Cursor;
+
+    fn builder(n: u16, compressed: bool) -> Builder {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(3).unwrap();
+        builder.point_format.is_compressed = compressed;
+        builder.extra_bytes(n);
+        builder
+    }
+
+    fn roundtrip(n: u16, compressed: bool) {
+        let points: Vec<Point> = (0..10u8)
+            .map(|i| Point {
+                x: f64::from(i),
+                gps_time: Some(f64::from(i)),
+                color: Some(Default::default()),
+                extra_bytes: (0..n)
+                    .map(|j| i.wrapping_add(j as u8))
+                    .collect::<Vec<_>>()
+                    .into(),
+                ..Default::default()
+            })
+            .collect();
+        let header = builder(n, compressed).into_header().unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        for point in &points {
+            writer.write_point_ref(point).unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let header = reader.header().clone();
+        assert_eq!(
+            Format::new(3).unwrap().len() + n,
+            header.point_format().len()
+        );
+        assert!(header.find_vlr("LASF_Spec", 4).is_none());
+        let read = reader.points().collect::<Result<Vec<_>, _>>().unwrap();
+        assert!(read
+            .iter()
+            .all(|point| point.extra_bytes.len() == usize::from(n)));
+        assert_eq!(points, read);
+    }
+
+    fn wrong_length(compressed: bool) {
+        let header = builder(2, compressed).into_header().unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        let point = Point {
+            gps_time: Some(0.),
+            color: Some(Default::default()),
+            extra_bytes: vec![1, 2].into(),
+            ..Default::default()
+        };
+        writer.write_point_ref(&point).unwrap();
+        let short = Point {
+            extra_bytes: vec![1].into(),
+            ..point
+        };
+        match writer.write_point(short).unwrap_err() {
+            Error::PointWriteFailed { index, .. } => assert_eq!(1, index),
+            err => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn las() {
+        for n in [0, 1, 61] {
+            roundtrip(n, false);
+        }
+        wrong_length(false);
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn laz() {
+        for n in [0, 1, 61] {
+            roundtrip(n, true);
+        }
+        wrong_length(true);
+    }
+}
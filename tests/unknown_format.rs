@@ -0,0 +1,134 @@
+//! Point formats that this crate doesn't know, opened for raw access.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+mod common;
+
+use las::{
+    point::{Classification, Format},
+    reader::ReadOptions,
+    Builder, Error, Point, Reader, Writer,
+};
+use std::{fs::File, io::Cursor};
+
+/// Format 1 records are 28 bytes.
+const RECORD_LEN: usize = 28;
+
+/// Writes ten format 1 points, then patches the point format number to `n`.
+fn fixture(n: u8) -> Vec<u8> {
+    let mut builder = Builder::from((1, 4));
+    builder.point_format = Format::new(1).unwrap();
+    let points = (0..10).map(|i| Point {
+        x: f64::from(i),
+        return_number: 1,
+        number_of_returns: 1,
+        gps_time: Some(f64::from(i)),
+        ..Default::default()
+    });
+    let mut bytes = common::write(builder, points);
+    bytes[104] = n;
+    bytes
+}
+
+fn records(bytes: &[u8]) -> Vec<&[u8]> {
+    let start = u32::from_le_bytes(bytes[96..100].try_into().unwrap()) as usize;
+    bytes[start..start + 10 * RECORD_LEN]
+        .chunks(RECORD_LEN)
+        .collect()
+}
+
+fn open(bytes: Vec<u8>) -> las::Result<Reader<Cursor<Vec<u8>>>> {
+    let options = ReadOptions::default().read_unknown_formats(true);
+    Reader::with_options(Cursor::new(bytes), options)
+}
+
+fn source(err: Error) -> Error {
+    match err {
+        Error::ReadFailed { source, .. } | Error::PointReadFailed { source, .. } => *source,
+        err => err,
+    }
+}
+
+#[test]
+fn refused_by_default() {
+    let err = Reader::new(Cursor::new(fixture(11)))
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(source(err), Error::InvalidPointFormatNumber(11)));
+}
+
+#[test]
+fn header() {
+    let reader = open(fixture(11)).unwrap();
+    let header = reader.header();
+    assert_eq!(Format::unknown(11, 28), *header.point_format());
+    assert_eq!(10, header.number_of_points());
+    assert_eq!(9., header.bounds().max.x);
+}
+
+#[test]
+fn raw_records() {
+    let bytes = fixture(11);
+    let mut reader = open(bytes.clone()).unwrap();
+    let read: Vec<Vec<u8>> = reader.raw_records().map(Result::unwrap).collect();
+    assert_eq!(records(&bytes), read);
+}
+
+#[test]
+fn points_are_refused() {
+    let mut reader = open(fixture(11)).unwrap();
+    let err = reader.read_point().unwrap_err();
+    assert!(matches!(err, Error::PointReadFailed { index: 0, .. }));
+    assert!(matches!(source(err), Error::UnknownPointFormat(11)));
+    assert!(reader.points().next().unwrap().is_err());
+    assert!(reader.read_points(5).is_err());
+    assert!(reader
+        .points_with_classification(Classification::Ground)
+        .next()
+        .unwrap()
+        .is_err());
+    assert!(reader.points_with_source_id(0).next().unwrap().is_err());
+}
+
+#[test]
+fn cannot_write() {
+    let header = open(fixture(11)).unwrap().header().clone();
+    let err = Writer::new(Cursor::new(Vec::new()), header)
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(err, Error::UnknownPointFormat(11)));
+}
+
+#[test]
+fn extract() {
+    let bytes = fixture(42);
+    let path = std::env::temp_dir().join("las-rs-unknown-format-extract.las");
+    let header = las::extract_range(
+        &mut open(bytes.clone()).unwrap(),
+        2..5,
+        File::create(&path).unwrap(),
+    )
+    .unwrap();
+    let extracted = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(3, header.number_of_points());
+    let mut reader = open(extracted).unwrap();
+    assert_eq!(Format::unknown(42, 28), *reader.header().point_format());
+    assert_eq!(3, reader.header().number_of_points());
+    let read: Vec<Vec<u8>> = reader.raw_records().map(Result::unwrap).collect();
+    assert_eq!(records(&bytes)[2..5], read);
+}
+
+#[test]
+fn implausible() {
+    let mut short = fixture(11);
+    short[105..107].copy_from_slice(&10u16.to_le_bytes());
+    let mut truncated = fixture(11);
+    truncated.truncate(truncated.len() - 1);
+    for bytes in [short, truncated] {
+        let err = open(bytes).map(|_| ()).unwrap_err();
+        assert!(matches!(source(err), Error::InvalidPointFormatNumber(11)));
+    }
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "std")]
+
+use las::{
+    point::Format,
+    waveform::{Samples, WaveformPacketDescriptor, WaveformPackets},
+    Builder, Point, Reader, Writer,
+};
+use std::io::Cursor;
+
+fn descriptor() -> WaveformPacketDescriptor {
+    WaveformPacketDescriptor {
+        bits_per_sample: 16,
+        compression_type: 0,
+        number_of_samples: 3,
+        temporal_sample_spacing: 1000,
+        digitizer_gain: 1.,
+        digitizer_offset: 0.,
+    }
+}
+
+fn points(packets: &mut WaveformPackets) -> Vec<Point> {
+    (0..3)
+        .map(|i| Point {
+            gps_time: Some(f64::from(i)),
+            waveform: Some(
+                packets
+                    .push(1, &descriptor(), &Samples::U16(vec![i, i + 1, i + 2]))
+                    .unwrap(),
+            ),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn builder() -> Builder {
+    let mut builder = Builder::from((1, 4));
+    builder.point_format = Format::new(4).unwrap();
+    builder
+        .waveform_packet_descriptor(1, &descriptor())
+        .unwrap();
+    builder
+}
+
+fn verify<R: std::io::Read + std::io::Seek + Send + 'static>(mut reader: Reader<R>) {
+    assert_eq!(
+        descriptor(),
+        reader
+            .header()
+            .waveform_packet_descriptor(1)
+            .unwrap()
+            .unwrap()
+    );
+    let mut points = Vec::new();
+    let _ = reader.read_all_points_into(&mut points).unwrap();
+    for (i, point) in points.iter().enumerate() {
+        let i = i as u16;
+        assert_eq!(
+            Samples::U16(vec![i, i + 1, i + 2]),
+            reader.waveform(point).unwrap()
+        );
+    }
+}
+
+#[test]
+fn internal() {
+    let mut packets = WaveformPackets::new();
+    let points = points(&mut packets);
+    let mut builder = builder();
+    builder.has_internal_waveform_data_packets = true;
+    builder.evlrs.push(packets.into_evlr());
+    let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    for point in points {
+        writer.write_point(point).unwrap();
+    }
+    let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    assert!(reader
+        .header()
+        .start_of_waveform_data_packet_record()
+        .is_some());
+    verify(reader);
+}
+
+#[test]
+fn external() {
+    let dir = std::env::temp_dir().join("las-rs-waveform-external");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("points.las");
+    let mut packets = WaveformPackets::new();
+    let points = points(&mut packets);
+    packets
+        .write_wdp(std::fs::File::create(path.with_extension("wdp")).unwrap())
+        .unwrap();
+    let mut builder = builder();
+    builder.has_external_waveform_data_packets = true;
+    let mut writer = Writer::from_path(&path, builder.into_header().unwrap()).unwrap();
+    for point in points {
+        writer.write_point(point).unwrap();
+    }
+    writer.close().unwrap();
+    verify(Reader::from_path(&path).unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,146 @@
+//! Reading files with a mix of withheld and normal points, with and without skipping them.
+
+#![cfg(feature = "std")]
+
+extern crate las;
+
+mod common;
+
+use las::{
+    point::{Classification, Format},
+    reader::ReadOptions,
+    Builder, GpsTimeType, Point, Reader,
+};
+use std::io::Cursor;
+
+/// Every third point is withheld, starting with the first.
+fn points() -> Vec<Point> {
+    (0..10)
+        .map(|i| Point {
+            x: f64::from(i),
+            y: f64::from(i),
+            z: f64::from(i),
+            is_withheld: i % 3 == 0,
+            classification: Classification::Ground,
+            point_source_id: 7,
+            gps_time: Some(f64::from(i)),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn kept() -> Vec<(u64, Point)> {
+    (0..)
+        .zip(points())
+        .filter(|(_, point)| !point.is_withheld)
+        .collect()
+}
+
+fn fixture(format: u8, compressed: bool) -> Vec<u8> {
+    let mut builder = Builder::from((1, 4));
+    builder.point_format = Format::new(format).unwrap();
+    builder.point_format.is_compressed = compressed;
+    builder.gps_time_type = GpsTimeType::Standard;
+    let mut writer = common::writer(builder, points());
+    writer.close().unwrap();
+    assert_eq!(4, writer.summary().withheld_points);
+    writer.into_inner().unwrap().into_inner()
+}
+
+fn reader(bytes: &[u8], skip_withheld: bool) -> Reader<Cursor<Vec<u8>>> {
+    let options = ReadOptions::default().skip_withheld(skip_withheld);
+    Reader::with_options(Cursor::new(bytes.to_vec()), options).unwrap()
+}
+
+fn check(format: u8, compressed: bool) {
+    let bytes = fixture(format, compressed);
+
+    let mut inclusive = reader(&bytes, false);
+    assert_eq!(points(), inclusive.read_points(20).unwrap());
+    assert_eq!(0, inclusive.summary().skipped_withheld);
+    assert!(inclusive.summary().is_clean());
+
+    let mut skipping = reader(&bytes, true);
+    assert_eq!(10, skipping.header().number_of_points());
+    let expected: Vec<Point> = kept().into_iter().map(|(_, point)| point).collect();
+    assert_eq!(expected[..3], skipping.read_points(3).unwrap()[..]);
+    assert_eq!(expected[3..], skipping.read_points(20).unwrap()[..]);
+    assert_eq!(4, skipping.summary().skipped_withheld);
+    assert!(!skipping.summary().is_clean());
+
+    let mut skipping = reader(&bytes, true);
+    assert_eq!(
+        expected,
+        skipping.points().collect::<las::Result<Vec<_>>>().unwrap()
+    );
+
+    let mut skipping = reader(&bytes, true);
+    assert_eq!(
+        kept(),
+        skipping
+            .indexed_points()
+            .collect::<las::Result<Vec<_>>>()
+            .unwrap()
+    );
+
+    let mut skipping = reader(&bytes, true);
+    let mut point = Point::default();
+    let mut read = Vec::new();
+    while skipping.read_point_into(&mut point).unwrap() {
+        read.push(point.clone());
+    }
+    assert_eq!(expected, read);
+
+    let mut skipping = reader(&bytes, true);
+    assert_eq!(
+        expected,
+        skipping
+            .points_with_classification([Classification::Ground])
+            .collect::<las::Result<Vec<_>>>()
+            .unwrap()
+    );
+    let mut skipping = reader(&bytes, true);
+    assert_eq!(
+        expected,
+        skipping
+            .points_with_source_id(7)
+            .collect::<las::Result<Vec<_>>>()
+            .unwrap()
+    );
+
+    assert_eq!(expected, reader(&bytes, true).into_points().unwrap());
+    assert_eq!(points(), reader(&bytes, false).into_points().unwrap());
+}
+
+#[test]
+fn legacy_format() {
+    check(1, false);
+}
+
+#[test]
+fn extended_format() {
+    check(6, false);
+}
+
+#[test]
+#[cfg(feature = "laz")]
+fn compressed() {
+    check(1, true);
+    check(6, true);
+}
+
+#[test]
+fn seek_counts_withheld_points() {
+    let bytes = fixture(1, false);
+    let mut reader = reader(&bytes, true);
+    reader.seek(3).unwrap();
+    assert_eq!(4., reader.read_point().unwrap().unwrap().x);
+    assert_eq!(1, reader.summary().skipped_withheld);
+}
+
+#[test]
+fn bounds_include_withheld_points() {
+    let bytes = fixture(1, false);
+    let reader = reader(&bytes, true);
+    assert_eq!(0., reader.header().bounds().min.x);
+}